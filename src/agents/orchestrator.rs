@@ -562,6 +562,7 @@ impl SubagentRunner {
                     success = false;
                 }
                 StreamEvent::MessageComplete { .. } | StreamEvent::MessageStop => break,
+                StreamEvent::Usage { .. } | StreamEvent::ThinkingDelta(_) => {}
                 // Tool calls would need a tool execution loop in a full implementation
                 StreamEvent::ToolUseStart { .. }
                 | StreamEvent::ToolUseInputDelta { .. }
@@ -1104,4 +1105,121 @@ mod tests {
         assert!(ctx_msg.contains("CLAUDE.md"));
         assert!(ctx_msg.contains("README.md"));
     }
+
+    // ============================================================================
+    // Shared request concurrency limit
+    // ============================================================================
+
+    /// Starts a minimal HTTP server that answers every request with a short SSE
+    /// stream after `delay`, returning its URL and the peak number of requests
+    /// it was handling at once.
+    async fn start_counting_server(
+        delay: std::time::Duration,
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let peak_out = Arc::clone(&peak);
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let active = Arc::clone(&active);
+                let peak = Arc::clone(&peak);
+                tokio::spawn(async move {
+                    // Read headers and body so the client finishes sending
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let n = socket.read(&mut chunk).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        buf.extend_from_slice(&chunk[..n]);
+                        let text = String::from_utf8_lossy(&buf);
+                        if let Some(header_end) = text.find("\r\n\r\n") {
+                            let content_length = text[..header_end]
+                                .lines()
+                                .find_map(|l| {
+                                    l.to_ascii_lowercase()
+                                        .strip_prefix("content-length:")
+                                        .and_then(|v| v.trim().parse::<usize>().ok())
+                                })
+                                .unwrap_or(0);
+                            if buf.len() >= header_end + 4 + content_length {
+                                break;
+                            }
+                        }
+                    }
+
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+
+                    let body = "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"done\"}}\n\nevent: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        (url, peak_out)
+    }
+
+    #[tokio::test]
+    async fn test_subagents_share_concurrent_request_limit() {
+        use secrecy::SecretString;
+        use std::sync::atomic::Ordering;
+        use std::time::Duration;
+
+        let (url, peak) = start_counting_server(Duration::from_millis(100)).await;
+
+        // Given a parent client limited to 2 in-flight requests
+        let client = crate::api::AnthropicClient::new_with_base_url(
+            SecretString::from("test-key"),
+            "claude-sonnet-4-20250514",
+            &url,
+        )
+        .with_max_concurrent_requests(2);
+
+        // When six subagents run at once, each with a clone of the client
+        let spawner = SubagentSpawner::new();
+        let mut handles = Vec::new();
+        for i in 0..6 {
+            let runner = SubagentRunner::new(client.clone());
+            let session = spawner
+                .spawn(
+                    format!("agent-{i}"),
+                    "Does work",
+                    SubagentContext::default(),
+                    vec![],
+                )
+                .await
+                .unwrap();
+            handles.push(tokio::spawn(async move {
+                runner.execute(&session, "work").await.unwrap()
+            }));
+        }
+
+        // Then every subagent completes, queued rather than failed
+        for handle in handles {
+            let result = handle.await.unwrap();
+            assert!(result.success, "errors: {:?}", result.errors);
+            assert_eq!(result.output, "done");
+        }
+
+        // And the server never saw more than 2 requests at once
+        let peak = peak.load(Ordering::SeqCst);
+        assert!((1..=2).contains(&peak), "peak in-flight requests: {peak}");
+    }
 }
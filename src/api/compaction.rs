@@ -168,6 +168,33 @@ pub enum SummaryStyle {
     Narrative,
 }
 
+impl SummaryStyle {
+    /// Returns the style name used by `/compact` and `--summary-style`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Timeline => "detailed",
+            Self::BulletPoints => "bullet",
+            Self::Narrative => "paragraph",
+        }
+    }
+}
+
+impl std::str::FromStr for SummaryStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "detailed" => Ok(Self::Timeline),
+            "bullet" => Ok(Self::BulletPoints),
+            "paragraph" => Ok(Self::Narrative),
+            other => anyhow::bail!(
+                "unknown summary style '{other}' (expected bullet, paragraph or detailed)"
+            ),
+        }
+    }
+}
+
 /// Result of a compaction operation.
 #[derive(Debug, Clone)]
 pub struct CompactionResult {
@@ -476,6 +503,22 @@ mod tests {
         assert_eq!(config.summary_style, SummaryStyle::Timeline);
     }
 
+    #[test]
+    fn test_summary_style_names_round_trip() {
+        for style in [
+            SummaryStyle::Timeline,
+            SummaryStyle::BulletPoints,
+            SummaryStyle::Narrative,
+        ] {
+            assert_eq!(style.as_str().parse::<SummaryStyle>().unwrap(), style);
+        }
+        assert_eq!(
+            "bullet".parse::<SummaryStyle>().unwrap(),
+            SummaryStyle::BulletPoints
+        );
+        assert!("terse".parse::<SummaryStyle>().is_err());
+    }
+
     #[test]
     fn test_context_compactor_new_mock() {
         let compactor = ContextCompactor::new_mock();
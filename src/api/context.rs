@@ -38,9 +38,9 @@
 //! assert!(truncated.len() <= messages.len());
 //! ```
 
-use crate::api::compaction::{CompactionConfig, ContextCompactor};
+use crate::api::compaction::{CompactionConfig, ContextCompactor, SummaryStyle};
 use crate::api::tokens::estimate_message_tokens;
-use crate::types::ApiMessageV2;
+use crate::types::{ApiMessageV2, MessageContent};
 
 /// Default maximum input tokens per request.
 ///
@@ -50,14 +50,82 @@ use crate::types::ApiMessageV2;
 /// - Safety margin for estimation errors
 ///
 /// At $3/million input tokens (Claude Sonnet), this limits per-request cost to ~$0.30.
+/// Also the window assumed for models unknown to [`model_context_limit`].
 pub const DEFAULT_MAX_INPUT_TOKENS: usize = 100_000;
 
+/// Context windows, in tokens, by model ID prefix.
+///
+/// Matched in order, so more specific prefixes must come first.
+const MODEL_CONTEXT_LIMITS: &[(&str, usize)] = &[
+    ("claude-opus-4", 200_000),
+    ("claude-sonnet-4", 200_000),
+    ("claude-haiku-4", 200_000),
+    ("claude-3-7-sonnet", 200_000),
+    ("claude-3-5-sonnet", 200_000),
+    ("claude-3-5-haiku", 200_000),
+    ("claude-3-opus", 200_000),
+    ("claude-3-sonnet", 200_000),
+    ("claude-3-haiku", 200_000),
+    ("claude-2.1", 200_000),
+    ("claude-2.0", 100_000),
+    ("claude-instant-1", 100_000),
+];
+
+/// Returns the context window, in tokens, for a model ID.
+///
+/// Unknown models get [`DEFAULT_MAX_INPUT_TOKENS`].
+///
+/// # Example
+///
+/// ```rust
+/// use patina::api::context::{model_context_limit, DEFAULT_MAX_INPUT_TOKENS};
+///
+/// assert_eq!(model_context_limit("claude-sonnet-4-20250514"), 200_000);
+/// assert_eq!(model_context_limit("my-proxy-model"), DEFAULT_MAX_INPUT_TOKENS);
+/// ```
+#[must_use]
+pub fn model_context_limit(model: &str) -> usize {
+    MODEL_CONTEXT_LIMITS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(DEFAULT_MAX_INPUT_TOKENS, |&(_, limit)| limit)
+}
+
 /// Default maximum number of messages to include.
 ///
 /// Provides a hard cap independent of token counting to ensure
 /// reasonable context even if token estimation is off.
 pub const DEFAULT_MAX_MESSAGES: usize = 30;
 
+/// Default fraction of the context window at which history is compacted.
+pub const DEFAULT_AUTO_COMPACT_THRESHOLD: f64 = 0.8;
+
+/// Number of recent messages kept verbatim by automatic compaction.
+pub const AUTO_COMPACT_PRESERVE_RECENT: usize = 4;
+
+/// Returns how many recent messages to keep verbatim when compacting.
+///
+/// Starts from `preserve_recent` and grows until the kept messages no longer
+/// begin with a tool result, so a result is never separated from the
+/// `tool_use` it answers. The first message is never counted.
+#[must_use]
+pub fn preserve_count_for(messages: &[ApiMessageV2], preserve_recent: usize) -> usize {
+    let available = messages.len().saturating_sub(1);
+    let mut count = preserve_recent.min(available);
+    while count > 0 && count < available && has_tool_result(&messages[messages.len() - count]) {
+        count += 1;
+    }
+    count
+}
+
+/// Returns true if a message carries tool results.
+fn has_tool_result(message: &ApiMessageV2) -> bool {
+    match &message.content {
+        MessageContent::Text(_) => false,
+        MessageContent::Blocks(blocks) => blocks.iter().any(|block| block.is_tool_result()),
+    }
+}
+
 /// Truncates messages to fit within a token budget.
 ///
 /// Uses the default message limit (`DEFAULT_MAX_MESSAGES`).
@@ -136,13 +204,30 @@ pub fn compact_or_truncate_context(
     messages: &[ApiMessageV2],
     max_tokens: usize,
     preserve_recent: usize,
+) -> Vec<ApiMessageV2> {
+    compact_or_truncate_context_with_style(
+        messages,
+        max_tokens,
+        preserve_recent,
+        SummaryStyle::default(),
+    )
+}
+
+/// Compacts or truncates messages like [`compact_or_truncate_context`],
+/// summarizing in the given style.
+#[must_use]
+pub fn compact_or_truncate_context_with_style(
+    messages: &[ApiMessageV2],
+    max_tokens: usize,
+    preserve_recent: usize,
+    summary_style: SummaryStyle,
 ) -> Vec<ApiMessageV2> {
     // Try compaction first
     let compactor = ContextCompactor::new_mock();
     let config = CompactionConfig {
         target_tokens: max_tokens,
         preserve_recent,
-        ..Default::default()
+        summary_style,
     };
 
     match compactor.compact(messages, &config) {
@@ -242,7 +327,7 @@ pub fn truncate_context_with_limits(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{ContentBlock, MessageContent};
+    use crate::types::ContentBlock;
     use serde_json::json;
 
     fn make_message(role: &str, content: &str) -> ApiMessageV2 {
@@ -253,6 +338,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_model_context_limit_known_and_unknown_models() {
+        for model in [
+            "claude-opus-4-1-20250805",
+            "claude-sonnet-4-20250514",
+            "claude-sonnet-4-5",
+            "claude-haiku-4-5",
+            "claude-3-7-sonnet-20250219",
+            "claude-3-5-sonnet-20241022",
+            "claude-3-5-haiku-20241022",
+            "claude-3-opus-20240229",
+            "claude-3-haiku-20240307",
+            "claude-2.1",
+        ] {
+            assert_eq!(model_context_limit(model), 200_000, "{model}");
+        }
+        assert_eq!(model_context_limit("claude-2.0"), 100_000);
+        assert_eq!(model_context_limit("claude-instant-1.2"), 100_000);
+
+        assert_eq!(model_context_limit("gpt-4o"), DEFAULT_MAX_INPUT_TOKENS);
+        assert_eq!(model_context_limit(""), DEFAULT_MAX_INPUT_TOKENS);
+    }
+
     // =========================================================================
     // truncate_context tests
     // =========================================================================
@@ -551,4 +659,58 @@ mod tests {
         let last_result = result.last().unwrap().content.to_text();
         assert_eq!(last_result, last_original);
     }
+
+    #[test]
+    fn test_compact_with_style_uses_style() {
+        let long_padding = "x".repeat(200);
+        let mut messages = vec![make_message("user", &format!("System {}", long_padding))];
+        for i in 0..10 {
+            messages.push(make_message("user", &format!("Q{} {}", i, long_padding)));
+            messages.push(make_message(
+                "assistant",
+                &format!("A{} {}", i, long_padding),
+            ));
+        }
+
+        let result =
+            compact_or_truncate_context_with_style(&messages, 200, 2, SummaryStyle::Narrative);
+
+        assert!(result.iter().any(|m| m
+            .content
+            .to_text()
+            .contains("Summary of earlier conversation:")));
+    }
+
+    #[test]
+    fn test_preserve_count_keeps_tool_pairs_together() {
+        let tool_use = |id: &str| {
+            ApiMessageV2::assistant_with_content(MessageContent::blocks(vec![
+                ContentBlock::tool_use(id, "bash", json!({"command": "ls"})),
+            ]))
+        };
+        let tool_result = |id: &str| {
+            ApiMessageV2::user_with_content(MessageContent::blocks(vec![
+                ContentBlock::tool_result(id, "output"),
+            ]))
+        };
+        let messages = vec![
+            make_message("user", "System"),
+            make_message("user", "List files"),
+            tool_use("tool_1"),
+            tool_result("tool_1"),
+            tool_use("tool_2"),
+            tool_result("tool_2"),
+            make_message("assistant", "Done"),
+        ];
+
+        // The last message is a plain reply
+        assert_eq!(preserve_count_for(&messages, 1), 1);
+        // Keeping the last two would orphan tool_2's result
+        assert_eq!(preserve_count_for(&messages, 2), 3);
+        // Keeping the last four would orphan tool_1's result
+        assert_eq!(preserve_count_for(&messages, 4), 5);
+        // Never counts the first message
+        assert_eq!(preserve_count_for(&messages, 10), 6);
+        assert_eq!(preserve_count_for(&[], 4), 0);
+    }
 }
@@ -0,0 +1,151 @@
+//! Request/response interceptors for [`AnthropicClient`](super::AnthropicClient).
+//!
+//! Interceptors let embedders hook every API call without forking the client:
+//! metrics, header injection, request logging, or asserting request shapes in
+//! tests. Each registered interceptor sees the outgoing request before it is
+//! sent, the HTTP status of every response (including retried ones), and each
+//! stream event before it is delivered. Interceptors run in registration order.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use patina::api::interceptor::{Interceptor, OutgoingRequest};
+//! use patina::api::AnthropicClient;
+//! use secrecy::SecretString;
+//!
+//! struct Tagger;
+//!
+//! impl Interceptor for Tagger {
+//!     fn on_request(&self, request: &mut OutgoingRequest) {
+//!         request
+//!             .headers
+//!             .insert("x-request-source", "docs".parse().unwrap());
+//!     }
+//! }
+//!
+//! let client = AnthropicClient::new(SecretString::from("key"), "claude-sonnet-4-20250514")
+//!     .with_interceptor(Tagger);
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use super::StreamEvent;
+
+/// An API request about to be sent.
+#[derive(Debug, Clone)]
+pub struct OutgoingRequest {
+    /// Endpoint the request is sent to.
+    pub url: String,
+    /// Extra headers added on top of authentication and version headers.
+    pub headers: HeaderMap,
+    /// JSON request body.
+    pub body: serde_json::Value,
+}
+
+/// Hook invoked around every API call made by the client.
+///
+/// All methods have no-op defaults, so implementors only override what they
+/// need. Interceptors are shared across client clones and must be thread-safe.
+pub trait Interceptor: Send + Sync {
+    /// Called once per request before it is sent; may modify it.
+    ///
+    /// Retries reuse the request as modified here.
+    fn on_request(&self, _request: &mut OutgoingRequest) {}
+
+    /// Called with the status of each HTTP response, including retried ones.
+    fn on_response(&self, _status: StatusCode) {}
+
+    /// Called for each stream event before it is delivered to the caller.
+    fn on_event(&self, _event: &StreamEvent) {}
+}
+
+/// Interceptor that totals token usage and requests across the session.
+///
+/// Clones share the same counters, so one copy can be registered on the
+/// client while another is kept for reporting.
+#[derive(Debug, Clone, Default)]
+pub struct UsageCounter {
+    requests: Arc<AtomicU64>,
+    input_tokens: Arc<AtomicU64>,
+    output_tokens: Arc<AtomicU64>,
+}
+
+impl UsageCounter {
+    /// Creates a counter with all totals at zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of requests sent.
+    #[must_use]
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total input tokens reported by the API.
+    #[must_use]
+    pub fn input_tokens(&self) -> u64 {
+        self.input_tokens.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total output tokens reported by the API.
+    #[must_use]
+    pub fn output_tokens(&self) -> u64 {
+        self.output_tokens.load(Ordering::Relaxed)
+    }
+}
+
+impl Interceptor for UsageCounter {
+    fn on_request(&self, _request: &mut OutgoingRequest) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_event(&self, event: &StreamEvent) {
+        if let StreamEvent::Usage {
+            input_tokens,
+            output_tokens,
+        } = event
+        {
+            self.input_tokens
+                .fetch_add(u64::from(*input_tokens), Ordering::Relaxed);
+            self.output_tokens
+                .fetch_add(u64::from(*output_tokens), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_counter_totals_usage_events() {
+        let counter = UsageCounter::new();
+        let shared = counter.clone();
+
+        let mut request = OutgoingRequest {
+            url: "http://localhost/v1/messages".to_string(),
+            headers: HeaderMap::new(),
+            body: serde_json::json!({}),
+        };
+        counter.on_request(&mut request);
+        counter.on_event(&StreamEvent::Usage {
+            input_tokens: 100,
+            output_tokens: 20,
+        });
+        counter.on_event(&StreamEvent::ContentDelta("ignored".to_string()));
+        counter.on_event(&StreamEvent::Usage {
+            input_tokens: 150,
+            output_tokens: 30,
+        });
+
+        assert_eq!(shared.requests(), 1);
+        assert_eq!(shared.input_tokens(), 250);
+        assert_eq!(shared.output_tokens(), 50);
+    }
+}
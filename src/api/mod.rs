@@ -2,32 +2,40 @@
 
 pub mod compaction;
 pub mod context;
+pub mod interceptor;
 pub mod multi_model;
+pub mod retry;
 pub mod tokens;
 pub mod tools;
 
 // Re-export token utilities for convenience
 pub use tokens::{
     estimate_image_tokens, estimate_message_tokens, estimate_messages_tokens, estimate_tokens,
-    TokenBudget, TokenEstimator, DEFAULT_IMAGE_TOKENS,
+    BudgetStatus, TokenBudget, TokenEstimator, CONTEXT_WARNING_PERCENT, DEFAULT_IMAGE_TOKENS,
 };
 
 // Re-export context utilities for convenience
 pub use context::{
-    compact_or_truncate_context, truncate_context, DEFAULT_MAX_INPUT_TOKENS, DEFAULT_MAX_MESSAGES,
+    compact_or_truncate_context, compact_or_truncate_context_with_style, model_context_limit,
+    preserve_count_for, truncate_context, AUTO_COMPACT_PRESERVE_RECENT,
+    DEFAULT_AUTO_COMPACT_THRESHOLD, DEFAULT_MAX_INPUT_TOKENS, DEFAULT_MAX_MESSAGES,
 };
 
 // Re-export compaction types for convenience
 pub use compaction::{CompactionConfig, CompactionResult, ContextCompactor, SummaryStyle};
 
+pub use interceptor::{Interceptor, OutgoingRequest, UsageCounter};
+pub use retry::RetryPolicy;
+
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Semaphore, SemaphorePermit};
 
-use crate::types::{Message, Role};
+use crate::types::{Message, RequestParams, Role};
 
 // Re-export tool types for convenience
 pub use tools::{ToolChoice, ToolDefinition};
@@ -38,11 +46,28 @@ pub use crate::types::StreamEvent;
 /// Default Anthropic API endpoint.
 const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
 
-/// Maximum number of retry attempts for retryable errors.
-const MAX_RETRIES: u32 = 2;
+/// Default maximum number of tokens to generate per response.
+pub const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+/// Largest `max_tokens` accepted by [`AnthropicClient::with_max_tokens`].
+pub const MAX_TOKENS_LIMIT: u32 = 128_000;
+
+/// `anthropic-beta` flag that enables the 1M-token context window.
+pub const LARGE_CONTEXT_BETA: &str = "context-1m-2025-08-07";
+
+/// Context window, in tokens, available with [`LARGE_CONTEXT_BETA`].
+pub const LARGE_CONTEXT_WINDOW: usize = 1_000_000;
 
-/// Base delay for exponential backoff in milliseconds.
-const BASE_BACKOFF_MS: u64 = 100;
+/// Model prefixes that accept [`LARGE_CONTEXT_BETA`].
+const LARGE_CONTEXT_MODELS: &[&str] = &["claude-sonnet-4"];
+
+/// Returns true if `model` supports the 1M-token context beta.
+#[must_use]
+pub fn supports_large_context(model: &str) -> bool {
+    LARGE_CONTEXT_MODELS
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+}
 
 #[derive(Clone)]
 pub struct AnthropicClient {
@@ -50,12 +75,29 @@ pub struct AnthropicClient {
     api_key: SecretString,
     model: String,
     base_url: String,
+    params: RequestParams,
+    retry_policy: RetryPolicy,
+    system: Option<String>,
+    stop_sequences: Option<Vec<String>>,
+    /// Shared by all clones so subagents draw from the parent's budget.
+    request_limiter: Option<Arc<Semaphore>>,
+    large_context: bool,
+    /// Run in registration order around every request.
+    interceptors: Vec<Arc<dyn Interceptor>>,
 }
 
 #[derive(Serialize)]
 struct ApiRequest<'a> {
     model: &'a str,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<&'a [String]>,
     stream: bool,
     messages: Vec<ApiMessage<'a>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -75,6 +117,14 @@ struct ApiMessage<'a> {
 struct ApiRequestV2<'a> {
     model: &'a str,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<&'a [String]>,
     stream: bool,
     messages: &'a [crate::types::ApiMessageV2],
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -98,6 +148,23 @@ struct StreamLine {
     content_block: Option<ContentBlockStart>,
     /// For content_block_stop events - the block index.
     index: Option<usize>,
+    /// For message_start events - the message envelope (carries input usage).
+    message: Option<MessageStartPayload>,
+    /// For message_delta events - cumulative token usage.
+    usage: Option<UsagePayload>,
+}
+
+/// Message envelope from message_start events.
+#[derive(Deserialize, Debug)]
+struct MessageStartPayload {
+    usage: Option<UsagePayload>,
+}
+
+/// Token usage reported by message_start and message_delta events.
+#[derive(Deserialize, Debug, Default)]
+struct UsagePayload {
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
 }
 
 /// Payload for delta events (content or message).
@@ -107,6 +174,8 @@ struct DeltaPayload {
     text: Option<String>,
     /// For tool_use input JSON deltas.
     partial_json: Option<String>,
+    /// For thinking content deltas.
+    thinking: Option<String>,
     /// For message_delta - the stop reason.
     stop_reason: Option<String>,
     /// Delta type indicator.
@@ -153,9 +222,258 @@ impl AnthropicClient {
             api_key,
             model: model.to_string(),
             base_url: base_url.to_string(),
+            params: RequestParams::default(),
+            retry_policy: RetryPolicy::default(),
+            system: None,
+            stop_sequences: None,
+            request_limiter: None,
+            large_context: false,
+            interceptors: Vec::new(),
         }
     }
 
+    /// Returns the model identifier used for requests.
+    #[must_use]
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Returns a client that sends requests to a different model.
+    ///
+    /// All other settings, including the shared request limit, are kept.
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sets the sampling parameters sent with each request.
+    ///
+    /// Unset fields fall back to the API defaults; an unset `max_tokens`
+    /// uses [`DEFAULT_MAX_TOKENS`].
+    #[must_use]
+    pub fn with_params(mut self, params: RequestParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Sets the maximum number of tokens to generate per response.
+    ///
+    /// Overrides `max_tokens` in the current [`RequestParams`]; a later call to
+    /// [`with_params`](Self::with_params) replaces it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_tokens` is zero or exceeds [`MAX_TOKENS_LIMIT`].
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Result<Self> {
+        if max_tokens == 0 {
+            anyhow::bail!("max_tokens must be greater than zero");
+        }
+        if max_tokens > MAX_TOKENS_LIMIT {
+            anyhow::bail!("max_tokens {max_tokens} exceeds the limit of {MAX_TOKENS_LIMIT}");
+        }
+        self.params.max_tokens = Some(max_tokens);
+        Ok(self)
+    }
+
+    /// Returns the sampling parameters sent with each request.
+    #[must_use]
+    pub fn params(&self) -> &RequestParams {
+        &self.params
+    }
+
+    /// Sets the retry count and backoff used for 429 and 5xx responses.
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Returns the retry policy.
+    #[must_use]
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Sets the system prompt sent as the top-level `system` field of each request.
+    ///
+    /// Use this for role and behavior instructions that should stay separate
+    /// from the conversation messages.
+    #[must_use]
+    pub fn with_system_prompt(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    /// Limits how many requests may be in flight at once.
+    ///
+    /// The limit is shared by this client and every clone made from it, so a
+    /// client handed to subagents keeps the whole agent tree within a single
+    /// budget. Requests beyond the limit wait for a free slot rather than
+    /// failing. A limit of zero is treated as one.
+    #[must_use]
+    pub fn with_max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.request_limiter = Some(Arc::new(Semaphore::new(limit.max(1))));
+        self
+    }
+
+    /// Enables the 1M-token context beta.
+    ///
+    /// Requests carry the [`LARGE_CONTEXT_BETA`] header and
+    /// [`context_window`](Self::context_window) reports
+    /// [`LARGE_CONTEXT_WINDOW`]. Logs a warning if the model does not support
+    /// the beta; the header is still sent in case the list is out of date.
+    #[must_use]
+    pub fn with_large_context(mut self, enabled: bool) -> Self {
+        self.large_context = enabled;
+        if let Some(warning) = self.large_context_warning() {
+            tracing::warn!("{}", warning);
+        }
+        self
+    }
+
+    /// Returns true if the 1M-token context beta is enabled.
+    #[must_use]
+    pub fn large_context(&self) -> bool {
+        self.large_context
+    }
+
+    /// Returns a warning if the large-context beta is enabled for a model
+    /// that does not support it.
+    #[must_use]
+    pub fn large_context_warning(&self) -> Option<String> {
+        (self.large_context && !supports_large_context(&self.model)).then(|| {
+            format!(
+                "Model {} does not support the 1M context beta; requests may be rejected \
+                 or limited to the standard context window",
+                self.model
+            )
+        })
+    }
+
+    /// Returns the input token budget for conversations with this client.
+    ///
+    /// This is the model's context window (see [`model_context_limit`]), or
+    /// [`LARGE_CONTEXT_WINDOW`] with the large-context beta.
+    #[must_use]
+    pub fn context_window(&self) -> usize {
+        if self.large_context {
+            LARGE_CONTEXT_WINDOW
+        } else {
+            model_context_limit(&self.model)
+        }
+    }
+
+    /// Registers an interceptor that runs around every API call.
+    ///
+    /// Interceptors run in the order they are registered and are shared by
+    /// clones of this client. See [`interceptor`] for details.
+    #[must_use]
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Serializes a request and passes it through the registered interceptors.
+    fn intercept_request(&self, url: String, request: &impl Serialize) -> Result<OutgoingRequest> {
+        let mut outgoing = OutgoingRequest {
+            url,
+            headers: reqwest::header::HeaderMap::new(),
+            body: serde_json::to_value(request)?,
+        };
+        for interceptor in &self.interceptors {
+            interceptor.on_request(&mut outgoing);
+        }
+        Ok(outgoing)
+    }
+
+    /// Sends an intercepted request and reports the response status.
+    async fn send(&self, outgoing: &OutgoingRequest) -> Result<reqwest::Response> {
+        let response = self
+            .post(&outgoing.url)
+            .headers(outgoing.headers.clone())
+            .json(&outgoing.body)
+            .send()
+            .await?;
+        for interceptor in &self.interceptors {
+            interceptor.on_response(response.status());
+        }
+        Ok(response)
+    }
+
+    /// Passes a stream event through the interceptors and delivers it.
+    async fn emit(&self, tx: &mpsc::Sender<StreamEvent>, event: StreamEvent) {
+        for interceptor in &self.interceptors {
+            interceptor.on_event(&event);
+        }
+        tx.send(event).await.ok();
+    }
+
+    /// Starts a POST request with authentication and API headers.
+    fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        let builder = self
+            .client
+            .post(url)
+            .header("x-api-key", self.api_key.expose_secret())
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json");
+        if self.large_context {
+            builder.header("anthropic-beta", LARGE_CONTEXT_BETA)
+        } else {
+            builder
+        }
+    }
+
+    /// Waits for a request slot if a concurrency limit is set.
+    ///
+    /// The slot is held until the returned permit is dropped.
+    async fn acquire_request_slot(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.request_limiter {
+            Some(limiter) => Some(
+                limiter
+                    .acquire()
+                    .await
+                    .expect("request limiter is never closed"),
+            ),
+            None => None,
+        }
+    }
+
+    /// Sets custom sequences that stop generation when produced.
+    ///
+    /// When one triggers, the response completes with
+    /// [`StopReason::StopSequence`](crate::types::content::StopReason::StopSequence).
+    /// An empty list clears them.
+    #[must_use]
+    pub fn with_stop_sequences<I, S>(mut self, sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let sequences: Vec<String> = sequences.into_iter().map(Into::into).collect();
+        self.stop_sequences = (!sequences.is_empty()).then_some(sequences);
+        self
+    }
+
+    /// Returns the stop sequences sent with each request, if any.
+    #[must_use]
+    pub fn stop_sequences(&self) -> Option<&[String]> {
+        self.stop_sequences
+            .as_deref()
+            .filter(|sequences| !sequences.is_empty())
+    }
+
+    /// Returns the system prompt, if one is set.
+    #[must_use]
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    /// Returns the `max_tokens` value for requests.
+    fn max_tokens(&self) -> u32 {
+        self.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)
+    }
+
     /// Sends a streaming message request to the Anthropic API.
     ///
     /// # Arguments
@@ -174,7 +492,8 @@ impl AnthropicClient {
     /// - 429 Too Many Requests (rate limit)
     /// - 5xx Server Errors (500, 502, 503, 504)
     ///
-    /// Uses exponential backoff starting at 100ms.
+    /// Uses exponential backoff per the client's [`RetryPolicy`] (100ms base by
+    /// default), or the `retry-after` header on 429 responses when present.
     pub async fn stream_message(
         &self,
         messages: &[Message],
@@ -184,6 +503,72 @@ impl AnthropicClient {
             .await
     }
 
+    /// Sends a message and returns the complete response text.
+    ///
+    /// A non-streaming convenience over [`stream_message`](Self::stream_message)
+    /// for one-shot callers: content deltas are collected until the message
+    /// completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails, if the API reports an error
+    /// (including after retries are exhausted), or if the stream ends before
+    /// the message completes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() -> anyhow::Result<()> {
+    /// use patina::api::AnthropicClient;
+    /// use patina::types::{Message, Role};
+    /// use secrecy::SecretString;
+    ///
+    /// let client = AnthropicClient::new(SecretString::from("sk-ant-..."), "claude-sonnet-4-20250514");
+    /// let messages = vec![Message {
+    ///     role: Role::User,
+    ///     content: "Say hello".to_string(),
+    /// }];
+    /// let reply = client.complete(&messages).await?;
+    /// println!("{reply}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn complete(&self, messages: &[Message]) -> Result<String> {
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let collect = async move {
+            let mut text = String::new();
+            let mut error = None;
+            let mut completed = false;
+
+            // Drain until the sender is dropped so the stream never blocks
+            while let Some(event) = rx.recv().await {
+                match event {
+                    StreamEvent::ContentDelta(delta) => text.push_str(&delta),
+                    StreamEvent::Error(message) => {
+                        error.get_or_insert(message);
+                    }
+                    StreamEvent::MessageStop | StreamEvent::MessageComplete { .. } => {
+                        completed = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(message) = error {
+                anyhow::bail!("API error: {message}");
+            }
+            if !completed {
+                anyhow::bail!("Stream ended before the message completed");
+            }
+            Ok(text)
+        };
+
+        let (sent, collected) = tokio::join!(self.stream_message(messages, tx), collect);
+        sent?;
+        collected
+    }
+
     /// Sends a streaming message request with tool definitions.
     ///
     /// This is the primary method for agentic tool use. When tools are provided,
@@ -198,7 +583,8 @@ impl AnthropicClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the HTTP request fails (network error).
+    /// Returns an error if `tool_choice` cannot be satisfied by `tools`, or
+    /// if the HTTP request fails (network error).
     /// API errors (4xx, 5xx) are sent as `StreamEvent::Error` on the channel.
     pub async fn stream_message_with_tools(
         &self,
@@ -207,6 +593,10 @@ impl AnthropicClient {
         tool_choice: Option<&ToolChoice>,
         tx: mpsc::Sender<StreamEvent>,
     ) -> Result<()> {
+        if let Some(choice) = tool_choice {
+            choice.validate(tools)?;
+        }
+
         let api_messages: Vec<_> = messages
             .iter()
             .map(|m| ApiMessage {
@@ -220,26 +610,25 @@ impl AnthropicClient {
 
         let request = ApiRequest {
             model: &self.model,
-            max_tokens: 8192,
+            max_tokens: self.max_tokens(),
+            system: self.system.as_deref(),
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop_sequences: self.stop_sequences(),
             stream: true,
             messages: api_messages,
             tools,
             tool_choice,
         };
 
+        let _slot = self.acquire_request_slot().await;
         let url = format!("{}/v1/messages", self.base_url);
+        let outgoing = self.intercept_request(url, &request)?;
         let mut last_error: Option<(reqwest::StatusCode, String)> = None;
 
-        for attempt in 0..=MAX_RETRIES {
-            let response = self
-                .client
-                .post(&url)
-                .header("x-api-key", self.api_key.expose_secret())
-                .header("anthropic-version", "2023-06-01")
-                .header("content-type", "application/json")
-                .json(&request)
-                .send()
-                .await?;
+        let max_retries = self.retry_policy.max_retries;
+        for attempt in 0..=max_retries {
+            let response = self.send(&outgoing).await?;
 
             let status = response.status();
 
@@ -249,34 +638,45 @@ impl AnthropicClient {
             }
 
             // Check if this is a retryable error
-            if Self::is_retryable_status(status) && attempt < MAX_RETRIES {
+            if Self::is_retryable_status(status) && attempt < max_retries {
+                let delay = self.retry_delay(attempt, &response);
                 let body = response.text().await.unwrap_or_default();
                 last_error = Some((status, body));
 
-                // Exponential backoff: 100ms, 200ms, 400ms...
-                let delay = Duration::from_millis(BASE_BACKOFF_MS * (1 << attempt));
+                // Exponential backoff: 100ms, 200ms, 400ms... (or retry-after)
                 tokio::time::sleep(delay).await;
                 continue;
             }
 
             // Non-retryable error or exhausted retries
             let body = response.text().await.unwrap_or_default();
-            tx.send(StreamEvent::Error(format!("{}: {}", status, body)))
-                .await
-                .ok();
+            self.emit(&tx, StreamEvent::Error(format!("{}: {}", status, body)))
+                .await;
             return Ok(());
         }
 
         // Exhausted retries - send the last error
         if let Some((status, body)) = last_error {
-            tx.send(StreamEvent::Error(format!("{}: {}", status, body)))
-                .await
-                .ok();
+            self.emit(&tx, StreamEvent::Error(format!("{}: {}", status, body)))
+                .await;
         }
 
         Ok(())
     }
 
+    /// Returns the delay before retrying a failed response.
+    ///
+    /// A `retry-after` header is honored on 429 responses; otherwise the
+    /// retry policy's exponential backoff is used.
+    fn retry_delay(&self, attempt: u32, response: &reqwest::Response) -> Duration {
+        let retry_after = if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            retry::parse_retry_after(response.headers())
+        } else {
+            None
+        };
+        self.retry_policy.delay(attempt, retry_after)
+    }
+
     /// Checks if an HTTP status code should trigger a retry.
     fn is_retryable_status(status: reqwest::StatusCode) -> bool {
         status == reqwest::StatusCode::TOO_MANY_REQUESTS  // 429
@@ -306,26 +706,25 @@ impl AnthropicClient {
 
         let request = ApiRequestV2 {
             model: &self.model,
-            max_tokens: 8192,
+            max_tokens: self.max_tokens(),
+            system: self.system.as_deref(),
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop_sequences: self.stop_sequences(),
             stream: true,
             messages,
             tools: Some(&tools),
             tool_choice: Some(&ToolChoice::Auto),
         };
 
+        let _slot = self.acquire_request_slot().await;
         let url = format!("{}/v1/messages", self.base_url);
+        let outgoing = self.intercept_request(url, &request)?;
         let mut last_error: Option<(reqwest::StatusCode, String)> = None;
 
-        for attempt in 0..=MAX_RETRIES {
-            let response = self
-                .client
-                .post(&url)
-                .header("x-api-key", self.api_key.expose_secret())
-                .header("anthropic-version", "2023-06-01")
-                .header("content-type", "application/json")
-                .json(&request)
-                .send()
-                .await?;
+        let max_retries = self.retry_policy.max_retries;
+        for attempt in 0..=max_retries {
+            let response = self.send(&outgoing).await?;
 
             let status = response.status();
 
@@ -333,25 +732,23 @@ impl AnthropicClient {
                 return self.process_stream(response, tx).await;
             }
 
-            if Self::is_retryable_status(status) && attempt < MAX_RETRIES {
+            if Self::is_retryable_status(status) && attempt < max_retries {
+                let delay = self.retry_delay(attempt, &response);
                 let body = response.text().await.unwrap_or_default();
                 last_error = Some((status, body));
-                let delay = Duration::from_millis(BASE_BACKOFF_MS * (1 << attempt));
                 tokio::time::sleep(delay).await;
                 continue;
             }
 
             let body = response.text().await.unwrap_or_default();
-            tx.send(StreamEvent::Error(format!("{}: {}", status, body)))
-                .await
-                .ok();
+            self.emit(&tx, StreamEvent::Error(format!("{}: {}", status, body)))
+                .await;
             return Ok(());
         }
 
         if let Some((status, body)) = last_error {
-            tx.send(StreamEvent::Error(format!("{}: {}", status, body)))
-                .await
-                .ok();
+            self.emit(&tx, StreamEvent::Error(format!("{}: {}", status, body)))
+                .await;
         }
 
         Ok(())
@@ -371,7 +768,8 @@ impl AnthropicClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the HTTP request fails.
+    /// Returns an error if `tool_choice` cannot be satisfied by `tools`, or
+    /// if the HTTP request fails.
     pub async fn stream_message_v2_with_tools(
         &self,
         messages: &[crate::types::ApiMessageV2],
@@ -379,28 +777,31 @@ impl AnthropicClient {
         tool_choice: Option<&ToolChoice>,
         tx: mpsc::Sender<StreamEvent>,
     ) -> Result<()> {
+        if let Some(choice) = tool_choice {
+            choice.validate(tools)?;
+        }
+
         let request = ApiRequestV2 {
             model: &self.model,
-            max_tokens: 8192,
+            max_tokens: self.max_tokens(),
+            system: self.system.as_deref(),
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop_sequences: self.stop_sequences(),
             stream: true,
             messages,
             tools,
             tool_choice,
         };
 
+        let _slot = self.acquire_request_slot().await;
         let url = format!("{}/v1/messages", self.base_url);
+        let outgoing = self.intercept_request(url, &request)?;
         let mut last_error: Option<(reqwest::StatusCode, String)> = None;
 
-        for attempt in 0..=MAX_RETRIES {
-            let response = self
-                .client
-                .post(&url)
-                .header("x-api-key", self.api_key.expose_secret())
-                .header("anthropic-version", "2023-06-01")
-                .header("content-type", "application/json")
-                .json(&request)
-                .send()
-                .await?;
+        let max_retries = self.retry_policy.max_retries;
+        for attempt in 0..=max_retries {
+            let response = self.send(&outgoing).await?;
 
             let status = response.status();
 
@@ -408,25 +809,23 @@ impl AnthropicClient {
                 return self.process_stream(response, tx).await;
             }
 
-            if Self::is_retryable_status(status) && attempt < MAX_RETRIES {
+            if Self::is_retryable_status(status) && attempt < max_retries {
+                let delay = self.retry_delay(attempt, &response);
                 let body = response.text().await.unwrap_or_default();
                 last_error = Some((status, body));
-                let delay = Duration::from_millis(BASE_BACKOFF_MS * (1 << attempt));
                 tokio::time::sleep(delay).await;
                 continue;
             }
 
             let body = response.text().await.unwrap_or_default();
-            tx.send(StreamEvent::Error(format!("{}: {}", status, body)))
-                .await
-                .ok();
+            self.emit(&tx, StreamEvent::Error(format!("{}: {}", status, body)))
+                .await;
             return Ok(());
         }
 
         if let Some((status, body)) = last_error {
-            tx.send(StreamEvent::Error(format!("{}: {}", status, body)))
-                .await
-                .ok();
+            self.emit(&tx, StreamEvent::Error(format!("{}: {}", status, body)))
+                .await;
         }
 
         Ok(())
@@ -469,6 +868,7 @@ impl AnthropicClient {
     /// - "tool_use" → `StopReason::ToolUse`
     /// - "max_tokens" → `StopReason::MaxTokens`
     /// - "stop_sequence" → `StopReason::StopSequence`
+    /// - "refusal" → `StopReason::Refusal`
     /// - anything else → `StopReason::EndTurn`
     fn handle_message_delta(delta: &DeltaPayload) -> Option<StreamEvent> {
         use crate::types::content::StopReason;
@@ -478,12 +878,24 @@ impl AnthropicClient {
                 "tool_use" => StopReason::ToolUse,
                 "max_tokens" => StopReason::MaxTokens,
                 "stop_sequence" => StopReason::StopSequence,
+                "refusal" => StopReason::Refusal,
                 _ => StopReason::EndTurn,
             };
             StreamEvent::MessageComplete { stop_reason }
         })
     }
 
+    /// Handles the usage reported by a message_delta event.
+    ///
+    /// The delta normally reports only `output_tokens`; `input_tokens` falls back
+    /// to the value recorded from `message_start`.
+    fn handle_usage(usage: &UsagePayload, input_tokens: u32) -> StreamEvent {
+        StreamEvent::Usage {
+            input_tokens: usage.input_tokens.unwrap_or(input_tokens),
+            output_tokens: usage.output_tokens.unwrap_or(0),
+        }
+    }
+
     /// Handles a content_block_delta event, returning the appropriate StreamEvent.
     ///
     /// Supports:
@@ -502,6 +914,11 @@ impl AnthropicClient {
                         partial_json: partial_json.clone(),
                     })
             }
+            Some("thinking_delta") => delta
+                .thinking
+                .as_ref()
+                .map(|thinking| StreamEvent::ThinkingDelta(thinking.clone())),
+            Some("signature_delta") => None,
             Some("text_delta") | None => {
                 // Regular text content
                 delta
@@ -538,6 +955,8 @@ impl AnthropicClient {
         let mut current_block_index: usize = 0;
         // Track if current block is tool_use (vs text)
         let mut in_tool_use_block = false;
+        // Input token count from message_start, reported alongside output usage
+        let mut input_tokens: u32 = 0;
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
@@ -560,7 +979,7 @@ impl AnthropicClient {
                                             content_block,
                                             current_block_index,
                                         ) {
-                                            tx.send(event).await.ok();
+                                            self.emit(&tx, event).await;
                                         }
                                     }
                                 }
@@ -573,7 +992,7 @@ impl AnthropicClient {
                                         if let Some(event) =
                                             Self::handle_content_block_delta(delta, block_index)
                                         {
-                                            tx.send(event).await.ok();
+                                            self.emit(&tx, event).await;
                                         }
                                     }
                                 }
@@ -585,25 +1004,38 @@ impl AnthropicClient {
                                         block_index,
                                         in_tool_use_block,
                                     );
-                                    tx.send(event).await.ok();
+                                    self.emit(&tx, event).await;
                                     in_tool_use_block = false;
                                 }
 
-                                // Message metadata update (includes stop_reason)
+                                // Message envelope (includes input token usage)
+                                "message_start" => {
+                                    if let Some(usage) =
+                                        parsed.message.as_ref().and_then(|m| m.usage.as_ref())
+                                    {
+                                        input_tokens = usage.input_tokens.unwrap_or(0);
+                                    }
+                                }
+
+                                // Message metadata update (includes stop_reason and usage)
                                 "message_delta" => {
+                                    if let Some(ref usage) = parsed.usage {
+                                        let event = Self::handle_usage(usage, input_tokens);
+                                        self.emit(&tx, event).await;
+                                    }
                                     if let Some(ref delta) = parsed.delta {
                                         if let Some(event) = Self::handle_message_delta(delta) {
-                                            tx.send(event).await.ok();
+                                            self.emit(&tx, event).await;
                                         }
                                     }
                                 }
 
                                 // Message stream complete (legacy)
                                 "message_stop" => {
-                                    tx.send(StreamEvent::MessageStop).await.ok();
+                                    self.emit(&tx, StreamEvent::MessageStop).await;
                                 }
 
-                                // Ignore other event types (message_start, ping, etc.)
+                                // Ignore other event types (ping, etc.)
                                 _ => {}
                             }
                         }
@@ -1044,6 +1476,75 @@ data: {"type":"message_stop"}
         )));
     }
 
+    /// Test: message_delta usage emits a Usage event before MessageComplete.
+    #[tokio::test]
+    async fn test_process_stream_message_delta_usage() {
+        let mock_server = MockServer::start().await;
+        let client = test_client(&mock_server.uri());
+
+        let sse_response = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"usage":{"input_tokens":25,"output_tokens":1}}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":15}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+        let events = collect_stream_events(&client, sse_response, &mock_server).await;
+
+        let usage_pos = events
+            .iter()
+            .position(|e| {
+                matches!(
+                    e,
+                    StreamEvent::Usage {
+                        input_tokens: 25,
+                        output_tokens: 15
+                    }
+                )
+            })
+            .expect("usage event should be emitted");
+        let complete_pos = events
+            .iter()
+            .position(|e| matches!(e, StreamEvent::MessageComplete { .. }))
+            .expect("message complete should be emitted");
+        assert!(usage_pos < complete_pos);
+    }
+
+    /// Test: a content-free refusal emits no content and reports the refusal.
+    #[tokio::test]
+    async fn test_process_stream_content_free_refusal() {
+        let mock_server = MockServer::start().await;
+        let client = test_client(&mock_server.uri());
+
+        let sse_response = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-3"}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"refusal"}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+        let events = collect_stream_events(&client, sse_response, &mock_server).await;
+
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::ContentDelta(_))));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::MessageComplete {
+                stop_reason: StopReason::Refusal
+            }
+        )));
+        assert!(events.iter().any(|e| matches!(e, StreamEvent::MessageStop)));
+    }
+
     /// Test: message_stop event emits MessageStop.
     #[tokio::test]
     async fn test_process_stream_message_stop() {
@@ -1165,6 +1666,8 @@ data: {"type":"message_stop"}
                 StreamEvent::ContentDelta(_) => "ContentDelta",
                 StreamEvent::ContentBlockComplete { .. } => "ContentBlockComplete",
                 StreamEvent::Error(_) => "Error",
+                StreamEvent::Usage { .. } => "Usage",
+                StreamEvent::ThinkingDelta(_) => "ThinkingDelta",
             })
             .collect();
 
@@ -1290,6 +1793,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: Some("Hello world".to_string()),
             partial_json: None,
+            thinking: None,
             stop_reason: None,
             delta_type: Some("text_delta".to_string()),
         };
@@ -1306,6 +1810,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: Some("{\"key\":".to_string()),
+            thinking: None,
             stop_reason: None,
             delta_type: Some("input_json_delta".to_string()),
         };
@@ -1328,6 +1833,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: Some("Fallback text".to_string()),
             partial_json: None,
+            thinking: None,
             stop_reason: None,
             delta_type: None,
         };
@@ -1344,6 +1850,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: Some("Unknown type text".to_string()),
             partial_json: None,
+            thinking: None,
             stop_reason: None,
             delta_type: Some("future_unknown_type".to_string()),
         };
@@ -1360,6 +1867,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: None,
             delta_type: Some("input_json_delta".to_string()),
         };
@@ -1376,6 +1884,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: None,
             delta_type: Some("text_delta".to_string()),
         };
@@ -1384,6 +1893,23 @@ data: {"type":"message_stop"}
         assert!(result.is_none(), "Should return None when text is missing");
     }
 
+    #[test]
+    fn test_handle_content_block_delta_thinking_delta() {
+        let delta = DeltaPayload {
+            text: None,
+            partial_json: None,
+            thinking: Some("Let me consider".to_string()),
+            stop_reason: None,
+            delta_type: Some("thinking_delta".to_string()),
+        };
+
+        let result = AnthropicClient::handle_content_block_delta(&delta, 0);
+        assert!(matches!(
+            result,
+            Some(StreamEvent::ThinkingDelta(ref s)) if s == "Let me consider"
+        ));
+    }
+
     // ============================================================================
     // End Phase 2.9.1.3 unit tests
     // ============================================================================
@@ -1397,6 +1923,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: Some("tool_use".to_string()),
             delta_type: None,
         };
@@ -1415,6 +1942,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: Some("max_tokens".to_string()),
             delta_type: None,
         };
@@ -1433,6 +1961,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: Some("stop_sequence".to_string()),
             delta_type: None,
         };
@@ -1446,11 +1975,58 @@ data: {"type":"message_stop"}
         ));
     }
 
+    #[test]
+    fn test_handle_usage_falls_back_to_message_start_input() {
+        let usage = UsagePayload {
+            input_tokens: None,
+            output_tokens: Some(42),
+        };
+        assert_eq!(
+            AnthropicClient::handle_usage(&usage, 100),
+            StreamEvent::Usage {
+                input_tokens: 100,
+                output_tokens: 42
+            }
+        );
+
+        let usage = UsagePayload {
+            input_tokens: Some(7),
+            output_tokens: None,
+        };
+        assert_eq!(
+            AnthropicClient::handle_usage(&usage, 100),
+            StreamEvent::Usage {
+                input_tokens: 7,
+                output_tokens: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_handle_message_delta_refusal() {
+        let delta = DeltaPayload {
+            text: None,
+            partial_json: None,
+            thinking: None,
+            stop_reason: Some("refusal".to_string()),
+            delta_type: None,
+        };
+
+        let result = AnthropicClient::handle_message_delta(&delta);
+        assert!(matches!(
+            result,
+            Some(StreamEvent::MessageComplete {
+                stop_reason: StopReason::Refusal
+            })
+        ));
+    }
+
     #[test]
     fn test_handle_message_delta_end_turn() {
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: Some("end_turn".to_string()),
             delta_type: None,
         };
@@ -1469,6 +2045,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: Some("some_future_reason".to_string()),
             delta_type: None,
         };
@@ -1488,6 +2065,7 @@ data: {"type":"message_stop"}
         let delta = DeltaPayload {
             text: None,
             partial_json: None,
+            thinking: None,
             stop_reason: None,
             delta_type: None,
         };
@@ -1591,6 +2169,10 @@ data: {"type":"message_stop"}
         let request = ApiRequest {
             model: "claude-3-opus",
             max_tokens: 1024,
+            system: None,
+            temperature: None,
+            top_p: None,
+            stop_sequences: None,
             stream: true,
             messages,
             tools: None,
@@ -1619,6 +2201,10 @@ data: {"type":"message_stop"}
         let request = ApiRequest {
             model: "claude-3-opus",
             max_tokens: 1024,
+            system: None,
+            temperature: None,
+            top_p: None,
+            stop_sequences: None,
             stream: true,
             messages,
             tools: Some(&tools),
@@ -1646,6 +2232,10 @@ data: {"type":"message_stop"}
         let request = ApiRequest {
             model: "claude-sonnet",
             max_tokens: 8192,
+            system: None,
+            temperature: None,
+            top_p: None,
+            stop_sequences: None,
             stream: true,
             messages,
             tools: Some(&tools),
@@ -1663,4 +2253,379 @@ data: {"type":"message_stop"}
         assert!(json.contains("\"glob\""));
         assert!(json.contains("\"grep\""));
     }
+
+    #[test]
+    fn test_api_request_v2_serializes_request_params() {
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus")
+            .with_params(RequestParams {
+                temperature: Some(0.2),
+                top_p: Some(0.9),
+                max_tokens: Some(2048),
+            });
+        let messages = vec![crate::types::ApiMessageV2::user("Hello")];
+
+        let request = ApiRequestV2 {
+            model: &client.model,
+            max_tokens: client.max_tokens(),
+            system: None,
+            temperature: client.params.temperature,
+            top_p: client.params.top_p,
+            stop_sequences: None,
+            stream: true,
+            messages: &messages,
+            tools: None,
+            tool_choice: None,
+        };
+
+        let json = serde_json::to_value(&request).expect("serialization should succeed");
+
+        assert_eq!(json["max_tokens"], 2048);
+        assert!((json["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+        assert!((json["top_p"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_api_request_omits_unset_params() {
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus");
+        let messages = vec![crate::types::ApiMessageV2::user("Hello")];
+
+        let request = ApiRequestV2 {
+            model: &client.model,
+            max_tokens: client.max_tokens(),
+            system: None,
+            temperature: client.params.temperature,
+            top_p: client.params.top_p,
+            stop_sequences: None,
+            stream: true,
+            messages: &messages,
+            tools: None,
+            tool_choice: None,
+        };
+
+        let json = serde_json::to_string(&request).expect("serialization should succeed");
+
+        assert!(json.contains(&format!("\"max_tokens\":{}", DEFAULT_MAX_TOKENS)));
+        assert!(!json.contains("temperature"));
+        assert!(!json.contains("top_p"));
+        assert!(!json.contains("\"system\""));
+    }
+
+    #[test]
+    fn test_api_request_serializes_system_prompt() {
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus")
+            .with_system_prompt("You are a code reviewer.");
+        let messages = vec![crate::types::ApiMessageV2::user("Hello")];
+
+        let request = ApiRequestV2 {
+            model: &client.model,
+            max_tokens: client.max_tokens(),
+            system: client.system_prompt(),
+            temperature: None,
+            top_p: None,
+            stop_sequences: None,
+            stream: true,
+            messages: &messages,
+            tools: None,
+            tool_choice: None,
+        };
+
+        let json = serde_json::to_value(&request).expect("serialization should succeed");
+
+        assert_eq!(json["system"], "You are a code reviewer.");
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_sent_with_request() {
+        use wiremock::matchers::body_partial_json;
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_partial_json(
+                serde_json::json!({ "system": "Answer tersely." }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+                "text/event-stream",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = test_client(&mock_server.uri()).with_system_prompt("Answer tersely.");
+        let reply = client
+            .complete(&[Message {
+                role: Role::User,
+                content: "Hi".to_string(),
+            }])
+            .await
+            .unwrap();
+
+        assert!(reply.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_large_context_sends_beta_header() {
+        use wiremock::matchers::header;
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("anthropic-beta", LARGE_CONTEXT_BETA))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+                "text/event-stream",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = test_client(&mock_server.uri())
+            .with_model("claude-sonnet-4-20250514")
+            .with_large_context(true);
+        client
+            .complete(&[Message {
+                role: Role::User,
+                content: "Hi".to_string(),
+            }])
+            .await
+            .unwrap();
+    }
+
+    /// Adds a header and a body field to every request.
+    struct Tagging;
+
+    impl Interceptor for Tagging {
+        fn on_request(&self, request: &mut OutgoingRequest) {
+            request
+                .headers
+                .insert("x-test-tag", "tagged".parse().unwrap());
+            request.body["metadata"] = serde_json::json!({ "user_id": "tester" });
+        }
+    }
+
+    /// Records request bodies, response statuses and event counts.
+    #[derive(Clone, Default)]
+    struct Recording {
+        bodies: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+        statuses: Arc<std::sync::Mutex<Vec<u16>>>,
+        events: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Interceptor for Recording {
+        fn on_request(&self, request: &mut OutgoingRequest) {
+            self.bodies.lock().unwrap().push(request.body.clone());
+        }
+
+        fn on_response(&self, status: reqwest::StatusCode) {
+            self.statuses.lock().unwrap().push(status.as_u16());
+        }
+
+        fn on_event(&self, _event: &StreamEvent) {
+            self.events
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptors_run_in_order_around_requests() {
+        use wiremock::matchers::{body_partial_json, header};
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("x-test-tag", "tagged"))
+            .and(body_partial_json(
+                serde_json::json!({ "metadata": { "user_id": "tester" } }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n\
+                 event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+                "text/event-stream",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let recording = Recording::default();
+        let client = test_client(&mock_server.uri())
+            .with_interceptor(Tagging)
+            .with_interceptor(recording.clone());
+
+        let reply = client
+            .complete(&[Message {
+                role: Role::User,
+                content: "Hello".to_string(),
+            }])
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hi");
+
+        // The recorder runs after the tagger, so it sees the modified body
+        let bodies = recording.bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 1);
+        assert_eq!(bodies[0]["model"], "claude-3-opus");
+        assert_eq!(bodies[0]["messages"][0]["content"], "Hello");
+        assert_eq!(bodies[0]["metadata"]["user_id"], "tester");
+
+        assert_eq!(*recording.statuses.lock().unwrap(), vec![200]);
+        assert_eq!(
+            recording.events.load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+    }
+
+    #[test]
+    fn test_large_context_raises_context_window() {
+        let client = AnthropicClient::new(SecretString::from("key"), "claude-sonnet-4-20250514");
+        assert!(!client.large_context());
+        assert_eq!(client.context_window(), 200_000);
+
+        let client = client.with_large_context(true);
+        assert!(client.large_context());
+        assert_eq!(client.context_window(), LARGE_CONTEXT_WINDOW);
+        assert!(client.large_context_warning().is_none());
+    }
+
+    #[test]
+    fn test_context_window_follows_model() {
+        let client = AnthropicClient::new(SecretString::from("key"), "claude-2.0");
+        assert_eq!(client.context_window(), 100_000);
+
+        let client = client.with_model("unknown-model");
+        assert_eq!(client.context_window(), DEFAULT_MAX_INPUT_TOKENS);
+    }
+
+    #[test]
+    fn test_large_context_warns_for_unsupported_model() {
+        let client = AnthropicClient::new(SecretString::from("key"), "claude-3-haiku-20240307");
+        assert!(client.large_context_warning().is_none());
+
+        let warning = client
+            .with_large_context(true)
+            .large_context_warning()
+            .expect("unsupported model should warn");
+        assert!(warning.contains("claude-3-haiku-20240307"));
+    }
+
+    #[test]
+    fn test_supports_large_context() {
+        assert!(supports_large_context("claude-sonnet-4-20250514"));
+        assert!(supports_large_context("claude-sonnet-4-5"));
+        assert!(!supports_large_context("claude-opus-4-20250514"));
+        assert!(!supports_large_context("claude-3-5-sonnet-20241022"));
+    }
+
+    #[test]
+    fn test_stop_sequences_serialize_only_when_non_empty() {
+        let messages = vec![crate::types::ApiMessageV2::user("Hello")];
+        let serialize = |client: &AnthropicClient| {
+            serde_json::to_value(ApiRequestV2 {
+                model: &client.model,
+                max_tokens: client.max_tokens(),
+                system: None,
+                temperature: None,
+                top_p: None,
+                stop_sequences: client.stop_sequences(),
+                stream: true,
+                messages: &messages,
+                tools: None,
+                tool_choice: None,
+            })
+            .expect("serialization should succeed")
+        };
+
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus");
+        assert!(serialize(&client).get("stop_sequences").is_none());
+
+        let client = client.with_stop_sequences(Vec::<String>::new());
+        assert!(serialize(&client).get("stop_sequences").is_none());
+
+        let client = client.with_stop_sequences(["</answer>", "END"]);
+        assert_eq!(
+            serialize(&client)["stop_sequences"],
+            serde_json::json!(["</answer>", "END"])
+        );
+    }
+
+    #[test]
+    fn test_with_max_tokens() {
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus")
+            .with_max_tokens(32_000)
+            .unwrap();
+        assert_eq!(client.max_tokens(), 32_000);
+
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus");
+        assert_eq!(client.max_tokens(), DEFAULT_MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_with_max_tokens_rejects_out_of_range() {
+        let client = AnthropicClient::new(SecretString::from("test-key"), "claude-3-opus");
+        assert!(client.clone().with_max_tokens(0).is_err());
+        assert!(client.clone().with_max_tokens(MAX_TOKENS_LIMIT).is_ok());
+        assert!(client.with_max_tokens(MAX_TOKENS_LIMIT + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_returns_concatenated_text() {
+        let mock_server = MockServer::start().await;
+        let sse_response = r#"event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello, "}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"world"}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn"}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(sse_response, "text/event-stream"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = test_client(&mock_server.uri());
+        let reply = client
+            .complete(&[Message {
+                role: Role::User,
+                content: "Hi".to_string(),
+            }])
+            .await
+            .unwrap();
+
+        assert_eq!(reply, "Hello, world");
+    }
+
+    #[tokio::test]
+    async fn test_complete_converts_error_event() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .mount(&mock_server)
+            .await;
+
+        let client = test_client(&mock_server.uri());
+        let err = client
+            .complete(&[Message {
+                role: Role::User,
+                content: "Hi".to_string(),
+            }])
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("bad request"), "got: {err}");
+    }
 }
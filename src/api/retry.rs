@@ -0,0 +1,208 @@
+//! Retry policy for Anthropic API requests.
+//!
+//! Retryable failures (429 rate limits and 5xx server errors) are retried with
+//! exponential backoff, clamped to a maximum delay. A `retry-after` header on
+//! a 429 response (seconds or HTTP date) takes precedence over the computed
+//! backoff, but is clamped to the same maximum so a misbehaving server cannot
+//! stall the client indefinitely.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+/// Controls how many times, and how long to wait before, a failed request is retried.
+///
+/// # Examples
+///
+/// ```rust
+/// use patina::api::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(5, Duration::from_millis(500), Duration::from_secs(8));
+/// assert_eq!(policy.backoff(0), Duration::from_millis(500));
+/// assert_eq!(policy.backoff(10), Duration::from_secs(8));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of retries after the initial attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on each subsequent retry.
+    pub base_backoff: Duration,
+    /// Upper bound for any retry delay, including a server-provided `retry-after`.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a retry policy.
+    #[must_use]
+    pub fn new(max_retries: u32, base_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            base_backoff,
+            max_backoff,
+        }
+    }
+
+    /// Returns the exponential backoff delay for the given retry attempt (0-based).
+    ///
+    /// The delay is `base_backoff * 2^attempt`, clamped to `max_backoff`.
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.base_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Returns the delay before retrying, preferring a server-provided `retry-after`.
+    ///
+    /// The `retry-after` value is clamped to `max_backoff`.
+    #[must_use]
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after.map_or_else(|| self.backoff(attempt), |d| d.min(self.max_backoff))
+    }
+}
+
+/// Parses a `retry-after` header.
+///
+/// Accepts either delay-seconds (`120`) or an HTTP date
+/// (`Wed, 21 Oct 2015 07:28:00 GMT`); a date in the past yields a zero delay.
+/// Returns `None` if the header is missing or unparseable.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let target = parse_http_date(value)?;
+    Some(
+        target
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Parses an IMF-fixdate HTTP date (`Wed, 21 Oct 2015 07:28:00 GMT`).
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (_weekday, rest) = value.split_once(", ")?;
+    let parts: Vec<&str> = rest.split_whitespace().collect();
+    let [day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| m == month)? as u64 + 1;
+    let year: u64 = year.parse().ok()?;
+    let mut hms = time.split(':').map(|p| p.parse::<u64>().ok());
+    let (hour, minute, second) = (hms.next()??, hms.next()??, hms.next()??);
+    if hms.next().is_some() || !(1..=31).contains(&day) || year < 1970 {
+        return None;
+    }
+
+    // Days since the Unix epoch for a proleptic Gregorian date
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let yoe = y % 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = (era * 146_097 + doe).checked_sub(719_468)?;
+
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_default_policy_matches_previous_constants() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.max_retries, 2);
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_backoff_clamps_to_max() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(100), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_delay_prefers_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(policy.delay(1, None), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_delay_clamps_large_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(6 * 3600))),
+            policy.max_backoff
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(2)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let parsed = parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(
+            parsed.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_secs(1_445_412_480)
+        );
+
+        let parsed = parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT").unwrap();
+        assert_eq!(parsed, UNIX_EPOCH);
+
+        assert!(parse_http_date("Wed, 21 Oct 2015 07:28:00 PST").is_none());
+        assert!(parse_http_date("21 Oct 2015").is_none());
+    }
+
+    #[test]
+    fn test_parse_retry_after_past_date_is_zero() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+}
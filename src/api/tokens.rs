@@ -374,6 +374,51 @@ impl TokenEstimator {
     }
 }
 
+/// Percentage of the limit at which [`TokenBudget::check`] starts warning.
+pub const CONTEXT_WARNING_PERCENT: usize = 90;
+
+/// How a request compares to a [`TokenBudget`] limit before it is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetStatus {
+    /// Comfortably within the limit.
+    Ok,
+    /// At or above [`CONTEXT_WARNING_PERCENT`] of the limit.
+    Warning {
+        /// Estimated tokens in the request.
+        used: usize,
+        /// The budget limit.
+        limit: usize,
+    },
+    /// Larger than the limit; older messages will be dropped to fit.
+    OverLimit {
+        /// Estimated tokens in the request.
+        used: usize,
+        /// The budget limit.
+        limit: usize,
+    },
+}
+
+impl BudgetStatus {
+    /// Returns a message to show the user, or `None` when within the limit.
+    #[must_use]
+    pub fn warning(&self) -> Option<String> {
+        match *self {
+            Self::Ok => None,
+            Self::Warning { used, limit } => Some(format!(
+                "Context {}% full (~{}k/{}k tokens): older messages will soon be dropped from requests",
+                used * 100 / limit.max(1),
+                used / 1000,
+                limit / 1000
+            )),
+            Self::OverLimit { used, limit } => Some(format!(
+                "Context over limit (~{}k/{}k tokens): older messages are being dropped from requests",
+                used / 1000,
+                limit / 1000
+            )),
+        }
+    }
+}
+
 /// Token budget tracker for monitoring API usage against limits.
 ///
 /// Tracks token usage and provides warning/critical thresholds to help
@@ -458,6 +503,11 @@ impl TokenBudget {
         self.used = self.used.saturating_add(tokens);
     }
 
+    /// Sets the token usage count, replacing the previous value.
+    pub fn set_usage(&mut self, tokens: usize) {
+        self.used = tokens;
+    }
+
     /// Returns the percentage of budget used (0-100+).
     #[must_use]
     pub fn percentage_used(&self) -> usize {
@@ -489,6 +539,23 @@ impl TokenBudget {
     pub fn reset(&mut self) {
         self.used = 0;
     }
+
+    /// Estimates whether `messages` fit the limit before they are sent.
+    ///
+    /// Unlike [`used`](Self::used), which reflects the last response, this
+    /// looks at the request about to be made.
+    #[must_use]
+    pub fn check(&self, messages: &[ApiMessageV2]) -> BudgetStatus {
+        let used = estimate_messages_tokens(messages);
+        let limit = self.limit;
+        if used > limit {
+            BudgetStatus::OverLimit { used, limit }
+        } else if used * 100 >= limit * CONTEXT_WARNING_PERCENT {
+            BudgetStatus::Warning { used, limit }
+        } else {
+            BudgetStatus::Ok
+        }
+    }
 }
 
 impl std::fmt::Display for TokenBudget {
@@ -921,6 +988,14 @@ mod tests {
         assert_eq!(budget.remaining(), 95_000);
     }
 
+    #[test]
+    fn test_token_budget_set_usage_replaces() {
+        let mut budget = TokenBudget::new(100_000);
+        budget.add_usage(5_000);
+        budget.set_usage(12_000);
+        assert_eq!(budget.used(), 12_000);
+    }
+
     #[test]
     fn test_token_budget_tracking_accumulates() {
         let mut budget = TokenBudget::new(100_000);
@@ -1004,4 +1079,44 @@ mod tests {
             "Display should show limit"
         );
     }
+
+    #[test]
+    fn test_token_budget_check_before_sending() {
+        let budget = TokenBudget::new(1000);
+        let request = |chars: usize| vec![ApiMessageV2::user("x".repeat(chars))];
+
+        // 4 tokens of overhead plus 1 token per 4 characters
+        assert_eq!(budget.check(&request(400)), BudgetStatus::Ok);
+        assert_eq!(
+            budget.check(&request(3600)),
+            BudgetStatus::Warning {
+                used: 904,
+                limit: 1000
+            }
+        );
+        assert_eq!(
+            budget.check(&request(4000)),
+            BudgetStatus::OverLimit {
+                used: 1004,
+                limit: 1000
+            }
+        );
+    }
+
+    #[test]
+    fn test_budget_status_warning_message() {
+        assert_eq!(BudgetStatus::Ok.warning(), None);
+
+        let warning = BudgetStatus::Warning {
+            used: 92_000,
+            limit: 100_000,
+        };
+        assert!(warning.warning().unwrap().contains("92% full"));
+
+        let over = BudgetStatus::OverLimit {
+            used: 120_000,
+            limit: 100_000,
+        };
+        assert!(over.warning().unwrap().contains("over limit (~120k/100k"));
+    }
 }
@@ -18,9 +18,13 @@
 //! assert!(tools.iter().any(|t| t.name == "bash"));
 //! ```
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
+use crate::types::settings::ToolProfile;
+
 /// A tool definition for the Anthropic API.
 ///
 /// This struct represents the schema that Claude uses to understand
@@ -65,7 +69,7 @@ impl ToolDefinition {
 /// Tool choice configuration for the API request.
 ///
 /// Controls how Claude selects tools during a conversation.
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ToolChoice {
     /// Claude decides whether to use tools based on the conversation.
@@ -80,6 +84,33 @@ pub enum ToolChoice {
     },
 }
 
+impl ToolChoice {
+    /// Creates a choice that forces Claude to call the named tool.
+    #[must_use]
+    pub fn tool(name: impl Into<String>) -> Self {
+        Self::Tool { name: name.into() }
+    }
+
+    /// Checks that this choice can be satisfied by the given tools.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the choice requires a tool but none are provided,
+    /// or names a tool that is not among `tools`.
+    pub fn validate(&self, tools: Option<&[ToolDefinition]>) -> anyhow::Result<()> {
+        let tools = tools.unwrap_or_default();
+        match self {
+            Self::Auto => Ok(()),
+            Self::Any if tools.is_empty() => {
+                anyhow::bail!("tool_choice \"any\" requires at least one tool")
+            }
+            Self::Any => Ok(()),
+            Self::Tool { name } if tools.iter().any(|t| &t.name == name) => Ok(()),
+            Self::Tool { name } => anyhow::bail!("tool_choice names unknown tool '{name}'"),
+        }
+    }
+}
+
 /// Returns the default set of tools for Patina.
 ///
 /// Includes: bash, read_file, write_file, edit, list_files, glob, grep, web_fetch, web_search, analyze_image
@@ -99,6 +130,43 @@ pub fn default_tools() -> Vec<ToolDefinition> {
     ]
 }
 
+/// Returns the default tools, followed by `extra_tools` (plugin and MCP
+/// tools), advertised to `model`.
+///
+/// Tools named in `disabled` are never advertised. The profile whose key is
+/// the longest prefix of `model`, if any, then narrows what remains.
+#[must_use]
+pub fn tools_for_model(
+    model: &str,
+    disabled: &[String],
+    profiles: &HashMap<String, ToolProfile>,
+    extra_tools: &[ToolDefinition],
+) -> Vec<ToolDefinition> {
+    let profile = profiles
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, profile)| profile);
+
+    default_tools()
+        .into_iter()
+        .chain(extra_tools.iter().cloned())
+        .filter(|tool| !disabled.contains(&tool.name))
+        .filter(|tool| profile.map_or(true, |p| p.allows(&tool.name)))
+        .collect()
+}
+
+/// Returns true if `name` belongs to a tool Patina provides itself.
+///
+/// Covers the default tools, `read_mcp_resource` and every
+/// `mcp__<server>__<tool>` name, so plugins cannot shadow any of them.
+#[must_use]
+pub fn is_builtin_tool(name: &str) -> bool {
+    name.starts_with(crate::mcp::MCP_TOOL_PREFIX)
+        || name == read_mcp_resource_tool().name
+        || default_tools().iter().any(|tool| tool.name == name)
+}
+
 /// Creates the bash tool definition.
 ///
 /// Executes shell commands in the working directory.
@@ -130,13 +198,25 @@ pub fn read_file_tool() -> ToolDefinition {
     ToolDefinition::new(
         "read_file",
         "Read the contents of a file. The path must be relative to the working directory. \
-         Returns the full file content as text. Binary files may not read correctly.",
+         Returns the full file content as text. For large files, pass `offset` and/or \
+         `limit` to read a window of lines, returned with line numbers. Binary files \
+         may not read correctly.",
         json!({
             "type": "object",
             "properties": {
                 "path": {
                     "type": "string",
                     "description": "The relative path to the file to read"
+                },
+                "offset": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "1-based line number to start reading from (default: 1)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Maximum number of lines to read (default: rest of file)"
                 }
             },
             "required": ["path"]
@@ -179,8 +259,8 @@ pub fn edit_tool() -> ToolDefinition {
     ToolDefinition::new(
         "edit",
         "Edit a file by replacing a specific string with another. The old_string must \
-         match exactly once in the file (unique match required). Use this for precise \
-         modifications rather than rewriting entire files.",
+         match exactly once in the file (unique match required) unless replace_all is set. \
+         Use this for precise modifications rather than rewriting entire files.",
         json!({
             "type": "object",
             "properties": {
@@ -195,6 +275,18 @@ pub fn edit_tool() -> ToolDefinition {
                 "new_string": {
                     "type": "string",
                     "description": "The string to replace old_string with"
+                },
+                "replace_all": {
+                    "type": "boolean",
+                    "description": "Replace every occurrence of old_string instead of requiring a unique match (default: false)"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line (1-based) of the range to search for old_string; requires end_line"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line (inclusive) of the range to search for old_string; requires start_line"
                 }
             },
             "required": ["path", "old_string", "new_string"]
@@ -245,6 +337,10 @@ pub fn glob_tool() -> ToolDefinition {
                 "respect_gitignore": {
                     "type": "boolean",
                     "description": "Whether to respect .gitignore rules (default: false)"
+                },
+                "git_tracked_only": {
+                    "type": "boolean",
+                    "description": "Only match files tracked by git, as listed by git ls-files (default: false). Falls back to a normal walk outside a git repository."
                 }
             },
             "required": ["pattern"]
@@ -260,7 +356,8 @@ pub fn grep_tool() -> ToolDefinition {
     ToolDefinition::new(
         "grep",
         "Search file contents for a regular expression pattern. Returns matching lines \
-         with file paths and line numbers. Useful for finding code references, function \
+         as `path:line: text`, with optional context lines as `path-line- text` and `--` \
+         between non-adjacent groups. Useful for finding code references, function \
          definitions, and text patterns across the codebase.",
         json!({
             "type": "object",
@@ -276,6 +373,28 @@ pub fn grep_tool() -> ToolDefinition {
                 "case_insensitive": {
                     "type": "boolean",
                     "description": "Whether to perform case-insensitive search (default: false)"
+                },
+                "before": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Lines of context to show before each match, like grep -B (default: 0)"
+                },
+                "after": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Lines of context to show after each match, like grep -A (default: 0)"
+                },
+                "files_with_matches": {
+                    "type": "boolean",
+                    "description": "Return only the paths of files containing a match, one per line, like grep -l (default: false)"
+                },
+                "respect_gitignore": {
+                    "type": "boolean",
+                    "description": "Whether to skip files ignored by .gitignore (default: false). The .git directory is always skipped."
+                },
+                "git_tracked_only": {
+                    "type": "boolean",
+                    "description": "Only search files tracked by git, as listed by git ls-files (default: false). Much faster in large repositories; falls back to a normal walk outside a git repository."
                 }
             },
             "required": ["pattern"]
@@ -342,22 +461,68 @@ pub fn web_search_tool() -> ToolDefinition {
 pub fn vision_tool() -> ToolDefinition {
     ToolDefinition::new(
         "analyze_image",
-        "Analyze an image using Claude's vision capabilities. Load an image from a file path \
-         and optionally provide a prompt to guide the analysis. Supported formats: PNG, JPEG, \
-         GIF, WebP. Maximum file size: 20MB.",
+        "Analyze an image using Claude's vision capabilities. Load an image from a file path, \
+         a URL or the system clipboard and optionally provide a prompt to guide the analysis. \
+         Supported formats: PNG, JPEG, GIF, WebP. Maximum file size: 20MB.",
         json!({
             "type": "object",
             "properties": {
+                "source": {
+                    "type": "object",
+                    "description": "Where to load the image from; give exactly one of path, url or clipboard",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The relative path to the image file"
+                        },
+                        "url": {
+                            "type": "string",
+                            "description": "An http or https URL of the image"
+                        },
+                        "clipboard": {
+                            "type": "boolean",
+                            "description": "Set to true to use the image on the system clipboard"
+                        }
+                    }
+                },
                 "path": {
                     "type": "string",
-                    "description": "The relative path to the image file to analyze"
+                    "description": "The relative path to the image file to analyze (same as source.path)"
                 },
                 "prompt": {
                     "type": "string",
                     "description": "Optional prompt to guide the image analysis (e.g., 'What objects are in this image?')"
                 }
             },
-            "required": ["path"]
+            "required": []
+        }),
+    )
+}
+
+/// Creates the read_mcp_resource tool definition.
+///
+/// Lists and reads resources exposed by MCP servers. Not part of
+/// [`default_tools`]; advertise it alongside the MCP servers' own tools.
+#[must_use]
+pub fn read_mcp_resource_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "read_mcp_resource",
+        "Read a resource (file, document or data) exposed by an MCP server. Call without a \
+         uri to list the available resources; then pass the server and uri of one to read \
+         its content.",
+        json!({
+            "type": "object",
+            "properties": {
+                "server": {
+                    "type": "string",
+                    "description": "Name of the MCP server that exposes the resource"
+                },
+                "uri": {
+                    "type": "string",
+                    "description": "URI of the resource to read, as listed"
+                }
+            },
+            "required": []
         }),
     )
 }
@@ -366,6 +531,44 @@ pub fn vision_tool() -> ToolDefinition {
 mod tests {
     use super::*;
 
+    fn tool_names(tools: &[ToolDefinition]) -> Vec<&str> {
+        tools.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tools_for_model_applies_disabled_list_and_longest_profile() {
+        let disabled = vec!["web_search".to_string()];
+        let mut profiles = HashMap::new();
+        profiles.insert(
+            "claude-3".to_string(),
+            ToolProfile {
+                tools: Some(vec!["bash".to_string(), "web_search".to_string()]),
+                ..ToolProfile::default()
+            },
+        );
+        profiles.insert(
+            "claude-3-haiku".to_string(),
+            ToolProfile {
+                disabled_tools: vec!["web_fetch".to_string()],
+                ..ToolProfile::default()
+            },
+        );
+
+        // No profile matches: everything but the globally disabled tool
+        let tools = tools_for_model("claude-sonnet-4-20250514", &disabled, &profiles, &[]);
+        assert_eq!(tools.len(), default_tools().len() - 1);
+        assert!(!tool_names(&tools).contains(&"web_search"));
+
+        // The longest matching prefix wins
+        let tools = tools_for_model("claude-3-haiku-20240307", &disabled, &profiles, &[]);
+        assert!(!tool_names(&tools).contains(&"web_fetch"));
+        assert!(tool_names(&tools).contains(&"grep"));
+
+        // A profile cannot re-enable a globally disabled tool
+        let tools = tools_for_model("claude-3-opus-20240229", &disabled, &profiles, &[]);
+        assert_eq!(tool_names(&tools), vec!["bash"]);
+    }
+
     #[test]
     fn test_tool_definition_new() {
         let tool = ToolDefinition::new(
@@ -509,6 +712,7 @@ mod tests {
         assert_eq!(schema["type"], "object");
         assert!(schema["properties"]["pattern"].is_object());
         assert!(schema["properties"]["respect_gitignore"].is_object());
+        assert!(schema["properties"]["git_tracked_only"].is_object());
         assert_eq!(schema["required"], json!(["pattern"]));
     }
 
@@ -523,6 +727,11 @@ mod tests {
         assert!(schema["properties"]["pattern"].is_object());
         assert!(schema["properties"]["file_pattern"].is_object());
         assert!(schema["properties"]["case_insensitive"].is_object());
+        assert!(schema["properties"]["before"].is_object());
+        assert!(schema["properties"]["after"].is_object());
+        assert!(schema["properties"]["files_with_matches"].is_object());
+        assert!(schema["properties"]["respect_gitignore"].is_object());
+        assert!(schema["properties"]["git_tracked_only"].is_object());
         assert_eq!(schema["required"], json!(["pattern"]));
     }
 
@@ -563,6 +772,31 @@ mod tests {
         assert!(json.contains(r#""name":"bash""#));
     }
 
+    #[test]
+    fn test_tool_choice_deserialization_round_trip() {
+        for choice in [ToolChoice::Auto, ToolChoice::Any, ToolChoice::tool("grep")] {
+            let json = serde_json::to_string(&choice).expect("serialization should succeed");
+            let parsed: ToolChoice =
+                serde_json::from_str(&json).expect("deserialization should succeed");
+            assert_eq!(parsed, choice);
+        }
+    }
+
+    #[test]
+    fn test_tool_choice_validate() {
+        let tools = default_tools();
+
+        assert!(ToolChoice::Auto.validate(None).is_ok());
+        assert!(ToolChoice::Any.validate(Some(&tools)).is_ok());
+        assert!(ToolChoice::Any.validate(None).is_err());
+        assert!(ToolChoice::tool("bash").validate(Some(&tools)).is_ok());
+
+        let err = ToolChoice::tool("run_tests")
+            .validate(Some(&tools))
+            .unwrap_err();
+        assert!(err.to_string().contains("run_tests"), "got: {err}");
+    }
+
     #[test]
     fn test_tool_choice_default_is_auto() {
         let choice = ToolChoice::default();
@@ -0,0 +1,604 @@
+//! Autonomous mode: keep working on a prompt until it is done.
+//!
+//! `patina --auto "<prompt>"` runs without anyone at the keyboard. Tool calls
+//! are approved automatically and their results sent back until Claude ends
+//! its turn. Three guardrails bound the run:
+//!
+//! - an iteration limit ([`DEFAULT_AUTO_MAX_ITERATIONS`], or the
+//!   `auto_max_iterations` setting), where each model response counts as one
+//!   iteration
+//! - a cost budget ([`DEFAULT_AUTO_BUDGET`] USD, or the `session_budget`
+//!   setting)
+//! - an allowlist ([`Allowlist`]): read-only tools, strictly read-only bash
+//!   commands, file writes inside the confinement root and the commands in
+//!   the `auto_allowed_commands` setting run without prompting; everything
+//!   else is denied
+//!
+//! The run stops at the first guardrail that trips and prints a summary of
+//! what it did and why it stopped.
+
+use anyhow::Result;
+use tokio::sync::mpsc;
+
+use super::state::AppState;
+use super::tool_loop::{ToolLoopState, TOOL_CANCELLED_MESSAGE};
+use super::{
+    format_tool_results_for_display, headless_client, headless_state, Config,
+    STREAMING_CHANNEL_BUFFER,
+};
+use crate::api::{AnthropicClient, StreamEvent, ToolChoice};
+use crate::permissions::allowlist::Allowlist;
+use crate::types::{Message, Role};
+
+/// Iteration limit used when `auto_max_iterations` is not set.
+pub const DEFAULT_AUTO_MAX_ITERATIONS: usize = 25;
+
+/// Cost budget in USD used when `session_budget` is not set.
+pub const DEFAULT_AUTO_BUDGET: f64 = 5.0;
+
+/// Returns the tool calls auto mode runs without prompting.
+///
+/// Besides read-only tools and commands, it admits `write_file` and `edit`
+/// inside the confinement root and bash commands starting with one of
+/// `commands`.
+#[must_use]
+pub fn auto_allowlist(state: &AppState, commands: &[String]) -> Allowlist {
+    Allowlist::new(&state.working_dir)
+        .with_write_root(state.confinement_root())
+        .with_commands(commands)
+}
+
+/// Why an autonomous run stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoStop {
+    /// Claude ended its turn without requesting more tools.
+    Done,
+    /// The iteration limit was reached.
+    IterationLimit(usize),
+    /// The cost budget was exceeded.
+    Budget,
+    /// A tool call was outside the auto-mode allowlist.
+    DeniedTool(String),
+    /// The API request or the tool loop failed.
+    Error(String),
+}
+
+impl AutoStop {
+    /// Returns true if the run finished on its own.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Done)
+    }
+
+    /// Returns a one-line description of why the run stopped.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Done => "Done.".to_string(),
+            Self::IterationLimit(limit) => {
+                format!("Stopped: reached the limit of {limit} iterations.")
+            }
+            Self::Budget => "Stopped: the cost budget is exceeded.".to_string(),
+            Self::DeniedTool(call) => {
+                format!("Stopped: `{call}` is denied in auto mode.")
+            }
+            Self::Error(e) => format!("Stopped: {e}"),
+        }
+    }
+}
+
+/// Outcome of an autonomous run.
+#[derive(Debug, Clone)]
+pub struct AutoReport {
+    /// Why the run stopped.
+    pub stop: AutoStop,
+    /// Number of tool rounds executed.
+    pub rounds: usize,
+    /// Number of tool calls executed.
+    pub tool_calls: usize,
+    /// Text of the last response from Claude.
+    pub response: String,
+    /// Estimated spend against the budget.
+    pub budget: String,
+}
+
+impl AutoReport {
+    /// Returns the work done and the spend, without the stop reason.
+    #[must_use]
+    pub fn stats(&self) -> String {
+        format!(
+            "{} tool rounds, {} tool calls. {}",
+            self.rounds, self.tool_calls, self.budget
+        )
+    }
+
+    /// Returns a summary of the run.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!("{}\n{}", self.stop.describe(), self.stats())
+    }
+}
+
+/// Applies the auto-mode guardrails to `state`.
+///
+/// Permission prompts are replaced by [`auto_allowlist`] with `commands`, so
+/// calls on it run without prompting and everything else is denied. A
+/// read-only permission mode still applies on top. Must be called before any
+/// tools run.
+///
+/// # Errors
+///
+/// Returns an error if the budget is not positive.
+pub fn apply_guardrails(
+    state: &mut AppState,
+    max_iterations: usize,
+    budget: f64,
+    commands: &[String],
+    model: &str,
+) -> Result<()> {
+    let allowlist = auto_allowlist(state, commands);
+    state.set_permission_allowlist(allowlist);
+    state.set_max_tool_iterations(max_iterations);
+    state.set_session_budget(Some(budget), model)
+}
+
+/// Runs `prompt` autonomously in auto mode and prints the outcome.
+///
+/// The final response goes to stdout and the summary to stderr.
+///
+/// # Errors
+///
+/// Returns an error if setup fails or a guardrail stopped the run.
+pub async fn run_auto_mode(config: &Config, prompt: &str) -> Result<()> {
+    let client = headless_client(config);
+    let mut state = headless_state(config, &client).await?;
+    apply_guardrails(
+        &mut state,
+        config
+            .settings
+            .auto_max_iterations
+            .unwrap_or(DEFAULT_AUTO_MAX_ITERATIONS),
+        config
+            .settings
+            .session_budget
+            .unwrap_or(DEFAULT_AUTO_BUDGET),
+        &config.settings.auto_allowed_commands,
+        &config.model,
+    )?;
+
+    let report = run_auto(&mut state, &client, prompt).await;
+    if !report.response.is_empty() {
+        println!("{}", report.response);
+    }
+    if report.stop.is_done() {
+        eprintln!("{}", report.summary());
+        Ok(())
+    } else {
+        eprintln!("{}", report.stats());
+        anyhow::bail!("{}", report.stop.describe())
+    }
+}
+
+/// Sends `prompt` and keeps executing tools until done or a guardrail trips.
+///
+/// Guardrails must already be applied with [`apply_guardrails`].
+pub async fn run_auto(state: &mut AppState, client: &AnthropicClient, prompt: &str) -> AutoReport {
+    let mut report = AutoReport {
+        stop: AutoStop::Done,
+        rounds: 0,
+        tool_calls: 0,
+        response: String::new(),
+        budget: String::new(),
+    };
+    report.stop = match drive(state, client, prompt, &mut report).await {
+        Ok(stop) => stop,
+        Err(e) => AutoStop::Error(e.to_string()),
+    };
+    report.budget = state.budget_status();
+    report
+}
+
+/// Runs the tool loop for `prompt`, returning why it stopped.
+async fn drive(
+    state: &mut AppState,
+    client: &AnthropicClient,
+    prompt: &str,
+    report: &mut AutoReport,
+) -> Result<AutoStop> {
+    // UserPromptSubmit hooks may rewrite the prompt or block it
+    let prompt = state
+        .run_prompt_hooks(prompt)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    state.submit_message(client, prompt).await?;
+
+    loop {
+        report.response = receive_response(state).await?;
+
+        match state.tool_loop_state() {
+            ToolLoopState::PendingApproval => {}
+            ToolLoopState::Error(e) => return Ok(AutoStop::Error(e.clone())),
+            _ => return Ok(AutoStop::Done),
+        }
+        if state.budget_paused() {
+            return Ok(AutoStop::Budget);
+        }
+        if state.tool_loop().is_at_limit() {
+            return Ok(AutoStop::IterationLimit(state.tool_loop().max_iterations()));
+        }
+
+        state.approve_all_tools()?;
+        let needs_permission = state.execute_pending_tools().await?;
+        report.rounds += 1;
+        report.tool_calls += state
+            .tool_loop()
+            .pending_calls()
+            .values()
+            .filter(|call| call.result.is_some())
+            .count();
+
+        if let Some(call) = denied_call(state) {
+            return Ok(AutoStop::DeniedTool(call));
+        }
+        if !needs_permission.is_empty() {
+            anyhow::bail!("tools need permission: {}", needs_permission.join(", "));
+        }
+
+        continue_with_tool_results(state, client)?;
+    }
+}
+
+/// Feeds the current response into `state` and returns its text.
+async fn receive_response(state: &mut AppState) -> Result<String> {
+    let mut text = String::new();
+    while let Some(event) = state.recv_api_chunk().await {
+        match &event {
+            StreamEvent::ContentDelta(delta) => text.push_str(delta),
+            StreamEvent::Error(e) => anyhow::bail!("API error: {e}"),
+            _ => {}
+        }
+        state.append_chunk(event)?;
+    }
+    Ok(text)
+}
+
+/// Returns the first tool call of this round that permissions denied.
+fn denied_call(state: &AppState) -> Option<String> {
+    state
+        .tool_loop()
+        .pending_calls()
+        .values()
+        .find(|call| {
+            call.result
+                .as_ref()
+                .is_some_and(|r| r.is_error && r.content == TOOL_CANCELLED_MESSAGE)
+        })
+        .map(|call| {
+            let input = &call.tool_use.input;
+            match input.get("command").and_then(|v| v.as_str()) {
+                Some(command) => command.to_string(),
+                None => format!("{} {}", call.tool_use.name, input),
+            }
+        })
+}
+
+/// Sends this round's tool results and starts streaming the next response.
+fn continue_with_tool_results(state: &mut AppState, client: &AnthropicClient) -> Result<()> {
+    let continuation = state.finish_tool_execution()?;
+    let (assistant_msg, user_msg) = continuation.build_messages();
+
+    state.api_messages_mut().push(assistant_msg);
+    state.add_message(Message {
+        role: Role::User,
+        content: format_tool_results_for_display(&user_msg),
+    });
+    state.api_messages_mut().push(user_msg);
+
+    state.tool_loop_mut().start_streaming()?;
+    let (tx, rx) = mpsc::channel(STREAMING_CHANNEL_BUFFER);
+    state.set_streaming_rx(rx);
+    state.set_loading(true);
+    state.set_current_response(String::new());
+
+    state.maybe_auto_compact();
+    state.check_context_budget();
+    let api_messages = state.api_messages().to_vec();
+    let client = state.request_client(client);
+    let tools = state.tools();
+
+    tokio::spawn(async move {
+        if let Err(e) = client
+            .stream_message_v2_with_tools(&api_messages, Some(&tools), Some(&ToolChoice::Auto), tx)
+            .await
+        {
+            tracing::error!("API error during tool continuation: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{PermissionDecision, PermissionManager};
+    use crate::types::config::{ParallelMode, PermissionMode};
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const MODEL: &str = "claude-sonnet-4-20250514";
+
+    fn sse(events: &[Value]) -> String {
+        events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {}\n\n",
+                    event["type"].as_str().unwrap(),
+                    event
+                )
+            })
+            .collect()
+    }
+
+    fn tool_round(id: &str, command: &str, input_tokens: u32) -> String {
+        tool_call_round(id, "bash", &json!({"command": command}), input_tokens)
+    }
+
+    fn tool_call_round(id: &str, name: &str, input: &Value, input_tokens: u32) -> String {
+        sse(&[
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": input_tokens}}}),
+            json!({"type": "content_block_start", "index": 0,
+                   "content_block": {"type": "tool_use", "id": id, "name": name}}),
+            json!({"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "input_json_delta",
+                             "partial_json": input.to_string()}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"},
+                   "usage": {"output_tokens": 10}}),
+            json!({"type": "message_stop"}),
+        ])
+    }
+
+    fn final_answer(text: &str) -> String {
+        sse(&[
+            json!({"type": "content_block_start", "index": 0,
+                   "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "text_delta", "text": text}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}}),
+            json!({"type": "message_stop"}),
+        ])
+    }
+
+    /// Mounts responses that are served once each, in order.
+    async fn mount_in_order(server: &MockServer, responses: &[String]) {
+        for body in responses {
+            Mock::given(method("POST"))
+                .and(path("/v1/messages"))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_raw(body.clone(), "text/event-stream"),
+                )
+                .up_to_n_times(1)
+                .mount(server)
+                .await;
+        }
+    }
+
+    fn setup(
+        server: &MockServer,
+        temp_dir: &TempDir,
+        max_iterations: usize,
+        budget: f64,
+    ) -> (AppState, AnthropicClient) {
+        let client = AnthropicClient::new_with_base_url(
+            secrecy::SecretString::from("key"),
+            MODEL,
+            &server.uri(),
+        );
+        let mut state = AppState::new(temp_dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        apply_guardrails(&mut state, max_iterations, budget, &[], MODEL).unwrap();
+        (state, client)
+    }
+
+    #[test]
+    fn test_guardrails_allow_only_the_auto_allowlist() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut state = AppState::new(temp_dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        state.set_permission_mode(PermissionMode::FullAccess, None);
+        let commands = vec!["cargo test".to_string()];
+
+        apply_guardrails(&mut state, 10, 5.0, &commands, MODEL).unwrap();
+
+        let mut manager = PermissionManager::new();
+        manager.set_mode(state.permission_mode());
+        manager.set_allowlist(Some(auto_allowlist(&state, &commands)));
+        for command in [
+            "rm -rf target",
+            "cd repo && git push origin main",
+            "sudo ls",
+            "python3 build.py",
+            "./deploy.sh",
+            "env rm -rf ~",
+            "command rm -rf target",
+            "find . -delete",
+            "find . -exec rm {} +",
+            "awk 'BEGIN{system(\"rm -rf ~\")}'",
+            "git -c core.pager=less log",
+            "git branch -D main",
+            "git config user.name x",
+            "git stash drop",
+            "git remote add origin https://example.com/x.git",
+            "cargo build",
+            "env cargo test",
+        ] {
+            assert_eq!(
+                manager.check("bash", Some(command)),
+                PermissionDecision::Denied,
+                "{command}"
+            );
+        }
+        for command in ["git status", "ls -la", "cargo test", "cargo test --lib"] {
+            assert_eq!(
+                manager.check("bash", Some(command)),
+                PermissionDecision::Allowed,
+                "{command}"
+            );
+        }
+        for (tool, path, decision) in [
+            ("read_file", "src/lib.rs", PermissionDecision::Allowed),
+            ("write_file", "src/lib.rs", PermissionDecision::Allowed),
+            ("edit", "src/lib.rs", PermissionDecision::Allowed),
+            ("write_file", "../outside.txt", PermissionDecision::Denied),
+            ("edit", "/etc/passwd", PermissionDecision::Denied),
+            ("mcp__server__tool", "{}", PermissionDecision::Denied),
+        ] {
+            assert_eq!(manager.check(tool, Some(path)), decision, "{tool} {path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_edits_files_inside_the_project() {
+        let server = MockServer::start().await;
+        mount_in_order(
+            &server,
+            &[
+                tool_call_round(
+                    "toolu_1",
+                    "write_file",
+                    &json!({"path": "notes.txt", "content": "draft\n"}),
+                    100,
+                ),
+                tool_call_round(
+                    "toolu_2",
+                    "edit",
+                    &json!({"path": "notes.txt", "old_string": "draft", "new_string": "final"}),
+                    100,
+                ),
+                final_answer("Notes written."),
+            ],
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir, 10, 5.0);
+
+        let report = run_auto(&mut state, &client, "write the notes").await;
+
+        assert_eq!(report.stop, AutoStop::Done);
+        assert_eq!(report.rounds, 2);
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("notes.txt")).unwrap(),
+            "final\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_runs_tool_rounds_until_done() {
+        let server = MockServer::start().await;
+        mount_in_order(
+            &server,
+            &[
+                tool_round("toolu_1", "echo one", 100),
+                tool_round("toolu_2", "echo two", 100),
+                final_answer("Both commands ran."),
+            ],
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir, 10, 5.0);
+
+        let report = run_auto(&mut state, &client, "run two commands").await;
+
+        assert_eq!(report.stop, AutoStop::Done);
+        assert_eq!(report.rounds, 2);
+        assert_eq!(report.tool_calls, 2);
+        assert_eq!(report.response, "Both commands ran.");
+        // prompt, then an assistant tool_use and user tool_result per round, then the answer
+        assert_eq!(state.api_messages().len(), 6);
+        assert!(report.summary().starts_with("Done."));
+    }
+
+    #[tokio::test]
+    async fn test_auto_stops_at_iteration_limit() {
+        let server = MockServer::start().await;
+        mount_in_order(
+            &server,
+            &[
+                tool_round("toolu_1", "echo one", 100),
+                tool_round("toolu_2", "echo two", 100),
+                tool_round("toolu_3", "echo three", 100),
+            ],
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir, 3, 5.0);
+
+        let report = run_auto(&mut state, &client, "keep going").await;
+
+        assert_eq!(report.stop, AutoStop::IterationLimit(3));
+        assert_eq!(report.rounds, 2);
+    }
+
+    #[tokio::test]
+    async fn test_auto_stops_when_budget_exceeded() {
+        let server = MockServer::start().await;
+        mount_in_order(&server, &[tool_round("toolu_1", "echo one", 1_000_000)]).await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir, 10, 0.01);
+
+        let report = run_auto(&mut state, &client, "expensive").await;
+
+        assert_eq!(report.stop, AutoStop::Budget);
+        assert_eq!(report.rounds, 0);
+    }
+
+    #[tokio::test]
+    async fn test_auto_stops_when_prompt_hook_blocks() {
+        let server = MockServer::start().await;
+        let temp_dir = TempDir::new().unwrap();
+        let hooks = temp_dir.path().join("hooks.toml");
+        std::fs::write(
+            &hooks,
+            "[[UserPromptSubmit]]\nhooks = [{ type = \"command\", command = \"echo no secrets; exit 2\" }]\n",
+        )
+        .unwrap();
+        let (mut state, client) = setup(&server, &temp_dir, 10, 5.0);
+        state.load_hooks(Some(&hooks), false);
+
+        let report = run_auto(&mut state, &client, "print the API key").await;
+
+        assert_eq!(
+            report.stop,
+            AutoStop::Error("Not sent: no secrets".to_string())
+        );
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auto_stops_on_denied_tool() {
+        let server = MockServer::start().await;
+        mount_in_order(
+            &server,
+            &[
+                tool_round("toolu_1", "echo safe", 100),
+                tool_round("toolu_2", "git push origin main", 100),
+                final_answer("Pushed."),
+            ],
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir, 10, 5.0);
+
+        let report = run_auto(&mut state, &client, "ship it").await;
+
+        assert_eq!(
+            report.stop,
+            AutoStop::DeniedTool("git push origin main".to_string())
+        );
+        assert_eq!(report.rounds, 2);
+        assert!(report.response.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+//! Per-session cost budget for the interactive loop.
+//!
+//! Estimated spend is accumulated from the usage reported with each response,
+//! priced per model by [`CostTracker`]. Crossing the warning threshold yields a
+//! single warning; exceeding the budget pauses the loop until the user
+//! confirms with `/budget continue`.
+
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::enterprise::cost::{
+    BudgetLimit, BudgetPeriod, CostAlert, CostConfig, CostTracker, UsageRecord,
+};
+
+/// A notice raised by recording usage against the budget.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetNotice {
+    /// Spend crossed the warning threshold.
+    Warning(String),
+    /// Spend exceeded the budget; the loop is paused pending confirmation.
+    Exceeded(String),
+}
+
+/// Tracks estimated spend against a per-session dollar budget.
+#[derive(Debug)]
+pub struct SessionBudget {
+    tracker: CostTracker,
+    limit: f64,
+    model: String,
+    warned: bool,
+    exceeded_notified: bool,
+    confirmed: bool,
+}
+
+impl SessionBudget {
+    /// Creates a budget of `limit` USD, pricing usage for `model`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `limit` is not positive.
+    pub fn new(limit: f64, model: impl Into<String>) -> Result<Self> {
+        let config = CostConfig {
+            enabled: true,
+            session_limit: Some(BudgetLimit::new(BudgetPeriod::Session, limit)),
+            ..CostConfig::default()
+        };
+        config.validate()?;
+
+        Ok(Self {
+            tracker: CostTracker::new(config),
+            limit,
+            model: model.into(),
+            warned: false,
+            exceeded_notified: false,
+            confirmed: false,
+        })
+    }
+
+    /// Records the usage of one response and returns any new notice.
+    ///
+    /// The warning is raised once; exceeding the budget is reported once and
+    /// not again after the user confirms.
+    pub fn record(&mut self, input_tokens: u32, output_tokens: u32) -> Option<BudgetNotice> {
+        self.tracker.record_usage(UsageRecord::new(
+            &self.model,
+            input_tokens,
+            output_tokens,
+            Duration::ZERO,
+        ));
+
+        let spent = self.spent();
+        match self.tracker.check_alerts().into_iter().next()? {
+            CostAlert::LimitExceeded { .. } if !self.exceeded_notified => {
+                self.exceeded_notified = true;
+                self.warned = true;
+                Some(BudgetNotice::Exceeded(format!(
+                    "Session budget of ${:.2} exceeded (${:.2} spent). Paused; type /budget continue to keep going.",
+                    self.limit, spent
+                )))
+            }
+            CostAlert::ApproachingLimit {
+                usage_percentage,
+                remaining,
+                ..
+            } if !self.warned => {
+                self.warned = true;
+                Some(BudgetNotice::Warning(format!(
+                    "Warning: {:.0}% of the ${:.2} session budget used (${:.2} remaining).",
+                    usage_percentage * 100.0,
+                    self.limit,
+                    remaining
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the budget is exceeded and continuing is unconfirmed.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        !self.confirmed && self.tracker.is_budget_exceeded()
+    }
+
+    /// Prices usage recorded from now on for `model`.
+    ///
+    /// Spend already recorded keeps the rates of the model that produced it.
+    pub fn set_model(&mut self, model: impl Into<String>) {
+        self.model = model.into();
+    }
+
+    /// Confirms spending past the budget for the rest of the session.
+    pub fn confirm(&mut self) {
+        self.confirmed = true;
+    }
+
+    /// Returns the estimated spend so far in USD.
+    #[must_use]
+    pub fn spent(&self) -> f64 {
+        self.tracker.session_cost()
+    }
+
+    /// Returns the budget in USD.
+    #[must_use]
+    pub fn limit(&self) -> f64 {
+        self.limit
+    }
+
+    /// Returns a one-line summary of spend against the budget.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let status = if self.is_paused() {
+            " (paused; type /budget continue to keep going)"
+        } else if self.confirmed {
+            " (continuing past budget)"
+        } else {
+            ""
+        };
+        format!(
+            "Estimated spend: ${:.2} of ${:.2} session budget{}",
+            self.spent(),
+            self.limit,
+            status
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Claude 3 Opus: $15/1M input tokens, so 10k input tokens cost $0.15
+    const MODEL: &str = "claude-3-opus";
+
+    #[test]
+    fn test_rejects_non_positive_budget() {
+        assert!(SessionBudget::new(0.0, MODEL).is_err());
+    }
+
+    #[test]
+    fn test_warns_once_when_crossing_threshold() {
+        let mut budget = SessionBudget::new(1.0, MODEL).unwrap();
+
+        assert_eq!(budget.record(50_000, 0), None);
+        let notice = budget.record(6_000, 0);
+        assert!(
+            matches!(&notice, Some(BudgetNotice::Warning(msg)) if msg.contains("84%")),
+            "got {notice:?}"
+        );
+        assert_eq!(budget.record(1_000, 0), None);
+        assert!(!budget.is_paused());
+    }
+
+    #[test]
+    fn test_exceeding_budget_pauses_until_confirmed() {
+        let mut budget = SessionBudget::new(1.0, MODEL).unwrap();
+
+        let notice = budget.record(70_000, 0);
+        assert!(
+            matches!(&notice, Some(BudgetNotice::Exceeded(msg)) if msg.contains("exceeded")),
+            "got {notice:?}"
+        );
+        assert!(budget.is_paused());
+
+        budget.confirm();
+        assert!(!budget.is_paused());
+        assert_eq!(budget.record(10_000, 0), None);
+        assert!(!budget.is_paused());
+        assert!((budget.spent() - 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_set_model_prices_later_usage_at_new_rates() {
+        let mut budget = SessionBudget::new(10.0, "claude-3-haiku").unwrap();
+
+        // Haiku: $0.25/1M input tokens
+        budget.record(100_000, 0);
+        assert!((budget.spent() - 0.025).abs() < 1e-9);
+
+        budget.set_model(MODEL);
+        budget.record(100_000, 0);
+        assert!((budget.spent() - 1.525).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,177 @@
+//! Coalescing of streamed text deltas before they are rendered.
+//!
+//! Redrawing on every tiny delta causes flicker on some terminals. Deltas are
+//! buffered until the flush window elapses or enough characters accumulate,
+//! then appended as one chunk. Callers flush before any other stream event so
+//! ordering is preserved and the final text is never held back.
+
+use std::time::{Duration, Instant};
+
+use crate::types::settings::StreamFlush;
+
+/// Buffers content deltas and releases them in batches.
+#[derive(Debug)]
+pub struct DeltaCoalescer {
+    window: Duration,
+    max_chars: usize,
+    pending: String,
+    pending_chars: usize,
+    started: Option<Instant>,
+}
+
+impl DeltaCoalescer {
+    /// Creates a coalescer that holds deltas for up to `window` or `max_chars`.
+    ///
+    /// A zero window or a `max_chars` of zero disables coalescing.
+    #[must_use]
+    pub fn new(window: Duration, max_chars: usize) -> Self {
+        Self {
+            window,
+            max_chars,
+            pending: String::new(),
+            pending_chars: 0,
+            started: None,
+        }
+    }
+
+    /// Creates a coalescer from the `stream_flush` settings.
+    #[must_use]
+    pub fn from_settings(settings: &StreamFlush) -> Self {
+        Self::new(
+            Duration::from_millis(settings.interval_ms),
+            settings.max_chars,
+        )
+    }
+
+    /// Buffers a delta, returning the batch if it is due to be rendered.
+    pub fn push(&mut self, text: &str, now: Instant) -> Option<String> {
+        if text.is_empty() {
+            return None;
+        }
+        self.pending.push_str(text);
+        self.pending_chars += text.chars().count();
+        let started = *self.started.get_or_insert(now);
+
+        if self.window.is_zero()
+            || self.pending_chars >= self.max_chars
+            || now.duration_since(started) >= self.window
+        {
+            return self.flush();
+        }
+        None
+    }
+
+    /// Returns when the buffered text must be flushed, if any is buffered.
+    #[must_use]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started.map(|started| started + self.window)
+    }
+
+    /// Returns true if text is buffered.
+    #[must_use]
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Takes all buffered text.
+    pub fn flush(&mut self) -> Option<String> {
+        self.started = None;
+        self.pending_chars = 0;
+        if self.pending.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.pending))
+    }
+
+    /// Discards buffered text, e.g. after the response was cancelled.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.pending_chars = 0;
+        self.started = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batches_deltas_within_window() {
+        let mut coalescer = DeltaCoalescer::new(Duration::from_millis(30), 1000);
+        let start = Instant::now();
+
+        assert_eq!(coalescer.push("Hel", start), None);
+        assert_eq!(
+            coalescer.push("lo, ", start + Duration::from_millis(10)),
+            None
+        );
+        assert_eq!(
+            coalescer.push("world", start + Duration::from_millis(30)),
+            Some("Hello, world".to_string())
+        );
+        assert!(!coalescer.has_pending());
+        assert_eq!(coalescer.deadline(), None);
+    }
+
+    #[test]
+    fn test_flushes_at_max_chars() {
+        let mut coalescer = DeltaCoalescer::new(Duration::from_secs(60), 5);
+        let now = Instant::now();
+
+        assert_eq!(coalescer.push("ab", now), None);
+        assert_eq!(coalescer.push("cdé", now), Some("abcdé".to_string()));
+        assert_eq!(coalescer.push("f", now), None);
+    }
+
+    #[test]
+    fn test_final_flush_loses_no_characters() {
+        let mut coalescer = DeltaCoalescer::new(Duration::from_millis(30), 8);
+        let now = Instant::now();
+        let deltas = ["The ", "quick ", "brown ", "fox ", "jumps", "."];
+
+        let mut rendered = String::new();
+        let mut renders = 0;
+        for delta in deltas {
+            if let Some(batch) = coalescer.push(delta, now) {
+                rendered.push_str(&batch);
+                renders += 1;
+            }
+        }
+        if let Some(batch) = coalescer.flush() {
+            rendered.push_str(&batch);
+            renders += 1;
+        }
+
+        assert_eq!(rendered, deltas.concat());
+        assert!(renders < deltas.len());
+        assert_eq!(coalescer.flush(), None);
+    }
+
+    #[test]
+    fn test_deadline_tracks_first_buffered_delta() {
+        let mut coalescer = DeltaCoalescer::new(Duration::from_millis(30), 1000);
+        let start = Instant::now();
+        assert_eq!(coalescer.deadline(), None);
+
+        coalescer.push("a", start);
+        coalescer.push("b", start + Duration::from_millis(20));
+        assert_eq!(
+            coalescer.deadline(),
+            Some(start + Duration::from_millis(30))
+        );
+
+        coalescer.clear();
+        assert!(!coalescer.has_pending());
+        assert_eq!(coalescer.deadline(), None);
+    }
+
+    #[test]
+    fn test_zero_window_disables_coalescing() {
+        let mut coalescer = DeltaCoalescer::new(Duration::ZERO, 256);
+        let now = Instant::now();
+
+        assert_eq!(coalescer.push("a", now), Some("a".to_string()));
+        assert_eq!(coalescer.push("", now), None);
+        assert!(!coalescer.has_pending());
+    }
+}
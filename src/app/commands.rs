@@ -19,10 +19,15 @@
 //!     CommandResult::NotACommand => println!("Not a slash command"),
 //!     CommandResult::UnknownCommand(cmd) => println!("Unknown: {}", cmd),
 //!     CommandResult::Error(e) => println!("Error: {}", e),
+//!     CommandResult::Action(action) => println!("Action: {:?}", action),
 //! }
 //! ```
 
+use crate::api::SummaryStyle;
 use crate::commands::worktree::{parse_worktree_command, WorktreeCommand};
+use crate::permissions::PermissionRule;
+use crate::session::validate_session_name;
+use crate::types::config::ThinkingDisplay;
 use crate::worktree::{WorktreeInfo, WorktreeManager};
 use std::path::PathBuf;
 
@@ -55,6 +60,115 @@ pub enum CommandResult {
 
     /// An error occurred while executing the command.
     Error(String),
+
+    /// The command requests a change to application state.
+    ///
+    /// The handler does not own session state, so commands that modify it
+    /// return an action for the caller to apply.
+    Action(CommandAction),
+}
+
+/// A state change requested by a slash command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandAction {
+    /// Add tags to the current session.
+    AddTags(Vec<String>),
+
+    /// Remove tags from the current session.
+    RemoveTags(Vec<String>),
+
+    /// Show the current session's tags.
+    ListTags,
+
+    /// Name the current session so it can be resumed by name.
+    NameSession(String),
+
+    /// Add a persistent rule that allows matching tool calls without a prompt.
+    AllowRule(PermissionRule),
+
+    /// Show the persistent allow rules.
+    ListAllowRules,
+
+    /// Apply a named request parameter preset for subsequent turns.
+    ApplyPreset(String),
+
+    /// Return to default request parameters.
+    ClearPreset,
+
+    /// Show the available presets and the active one.
+    ListPresets,
+
+    /// Require a tool call on the next turn: `any` tool or the named one.
+    ForceTool(String),
+
+    /// Stop forcing a tool call on the next turn.
+    ClearForcedTool,
+
+    /// Show the tool forced for the next turn, if any.
+    ShowForcedTool,
+
+    /// Change how model reasoning is displayed.
+    SetThinkingDisplay(ThinkingDisplay),
+
+    /// Show how model reasoning is displayed.
+    ShowThinkingDisplay,
+
+    /// Change how history is summarized at the next compaction.
+    SetSummaryStyle(SummaryStyle),
+
+    /// Show how history is summarized when compacted.
+    ShowSummaryStyle,
+
+    /// Summarize earlier history now instead of waiting for auto-compaction.
+    CompactNow,
+
+    /// Start a fresh conversation in the current session.
+    ClearConversation {
+        /// Keep the session's context file list.
+        keep_context: bool,
+    },
+
+    /// Switch the model used for subsequent requests.
+    SetModel(String),
+
+    /// Show the current model and the tools advertised to it.
+    ShowModel,
+
+    /// Show estimated context usage for the current conversation.
+    ShowTokens,
+
+    /// Show estimated spend against the session budget.
+    ShowBudget,
+
+    /// Continue past an exceeded session budget.
+    ConfirmBudget,
+
+    /// Reset a stuck tool loop to idle.
+    ResetToolLoop,
+
+    /// Ask the model to explain the most recent error.
+    ExplainLastError,
+
+    /// Show the available skills and which are active.
+    ListSkills,
+
+    /// Turn a skill on for this session.
+    ActivateSkill(String),
+
+    /// Turn a skill off and stop it from activating automatically.
+    DeactivateSkill(String),
+
+    /// Move the session into a git worktree.
+    SwitchWorktree {
+        /// Worktree name as shown by `/worktree list`.
+        name: String,
+        /// Directory the worktree is checked out in.
+        path: PathBuf,
+        /// Branch checked out in the worktree.
+        branch: String,
+        /// Branch the worktree was created from; `None` for the main worktree.
+        original_branch: Option<String>,
+    },
 }
 
 /// Handler for slash commands in the TUI.
@@ -125,10 +239,218 @@ impl SlashCommandHandler {
             "help" => self.handle_help(if args.is_empty() { None } else { Some(&args) }),
             "plugins" => self.handle_plugins(),
             "terminal-setup" => self.handle_terminal_setup(),
+            "tag" => Self::handle_tag(&args),
+            "save" => Self::handle_save(&args),
+            "allow" => Self::handle_allow(&args),
+            "preset" => Self::handle_preset(&args),
+            "force-tool" => Self::handle_force_tool(&args),
+            "thinking" => Self::handle_thinking(&args),
+            "compact" => Self::handle_compact(&args),
+            "clear" => Self::handle_clear(&args),
+            "tokens" => Self::handle_tokens(&args),
+            "budget" => Self::handle_budget(&args),
+            "model" => Self::handle_model(&args),
+            "reset-loop" => Self::handle_reset_loop(&args),
+            "explain" => Self::handle_explain(&args),
+            "skill" => Self::handle_skill(&args),
             _ => CommandResult::UnknownCommand(command_name.to_string()),
         }
     }
 
+    /// Handles the `/save` command.
+    fn handle_save(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some(name), None) => match validate_session_name(name) {
+                Ok(()) => CommandResult::Action(CommandAction::NameSession(name.to_string())),
+                Err(e) => CommandResult::Error(e.to_string()),
+            },
+            _ => CommandResult::Error("Usage: /save <name>".to_string()),
+        }
+    }
+
+    /// Handles the `/allow` command.
+    fn handle_allow(args: &str) -> CommandResult {
+        if args.is_empty() {
+            return CommandResult::Action(CommandAction::ListAllowRules);
+        }
+        match PermissionRule::parse(args, true) {
+            Ok(rule) => CommandResult::Action(CommandAction::AllowRule(rule)),
+            Err(e) => CommandResult::Error(format!("{e}. Usage: /allow <tool>[:<pattern>]")),
+        }
+    }
+
+    /// Handles the `/preset` command.
+    fn handle_preset(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) => CommandResult::Action(CommandAction::ListPresets),
+            (Some("--clear"), None) => CommandResult::Action(CommandAction::ClearPreset),
+            (Some(name), None) => {
+                CommandResult::Action(CommandAction::ApplyPreset(name.to_string()))
+            }
+            (Some(_), Some(_)) => {
+                CommandResult::Error("Usage: /preset [<name> | --clear]".to_string())
+            }
+        }
+    }
+
+    /// Handles the `/force-tool` command.
+    fn handle_force_tool(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) => CommandResult::Action(CommandAction::ShowForcedTool),
+            (Some("--clear"), None) => CommandResult::Action(CommandAction::ClearForcedTool),
+            (Some(name), None) => CommandResult::Action(CommandAction::ForceTool(name.to_string())),
+            (Some(_), Some(_)) => {
+                CommandResult::Error("Usage: /force-tool [<name> | any | --clear]".to_string())
+            }
+        }
+    }
+
+    /// Handles the `/thinking` command.
+    fn handle_thinking(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) => CommandResult::Action(CommandAction::ShowThinkingDisplay),
+            (Some(mode), None) => match mode.parse::<ThinkingDisplay>() {
+                Ok(mode) => CommandResult::Action(CommandAction::SetThinkingDisplay(mode)),
+                Err(e) => CommandResult::Error(e.to_string()),
+            },
+            (Some(_), Some(_)) => {
+                CommandResult::Error("Usage: /thinking [hidden | collapsed | shown]".to_string())
+            }
+        }
+    }
+
+    /// Handles the `/compact` command.
+    fn handle_compact(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) => CommandResult::Action(CommandAction::CompactNow),
+            (Some("style"), None) => CommandResult::Action(CommandAction::ShowSummaryStyle),
+            (Some(style), None) => match style.parse::<SummaryStyle>() {
+                Ok(style) => CommandResult::Action(CommandAction::SetSummaryStyle(style)),
+                Err(e) => CommandResult::Error(e.to_string()),
+            },
+            (Some(_), Some(_)) => CommandResult::Error(
+                "Usage: /compact [bullet | paragraph | detailed | style]".to_string(),
+            ),
+        }
+    }
+
+    /// Handles the `/model` command.
+    fn handle_model(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) => CommandResult::Action(CommandAction::ShowModel),
+            (Some(model), None) => {
+                CommandResult::Action(CommandAction::SetModel(model.to_string()))
+            }
+            (Some(_), Some(_)) => CommandResult::Error("Usage: /model [<name>]".to_string()),
+        }
+    }
+
+    /// Handles the `/budget` command.
+    fn handle_budget(args: &str) -> CommandResult {
+        match args {
+            "" => CommandResult::Action(CommandAction::ShowBudget),
+            "continue" => CommandResult::Action(CommandAction::ConfirmBudget),
+            _ => CommandResult::Error("Usage: /budget [continue]".to_string()),
+        }
+    }
+
+    /// Handles the `/clear` command.
+    fn handle_clear(args: &str) -> CommandResult {
+        match args {
+            "" => CommandResult::Action(CommandAction::ClearConversation {
+                keep_context: false,
+            }),
+            "keep-context" => {
+                CommandResult::Action(CommandAction::ClearConversation { keep_context: true })
+            }
+            _ => CommandResult::Error("Usage: /clear [keep-context]".to_string()),
+        }
+    }
+
+    /// Handles the `/tokens` command.
+    fn handle_tokens(args: &str) -> CommandResult {
+        if args.is_empty() {
+            CommandResult::Action(CommandAction::ShowTokens)
+        } else {
+            CommandResult::Error("Usage: /tokens".to_string())
+        }
+    }
+
+    /// Handles the `/reset-loop` command.
+    fn handle_reset_loop(args: &str) -> CommandResult {
+        if args.is_empty() {
+            CommandResult::Action(CommandAction::ResetToolLoop)
+        } else {
+            CommandResult::Error("Usage: /reset-loop".to_string())
+        }
+    }
+
+    /// Handles the `/explain` command.
+    fn handle_explain(args: &str) -> CommandResult {
+        if args.is_empty() {
+            CommandResult::Action(CommandAction::ExplainLastError)
+        } else {
+            CommandResult::Error("Usage: /explain".to_string())
+        }
+    }
+
+    /// Handles the `/skill` command.
+    fn handle_skill(args: &str) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => CommandResult::Action(CommandAction::ListSkills),
+            (Some("on"), Some(name), None) => {
+                CommandResult::Action(CommandAction::ActivateSkill(name.to_string()))
+            }
+            (Some("off"), Some(name), None) => {
+                CommandResult::Action(CommandAction::DeactivateSkill(name.to_string()))
+            }
+            _ => CommandResult::Error("Usage: /skill [on <name> | off <name>]".to_string()),
+        }
+    }
+
+    /// Handles the `/tag` command.
+    ///
+    /// `/tag <name>...` adds tags, `/tag -<name>...` removes them, and a bare
+    /// `/tag` lists the current tags.
+    fn handle_tag(args: &str) -> CommandResult {
+        if args.is_empty() {
+            return CommandResult::Action(CommandAction::ListTags);
+        }
+
+        let (remove, add): (Vec<&str>, Vec<&str>) =
+            args.split_whitespace().partition(|t| t.starts_with('-'));
+
+        if !remove.is_empty() && !add.is_empty() {
+            return CommandResult::Error(
+                "Cannot add and remove tags in the same command".to_string(),
+            );
+        }
+
+        if add.is_empty() {
+            let tags: Vec<String> = remove
+                .iter()
+                .map(|t| t.trim_start_matches('-'))
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect();
+            if tags.is_empty() {
+                return CommandResult::Error("missing tag name".to_string());
+            }
+            return CommandResult::Action(CommandAction::RemoveTags(tags));
+        }
+
+        CommandResult::Action(CommandAction::AddTags(
+            add.into_iter().map(String::from).collect(),
+        ))
+    }
+
     /// Handles the `/plugins` command.
     fn handle_plugins(&self) -> CommandResult {
         if self.plugins.is_empty() {
@@ -210,14 +532,27 @@ impl SlashCommandHandler {
         };
 
         match worktree_cmd {
-            WorktreeCommand::New { name } => match manager.create(&name) {
-                Ok(info) => CommandResult::Executed(format!(
-                    "Created worktree '{}' at {}",
-                    name,
-                    info.path.display()
-                )),
-                Err(e) => CommandResult::Error(format!("Failed to create worktree: {}", e)),
-            },
+            WorktreeCommand::New { name } => {
+                // Changes in the current tree would not follow us into the new one
+                let original_branch = match manager
+                    .ensure_clean(&self.working_dir)
+                    .and_then(|()| manager.current_branch(&self.working_dir))
+                {
+                    Ok(branch) => branch,
+                    Err(e) => {
+                        return CommandResult::Error(format!("Failed to create worktree: {}", e))
+                    }
+                };
+                match manager.create(&name) {
+                    Ok(info) => CommandResult::Action(CommandAction::SwitchWorktree {
+                        name: info.name,
+                        path: info.path,
+                        branch: info.branch,
+                        original_branch: Some(original_branch),
+                    }),
+                    Err(e) => CommandResult::Error(format!("Failed to create worktree: {}", e)),
+                }
+            }
 
             WorktreeCommand::List => match manager.list() {
                 Ok(worktrees) if worktrees.is_empty() => {
@@ -235,12 +570,32 @@ impl SlashCommandHandler {
             },
 
             WorktreeCommand::Switch { name } => {
-                // Switch is not directly applicable in TUI context
-                // Just report what would happen
-                CommandResult::Executed(format!(
-                    "To switch to worktree '{}', open a new terminal in that directory.",
-                    name
-                ))
+                let worktrees = match manager.list() {
+                    Ok(worktrees) => worktrees,
+                    Err(e) => {
+                        return CommandResult::Error(format!("Failed to list worktrees: {}", e))
+                    }
+                };
+                let Some(target) = worktrees.iter().find(|w| w.name == name) else {
+                    return CommandResult::Error(format!(
+                        "No worktree named '{}'. See /worktree list.",
+                        name
+                    ));
+                };
+                let original_branch = if target.is_main {
+                    None
+                } else {
+                    worktrees
+                        .iter()
+                        .find(|w| w.is_main)
+                        .map(|main| main.branch.clone())
+                };
+                CommandResult::Action(CommandAction::SwitchWorktree {
+                    name: target.name.clone(),
+                    path: target.path.clone(),
+                    branch: target.branch.clone(),
+                    original_branch,
+                })
             }
 
             WorktreeCommand::Remove { name } => match manager.remove(&name) {
@@ -300,6 +655,34 @@ impl SlashCommandHandler {
 
   /terminal-setup         - Configure terminal keyboard shortcuts
 
+  /tag [name...]          - Tag the current session (prefix with - to remove)
+
+  /save <name>            - Name the session so --resume <name> finds it
+
+  /allow [tool:pattern]   - Always allow matching tool calls, or list rules
+
+  /preset [name]          - Apply a request parameter preset
+
+  /force-tool [name|any]  - Require a tool call on the next turn
+
+  /thinking [mode]        - Show reasoning as hidden, collapsed or shown
+
+  /compact [style]        - Summarize earlier history now, or set its style
+
+  /clear [keep-context]   - Start a fresh conversation in this session
+
+  /tokens                 - Show estimated context usage
+
+  /budget [continue]      - Show spend, or continue past the session budget
+
+  /model [name]           - Show or switch the model
+
+  /reset-loop             - Recover from a stuck tool loop
+
+  /explain                - Explain the last error and how to fix it
+
+  /skill [on|off name]    - List skills, or turn one on or off
+
   /help [command]         - Show help for a command
 
 Type /help <command> for detailed help on a specific command."#;
@@ -310,13 +693,17 @@ Type /help <command> for detailed help on a specific command."#;
                 let help_text = r#"/worktree - Manage git worktrees
 
 Subcommands:
-  new <name>     Create a new worktree with the given name
+  new <name>     Create a worktree on branch wt/<name> and work in it
   list           List all worktrees in the repository
-  switch <name>  Switch to an existing worktree
+  switch <name>  Work in an existing worktree (the main one to go back)
   remove <name>  Remove an existing worktree
   clean          Remove prunable worktrees (missing directories)
   status         Show status of all worktrees
 
+The conversation carries over and is saved as a new session linked to
+the worktree. When you quit from a worktree you are offered to merge
+its branch back or remove it.
+
 Examples:
   /worktree new feature-123
   /worktree list
@@ -380,6 +767,222 @@ Other terminals:
                 CommandResult::Executed(help_text.to_string())
             }
 
+            Some("save") => {
+                let help_text = r#"/save - Name the current session
+
+Usage:
+  /save <name>   Save the session under a memorable name
+
+Names may contain letters, digits, hyphens and underscores. The name is
+shown by --list-sessions and can be passed to --resume instead of the
+session ID. If several sessions share a name, the most recent is resumed.
+
+Examples:
+  /save my-refactor
+  --resume my-refactor"#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("allow") => {
+                let help_text = r#"/allow - Pre-approve tool calls
+
+Usage:
+  /allow                  List the saved allow rules
+  /allow <tool>           Always allow a tool
+  /allow <tool>:<pattern> Always allow calls whose input matches a glob
+
+The pattern is matched against the command for bash and the path for file
+tools. `*` matches any text and `?` one character. A bash pattern with
+wildcards never matches commands chained with ;, &&, | and the like.
+Rules are saved to permissions.json and apply to future sessions.
+
+Examples:
+  /allow bash:git diff*
+  /allow bash:git status
+  /allow read_file:src/**"#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("tag") => {
+                let help_text = r#"/tag - Tag the current session
+
+Usage:
+  /tag              Show the current session's tags
+  /tag <name>...    Add one or more tags
+  /tag -<name>...   Remove one or more tags
+
+Tags are saved with the session and shown by --list-sessions.
+Use --list-sessions --tag <name> to filter by tag.
+
+Examples:
+  /tag bugfix auth
+  /tag -auth"#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("preset") => {
+                let help_text = r#"/preset - Apply a request parameter preset
+
+Usage:
+  /preset           List available presets and show the active one
+  /preset <name>    Use the named preset for subsequent turns
+  /preset --clear   Return to default parameters
+
+Presets are defined in settings.toml:
+
+  [presets.precise]
+  temperature = 0.2
+
+  [presets.creative]
+  temperature = 1.0
+  top_p = 0.95
+
+The active preset is saved with the session and restored on resume."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("force-tool") => {
+                let help_text = r#"/force-tool - Require a tool call on the next turn
+
+Usage:
+  /force-tool           Show the tool forced for the next turn
+  /force-tool <name>    Make Claude call the named tool on the next turn
+  /force-tool any       Make Claude call some tool on the next turn
+  /force-tool --clear   Let Claude decide again
+
+The constraint applies to the first response of the next message only;
+follow-up responses after tool results are unconstrained.
+
+Examples:
+  /force-tool grep
+  /force-tool any"#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("thinking") => {
+                let help_text = r#"/thinking - Control how model reasoning is displayed
+
+Usage:
+  /thinking             Show the current mode
+  /thinking hidden      Do not display reasoning
+  /thinking collapsed   Summarize reasoning on one line (default)
+  /thinking shown       Display reasoning inline, dimmed
+
+Reasoning is only included when copying in shown mode. The default is
+set with `thinking_display` in settings.toml."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("compact") => {
+                let help_text = r#"/compact - Summarize earlier history now
+
+Usage:
+  /compact              Summarize all but the most recent messages
+  /compact style        Show the current summary style
+  /compact bullet       Summarize as bullet points of key outcomes
+  /compact paragraph    Summarize as a short narrative
+  /compact detailed     Summarize as a numbered timeline (default)
+
+Compacting reports how many messages and estimated tokens were reclaimed.
+A style applies from the next compaction. The default is set with
+--summary-style."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("model") => {
+                let help_text = r#"/model - Show or switch the model
+
+Usage:
+  /model          Show the current model and the tools advertised to it
+  /model <name>   Use another model for subsequent requests
+
+The tools advertised to each model can be restricted with `disabled_tools`
+and `[tool_profiles."<model prefix>"]` in settings.toml.
+
+Examples:
+  /model claude-3-haiku-20240307
+  /model claude-sonnet-4-20250514"#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("clear") => {
+                let help_text = r#"/clear - Start a fresh conversation
+
+Usage:
+  /clear                Drop all messages and start over
+  /clear keep-context   Drop messages but keep the session's context files
+
+The working directory, permissions and session stay the same; the cleared
+session is saved, so resuming it starts from the reset."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("tokens") => {
+                let help_text = r#"/tokens - Show context usage
+
+Usage:
+  /tokens   Show estimated tokens used by the conversation
+
+Reports the estimated input tokens of the conversation, the model's context
+window and the percentage used, along with the number of messages and tool
+results. Estimates are computed locally; no request is sent."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("budget") => {
+                let help_text = r#"/budget - Session cost budget
+
+Usage:
+  /budget            Show estimated spend against the session budget
+  /budget continue   Keep going after the budget has been exceeded
+
+Set the budget in USD with `session_budget` in settings.toml. A warning is
+shown at 80% of the budget; once it is exceeded, Patina pauses before the
+next request until you type /budget continue. Costs are estimates based on
+per-model pricing and the token usage reported by the API."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("reset-loop") => {
+                let help_text = r#"/reset-loop - Recover from a stuck tool loop
+
+Usage:
+  /reset-loop   Reset the tool loop to idle
+
+Use this if Patina stops responding after a tool call, for example when it
+waits for approval of tools that no longer exist. Partially received tool
+calls are discarded; the conversation itself is kept. A response that is
+still streaming or tools that are still running are left alone."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("explain") => {
+                let help_text = r#"/explain - Explain the last error
+
+Usage:
+  /explain   Explain the most recent tool or API error
+
+The error is sent to the current model on its own, without the rest of the
+conversation, and the explanation with suggested next steps is shown here.
+The explanation is not added to the conversation sent on later turns."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
+            Some("skill") => {
+                let help_text = r#"/skill - List and control skills
+
+Usage:
+  /skill             List skills and show which are active
+  /skill on <name>   Turn a skill on for this session
+  /skill off <name>  Turn a skill off for this session
+
+Skills turn on automatically when their triggers match: a keyword in your
+prompt, a file matching one of their file patterns, or `always_active`.
+Each automatic activation is announced. A skill turned off with /skill off
+does not turn on again automatically until the session ends."#;
+                CommandResult::Executed(help_text.to_string())
+            }
+
             Some(cmd) => CommandResult::UnknownCommand(cmd.to_string()),
         }
     }
@@ -472,7 +1075,25 @@ Other terminals:
     /// Returns available command names for tab completion.
     #[must_use]
     pub fn available_commands(&self) -> Vec<&'static str> {
-        vec!["worktree", "help", "plugins", "terminal-setup"]
+        vec![
+            "worktree",
+            "help",
+            "plugins",
+            "terminal-setup",
+            "tag",
+            "save",
+            "allow",
+            "preset",
+            "force-tool",
+            "thinking",
+            "compact",
+            "clear",
+            "tokens",
+            "budget",
+            "model",
+            "reset-loop",
+            "explain",
+        ]
     }
 
     /// Creates plugin info from a plugin registry.
@@ -592,7 +1213,7 @@ mod tests {
                     msg
                 );
             }
-            CommandResult::Executed(_) => {
+            CommandResult::Action(CommandAction::SwitchWorktree { .. }) => {
                 // Would succeed in a real git repo
             }
             other => panic!("Unexpected result: {:?}", other),
@@ -996,4 +1617,340 @@ mod tests {
             "Available commands should include 'terminal-setup'"
         );
     }
+
+    // =========================================================================
+    // Tag command tests
+    // =========================================================================
+
+    #[test]
+    fn test_handle_tag_adds_tags() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        let result = handler.handle("/tag bugfix auth");
+
+        assert_eq!(
+            result,
+            CommandResult::Action(CommandAction::AddTags(vec![
+                "bugfix".to_string(),
+                "auth".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_handle_tag_removes_tags() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        let result = handler.handle("/tag -auth");
+
+        assert_eq!(
+            result,
+            CommandResult::Action(CommandAction::RemoveTags(vec!["auth".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_handle_tag_without_args_lists() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        let result = handler.handle("/tag");
+
+        assert_eq!(result, CommandResult::Action(CommandAction::ListTags));
+    }
+
+    #[test]
+    fn test_handle_tag_rejects_mixed_add_and_remove() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        let result = handler.handle("/tag bugfix -auth");
+
+        assert!(matches!(result, CommandResult::Error(_)));
+    }
+
+    #[test]
+    fn test_available_commands_includes_tag() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert!(handler.available_commands().contains(&"tag"));
+    }
+
+    #[test]
+    fn test_handle_allow() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/allow bash:git diff*"),
+            CommandResult::Action(CommandAction::AllowRule(PermissionRule::new(
+                "bash",
+                Some("git diff*"),
+                true
+            )))
+        );
+        assert_eq!(
+            handler.handle("/allow"),
+            CommandResult::Action(CommandAction::ListAllowRules)
+        );
+        assert!(matches!(
+            handler.handle("/allow bash:"),
+            CommandResult::Error(e) if e.contains("Usage: /allow")
+        ));
+        assert!(handler.available_commands().contains(&"allow"));
+    }
+
+    #[test]
+    fn test_handle_save() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/save my-refactor"),
+            CommandResult::Action(CommandAction::NameSession("my-refactor".to_string()))
+        );
+        assert!(matches!(
+            handler.handle("/save ../escape"),
+            CommandResult::Error(e) if e.contains("invalid session name")
+        ));
+        assert!(matches!(handler.handle("/save"), CommandResult::Error(_)));
+        assert!(matches!(
+            handler.handle("/save two words"),
+            CommandResult::Error(_)
+        ));
+        assert!(handler.available_commands().contains(&"save"));
+    }
+
+    // =========================================================================
+    // Preset command tests
+    // =========================================================================
+
+    #[test]
+    fn test_handle_preset_applies_named_preset() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/preset creative"),
+            CommandResult::Action(CommandAction::ApplyPreset("creative".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_handle_preset_without_args_lists() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/preset"),
+            CommandResult::Action(CommandAction::ListPresets)
+        );
+        assert_eq!(
+            handler.handle("/preset --clear"),
+            CommandResult::Action(CommandAction::ClearPreset)
+        );
+    }
+
+    #[test]
+    fn test_handle_force_tool() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/force-tool grep"),
+            CommandResult::Action(CommandAction::ForceTool("grep".to_string()))
+        );
+        assert_eq!(
+            handler.handle("/force-tool"),
+            CommandResult::Action(CommandAction::ShowForcedTool)
+        );
+        assert_eq!(
+            handler.handle("/force-tool --clear"),
+            CommandResult::Action(CommandAction::ClearForcedTool)
+        );
+        assert!(matches!(
+            handler.handle("/force-tool a b"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_thinking() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/thinking shown"),
+            CommandResult::Action(CommandAction::SetThinkingDisplay(ThinkingDisplay::Shown))
+        );
+        assert_eq!(
+            handler.handle("/thinking"),
+            CommandResult::Action(CommandAction::ShowThinkingDisplay)
+        );
+        assert!(matches!(
+            handler.handle("/thinking verbose"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_compact_style() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/compact bullet"),
+            CommandResult::Action(CommandAction::SetSummaryStyle(SummaryStyle::BulletPoints))
+        );
+        assert_eq!(
+            handler.handle("/compact"),
+            CommandResult::Action(CommandAction::CompactNow)
+        );
+        assert_eq!(
+            handler.handle("/compact style"),
+            CommandResult::Action(CommandAction::ShowSummaryStyle)
+        );
+        assert!(matches!(
+            handler.handle("/compact terse"),
+            CommandResult::Error(e) if e.contains("bullet, paragraph or detailed")
+        ));
+        assert!(matches!(
+            handler.handle("/compact bullet paragraph"),
+            CommandResult::Error(_)
+        ));
+        assert!(handler.available_commands().contains(&"compact"));
+    }
+
+    #[test]
+    fn test_handle_clear() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/clear"),
+            CommandResult::Action(CommandAction::ClearConversation {
+                keep_context: false
+            })
+        );
+        assert_eq!(
+            handler.handle("/clear keep-context"),
+            CommandResult::Action(CommandAction::ClearConversation { keep_context: true })
+        );
+        assert!(matches!(
+            handler.handle("/clear all"),
+            CommandResult::Error(_)
+        ));
+        assert!(handler.available_commands().contains(&"clear"));
+    }
+
+    #[test]
+    fn test_handle_tokens() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/tokens"),
+            CommandResult::Action(CommandAction::ShowTokens)
+        );
+        assert!(matches!(
+            handler.handle("/tokens all"),
+            CommandResult::Error(_)
+        ));
+        assert!(handler.available_commands().contains(&"tokens"));
+    }
+
+    #[test]
+    fn test_handle_budget() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/budget"),
+            CommandResult::Action(CommandAction::ShowBudget)
+        );
+        assert_eq!(
+            handler.handle("/budget continue"),
+            CommandResult::Action(CommandAction::ConfirmBudget)
+        );
+        assert!(matches!(
+            handler.handle("/budget 10"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_model() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/model claude-3-haiku-20240307"),
+            CommandResult::Action(CommandAction::SetModel(
+                "claude-3-haiku-20240307".to_string()
+            ))
+        );
+        assert_eq!(
+            handler.handle("/model"),
+            CommandResult::Action(CommandAction::ShowModel)
+        );
+        assert!(matches!(
+            handler.handle("/model a b"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_reset_loop() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/reset-loop"),
+            CommandResult::Action(CommandAction::ResetToolLoop)
+        );
+        assert!(matches!(
+            handler.handle("/reset-loop now"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_skill() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/skill"),
+            CommandResult::Action(CommandAction::ListSkills)
+        );
+        assert_eq!(
+            handler.handle("/skill on rust"),
+            CommandResult::Action(CommandAction::ActivateSkill("rust".to_string()))
+        );
+        assert_eq!(
+            handler.handle("/skill off rust"),
+            CommandResult::Action(CommandAction::DeactivateSkill("rust".to_string()))
+        );
+        assert!(matches!(
+            handler.handle("/skill toggle rust"),
+            CommandResult::Error(_)
+        ));
+        assert!(matches!(
+            handler.handle("/skill off"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_explain() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert_eq!(
+            handler.handle("/explain"),
+            CommandResult::Action(CommandAction::ExplainLastError)
+        );
+        assert!(matches!(
+            handler.handle("/explain that"),
+            CommandResult::Error(_)
+        ));
+        assert!(matches!(
+            handler.handle("/help explain"),
+            CommandResult::Executed(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_preset_rejects_extra_args() {
+        let (handler, _temp) = create_handler_in_temp();
+
+        assert!(matches!(
+            handler.handle("/preset a b"),
+            CommandResult::Error(_)
+        ));
+    }
 }
@@ -0,0 +1,120 @@
+//! Plain-language explanations of session errors for `/explain`.
+//!
+//! The most recent tool or API error is sent to the model in a one-off
+//! request. The explanation is shown in the timeline only, so it never becomes
+//! part of the conversation sent on later turns.
+
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::api::AnthropicClient;
+use crate::types::{Message, Role};
+
+/// `max_tokens` to request for an explanation.
+pub const EXPLAIN_MAX_TOKENS: u32 = 1024;
+
+/// Maximum characters of the error message included in the prompt.
+const MAX_ERROR_CHARS: usize = 4000;
+
+/// Where an error came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorSource {
+    /// The Anthropic API rejected or failed a request.
+    Api,
+    /// A tool call failed; holds the tool name.
+    Tool(String),
+}
+
+impl fmt::Display for ErrorSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api => write!(f, "API error"),
+            Self::Tool(name) => write!(f, "error from the {name} tool"),
+        }
+    }
+}
+
+/// The most recent error seen in a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastError {
+    /// Where the error came from.
+    pub source: ErrorSource,
+    /// The error message as reported.
+    pub message: String,
+}
+
+impl LastError {
+    /// Creates an error reported by the API.
+    #[must_use]
+    pub fn api(message: impl Into<String>) -> Self {
+        Self {
+            source: ErrorSource::Api,
+            message: message.into(),
+        }
+    }
+
+    /// Creates an error returned by the named tool.
+    #[must_use]
+    pub fn tool(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            source: ErrorSource::Tool(name.into()),
+            message: message.into(),
+        }
+    }
+}
+
+/// Asks the model to explain an error and suggest next steps.
+///
+/// The request is sent with `client` as configured and contains only the
+/// error, not the conversation.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the model returns no text.
+pub async fn explain_error(client: &AnthropicClient, error: &LastError) -> Result<String> {
+    let response = client
+        .complete(&[Message {
+            role: Role::User,
+            content: explain_prompt(error),
+        }])
+        .await?;
+
+    let explanation = response.trim();
+    if explanation.is_empty() {
+        anyhow::bail!("Model returned an empty explanation");
+    }
+    Ok(explanation.to_string())
+}
+
+/// Builds the prompt for explaining an error.
+fn explain_prompt(error: &LastError) -> String {
+    let message: String = error.message.chars().take(MAX_ERROR_CHARS).collect();
+    format!(
+        "I am using Patina, a terminal coding assistant, and hit the {} below. \
+         Explain in plain language what it means and list concrete next steps \
+         to fix it. Be brief.\n\n```\n{}\n```",
+        error.source, message
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_prompt_names_source() {
+        let prompt = explain_prompt(&LastError::tool("bash", "cargo: command not found"));
+        assert!(prompt.contains("error from the bash tool"));
+        assert!(prompt.contains("cargo: command not found"));
+
+        let prompt = explain_prompt(&LastError::api("401 Unauthorized"));
+        assert!(prompt.contains("API error"));
+    }
+
+    #[test]
+    fn test_explain_prompt_truncates_long_errors() {
+        let prompt = explain_prompt(&LastError::api("x".repeat(MAX_ERROR_CHARS * 2)));
+        assert!(prompt.len() < MAX_ERROR_CHARS + 500);
+    }
+}
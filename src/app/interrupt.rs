@@ -0,0 +1,157 @@
+//! Ctrl+C handling for the interactive TUI.
+//!
+//! In [`CtrlCMode::Exit`] every Ctrl+C exits. In [`CtrlCMode::CancelThenExit`]
+//! a press cancels whatever is in flight (or, when idle, arms exit), and a
+//! second press within [`DOUBLE_PRESS_WINDOW`] exits. A second press after the
+//! window has elapsed is treated as a fresh first press.
+
+use std::time::{Duration, Instant};
+
+use crate::types::config::CtrlCMode;
+
+/// How long after a Ctrl+C a second press counts as "press again to exit".
+pub const DOUBLE_PRESS_WINDOW: Duration = Duration::from_secs(2);
+
+/// What the event loop should do in response to a Ctrl+C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtrlCAction {
+    /// Exit the application.
+    Exit,
+    /// Cancel the in-flight stream or tool execution and return to input.
+    Cancel,
+    /// Nothing was running; a second press within the window will exit.
+    ArmExit,
+}
+
+/// Tracks Ctrl+C presses and decides whether each one cancels or exits.
+#[derive(Debug, Clone)]
+pub struct CtrlCHandler {
+    mode: CtrlCMode,
+    window: Duration,
+    last_press: Option<Instant>,
+}
+
+impl Default for CtrlCHandler {
+    fn default() -> Self {
+        Self::new(CtrlCMode::default())
+    }
+}
+
+impl CtrlCHandler {
+    /// Creates a handler for the given mode using [`DOUBLE_PRESS_WINDOW`].
+    #[must_use]
+    pub fn new(mode: CtrlCMode) -> Self {
+        Self {
+            mode,
+            window: DOUBLE_PRESS_WINDOW,
+            last_press: None,
+        }
+    }
+
+    /// Sets the window within which a second press exits.
+    #[must_use]
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Returns the configured mode.
+    #[must_use]
+    pub fn mode(&self) -> CtrlCMode {
+        self.mode
+    }
+
+    /// Records a Ctrl+C at `now` and returns the action to take.
+    ///
+    /// `busy` reports whether a stream or tool execution is in flight.
+    pub fn press(&mut self, now: Instant, busy: bool) -> CtrlCAction {
+        if self.mode == CtrlCMode::Exit || self.exit_armed(now) {
+            self.last_press = None;
+            return CtrlCAction::Exit;
+        }
+
+        self.last_press = Some(now);
+        if busy {
+            CtrlCAction::Cancel
+        } else {
+            CtrlCAction::ArmExit
+        }
+    }
+
+    /// Returns true if a press at `now` would exit.
+    #[must_use]
+    pub fn exit_armed(&self, now: Instant) -> bool {
+        self.last_press
+            .is_some_and(|last| now.saturating_duration_since(last) <= self.window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_mode_always_exits() {
+        let mut handler = CtrlCHandler::new(CtrlCMode::Exit);
+        let now = Instant::now();
+
+        assert_eq!(handler.press(now, true), CtrlCAction::Exit);
+        assert_eq!(handler.press(now, false), CtrlCAction::Exit);
+    }
+
+    #[test]
+    fn test_first_press_cancels_when_busy() {
+        let mut handler = CtrlCHandler::new(CtrlCMode::CancelThenExit);
+
+        assert_eq!(handler.press(Instant::now(), true), CtrlCAction::Cancel);
+    }
+
+    #[test]
+    fn test_first_press_arms_exit_when_idle() {
+        let mut handler = CtrlCHandler::new(CtrlCMode::CancelThenExit);
+        let now = Instant::now();
+
+        assert!(!handler.exit_armed(now));
+        assert_eq!(handler.press(now, false), CtrlCAction::ArmExit);
+        assert!(handler.exit_armed(now));
+    }
+
+    #[test]
+    fn test_second_press_within_window_exits() {
+        let mut handler = CtrlCHandler::new(CtrlCMode::CancelThenExit);
+        let start = Instant::now();
+
+        assert_eq!(handler.press(start, true), CtrlCAction::Cancel);
+        assert_eq!(
+            handler.press(start + Duration::from_millis(500), false),
+            CtrlCAction::Exit
+        );
+    }
+
+    #[test]
+    fn test_stale_second_press_is_treated_as_first() {
+        let mut handler =
+            CtrlCHandler::new(CtrlCMode::CancelThenExit).with_window(Duration::from_secs(1));
+        let start = Instant::now();
+
+        assert_eq!(handler.press(start, true), CtrlCAction::Cancel);
+
+        let stale = start + Duration::from_secs(3);
+        assert!(!handler.exit_armed(stale));
+        assert_eq!(handler.press(stale, true), CtrlCAction::Cancel);
+        assert_eq!(
+            handler.press(stale + Duration::from_millis(200), true),
+            CtrlCAction::Exit
+        );
+    }
+
+    #[test]
+    fn test_exit_resets_handler() {
+        let mut handler = CtrlCHandler::new(CtrlCMode::CancelThenExit);
+        let start = Instant::now();
+
+        handler.press(start, false);
+        assert_eq!(handler.press(start, false), CtrlCAction::Exit);
+        assert!(!handler.exit_armed(start));
+    }
+}
@@ -16,19 +16,31 @@ use crossterm::{
 use futures::StreamExt;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::{io, time::Duration};
-use tokio::time::interval;
+use tokio::time::{interval, sleep_until};
 use tracing::{debug, info, warn};
 
+pub mod auto;
+pub mod budget;
+pub mod coalesce;
 pub mod commands;
+pub mod explain;
+pub mod interrupt;
+pub mod paste;
+pub mod print;
 pub mod state;
 pub mod tool_loop;
+pub mod worktree;
 
+use coalesce::DeltaCoalescer;
+use interrupt::CtrlCAction;
 use state::{AppState, BackgroundEvent};
 use tool_loop::ToolLoopState;
 
-use crate::api::AnthropicClient;
+use crate::api::{AnthropicClient, UsageCounter, DEFAULT_AUTO_COMPACT_THRESHOLD};
+use crate::enterprise::policy::{OrgPolicy, ORG_POLICY_PATH};
 use crate::ide::controller::IdeController;
-use crate::permissions::PermissionResponse;
+use crate::mcp::{McpManager, McpTransport};
+use crate::permissions::{PermissionManager, PermissionResponse};
 use crate::session::{default_sessions_dir, SessionManager};
 use crate::terminal;
 use crate::tui;
@@ -36,7 +48,7 @@ use crate::tui::selection::{ContentPosition, FocusArea};
 use crate::tui::widgets::handle_permission_key;
 use crate::tui::widgets::permission_prompt::PermissionPromptState;
 use crate::types::config::ResumeMode;
-use crate::types::{ApiMessageV2, Message, Role};
+use crate::types::{ApiMessageV2, Message, Role, Settings};
 
 // Re-export Config for backward compatibility
 pub use crate::types::Config;
@@ -52,59 +64,20 @@ pub use crate::types::Config;
 /// without causing backpressure.
 pub const STREAMING_CHANNEL_BUFFER: usize = 1000;
 
-/// Result of processing a print mode stream.
-enum PrintStreamResult {
-    /// Stream completed successfully (MessageStop or MessageComplete).
-    Completed(String),
-    /// Stream ended with an error.
-    Error(String),
-}
-
-/// Processes a print mode stream, printing content and handling tool use events.
+/// Buffer size for partial results from running MCP tools.
 ///
-/// Returns the accumulated response text and the final result.
-async fn process_print_stream(
-    rx: &mut tokio::sync::mpsc::Receiver<crate::api::StreamEvent>,
-    state: &mut AppState,
-) -> Result<PrintStreamResult> {
-    use crate::api::StreamEvent;
+/// Progress arriving while the buffer is full is not displayed; the model
+/// still receives every partial result with the final tool result.
+pub const MCP_PROGRESS_BUFFER: usize = 256;
 
-    let mut response = String::new();
+/// Notice shown when Claude declines to respond (`stop_reason: refusal`).
+pub const REFUSAL_NOTICE: &str = "Claude declined to respond to this request (refusal).";
 
-    while let Some(event) = rx.recv().await {
-        match event {
-            StreamEvent::ContentDelta(text) => {
-                print!("{}", text);
-                response.push_str(&text);
-            }
-            StreamEvent::MessageStop | StreamEvent::MessageComplete { .. } => {
-                println!(); // Newline after response
-                return Ok(PrintStreamResult::Completed(response));
-            }
-            StreamEvent::Error(e) => {
-                eprintln!("Error: {}", e);
-                return Ok(PrintStreamResult::Error(e));
-            }
-            StreamEvent::ToolUseStart { id, name, index } => {
-                state.tool_loop_mut().start_streaming().ok();
-                state.handle_tool_use_start(id, name, index);
-            }
-            StreamEvent::ToolUseInputDelta {
-                index,
-                partial_json,
-            } => {
-                state.handle_tool_use_input_delta(index, &partial_json);
-            }
-            StreamEvent::ToolUseComplete { index } => {
-                state.handle_tool_use_complete(index)?;
-            }
-            _ => {}
-        }
-    }
+/// Most lines of the input box shown at once; longer drafts scroll.
+pub const MAX_INPUT_LINES: usize = 8;
 
-    // Channel closed without explicit completion
-    Ok(PrintStreamResult::Completed(response))
-}
+/// Notice shown after the user cancels a response in progress.
+pub const CANCELLED_NOTICE: &str = "Response cancelled.";
 
 /// Handles copy operation with detailed logging.
 ///
@@ -148,10 +121,17 @@ fn handle_copy(state: &AppState) {
 }
 
 pub async fn run(config: Config) -> Result<()> {
+    // Autonomous mode keeps working on the prompt until done or a guardrail trips
+    if config.auto_mode {
+        if let Some(ref prompt) = config.initial_prompt {
+            return auto::run_auto_mode(&config, prompt).await;
+        }
+    }
+
     // If print mode is enabled with an initial prompt, run non-interactively
     if config.print_mode {
         if let Some(ref prompt) = config.initial_prompt {
-            return run_print_mode(&config, prompt).await;
+            return print::run_print_mode(&config, prompt).await;
         }
     }
 
@@ -173,7 +153,22 @@ pub async fn run(config: Config) -> Result<()> {
 
     // Initialize session manager for auto-save
     let sessions_dir = default_sessions_dir()?;
-    let session_manager = SessionManager::new(sessions_dir);
+    let session_manager = SessionManager::new(sessions_dir)
+        .with_secret_redaction(config.settings.redact_sessions)
+        .with_write_mode(config.settings.session_write_mode);
+    if let Some(fs_type) = session_manager.network_filesystem() {
+        if session_manager.durable_writes() {
+            eprintln!(
+                "Warning: sessions directory is on a network filesystem ({fs_type}); \
+                 using fsync-and-verify session writes."
+            );
+        } else {
+            eprintln!(
+                "Warning: sessions directory is on a network filesystem ({fs_type}); \
+                 set session_write_mode = \"durable\" to avoid lost or corrupt sessions."
+            );
+        }
+    }
 
     // Check for session resume before initializing terminal
     let mut state = match &config.resume_mode {
@@ -186,6 +181,51 @@ pub async fn run(config: Config) -> Result<()> {
         ),
         ResumeMode::Last | ResumeMode::SessionId(_) => load_session_state(&config).await?,
     };
+    if config.watch_plugins && config.plugins_enabled {
+        if let Err(e) = state.watch_plugins() {
+            warn!("Plugin watching disabled: {:#}", e);
+        }
+    }
+    state.set_presets(config.settings.presets.clone());
+    state.set_title_model(config.settings.title_model.clone());
+    state.set_ctrl_c_mode(config.settings.ctrl_c);
+    state.set_thinking_display(config.settings.thinking_display);
+    state.set_paste_threshold(
+        config
+            .settings
+            .paste_file_threshold
+            .unwrap_or(paste::DEFAULT_PASTE_FILE_THRESHOLD),
+    );
+    state.set_stream_flush(config.settings.stream_flush.clone());
+    state.set_session_budget(config.settings.session_budget, &config.model)?;
+    state.set_auto_compact_threshold(
+        config
+            .settings
+            .auto_compact_threshold
+            .unwrap_or(DEFAULT_AUTO_COMPACT_THRESHOLD),
+    )?;
+    state.set_summary_style(config.summary_style);
+    apply_tool_profiles(&mut state, &config);
+    state.set_path_confinement(config.settings.path_confinement);
+    state.set_mcp_tool_safety(&config.settings.mcp_tool_safety);
+    state.set_web_fetch_config(config.settings.web_fetch.to_config());
+    state.set_web_search_config(config.settings.web_search.to_config());
+    state.set_vision_config(config.settings.vision.to_config());
+    state.set_tool_output_threshold(config.settings.tool_output_file_threshold);
+    state.set_tool_timeout(config.settings.tool_timeout_secs.map(Duration::from_secs));
+    state.set_backups_enabled(config.backups_enabled);
+    apply_org_policy(&mut state)?;
+    connect_mcp_servers(&mut state, &config).await;
+    if config.settings.load_dotenv {
+        state.load_dotenv();
+    }
+    enable_redaction(&mut state, &config)?;
+    enable_tool_audit(&mut state, &config)?;
+    state.set_auto_commit_worktree(config.settings.auto_commit_worktree);
+    apply_permission_mode(&mut state, &config);
+    load_permission_rules(&mut state);
+    load_hooks(&mut state, &config);
+    state.fire_session_start().await?;
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -225,15 +265,40 @@ pub async fn run(config: Config) -> Result<()> {
         info!("Keyboard enhancement not supported - use Ctrl+A/Ctrl+Y instead");
     }
 
+    // Explain the alternative shortcuts once, the first time they are needed
+    if let Some(marker) = terminal::keyboard_hint_marker() {
+        if terminal::should_show_keyboard_hint(keyboard_enhancement_supported, &marker) {
+            let bindings =
+                terminal::ClipboardBindings::active(keyboard_enhancement_supported, is_jetbrains);
+            state.show_keyboard_hint(terminal::keyboard_fallback_hint(&bindings));
+            if let Err(e) = terminal::mark_keyboard_hint_shown(&marker) {
+                warn!("Failed to record keyboard hint: {}", e);
+            }
+        }
+    }
+
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let client = AnthropicClient::new(config.api_key.clone(), &config.model);
+    // Usage across the whole session, including title generation and subagents
+    let usage = UsageCounter::new();
+    let mut client = AnthropicClient::new(config.api_key.clone(), &config.model)
+        .with_large_context(config.settings.large_context)
+        .with_interceptor(usage.clone());
+    if let Some(limit) = config.settings.max_concurrent_requests {
+        client = client.with_max_concurrent_requests(limit);
+    }
+
+    state.set_context_window(client.context_window());
 
     // Start IDE server if port is specified
     if let Some(port) = config.ide_port {
-        let controller = IdeController::new(port);
+        let mut controller = IdeController::new(port);
+        if let Some(rx) = controller.take_context_receiver() {
+            state.set_ide_context_receiver(rx);
+        }
+        state.set_ide_notifier(controller.notifier());
         tokio::spawn(async move {
             if let Err(e) = controller.run().await {
                 warn!("IDE server error: {}", e);
@@ -243,11 +308,18 @@ pub async fn run(config: Config) -> Result<()> {
     }
 
     // If there's an initial prompt, submit it immediately
+    let mut result = Ok(());
     if let Some(ref prompt) = config.initial_prompt {
-        state.submit_message(&client, prompt.clone()).await?;
+        if let Some(prompt) = state.apply_prompt_hooks(prompt.clone()).await {
+            result = state.submit_message(&client, prompt).await;
+        }
     }
 
-    let result = event_loop(&mut terminal, &client, &mut state, &session_manager).await;
+    if result.is_ok() {
+        result = event_loop(&mut terminal, &client, &mut state, &session_manager).await;
+    }
+    let stop_reason = if result.is_ok() { "user_exit" } else { "error" };
+    state.fire_session_end(stop_reason).await;
 
     // Clean up terminal state
     if keyboard_enhancement_supported {
@@ -261,6 +333,19 @@ pub async fn run(config: Config) -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
+    info!(
+        requests = usage.requests(),
+        input_tokens = usage.input_tokens(),
+        output_tokens = usage.output_tokens(),
+        "Session API usage"
+    );
+
+    if result.is_ok() {
+        if let Some(session) = state.worktree_session() {
+            worktree::offer_wrap_up(&state.working_dir, session);
+        }
+    }
+
     result
 }
 
@@ -307,144 +392,195 @@ async fn load_session_state(config: &Config) -> Result<AppState> {
     Ok(state)
 }
 
-/// Runs in print mode (non-interactive).
+/// Sets the initial permission mode from the settings rule matching the
+/// launch directory, falling back to the global default.
+fn apply_permission_mode(state: &mut AppState, config: &Config) {
+    let (mode, rule) = config.settings.permission_mode_for(&config.working_dir);
+    if let Some(rule) = rule {
+        info!(
+            rule = %rule,
+            mode = mode.as_str(),
+            "Permission mode chosen by directory rule"
+        );
+    }
+    state.set_permission_mode(mode, rule.map(String::from));
+}
+
+/// Enforces the organization policy installed at [`ORG_POLICY_PATH`], if any.
 ///
-/// This function:
-/// 1. Sends the prompt to Claude
-/// 2. Streams and prints the response to stdout
-/// 3. Executes any tools Claude requests
-/// 4. Continues the conversation until Claude is done
-/// 5. Exits
+/// # Errors
 ///
-/// This matches Claude Code's `-p` / `--print` flag behavior.
-async fn run_print_mode(config: &Config, prompt: &str) -> Result<()> {
-    use crate::api::tools::default_tools;
-    use crate::api::ToolChoice;
-
-    let client = AnthropicClient::new(config.api_key.clone(), &config.model);
-    let mut state = AppState::with_options(
-        config.working_dir.clone(),
-        config.skip_permissions,
-        config.parallel_mode,
-        config.plugins_enabled,
-        config.subagents_enabled,
-    );
-
-    // Add the user's prompt (adds to both display and API messages via submit logic)
-    let user_msg = ApiMessageV2::user(prompt);
-    state.add_message(Message {
-        role: Role::User,
-        content: prompt.to_string(),
-    });
-    state.api_messages_mut().push(user_msg);
-
-    // Set up streaming using API messages
-    let tools = default_tools();
-    let (tx, mut rx) = tokio::sync::mpsc::channel(STREAMING_CHANNEL_BUFFER);
-    let api_messages = state.api_messages().to_vec();
-    let client_clone = client.clone();
-    let tools_clone = tools.clone();
-
-    tokio::spawn(async move {
-        if let Err(e) = client_clone
-            .stream_message_v2_with_tools(
-                &api_messages,
-                Some(&tools_clone),
-                Some(&ToolChoice::Auto),
-                tx,
-            )
-            .await
-        {
-            tracing::error!("API error: {}", e);
-        }
-    });
+/// Returns an error if the policy cannot be read or is invalid, so a broken
+/// policy stops Patina instead of being ignored.
+fn apply_org_policy(state: &mut AppState) -> Result<()> {
+    if let Some(policy) = OrgPolicy::load(std::path::Path::new(ORG_POLICY_PATH))? {
+        info!("Enforcing org policy from {}", ORG_POLICY_PATH);
+        state.apply_org_policy(&policy)?;
+    }
+    Ok(())
+}
 
-    // Collect and print the response
-    let response = match process_print_stream(&mut rx, &mut state).await? {
-        PrintStreamResult::Completed(text) => text,
-        PrintStreamResult::Error(e) => return Err(anyhow::anyhow!("API error: {}", e)),
-    };
+/// Redacts secrets from tool output unless `[redaction]` is disabled.
+///
+/// # Errors
+///
+/// Returns an error if a configured pattern is invalid, so a typo never
+/// silently turns redaction off.
+fn enable_redaction(state: &mut AppState, config: &Config) -> Result<()> {
+    let redactor = config
+        .settings
+        .redaction
+        .to_redactor()
+        .context("Invalid [redaction] settings")?;
+    if let Some(redactor) = redactor {
+        state.set_redactor(redactor)?;
+    }
+    Ok(())
+}
 
-    // If there are no tool uses, add the assistant message to both display and API
-    if !response.is_empty() && !matches!(state.tool_loop_state(), ToolLoopState::PendingApproval) {
-        state.add_message(Message {
-            role: Role::Assistant,
-            content: response.clone(),
-        });
+/// Starts the tool audit log when `[audit]` is enabled in settings.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be opened, so tools never run
+/// unaudited when auditing was asked for.
+fn enable_tool_audit(state: &mut AppState, config: &Config) -> Result<()> {
+    if let Some(audit) = config.settings.audit.to_config()? {
+        info!("Recording tool executions in {}", audit.path.display());
         state
-            .api_messages_mut()
-            .push(ApiMessageV2::assistant(&response));
+            .enable_tool_audit(audit)
+            .context("Failed to open the tool audit log")?;
     }
+    Ok(())
+}
 
-    // Handle any tool execution if needed
-    while matches!(state.tool_loop_state(), ToolLoopState::PendingApproval) {
-        // Auto-approve all tools in non-interactive mode
-        state.approve_all_tools()?;
-
-        // Execute the tools
-        let needs_permission = state.execute_pending_tools().await?;
-
-        // Check if any tools still need permission
-        if !needs_permission.is_empty() {
-            warn!(
-                "Tools need permission in print mode (skipping): {:?}",
-                needs_permission
-            );
-            break;
+/// Starts the MCP servers configured in `[mcp_servers]` and offers their
+/// tools to the model.
+///
+/// A server that fails to start is logged and skipped, as is a stdio
+/// server whose launch command the execution policy blocks. Call after the
+/// org policy is applied.
+async fn connect_mcp_servers(state: &mut AppState, config: &Config) {
+    if config.settings.mcp_servers.is_empty() {
+        return;
+    }
+    let mut servers = config.settings.mcp_servers.clone();
+    servers.retain(|name, server| {
+        let McpTransport::Stdio { command, args, .. } = &server.transport else {
+            return true;
+        };
+        let command_line = std::iter::once(command)
+            .chain(args)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        match state.check_command(&command_line) {
+            Ok(()) => true,
+            Err(reason) => {
+                warn!("MCP server '{}' not started: {}", name, reason);
+                false
+            }
         }
+    });
+    let (events_tx, events_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut manager = McpManager::new().with_connection_events(events_tx);
+    if let Err(e) = manager.initialize(servers).await {
+        warn!(error = %e, "Failed to start MCP servers");
+    }
+    state.set_mcp_manager(manager);
+    state.set_mcp_events_receiver(events_rx);
+}
 
-        // Finish execution and get continuation data
-        let continuation = state.finish_tool_execution()?;
-
-        // Build the messages for the conversation
-        let (assistant_msg, user_msg) = continuation.build_messages();
-
-        // Add to API message history for conversation continuation
-        // Note: The assistant message is NOT added to the timeline here because
-        // finalize_streaming_for_tool_use() already converted the streaming entry
-        // to an AssistantMessage. Adding it again would cause duplicate messages.
-        state.api_messages_mut().push(assistant_msg);
-
-        // Add tool results to both timeline (for display) and API (for continuation)
-        let tool_result_summary = format_tool_results_for_display(&user_msg);
-        state.add_message(Message {
-            role: Role::User,
-            content: tool_result_summary,
-        });
-        state.api_messages_mut().push(user_msg);
-
-        state.tool_loop_mut().start_streaming()?;
+/// Loads the saved permission rules so "allow always" answers and `/allow`
+/// rules carry over between sessions.
+fn load_permission_rules(state: &mut AppState) {
+    let result =
+        PermissionManager::default_config_path().and_then(|path| state.load_permission_rules(path));
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to load saved permission rules");
+    }
+}
 
-        let (tx, mut rx) = tokio::sync::mpsc::channel(STREAMING_CHANNEL_BUFFER);
-        let api_messages = state.api_messages().to_vec();
-        let client_clone = client.clone();
-        let tools = default_tools();
+/// Loads hooks from the config directory, and the project's own hooks if the
+/// working directory is listed in `trusted_projects`.
+fn load_hooks(state: &mut AppState, config: &Config) {
+    let user_hooks = Settings::default_path()
+        .ok()
+        .and_then(|path| Some(path.parent()?.join("hooks.toml")));
+    let working_dir = config.working_dir.canonicalize().ok();
+    let trusted = config.settings.trusted_projects.iter().any(|project| {
+        project
+            .canonicalize()
+            .ok()
+            .is_some_and(|p| Some(p) == working_dir)
+    });
+    state.load_hooks(user_hooks.as_deref(), trusted);
+}
 
-        tokio::spawn(async move {
-            if let Err(e) = client_clone
-                .stream_message_v2_with_tools(
-                    &api_messages,
-                    Some(&tools),
-                    Some(&ToolChoice::Auto),
-                    tx,
-                )
-                .await
-            {
-                tracing::error!("API error during tool continuation: {}", e);
-            }
-        });
+/// Sets the model and the tools advertised to it from settings.
+fn apply_tool_profiles(state: &mut AppState, config: &Config) {
+    state.set_model(&config.model);
+    state.set_tool_profiles(
+        config.settings.disabled_tools.clone(),
+        config.settings.tool_profiles.clone(),
+    );
+}
 
-        // Process the continuation using the same helper
-        match process_print_stream(&mut rx, &mut state).await? {
-            PrintStreamResult::Completed(_) => {} // Continue loop if more tools
-            PrintStreamResult::Error(e) => {
-                warn!("Error during tool continuation: {}", e);
-                break;
-            }
-        }
+/// Creates the API client for a run without the TUI.
+fn headless_client(config: &Config) -> AnthropicClient {
+    let mut client = AnthropicClient::new(config.api_key.clone(), &config.model)
+        .with_large_context(config.settings.large_context);
+    if let Some(limit) = config.settings.max_concurrent_requests {
+        client = client.with_max_concurrent_requests(limit);
     }
+    client
+}
 
-    Ok(())
+/// Creates the application state for a run without the TUI.
+///
+/// # Errors
+///
+/// Returns an error if a budget or compaction setting or the org policy is
+/// invalid, or the tool audit log cannot be opened.
+async fn headless_state(config: &Config, client: &AnthropicClient) -> Result<AppState> {
+    let mut state = AppState::with_options(
+        config.working_dir.clone(),
+        config.skip_permissions,
+        config.parallel_mode,
+        config.plugins_enabled,
+        config.subagents_enabled,
+    );
+    state.set_path_confinement(config.settings.path_confinement);
+    state.set_mcp_tool_safety(&config.settings.mcp_tool_safety);
+    state.set_web_fetch_config(config.settings.web_fetch.to_config());
+    state.set_web_search_config(config.settings.web_search.to_config());
+    state.set_vision_config(config.settings.vision.to_config());
+    state.set_tool_output_threshold(config.settings.tool_output_file_threshold);
+    state.set_tool_timeout(config.settings.tool_timeout_secs.map(Duration::from_secs));
+    state.set_backups_enabled(config.backups_enabled);
+    apply_org_policy(&mut state)?;
+    connect_mcp_servers(&mut state, config).await;
+    if config.settings.load_dotenv {
+        state.load_dotenv();
+    }
+    enable_redaction(&mut state, config)?;
+    enable_tool_audit(&mut state, config)?;
+    apply_permission_mode(&mut state, config);
+    load_hooks(&mut state, config);
+    state.set_session_budget(config.settings.session_budget, &config.model)?;
+    state.set_auto_compact_threshold(
+        config
+            .settings
+            .auto_compact_threshold
+            .unwrap_or(DEFAULT_AUTO_COMPACT_THRESHOLD),
+    )?;
+    state.set_summary_style(config.summary_style);
+    apply_tool_profiles(&mut state, config);
+    state.set_context_window(client.context_window());
+    if let Some(warning) = client.large_context_warning() {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(state)
 }
 
 async fn event_loop(
@@ -455,8 +591,18 @@ async fn event_loop(
 ) -> Result<()> {
     let mut events = EventStream::new();
     let mut throbber_interval = interval(Duration::from_millis(250));
+    let mut coalescer = DeltaCoalescer::from_settings(state.stream_flush());
 
     loop {
+        // Watchdog: reset a tool loop that no event can move forward
+        state.recover_wedged_tool_loop();
+
+        // Drop text buffered for a response that was cancelled
+        if coalescer.has_pending() && !state.has_streaming() {
+            coalescer.clear();
+        }
+        let flush_deadline = coalescer.deadline();
+
         if state.needs_render() {
             terminal.draw(|frame| tui::render(frame, state))?;
             state.mark_rendered();
@@ -477,16 +623,21 @@ async fn event_loop(
                         // Check if we have a pending permission - handle that first
                         if state.has_pending_permission() {
                             if let Some(response) = handle_permission_key_event(state, key) {
+                                let allowed = response.is_allowed();
+                                let reason = response.denial_reason().map(str::to_string);
+
                                 // Handle the permission response
                                 state.handle_permission_response(response).await;
 
                                 // If user allowed, continue with tool execution
-                                if matches!(
-                                    response,
-                                    PermissionResponse::AllowOnce | PermissionResponse::AllowAlways
-                                ) {
+                                if allowed {
                                     // Continue with the tool execution
                                     handle_tool_execution(state, client, session_manager).await?;
+                                } else if let Some(reason) = reason {
+                                    // Denied with a reason - send it back as the tool results
+                                    state.deny_all_tools_with_reason(&reason)?;
+                                    finish_tool_execution_and_continue(state, client, session_manager)
+                                        .await?;
                                 } else {
                                     // User denied - cancel the tool execution
                                     state.deny_all_tools()?;
@@ -499,9 +650,18 @@ async fn event_loop(
 
                         match (key.code, key.modifiers) {
                             // Exit commands
-                            (KeyCode::Char('c'), KeyModifiers::CONTROL) |
+                            (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                                if state.handle_ctrl_c() == CtrlCAction::Exit {
+                                    break;
+                                }
+                            }
                             (KeyCode::Char('d'), KeyModifiers::CONTROL) => break,
 
+                            // New line in the input: Shift+Enter (needs keyboard enhancement) or Alt+Enter
+                            (KeyCode::Enter, KeyModifiers::SHIFT | KeyModifiers::ALT) => {
+                                state.insert_newline();
+                            }
+
                             // Submit input
                             (KeyCode::Enter, KeyModifiers::NONE) if !state.input.is_empty() => {
                                 let input = state.take_input();
@@ -523,6 +683,8 @@ async fn event_loop(
                                     });
 
                                     // Display the command result
+                                    let mut resume_continuation = false;
+                                    let mut save_session = false;
                                     let response = match result {
                                         CommandResult::Executed(output) => output,
                                         CommandResult::NotACommand => {
@@ -535,6 +697,13 @@ async fn event_loop(
                                         CommandResult::Error(err) => {
                                             format!("Error: {}", err)
                                         }
+                                        CommandResult::Action(action) => {
+                                            let output = state.apply_command_action(action);
+                                            state.maybe_request_explanation(client);
+                                            save_session = true;
+                                            resume_continuation = state.take_resumable_continuation();
+                                            output
+                                        }
                                     };
 
                                     state.add_message(Message {
@@ -542,8 +711,29 @@ async fn event_loop(
                                         content: response,
                                     });
 
+                                    // Saved after the output so reports such as
+                                    // /compact's are kept with the session
+                                    if save_session {
+                                        auto_save_session(state, session_manager).await;
+                                    }
+
                                     state.mark_full_redraw();
-                                } else {
+
+                                    if resume_continuation {
+                                        finish_tool_execution_and_continue(state, client, session_manager).await?;
+                                    }
+                                } else if state.budget_paused() {
+                                    // Hold new requests until spending past the budget is confirmed
+                                    state.add_message(Message {
+                                        role: Role::User,
+                                        content: input,
+                                    });
+                                    state.add_message(Message {
+                                        role: Role::Assistant,
+                                        content: format!("Not sent. {}", state.budget_status()),
+                                    });
+                                    state.mark_full_redraw();
+                                } else if let Some(input) = state.apply_prompt_hooks(input).await {
                                     state.submit_message(client, input).await?;
                                     // Auto-save after user message
                                     auto_save_session(state, session_manager).await;
@@ -555,6 +745,12 @@ async fn event_loop(
                                 state.delete_char();
                             }
 
+                            // Move the cursor within the input
+                            (KeyCode::Left, KeyModifiers::NONE) => state.cursor_left(),
+                            (KeyCode::Right, KeyModifiers::NONE) => state.cursor_right(),
+                            (KeyCode::Up, KeyModifiers::NONE) => state.cursor_up(),
+                            (KeyCode::Down, KeyModifiers::NONE) => state.cursor_down(),
+
                             // Scroll up: Ctrl+Up, PageUp, Ctrl+k (vim-style)
                             (KeyCode::Up, KeyModifiers::CONTROL) |
                             (KeyCode::PageUp, _) |
@@ -663,13 +859,7 @@ async fn event_loop(
                                 debug!(modifier = ?modifiers, "paste triggered");
                                 match tui::clipboard::paste_from_clipboard() {
                                     Ok(text) => {
-                                        // Insert clipboard text into input
-                                        for c in text.chars() {
-                                            // Skip control characters except newlines
-                                            if c == '\n' || (!c.is_control()) {
-                                                state.insert_char(c);
-                                            }
-                                        }
+                                        state.paste(&text);
                                         info!(len = text.len(), "Pasted from clipboard");
                                     }
                                     Err(e) => {
@@ -678,6 +868,16 @@ async fn event_loop(
                                 }
                             }
 
+                            // Cancel the response or tools in flight: Escape
+                            (KeyCode::Esc, KeyModifiers::NONE) if state.is_busy() => {
+                                state.cancel_in_flight();
+                            }
+
+                            // Dismiss the keyboard hint banner: Escape
+                            (KeyCode::Esc, KeyModifiers::NONE) if state.keyboard_hint().is_some() => {
+                                state.dismiss_keyboard_hint();
+                            }
+
                             // Clear selection: Escape
                             (KeyCode::Esc, KeyModifiers::NONE) if state.selection().has_selection() => {
                                 state.selection_mut().clear();
@@ -705,7 +905,11 @@ async fn event_loop(
                             MouseEventKind::Down(MouseButton::Left) => {
                                 // Determine which area was clicked and set focus
                                 let clicked_area =
-                                    AppState::focus_area_for_row(mouse.row, terminal_height);
+                                    AppState::focus_area_for_row(
+                                    mouse.row,
+                                    terminal_height,
+                                    state.input_height(),
+                                );
 
                                 // Update focus (clears selection if focus changes)
                                 state.set_focus_area(clicked_area);
@@ -726,25 +930,25 @@ async fn event_loop(
                                 }
                                 state.mark_full_redraw();
                             }
-                            MouseEventKind::Drag(MouseButton::Left) => {
-                                // Only update selection if content area has focus
-                                if state.focus_area() == FocusArea::Content {
-                                    let first_visible = state.scroll_state().first_visible_line();
-                                    let content_row = mouse.row.saturating_sub(1) as usize;
-                                    let pos = ContentPosition::new(
-                                        first_visible + content_row,
-                                        mouse.column.saturating_sub(1) as usize,
-                                    );
-                                    state.selection_mut().update(pos);
-                                    state.mark_full_redraw();
-                                }
+                            // Only update selection if content area has focus
+                            MouseEventKind::Drag(MouseButton::Left)
+                                if state.focus_area() == FocusArea::Content =>
+                            {
+                                let first_visible = state.scroll_state().first_visible_line();
+                                let content_row = mouse.row.saturating_sub(1) as usize;
+                                let pos = ContentPosition::new(
+                                    first_visible + content_row,
+                                    mouse.column.saturating_sub(1) as usize,
+                                );
+                                state.selection_mut().update(pos);
+                                state.mark_full_redraw();
                             }
-                            MouseEventKind::Up(MouseButton::Left) => {
-                                // Complete selection if content area has focus
-                                if state.focus_area() == FocusArea::Content {
-                                    state.selection_mut().end();
-                                    state.mark_full_redraw();
-                                }
+                            // Complete selection if content area has focus
+                            MouseEventKind::Up(MouseButton::Left)
+                                if state.focus_area() == FocusArea::Content =>
+                            {
+                                state.selection_mut().end();
+                                state.mark_full_redraw();
                             }
                             MouseEventKind::ScrollUp => {
                                 debug!("mouse scroll up");
@@ -765,7 +969,18 @@ async fn event_loop(
             // Combined into a single branch to avoid borrow checker conflicts
             Some(event) = state.recv_background_event(), if state.has_background_work() => {
                 match event {
+                    BackgroundEvent::ApiChunk(crate::api::StreamEvent::ContentDelta(text)) => {
+                        if let Some(batch) = coalescer.push(&text, std::time::Instant::now()) {
+                            state.append_chunk(crate::api::StreamEvent::ContentDelta(batch))?;
+                        }
+                    }
+
                     BackgroundEvent::ApiChunk(chunk) => {
+                        // Buffered text precedes this event, including the final chunk
+                        if let Some(batch) = coalescer.flush() {
+                            state.append_chunk(crate::api::StreamEvent::ContentDelta(batch))?;
+                        }
+
                         let is_message_complete = matches!(
                             &chunk,
                             crate::api::StreamEvent::MessageStop | crate::api::StreamEvent::MessageComplete { .. }
@@ -782,7 +997,11 @@ async fn event_loop(
 
                         // Auto-save after assistant message completes
                         if is_message_complete {
+                            if !is_tool_use_complete {
+                                state.auto_commit_worktree();
+                            }
                             auto_save_session(state, session_manager).await;
+                            state.maybe_request_title(client);
                         }
 
                         // Handle tool execution if this was a tool_use stop
@@ -802,13 +1021,59 @@ async fn event_loop(
                         if state.all_tools_complete() {
                             debug!("All tools complete, setting up continuation");
                             state.clear_tool_result_rx();
-                            finish_tool_execution_and_continue(state, client, session_manager).await?;
+                            if state.budget_paused() {
+                                debug!("Session budget exceeded, deferring continuation");
+                                state.defer_continuation();
+                            } else {
+                                finish_tool_execution_and_continue(state, client, session_manager).await?;
+                            }
                         }
                     }
+
+                    BackgroundEvent::Title(title) => {
+                        if state.apply_generated_title(title) {
+                            auto_save_session(state, session_manager).await;
+                        }
+                    }
+
+                    BackgroundEvent::Explanation(explanation) => {
+                        state.apply_explanation(explanation);
+                    }
+
+                    BackgroundEvent::PluginChanged(dir) => {
+                        state.reload_changed_plugins(dir);
+                    }
+
+                    BackgroundEvent::EditorContext(update) => {
+                        state.apply_editor_context(update);
+                    }
+
+                    BackgroundEvent::ToolProgress(progress) => {
+                        state.append_tool_progress(&progress.tool, &progress.text);
+                    }
+
+                    BackgroundEvent::McpConnection(event) => {
+                        state.apply_mcp_connection_event(event);
+                    }
+
+                    BackgroundEvent::McpTools(tools) => {
+                        state.apply_mcp_tools(tools);
+                    }
+
+                    BackgroundEvent::PermissionNeeded(request) => {
+                        debug!(tool = %request.tool_name, "Tool needs permission");
+                        state.request_tool_permission(request);
+                    }
+                }
+            }
+
+            _ = sleep_until(flush_deadline.unwrap_or_else(std::time::Instant::now).into()), if flush_deadline.is_some() => {
+                if let Some(batch) = coalescer.flush() {
+                    state.append_chunk(crate::api::StreamEvent::ContentDelta(batch))?;
                 }
             }
 
-            _ = throbber_interval.tick(), if state.is_loading() || state.has_executing_tools() => {
+            _ = throbber_interval.tick(), if state.is_loading() || state.has_executing_tools() || state.ctrl_c_exit_armed() => {
                 state.tick_throbber();
             }
         }
@@ -827,6 +1092,9 @@ async fn event_loop(
 /// 2. Auto-approves pending tools
 /// 3. Spawns tool execution in background task
 ///
+/// In the Executing state (resuming after a permission prompt) it only
+/// spawns the calls that have no result yet.
+///
 /// The event loop continues to process input while tools execute.
 /// Tool results are received via the `recv_tool_result()` channel.
 ///
@@ -834,18 +1102,20 @@ async fn event_loop(
 ///
 /// Returns an error if tool approval fails.
 fn start_tool_execution(state: &mut AppState) -> Result<()> {
-    // Only process if we're in PendingApproval state
-    if !matches!(state.tool_loop_state(), ToolLoopState::PendingApproval) {
-        debug!("Tool loop not in PendingApproval state, skipping");
-        return Ok(());
+    match state.tool_loop_state() {
+        ToolLoopState::PendingApproval => {
+            // Permissions are checked per call by the executor, which pauses
+            // for a prompt when a call needs one
+            debug!("Tool loop in PendingApproval state, approving tools");
+            state.approve_all_tools()?;
+        }
+        ToolLoopState::Executing => debug!("Resuming tool execution after permission prompt"),
+        _ => {
+            debug!("Tool loop not in PendingApproval state, skipping");
+            return Ok(());
+        }
     }
 
-    debug!("Tool loop in PendingApproval state, auto-approving tools");
-
-    // For now, auto-approve all tools
-    // TODO: In Phase 10.5.3, show permission prompt and wait for user response
-    state.approve_all_tools()?;
-
     // Spawn tool execution in background - returns immediately
     debug!("Spawning tool execution in background");
     let _handle = state.spawn_tool_execution();
@@ -873,7 +1143,6 @@ async fn finish_tool_execution_and_continue(
     client: &AnthropicClient,
     session_manager: &SessionManager,
 ) -> Result<()> {
-    use crate::api::tools::default_tools;
     use crate::api::ToolChoice;
 
     // Finish execution and get continuation data
@@ -931,11 +1200,13 @@ async fn finish_tool_execution_and_continue(
     state.set_loading(true);
     state.set_current_response(String::new());
 
+    state.maybe_auto_compact();
+    state.check_context_budget();
     let api_messages = state.api_messages().to_vec();
-    let client_clone = client.clone();
-    let tools = default_tools();
+    let client_clone = state.request_client(client);
+    let tools = state.tools();
 
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         if let Err(e) = client_clone
             .stream_message_v2_with_tools(&api_messages, Some(&tools), Some(&ToolChoice::Auto), tx)
             .await
@@ -943,6 +1214,7 @@ async fn finish_tool_execution_and_continue(
             tracing::error!("API error during tool continuation: {}", e);
         }
     });
+    state.set_stream_task(task.abort_handle());
 
     // Return immediately - the main event loop will receive chunks via recv_api_chunk()
     // When another tool_use stop is received, this function will be called again
@@ -984,7 +1256,11 @@ fn format_tool_results_for_display(user_msg: &ApiMessageV2) -> String {
                     } else {
                         result.content.clone()
                     };
-                    let prefix = if result.is_error { "Error: " } else { "" };
+                    let prefix = if result.is_failure() || !result.is_error {
+                        ""
+                    } else {
+                        "Error: "
+                    };
                     parts.push(format!("[Tool result: {}{}]", prefix, content));
                 }
             }
@@ -1018,21 +1294,25 @@ fn handle_permission_key_event(
     // Create a prompt state from the pending permission
     let request = state.pending_permission()?.clone();
     let mut prompt_state = PermissionPromptState::new(request);
+    prompt_state.set_reason(state.permission_reason().map(str::to_string));
+    let entering_reason = prompt_state.is_entering_reason();
 
     // Convert crossterm key event to char for the handler
     let key_char = match key.code {
         KeyCode::Char(c) => c,
         KeyCode::Enter => '\r',
         KeyCode::Esc => '\x1b',
-        KeyCode::Tab => '\t',
         KeyCode::Backspace => '\x08',
-        KeyCode::Left => 'h',  // vim-style navigation
-        KeyCode::Right => 'l', // vim-style navigation
+        // Navigation keys would type h/l into a denial reason
+        KeyCode::Tab if !entering_reason => '\t',
+        KeyCode::Left if !entering_reason => 'h', // vim-style navigation
+        KeyCode::Right if !entering_reason => 'l', // vim-style navigation
         _ => return None,
     };
 
     // Handle the key input
     let response = handle_permission_key(&mut prompt_state, key_char);
+    state.set_permission_reason(prompt_state.take_reason());
 
     // If we got a response, clear the pending permission
     if response.is_some() {
@@ -1184,6 +1464,36 @@ mod tests {
         assert_eq!(response, Some(PermissionResponse::Deny));
     }
 
+    #[test]
+    fn test_r_key_denies_with_typed_reason() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let request = PermissionRequest::new("Bash", Some("rm -r migrations"), "Delete");
+        state.set_pending_permission(request);
+
+        // Each key event rebuilds the prompt, so the reason lives on AppState
+        for code in [
+            KeyCode::Char('r'),
+            KeyCode::Char('n'),
+            KeyCode::Char('o'),
+            KeyCode::Left,
+        ] {
+            let key = make_key_event(code, KeyModifiers::NONE);
+            assert_eq!(handle_permission_key_event(&mut state, key), None);
+        }
+        assert_eq!(state.permission_reason(), Some("no"));
+        assert!(state.has_pending_permission());
+
+        let key = make_key_event(KeyCode::Enter, KeyModifiers::NONE);
+        let response = handle_permission_key_event(&mut state, key);
+
+        assert_eq!(
+            response,
+            Some(PermissionResponse::DenyWithReason("no".to_string()))
+        );
+        assert!(!state.has_pending_permission());
+        assert_eq!(state.permission_reason(), None);
+    }
+
     #[test]
     fn test_enter_key_confirms_default() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
@@ -0,0 +1,319 @@
+//! Guard for oversized and binary clipboard pastes.
+//!
+//! Inlining megabytes of text into the input buffer stalls rendering, so
+//! pastes above a threshold are written to a file and referenced as `@path`
+//! instead. The reference is replaced by the file's content when the message
+//! is sent. Pastes that look like binary data are rejected.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Paste size in bytes above which content is saved to a file.
+pub const DEFAULT_PASTE_FILE_THRESHOLD: usize = 100 * 1024;
+
+/// Number of leading characters inspected when checking for binary data.
+const BINARY_SAMPLE_CHARS: usize = 8192;
+
+/// What to do with a pasted string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasteOutcome {
+    /// Text to insert into the input buffer, with control characters removed.
+    Inline(String),
+
+    /// Content saved to a file that should be referenced instead.
+    Saved {
+        /// File holding the pasted content.
+        path: PathBuf,
+        /// Size of the pasted content in bytes.
+        bytes: usize,
+    },
+
+    /// Content looked binary and was discarded.
+    Binary,
+}
+
+/// Session-scoped store for pastes too large to inline.
+///
+/// Files are readable only by the user and live in a per-session directory
+/// under the data directory, which is removed when the store is dropped.
+#[derive(Debug)]
+pub struct PasteFiles {
+    dir: PathBuf,
+}
+
+impl PasteFiles {
+    /// Creates a store in a new session directory under the data directory.
+    #[must_use]
+    pub fn new() -> Self {
+        let root = ProjectDirs::from("com", "patina", "patina").map_or_else(
+            || std::env::temp_dir().join("patina-pastes"),
+            |dirs| dirs.data_dir().join("pastes"),
+        );
+        Self::with_dir(root.join(uuid::Uuid::new_v4().to_string()))
+    }
+
+    /// Creates a store that writes to `dir`, which is removed on drop.
+    #[must_use]
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the directory pastes are saved to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Saves pasted text to a new file only the user can read.
+    fn save(&self, text: &str) -> Result<PathBuf> {
+        let mut dir = std::fs::DirBuilder::new();
+        dir.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut dir, 0o700);
+        dir.create(&self.dir)
+            .with_context(|| format!("Failed to create paste directory: {}", self.dir.display()))?;
+
+        let path = self.dir.join(format!("paste-{}.txt", uuid::Uuid::new_v4()));
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(text.as_bytes()))
+            .with_context(|| format!("Failed to save paste: {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Replaces `@path` references to pastes saved by this store with the
+    /// pasted content.
+    ///
+    /// Other `@` references, including paths outside the store directory
+    /// reached through symlinks or `..`, are left unchanged.
+    #[must_use]
+    pub fn expand_references(&self, text: &str) -> String {
+        let prefix = format!("@{}", self.dir.display());
+        if !text.contains(&prefix) {
+            return text.to_string();
+        }
+
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(&prefix) {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + prefix.len()..];
+            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+            let reference = &rest[start..start + prefix.len() + end];
+            match self.read(&reference[1..]) {
+                Some(content) => expanded.push_str(&content),
+                None => expanded.push_str(reference),
+            }
+            rest = &after[end..];
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Reads a saved paste, refusing anything outside the store directory.
+    fn read(&self, path: &str) -> Option<String> {
+        let dir = self.dir.canonicalize().ok()?;
+        let path = Path::new(path).canonicalize().ok()?;
+        if !path.starts_with(&dir) {
+            return None;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to read paste");
+                None
+            }
+        }
+    }
+}
+
+impl Default for PasteFiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for PasteFiles {
+    fn drop(&mut self) {
+        if self.dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+                warn!(dir = %self.dir.display(), error = %e, "Failed to remove saved pastes");
+            }
+        }
+    }
+}
+
+/// Returns true if the text looks like binary data rather than prose or code.
+///
+/// Any NUL byte counts as binary, as does a sample where more than one in ten
+/// characters is a replacement character or a control other than whitespace.
+#[must_use]
+pub fn looks_binary(text: &str) -> bool {
+    if text.contains('\0') {
+        return true;
+    }
+
+    let mut total = 0usize;
+    let mut suspicious = 0usize;
+    for c in text.chars().take(BINARY_SAMPLE_CHARS) {
+        total += 1;
+        if c == char::REPLACEMENT_CHARACTER || (c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        {
+            suspicious += 1;
+        }
+    }
+    total > 0 && suspicious * 10 > total
+}
+
+/// Decides how a paste is inserted, saving oversized content to `store`.
+///
+/// # Errors
+///
+/// Returns an error if oversized content cannot be saved.
+pub fn prepare_paste(text: &str, threshold: usize, store: &PasteFiles) -> Result<PasteOutcome> {
+    if looks_binary(text) {
+        return Ok(PasteOutcome::Binary);
+    }
+
+    if text.len() <= threshold {
+        let inline = text
+            .chars()
+            .filter(|c| *c == '\n' || !c.is_control())
+            .collect();
+        return Ok(PasteOutcome::Inline(inline));
+    }
+
+    Ok(PasteOutcome::Saved {
+        path: store.save(text)?,
+        bytes: text.len(),
+    })
+}
+
+/// Returns the input-buffer reference for a saved paste.
+#[must_use]
+pub fn paste_reference(path: &Path) -> String {
+    format!("@{}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_small_paste_is_inlined() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        let outcome = prepare_paste("fn main() {\n\tok();\r\n}", 1024, &store).unwrap();
+
+        assert_eq!(
+            outcome,
+            PasteOutcome::Inline("fn main() {\nok();\n}".to_string())
+        );
+        assert!(!store.dir().exists());
+    }
+
+    #[test]
+    fn test_oversized_paste_is_saved_to_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        let text = "line of log output\n".repeat(100);
+
+        let outcome = prepare_paste(&text, 256, &store).unwrap();
+
+        let PasteOutcome::Saved { path, bytes } = outcome else {
+            panic!("Expected paste to be saved, got {:?}", outcome);
+        };
+        assert_eq!(bytes, text.len());
+        assert!(path.starts_with(store.dir()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert_eq!(paste_reference(&path), format!("@{}", path.display()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saved_paste_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+
+        let PasteOutcome::Saved { path, .. } = prepare_paste("secret", 1, &store).unwrap() else {
+            panic!("Expected paste to be saved");
+        };
+
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(store.dir()), 0o700);
+    }
+
+    #[test]
+    fn test_saved_pastes_are_removed_on_drop() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        prepare_paste("some text", 1, &store).unwrap();
+        let dir = store.dir().to_path_buf();
+        assert!(dir.exists());
+
+        drop(store);
+
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_expand_references_inlines_saved_pastes() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        let PasteOutcome::Saved { path, .. } = prepare_paste("log line", 1, &store).unwrap() else {
+            panic!("Expected paste to be saved");
+        };
+        let outside = temp_dir.path().join("other.txt");
+        std::fs::write(&outside, "not a paste").unwrap();
+
+        let message = format!(
+            "see {} and {}",
+            paste_reference(&path),
+            paste_reference(&outside)
+        );
+        assert_eq!(
+            store.expand_references(&message),
+            format!("see log line and @{}", outside.display())
+        );
+
+        // Traversal out of the store directory is not followed
+        let escape = format!("@{}/../other.txt", store.dir().display());
+        assert_eq!(store.expand_references(&escape), escape);
+    }
+
+    #[test]
+    fn test_binary_paste_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+
+        assert_eq!(
+            prepare_paste("PK\u{3}\u{4}\0\0data", 1024, &store).unwrap(),
+            PasteOutcome::Binary
+        );
+        // Binary content is rejected before the size check
+        let garbled = "\u{FFFD}\u{1}\u{2}abc".repeat(1000);
+        assert_eq!(
+            prepare_paste(&garbled, 16, &store).unwrap(),
+            PasteOutcome::Binary
+        );
+        assert!(!store.dir().exists());
+    }
+
+    #[test]
+    fn test_looks_binary_allows_text() {
+        assert!(!looks_binary(""));
+        assert!(!looks_binary("plain text\twith tabs\r\nand newlines"));
+        assert!(!looks_binary("unicode: héllo wörld ✓"));
+    }
+}
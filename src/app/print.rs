@@ -0,0 +1,594 @@
+//! Print mode: answer one prompt and exit.
+//!
+//! `patina -p "<prompt>"` sends the prompt, runs any tools Claude requests
+//! without prompting, and exits when Claude is done. By default the response
+//! is streamed to stdout as it arrives. With `--output-format json` nothing is
+//! printed until the run ends, and then a single [`PrintReport`] object:
+//!
+//! ```json
+//! {
+//!   "status": "finished",
+//!   "result": "The tests pass.",
+//!   "stop_reason": "end_turn",
+//!   "tool_calls": [
+//!     {
+//!       "id": "toolu_01",
+//!       "name": "bash",
+//!       "input": { "command": "cargo test" },
+//!       "output": "test result: ok. 12 passed",
+//!       "is_error": false
+//!     }
+//!   ],
+//!   "usage": { "input_tokens": 2310, "output_tokens": 96 },
+//!   "error": null
+//! }
+//! ```
+//!
+//! If the API or a tool fails, the run is aborted: the status is `"aborted"`,
+//! `error` says why, and patina exits non-zero in either format.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::state::AppState;
+use super::tool_loop::ToolLoopState;
+use super::{
+    format_tool_results_for_display, headless_client, headless_state, Config, REFUSAL_NOTICE,
+    STREAMING_CHANNEL_BUFFER,
+};
+use crate::api::{AnthropicClient, StreamEvent, ToolChoice};
+use crate::types::config::OutputFormat;
+use crate::types::{ApiMessageV2, ContentBlock, Message, Role, StopReason};
+
+/// How a print mode run ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrintStatus {
+    /// Claude finished responding.
+    #[default]
+    Finished,
+    /// The run stopped early because the API or a tool failed.
+    Aborted,
+}
+
+/// Tokens used across every request of a print mode run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PrintUsage {
+    /// Input tokens, summed over all requests.
+    pub input_tokens: u64,
+    /// Output tokens, summed over all requests.
+    pub output_tokens: u64,
+}
+
+/// A tool call made during a print mode run.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintToolCall {
+    /// ID of the `tool_use` block.
+    pub id: String,
+    /// Tool name.
+    pub name: String,
+    /// Input Claude passed to the tool.
+    pub input: Value,
+    /// Output sent back to Claude.
+    pub output: String,
+    /// Whether the tool failed.
+    pub is_error: bool,
+}
+
+/// Outcome of a print mode run, printed as JSON by `--output-format json`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrintReport {
+    /// Whether the run finished or was aborted.
+    pub status: PrintStatus,
+    /// Text of every response from Claude, separated by blank lines.
+    pub result: String,
+    /// Stop reason of the last response, if one completed.
+    pub stop_reason: Option<StopReason>,
+    /// Tool calls in the order they ran.
+    pub tool_calls: Vec<PrintToolCall>,
+    /// Token usage.
+    pub usage: PrintUsage,
+    /// Why the run ended early, if it did.
+    pub error: Option<String>,
+}
+
+impl PrintReport {
+    /// Checks that the run finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns the run's error, which exits the process non-zero, if the run
+    /// was aborted.
+    pub fn ensure_finished(&self) -> Result<()> {
+        match self.status {
+            PrintStatus::Finished => Ok(()),
+            PrintStatus::Aborted => {
+                anyhow::bail!("{}", self.error.as_deref().unwrap_or("print mode aborted"))
+            }
+        }
+    }
+}
+
+/// Runs `prompt` in print mode and writes the output in `config.output_format`.
+///
+/// # Errors
+///
+/// Returns an error if setup fails or the run ended with an error. In JSON
+/// mode the report, including the error, is printed first.
+pub async fn run_print_mode(config: &Config, prompt: &str) -> Result<()> {
+    let client = headless_client(config);
+    let mut state = headless_state(config, &client).await?;
+
+    let report = run_print(&mut state, &client, prompt, config.output_format).await;
+    if config.output_format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+    report.ensure_finished()
+}
+
+/// Sends `prompt`, executing tools until Claude is done, and reports the run.
+///
+/// In [`OutputFormat::Text`] the response is streamed to stdout as it
+/// arrives.
+pub async fn run_print(
+    state: &mut AppState,
+    client: &AnthropicClient,
+    prompt: &str,
+    format: OutputFormat,
+) -> PrintReport {
+    let mut report = PrintReport::default();
+    let echo = format == OutputFormat::Text;
+    if let Err(e) = drive(state, client, prompt, echo, &mut report).await {
+        report.status = PrintStatus::Aborted;
+        report.error = Some(e.to_string());
+    }
+    report
+}
+
+/// Runs the conversation for `prompt`, filling in `report` as it goes.
+async fn drive(
+    state: &mut AppState,
+    client: &AnthropicClient,
+    prompt: &str,
+    echo: bool,
+    report: &mut PrintReport,
+) -> Result<()> {
+    // UserPromptSubmit hooks may rewrite the prompt or block it
+    let prompt = state
+        .run_prompt_hooks(prompt)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    // Add the user's prompt (adds to both display and API messages via submit logic)
+    state.add_message(Message {
+        role: Role::User,
+        content: prompt.clone(),
+    });
+    state.api_messages_mut().push(ApiMessageV2::user(&prompt));
+
+    let mut rx = start_stream(state, client);
+    let response = process_print_stream(&mut rx, state, echo, report).await?;
+
+    // If there are no tool uses, add the assistant message to both display and API
+    if !response.is_empty() && !matches!(state.tool_loop_state(), ToolLoopState::PendingApproval) {
+        state.add_message(Message {
+            role: Role::Assistant,
+            content: response.clone(),
+        });
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::assistant(&response));
+    }
+
+    // Handle any tool execution if needed
+    while matches!(state.tool_loop_state(), ToolLoopState::PendingApproval) {
+        // There is no one to confirm continuing past the budget, so stop
+        if state.budget_paused() {
+            anyhow::bail!("{}", state.budget_status());
+        }
+
+        // Auto-approve all tools in non-interactive mode
+        state.approve_all_tools()?;
+
+        // Execute the tools
+        let needs_permission = state.execute_pending_tools().await?;
+
+        // Check if any tools still need permission
+        if !needs_permission.is_empty() {
+            anyhow::bail!("tools need permission: {}", needs_permission.join(", "));
+        }
+
+        // Finish execution and get continuation data
+        let continuation = state.finish_tool_execution()?;
+
+        // Build the messages for the conversation
+        let (assistant_msg, user_msg) = continuation.build_messages();
+        record_tool_calls(&assistant_msg, &user_msg, report);
+
+        // Add to API message history for conversation continuation
+        // Note: The assistant message is NOT added to the timeline here because
+        // finalize_streaming_for_tool_use() already converted the streaming entry
+        // to an AssistantMessage. Adding it again would cause duplicate messages.
+        state.api_messages_mut().push(assistant_msg);
+
+        // Add tool results to both timeline (for display) and API (for continuation)
+        state.add_message(Message {
+            role: Role::User,
+            content: format_tool_results_for_display(&user_msg),
+        });
+        state.api_messages_mut().push(user_msg);
+
+        state.tool_loop_mut().start_streaming()?;
+
+        let mut rx = start_stream(state, client);
+        process_print_stream(&mut rx, state, echo, report).await?;
+    }
+
+    Ok(())
+}
+
+/// Starts streaming a response to the current API messages.
+fn start_stream(state: &AppState, client: &AnthropicClient) -> mpsc::Receiver<StreamEvent> {
+    let (tx, rx) = mpsc::channel(STREAMING_CHANNEL_BUFFER);
+    let api_messages = state.api_messages().to_vec();
+    let client = state.request_client(client);
+    let tools = state.tools();
+
+    tokio::spawn(async move {
+        let error_tx = tx.clone();
+        if let Err(e) = client
+            .stream_message_v2_with_tools(&api_messages, Some(&tools), Some(&ToolChoice::Auto), tx)
+            .await
+        {
+            tracing::error!("API error: {}", e);
+            // Requests that fail before streaming send no error event
+            error_tx.send(StreamEvent::Error(e.to_string())).await.ok();
+        }
+    });
+
+    rx
+}
+
+/// Processes a print mode stream, handling tool use events.
+///
+/// Text is printed as it arrives when `echo` is set. Returns the text of
+/// the response.
+///
+/// # Errors
+///
+/// Returns an error if the stream reports an API error, ends before the
+/// message completes, or leaves the tool loop in an error state.
+async fn process_print_stream(
+    rx: &mut mpsc::Receiver<StreamEvent>,
+    state: &mut AppState,
+    echo: bool,
+    report: &mut PrintReport,
+) -> Result<String> {
+    let mut response = String::new();
+    let mut completed = false;
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            StreamEvent::ContentDelta(text) => {
+                if echo {
+                    print!("{}", text);
+                }
+                response.push_str(&text);
+            }
+            StreamEvent::MessageStop | StreamEvent::MessageComplete { .. } => {
+                if echo && !response.is_empty() {
+                    println!(); // Newline after response
+                }
+                report.stop_reason = event.stop_reason();
+                if let Some(stop_reason) = event.stop_reason() {
+                    if stop_reason.is_refusal() {
+                        eprintln!("{}", REFUSAL_NOTICE);
+                    }
+                    // Moves the tool loop to pending approval on tool use
+                    state.handle_message_complete(stop_reason)?;
+                }
+                if let ToolLoopState::Error(e) = state.tool_loop_state() {
+                    anyhow::bail!("{e}");
+                }
+                completed = true;
+                break;
+            }
+            StreamEvent::Error(e) => anyhow::bail!("API error: {}", e),
+            StreamEvent::ToolUseStart { id, name, index } => {
+                state.tool_loop_mut().start_streaming().ok();
+                state.handle_tool_use_start(id, name, index);
+            }
+            StreamEvent::ToolUseInputDelta {
+                index,
+                partial_json,
+            } => {
+                state.handle_tool_use_input_delta(index, &partial_json);
+            }
+            StreamEvent::ToolUseComplete { index } => {
+                state.handle_tool_use_complete(index)?;
+            }
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+            } => {
+                report.usage.input_tokens += u64::from(input_tokens);
+                report.usage.output_tokens += u64::from(output_tokens);
+                state.record_api_usage(input_tokens, output_tokens);
+            }
+            _ => {}
+        }
+    }
+
+    if !completed {
+        anyhow::bail!("API error: response ended before the message completed");
+    }
+    if !response.is_empty() {
+        if !report.result.is_empty() {
+            report.result.push_str("\n\n");
+        }
+        report.result.push_str(&response);
+    }
+    Ok(response)
+}
+
+/// Adds the tool calls of one round, with their results, to `report`.
+fn record_tool_calls(
+    assistant_msg: &ApiMessageV2,
+    user_msg: &ApiMessageV2,
+    report: &mut PrintReport,
+) {
+    let results = user_msg.content.as_blocks().unwrap_or_default();
+    for block in assistant_msg.content.as_blocks().unwrap_or_default() {
+        let ContentBlock::ToolUse(tool_use) = block else {
+            continue;
+        };
+        let result = results.iter().find_map(|block| match block {
+            ContentBlock::ToolResult(result) if result.tool_use_id == tool_use.id => Some(result),
+            _ => None,
+        });
+        report.tool_calls.push(PrintToolCall {
+            id: tool_use.id.clone(),
+            name: tool_use.name.clone(),
+            input: tool_use.input.clone(),
+            output: result.map(|r| r.content.clone()).unwrap_or_default(),
+            is_error: result.is_some_and(|r| r.is_error),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::RetryPolicy;
+    use crate::types::config::ParallelMode;
+    use serde_json::json;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const MODEL: &str = "claude-sonnet-4-20250514";
+
+    fn sse(events: &[Value]) -> String {
+        events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {}\n\n",
+                    event["type"].as_str().unwrap(),
+                    event
+                )
+            })
+            .collect()
+    }
+
+    fn tool_round(id: &str, command: &str) -> String {
+        sse(&[
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": 100}}}),
+            json!({"type": "content_block_start", "index": 0,
+                   "content_block": {"type": "tool_use", "id": id, "name": "bash"}}),
+            json!({"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "input_json_delta",
+                             "partial_json": json!({"command": command}).to_string()}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"},
+                   "usage": {"output_tokens": 10}}),
+            json!({"type": "message_stop"}),
+        ])
+    }
+
+    fn final_answer(text: &str) -> String {
+        sse(&[
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": 150}}}),
+            json!({"type": "content_block_start", "index": 0,
+                   "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "text_delta", "text": text}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"},
+                   "usage": {"output_tokens": 5}}),
+            json!({"type": "message_stop"}),
+        ])
+    }
+
+    /// Mounts responses that are served once each, in order.
+    async fn mount_in_order(server: &MockServer, responses: &[String]) {
+        for body in responses {
+            Mock::given(method("POST"))
+                .and(path("/v1/messages"))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_raw(body.clone(), "text/event-stream"),
+                )
+                .up_to_n_times(1)
+                .mount(server)
+                .await;
+        }
+    }
+
+    fn setup(server: &MockServer, temp_dir: &TempDir) -> (AppState, AnthropicClient) {
+        let client = AnthropicClient::new_with_base_url(
+            secrecy::SecretString::from("key"),
+            MODEL,
+            &server.uri(),
+        );
+        let state = AppState::new(temp_dir.path().to_path_buf(), true, ParallelMode::Enabled);
+        (state, client)
+    }
+
+    #[tokio::test]
+    async fn test_json_report_has_response_tool_calls_and_usage() {
+        let server = MockServer::start().await;
+        mount_in_order(
+            &server,
+            &[
+                tool_round("toolu_1", "echo hello"),
+                final_answer("It printed hello."),
+            ],
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir);
+
+        let report = run_print(&mut state, &client, "run echo", OutputFormat::Json).await;
+        let json: Value = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+
+        assert_eq!(json["status"], "finished");
+        assert_eq!(json["result"], "It printed hello.");
+        assert_eq!(json["stop_reason"], "end_turn");
+        assert_eq!(json["error"], Value::Null);
+        assert_eq!(json["usage"]["input_tokens"], 250);
+        assert_eq!(json["usage"]["output_tokens"], 15);
+
+        let calls = json["tool_calls"].as_array().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0]["id"], "toolu_1");
+        assert_eq!(calls[0]["name"], "bash");
+        assert_eq!(calls[0]["input"]["command"], "echo hello");
+        assert!(calls[0]["output"].as_str().unwrap().contains("hello"));
+        assert_eq!(calls[0]["is_error"], false);
+        assert!(report.ensure_finished().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_hooks_rewrite_the_prompt() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_string_contains("rewritten by hook"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(final_answer("Rewritten."), "text/event-stream"),
+            )
+            .mount(&server)
+            .await;
+        let temp_dir = TempDir::new().unwrap();
+        let hooks = temp_dir.path().join("hooks.toml");
+        std::fs::write(
+            &hooks,
+            r#"[[UserPromptSubmit]]
+hooks = [{ type = "command", command = "echo '{\"prompt\": \"rewritten by hook\"}'" }]
+"#,
+        )
+        .unwrap();
+        let (mut state, client) = setup(&server, &temp_dir);
+        state.load_hooks(Some(&hooks), false);
+
+        let report = run_print(&mut state, &client, "original", OutputFormat::Json).await;
+
+        report.ensure_finished().unwrap();
+        assert_eq!(report.result, "Rewritten.");
+    }
+
+    #[tokio::test]
+    async fn test_json_report_includes_api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "type": "error",
+                "error": {"type": "invalid_request_error", "message": "prompt is too long"}
+            })))
+            .mount(&server)
+            .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir);
+
+        let report = run_print(&mut state, &client, "hello", OutputFormat::Json).await;
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["status"], "aborted");
+        assert!(json["error"].as_str().unwrap().contains("API error"));
+        assert_eq!(json["stop_reason"], Value::Null);
+        assert_eq!(json["result"], "");
+    }
+
+    #[tokio::test]
+    async fn test_server_error_aborts_with_nonzero_exit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(ResponseTemplate::new(500).set_body_json(json!({
+                "type": "error",
+                "error": {"type": "api_error", "message": "Internal server error"}
+            })))
+            .mount(&server)
+            .await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir);
+        let client = client.with_retry_policy(RetryPolicy::new(
+            0,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+        ));
+
+        for format in [OutputFormat::Text, OutputFormat::Json] {
+            let report = run_print(&mut state, &client, "hello", format).await;
+
+            assert_eq!(report.status, PrintStatus::Aborted);
+            assert!(report.error.as_deref().unwrap().contains("500"));
+            // run_print_mode returns this error from main, exiting non-zero
+            let err = report.ensure_finished().unwrap_err();
+            assert!(err.to_string().contains("500"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_ending_early_aborts() {
+        let server = MockServer::start().await;
+        let truncated = sse(&[
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": 10}}}),
+            json!({"type": "content_block_start", "index": 0,
+                   "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "text_delta", "text": "Partial"}}),
+        ]);
+        mount_in_order(&server, &[truncated]).await;
+        let temp_dir = TempDir::new().unwrap();
+        let (mut state, client) = setup(&server, &temp_dir);
+
+        let report = run_print(&mut state, &client, "hello", OutputFormat::Json).await;
+
+        assert_eq!(report.status, PrintStatus::Aborted);
+        assert!(report.ensure_finished().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tool_needing_permission_aborts() {
+        let server = MockServer::start().await;
+        mount_in_order(&server, &[tool_round("toolu_1", "rm -rf build")]).await;
+        let temp_dir = TempDir::new().unwrap();
+        let (_, client) = setup(&server, &temp_dir);
+        let mut state = AppState::new(temp_dir.path().to_path_buf(), false, ParallelMode::Enabled);
+
+        let report = run_print(&mut state, &client, "clean up", OutputFormat::Json).await;
+
+        assert_eq!(report.status, PrintStatus::Aborted);
+        assert!(
+            report.error.as_deref().unwrap().contains("need permission"),
+            "{:?}",
+            report.error
+        );
+    }
+}
@@ -1,27 +1,67 @@
 //! Application state management
 
 use crate::agents::SubagentSpawner;
-use crate::api::tools::default_tools;
-use crate::api::{AnthropicClient, StreamEvent, TokenBudget, ToolChoice};
+use crate::api::tools::{read_mcp_resource_tool, tools_for_model, ToolDefinition};
+use crate::api::{
+    compact_or_truncate_context_with_style, estimate_messages_tokens, model_context_limit,
+    preserve_count_for, AnthropicClient, BudgetStatus, CompactionConfig, ContextCompactor,
+    StreamEvent, SummaryStyle, TokenBudget, ToolChoice, AUTO_COMPACT_PRESERVE_RECENT,
+    DEFAULT_AUTO_COMPACT_THRESHOLD, DEFAULT_MAX_INPUT_TOKENS, LARGE_CONTEXT_WINDOW,
+};
+use crate::app::budget::{BudgetNotice, SessionBudget};
+use crate::app::commands::CommandAction;
+use crate::app::explain::{LastError, EXPLAIN_MAX_TOKENS};
+use crate::app::interrupt::{CtrlCAction, CtrlCHandler};
+use crate::app::paste::{
+    paste_reference, prepare_paste, PasteFiles, PasteOutcome, DEFAULT_PASTE_FILE_THRESHOLD,
+};
 use crate::app::tool_loop::{ContinuationData, ToolLoop, ToolLoopState};
-use crate::app::STREAMING_CHANNEL_BUFFER;
-use crate::hooks::HookManager;
+use crate::app::worktree::commit_message_for_turn;
+use crate::app::{
+    CANCELLED_NOTICE, MAX_INPUT_LINES, MCP_PROGRESS_BUFFER, REFUSAL_NOTICE,
+    STREAMING_CHANNEL_BUFFER,
+};
+use crate::enterprise::policy::OrgPolicy;
+use crate::enterprise::tool_audit::{ToolAuditConfig, ToolAuditLog, ToolAuditStatus};
+use crate::hooks::{HookDecision, HookManager, HookResult, PromptHookResult};
+use crate::ide::notify::IdeNotifier;
+use crate::ide::rpc::{EditorContextUpdate, RpcNotification};
+use crate::mcp::client::McpConnectionEvent;
+use crate::mcp::{McpManager, McpToolProgress};
 use crate::narsil::context::ContextSuggestion;
-use crate::permissions::{PermissionManager, PermissionRequest, PermissionResponse};
-use crate::plugins::PluginRegistry;
-use crate::session::Session;
-use crate::tools::{HookedToolExecutor, ParallelConfig};
+use crate::permissions::allowlist::Allowlist;
+use crate::permissions::patterns::normalize_path;
+use crate::permissions::{
+    PermissionManager, PermissionRequest, PermissionResponse, PermissionRule,
+};
+use crate::plugins::watcher::PluginWatcher;
+use crate::plugins::{plugin_search_paths, PluginRegistry};
+use crate::session::{ContextResource, Session, SessionContext, WorktreeSession, TITLE_MAX_TOKENS};
+use crate::skills::{skill_search_paths, ActivationContext, SkillEngine, MAX_SKILL_PROMPT_CHARS};
+use crate::tools::parallel::ToolSafetyClass;
+use crate::tools::vision::VisionConfig;
+use crate::tools::web_fetch::WebFetchConfig;
+use crate::tools::web_search::WebSearchConfig;
+use crate::tools::{HookedToolExecutor, ParallelConfig, ToolExecutor};
 use crate::tui::scroll::ScrollState;
 use crate::tui::selection::{FocusArea, SelectionState};
 use crate::tui::widgets::{CompactionProgressState, ToolBlockState};
-use crate::types::config::ParallelMode;
+use crate::types::config::{
+    CtrlCMode, ParallelMode, PathConfinement, PermissionMode, ThinkingDisplay,
+};
 use crate::types::content::StopReason;
-use crate::types::{ApiMessageV2, Message, Role, Timeline};
+use crate::types::settings::{StreamFlush, ToolProfile};
+use crate::types::{ApiMessageV2, Message, RequestParams, RequestPreset, Role, Timeline};
+use crate::util::format_bytes;
+use crate::util::redact::Redactor;
+use crate::worktree::WorktreeManager;
 use anyhow::Result;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
+use tokio::task::AbortHandle;
 
 /// Formats tool input JSON into a readable string for display.
 ///
@@ -89,6 +129,21 @@ fn compact_json(value: &Value) -> &str {
     }
 }
 
+/// Returns the API definitions of the tools the MCP servers provide.
+///
+/// Includes `read_mcp_resource` when a server exposes resources.
+fn mcp_tool_definitions(manager: &McpManager) -> Vec<ToolDefinition> {
+    let mut tools: Vec<ToolDefinition> = manager
+        .get_tools()
+        .iter()
+        .map(|tool| ToolDefinition::new(&tool.name, &tool.description, tool.input_schema.clone()))
+        .collect();
+    if manager.has_resources() {
+        tools.push(read_mcp_resource_tool());
+    }
+    tools
+}
+
 /// Events received from background tasks (API streaming or tool execution).
 ///
 /// Used by `recv_background_event()` to return events from multiple channels
@@ -99,6 +154,22 @@ pub enum BackgroundEvent {
     ApiChunk(StreamEvent),
     /// A tool execution completed with its result.
     ToolResult(String, crate::types::ToolResultBlock),
+    /// Background title generation finished (`None` if it failed).
+    Title(Option<String>),
+    /// An explanation requested with `/explain` arrived (`None` if the task ended early).
+    Explanation(Option<String>),
+    /// Files in a plugin directory changed (with `--watch-plugins`).
+    PluginChanged(PathBuf),
+    /// An IDE reported the user's selection or open files.
+    EditorContext(EditorContextUpdate),
+    /// A running MCP tool reported a partial result.
+    ToolProgress(McpToolProgress),
+    /// An MCP server disconnected, reconnected or was given up on.
+    McpConnection(McpConnectionEvent),
+    /// The MCP tool list was refreshed after a reconnect (`None` if the task ended early).
+    McpTools(Option<Vec<ToolDefinition>>),
+    /// A tool call needs the user's permission; execution paused before it.
+    PermissionNeeded(PermissionRequest),
 }
 
 pub struct AppState {
@@ -116,6 +187,8 @@ pub struct AppState {
     loading: bool,
     throbber_frame: usize,
     streaming_rx: Option<mpsc::Receiver<StreamEvent>>,
+    /// Task streaming the current response, aborted on cancel.
+    stream_task: Option<AbortHandle>,
 
     dirty: DirtyFlags,
 
@@ -124,9 +197,85 @@ pub struct AppState {
     worktree_modified: usize,
     worktree_ahead: usize,
     worktree_behind: usize,
+    /// Worktree the session was moved into with `/worktree`, if any.
+    worktree_session: Option<WorktreeSession>,
+    /// Commit worktree changes after each turn that edited files.
+    auto_commit_worktree: bool,
+    /// Files changed by `write_file` and `edit` calls during the current turn.
+    turn_edited_files: Vec<PathBuf>,
 
     // Session tracking for auto-save
     session_id: Option<String>,
+    session_tags: Vec<String>,
+
+    /// Session name assigned with `/save <name>`.
+    session_name: Option<String>,
+
+    /// Session title generated after the first exchange.
+    session_title: Option<String>,
+
+    /// Files read and skills active in this session, kept across saves.
+    session_context: Option<SessionContext>,
+
+    /// Model used to generate session titles; `None` disables generation.
+    title_model: Option<String>,
+
+    /// Paste size in bytes above which content is saved to a file.
+    paste_threshold: usize,
+
+    /// Oversized pastes of this session, removed on exit.
+    pastes: PasteFiles,
+
+    /// How streamed text deltas are batched before rendering.
+    stream_flush: StreamFlush,
+
+    /// One-time keyboard hint shown until dismissed.
+    keyboard_hint: Option<String>,
+
+    /// Whether title generation has been attempted for this session.
+    title_requested: bool,
+
+    /// Receives the generated title from the background task.
+    title_rx: Option<mpsc::UnboundedReceiver<String>>,
+
+    /// Most recent tool or API error, for `/explain`.
+    last_error: Option<LastError>,
+
+    /// Whether `/explain` is waiting for its request to be sent.
+    explain_requested: bool,
+
+    /// Receives the explanation from the background task.
+    explain_rx: Option<mpsc::UnboundedReceiver<String>>,
+
+    /// Named request parameter presets from settings.
+    presets: HashMap<String, RequestParams>,
+
+    /// Preset applied with `/preset`, recorded on the session.
+    active_preset: Option<RequestPreset>,
+
+    /// Tool choice set with `/force-tool`, used for the next submitted message only.
+    forced_tool_choice: Option<ToolChoice>,
+
+    /// How model reasoning is rendered in the timeline.
+    thinking_display: ThinkingDisplay,
+
+    /// Model for API requests, switched with `/model`; `None` uses the client's.
+    model: Option<String>,
+
+    /// Tools never advertised to the model.
+    disabled_tools: Vec<String>,
+
+    /// Per-model tool profiles keyed by model name prefix.
+    tool_profiles: HashMap<String, ToolProfile>,
+
+    /// Estimated spend against the `session_budget` setting, if one is set.
+    session_budget: Option<SessionBudget>,
+
+    /// Whether a tool-result continuation is held back by an exceeded budget.
+    continuation_deferred: bool,
+
+    /// Decides whether Ctrl+C cancels the current turn or exits.
+    ctrl_c: CtrlCHandler,
 
     // Tool execution state
     tool_loop: ToolLoop,
@@ -134,6 +283,15 @@ pub struct AppState {
     permission_manager: Arc<Mutex<PermissionManager>>,
     pending_permission: Option<PermissionRequest>,
 
+    /// Denial reason being typed in the permission prompt, if any.
+    permission_reason: Option<String>,
+
+    /// Baseline permission mode, mirrored from the permission manager for display.
+    permission_mode: PermissionMode,
+
+    /// Directory rule from settings that selected the permission mode, if any.
+    permission_mode_rule: Option<String>,
+
     /// Tool blocks for UI display.
     /// Each block represents a tool execution with its name, input, and result.
     tool_blocks: Vec<ToolBlockState>,
@@ -147,6 +305,11 @@ pub struct AppState {
     /// When set, tool execution runs in the background and results
     /// are streamed back through this channel.
     tool_result_rx: Option<mpsc::UnboundedReceiver<(String, crate::types::ToolResultBlock)>>,
+    /// Task executing the current tool calls, aborted on cancel.
+    tool_task: Option<AbortHandle>,
+
+    /// Permission request from the tool execution task, if it paused.
+    permission_rx: Option<mpsc::UnboundedReceiver<PermissionRequest>>,
 
     /// Set of tool IDs currently being executed.
     /// Used to track which tools are in-flight for progress display.
@@ -171,6 +334,19 @@ pub struct AppState {
     /// Displays usage in the status bar with color-coded warnings.
     token_budget: TokenBudget,
 
+    /// Input token limit that history is truncated to before each request.
+    context_window: usize,
+
+    /// Fraction of the context window at which older history is compacted.
+    auto_compact_threshold: f64,
+
+    /// How compacted history is summarized, chosen with `/compact <style>`.
+    summary_style: SummaryStyle,
+
+    /// How the last request compared to the context window when it was sent.
+    /// A warning banner is shown while it is not `Ok`.
+    context_status: BudgetStatus,
+
     /// Optional compaction progress state for displaying the compaction overlay.
     /// When set, the compaction progress widget is shown as a modal.
     compaction_state: Option<CompactionProgressState>,
@@ -179,6 +355,39 @@ pub struct AppState {
     /// Loaded from `~/.config/patina/plugins/` on startup unless disabled.
     plugin_registry: PluginRegistry,
 
+    /// Watcher for plugin hot-reload, kept alive while `--watch-plugins` is on.
+    plugin_watcher: Option<PluginWatcher>,
+
+    /// Plugin directories reported changed by the watcher.
+    plugin_changes_rx: Option<mpsc::UnboundedReceiver<PathBuf>>,
+
+    /// Editor context reported by a connected IDE (with `--ide-port`).
+    ide_context_rx: Option<mpsc::UnboundedReceiver<EditorContextUpdate>>,
+
+    /// Pushes file changes to connected IDE clients (with `--ide-port`).
+    ide_notifier: Option<IdeNotifier>,
+
+    /// MCP servers configured in `[mcp_servers]`, once connected.
+    mcp: Option<Arc<Mutex<McpManager>>>,
+
+    /// Definitions of the connected MCP servers' tools.
+    mcp_tools: Vec<ToolDefinition>,
+
+    /// Partial results from running MCP tools, shown in the timeline.
+    mcp_progress_rx: Option<mpsc::Receiver<McpToolProgress>>,
+
+    /// Disconnect and reconnect notices from the MCP servers.
+    mcp_events_rx: Option<mpsc::UnboundedReceiver<McpConnectionEvent>>,
+
+    /// Receives the MCP tool list refreshed after a server reconnected.
+    mcp_tools_rx: Option<mpsc::UnboundedReceiver<Vec<ToolDefinition>>>,
+
+    /// Skills available for activation.
+    skill_engine: SkillEngine,
+
+    /// Skills turned off with `/skill off`, never auto-activated again.
+    suppressed_skills: Vec<String>,
+
     /// Optional subagent spawner for creating subagent sessions.
     /// Only initialized when subagent orchestration is enabled via `--enable-subagents`.
     subagent_spawner: Option<SubagentSpawner>,
@@ -262,7 +471,7 @@ impl AppState {
     ) -> Self {
         // Generate a unique session ID for hooks
         let hook_session_id = uuid::Uuid::new_v4().to_string();
-        let hook_manager = HookManager::new(hook_session_id);
+        let hook_manager = HookManager::new(hook_session_id).with_working_dir(working_dir.clone());
 
         // Create permission manager with skip_permissions setting
         let mut pm = PermissionManager::new();
@@ -276,20 +485,23 @@ impl AppState {
             ParallelMode::Aggressive => ParallelConfig::aggressive(),
         };
 
-        // Create tool executor with hook, permission, and parallel configuration
-        let tool_executor = Arc::new(
-            HookedToolExecutor::new(working_dir.clone(), hook_manager)
-                .with_permissions(Arc::clone(&permission_manager))
-                .with_parallel_config(parallel_config),
-        );
-
         // Load plugins if enabled
         let plugin_registry = if plugins_enabled {
-            Self::load_plugins()
+            Self::load_plugins(&working_dir)
         } else {
             PluginRegistry::new()
         };
 
+        let mut skill_engine = SkillEngine::new();
+        skill_engine.load_all(&skill_search_paths(&working_dir));
+
+        // Create tool executor with hook, permission, and parallel configuration
+        let mut tool_executor = HookedToolExecutor::new(working_dir.clone(), hook_manager)
+            .with_permissions(Arc::clone(&permission_manager))
+            .with_parallel_config(parallel_config);
+        tool_executor.set_plugin_tools(plugin_registry.tools().clone());
+        let tool_executor = Arc::new(tool_executor);
+
         // Initialize subagent spawner if enabled
         let subagent_spawner = if subagents_enabled {
             Some(SubagentSpawner::new())
@@ -306,6 +518,7 @@ impl AppState {
             loading: false,
             throbber_frame: 0,
             streaming_rx: None,
+            stream_task: None,
             dirty: DirtyFlags {
                 full: true,
                 ..Default::default()
@@ -314,22 +527,69 @@ impl AppState {
             worktree_modified: 0,
             worktree_ahead: 0,
             worktree_behind: 0,
+            worktree_session: None,
+            auto_commit_worktree: false,
+            turn_edited_files: Vec::new(),
             session_id: None,
+            session_tags: Vec::new(),
+            session_name: None,
+            session_title: None,
+            session_context: None,
+            title_model: None,
+            paste_threshold: DEFAULT_PASTE_FILE_THRESHOLD,
+            pastes: PasteFiles::new(),
+            stream_flush: StreamFlush::default(),
+            keyboard_hint: None,
+            title_requested: false,
+            title_rx: None,
+            last_error: None,
+            explain_requested: false,
+            explain_rx: None,
+            presets: HashMap::new(),
+            active_preset: None,
+            forced_tool_choice: None,
+            thinking_display: ThinkingDisplay::default(),
+            model: None,
+            disabled_tools: Vec::new(),
+            tool_profiles: HashMap::new(),
+            session_budget: None,
+            continuation_deferred: false,
+            ctrl_c: CtrlCHandler::default(),
             tool_loop: ToolLoop::new(),
             tool_executor,
             permission_manager,
             pending_permission: None,
+            permission_reason: None,
+            permission_mode: PermissionMode::default(),
+            permission_mode_rule: None,
             tool_blocks: Vec::new(),
             timeline: Timeline::new(),
             tool_result_rx: None,
+            tool_task: None,
+            permission_rx: None,
             executing_tool_ids: std::collections::HashSet::new(),
             selection: SelectionState::new(),
             copy_pending: false,
             rendered_lines_cache: Vec::new(),
             focus_area: FocusArea::default(),
-            token_budget: TokenBudget::new(100_000), // Claude's typical context window
+            token_budget: TokenBudget::new(DEFAULT_MAX_INPUT_TOKENS),
+            context_window: DEFAULT_MAX_INPUT_TOKENS,
+            auto_compact_threshold: DEFAULT_AUTO_COMPACT_THRESHOLD,
+            summary_style: SummaryStyle::default(),
+            context_status: BudgetStatus::Ok,
             compaction_state: None,
             plugin_registry,
+            plugin_watcher: None,
+            plugin_changes_rx: None,
+            ide_context_rx: None,
+            ide_notifier: None,
+            mcp: None,
+            mcp_tools: Vec::new(),
+            mcp_progress_rx: None,
+            mcp_events_rx: None,
+            mcp_tools_rx: None,
+            skill_engine,
+            suppressed_skills: Vec::new(),
             subagent_spawner,
             auto_context_enabled: false,
             pending_context: Vec::new(),
@@ -341,19 +601,9 @@ impl AppState {
     /// Searches for plugins in:
     /// - `~/.config/patina/plugins/`
     /// - `./.patina/plugins/` (project-local)
-    fn load_plugins() -> PluginRegistry {
+    fn load_plugins(working_dir: &Path) -> PluginRegistry {
         let mut registry = PluginRegistry::new();
-
-        // Build search paths
-        let mut search_paths: Vec<PathBuf> = Vec::new();
-
-        // User config directory using directories crate
-        if let Some(base_dirs) = directories::BaseDirs::new() {
-            search_paths.push(base_dirs.config_dir().join("patina/plugins"));
-        }
-
-        // Project-local plugins
-        search_paths.push(PathBuf::from(".patina/plugins"));
+        let search_paths = plugin_search_paths(working_dir);
 
         // Load plugins from all paths (errors are logged, not propagated)
         if let Err(e) = registry.load_all(&search_paths) {
@@ -374,6 +624,54 @@ impl AppState {
         &self.plugin_registry
     }
 
+    /// Starts reloading plugins when their files change (`--watch-plugins`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plugin directories cannot be watched.
+    pub fn watch_plugins(&mut self) -> Result<()> {
+        let (watcher, rx) = PluginWatcher::start(&plugin_search_paths(&self.working_dir))?;
+        self.plugin_watcher = Some(watcher);
+        self.plugin_changes_rx = Some(rx);
+        Ok(())
+    }
+
+    /// Reloads plugins whose files changed and reports each reload.
+    ///
+    /// Further changes already queued are handled in the same pass, so one
+    /// save that touches several files reloads the plugin once. A plugin
+    /// that fails to load keeps its previous version.
+    pub fn reload_changed_plugins(&mut self, dir: PathBuf) {
+        let mut dirs = vec![dir];
+        if let Some(rx) = &mut self.plugin_changes_rx {
+            while let Ok(dir) = rx.try_recv() {
+                if !dirs.contains(&dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+
+        for dir in dirs {
+            match self.plugin_registry.reload_plugin_dir(&dir) {
+                Ok(reload) => {
+                    if let Some(notice) = reload.notice() {
+                        tracing::info!("{}", notice);
+                        self.timeline.push_assistant_message(notice);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to reload plugin {}: {:#}", dir.display(), e);
+                    self.timeline.push_assistant_message(format!(
+                        "Plugin in {} failed to reload, keeping the previous version: {:#}",
+                        dir.display(),
+                        e
+                    ));
+                }
+            }
+        }
+        self.dirty.full = true;
+    }
+
     /// Returns whether subagent orchestration is enabled.
     #[must_use]
     pub fn subagents_enabled(&self) -> bool {
@@ -485,6 +783,88 @@ impl AppState {
         self.dirty.input = true;
     }
 
+    /// Inserts a string at the current cursor position.
+    pub fn insert_str(&mut self, text: &str) {
+        let byte_pos = self
+            .input
+            .char_indices()
+            .nth(self.cursor_pos)
+            .map(|(i, _)| i)
+            .unwrap_or(self.input.len());
+        self.input.insert_str(byte_pos, text);
+        self.cursor_pos += text.chars().count();
+        self.dirty.input = true;
+    }
+
+    /// Sets the paste size in bytes above which content is saved to a file.
+    pub fn set_paste_threshold(&mut self, threshold: usize) {
+        self.paste_threshold = threshold;
+    }
+
+    /// Sets how streamed text deltas are batched before rendering.
+    pub fn set_stream_flush(&mut self, stream_flush: StreamFlush) {
+        self.stream_flush = stream_flush;
+    }
+
+    /// Returns how streamed text deltas are batched before rendering.
+    #[must_use]
+    pub fn stream_flush(&self) -> &StreamFlush {
+        &self.stream_flush
+    }
+
+    /// Shows a keyboard hint banner until it is dismissed.
+    pub fn show_keyboard_hint(&mut self, hint: String) {
+        self.keyboard_hint = Some(hint);
+        self.dirty.full = true;
+    }
+
+    /// Returns the keyboard hint banner, if it is showing.
+    #[must_use]
+    pub fn keyboard_hint(&self) -> Option<&str> {
+        self.keyboard_hint.as_deref()
+    }
+
+    /// Dismisses the keyboard hint banner; returns false if none was showing.
+    pub fn dismiss_keyboard_hint(&mut self) -> bool {
+        let dismissed = self.keyboard_hint.take().is_some();
+        if dismissed {
+            self.dirty.full = true;
+        }
+        dismissed
+    }
+
+    /// Inserts pasted text, guarding against oversized and binary content.
+    ///
+    /// Content above the paste threshold is saved to a private session file
+    /// and inserted as an `@path` reference, which is replaced by the content
+    /// when the message is sent; binary content is rejected with a notice.
+    pub fn paste(&mut self, text: &str) {
+        match prepare_paste(text, self.paste_threshold, &self.pastes) {
+            Ok(PasteOutcome::Inline(inline)) => self.insert_str(&inline),
+            Ok(PasteOutcome::Saved { path, bytes }) => {
+                self.insert_str(&paste_reference(&path));
+                self.timeline.push_assistant_message(format!(
+                    "Pasted {} saved to {} and referenced instead of inlined.",
+                    format_bytes(bytes as u64),
+                    path.display()
+                ));
+                self.dirty.full = true;
+            }
+            Ok(PasteOutcome::Binary) => {
+                self.timeline.push_assistant_message(
+                    "Paste rejected: the clipboard content looks like binary data.".to_string(),
+                );
+                self.dirty.full = true;
+            }
+            Err(e) => {
+                tracing::warn!("paste: {:#}", e);
+                self.timeline
+                    .push_assistant_message(format!("Paste failed: {:#}", e));
+                self.dirty.full = true;
+            }
+        }
+    }
+
     /// Deletes the character before the cursor (backspace behavior).
     pub fn delete_char(&mut self) {
         if self.cursor_pos > 0 {
@@ -541,6 +921,70 @@ impl AppState {
         self.dirty.input = true;
     }
 
+    /// Inserts a line break at the cursor, for multiline prompts.
+    pub fn insert_newline(&mut self) {
+        self.insert_char('\n');
+    }
+
+    /// Returns the cursor's line and column in the input, counted in characters.
+    #[must_use]
+    pub fn cursor_line_col(&self) -> (usize, usize) {
+        let mut line = 0;
+        let mut col = 0;
+        for c in self.input.chars().take(self.cursor_pos) {
+            if c == '\n' {
+                line += 1;
+                col = 0;
+            } else {
+                col += 1;
+            }
+        }
+        (line, col)
+    }
+
+    /// Moves the cursor up one line, keeping its column where the line allows.
+    pub fn cursor_up(&mut self) {
+        let (line, col) = self.cursor_line_col();
+        if line > 0 {
+            self.move_cursor_to(line - 1, col);
+        }
+    }
+
+    /// Moves the cursor down one line, keeping its column where the line allows.
+    pub fn cursor_down(&mut self) {
+        let (line, col) = self.cursor_line_col();
+        self.move_cursor_to(line + 1, col);
+    }
+
+    /// Moves the cursor to `col` on input line `line`, clamped to the line's
+    /// length. Does nothing if the line does not exist.
+    fn move_cursor_to(&mut self, line: usize, col: usize) {
+        let mut start = 0;
+        for (i, text) in self.input.split('\n').enumerate() {
+            let len = text.chars().count();
+            if i == line {
+                self.cursor_pos = start + col.min(len);
+                self.dirty.input = true;
+                return;
+            }
+            start += len + 1;
+        }
+    }
+
+    /// Returns the number of lines in the input.
+    #[must_use]
+    pub fn input_line_count(&self) -> usize {
+        self.input.split('\n').count()
+    }
+
+    /// Returns the height of the input box in rows, including its border.
+    ///
+    /// The box grows with the input up to [`MAX_INPUT_LINES`] lines.
+    #[must_use]
+    pub fn input_height(&self) -> u16 {
+        self.input_line_count().min(MAX_INPUT_LINES) as u16 + 2
+    }
+
     /// Returns the current scroll offset for rendering.
     ///
     /// This provides backward compatibility with TUI rendering.
@@ -656,24 +1100,25 @@ impl AppState {
     /// Determines which focus area a screen row belongs to.
     ///
     /// Layout (from top to bottom):
-    /// - Messages/Content: rows 0 to (terminal_height - 5)
-    /// - Status bar: row (terminal_height - 4)
-    /// - Input: rows (terminal_height - 3) to (terminal_height - 1)
+    /// - Messages/Content: rows 0 to (terminal_height - input_height - 2)
+    /// - Status bar: row (terminal_height - input_height - 1)
+    /// - Input: the bottom `input_height` rows
     ///
     /// # Arguments
     ///
     /// * `row` - The screen row (0-indexed, 0 = top)
     /// * `terminal_height` - Total terminal height in rows
+    /// * `input_height` - Height of the input box, from [`input_height`](Self::input_height)
     ///
     /// # Returns
     ///
     /// The `FocusArea` that the row belongs to.
     #[must_use]
-    pub fn focus_area_for_row(row: u16, terminal_height: u16) -> FocusArea {
-        // Input area is the bottom 3 rows
+    pub fn focus_area_for_row(row: u16, terminal_height: u16, input_height: u16) -> FocusArea {
+        // Input area is the bottom rows
         // Status bar is 1 row above input
         // Content area is everything else
-        let input_start = terminal_height.saturating_sub(3);
+        let input_start = terminal_height.saturating_sub(input_height);
         if row >= input_start {
             FocusArea::Input
         } else {
@@ -737,29 +1182,32 @@ impl AppState {
     /// * `width` - The terminal content width (excluding borders)
     pub fn update_rendered_lines_cache(&mut self, lines: &[ratatui::text::Line<'_>], width: usize) {
         self.rendered_lines_cache = crate::tui::wrap_lines_to_strings(lines, width);
+        // Content may have shrunk since the selection was made or restored
+        self.selection
+            .clamp_to_lines(self.rendered_lines_cache.len());
     }
 
-    /// Copies the current selection to clipboard using cached lines.
+    /// Returns the selected text from the cached rendered lines.
     ///
-    /// # Errors
-    ///
-    /// Returns an error if clipboard access fails.
-    pub fn copy_from_cache(&self) -> Result<bool> {
+    /// Content that is not rendered, such as hidden or collapsed thinking,
+    /// is never part of the result.
+    #[must_use]
+    pub fn selected_text(&self) -> Option<String> {
         let Some((start, end)) = self.selection.range() else {
-            tracing::debug!("copy_from_cache: no selection range");
-            return Ok(false);
+            tracing::debug!("selected_text: no selection range");
+            return None;
         };
 
         tracing::debug!(
             ?start,
             ?end,
             cache_len = self.rendered_lines_cache.len(),
-            "copy_from_cache: extracting"
+            "selected_text: extracting"
         );
 
         if self.rendered_lines_cache.is_empty() {
-            tracing::debug!("copy_from_cache: cache is empty");
-            return Ok(false);
+            tracing::debug!("selected_text: cache is empty");
+            return None;
         }
 
         // Extract text from cached lines
@@ -794,9 +1242,21 @@ impl AppState {
         }
 
         if result.is_empty() {
-            tracing::debug!("copy_from_cache: extracted empty result");
-            return Ok(false);
+            tracing::debug!("selected_text: extracted empty result");
+            return None;
         }
+        Some(result)
+    }
+
+    /// Copies the current selection to clipboard using cached lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if clipboard access fails.
+    pub fn copy_from_cache(&self) -> Result<bool> {
+        let Some(result) = self.selected_text() else {
+            return Ok(false);
+        };
 
         tracing::debug!(
             result_len = result.len(),
@@ -891,15 +1351,16 @@ impl AppState {
     /// The truncation:
     /// - Always preserves the first message (system/project context)
     /// - Prioritizes recent messages over older ones
-    /// - Respects the `DEFAULT_MAX_INPUT_TOKENS` limit
+    /// - Respects the context window (`DEFAULT_MAX_INPUT_TOKENS` unless changed
+    ///   with [`set_context_window`](Self::set_context_window))
     ///
     /// # Returns
     ///
     /// A new vector containing the truncated message history.
     #[must_use]
     pub fn api_messages_truncated(&self) -> Vec<ApiMessageV2> {
-        use crate::api::{truncate_context, DEFAULT_MAX_INPUT_TOKENS};
-        truncate_context(&self.api_messages, DEFAULT_MAX_INPUT_TOKENS)
+        use crate::api::truncate_context;
+        truncate_context(&self.api_messages, self.context_window)
     }
 
     pub async fn submit_message(
@@ -907,10 +1368,15 @@ impl AppState {
         client: &AnthropicClient,
         content: String,
     ) -> Result<()> {
-        // Add to both timeline and API messages
-        let user_msg = ApiMessageV2::user(&content);
+        // Add to both timeline and API messages; only the API message
+        // carries the content of saved pastes
+        let user_msg = ApiMessageV2::user(self.pastes.expand_references(&content));
         self.timeline.push_user_message(&content);
         self.api_messages.push(user_msg);
+        self.auto_activate_skills(&ActivationContext {
+            prompt: &content,
+            files: &[],
+        });
 
         self.loading = true;
         // Start streaming in timeline
@@ -930,6 +1396,9 @@ impl AppState {
         let (tx, rx) = mpsc::channel(STREAMING_CHANNEL_BUFFER);
         self.streaming_rx = Some(rx);
 
+        self.maybe_auto_compact();
+        self.check_context_budget();
+
         // Use truncated api_messages for the API call to control costs
         // while preserving content blocks for tool results
         let total_messages = self.api_messages.len();
@@ -945,21 +1414,20 @@ impl AppState {
             );
         }
 
-        let client = client.clone();
-        let tools = default_tools();
-        tokio::spawn(async move {
+        let client = self.request_client(client);
+        let tools = self.tools();
+        // A forced tool applies to this turn's first response only; tool
+        // continuations go back to letting Claude decide
+        let tool_choice = self.forced_tool_choice.take().unwrap_or_default();
+        let task = tokio::spawn(async move {
             if let Err(e) = client
-                .stream_message_v2_with_tools(
-                    &api_messages,
-                    Some(&tools),
-                    Some(&ToolChoice::Auto),
-                    tx,
-                )
+                .stream_message_v2_with_tools(&api_messages, Some(&tools), Some(&tool_choice), tx)
                 .await
             {
                 tracing::error!("API error: {}", e);
             }
         });
+        self.stream_task = Some(task.abort_handle());
 
         Ok(())
     }
@@ -995,7 +1463,102 @@ impl AppState {
     /// Used for guard conditions in the event loop.
     #[must_use]
     pub fn has_background_work(&self) -> bool {
-        self.streaming_rx.is_some() || self.tool_result_rx.is_some()
+        self.streaming_rx.is_some()
+            || self.tool_result_rx.is_some()
+            || self.permission_rx.is_some()
+            || self.title_rx.is_some()
+            || self.explain_rx.is_some()
+            || self.plugin_changes_rx.is_some()
+            || self.ide_context_rx.is_some()
+            || self.mcp_progress_rx.is_some()
+            || self.mcp_events_rx.is_some()
+            || self.mcp_tools_rx.is_some()
+    }
+
+    /// Returns true if a response is streaming or tools are executing.
+    #[must_use]
+    pub fn is_busy(&self) -> bool {
+        self.loading || self.streaming_rx.is_some() || self.tool_result_rx.is_some()
+    }
+
+    /// Sets how model reasoning is rendered in the timeline.
+    pub fn set_thinking_display(&mut self, mode: ThinkingDisplay) {
+        self.thinking_display = mode;
+        self.dirty.messages = true;
+    }
+
+    /// Returns how model reasoning is rendered in the timeline.
+    #[must_use]
+    pub fn thinking_display(&self) -> ThinkingDisplay {
+        self.thinking_display
+    }
+
+    /// Sets what Ctrl+C does in the TUI.
+    pub fn set_ctrl_c_mode(&mut self, mode: CtrlCMode) {
+        self.ctrl_c = CtrlCHandler::new(mode);
+    }
+
+    /// Returns the configured Ctrl+C mode.
+    #[must_use]
+    pub fn ctrl_c_mode(&self) -> CtrlCMode {
+        self.ctrl_c.mode()
+    }
+
+    /// Handles a Ctrl+C press and returns what the event loop should do.
+    ///
+    /// [`CtrlCAction::Cancel`] has already been applied via
+    /// [`cancel_in_flight`](Self::cancel_in_flight) when returned.
+    pub fn handle_ctrl_c(&mut self) -> CtrlCAction {
+        let action = self.ctrl_c.press(std::time::Instant::now(), self.is_busy());
+        if action == CtrlCAction::Cancel {
+            self.cancel_in_flight();
+        }
+        self.dirty.input = true;
+        action
+    }
+
+    /// Returns true if pressing Ctrl+C now would exit.
+    #[must_use]
+    pub fn ctrl_c_exit_armed(&self) -> bool {
+        self.ctrl_c.exit_armed(std::time::Instant::now())
+    }
+
+    /// Abandons the in-flight stream and any executing tools.
+    ///
+    /// The streaming and tool execution tasks are aborted, which also kills
+    /// any command a tool had started, and dropping the receivers discards
+    /// events already in flight. Text streamed so far
+    /// is kept as the assistant's reply, tools that had not finished are
+    /// marked as cancelled, and the turn ends with [`CANCELLED_NOTICE`].
+    pub fn cancel_in_flight(&mut self) {
+        let was_busy = self.is_busy();
+        if let Some(task) = self.stream_task.take() {
+            task.abort();
+        }
+        if let Some(task) = self.tool_task.take() {
+            task.abort();
+        }
+        self.streaming_rx = None;
+        self.tool_result_rx = None;
+        self.permission_rx = None;
+
+        if let Some(text) = self.timeline.finalize_streaming_as_message() {
+            self.api_messages.push(ApiMessageV2::assistant(&text));
+        }
+
+        let cancelled: Vec<String> = self.executing_tool_ids.drain().collect();
+        for tool_id in cancelled {
+            self.update_timeline_tool_by_id(&tool_id, Some("Cancelled".to_string()), true);
+        }
+
+        self.tool_loop.reset();
+        self.pending_permission = None;
+        self.permission_reason = None;
+        self.loading = false;
+        if was_busy {
+            self.timeline.push_assistant_message(CANCELLED_NOTICE);
+        }
+        self.dirty.full = true;
     }
 
     /// Receives the next background event from either API streaming or tool execution.
@@ -1015,14 +1578,25 @@ impl AppState {
         tokio::select! {
             biased;
 
-            // Prioritize tool results to update UI quickly
-            result = async {
+            // Prioritize tool results to update UI quickly; a closed channel
+            // falls through so a paused task's permission request is seen
+            Some((id, result)) = async {
                 match &mut self.tool_result_rx {
                     Some(rx) => rx.recv().await,
                     None => std::future::pending().await,
                 }
             }, if self.tool_result_rx.is_some() => {
-                result.map(|(id, r)| BackgroundEvent::ToolResult(id, r))
+                Some(BackgroundEvent::ToolResult(id, result))
+            }
+
+            // A tool call that needs the user's permission
+            Some(request) = async {
+                match &mut self.permission_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.permission_rx.is_some() => {
+                Some(BackgroundEvent::PermissionNeeded(request))
             }
 
             // Then API streaming chunks
@@ -1035,6 +1609,76 @@ impl AppState {
                 chunk.map(BackgroundEvent::ApiChunk)
             }
 
+            // Title generation completes at most once per session
+            title = async {
+                match &mut self.title_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.title_rx.is_some() => {
+                Some(BackgroundEvent::Title(title))
+            }
+
+            // Explanations requested with /explain
+            explanation = async {
+                match &mut self.explain_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.explain_rx.is_some() => {
+                Some(BackgroundEvent::Explanation(explanation))
+            }
+
+            // Plugin files changed on disk
+            dir = async {
+                match &mut self.plugin_changes_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.plugin_changes_rx.is_some() => {
+                dir.map(BackgroundEvent::PluginChanged)
+            }
+
+            // A connected IDE reported editor context
+            update = async {
+                match &mut self.ide_context_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.ide_context_rx.is_some() => {
+                update.map(BackgroundEvent::EditorContext)
+            }
+
+            // Partial results from running MCP tools
+            progress = async {
+                match &mut self.mcp_progress_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.mcp_progress_rx.is_some() => {
+                progress.map(BackgroundEvent::ToolProgress)
+            }
+
+            // MCP servers disconnecting or reconnecting
+            event = async {
+                match &mut self.mcp_events_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.mcp_events_rx.is_some() => {
+                event.map(BackgroundEvent::McpConnection)
+            }
+
+            // The MCP tool list after a reconnect
+            tools = async {
+                match &mut self.mcp_tools_rx {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if self.mcp_tools_rx.is_some() => {
+                Some(BackgroundEvent::McpTools(tools))
+            }
+
             // If neither channel is active, return None immediately
             else => None
         }
@@ -1048,6 +1692,11 @@ impl AppState {
         self.streaming_rx = Some(rx);
     }
 
+    /// Sets the task streaming the current response so cancelling can abort it.
+    pub fn set_stream_task(&mut self, task: AbortHandle) {
+        self.stream_task = Some(task);
+    }
+
     /// Sets the loading state.
     ///
     /// When loading is true, the throbber animates and content accumulates.
@@ -1076,20 +1725,23 @@ impl AppState {
                 self.tool_loop.append_text(&text);
                 self.dirty.messages = true;
             }
+            StreamEvent::ThinkingDelta(text) => {
+                // Reasoning is displayed only; it never joins the answer text
+                self.timeline.append_thinking(&text);
+                self.dirty.messages = true;
+            }
             StreamEvent::MessageStop => {
                 // Only process if we're actually streaming (prevents duplicates)
                 // MessageComplete may have already handled this
                 if self.timeline.is_streaming() {
-                    self.timeline.finalize_streaming_as_message();
-                    // Get the finalized text for API messages
-                    if let Some(crate::types::ConversationEntry::AssistantMessage(text)) =
-                        self.timeline.entries().last()
-                    {
+                    // Empty responses are dropped rather than sent back as empty turns
+                    if let Some(text) = self.timeline.finalize_streaming_as_message() {
                         self.api_messages.push(ApiMessageV2::assistant(text));
                     }
                 }
                 self.loading = false;
                 self.streaming_rx = None;
+                self.stream_task = None;
                 self.dirty.messages = true;
             }
             StreamEvent::MessageComplete { stop_reason } => {
@@ -1108,13 +1760,14 @@ impl AppState {
                     );
                 } else {
                     // For normal responses, finalize streaming and add to API messages
-                    self.timeline.finalize_streaming_as_message();
-                    if let Some(crate::types::ConversationEntry::AssistantMessage(text)) =
-                        self.timeline.entries().last()
-                    {
+                    if let Some(text) = self.timeline.finalize_streaming_as_message() {
                         self.api_messages.push(ApiMessageV2::assistant(text));
                     }
                 }
+                if stop_reason.is_refusal() {
+                    tracing::warn!("Response ended with a refusal");
+                    self.timeline.push_assistant_message(REFUSAL_NOTICE);
+                }
                 // Handle stop reason in tool loop
                 self.handle_message_complete(stop_reason)?;
                 self.loading = false;
@@ -1123,6 +1776,7 @@ impl AppState {
             }
             StreamEvent::Error(e) => {
                 tracing::error!("Stream error: {}", e);
+                self.last_error = Some(LastError::api(e));
                 self.loading = false;
                 self.streaming_rx = None;
                 self.dirty.messages = true;
@@ -1143,6 +1797,13 @@ impl AppState {
                 // Content block completion is tracked internally
                 tracing::debug!("Content block complete");
             }
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+            } => {
+                tracing::debug!(input_tokens, output_tokens, "API usage");
+                self.record_api_usage(input_tokens, output_tokens);
+            }
         }
         Ok(())
     }
@@ -1201,27 +1862,352 @@ impl AppState {
         self.worktree_behind
     }
 
-    // ========================================================================
-    // Token Budget Tracking
-    // ========================================================================
-
-    /// Returns a reference to the token budget for display.
+    /// Returns the worktree this session works in, if it was moved into one.
     #[must_use]
-    pub fn token_budget(&self) -> &TokenBudget {
-        &self.token_budget
+    pub fn worktree_session(&self) -> Option<&WorktreeSession> {
+        self.worktree_session.as_ref()
     }
 
-    /// Returns a mutable reference to the token budget.
-    pub fn token_budget_mut(&mut self) -> &mut TokenBudget {
-        &mut self.token_budget
+    /// Sets whether worktree changes are committed after each turn that
+    /// edited files.
+    pub fn set_auto_commit_worktree(&mut self, enabled: bool) {
+        self.auto_commit_worktree = enabled;
     }
 
-    /// Adds token usage to the budget.
+    /// Commits the worktree after a turn in which edit tools changed files.
     ///
-    /// Call this after each API request to track cumulative usage.
-    pub fn add_token_usage(&mut self, tokens: usize) {
-        self.token_budget.add_usage(tokens);
-        self.dirty.full = true;
+    /// Only runs when auto-commit is enabled and the session is linked to a
+    /// worktree. The files the turn's edit tools changed are committed with
+    /// a message taken from the assistant's reply, and the commit is
+    /// recorded on the worktree link.
+    pub fn auto_commit_worktree(&mut self) {
+        let files = std::mem::take(&mut self.turn_edited_files);
+        if files.is_empty() || !self.auto_commit_worktree {
+            return;
+        }
+        let Some(worktree) = self.worktree_session.as_mut() else {
+            return;
+        };
+
+        let reply = self
+            .timeline
+            .entries()
+            .iter()
+            .rev()
+            .find_map(|entry| match entry {
+                crate::types::ConversationEntry::AssistantMessage(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let message = commit_message_for_turn(reply);
+
+        let result = WorktreeManager::new(&self.working_dir)
+            .and_then(|manager| manager.commit_paths(&self.working_dir, &files, &message));
+        let notice = match result {
+            Ok(Some(hash)) => {
+                worktree.add_commit(hash.clone(), message.clone());
+                format!(
+                    "Committed {} in worktree '{}': {}",
+                    hash,
+                    worktree.worktree_name(),
+                    message
+                )
+            }
+            Ok(None) => return,
+            Err(e) => format!(
+                "Auto-commit in worktree '{}' failed: {}",
+                worktree.worktree_name(),
+                e
+            ),
+        };
+        self.timeline.push_assistant_message(notice);
+        self.dirty.messages = true;
+    }
+
+    /// Moves the session into a git worktree.
+    ///
+    /// Tools run in the worktree from now on. The conversation carries over
+    /// but is saved as a new session, linked to the worktree unless it is the
+    /// main one, so the session started elsewhere stays as it was.
+    fn switch_worktree(
+        &mut self,
+        name: String,
+        path: PathBuf,
+        branch: String,
+        original_branch: Option<String>,
+    ) -> String {
+        let Some(executor) = Arc::get_mut(&mut self.tool_executor) else {
+            return format!(
+                "Can't switch to worktree '{}' while tools are running. Try again when they finish.",
+                name
+            );
+        };
+        executor.set_working_dir(path.clone());
+        self.working_dir = path;
+
+        self.worktree_session = original_branch
+            .as_ref()
+            .map(|original| WorktreeSession::new(name.clone(), original.clone()));
+        self.set_worktree_branch(branch.clone());
+        self.session_id = None;
+
+        match original_branch {
+            Some(original) => format!(
+                "Working in worktree '{}' on branch {} (from {}) at {}. \
+                 When you quit you can merge it into {} or remove it.",
+                name,
+                branch,
+                original,
+                self.working_dir.display(),
+                original
+            ),
+            None => format!(
+                "Back in the main worktree on branch {} at {}.",
+                branch,
+                self.working_dir.display()
+            ),
+        }
+    }
+
+    // ========================================================================
+    // Token Budget Tracking
+    // ========================================================================
+
+    /// Returns a reference to the token budget for display.
+    #[must_use]
+    pub fn token_budget(&self) -> &TokenBudget {
+        &self.token_budget
+    }
+
+    /// Returns a mutable reference to the token budget.
+    pub fn token_budget_mut(&mut self) -> &mut TokenBudget {
+        &mut self.token_budget
+    }
+
+    /// Adds token usage to the budget.
+    ///
+    /// Call this after each API request to track cumulative usage.
+    pub fn add_token_usage(&mut self, tokens: usize) {
+        self.token_budget.add_usage(tokens);
+        self.dirty.full = true;
+    }
+
+    /// Records the token usage reported by the API for the latest response.
+    ///
+    /// Each request's input already includes the whole conversation, so the
+    /// latest input plus output is the context currently in use.
+    pub fn record_api_usage(&mut self, input_tokens: u32, output_tokens: u32) {
+        let total = input_tokens as usize + output_tokens as usize;
+        self.token_budget.set_usage(total);
+        if let Some(notice) = self
+            .session_budget
+            .as_mut()
+            .and_then(|budget| budget.record(input_tokens, output_tokens))
+        {
+            let (BudgetNotice::Warning(message) | BudgetNotice::Exceeded(message)) = notice;
+            tracing::warn!("{}", message);
+            self.timeline.push_assistant_message(message);
+        }
+        self.dirty.full = true;
+    }
+
+    /// Sets the per-session cost budget in USD, priced for `model`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the budget is not positive.
+    pub fn set_session_budget(&mut self, limit: Option<f64>, model: &str) -> Result<()> {
+        self.session_budget = limit
+            .map(|limit| SessionBudget::new(limit, model))
+            .transpose()?;
+        Ok(())
+    }
+
+    /// Returns true if the session budget is exceeded and continuing is unconfirmed.
+    #[must_use]
+    pub fn budget_paused(&self) -> bool {
+        self.session_budget
+            .as_ref()
+            .is_some_and(SessionBudget::is_paused)
+    }
+
+    /// Returns a summary of estimated spend against the session budget.
+    #[must_use]
+    pub fn budget_status(&self) -> String {
+        self.session_budget.as_ref().map_or_else(
+            || "No session budget is set (see session_budget in settings.toml).".to_string(),
+            SessionBudget::summary,
+        )
+    }
+
+    /// Holds back the pending tool-result continuation while the budget is exceeded.
+    pub fn defer_continuation(&mut self) {
+        self.continuation_deferred = true;
+    }
+
+    /// Returns true, once, when a deferred continuation may resume.
+    pub fn take_resumable_continuation(&mut self) -> bool {
+        if self.continuation_deferred && !self.budget_paused() {
+            self.continuation_deferred = false;
+            return true;
+        }
+        false
+    }
+
+    /// Sets the context window used for the token budget and truncation.
+    ///
+    /// Usage recorded so far is kept.
+    pub fn set_context_window(&mut self, tokens: usize) {
+        let used = self.token_budget.used();
+        self.token_budget = TokenBudget::new(tokens);
+        self.token_budget.set_usage(used);
+        self.context_window = tokens;
+        self.dirty.full = true;
+    }
+
+    /// Sets the fraction of the context window at which history is compacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the threshold is not greater than 0 and at most 1.
+    pub fn set_auto_compact_threshold(&mut self, threshold: f64) -> Result<()> {
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            anyhow::bail!("auto_compact_threshold must be greater than 0 and at most 1");
+        }
+        self.auto_compact_threshold = threshold;
+        Ok(())
+    }
+
+    /// Sets how history is summarized at the next compaction.
+    pub fn set_summary_style(&mut self, style: SummaryStyle) {
+        self.summary_style = style;
+    }
+
+    /// Returns how history is summarized when compacted.
+    #[must_use]
+    pub fn summary_style(&self) -> SummaryStyle {
+        self.summary_style
+    }
+
+    /// Summarizes older history when the conversation nears the context window.
+    ///
+    /// Once the estimated size passes the auto-compact threshold, messages
+    /// between the first one and the most recent turns are replaced by a
+    /// summary, and the timeline notes what happened. Returns true if the
+    /// history was compacted.
+    pub fn maybe_auto_compact(&mut self) -> bool {
+        let threshold = (self.context_window as f64 * self.auto_compact_threshold) as usize;
+        let before = estimate_messages_tokens(&self.api_messages);
+        if before <= threshold {
+            return false;
+        }
+
+        let preserve = preserve_count_for(&self.api_messages, AUTO_COMPACT_PRESERVE_RECENT);
+        let compacted = compact_or_truncate_context_with_style(
+            &self.api_messages,
+            threshold,
+            preserve,
+            self.summary_style,
+        );
+        if compacted.len() >= self.api_messages.len() {
+            return false;
+        }
+
+        let after = estimate_messages_tokens(&compacted);
+        let summarized = self.api_messages.len() - preserve - 1;
+        tracing::info!(
+            before,
+            after,
+            summarized,
+            "Auto-compacted conversation history"
+        );
+        self.api_messages = compacted;
+        self.timeline.push_assistant_message(format!(
+            "Context compacted: summarized {} earlier messages to stay within the \
+             context window (~{}k → ~{}k tokens).",
+            summarized,
+            before / 1000,
+            after / 1000
+        ));
+        self.dirty.full = true;
+        true
+    }
+
+    /// Summarizes earlier history now, regardless of the auto-compact threshold.
+    ///
+    /// Keeps the first message and the most recent exchanges verbatim and
+    /// replaces everything between them with a summary in the configured
+    /// style. Returns a report of what was reclaimed, or why nothing was.
+    pub fn compact_now(&mut self) -> String {
+        if self.is_busy() {
+            return "Cannot compact while a response is in progress.".to_string();
+        }
+
+        const NOTHING_TO_COMPACT: &str =
+            "Not enough history to compact yet; recent messages are always kept.";
+        let preserve = preserve_count_for(&self.api_messages, AUTO_COMPACT_PRESERVE_RECENT);
+        if self.api_messages.len() <= preserve + 1 {
+            return NOTHING_TO_COMPACT.to_string();
+        }
+
+        let config = CompactionConfig {
+            target_tokens: 0,
+            preserve_recent: preserve,
+            summary_style: self.summary_style,
+        };
+        let compacted = match ContextCompactor::new().compact(&self.api_messages, &config) {
+            Ok(result) => result.messages,
+            Err(e) => return format!("Compaction failed: {}", e),
+        };
+        if compacted.len() >= self.api_messages.len() {
+            return NOTHING_TO_COMPACT.to_string();
+        }
+
+        let before = estimate_messages_tokens(&self.api_messages);
+        let after = estimate_messages_tokens(&compacted);
+        let summarized = self.api_messages.len() - preserve - 1;
+        tracing::info!(before, after, summarized, "Compacted conversation history");
+        self.api_messages = compacted;
+        self.dirty.full = true;
+        format!(
+            "Compacted {} earlier messages into a {} summary, reclaiming ~{} tokens \
+             (~{}k → ~{}k).",
+            summarized,
+            self.summary_style.as_str(),
+            before.saturating_sub(after),
+            before / 1000,
+            after / 1000
+        )
+    }
+
+    /// Checks the conversation against the context window before a request.
+    ///
+    /// Updates the warning banner and logs when the conversation first
+    /// approaches or passes the limit.
+    pub fn check_context_budget(&mut self) -> BudgetStatus {
+        let status = self.token_budget.check(&self.api_messages);
+        if status != BudgetStatus::Ok && self.context_status == BudgetStatus::Ok {
+            if let Some(warning) = status.warning() {
+                tracing::warn!("{}", warning);
+            }
+        }
+        if status != self.context_status {
+            self.context_status = status;
+            self.dirty.full = true;
+        }
+        status
+    }
+
+    /// Returns the context window warning to show, if any.
+    #[must_use]
+    pub fn context_warning(&self) -> Option<String> {
+        self.context_status.warning()
+    }
+
+    /// Returns the context window used for the token budget and truncation.
+    #[must_use]
+    pub fn context_window(&self) -> usize {
+        self.context_window
     }
 
     /// Resets the token budget for a new conversation.
@@ -1307,1854 +2293,4615 @@ impl AppState {
         self.session_id = Some(id);
     }
 
-    /// Creates a `Session` from the current application state.
-    ///
-    /// The resulting session includes:
-    /// - All conversation messages (converted from timeline)
-    /// - Current UI state (scroll position, input buffer, cursor position)
-    /// - Working directory
-    ///
-    /// This is used for auto-save functionality.
+    /// Returns the tags assigned to the current session.
     #[must_use]
-    pub fn to_session(&self) -> Session {
-        use crate::session::UiState;
-
-        let mut session = Session::new(self.working_dir.clone());
-
-        // Convert timeline entries to messages for session persistence
-        for entry in self.timeline.iter() {
-            match entry {
-                crate::types::ConversationEntry::UserMessage(text) => {
-                    session.add_message(Message {
-                        role: Role::User,
-                        content: text.clone(),
-                    });
-                }
-                crate::types::ConversationEntry::AssistantMessage(text) => {
-                    session.add_message(Message {
-                        role: Role::Assistant,
-                        content: text.clone(),
-                    });
-                }
-                // Skip streaming and tool execution entries for session persistence
-                _ => {}
-            }
-        }
+    pub fn session_tags(&self) -> &[String] {
+        &self.session_tags
+    }
 
-        // Capture UI state (use scroll offset for backward compatibility)
-        let ui_state =
-            UiState::with_state(self.scroll.offset(), self.input.clone(), self.cursor_pos);
-        session.set_ui_state(Some(ui_state));
+    /// Returns the session name, if one has been assigned with `/save`.
+    #[must_use]
+    pub fn session_name(&self) -> Option<&str> {
+        self.session_name.as_deref()
+    }
 
-        session
+    /// Returns the generated session title, if any.
+    #[must_use]
+    pub fn session_title(&self) -> Option<&str> {
+        self.session_title.as_deref()
     }
 
-    /// Restores application state from a saved session.
-    ///
-    /// This restores:
-    /// - Message history (to timeline)
-    /// - UI state (scroll position, input buffer, cursor position) if saved
-    /// - Session ID for subsequent saves
-    ///
-    /// # Arguments
+    /// Sets the model used to generate a session title after the first exchange.
     ///
-    /// * `session` - The session to restore from.
-    pub fn restore_from_session(&mut self, session: &Session) {
-        // Clear and rebuild timeline from session messages
-        self.timeline = Timeline::new();
-        for message in session.messages() {
-            match message.role {
-                Role::User => self.timeline.push_user_message(&message.content),
-                Role::Assistant => self.timeline.push_assistant_message(&message.content),
-            }
-        }
+    /// `None` disables title generation.
+    pub fn set_title_model(&mut self, model: Option<String>) {
+        self.title_model = model;
+    }
 
-        // Restore UI state if available
-        if let Some(ui_state) = session.ui_state() {
-            self.scroll.restore_offset(ui_state.scroll_offset());
-            self.input = ui_state.input_buffer().to_string();
-            self.cursor_pos = ui_state.cursor_position();
+    /// Starts background title generation once the first exchange is complete.
+    ///
+    /// Does nothing if generation is disabled, already attempted, or the
+    /// session already has a name or title. The result arrives as
+    /// [`BackgroundEvent::Title`] and is applied with
+    /// [`apply_generated_title`](Self::apply_generated_title).
+    pub fn maybe_request_title(&mut self, client: &AnthropicClient) {
+        if self.title_requested || self.session_name.is_some() || self.session_title.is_some() {
+            return;
         }
+        let Some(model) = self.title_model.clone() else {
+            return;
+        };
 
-        // Restore session ID if available
-        if let Some(id) = session.id() {
-            self.session_id = Some(id.to_string());
+        // First user prompt (not a slash command) and the reply that follows it
+        let mut exchange = None;
+        let mut entries = self.timeline.iter();
+        while let Some(entry) = entries.next() {
+            if let crate::types::ConversationEntry::UserMessage(user) = entry {
+                if user.trim_start().starts_with('/') {
+                    continue;
+                }
+                if let Some(crate::types::ConversationEntry::AssistantMessage(assistant)) =
+                    entries.find(|e| !e.is_thinking())
+                {
+                    exchange = Some((user.clone(), assistant.clone()));
+                }
+                break;
+            }
         }
+        let Some((user, assistant)) = exchange else {
+            return;
+        };
 
-        // Mark for full redraw
-        self.dirty.full = true;
-    }
-
-    // ========================================================================
-    // Tool Execution Integration
-    // ========================================================================
-
-    /// Returns a reference to the tool loop state.
-    #[must_use]
-    pub fn tool_loop(&self) -> &ToolLoop {
-        &self.tool_loop
-    }
-
-    /// Returns a mutable reference to the tool loop.
-    pub fn tool_loop_mut(&mut self) -> &mut ToolLoop {
-        &mut self.tool_loop
-    }
+        self.title_requested = true;
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.title_rx = Some(rx);
 
-    /// Returns the current tool loop state.
-    #[must_use]
-    pub fn tool_loop_state(&self) -> &ToolLoopState {
-        self.tool_loop.state()
+        let client = client.clone().with_model(model).with_params(RequestParams {
+            max_tokens: Some(TITLE_MAX_TOKENS),
+            ..Default::default()
+        });
+        tokio::spawn(async move {
+            match crate::session::generate_title(&client, &user, &assistant).await {
+                Ok(title) => {
+                    tx.send(title).ok();
+                }
+                Err(e) => tracing::warn!(error = %e, "Session title generation failed"),
+            }
+        });
     }
 
-    /// Returns the pending permission request, if any.
-    #[must_use]
-    pub fn pending_permission(&self) -> Option<&PermissionRequest> {
-        self.pending_permission.as_ref()
+    /// Stores a generated title unless the session already has one.
+    ///
+    /// The title is kept apart from the user-assigned name, so a later
+    /// `/save <name>` does not replace it. Returns `true` if the title changed.
+    pub fn apply_generated_title(&mut self, title: Option<String>) -> bool {
+        self.title_rx = None;
+        match title {
+            Some(title) if self.session_title.is_none() => {
+                tracing::debug!(title = %title, "Generated session title");
+                self.session_title = Some(title);
+                true
+            }
+            _ => false,
+        }
     }
 
-    /// Returns true if there's a pending permission prompt.
+    /// Returns the most recent tool or API error, if any.
     #[must_use]
-    pub fn has_pending_permission(&self) -> bool {
-        self.pending_permission.is_some()
+    pub fn last_error(&self) -> Option<&LastError> {
+        self.last_error.as_ref()
     }
 
-    /// Sets a pending permission request.
+    /// Starts the explanation requested with `/explain`, if one is waiting.
     ///
-    /// The UI should display this as a modal prompt.
-    pub fn set_pending_permission(&mut self, request: PermissionRequest) {
-        self.pending_permission = Some(request);
-        self.dirty.full = true;
-    }
+    /// Only the error is sent, with the current model. The result arrives as
+    /// [`BackgroundEvent::Explanation`] and is shown with
+    /// [`apply_explanation`](Self::apply_explanation).
+    pub fn maybe_request_explanation(&mut self, client: &AnthropicClient) {
+        if !std::mem::take(&mut self.explain_requested) {
+            return;
+        }
+        let Some(error) = self.last_error.clone() else {
+            return;
+        };
 
-    /// Clears the pending permission request.
-    pub fn clear_pending_permission(&mut self) {
-        self.pending_permission = None;
-        self.dirty.full = true;
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.explain_rx = Some(rx);
+
+        let client = self.request_client(client).with_params(RequestParams {
+            max_tokens: Some(EXPLAIN_MAX_TOKENS),
+            ..Default::default()
+        });
+        tokio::spawn(async move {
+            let text = match crate::app::explain::explain_error(&client, &error).await {
+                Ok(explanation) => explanation,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Error explanation failed");
+                    format!("Could not explain the error: {e:#}")
+                }
+            };
+            tx.send(text).ok();
+        });
     }
 
-    /// Handles a permission response from the user.
-    ///
-    /// This grants or denies permission for the pending tool call and
-    /// updates the permission manager accordingly.
-    pub async fn handle_permission_response(&mut self, response: PermissionResponse) {
-        if let Some(request) = self.pending_permission.take() {
-            let mut manager = self.permission_manager.lock().await;
-            manager.handle_response(&request.tool_name, request.tool_input.as_deref(), response);
-            self.dirty.full = true;
-        }
+    /// Shows an explanation in the timeline without adding it to the
+    /// conversation sent to the API.
+    pub fn apply_explanation(&mut self, explanation: Option<String>) {
+        self.explain_rx = None;
+        let text = explanation.unwrap_or_else(|| "Could not explain the error.".to_string());
+        self.timeline.push_assistant_message(&text);
+        self.dirty.messages = true;
     }
 
-    /// Handles a tool_use stream event.
+    /// Applies a state change requested by a slash command.
     ///
-    /// Routes the event to the tool loop state machine.
-    pub fn handle_tool_use_start(&mut self, id: String, name: String, index: usize) {
-        self.tool_loop.start_tool_use(index, id, name);
-        self.dirty.messages = true;
-    }
-
-    /// Handles a tool_use input delta.
-    pub fn handle_tool_use_input_delta(&mut self, index: usize, partial_json: &str) {
-        self.tool_loop.append_tool_input(index, partial_json);
-    }
-
-    /// Handles tool_use completion.
-    pub fn handle_tool_use_complete(&mut self, index: usize) -> Result<()> {
-        self.tool_loop
-            .complete_tool_use(index)
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
-        Ok(())
+    /// Returns the text to display as the command's output.
+    pub fn apply_command_action(&mut self, action: CommandAction) -> String {
+        match action {
+            CommandAction::AddTags(tags) => {
+                for tag in tags {
+                    if !self.session_tags.contains(&tag) {
+                        self.session_tags.push(tag);
+                    }
+                }
+                format!("Session tags: {}", self.session_tags.join(", "))
+            }
+            CommandAction::RemoveTags(tags) => {
+                self.session_tags.retain(|t| !tags.contains(t));
+                if self.session_tags.is_empty() {
+                    "Session has no tags.".to_string()
+                } else {
+                    format!("Session tags: {}", self.session_tags.join(", "))
+                }
+            }
+            CommandAction::NameSession(name) => {
+                let output = format!(
+                    "Session saved as '{}'. Resume it with --resume {}.",
+                    name, name
+                );
+                self.session_name = Some(name);
+                output
+            }
+            CommandAction::AllowRule(rule) => self.allow_rule(rule),
+            CommandAction::ListAllowRules => self.format_allow_rules(),
+            CommandAction::ListTags => {
+                if self.session_tags.is_empty() {
+                    "Session has no tags.".to_string()
+                } else {
+                    format!("Session tags: {}", self.session_tags.join(", "))
+                }
+            }
+            CommandAction::ApplyPreset(name) => match self.presets.get(&name) {
+                Some(params) => {
+                    self.active_preset = Some(RequestPreset::new(&name, params.clone()));
+                    format!("Using preset '{}' for subsequent turns.", name)
+                }
+                None => format!(
+                    "Unknown preset '{}'. Type /preset to list available presets.",
+                    name
+                ),
+            },
+            CommandAction::ClearPreset => {
+                self.active_preset = None;
+                "Using default request parameters.".to_string()
+            }
+            CommandAction::ListPresets => self.format_presets(),
+            CommandAction::ForceTool(name) => {
+                let choice = if name == "any" {
+                    ToolChoice::Any
+                } else {
+                    ToolChoice::tool(&name)
+                };
+                match choice.validate(Some(&self.tools())) {
+                    Ok(()) => {
+                        self.forced_tool_choice = Some(choice);
+                        if name == "any" {
+                            "The next response must call a tool.".to_string()
+                        } else {
+                            format!("The next response must call '{}'.", name)
+                        }
+                    }
+                    Err(_) => {
+                        let names: Vec<String> = self.tools().into_iter().map(|t| t.name).collect();
+                        format!(
+                            "Unknown tool '{}'. Available tools: {}",
+                            name,
+                            names.join(", ")
+                        )
+                    }
+                }
+            }
+            CommandAction::ClearForcedTool => {
+                self.forced_tool_choice = None;
+                "Claude will decide whether to use tools.".to_string()
+            }
+            CommandAction::ShowForcedTool => match &self.forced_tool_choice {
+                Some(ToolChoice::Tool { name }) => {
+                    format!("The next response must call '{}'.", name)
+                }
+                Some(ToolChoice::Any) => "The next response must call a tool.".to_string(),
+                Some(ToolChoice::Auto) | None => "No tool is forced.".to_string(),
+            },
+            CommandAction::SetThinkingDisplay(mode) => {
+                self.set_thinking_display(mode);
+                format!("Thinking display set to {}.", mode.as_str())
+            }
+            CommandAction::ShowThinkingDisplay => {
+                format!("Thinking display: {}", self.thinking_display.as_str())
+            }
+            CommandAction::SetSummaryStyle(style) => {
+                self.set_summary_style(style);
+                format!("Summary style set to {}.", style.as_str())
+            }
+            CommandAction::ShowSummaryStyle => {
+                format!("Summary style: {}", self.summary_style.as_str())
+            }
+            CommandAction::CompactNow => self.compact_now(),
+            CommandAction::ClearConversation { keep_context } => {
+                if self.is_busy() {
+                    return "Cannot clear while a response is in progress.".to_string();
+                }
+                self.clear_conversation();
+                if keep_context {
+                    "Conversation cleared; context files kept.".to_string()
+                } else {
+                    self.session_context = None;
+                    "Conversation cleared.".to_string()
+                }
+            }
+            CommandAction::SetModel(model) => {
+                // The large-context window is kept; otherwise it follows the model
+                if self.context_window != LARGE_CONTEXT_WINDOW {
+                    self.set_context_window(model_context_limit(&model));
+                }
+                self.set_model(model);
+                self.format_model()
+            }
+            CommandAction::ShowModel => self.format_model(),
+            CommandAction::ShowTokens => self.format_tokens(),
+            CommandAction::ShowBudget => self.budget_status(),
+            CommandAction::ConfirmBudget => match self.session_budget.as_mut() {
+                Some(budget) if budget.is_paused() => {
+                    budget.confirm();
+                    "Continuing past the session budget.".to_string()
+                }
+                Some(_) => "The session budget has not been exceeded.".to_string(),
+                None => self.budget_status(),
+            },
+            CommandAction::ResetToolLoop => self.reset_stuck_tool_loop(),
+            CommandAction::ExplainLastError => match &self.last_error {
+                None => "No errors to explain in this session.".to_string(),
+                Some(_) if self.explain_rx.is_some() => {
+                    "An explanation is already on its way.".to_string()
+                }
+                Some(error) => {
+                    self.explain_requested = true;
+                    format!("Explaining the last {}...", error.source)
+                }
+            },
+            CommandAction::ListSkills => self.format_skills(),
+            CommandAction::ActivateSkill(name) => {
+                if self.skill_engine.get(&name).is_none() {
+                    return format!("Unknown skill '{}'. Type /skill to list skills.", name);
+                }
+                self.suppressed_skills.retain(|s| s != &name);
+                self.session_context
+                    .get_or_insert_with(SessionContext::new)
+                    .add_skill(name.clone());
+                format!("Skill '{}' is on.", name)
+            }
+            CommandAction::DeactivateSkill(name) => {
+                if !self.suppressed_skills.contains(&name) {
+                    self.suppressed_skills.push(name.clone());
+                }
+                if let Some(context) = &mut self.session_context {
+                    context.remove_skill(&name);
+                }
+                format!(
+                    "Skill '{}' is off and will not turn on automatically.",
+                    name
+                )
+            }
+            CommandAction::SwitchWorktree {
+                name,
+                path,
+                branch,
+                original_branch,
+            } => self.switch_worktree(name, path, branch, original_branch),
+        }
     }
 
-    /// Handles message completion with a stop reason.
-    ///
-    /// If the stop reason is `ToolUse`, transitions the tool loop to
-    /// `PendingApproval` state.
-    pub fn handle_message_complete(&mut self, stop_reason: StopReason) -> Result<()> {
-        self.tool_loop
-            .message_complete(stop_reason)
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
-        Ok(())
+    /// Returns the names of the active skills.
+    #[must_use]
+    pub fn active_skills(&self) -> &[String] {
+        self.session_context
+            .as_ref()
+            .map_or(&[], SessionContext::active_skills)
     }
 
-    /// Executes all pending tools that have been approved.
-    ///
-    /// Creates tool blocks for UI display, executes the tools, and
-    /// updates the blocks with results.
-    ///
-    /// Returns a list of tool IDs that need permission (if any).
-    ///
-    /// # Errors
+    /// Turns on skills whose triggers match `ctx` and announces each one.
     ///
-    /// Returns an error if the tool loop is not in `Executing` state.
-    pub async fn execute_pending_tools(&mut self) -> Result<Vec<String>> {
-        use crate::app::tool_loop::ToolLoopError;
-        use std::collections::HashMap;
-
-        // Collect tool info before creating blocks (to avoid borrow issues)
-        let tools_to_display: Vec<(String, String, String)> = self
-            .tool_loop
-            .pending_calls()
+    /// Skills that are already active or were turned off with `/skill off`
+    /// are left alone.
+    pub fn auto_activate_skills(&mut self, ctx: &ActivationContext<'_>) {
+        let activated: Vec<(String, String)> = self
+            .skill_engine
+            .all_skills()
             .iter()
-            .filter(|(_, call)| call.approved && !call.executed)
-            .map(|(tool_id, call)| {
-                let input_str = format_tool_input(&call.tool_use.name, &call.tool_use.input);
-                (tool_id.clone(), call.tool_use.name.clone(), input_str)
+            .filter(|skill| {
+                !self.active_skills().contains(&skill.name)
+                    && !self.suppressed_skills.contains(&skill.name)
             })
+            .filter_map(|skill| Some((skill.name.clone(), skill.activation_reason(ctx)?)))
             .collect();
 
-        // Create tool blocks for pending tools (before execution)
-        let mut tool_id_to_block_index: HashMap<String, usize> = HashMap::new();
-        for (tool_id, tool_name, input_str) in tools_to_display {
-            let index = self.start_tool_block(&tool_name, &input_str);
-            tool_id_to_block_index.insert(tool_id, index);
+        for (name, reason) in activated {
+            tracing::info!(skill = %name, reason = %reason, "Skill activated");
+            self.session_context
+                .get_or_insert_with(SessionContext::new)
+                .add_skill(name.clone());
+            self.timeline.push_assistant_message(format!(
+                "Skill '{}' activated ({}). Turn it off with /skill off {}.",
+                name, reason, name
+            ));
         }
+    }
 
-        // Execute the tools
-        let result = self
-            .tool_loop
-            .execute_pending(&self.tool_executor)
-            .await
-            .map_err(|e| match e {
-                ToolLoopError::InvalidStateTransition { from, to } => {
-                    anyhow::anyhow!("Invalid state transition from {} to {}", from, to)
-                }
-                _ => anyhow::anyhow!("{}", e),
-            })?;
-
-        // Collect results (to avoid borrow issues)
-        let results: Vec<(String, String, bool)> = self
-            .tool_loop
-            .pending_calls()
-            .iter()
-            .filter_map(|(tool_id, call)| {
-                if let Some(result_block) = &call.result {
-                    if tool_id_to_block_index.contains_key(tool_id) {
-                        return Some((
-                            tool_id.clone(),
-                            result_block.content.clone(),
-                            result_block.is_error,
-                        ));
-                    }
-                }
-                None
-            })
-            .collect();
+    /// Sets the receiver for editor context reported by the IDE server.
+    pub fn set_ide_context_receiver(&mut self, rx: mpsc::UnboundedReceiver<EditorContextUpdate>) {
+        self.ide_context_rx = Some(rx);
+    }
 
-        // Update tool blocks with results
-        for (tool_id, content, is_error) in results {
-            if let Some(&block_index) = tool_id_to_block_index.get(&tool_id) {
-                self.complete_tool_block(block_index, &content, is_error);
+    /// Routes MCP tool calls to `manager` and advertises its tools.
+    ///
+    /// Partial results of running MCP tools are delivered as
+    /// [`BackgroundEvent::ToolProgress`]. Must be called before any tools
+    /// run, while the executor is not shared.
+    pub fn set_mcp_manager(&mut self, manager: McpManager) {
+        let tools = mcp_tool_definitions(&manager);
+        let mcp = Arc::new(Mutex::new(manager));
+        let (tx, rx) = mpsc::channel(MCP_PROGRESS_BUFFER);
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_mcp_manager(Arc::clone(&mcp), tx),
+            None => {
+                tracing::warn!("Tool executor in use; MCP servers not connected");
+                return;
             }
         }
+        self.mcp = Some(mcp);
+        self.mcp_tools = tools;
+        self.mcp_progress_rx = Some(rx);
+    }
 
-        Ok(result)
+    /// Sets the receiver for the MCP servers' connection notices.
+    ///
+    /// Pass the other end of the sender given to
+    /// [`McpManager::with_connection_events`].
+    pub fn set_mcp_events_receiver(&mut self, rx: mpsc::UnboundedReceiver<McpConnectionEvent>) {
+        self.mcp_events_rx = Some(rx);
     }
 
-    /// Finishes tool execution and returns continuation data.
+    /// Shows an MCP connection notice in the timeline.
     ///
-    /// The continuation data contains the messages needed to continue
-    /// the conversation with Claude.
-    pub fn finish_tool_execution(&mut self) -> Result<ContinuationData> {
-        self.tool_loop
-            .finish_execution()
-            .map_err(|e| anyhow::anyhow!("{}", e))
+    /// When a server reconnects, its tools are re-listed in the background
+    /// and arrive as [`BackgroundEvent::McpTools`]; the manager may be busy
+    /// with the tool call that triggered the reconnect.
+    pub fn apply_mcp_connection_event(&mut self, event: McpConnectionEvent) {
+        self.timeline.push_assistant_message(event.notice());
+        self.dirty.messages = true;
+
+        let McpConnectionEvent::Reconnected { server, .. } = event else {
+            return;
+        };
+        let Some(mcp) = self.mcp.as_ref().map(Arc::clone) else {
+            return;
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.mcp_tools_rx = Some(rx);
+        tokio::spawn(async move {
+            let mut manager = mcp.lock().await;
+            if let Err(e) = manager.refresh_tools(&server) {
+                tracing::warn!("Failed to refresh MCP tools: {:#}", e);
+            }
+            let _ = tx.send(mcp_tool_definitions(&manager));
+        });
     }
 
-    /// Approves all pending tools for execution.
-    pub fn approve_all_tools(&mut self) -> Result<()> {
-        self.tool_loop
-            .approve_all()
-            .map_err(|e| anyhow::anyhow!("{}", e))
+    /// Advertises the MCP tools re-listed after a reconnect.
+    pub fn apply_mcp_tools(&mut self, tools: Option<Vec<ToolDefinition>>) {
+        self.mcp_tools_rx = None;
+        if let Some(tools) = tools {
+            self.mcp_tools = tools;
+        }
     }
 
-    /// Denies all pending tools.
-    pub fn deny_all_tools(&mut self) -> Result<()> {
-        self.tool_loop
-            .deny_all()
-            .map_err(|e| anyhow::anyhow!("{}", e))
+    /// Sets the notifier used to tell IDE clients about changed files.
+    pub fn set_ide_notifier(&mut self, notifier: IdeNotifier) {
+        self.ide_notifier = Some(notifier);
     }
 
-    /// Resets the tool loop to idle state.
-    pub fn reset_tool_loop(&mut self) {
-        self.tool_loop.reset();
-        self.pending_permission = None;
-        self.dirty.full = true;
+    /// Records the selection or open files reported by an IDE.
+    ///
+    /// Paths inside the working directory are stored relative to it. The
+    /// context reaches the model with the next request.
+    pub fn apply_editor_context(&mut self, update: EditorContextUpdate) {
+        let relative = |path: PathBuf| match path.strip_prefix(&self.working_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path,
+        };
+        let update = match update {
+            EditorContextUpdate::Selection(selection) => {
+                EditorContextUpdate::Selection(selection.map(|mut selection| {
+                    selection.path = relative(selection.path);
+                    selection
+                }))
+            }
+            EditorContextUpdate::OpenFiles(paths) => {
+                EditorContextUpdate::OpenFiles(paths.into_iter().map(relative).collect())
+            }
+        };
+
+        let context = self.session_context.get_or_insert_with(SessionContext::new);
+        match update {
+            EditorContextUpdate::Selection(selection) => context.set_selection(selection),
+            EditorContextUpdate::OpenFiles(paths) => context.set_open_files(paths),
+        }
     }
 
-    /// Returns true if the tool loop is waiting for user action.
+    /// Returns the system prompt section built from the active skills.
     #[must_use]
-    pub fn tool_loop_needs_user_action(&self) -> bool {
-        self.tool_loop.state().needs_user_action() || self.pending_permission.is_some()
+    pub fn skill_system_prompt(&self) -> Option<String> {
+        self.skill_engine
+            .system_prompt_for(self.active_skills(), MAX_SKILL_PROMPT_CHARS)
     }
 
-    /// Returns true if the tool loop is actively processing.
-    #[must_use]
-    pub fn tool_loop_is_active(&self) -> bool {
-        self.tool_loop.state().is_active()
+    /// Formats the available skills for the `/skill` command.
+    fn format_skills(&self) -> String {
+        let skills = self.skill_engine.all_skills();
+        if skills.is_empty() {
+            return "No skills found. Add skills in .patina/skills/<name>/SKILL.md.".to_string();
+        }
+
+        let mut lines = vec!["Skills:".to_string()];
+        for skill in skills {
+            let status = if self.active_skills().contains(&skill.name) {
+                "on"
+            } else if self.suppressed_skills.contains(&skill.name) {
+                "off"
+            } else {
+                "auto"
+            };
+            lines.push(format!(
+                "  {} [{}] - {}",
+                skill.name, status, skill.description
+            ));
+        }
+        lines.join("\n")
     }
 
-    // ========================================================================
-    // Tool Block UI Methods (Phase 10.5.6)
-    // ========================================================================
+    /// Returns the tool choice forced for the next submitted message, if any.
+    #[must_use]
+    pub fn forced_tool_choice(&self) -> Option<&ToolChoice> {
+        self.forced_tool_choice.as_ref()
+    }
 
-    /// Starts a new tool block for UI display.
+    /// Sets the boundary that file tools are confined to.
     ///
-    /// Returns the index of the created block for later updates.
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_path_confinement(&mut self, confinement: PathConfinement) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_path_confinement(confinement),
+            None => tracing::warn!("Tool executor in use; path confinement unchanged"),
+        }
+    }
+
+    /// Loads the project `.env` so bash commands see its variables.
     ///
-    /// # Arguments
+    /// The values stay out of the conversation and the saved session.
+    pub fn load_dotenv(&self) {
+        match self.tool_executor.load_dotenv() {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "Loaded project .env into the shell environment"),
+            Err(e) => tracing::warn!("{:#}", e),
+        }
+    }
+
+    /// Loads lifecycle hooks from `user_hooks` and, if `trust_project` is
+    /// set, from the project's `.patina/hooks.toml`.
     ///
-    /// * `tool_name` - Name of the tool (e.g., "bash", "read")
-    /// * `tool_input` - Input provided to the tool
-    pub fn start_tool_block(&mut self, tool_name: &str, tool_input: &str) -> usize {
-        let block = ToolBlockState::new(tool_name, tool_input);
-        self.tool_blocks.push(block);
-        self.dirty.messages = true;
-        self.tool_blocks.len() - 1
+    /// Hooks run commands, so a cloned repository's hooks are only loaded
+    /// once the user has trusted the project. Must be called before any tools
+    /// run, while the executor is not shared.
+    pub fn load_hooks(&mut self, user_hooks: Option<&Path>, trust_project: bool) {
+        let Some(executor) = Arc::get_mut(&mut self.tool_executor) else {
+            tracing::warn!("Tool executor in use; hooks not loaded");
+            return;
+        };
+        if let Some(path) = user_hooks {
+            if let Err(e) = executor.load_hooks(path) {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to load hooks");
+            }
+        }
+        if trust_project {
+            if let Err(e) = executor.load_project_hooks() {
+                tracing::warn!("{:#}", e);
+            }
+        }
     }
 
-    /// Completes a tool block with its result.
+    /// Fires the `SessionStart` hooks.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `index` - Index of the tool block to update
-    /// * `result` - The tool's output
-    /// * `is_error` - Whether the result is an error
-    pub fn complete_tool_block(&mut self, index: usize, result: &str, is_error: bool) {
-        if let Some(block) = self.tool_blocks.get_mut(index) {
-            if is_error {
-                block.set_error(result);
-            } else {
-                block.set_result(result);
+    /// Returns an error if a hook blocks the session from starting.
+    pub async fn fire_session_start(&self) -> Result<()> {
+        match self.tool_executor.hooks().fire_session_start().await {
+            Ok(HookResult {
+                decision: HookDecision::Block { reason },
+                ..
+            }) => anyhow::bail!("Session start blocked by hook: {}", reason.trim()),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!(error = %e, "SessionStart hooks failed");
+                Ok(())
             }
-            self.dirty.messages = true;
         }
     }
 
-    /// Returns a slice of all tool blocks for rendering.
-    #[must_use]
-    pub fn tool_blocks(&self) -> &[ToolBlockState] {
-        &self.tool_blocks
+    /// Runs the `UserPromptSubmit` hooks on a prompt about to be sent.
+    ///
+    /// Returns the prompt to send, possibly rewritten by a hook, or `None`
+    /// if a hook blocked it. A blocked prompt is shown in the timeline with
+    /// the hook's message and never reaches the API.
+    pub async fn apply_prompt_hooks(&mut self, prompt: String) -> Option<String> {
+        match self.run_prompt_hooks(&prompt).await {
+            Ok(prompt) => Some(prompt),
+            Err(message) => {
+                self.timeline.push_user_message(&prompt);
+                self.timeline.push_assistant_message(message);
+                self.dirty.full = true;
+                None
+            }
+        }
     }
 
-    /// Clears all tool blocks.
+    /// Runs the `UserPromptSubmit` hooks without touching the timeline.
     ///
-    /// Call this when starting a new conversation turn.
-    pub fn clear_tool_blocks(&mut self) {
-        self.tool_blocks.clear();
-        self.dirty.messages = true;
+    /// Returns the prompt to send, possibly rewritten by a hook, or the
+    /// message explaining why a hook blocked it. Used directly by the
+    /// non-interactive modes.
+    pub async fn run_prompt_hooks(&self, prompt: &str) -> std::result::Result<String, String> {
+        match self
+            .tool_executor
+            .hooks()
+            .fire_user_prompt_submit(prompt)
+            .await
+        {
+            Ok(PromptHookResult::Submit(prompt)) => Ok(prompt),
+            Ok(PromptHookResult::Block { reason }) if reason.is_empty() => {
+                Err("Not sent: blocked by a UserPromptSubmit hook.".to_string())
+            }
+            Ok(PromptHookResult::Block { reason }) => Err(format!("Not sent: {}", reason)),
+            Err(e) => {
+                tracing::warn!(error = %e, "UserPromptSubmit hooks failed");
+                Ok(prompt.to_string())
+            }
+        }
     }
 
-    /// Returns true if there are any tool blocks to display.
-    #[must_use]
-    pub fn has_tool_blocks(&self) -> bool {
-        !self.tool_blocks.is_empty()
+    /// Fires the `SessionEnd` hooks. The session ends regardless of the outcome.
+    pub async fn fire_session_end(&self, stop_reason: &str) {
+        match self
+            .tool_executor
+            .hooks()
+            .fire_session_end(Some(stop_reason))
+            .await
+        {
+            Ok(HookResult {
+                decision: HookDecision::Block { reason },
+                ..
+            }) => tracing::warn!(reason = %reason.trim(), "SessionEnd hook tried to block"),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "SessionEnd hooks failed"),
+        }
     }
 
-    // ========================================================================
-    // Timeline Integration (Phase 2)
-    // ========================================================================
-
-    /// Returns a reference to the conversation timeline.
-    #[must_use]
-    pub fn timeline(&self) -> &Timeline {
-        &self.timeline
+    /// Sets the restrictions applied to `web_fetch` requests.
+    ///
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_web_fetch_config(&mut self, config: WebFetchConfig) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_web_fetch_config(config),
+            None => tracing::warn!("Tool executor in use; web fetch settings unchanged"),
+        }
     }
 
-    /// Returns a mutable reference to the conversation timeline.
-    pub fn timeline_mut(&mut self) -> &mut Timeline {
-        &mut self.timeline
+    /// Sets the provider and endpoint used by `web_search`.
+    ///
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_web_search_config(&mut self, config: WebSearchConfig) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_web_search_config(config),
+            None => tracing::warn!("Tool executor in use; web search settings unchanged"),
+        }
     }
 
-    /// Starts streaming mode.
+    /// Sets the size limits and downscaling used by `analyze_image`.
     ///
-    /// This creates a streaming entry in the timeline.
-    pub fn set_streaming(&mut self, _streaming: bool) {
-        self.loading = true;
-
-        // Add streaming entry to timeline
-        if self.timeline.try_push_streaming().is_err() {
-            // Already streaming - this is a no-op
-            tracing::warn!("set_streaming called but timeline already streaming");
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_vision_config(&mut self, config: VisionConfig) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_vision_config(config),
+            None => tracing::warn!("Tool executor in use; vision settings unchanged"),
         }
+    }
 
-        self.dirty.messages = true;
+    /// Sets the size in bytes above which tool output is saved to a file.
+    ///
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_tool_output_threshold(&mut self, threshold: Option<usize>) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_tool_output_threshold(threshold),
+            None => tracing::warn!("Tool executor in use; tool output threshold unchanged"),
+        }
     }
 
-    /// Appends text to the current streaming response.
+    /// Sets how long one tool call may run before it is cancelled.
     ///
-    /// Updates the timeline streaming entry.
-    pub fn append_streaming_text(&mut self, text: &str) {
-        // Update timeline streaming entry
-        self.timeline.append_to_streaming(text);
-        self.dirty.messages = true;
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_tool_timeout(&mut self, timeout: Option<std::time::Duration>) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_tool_timeout(timeout),
+            None => tracing::warn!("Tool executor in use; tool timeout unchanged"),
+        }
     }
 
-    /// Finalizes streaming as a complete assistant message.
+    /// Sets the safety classes declared for MCP tools in parallel batches.
     ///
-    /// Converts the streaming entry to an assistant message in the timeline.
-    pub fn finalize_streaming_as_message(&mut self) {
-        // Finalize timeline streaming entry
-        self.timeline.finalize_streaming_as_message();
-        self.loading = false;
-        self.dirty.messages = true;
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_mcp_tool_safety(&mut self, rules: &HashMap<String, ToolSafetyClass>) {
+        let registry = rules
+            .iter()
+            .map(|(pattern, class)| (pattern.clone(), *class))
+            .collect();
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_mcp_safety(registry),
+            None => tracing::warn!("Tool executor in use; MCP tool safety unchanged"),
+        }
     }
 
-    /// Adds a tool block with result to both legacy tool_blocks and timeline.
+    /// Sets the baseline permission mode and the directory rule that chose it.
     ///
-    /// # Arguments
+    /// Must be called before any tools run, while the permission manager is idle.
+    pub fn set_permission_mode(&mut self, mode: PermissionMode, rule: Option<String>) {
+        match self.permission_manager.try_lock() {
+            Ok(mut manager) => {
+                manager.set_mode(mode);
+                self.permission_mode = mode;
+                self.permission_mode_rule = rule;
+                self.dirty.full = true;
+            }
+            Err(_) => tracing::warn!("Permission manager in use; permission mode unchanged"),
+        }
+    }
+
+    /// Replaces permission prompts with `allowlist` for this session.
     ///
-    /// * `tool_name` - Name of the tool (e.g., "bash", "read_file")
-    /// * `input` - Tool input/command
-    /// * `output` - Tool output
-    /// * `is_error` - Whether the execution resulted in an error
-    pub fn add_tool_block_with_result(
-        &mut self,
-        tool_name: &str,
-        input: &str,
-        output: &str,
-        is_error: bool,
-    ) {
-        // Add to legacy tool_blocks using the constructor
-        let mut block = ToolBlockState::new(tool_name, input);
-        if is_error {
-            block.set_error(output);
-        } else {
-            block.set_result(output);
+    /// Must be called before any tools run, while the permission manager is idle.
+    pub fn set_permission_allowlist(&mut self, allowlist: Allowlist) {
+        match self.permission_manager.try_lock() {
+            Ok(mut manager) => manager.set_allowlist(Some(allowlist)),
+            Err(_) => tracing::warn!("Permission manager in use; allowlist not set"),
         }
-        self.tool_blocks.push(block);
+    }
 
-        // Add to timeline with message index tracking
-        self.timeline.push_tool_after_current_assistant(
-            tool_name,
-            input,
-            Some(output.to_string()),
-            is_error,
-        );
+    /// Returns the directory file tools are confined to.
+    #[must_use]
+    pub fn confinement_root(&self) -> &std::path::Path {
+        self.tool_executor.confinement_root()
+    }
 
-        self.dirty.messages = true;
+    /// Loads persistent permission rules from `path`; rules added later are
+    /// saved there.
+    ///
+    /// Must be called before any tools run, while the permission manager is idle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be parsed.
+    pub fn load_permission_rules(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        match self.permission_manager.try_lock() {
+            Ok(mut manager) => manager.load_from(path),
+            Err(_) => anyhow::bail!("permission manager in use; rules not loaded"),
+        }
     }
 
-    /// Clears all conversation state (timeline, API messages, tool blocks).
-    pub fn clear_conversation(&mut self) {
-        self.api_messages.clear();
-        self.tool_blocks.clear();
-        self.timeline = Timeline::new();
-        self.dirty.messages = true;
+    /// Adds a persistent allow rule for the `/allow` command.
+    fn allow_rule(&mut self, rule: PermissionRule) -> String {
+        match self.permission_manager.try_lock() {
+            Ok(mut manager) => {
+                let output = format!("Always allowing {}.", rule);
+                manager.add_rule(rule);
+                output
+            }
+            Err(_) => "Tools are running; try again when they finish.".to_string(),
+        }
     }
 
-    // ========================================================================
-    // Async Tool Execution (Phase 5)
-    // ========================================================================
+    /// Formats the persistent allow rules for the `/allow` command.
+    fn format_allow_rules(&self) -> String {
+        let Ok(manager) = self.permission_manager.try_lock() else {
+            return "Tools are running; try again when they finish.".to_string();
+        };
+        let rules: Vec<String> = manager
+            .rules()
+            .iter()
+            .filter(|rule| rule.allow)
+            .map(ToString::to_string)
+            .collect();
+        if rules.is_empty() {
+            "No allow rules. Add one with /allow <tool>:<pattern>.".to_string()
+        } else {
+            format!("Allow rules:\n  {}", rules.join("\n  "))
+        }
+    }
 
-    /// Sets the receiver channel for async tool results.
-    ///
-    /// When tool execution is spawned in the background, results will be
-    /// streamed back through this channel.
-    pub fn set_tool_result_rx(
-        &mut self,
-        rx: mpsc::UnboundedReceiver<(String, crate::types::ToolResultBlock)>,
-    ) {
-        self.tool_result_rx = Some(rx);
+    /// Returns the baseline permission mode.
+    #[must_use]
+    pub fn permission_mode(&self) -> PermissionMode {
+        self.permission_mode
     }
 
-    /// Returns true if a tool result channel is currently set.
+    /// Returns the directory rule that selected the permission mode, if any.
     #[must_use]
-    pub fn has_tool_result_rx(&self) -> bool {
-        self.tool_result_rx.is_some()
+    pub fn permission_mode_rule(&self) -> Option<&str> {
+        self.permission_mode_rule.as_deref()
     }
 
-    /// Attempts to receive a tool result without blocking.
+    /// Sets whether files are backed up before tools modify them.
     ///
-    /// Returns `Some((tool_id, result))` if a result is available,
-    /// `None` if no result is ready or channel is not set.
-    pub fn try_recv_tool_result(&mut self) -> Option<(String, crate::types::ToolResultBlock)> {
-        if let Some(ref mut rx) = self.tool_result_rx {
-            rx.try_recv().ok()
-        } else {
-            None
+    /// Must be called before any tools run, while the executor is not shared.
+    pub fn set_backups_enabled(&mut self, enabled: bool) {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => executor.set_backups_enabled(enabled),
+            None => tracing::warn!("Tool executor in use; backup setting unchanged"),
         }
     }
 
-    /// Receives a tool result asynchronously.
-    ///
-    /// Returns `None` immediately if no channel is set, otherwise waits for
-    /// the next result. This is designed for use in `tokio::select!`.
+    /// Records every tool execution in a tamper-evident audit log.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// - `Some((tool_id, result))` - A tool completed execution
-    /// - `None` - Channel closed or no channel set
-    pub async fn recv_tool_result(&mut self) -> Option<(String, crate::types::ToolResultBlock)> {
-        match &mut self.tool_result_rx {
-            Some(rx) => rx.recv().await,
-            None => None, // Return immediately - don't block with pending()
+    /// Returns an error if the log cannot be opened or the executor is
+    /// already shared.
+    pub fn enable_tool_audit(&mut self, config: ToolAuditConfig) -> Result<()> {
+        let log = ToolAuditLog::open(config)?;
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => {
+                executor.set_audit_log(log);
+                Ok(())
+            }
+            None => anyhow::bail!("Tool executor in use; audit log could not be enabled"),
         }
     }
 
-    /// Clears the tool result channel.
+    /// Redacts secrets from tool output before it is displayed, sent to the
+    /// model, saved or audited.
     ///
-    /// Called after all tools have completed and results have been processed.
-    pub fn clear_tool_result_rx(&mut self) {
-        self.tool_result_rx = None;
+    /// # Errors
+    ///
+    /// Returns an error if the executor is already shared, as output would
+    /// otherwise go unredacted.
+    pub fn set_redactor(&mut self, redactor: Redactor) -> Result<()> {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => {
+                executor.set_redactor(redactor);
+                Ok(())
+            }
+            None => anyhow::bail!("Tool executor in use; redaction could not be enabled"),
+        }
     }
 
-    /// Adds a pending tool to the tool loop.
+    /// Enforces an organization policy on tool execution.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `tool_use` - The tool use block to add
-    pub fn add_pending_tool(&mut self, tool_use: crate::types::ToolUseBlock) {
-        self.tool_loop.add_tool_use(tool_use);
+    /// Returns an error if the executor is already shared, as the policy
+    /// could not be enforced.
+    pub fn apply_org_policy(&mut self, org: &OrgPolicy) -> Result<()> {
+        match Arc::get_mut(&mut self.tool_executor) {
+            Some(executor) => {
+                executor.apply_org_policy(org);
+                Ok(())
+            }
+            None => anyhow::bail!("Tool executor in use; org policy could not be applied"),
+        }
     }
 
-    /// Spawns tool execution in the background.
-    ///
-    /// Returns immediately with a handle to the background task.
-    /// Results are sent through the tool_result_rx channel.
+    /// Checks a command Patina is about to launch against the execution
+    /// policy, including any org policy applied.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// `Some(JoinHandle)` if tools were spawned, `None` if no tools pending.
+    /// Returns why the command is blocked.
+    pub fn check_command(&self, command: &str) -> Result<(), String> {
+        self.tool_executor.check_command(command)
+    }
+
+    /// Sets the named request parameter presets available to `/preset`.
+    pub fn set_presets(&mut self, presets: HashMap<String, RequestParams>) {
+        self.presets = presets;
+    }
+
+    /// Returns the active request parameter preset, if any.
     #[must_use]
-    pub fn spawn_tool_execution(
-        &mut self,
-    ) -> Option<tokio::task::JoinHandle<Vec<(String, crate::types::ToolResultBlock)>>> {
-        // Create channel for results
-        let (tx, rx) = mpsc::unbounded_channel();
-        self.tool_result_rx = Some(rx);
+    pub fn active_preset(&self) -> Option<&RequestPreset> {
+        self.active_preset.as_ref()
+    }
 
-        // Get pending tools
-        let pending: Vec<_> = self
-            .tool_loop
-            .pending_calls()
-            .iter()
-            .filter(|(_, call)| !call.executed)
-            .map(|(id, call)| (id.clone(), call.tool_use.clone()))
-            .collect();
+    /// Returns the request parameters for the next API call.
+    #[must_use]
+    pub fn request_params(&self) -> RequestParams {
+        self.active_preset
+            .as_ref()
+            .map(|preset| preset.params.clone())
+            .unwrap_or_default()
+    }
 
-        if pending.is_empty() {
-            return None;
+    /// Returns a client for the next API call, with the current model and parameters.
+    ///
+    /// Instructions of the active skills and the editor context reported by
+    /// an IDE are appended to the system prompt, so changes to either apply
+    /// from the next request.
+    #[must_use]
+    pub fn request_client(&self, client: &AnthropicClient) -> AnthropicClient {
+        let mut client = client.clone().with_params(self.request_params());
+        let editor = self
+            .session_context
+            .as_ref()
+            .and_then(SessionContext::editor_prompt);
+        for section in [self.skill_system_prompt(), editor].into_iter().flatten() {
+            let system = match client.system_prompt() {
+                Some(base) => format!("{}\n\n{}", base, section),
+                None => section,
+            };
+            client = client.with_system_prompt(system);
         }
-
-        // Mark all as executing
-        for (id, _) in &pending {
-            self.executing_tool_ids.insert(id.clone());
+        match &self.model {
+            Some(model) => client.with_model(model.clone()),
+            None => client,
         }
+    }
 
-        let executor = Arc::clone(&self.tool_executor);
+    /// Sets the model used for subsequent API requests.
+    ///
+    /// Later usage is priced against the session budget at the new model's rates.
+    pub fn set_model(&mut self, model: impl Into<String>) {
+        let model = model.into();
+        if let Some(budget) = self.session_budget.as_mut() {
+            budget.set_model(model.clone());
+        }
+        self.model = Some(model);
+        self.dirty.full = true;
+    }
 
-        // Spawn background task
-        let handle = tokio::spawn(async move {
-            use crate::app::tool_loop::tool_use_to_call;
-            use crate::tools::ToolResult as TR;
+    /// Returns the model set for API requests, if any.
+    #[must_use]
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
 
-            let mut results = Vec::new();
-            for (tool_id, tool_use) in pending {
-                let call = tool_use_to_call(&tool_use);
-                let result = executor.execute(call).await;
+    /// Sets the globally disabled tools and the per-model tool profiles.
+    pub fn set_tool_profiles(
+        &mut self,
+        disabled_tools: Vec<String>,
+        tool_profiles: HashMap<String, ToolProfile>,
+    ) {
+        self.disabled_tools = disabled_tools;
+        self.tool_profiles = tool_profiles;
+    }
 
-                let result_block = match &result {
-                    Ok(TR::Success(output)) => crate::types::ToolResultBlock {
-                        tool_use_id: tool_id.clone(),
-                        content: output.clone(),
-                        is_error: false,
-                    },
-                    Ok(TR::Error(error)) => crate::types::ToolResultBlock {
-                        tool_use_id: tool_id.clone(),
-                        content: error.clone(),
-                        is_error: true,
-                    },
-                    Ok(TR::Cancelled) => crate::types::ToolResultBlock {
-                        tool_use_id: tool_id.clone(),
-                        content: "Tool execution cancelled".to_string(),
-                        is_error: true,
-                    },
-                    Ok(TR::NeedsPermission(perm)) => crate::types::ToolResultBlock {
-                        tool_use_id: tool_id.clone(),
-                        content: format!("Permission required: {perm:?}"),
-                        is_error: true,
-                    },
-                    Err(e) => crate::types::ToolResultBlock {
-                        tool_use_id: tool_id.clone(),
-                        content: e.to_string(),
-                        is_error: true,
-                    },
-                };
+    /// Returns the tools advertised to the current model.
+    #[must_use]
+    pub fn tools(&self) -> Vec<ToolDefinition> {
+        let mut extra_tools = self.plugin_registry.tools().definitions();
+        extra_tools.extend(self.mcp_tools.iter().cloned());
+        tools_for_model(
+            self.model.as_deref().unwrap_or_default(),
+            &self.disabled_tools,
+            &self.tool_profiles,
+            &extra_tools,
+        )
+    }
 
-                // Send through channel (ignore error if receiver dropped)
-                let _ = tx.send((tool_id.clone(), result_block.clone()));
-                results.push((tool_id, result_block));
-            }
-            results
-        });
+    /// Formats the current model and its tools for the `/model` command.
+    fn format_model(&self) -> String {
+        let names: Vec<String> = self.tools().into_iter().map(|t| t.name).collect();
+        format!(
+            "Model: {}\nTools: {}",
+            self.model.as_deref().unwrap_or("(default)"),
+            names.join(", ")
+        )
+    }
 
-        Some(handle)
+    /// Formats estimated context usage for the `/tokens` command.
+    fn format_tokens(&self) -> String {
+        let used = estimate_messages_tokens(&self.api_messages);
+        let percent = if self.context_window == 0 {
+            0.0
+        } else {
+            used as f64 / self.context_window as f64 * 100.0
+        };
+        let tool_results: usize = self
+            .api_messages
+            .iter()
+            .filter_map(|message| message.content.as_blocks())
+            .map(|blocks| blocks.iter().filter(|block| block.is_tool_result()).count())
+            .sum();
+        format!(
+            "Context: ~{} of {} tokens ({:.1}%)\nMessages: {} ({} tool results)",
+            used,
+            self.context_window,
+            percent,
+            self.api_messages.len(),
+            tool_results
+        )
     }
 
-    /// Returns true if there are any tools currently executing.
-    #[must_use]
-    pub fn has_executing_tools(&self) -> bool {
-        !self.executing_tool_ids.is_empty()
+    /// Formats the available presets for the `/preset` command.
+    fn format_presets(&self) -> String {
+        let active = self.active_preset.as_ref().map(|p| p.name.as_str());
+        let mut names: Vec<&String> = self.presets.keys().collect();
+        names.sort();
+
+        let mut output = match active {
+            Some(name) => format!("Active preset: {}\n", name),
+            None => "Active preset: (default)\n".to_string(),
+        };
+        if names.is_empty() {
+            output.push_str("No presets defined in settings.toml.");
+        } else {
+            output.push_str("Available presets:");
+            for name in names {
+                let params = &self.presets[name];
+                let mut fields = Vec::new();
+                if let Some(t) = params.temperature {
+                    fields.push(format!("temperature={}", t));
+                }
+                if let Some(p) = params.top_p {
+                    fields.push(format!("top_p={}", p));
+                }
+                if let Some(m) = params.max_tokens {
+                    fields.push(format!("max_tokens={}", m));
+                }
+                output.push_str(&format!("\n  {} ({})", name, fields.join(", ")));
+            }
+        }
+        output
     }
 
-    /// Marks a tool as currently executing.
+    /// Creates a `Session` from the current application state.
     ///
-    /// # Arguments
+    /// The resulting session includes:
+    /// - All conversation messages (converted from timeline)
+    /// - Current UI state (scroll position, input buffer, cursor position, focus and selection)
+    /// - Working directory
+    /// - Context files and active skills, if any were restored
+    /// - The worktree link, if the session was moved into a worktree
+    /// - Shell state (working directory and exported variables of the bash tool)
     ///
-    /// * `tool_id` - The ID of the tool to mark as executing
-    pub fn mark_tool_executing(&mut self, tool_id: &str) {
-        self.executing_tool_ids.insert(tool_id.to_string());
+    /// This is used for auto-save functionality.
+    #[must_use]
+    pub fn to_session(&self) -> Session {
+        use crate::session::UiState;
+
+        let mut session = Session::new(self.working_dir.clone());
+
+        // Convert timeline entries to messages for session persistence
+        for entry in self.timeline.iter() {
+            match entry {
+                crate::types::ConversationEntry::UserMessage(text) => {
+                    session.add_message(Message {
+                        role: Role::User,
+                        content: text.clone(),
+                    });
+                }
+                crate::types::ConversationEntry::AssistantMessage(text) => {
+                    session.add_message(Message {
+                        role: Role::Assistant,
+                        content: text.clone(),
+                    });
+                }
+                // Skip streaming and tool execution entries for session persistence
+                _ => {}
+            }
+        }
+
+        // Capture UI state (use scroll offset for backward compatibility)
+        let ui_state =
+            UiState::with_state(self.scroll.offset(), self.input.clone(), self.cursor_pos)
+                .with_focus(self.focus_area, self.selection.range());
+        session.set_ui_state(Some(ui_state));
+
+        for tag in &self.session_tags {
+            session.add_tag(tag.clone());
+        }
+        session.set_preset(self.active_preset.clone());
+        session.set_name(self.session_name.clone());
+        session.set_title(self.session_title.clone());
+        session.set_context(self.session_context.clone());
+        session.set_worktree_session(self.worktree_session.clone());
+        session.set_shell_state(Some(self.tool_executor.shell_state().snapshot()));
+
+        session
     }
 
-    /// Records a tool result and removes the tool from executing set.
+    /// Restores application state from a saved session.
+    ///
+    /// This restores:
+    /// - Message history (to timeline)
+    /// - UI state (scroll position, input buffer, cursor position, focus and
+    ///   selection) if saved
+    /// - Session ID for subsequent saves
+    /// - Context files and active skills
+    /// - The worktree link
+    /// - Shell state, if the saved directory is still inside the working directory
     ///
     /// # Arguments
     ///
-    /// * `tool_id` - The ID of the tool that completed
-    /// * `result` - The result of the tool execution
-    pub fn record_tool_result(&mut self, tool_id: &str, result: crate::types::ToolResultBlock) {
-        // Remove from executing set
-        self.executing_tool_ids.remove(tool_id);
+    /// * `session` - The session to restore from.
+    pub fn restore_from_session(&mut self, session: &Session) {
+        // Clear and rebuild timeline from session messages
+        self.timeline = Timeline::new();
+        for message in session.messages() {
+            match message.role {
+                Role::User => self.timeline.push_user_message(&message.content),
+                Role::Assistant => self.timeline.push_assistant_message(&message.content),
+            }
+        }
 
-        // Update tool loop with result (ignore error if tool not found)
-        let _ = self.tool_loop.set_tool_result(tool_id, result.clone());
+        // Restore UI state if available
+        if let Some(ui_state) = session.ui_state() {
+            self.scroll.restore_offset(ui_state.scroll_offset());
+            self.input = ui_state.input_buffer().to_string();
+            self.cursor_pos = ui_state.cursor_position().min(self.input.chars().count());
+
+            // Set focus first: changing focus clears the selection
+            self.set_focus_area(ui_state.focus_area());
+            match ui_state.selection() {
+                // Clamped to the rendered content once it is drawn
+                Some((start, end)) => self.selection.restore(start, end),
+                None => self.selection.clear(),
+            }
+            self.rendered_lines_cache.clear();
+        }
 
-        // Update timeline tool entry if it exists
-        self.update_timeline_tool_by_id(tool_id, Some(result.content), result.is_error);
+        // Restore session ID if available
+        if let Some(id) = session.id() {
+            self.session_id = Some(id.to_string());
+        }
 
-        self.dirty.messages = true;
+        self.session_tags = session.tags().to_vec();
+        self.active_preset = session.preset().cloned();
+        self.session_name = session.name().map(String::from);
+        self.session_title = session.title().map(String::from);
+        self.session_context = session.context().cloned();
+        self.worktree_session = session.worktree_session().cloned();
+        if let Some(shell_state) = session.shell_state() {
+            self.tool_executor.restore_shell_state(shell_state);
+        }
+
+        // Mark for full redraw
+        self.dirty.full = true;
     }
 
-    /// Returns true if all pending tools have completed execution.
+    // ========================================================================
+    // Tool Execution Integration
+    // ========================================================================
+
+    /// Returns a reference to the tool loop state.
     #[must_use]
-    pub fn all_tools_complete(&self) -> bool {
-        self.executing_tool_ids.is_empty()
-            && self
-                .tool_loop
-                .pending_calls()
-                .values()
-                .all(|call| call.executed || call.result.is_some())
+    pub fn tool_loop(&self) -> &ToolLoop {
+        &self.tool_loop
     }
 
-    /// Adds a tool to the timeline in executing state (no output yet).
-    ///
-    /// # Arguments
-    ///
-    /// * `tool_name` - Name of the tool (e.g., "bash")
-    /// * `input` - The tool input/command
-    pub fn add_tool_to_timeline_executing(&mut self, tool_name: &str, input: &str) {
-        self.timeline.push_tool_after_current_assistant(
-            tool_name, input, None, // No output yet - executing
-            false,
-        );
-        self.dirty.messages = true;
+    /// Returns a mutable reference to the tool loop.
+    pub fn tool_loop_mut(&mut self) -> &mut ToolLoop {
+        &mut self.tool_loop
     }
 
-    /// Updates a tool in the timeline with its result.
+    /// Sets how many tool loop iterations one prompt may take.
+    pub fn set_max_tool_iterations(&mut self, max_iterations: usize) {
+        self.tool_loop.set_max_iterations(max_iterations);
+    }
+
+    /// Returns the current tool loop state.
+    #[must_use]
+    pub fn tool_loop_state(&self) -> &ToolLoopState {
+        self.tool_loop.state()
+    }
+
+    /// Returns the pending permission request, if any.
+    #[must_use]
+    pub fn pending_permission(&self) -> Option<&PermissionRequest> {
+        self.pending_permission.as_ref()
+    }
+
+    /// Returns true if there's a pending permission prompt.
+    #[must_use]
+    pub fn has_pending_permission(&self) -> bool {
+        self.pending_permission.is_some()
+    }
+
+    /// Sets a pending permission request.
     ///
-    /// Finds the most recent tool entry with the given name that has no output
-    /// and updates it with the provided output.
+    /// The UI should display this as a modal prompt.
+    pub fn set_pending_permission(&mut self, request: PermissionRequest) {
+        self.pending_permission = Some(request);
+        self.permission_reason = None;
+        self.dirty.full = true;
+    }
+
+    /// Clears the pending permission request.
+    pub fn clear_pending_permission(&mut self) {
+        self.pending_permission = None;
+        self.permission_reason = None;
+        self.dirty.full = true;
+    }
+
+    /// Returns the denial reason being typed in the permission prompt, if any.
+    #[must_use]
+    pub fn permission_reason(&self) -> Option<&str> {
+        self.permission_reason.as_deref()
+    }
+
+    /// Sets the denial reason being typed (`None` leaves reason-input mode).
+    pub fn set_permission_reason(&mut self, reason: Option<String>) {
+        self.permission_reason = reason;
+        self.dirty.full = true;
+    }
+
+    /// Handles a permission response from the user.
     ///
-    /// # Arguments
+    /// This grants or denies permission for the pending tool call and
+    /// updates the permission manager accordingly. A denial refuses every
+    /// call that has not run yet, so each is recorded in the audit log.
+    pub async fn handle_permission_response(&mut self, response: PermissionResponse) {
+        if let Some(request) = self.pending_permission.take() {
+            let allowed = response.is_allowed();
+            self.permission_manager.lock().await.handle_response(
+                &request.tool_name,
+                request.tool_input.as_deref(),
+                response,
+            );
+            if !allowed {
+                let refused: Vec<(String, Value)> = self
+                    .tool_loop
+                    .pending_calls()
+                    .values()
+                    .filter(|call| call.result.is_none())
+                    .map(|call| (call.tool_use.name.clone(), call.tool_use.input.clone()))
+                    .collect();
+                for (name, input) in refused {
+                    self.tool_executor
+                        .record_audit(&name, &input, ToolAuditStatus::Rejected)
+                        .await;
+                }
+            }
+            self.dirty.full = true;
+        }
+    }
+
+    /// Handles a tool_use stream event.
     ///
-    /// * `tool_name` - Name of the tool to update
-    /// * `output` - The tool output (None if still executing)
-    /// * `is_error` - Whether the result is an error
-    pub fn update_tool_in_timeline(
-        &mut self,
-        tool_name: &str,
-        output: Option<String>,
-        is_error: bool,
-    ) {
-        self.timeline
-            .update_tool_result(tool_name, output, is_error);
+    /// Routes the event to the tool loop state machine.
+    pub fn handle_tool_use_start(&mut self, id: String, name: String, index: usize) {
+        self.tool_loop.start_tool_use(index, id, name);
         self.dirty.messages = true;
     }
 
-    /// Updates a tool in the timeline by its ID.
+    /// Handles a tool_use input delta.
+    pub fn handle_tool_use_input_delta(&mut self, index: usize, partial_json: &str) {
+        self.tool_loop.append_tool_input(index, partial_json);
+    }
+
+    /// Handles tool_use completion.
+    pub fn handle_tool_use_complete(&mut self, index: usize) -> Result<()> {
+        self.tool_loop
+            .complete_tool_use(index)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(())
+    }
+
+    /// Handles message completion with a stop reason.
     ///
-    /// This is used internally when recording tool results.
-    fn update_timeline_tool_by_id(
-        &mut self,
-        _tool_id: &str,
-        output: Option<String>,
-        is_error: bool,
-    ) {
-        // For now, update the most recent executing tool
-        // In the future, we could track tool_id -> timeline_index mapping
-        for entry in self.timeline.entries_mut().iter_mut().rev() {
-            if let crate::types::ConversationEntry::ToolExecution {
-                output: ref mut o @ None,
-                is_error: ref mut err,
-                ..
-            } = entry
-            {
-                *o = output;
-                *err = is_error;
-                break;
-            }
+    /// If the stop reason is `ToolUse`, transitions the tool loop to
+    /// `PendingApproval` state.
+    pub fn handle_message_complete(&mut self, stop_reason: StopReason) -> Result<()> {
+        self.tool_loop
+            .message_complete(stop_reason)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(())
+    }
+
+    /// Executes all pending tools that have been approved.
+    ///
+    /// Creates tool blocks for UI display, executes the tools, and
+    /// updates the blocks with results.
+    ///
+    /// Returns a list of tool IDs that need permission (if any).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool loop is not in `Executing` state.
+    pub async fn execute_pending_tools(&mut self) -> Result<Vec<String>> {
+        use crate::app::tool_loop::ToolLoopError;
+        use std::collections::HashMap;
+
+        // Collect tool info before creating blocks (to avoid borrow issues)
+        let tools_to_display: Vec<(String, String, String)> = self
+            .tool_loop
+            .pending_calls()
+            .iter()
+            .filter(|(_, call)| call.approved && !call.executed)
+            .map(|(tool_id, call)| {
+                let input_str = format_tool_input(&call.tool_use.name, &call.tool_use.input);
+                (tool_id.clone(), call.tool_use.name.clone(), input_str)
+            })
+            .collect();
+
+        // Create tool blocks for pending tools (before execution)
+        let mut tool_id_to_block_index: HashMap<String, usize> = HashMap::new();
+        for (tool_id, tool_name, input_str) in tools_to_display {
+            let index = self.start_tool_block(&tool_name, &input_str);
+            tool_id_to_block_index.insert(tool_id, index);
         }
-        self.dirty.messages = true;
+
+        // Execute the tools
+        let result = self
+            .tool_loop
+            .execute_pending(&self.tool_executor)
+            .await
+            .map_err(|e| match e {
+                ToolLoopError::InvalidStateTransition { from, to } => {
+                    anyhow::anyhow!("Invalid state transition from {} to {}", from, to)
+                }
+                _ => anyhow::anyhow!("{}", e),
+            })?;
+
+        // Collect results (to avoid borrow issues)
+        let results: Vec<(String, String, bool)> = self
+            .tool_loop
+            .pending_calls()
+            .iter()
+            .filter_map(|(tool_id, call)| {
+                if let Some(result_block) = &call.result {
+                    if tool_id_to_block_index.contains_key(tool_id) {
+                        return Some((
+                            tool_id.clone(),
+                            result_block.content.clone(),
+                            result_block.is_error,
+                        ));
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Update tool blocks with results
+        for (tool_id, content, is_error) in results {
+            if let Some(&block_index) = tool_id_to_block_index.get(&tool_id) {
+                self.complete_tool_block(block_index, &content, is_error);
+            }
+            if !is_error {
+                self.notify_ide_of_file_change(&tool_id);
+            }
+        }
+
+        self.record_context_resources();
+        self.activate_skills_for_tool_files();
+        self.record_file_edits();
+
+        Ok(result)
+    }
+
+    /// Records MCP resources read by `read_mcp_resource` in the session context.
+    fn record_context_resources(&mut self) {
+        let resources: Vec<ContextResource> = self
+            .tool_loop
+            .pending_calls()
+            .values()
+            .filter(|call| call.tool_use.name == "read_mcp_resource")
+            .filter(|call| call.result.as_ref().is_some_and(|r| !r.is_error))
+            .filter_map(|call| {
+                let input = &call.tool_use.input;
+                let server = input.get("server")?.as_str()?;
+                let uri = input.get("uri")?.as_str()?;
+                Some(ContextResource::new(server, uri))
+            })
+            .collect();
+
+        if resources.is_empty() {
+            return;
+        }
+        let context = self.session_context.get_or_insert_with(SessionContext::new);
+        for resource in resources {
+            context.add_resource(resource);
+        }
+    }
+
+    /// Turns on skills matching files that file tools used successfully.
+    fn activate_skills_for_tool_files(&mut self) {
+        let files: Vec<PathBuf> = self
+            .tool_loop
+            .pending_calls()
+            .values()
+            .filter(|call| {
+                matches!(
+                    call.tool_use.name.as_str(),
+                    "read_file" | "write_file" | "edit"
+                )
+            })
+            .filter(|call| call.result.as_ref().is_some_and(|r| !r.is_error))
+            .filter_map(|call| call.tool_use.input.get("path")?.as_str().map(PathBuf::from))
+            .collect();
+
+        if !files.is_empty() {
+            self.auto_activate_skills(&ActivationContext {
+                prompt: "",
+                files: &files,
+            });
+        }
+    }
+
+    /// Tells connected IDE clients about a file changed by a successful tool call.
+    ///
+    /// `edit` calls carry the same diff the tool reports; `write_file` calls
+    /// carry none, as the whole file was replaced.
+    fn notify_ide_of_file_change(&self, tool_id: &str) {
+        let Some(notifier) = &self.ide_notifier else {
+            return;
+        };
+        let Some(call) = self.tool_loop.pending_calls().get(tool_id) else {
+            return;
+        };
+        let input = &call.tool_use.input;
+        let text = |key: &str| input.get(key).and_then(Value::as_str);
+        let diff = match call.tool_use.name.as_str() {
+            "edit" => match (text("old_string"), text("new_string")) {
+                (Some(old), Some(new)) => Some(ToolExecutor::generate_diff(old, new)),
+                _ => None,
+            },
+            "write_file" => None,
+            _ => return,
+        };
+        let Some(path) = text("path") else {
+            return;
+        };
+        notifier.notify(&RpcNotification::file_did_change(
+            &self.working_dir.join(path),
+            diff,
+        ));
+    }
+
+    /// Notes the files of successful `write_file` and `edit` calls for
+    /// worktree auto-commit.
+    fn record_file_edits(&mut self) {
+        let edited = self.tool_loop.pending_calls().values().filter_map(|call| {
+            if !matches!(call.tool_use.name.as_str(), "write_file" | "edit")
+                || !call.result.as_ref().is_some_and(|r| !r.is_error)
+            {
+                return None;
+            }
+            let path = call.tool_use.input.get("path")?.as_str()?;
+            Some(self.working_dir.join(normalize_path(path)))
+        });
+        let edited: Vec<PathBuf> = edited.collect();
+        for path in edited {
+            if !self.turn_edited_files.contains(&path) {
+                self.turn_edited_files.push(path);
+            }
+        }
+    }
+
+    /// Returns the files, resources and skills tracked for session resume.
+    #[must_use]
+    pub fn session_context(&self) -> Option<&SessionContext> {
+        self.session_context.as_ref()
+    }
+
+    /// Finishes tool execution and returns continuation data.
+    ///
+    /// The continuation data contains the messages needed to continue
+    /// the conversation with Claude.
+    pub fn finish_tool_execution(&mut self) -> Result<ContinuationData> {
+        self.tool_loop
+            .finish_execution()
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    /// Approves all pending tools for execution.
+    pub fn approve_all_tools(&mut self) -> Result<()> {
+        self.tool_loop
+            .approve_all()
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    /// Denies all pending tools.
+    ///
+    /// Also used when the user denies a permission prompt raised while tools
+    /// were executing; calls that already ran are dropped with the rest.
+    pub fn deny_all_tools(&mut self) -> Result<()> {
+        self.tool_loop
+            .deny_all()
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    /// Denies all pending tools, answering each with the user's reason.
+    ///
+    /// Calls that already ran keep their results. The loop is left ready for
+    /// `finish_tool_execution` so the reason reaches the model as the tool
+    /// results.
+    pub fn deny_all_tools_with_reason(&mut self, reason: &str) -> Result<()> {
+        let unanswered: Vec<String> = self
+            .tool_loop
+            .pending_calls()
+            .iter()
+            .filter(|(_, call)| call.result.is_none())
+            .map(|(id, _)| id.clone())
+            .collect();
+        self.tool_loop
+            .deny_all_with_reason(reason)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        for tool_id in unanswered {
+            self.update_timeline_tool_by_id(&tool_id, Some(format!("denied: {reason}")), true);
+        }
+        Ok(())
+    }
+
+    /// Resets the tool loop to idle state.
+    pub fn reset_tool_loop(&mut self) {
+        self.tool_loop.reset();
+        self.pending_permission = None;
+        self.permission_reason = None;
+        self.dirty.full = true;
+    }
+
+    /// Watchdog for a wedged tool loop.
+    ///
+    /// If nothing is in flight and the loop is in a state no event can move
+    /// forward, resets it to idle and tells the user. Returns true if a reset
+    /// happened.
+    pub fn recover_wedged_tool_loop(&mut self) -> bool {
+        if self.is_busy() {
+            return false;
+        }
+        let Some(reason) = self.tool_loop.recover_from_inconsistency() else {
+            return false;
+        };
+
+        tracing::warn!(reason = %reason, "Tool loop wedged; reset to idle");
+        self.pending_permission = None;
+        self.permission_reason = None;
+        self.executing_tool_ids.clear();
+        self.timeline.push_assistant_message(format!(
+            "Tool loop was stuck ({}). Reset to idle and discarded partial tool data; you can continue.",
+            reason
+        ));
+        self.dirty.full = true;
+        true
+    }
+
+    /// Handles `/reset-loop`: resets a stuck tool loop to idle.
+    ///
+    /// Leaves the loop alone while a response streams, tools run, or a
+    /// continuation waits on the session budget.
+    fn reset_stuck_tool_loop(&mut self) -> String {
+        if self.is_busy() {
+            return "A response is streaming or tools are running; the tool loop was not reset."
+                .to_string();
+        }
+        if self.continuation_deferred {
+            return format!(
+                "The tool loop is waiting on the session budget. {}",
+                self.budget_status()
+            );
+        }
+
+        let reason = match self.tool_loop.inconsistency() {
+            Some(reason) => reason,
+            None if *self.tool_loop.state() == ToolLoopState::Idle => {
+                return "The tool loop is idle; nothing to reset.".to_string();
+            }
+            None => format!("{:?}", self.tool_loop.state()),
+        };
+
+        tracing::warn!(reason = %reason, "Tool loop reset by user");
+        self.reset_tool_loop();
+        self.executing_tool_ids.clear();
+        format!(
+            "Tool loop was stuck ({}). Reset to idle and discarded partial tool data.",
+            reason
+        )
+    }
+
+    /// Returns true if the tool loop is waiting for user action.
+    #[must_use]
+    pub fn tool_loop_needs_user_action(&self) -> bool {
+        self.tool_loop.state().needs_user_action() || self.pending_permission.is_some()
+    }
+
+    /// Returns true if the tool loop is actively processing.
+    #[must_use]
+    pub fn tool_loop_is_active(&self) -> bool {
+        self.tool_loop.state().is_active()
+    }
+
+    // ========================================================================
+    // Tool Block UI Methods (Phase 10.5.6)
+    // ========================================================================
+
+    /// Starts a new tool block for UI display.
+    ///
+    /// Returns the index of the created block for later updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_name` - Name of the tool (e.g., "bash", "read")
+    /// * `tool_input` - Input provided to the tool
+    pub fn start_tool_block(&mut self, tool_name: &str, tool_input: &str) -> usize {
+        let block = ToolBlockState::new(tool_name, tool_input);
+        self.tool_blocks.push(block);
+        self.dirty.messages = true;
+        self.tool_blocks.len() - 1
+    }
+
+    /// Completes a tool block with its result.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the tool block to update
+    /// * `result` - The tool's output
+    /// * `is_error` - Whether the result is an error
+    pub fn complete_tool_block(&mut self, index: usize, result: &str, is_error: bool) {
+        if let Some(block) = self.tool_blocks.get_mut(index) {
+            if is_error {
+                block.set_error(result);
+            } else {
+                block.set_result(result);
+            }
+            self.dirty.messages = true;
+        }
+    }
+
+    /// Returns a slice of all tool blocks for rendering.
+    #[must_use]
+    pub fn tool_blocks(&self) -> &[ToolBlockState] {
+        &self.tool_blocks
+    }
+
+    /// Clears all tool blocks.
+    ///
+    /// Call this when starting a new conversation turn.
+    pub fn clear_tool_blocks(&mut self) {
+        self.tool_blocks.clear();
+        self.dirty.messages = true;
+    }
+
+    /// Returns true if there are any tool blocks to display.
+    #[must_use]
+    pub fn has_tool_blocks(&self) -> bool {
+        !self.tool_blocks.is_empty()
+    }
+
+    // ========================================================================
+    // Timeline Integration (Phase 2)
+    // ========================================================================
+
+    /// Returns a reference to the conversation timeline.
+    #[must_use]
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Returns a mutable reference to the conversation timeline.
+    pub fn timeline_mut(&mut self) -> &mut Timeline {
+        &mut self.timeline
+    }
+
+    /// Starts streaming mode.
+    ///
+    /// This creates a streaming entry in the timeline.
+    pub fn set_streaming(&mut self, _streaming: bool) {
+        self.loading = true;
+
+        // Add streaming entry to timeline
+        if self.timeline.try_push_streaming().is_err() {
+            // Already streaming - this is a no-op
+            tracing::warn!("set_streaming called but timeline already streaming");
+        }
+
+        self.dirty.messages = true;
+    }
+
+    /// Appends text to the current streaming response.
+    ///
+    /// Updates the timeline streaming entry.
+    pub fn append_streaming_text(&mut self, text: &str) {
+        // Update timeline streaming entry
+        self.timeline.append_to_streaming(text);
+        self.dirty.messages = true;
+    }
+
+    /// Finalizes streaming as a complete assistant message.
+    ///
+    /// Converts the streaming entry to an assistant message in the timeline.
+    pub fn finalize_streaming_as_message(&mut self) {
+        // Finalize timeline streaming entry
+        self.timeline.finalize_streaming_as_message();
+        self.loading = false;
+        self.dirty.messages = true;
+    }
+
+    /// Adds a tool block with result to both legacy tool_blocks and timeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_name` - Name of the tool (e.g., "bash", "read_file")
+    /// * `input` - Tool input/command
+    /// * `output` - Tool output
+    /// * `is_error` - Whether the execution resulted in an error
+    pub fn add_tool_block_with_result(
+        &mut self,
+        tool_name: &str,
+        input: &str,
+        output: &str,
+        is_error: bool,
+    ) {
+        // Add to legacy tool_blocks using the constructor
+        let mut block = ToolBlockState::new(tool_name, input);
+        if is_error {
+            block.set_error(output);
+        } else {
+            block.set_result(output);
+        }
+        self.tool_blocks.push(block);
+
+        // Add to timeline with message index tracking
+        self.timeline.push_tool_after_current_assistant(
+            tool_name,
+            input,
+            Some(output.to_string()),
+            is_error,
+        );
+
+        self.dirty.messages = true;
+    }
+
+    /// Clears all conversation state (timeline, API messages, tool blocks).
+    ///
+    /// Pending tool calls, token usage and scroll position are reset too. The
+    /// working directory, permissions, session ID and context files are kept,
+    /// so the next save overwrites the same session file.
+    pub fn clear_conversation(&mut self) {
+        self.timeline = Timeline::new();
+        self.api_messages.clear();
+        self.reset_tool_loop();
+        self.executing_tool_ids.clear();
+        self.tool_blocks.clear();
+        self.continuation_deferred = false;
+        self.last_error = None;
+        self.token_budget.reset();
+        self.context_status = BudgetStatus::Ok;
+        self.scroll = ScrollState::new();
+        self.selection.clear();
+        self.rendered_lines_cache.clear();
+        self.dirty.full = true;
+    }
+
+    // ========================================================================
+    // Async Tool Execution (Phase 5)
+    // ========================================================================
+
+    /// Sets the receiver channel for async tool results.
+    ///
+    /// When tool execution is spawned in the background, results will be
+    /// streamed back through this channel.
+    pub fn set_tool_result_rx(
+        &mut self,
+        rx: mpsc::UnboundedReceiver<(String, crate::types::ToolResultBlock)>,
+    ) {
+        self.tool_result_rx = Some(rx);
+    }
+
+    /// Returns true if a tool result channel is currently set.
+    #[must_use]
+    pub fn has_tool_result_rx(&self) -> bool {
+        self.tool_result_rx.is_some()
+    }
+
+    /// Attempts to receive a tool result without blocking.
+    ///
+    /// Returns `Some((tool_id, result))` if a result is available,
+    /// `None` if no result is ready or channel is not set.
+    pub fn try_recv_tool_result(&mut self) -> Option<(String, crate::types::ToolResultBlock)> {
+        if let Some(ref mut rx) = self.tool_result_rx {
+            rx.try_recv().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Receives a tool result asynchronously.
+    ///
+    /// Returns `None` immediately if no channel is set, otherwise waits for
+    /// the next result. This is designed for use in `tokio::select!`.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((tool_id, result))` - A tool completed execution
+    /// - `None` - Channel closed or no channel set
+    pub async fn recv_tool_result(&mut self) -> Option<(String, crate::types::ToolResultBlock)> {
+        match &mut self.tool_result_rx {
+            Some(rx) => rx.recv().await,
+            None => None, // Return immediately - don't block with pending()
+        }
+    }
+
+    /// Clears the tool result and permission request channels.
+    ///
+    /// Called after all tools have completed and results have been processed.
+    pub fn clear_tool_result_rx(&mut self) {
+        self.tool_result_rx = None;
+        self.permission_rx = None;
+        self.tool_task = None;
+    }
+
+    /// Pauses tool execution until the user answers a permission prompt.
+    ///
+    /// Called when a tool reports that it needs permission. The execution
+    /// task has stopped before that call, so calls without a result run again
+    /// once the user allows it, or are answered with the denial.
+    pub fn request_tool_permission(&mut self, request: PermissionRequest) {
+        self.clear_tool_result_rx();
+        self.executing_tool_ids.clear();
+        self.loading = false;
+        self.set_pending_permission(request);
+    }
+
+    /// Adds a pending tool to the tool loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_use` - The tool use block to add
+    pub fn add_pending_tool(&mut self, tool_use: crate::types::ToolUseBlock) {
+        self.tool_loop.add_tool_use(tool_use);
+    }
+
+    /// Spawns tool execution in the background.
+    ///
+    /// Returns immediately with a handle to the background task.
+    /// Results are sent through the tool_result_rx channel.
+    ///
+    /// # Returns
+    ///
+    /// `Some(JoinHandle)` if tools were spawned, `None` if no tools pending.
+    ///
+    /// A call that needs permission stops the task; the request arrives as
+    /// [`BackgroundEvent::PermissionNeeded`] and the remaining calls wait
+    /// for the user's answer.
+    #[must_use]
+    pub fn spawn_tool_execution(
+        &mut self,
+    ) -> Option<tokio::task::JoinHandle<Vec<(String, crate::types::ToolResultBlock)>>> {
+        // Create channels for results and permission requests
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.tool_result_rx = Some(rx);
+        let (permission_tx, permission_rx) = mpsc::unbounded_channel();
+        self.permission_rx = Some(permission_rx);
+
+        // Get pending tools
+        let pending: Vec<_> = self
+            .tool_loop
+            .pending_calls()
+            .iter()
+            .filter(|(_, call)| !call.executed)
+            .map(|(id, call)| (id.clone(), call.tool_use.clone()))
+            .collect();
+
+        if pending.is_empty() {
+            return None;
+        }
+
+        // Mark all as executing
+        for (id, _) in &pending {
+            self.executing_tool_ids.insert(id.clone());
+        }
+
+        let executor = Arc::clone(&self.tool_executor);
+
+        // Spawn background task
+        let handle = tokio::spawn(async move {
+            use crate::app::tool_loop::tool_use_to_call;
+            use crate::tools::ToolResult as TR;
+
+            let mut results = Vec::new();
+            for (tool_id, tool_use) in pending {
+                let call = tool_use_to_call(&tool_use);
+                let result = executor.execute(call).await;
+
+                let result_block = match result {
+                    Ok(TR::Success(output)) => crate::types::ToolResultBlock {
+                        tool_use_id: tool_id.clone(),
+                        content: output,
+                        is_error: false,
+                    },
+                    Ok(TR::Error(error)) => crate::types::ToolResultBlock {
+                        tool_use_id: tool_id.clone(),
+                        content: error,
+                        is_error: true,
+                    },
+                    Ok(TR::Cancelled) => crate::types::ToolResultBlock {
+                        tool_use_id: tool_id.clone(),
+                        content: "Tool execution cancelled".to_string(),
+                        is_error: true,
+                    },
+                    Ok(TR::NeedsPermission(request)) => {
+                        // Wait for the user; this call and the rest run later
+                        let _ = permission_tx.send(request);
+                        break;
+                    }
+                    Err(e) => crate::types::ToolResultBlock::failure(&tool_id, e),
+                };
+
+                // Send through channel (ignore error if receiver dropped)
+                let _ = tx.send((tool_id.clone(), result_block.clone()));
+                results.push((tool_id, result_block));
+            }
+            results
+        });
+        self.tool_task = Some(handle.abort_handle());
+
+        Some(handle)
+    }
+
+    /// Returns true if there are any tools currently executing.
+    #[must_use]
+    pub fn has_executing_tools(&self) -> bool {
+        !self.executing_tool_ids.is_empty()
+    }
+
+    /// Marks a tool as currently executing.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_id` - The ID of the tool to mark as executing
+    pub fn mark_tool_executing(&mut self, tool_id: &str) {
+        self.executing_tool_ids.insert(tool_id.to_string());
+    }
+
+    /// Records a tool result and removes the tool from executing set.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_id` - The ID of the tool that completed
+    /// * `result` - The result of the tool execution
+    pub fn record_tool_result(&mut self, tool_id: &str, result: crate::types::ToolResultBlock) {
+        // Remove from executing set
+        self.executing_tool_ids.remove(tool_id);
+
+        if result.is_error {
+            let name = self
+                .tool_loop
+                .pending_calls()
+                .get(tool_id)
+                .map_or_else(|| "unknown".to_string(), |call| call.tool_use.name.clone());
+            self.last_error = Some(LastError::tool(name, result.content.clone()));
+        }
+
+        // Update tool loop with result (ignore error if tool not found)
+        let is_error = result.is_error;
+        let _ = self.tool_loop.set_tool_result(tool_id, result.clone());
+        if !is_error {
+            self.notify_ide_of_file_change(tool_id);
+        }
+        self.record_context_resources();
+
+        // Update timeline tool entry if it exists
+        self.update_timeline_tool_by_id(tool_id, Some(result.content), result.is_error);
+        self.activate_skills_for_tool_files();
+        self.record_file_edits();
+
+        self.dirty.messages = true;
+    }
+
+    /// Returns true if all pending tools have completed execution.
+    #[must_use]
+    pub fn all_tools_complete(&self) -> bool {
+        self.executing_tool_ids.is_empty()
+            && self
+                .tool_loop
+                .pending_calls()
+                .values()
+                .all(|call| call.executed || call.result.is_some())
+    }
+
+    /// Adds a tool to the timeline in executing state (no output yet).
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_name` - Name of the tool (e.g., "bash")
+    /// * `input` - The tool input/command
+    pub fn add_tool_to_timeline_executing(&mut self, tool_name: &str, input: &str) {
+        self.timeline.push_tool_after_current_assistant(
+            tool_name, input, None, // No output yet - executing
+            false,
+        );
+        self.dirty.messages = true;
+    }
+
+    /// Updates a tool in the timeline with its result.
+    ///
+    /// Finds the most recent tool entry with the given name that has no output
+    /// and updates it with the provided output.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_name` - Name of the tool to update
+    /// * `output` - The tool output (None if still executing)
+    /// * `is_error` - Whether the result is an error
+    pub fn update_tool_in_timeline(
+        &mut self,
+        tool_name: &str,
+        output: Option<String>,
+        is_error: bool,
+    ) {
+        self.timeline
+            .update_tool_result(tool_name, output, is_error);
+        self.dirty.messages = true;
+    }
+
+    /// Shows partial output from a tool that is still running.
+    ///
+    /// Used for tools that stream results, such as long MCP tasks.
+    pub fn append_tool_progress(&mut self, tool_name: &str, text: &str) {
+        self.timeline.append_tool_progress(tool_name, text);
+        self.dirty.messages = true;
+    }
+
+    /// Updates a tool in the timeline by its ID.
+    ///
+    /// This is used internally when recording tool results.
+    fn update_timeline_tool_by_id(
+        &mut self,
+        _tool_id: &str,
+        output: Option<String>,
+        is_error: bool,
+    ) {
+        // For now, update the most recent executing tool
+        // In the future, we could track tool_id -> timeline_index mapping
+        let mut completed = None;
+        for entry in self.timeline.entries_mut().iter_mut().rev() {
+            if let crate::types::ConversationEntry::ToolExecution {
+                name,
+                output: ref mut o @ None,
+                is_error: ref mut err,
+                ..
+            } = entry
+            {
+                *o = output;
+                *err = is_error;
+                completed = Some(name.clone());
+                break;
+            }
+        }
+        if let Some(name) = completed {
+            self.timeline.clear_tool_progress(&name);
+        }
+        self.dirty.messages = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::UiState;
+
+    fn test_message(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_app_state_new() {
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(state.timeline().is_empty());
+        assert!(state.input.is_empty());
+        assert_eq!(state.scroll_offset(), 0);
+        assert_eq!(state.working_dir, PathBuf::from("/test"));
+    }
+
+    #[test]
+    fn test_restore_from_session_messages() {
+        use crate::types::ConversationEntry;
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Create a session with messages
+        let mut session = Session::new(PathBuf::from("/project"));
+        session.add_message(test_message(Role::User, "Hello"));
+        session.add_message(test_message(Role::Assistant, "Hi there!"));
+
+        state.restore_from_session(&session);
+
+        assert_eq!(state.timeline().len(), 2);
+        let entries: Vec<_> = state.timeline().iter().collect();
+        assert!(matches!(entries[0], ConversationEntry::UserMessage(s) if s == "Hello"));
+        assert!(matches!(entries[1], ConversationEntry::AssistantMessage(s) if s == "Hi there!"));
+    }
+
+    #[test]
+    fn test_restore_from_session_with_ui_state() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Create a session with UI state
+        let mut session = Session::new(PathBuf::from("/project"));
+        session.add_message(test_message(Role::User, "Test"));
+        session.set_ui_state(Some(UiState::with_state(50, "draft input".to_string(), 5)));
+
+        state.restore_from_session(&session);
+
+        assert_eq!(state.scroll_offset(), 50);
+        assert_eq!(state.input, "draft input");
+        assert_eq!(state.cursor_position(), 5);
+    }
+
+    #[test]
+    fn test_restore_from_session_with_multiline_draft() {
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        state.insert_str("first line\nsecond");
+        state.cursor_up();
+
+        let session = state.to_session();
+        let mut restored = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        restored.restore_from_session(&session);
+
+        assert_eq!(restored.input, "first line\nsecond");
+        assert_eq!(restored.cursor_line_col(), (0, 6));
+    }
+
+    #[test]
+    fn test_restore_from_session_clamps_cursor() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let mut session = Session::new(PathBuf::from("/project"));
+        session.set_ui_state(Some(UiState::with_state(0, "short".to_string(), 40)));
+
+        state.restore_from_session(&session);
+
+        assert_eq!(state.cursor_position(), 5);
+    }
+
+    #[test]
+    fn test_restore_from_session_without_ui_state() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        // Set some initial state
+        state.scroll.restore_offset(100);
+        state.input = "existing".to_string();
+        state.cursor_pos = 8;
+
+        // Create a session without UI state
+        let mut session = Session::new(PathBuf::from("/project"));
+        session.add_message(test_message(Role::User, "Test"));
+
+        state.restore_from_session(&session);
+
+        // UI state should remain unchanged since session has no UI state
+        assert_eq!(state.scroll_offset(), 100);
+        assert_eq!(state.input, "existing");
+        assert_eq!(state.cursor_position(), 8);
+    }
+
+    #[test]
+    fn test_restore_marks_dirty() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.mark_rendered(); // Clear dirty flags
+
+        let session = Session::new(PathBuf::from("/project"));
+        state.restore_from_session(&session);
+
+        assert!(state.needs_render());
+    }
+
+    // ========================================================================
+    // Phase 10.4.1: Auto-save tests
+    // ========================================================================
+
+    #[test]
+    fn test_app_state_session_id_none_initially() {
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(state.session_id().is_none());
+    }
+
+    #[test]
+    fn test_app_state_set_session_id() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_session_id("abc123".to_string());
+        assert_eq!(state.session_id(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_to_session_empty() {
+        let state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        let session = state.to_session();
+
+        assert!(session.messages().is_empty());
+        assert_eq!(session.working_dir(), &PathBuf::from("/project"));
+    }
+
+    #[test]
+    fn test_to_session_with_messages() {
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        state.add_message(test_message(Role::User, "Hello"));
+        state.add_message(test_message(Role::Assistant, "Hi!"));
+
+        let session = state.to_session();
+
+        assert_eq!(session.messages().len(), 2);
+        assert_eq!(session.messages()[0].content, "Hello");
+        assert_eq!(session.messages()[1].content, "Hi!");
+    }
+
+    #[test]
+    fn test_resume_restores_focus_and_clamped_selection() {
+        use crate::tui::selection::ContentPosition;
+        use ratatui::text::Line;
+
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        state.add_message(test_message(Role::User, "Hello"));
+        state.set_focus_area(FocusArea::Content);
+        state
+            .selection_mut()
+            .restore(ContentPosition::new(1, 2), ContentPosition::new(30, 4));
+
+        let session = state.to_session();
+
+        let mut resumed = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        resumed.restore_from_session(&session);
+
+        assert_eq!(resumed.focus_area(), FocusArea::Content);
+        assert_eq!(
+            resumed.selection().range(),
+            Some((ContentPosition::new(1, 2), ContentPosition::new(30, 4)))
+        );
+
+        // The first render clamps the selection to the content that exists
+        let lines: Vec<Line<'_>> = (0..5).map(|i| Line::from(format!("line {i}"))).collect();
+        resumed.update_rendered_lines_cache(&lines, 80);
+
+        assert_eq!(
+            resumed.selection().range(),
+            Some((
+                ContentPosition::new(1, 2),
+                ContentPosition::new(4, usize::MAX)
+            ))
+        );
+        assert_eq!(
+            resumed.selected_text().as_deref(),
+            Some("ne 1\nline 2\nline 3\nline 4")
+        );
+    }
+
+    #[test]
+    fn test_to_session_preserves_ui_state() {
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        state.scroll.restore_offset(42);
+        state.input = "draft text".to_string();
+        state.cursor_pos = 5;
+
+        let session = state.to_session();
+        let ui_state = session.ui_state().expect("UI state should be present");
+
+        assert_eq!(ui_state.scroll_offset(), 42);
+        assert_eq!(ui_state.input_buffer(), "draft text");
+        assert_eq!(ui_state.cursor_position(), 5);
+    }
+
+    #[test]
+    fn test_to_session_roundtrip() {
+        // Create state with data
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        state.add_message(test_message(Role::User, "Test message"));
+        state.scroll.restore_offset(100);
+        state.input = "unsent input".to_string();
+        state.cursor_pos = 6;
+
+        // Convert to session
+        let session = state.to_session();
+
+        // Create new state and restore
+        let mut new_state =
+            AppState::new(PathBuf::from("/different"), false, ParallelMode::Enabled);
+        new_state.restore_from_session(&session);
+
+        // Verify roundtrip preserves data
+        assert_eq!(new_state.timeline().len(), 1);
+        let entries: Vec<_> = new_state.timeline().iter().collect();
+        assert!(
+            matches!(entries[0], crate::types::ConversationEntry::UserMessage(s) if s == "Test message")
+        );
+        assert_eq!(new_state.scroll_offset(), 100);
+        assert_eq!(new_state.input, "unsent input");
+        assert_eq!(new_state.cursor_position(), 6);
+    }
+
+    #[test]
+    fn test_restore_from_session_restores_session_id() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(state.session_id().is_none());
+
+        // Create session with an ID (simulating a saved session)
+        let mut session = Session::new(PathBuf::from("/project"));
+        session.add_message(test_message(Role::User, "Test"));
+        // Manually set the session ID via JSON (normally done by SessionManager::save)
+        let session_json = serde_json::to_string(&session).unwrap();
+        let json_with_id = session_json.replace(r#""id":null"#, r#""id":"test-session-id""#);
+        let session_with_id: Session = serde_json::from_str(&json_with_id).unwrap();
+
+        state.restore_from_session(&session_with_id);
+
+        assert_eq!(state.session_id(), Some("test-session-id"));
+    }
+
+    #[test]
+    fn test_apply_tag_actions() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        state.apply_command_action(CommandAction::AddTags(vec![
+            "bugfix".to_string(),
+            "auth".to_string(),
+            "bugfix".to_string(),
+        ]));
+        assert_eq!(state.session_tags(), ["bugfix", "auth"]);
+
+        let output =
+            state.apply_command_action(CommandAction::RemoveTags(vec!["auth".to_string()]));
+        assert_eq!(state.session_tags(), ["bugfix"]);
+        assert!(output.contains("bugfix"));
+    }
+
+    #[test]
+    fn test_allow_rule_action_persists_glob_rule() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("permissions.json");
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.load_permission_rules(config_path.clone()).unwrap();
+        assert!(state
+            .apply_command_action(CommandAction::ListAllowRules)
+            .starts_with("No allow rules"));
+
+        let rule = PermissionRule::parse("bash:git diff*", true).unwrap();
+        let output = state.apply_command_action(CommandAction::AllowRule(rule));
+        assert_eq!(output, "Always allowing bash:git diff*.");
+        assert_eq!(
+            state.apply_command_action(CommandAction::ListAllowRules),
+            "Allow rules:\n  bash:git diff*"
+        );
+
+        let mut restarted = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        restarted.load_permission_rules(config_path).unwrap();
+        assert!(restarted
+            .apply_command_action(CommandAction::ListAllowRules)
+            .contains("bash:git diff*"));
+    }
+
+    #[test]
+    fn test_name_session_action_sets_saved_name() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        let output =
+            state.apply_command_action(CommandAction::NameSession("my-refactor".to_string()));
+        assert_eq!(
+            output,
+            "Session saved as 'my-refactor'. Resume it with --resume my-refactor."
+        );
+        assert_eq!(state.session_name(), Some("my-refactor"));
+        assert_eq!(state.to_session().name(), Some("my-refactor"));
+    }
+
+    fn preset_state() -> AppState {
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        let mut presets = HashMap::new();
+        presets.insert(
+            "precise".to_string(),
+            RequestParams {
+                temperature: Some(0.2),
+                ..Default::default()
+            },
+        );
+        state.set_presets(presets);
+        state
+    }
+
+    #[test]
+    fn test_apply_preset_changes_request_params() {
+        let mut state = preset_state();
+        assert!(state.request_params().is_empty());
+
+        state.apply_command_action(CommandAction::ApplyPreset("precise".to_string()));
+
+        assert_eq!(state.request_params().temperature, Some(0.2));
+        let client = AnthropicClient::new(secrecy::SecretString::from("key"), "model")
+            .with_params(state.request_params());
+        assert_eq!(client.params().temperature, Some(0.2));
+
+        state.apply_command_action(CommandAction::ClearPreset);
+        assert!(state.request_params().is_empty());
+    }
+
+    #[test]
+    fn test_watchdog_resets_wedged_tool_loop() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(!state.recover_wedged_tool_loop());
+
+        state
+            .tool_loop_mut()
+            .force_state(ToolLoopState::PendingApproval);
+        assert!(state.recover_wedged_tool_loop());
+
+        assert_eq!(*state.tool_loop().state(), ToolLoopState::Idle);
+        assert!(!state.has_pending_permission());
+        let notice = state
+            .timeline()
+            .iter()
+            .last()
+            .and_then(|e| e.text())
+            .unwrap();
+        assert!(notice.contains("Tool loop was stuck"));
+        assert!(state.tool_loop_mut().start_streaming().is_ok());
+    }
+
+    #[test]
+    fn test_reset_loop_command() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        let output = state.apply_command_action(CommandAction::ResetToolLoop);
+        assert!(output.contains("nothing to reset"));
+
+        state.tool_loop_mut().force_state(ToolLoopState::Executing);
+        let output = state.apply_command_action(CommandAction::ResetToolLoop);
+        assert!(output.contains("no tool calls"), "got {output}");
+        assert_eq!(*state.tool_loop().state(), ToolLoopState::Idle);
+    }
+
+    #[test]
+    fn test_apply_unknown_preset_keeps_current() {
+        let mut state = preset_state();
+        state.apply_command_action(CommandAction::ApplyPreset("precise".to_string()));
+
+        let output = state.apply_command_action(CommandAction::ApplyPreset("nope".to_string()));
+
+        assert!(output.contains("Unknown preset"));
+        assert_eq!(state.active_preset().unwrap().name, "precise");
+    }
+
+    #[test]
+    fn test_preset_restored_from_session() {
+        let mut state = preset_state();
+        state.apply_command_action(CommandAction::ApplyPreset("precise".to_string()));
+
+        let session = state.to_session();
+        let mut resumed = AppState::new(PathBuf::from("/other"), false, ParallelMode::Enabled);
+        resumed.restore_from_session(&session);
+
+        assert_eq!(resumed.active_preset().unwrap().name, "precise");
+        assert_eq!(resumed.request_params().temperature, Some(0.2));
+    }
+
+    #[tokio::test]
+    async fn test_shell_state_round_trips_through_session_manager() {
+        use crate::session::{SessionManager, ShellSnapshot};
+        use std::collections::HashMap;
+
+        let work = tempfile::TempDir::new().unwrap();
+        let root = work.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        let sessions = tempfile::TempDir::new().unwrap();
+        let manager = SessionManager::new(sessions.path().to_path_buf());
+
+        let state = AppState::new(root.clone(), false, ParallelMode::Enabled);
+        let env = HashMap::from([("FOO".to_string(), "bar".to_string())]);
+        state
+            .tool_executor
+            .restore_shell_state(&ShellSnapshot::new(root.join("sub"), env));
+
+        let id = manager.save(&state.to_session()).await.unwrap();
+        let loaded = manager.load(&id).await.unwrap();
+        let mut resumed = AppState::new(root.clone(), false, ParallelMode::Enabled);
+        resumed.restore_from_session(&loaded);
+
+        let shell = resumed.tool_executor.shell_state();
+        assert_eq!(shell.cwd(), root.join("sub"));
+        assert_eq!(shell.env().get("FOO").map(String::as_str), Some("bar"));
+    }
+
+    #[test]
+    fn test_load_dotenv_keeps_values_out_of_session() {
+        let work = tempfile::TempDir::new().unwrap();
+        std::fs::write(work.path().join(".env"), "PATINA_TEST_TOKEN=tok-9f8e7d6c\n").unwrap();
+        let mut state = AppState::new(work.path().to_path_buf(), false, ParallelMode::Enabled);
+
+        state.load_dotenv();
+        state.add_message(test_message(Role::User, "hello"));
+
+        assert_eq!(
+            state.tool_executor.shell_state().dotenv_keys(),
+            vec!["PATINA_TEST_TOKEN"]
+        );
+        let session = serde_json::to_string(&state.to_session()).unwrap();
+        assert!(!session.contains("tok-9f8e7d6c"));
+        assert!(state
+            .timeline()
+            .iter()
+            .filter_map(|e| e.text())
+            .all(|text| !text.contains("tok-9f8e7d6c")));
+    }
+
+    #[test]
+    fn test_keyboard_hint_dismissed_once() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert_eq!(state.keyboard_hint(), None);
+        assert!(!state.dismiss_keyboard_hint());
+
+        state.show_keyboard_hint("Use Ctrl+Y to copy".to_string());
+        assert_eq!(state.keyboard_hint(), Some("Use Ctrl+Y to copy"));
+
+        assert!(state.dismiss_keyboard_hint());
+        assert_eq!(state.keyboard_hint(), None);
+        assert!(!state.dismiss_keyboard_hint());
+    }
+
+    #[test]
+    fn test_thinking_excluded_from_answer_and_copy_unless_shown() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_current_response(String::new());
+        state
+            .append_chunk(StreamEvent::ThinkingDelta("secret plan".to_string()))
+            .unwrap();
+        state
+            .append_chunk(StreamEvent::ContentDelta("Answer".to_string()))
+            .unwrap();
+        state.append_chunk(StreamEvent::MessageStop).unwrap();
+
+        assert_eq!(state.api_messages().len(), 1);
+        assert_eq!(state.api_messages()[0].content.to_text(), "Answer");
+
+        for (mode, copied) in [
+            (ThinkingDisplay::Hidden, false),
+            (ThinkingDisplay::Collapsed, false),
+            (ThinkingDisplay::Shown, true),
+        ] {
+            state.apply_command_action(CommandAction::SetThinkingDisplay(mode));
+            let lines = crate::tui::render_timeline_with_thinking(state.timeline(), '⠋', mode);
+            state.update_rendered_lines_cache(&lines, 80);
+            let total = state.rendered_line_count();
+            state.selection_mut().select_all(total);
+
+            let text = state.selected_text().unwrap();
+            assert!(text.contains("Answer"), "{mode:?}: {text}");
+            assert_eq!(text.contains("secret plan"), copied, "{mode:?}: {text}");
+        }
+    }
+
+    #[test]
+    fn test_session_budget_warns_then_pauses_pending_confirmation() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        // Claude 3 Opus input costs $0.15 per 10k tokens
+        state
+            .set_session_budget(Some(1.0), "claude-3-opus")
+            .unwrap();
+
+        state
+            .append_chunk(StreamEvent::Usage {
+                input_tokens: 56_000,
+                output_tokens: 0,
+            })
+            .unwrap();
+        assert!(!state.budget_paused());
+        assert!(state
+            .timeline()
+            .iter()
+            .any(|e| e.text().is_some_and(|t| t.starts_with("Warning: 84%"))));
+
+        state
+            .append_chunk(StreamEvent::Usage {
+                input_tokens: 14_000,
+                output_tokens: 0,
+            })
+            .unwrap();
+        assert!(state.budget_paused());
+        state.defer_continuation();
+        assert!(!state.take_resumable_continuation());
+
+        let output = state.apply_command_action(CommandAction::ConfirmBudget);
+        assert_eq!(output, "Continuing past the session budget.");
+        assert!(!state.budget_paused());
+        assert!(state.take_resumable_continuation());
+        assert!(!state.take_resumable_continuation());
+    }
+
+    #[test]
+    fn test_switching_model_applies_tool_profile() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let mut profiles = HashMap::new();
+        profiles.insert(
+            "claude-3-haiku".to_string(),
+            ToolProfile {
+                disabled_tools: vec!["web_fetch".to_string(), "web_search".to_string()],
+                ..ToolProfile::default()
+            },
+        );
+        state.set_tool_profiles(vec!["analyze_image".to_string()], profiles);
+        state.set_model("claude-sonnet-4-20250514");
+        let names = |state: &AppState| -> Vec<String> {
+            state.tools().into_iter().map(|t| t.name).collect()
+        };
+
+        let full = names(&state);
+        assert!(full.contains(&"web_fetch".to_string()));
+        assert!(!full.contains(&"analyze_image".to_string()));
+
+        state.apply_command_action(CommandAction::SetModel(
+            "claude-3-haiku-20240307".to_string(),
+        ));
+        assert_eq!(state.model(), Some("claude-3-haiku-20240307"));
+        let restricted = names(&state);
+        assert!(!restricted.contains(&"web_fetch".to_string()));
+        assert!(!restricted.contains(&"web_search".to_string()));
+        assert!(restricted.contains(&"bash".to_string()));
+
+        let output = state.apply_command_action(CommandAction::SetModel(
+            "claude-sonnet-4-20250514".to_string(),
+        ));
+        assert!(output.contains("web_search"), "got: {output}");
+        assert_eq!(names(&state), full);
+    }
+
+    #[test]
+    fn test_set_model_updates_context_window() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        state.apply_command_action(CommandAction::SetModel("claude-2.0".to_string()));
+        assert_eq!(state.context_window(), 100_000);
+        state.apply_command_action(CommandAction::SetModel("claude-opus-4-1".to_string()));
+        assert_eq!(state.context_window(), 200_000);
+        assert_eq!(state.token_budget().limit(), 200_000);
+
+        // The large-context window is not narrowed by a model switch
+        state.set_context_window(LARGE_CONTEXT_WINDOW);
+        state.apply_command_action(CommandAction::SetModel("claude-2.0".to_string()));
+        assert_eq!(state.context_window(), LARGE_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn test_set_model_reprices_session_budget() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state
+            .set_session_budget(Some(10.0), "claude-3-haiku")
+            .unwrap();
+
+        state.apply_command_action(CommandAction::SetModel("claude-3-opus".to_string()));
+        state.record_api_usage(100_000, 0);
+
+        // Opus: $15/1M input tokens
+        assert!(state.budget_status().contains("$1.50 of $10.00"));
+    }
+
+    #[test]
+    fn test_force_tool_validates_tool_name() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        let output = state.apply_command_action(CommandAction::ForceTool("run_tests".to_string()));
+        assert!(output.contains("Unknown tool"), "got: {output}");
+        assert!(state.forced_tool_choice().is_none());
+
+        state.apply_command_action(CommandAction::ForceTool("grep".to_string()));
+        assert_eq!(state.forced_tool_choice(), Some(&ToolChoice::tool("grep")));
+
+        state.apply_command_action(CommandAction::ForceTool("any".to_string()));
+        assert_eq!(state.forced_tool_choice(), Some(&ToolChoice::Any));
+
+        state.apply_command_action(CommandAction::ClearForcedTool);
+        assert!(state.forced_tool_choice().is_none());
+    }
+
+    #[test]
+    fn test_session_tags_roundtrip() {
+        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
+        state.apply_command_action(CommandAction::AddTags(vec!["release".to_string()]));
+
+        let session = state.to_session();
+        assert_eq!(session.tags(), ["release"]);
+
+        let mut new_state = AppState::new(PathBuf::from("/other"), false, ParallelMode::Enabled);
+        new_state.restore_from_session(&session);
+        assert_eq!(new_state.session_tags(), ["release"]);
+    }
+
+    // ========================================================================
+    // Tool Loop Integration Tests (Phase 10.5.2.4)
+    // ========================================================================
+
+    #[test]
+    fn test_appstate_has_tool_loop() {
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(matches!(state.tool_loop_state(), ToolLoopState::Idle));
+    }
+
+    #[test]
+    fn test_appstate_receives_tool_use() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Start streaming
+        state.tool_loop_mut().start_streaming().unwrap();
+
+        // Simulate receiving tool use events
+        state.handle_tool_use_start("toolu_123".to_string(), "bash".to_string(), 0);
+        state.handle_tool_use_input_delta(0, r#"{"command":"ls"}"#);
+        state.handle_tool_use_complete(0).unwrap();
+
+        // Complete the message with tool_use stop reason
+        state.handle_message_complete(StopReason::ToolUse).unwrap();
+
+        // Should be in PendingApproval state
+        assert!(matches!(
+            state.tool_loop_state(),
+            ToolLoopState::PendingApproval
+        ));
+
+        // Should need user action
+        assert!(state.tool_loop_needs_user_action());
+    }
+
+    #[test]
+    fn test_appstate_approve_and_deny_tools() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Set up tool use
+        state.tool_loop_mut().start_streaming().unwrap();
+        state.handle_tool_use_start("toolu_1".to_string(), "bash".to_string(), 0);
+        state.handle_tool_use_input_delta(0, "{}");
+        state.handle_tool_use_complete(0).unwrap();
+        state.handle_message_complete(StopReason::ToolUse).unwrap();
+
+        // Deny all
+        state.deny_all_tools().unwrap();
+        assert!(matches!(state.tool_loop_state(), ToolLoopState::Idle));
+    }
+
+    #[test]
+    fn test_appstate_pending_permission() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        assert!(!state.has_pending_permission());
+        assert!(state.pending_permission().is_none());
+
+        // Set a pending permission
+        let request = PermissionRequest::new("bash", Some("rm -rf temp"), "Execute command");
+        state.set_pending_permission(request);
+
+        assert!(state.has_pending_permission());
+        assert!(state.pending_permission().is_some());
+        let pending = state.pending_permission().unwrap();
+        assert_eq!(pending.tool_name, "bash");
+
+        // Clear it
+        state.clear_pending_permission();
+        assert!(!state.has_pending_permission());
+    }
+
+    #[tokio::test]
+    async fn test_appstate_handles_permission_response() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Set a pending permission
+        let request = PermissionRequest::new("bash", Some("echo hello"), "Execute command");
+        state.set_pending_permission(request);
+
+        // Handle the response (Allow Once)
+        state
+            .handle_permission_response(PermissionResponse::AllowOnce)
+            .await;
+
+        // Permission should be cleared
+        assert!(!state.has_pending_permission());
+    }
+
+    #[test]
+    fn test_appstate_reset_tool_loop() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Set up some state
+        state.tool_loop_mut().start_streaming().unwrap();
+        state.handle_tool_use_start("toolu_1".to_string(), "bash".to_string(), 0);
+        let request = PermissionRequest::new("bash", None, "test");
+        state.set_pending_permission(request);
+
+        // Reset
+        state.reset_tool_loop();
+
+        assert!(matches!(state.tool_loop_state(), ToolLoopState::Idle));
+        assert!(!state.has_pending_permission());
+    }
+
+    #[test]
+    fn test_appstate_tool_loop_state_helpers() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Initially idle - needs user action
+        assert!(state.tool_loop_needs_user_action());
+        assert!(!state.tool_loop_is_active());
+
+        // Start streaming - active
+        state.tool_loop_mut().start_streaming().unwrap();
+        assert!(!state.tool_loop_needs_user_action());
+        assert!(state.tool_loop_is_active());
+    }
+
+    // ========================================================================
+    // Scroll State Integration Tests (Phase 10.5.4.2)
+    // ========================================================================
+
+    #[test]
+    fn test_scroll_state_initial() {
+        use crate::tui::scroll::AutoScrollMode;
+
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Should start in Follow mode at offset 0
+        assert_eq!(state.scroll_offset(), 0);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+    }
+
+    #[test]
+    fn test_streaming_content_auto_scrolls() {
+        use crate::tui::scroll::AutoScrollMode;
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_viewport_height(20);
+
+        // Simulate content growth (streaming updates)
+        state.update_content_height(30);
+        assert_eq!(state.scroll_offset(), 0); // At bottom
+
+        // More content arrives
+        state.update_content_height(50);
+
+        // In Follow mode, should auto-scroll to stay at bottom
+        assert_eq!(state.scroll_offset(), 0);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+    }
+
+    #[test]
+    fn test_user_scroll_preserved_during_streaming() {
+        use crate::tui::scroll::AutoScrollMode;
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_viewport_height(20);
+        state.update_content_height(50);
+
+        // User scrolls up
+        state.scroll_up(15);
+        assert_eq!(state.scroll_offset(), 15);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+
+        // More content arrives (streaming)
+        state.update_content_height(80);
+
+        // User's scroll position should be preserved
+        assert_eq!(state.scroll_offset(), 15);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+    }
+
+    #[test]
+    fn test_scroll_down_resumes_follow_mode() {
+        use crate::tui::scroll::AutoScrollMode;
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_viewport_height(20);
+        state.update_content_height(50);
+
+        // User scrolls up (switches to Manual)
+        state.scroll_up(20);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+
+        // User scrolls all the way back down
+        state.scroll_down(20);
+
+        // Should resume Follow mode
+        assert_eq!(state.scroll_offset(), 0);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+    }
+
+    #[test]
+    fn test_scroll_to_bottom_method() {
+        use crate::tui::scroll::AutoScrollMode;
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_viewport_height(20);
+        state.update_content_height(50);
+
+        // User scrolls up
+        state.scroll_up(30);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+
+        // Explicitly scroll to bottom
+        state.scroll_to_bottom(80);
+
+        // Should be in Follow mode at bottom
+        assert_eq!(state.scroll_offset(), 0);
+        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::session::UiState;
+    #[test]
+    fn test_scroll_state_accessor() {
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-    fn test_message(role: Role, content: &str) -> Message {
-        Message {
-            role,
-            content: content.to_string(),
-        }
+        // Should be able to access scroll state
+        let scroll = state.scroll_state();
+        assert_eq!(scroll.offset(), 0);
     }
 
+    // ========================================================================
+    // Tool Block UI Tests (Phase 10.5.6)
+    // ========================================================================
+
     #[test]
-    fn test_app_state_new() {
+    fn test_tool_blocks_initially_empty() {
         let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(state.timeline().is_empty());
-        assert!(state.input.is_empty());
-        assert_eq!(state.scroll_offset(), 0);
-        assert_eq!(state.working_dir, PathBuf::from("/test"));
+        assert!(state.tool_blocks().is_empty());
+        assert!(!state.has_tool_blocks());
     }
 
     #[test]
-    fn test_restore_from_session_messages() {
-        use crate::types::ConversationEntry;
+    fn test_start_tool_block() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Create a session with messages
-        let mut session = Session::new(PathBuf::from("/project"));
-        session.add_message(test_message(Role::User, "Hello"));
-        session.add_message(test_message(Role::Assistant, "Hi there!"));
+        let index = state.start_tool_block("bash", "git status");
 
-        state.restore_from_session(&session);
+        assert_eq!(index, 0);
+        assert!(state.has_tool_blocks());
+        assert_eq!(state.tool_blocks().len(), 1);
 
-        assert_eq!(state.timeline().len(), 2);
-        let entries: Vec<_> = state.timeline().iter().collect();
-        assert!(matches!(entries[0], ConversationEntry::UserMessage(s) if s == "Hello"));
-        assert!(matches!(entries[1], ConversationEntry::AssistantMessage(s) if s == "Hi there!"));
+        let block = &state.tool_blocks()[0];
+        assert_eq!(block.tool_name(), "bash");
+        assert_eq!(block.tool_input(), "git status");
+        assert!(!block.is_complete());
     }
 
     #[test]
-    fn test_restore_from_session_with_ui_state() {
+    fn test_complete_tool_block_success() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let index = state.start_tool_block("bash", "echo hello");
 
-        // Create a session with UI state
-        let mut session = Session::new(PathBuf::from("/project"));
-        session.add_message(test_message(Role::User, "Test"));
-        session.set_ui_state(Some(UiState::with_state(50, "draft input".to_string(), 5)));
-
-        state.restore_from_session(&session);
+        state.complete_tool_block(index, "hello", false);
 
-        assert_eq!(state.scroll_offset(), 50);
-        assert_eq!(state.input, "draft input");
-        assert_eq!(state.cursor_position(), 5);
+        let block = &state.tool_blocks()[0];
+        assert!(block.is_complete());
+        assert_eq!(block.result(), Some("hello"));
+        assert!(!block.is_error());
     }
 
     #[test]
-    fn test_restore_from_session_without_ui_state() {
+    fn test_complete_tool_block_error() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        // Set some initial state
-        state.scroll.restore_offset(100);
-        state.input = "existing".to_string();
-        state.cursor_pos = 8;
-
-        // Create a session without UI state
-        let mut session = Session::new(PathBuf::from("/project"));
-        session.add_message(test_message(Role::User, "Test"));
+        let index = state.start_tool_block("bash", "bad-command");
 
-        state.restore_from_session(&session);
+        state.complete_tool_block(index, "Command not found", true);
 
-        // UI state should remain unchanged since session has no UI state
-        assert_eq!(state.scroll_offset(), 100);
-        assert_eq!(state.input, "existing");
-        assert_eq!(state.cursor_position(), 8);
+        let block = &state.tool_blocks()[0];
+        assert!(block.is_complete());
+        assert_eq!(block.result(), Some("Command not found"));
+        assert!(block.is_error());
     }
 
     #[test]
-    fn test_restore_marks_dirty() {
+    fn test_multiple_tool_blocks() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        state.mark_rendered(); // Clear dirty flags
 
-        let session = Session::new(PathBuf::from("/project"));
-        state.restore_from_session(&session);
+        let idx1 = state.start_tool_block("bash", "ls");
+        let idx2 = state.start_tool_block("read", "/tmp/file.txt");
 
-        assert!(state.needs_render());
-    }
+        assert_eq!(idx1, 0);
+        assert_eq!(idx2, 1);
+        assert_eq!(state.tool_blocks().len(), 2);
 
-    // ========================================================================
-    // Phase 10.4.1: Auto-save tests
-    // ========================================================================
+        state.complete_tool_block(idx1, "file1.txt\nfile2.txt", false);
+        state.complete_tool_block(idx2, "file contents", false);
 
-    #[test]
-    fn test_app_state_session_id_none_initially() {
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(state.session_id().is_none());
+        assert!(state.tool_blocks()[0].is_complete());
+        assert!(state.tool_blocks()[1].is_complete());
     }
 
     #[test]
-    fn test_app_state_set_session_id() {
+    fn test_clear_tool_blocks() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        state.set_session_id("abc123".to_string());
-        assert_eq!(state.session_id(), Some("abc123"));
+
+        state.start_tool_block("bash", "ls");
+        state.start_tool_block("read", "/tmp/test");
+        assert_eq!(state.tool_blocks().len(), 2);
+
+        state.clear_tool_blocks();
+
+        assert!(state.tool_blocks().is_empty());
+        assert!(!state.has_tool_blocks());
     }
 
     #[test]
-    fn test_to_session_empty() {
-        let state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
-        let session = state.to_session();
+    fn test_complete_invalid_index_is_safe() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        assert!(session.messages().is_empty());
-        assert_eq!(session.working_dir(), &PathBuf::from("/project"));
+        // Completing a non-existent index should not panic
+        state.complete_tool_block(999, "result", false);
+
+        assert!(state.tool_blocks().is_empty());
     }
 
+    // ========================================================================
+    // Context Truncation Integration Tests (Cost Optimization)
+    // ========================================================================
+
     #[test]
-    fn test_to_session_with_messages() {
-        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
-        state.add_message(test_message(Role::User, "Hello"));
-        state.add_message(test_message(Role::Assistant, "Hi!"));
+    fn test_api_messages_truncated_returns_truncated() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        let session = state.to_session();
+        // Add many messages to potentially exceed budget
+        for i in 0..50 {
+            state
+                .api_messages
+                .push(ApiMessageV2::user(format!("Message {}", i)));
+        }
 
-        assert_eq!(session.messages().len(), 2);
-        assert_eq!(session.messages()[0].content, "Hello");
-        assert_eq!(session.messages()[1].content, "Hi!");
+        let truncated = state.api_messages_truncated();
+
+        // Should return messages (truncation logic is in api::context)
+        assert!(!truncated.is_empty());
+        // First message preserved
+        assert_eq!(truncated[0].content.to_text(), "Message 0");
+        // Most recent preserved
+        assert_eq!(truncated.last().unwrap().content.to_text(), "Message 49");
     }
 
     #[test]
-    fn test_to_session_preserves_ui_state() {
-        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
-        state.scroll.restore_offset(42);
-        state.input = "draft text".to_string();
-        state.cursor_pos = 5;
+    fn test_api_messages_truncated_under_budget_unchanged() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        let session = state.to_session();
-        let ui_state = session.ui_state().expect("UI state should be present");
+        state.api_messages.push(ApiMessageV2::user("Hello"));
+        state
+            .api_messages
+            .push(ApiMessageV2::assistant("Hi there!"));
 
-        assert_eq!(ui_state.scroll_offset(), 42);
-        assert_eq!(ui_state.input_buffer(), "draft text");
-        assert_eq!(ui_state.cursor_position(), 5);
+        let truncated = state.api_messages_truncated();
+
+        // Under budget - should be unchanged
+        assert_eq!(truncated.len(), 2);
+        assert_eq!(truncated[0].content.to_text(), "Hello");
+        assert_eq!(truncated[1].content.to_text(), "Hi there!");
     }
 
     #[test]
-    fn test_to_session_roundtrip() {
-        // Create state with data
-        let mut state = AppState::new(PathBuf::from("/project"), false, ParallelMode::Enabled);
-        state.add_message(test_message(Role::User, "Test message"));
-        state.scroll.restore_offset(100);
-        state.input = "unsent input".to_string();
-        state.cursor_pos = 6;
-
-        // Convert to session
-        let session = state.to_session();
+    fn test_api_messages_truncated_empty() {
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Create new state and restore
-        let mut new_state =
-            AppState::new(PathBuf::from("/different"), false, ParallelMode::Enabled);
-        new_state.restore_from_session(&session);
+        let truncated = state.api_messages_truncated();
 
-        // Verify roundtrip preserves data
-        assert_eq!(new_state.timeline().len(), 1);
-        let entries: Vec<_> = new_state.timeline().iter().collect();
-        assert!(
-            matches!(entries[0], crate::types::ConversationEntry::UserMessage(s) if s == "Test message")
-        );
-        assert_eq!(new_state.scroll_offset(), 100);
-        assert_eq!(new_state.input, "unsent input");
-        assert_eq!(new_state.cursor_position(), 6);
+        assert!(truncated.is_empty());
     }
 
     #[test]
-    fn test_restore_from_session_restores_session_id() {
+    fn test_api_messages_truncated_with_large_content() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(state.session_id().is_none());
 
-        // Create session with an ID (simulating a saved session)
-        let mut session = Session::new(PathBuf::from("/project"));
-        session.add_message(test_message(Role::User, "Test"));
-        // Manually set the session ID via JSON (normally done by SessionManager::save)
-        let session_json = serde_json::to_string(&session).unwrap();
-        let json_with_id = session_json.replace(r#""id":null"#, r#""id":"test-session-id""#);
-        let session_with_id: Session = serde_json::from_str(&json_with_id).unwrap();
+        // Add first message (will be preserved)
+        state.api_messages.push(ApiMessageV2::user("System prompt"));
 
-        state.restore_from_session(&session_with_id);
+        // Add many large messages that would exceed 100k tokens
+        let large_content = "x".repeat(10_000); // ~2500 tokens each
+        for _ in 0..50 {
+            // 50 * 2500 = 125k tokens
+            state
+                .api_messages
+                .push(ApiMessageV2::assistant(&large_content));
+        }
 
-        assert_eq!(state.session_id(), Some("test-session-id"));
+        let truncated = state.api_messages_truncated();
+
+        // Should be fewer than 51 messages
+        assert!(
+            truncated.len() < 51,
+            "Should be truncated, got {}",
+            truncated.len()
+        );
+        // First message always preserved
+        assert_eq!(truncated[0].content.to_text(), "System prompt");
     }
 
-    // ========================================================================
-    // Tool Loop Integration Tests (Phase 10.5.2.4)
-    // ========================================================================
+    // =========================================================================
+    // Focus Area Tests
+    // =========================================================================
 
     #[test]
-    fn test_appstate_has_tool_loop() {
+    fn test_focus_area_default_is_input() {
+        use crate::tui::selection::FocusArea;
         let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(matches!(state.tool_loop_state(), ToolLoopState::Idle));
+        assert_eq!(state.focus_area(), FocusArea::Input);
     }
 
     #[test]
-    fn test_appstate_receives_tool_use() {
+    fn test_focus_area_can_be_set() {
+        use crate::tui::selection::FocusArea;
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Start streaming
-        state.tool_loop_mut().start_streaming().unwrap();
+        state.set_focus_area(FocusArea::Content);
+        assert_eq!(state.focus_area(), FocusArea::Content);
 
-        // Simulate receiving tool use events
-        state.handle_tool_use_start("toolu_123".to_string(), "bash".to_string(), 0);
-        state.handle_tool_use_input_delta(0, r#"{"command":"ls"}"#);
-        state.handle_tool_use_complete(0).unwrap();
+        state.set_focus_area(FocusArea::Input);
+        assert_eq!(state.focus_area(), FocusArea::Input);
+    }
 
-        // Complete the message with tool_use stop reason
-        state.handle_message_complete(StopReason::ToolUse).unwrap();
+    #[test]
+    fn test_focus_change_clears_selection() {
+        use crate::tui::selection::{ContentPosition, FocusArea};
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Should be in PendingApproval state
-        assert!(matches!(
-            state.tool_loop_state(),
-            ToolLoopState::PendingApproval
-        ));
+        // Create a selection
+        state.selection_mut().start(ContentPosition::new(0, 0));
+        state.selection_mut().update(ContentPosition::new(5, 10));
+        state.selection_mut().end();
+        assert!(state.selection().has_selection());
 
-        // Should need user action
-        assert!(state.tool_loop_needs_user_action());
+        // Change focus should clear selection
+        state.set_focus_area(FocusArea::Content);
+        assert!(!state.selection().has_selection());
     }
 
     #[test]
-    fn test_appstate_approve_and_deny_tools() {
+    fn test_focus_same_area_preserves_selection() {
+        use crate::tui::selection::{ContentPosition, FocusArea};
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Set up tool use
-        state.tool_loop_mut().start_streaming().unwrap();
-        state.handle_tool_use_start("toolu_1".to_string(), "bash".to_string(), 0);
-        state.handle_tool_use_input_delta(0, "{}");
-        state.handle_tool_use_complete(0).unwrap();
-        state.handle_message_complete(StopReason::ToolUse).unwrap();
+        // Set focus to content
+        state.set_focus_area(FocusArea::Content);
 
-        // Deny all
-        state.deny_all_tools().unwrap();
-        assert!(matches!(state.tool_loop_state(), ToolLoopState::Idle));
+        // Create a selection
+        state.selection_mut().start(ContentPosition::new(0, 0));
+        state.selection_mut().update(ContentPosition::new(5, 10));
+        state.selection_mut().end();
+        assert!(state.selection().has_selection());
+
+        // Setting same focus should NOT clear selection
+        state.set_focus_area(FocusArea::Content);
+        assert!(state.selection().has_selection());
     }
 
     #[test]
-    fn test_appstate_pending_permission() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    fn test_focus_area_for_row_content() {
+        use crate::tui::selection::FocusArea;
+        // Terminal height 30: input is rows 27-29, content is 0-26
+        assert_eq!(AppState::focus_area_for_row(0, 30, 3), FocusArea::Content);
+        assert_eq!(AppState::focus_area_for_row(10, 30, 3), FocusArea::Content);
+        assert_eq!(AppState::focus_area_for_row(26, 30, 3), FocusArea::Content);
+    }
 
-        assert!(!state.has_pending_permission());
-        assert!(state.pending_permission().is_none());
+    #[test]
+    fn test_focus_area_for_row_input() {
+        use crate::tui::selection::FocusArea;
+        // Terminal height 30: input is rows 27-29
+        assert_eq!(AppState::focus_area_for_row(27, 30, 3), FocusArea::Input);
+        assert_eq!(AppState::focus_area_for_row(28, 30, 3), FocusArea::Input);
+        assert_eq!(AppState::focus_area_for_row(29, 30, 3), FocusArea::Input);
+    }
 
-        // Set a pending permission
-        let request = PermissionRequest::new("bash", Some("rm -rf temp"), "Execute command");
-        state.set_pending_permission(request);
+    #[test]
+    fn test_focus_area_for_row_small_terminal() {
+        use crate::tui::selection::FocusArea;
+        // Minimum terminal height 7: content rows 0-3, input rows 4-6
+        assert_eq!(AppState::focus_area_for_row(0, 7, 3), FocusArea::Content);
+        assert_eq!(AppState::focus_area_for_row(3, 7, 3), FocusArea::Content);
+        assert_eq!(AppState::focus_area_for_row(4, 7, 3), FocusArea::Input);
+        assert_eq!(AppState::focus_area_for_row(6, 7, 3), FocusArea::Input);
+    }
 
-        assert!(state.has_pending_permission());
-        assert!(state.pending_permission().is_some());
-        let pending = state.pending_permission().unwrap();
-        assert_eq!(pending.tool_name, "bash");
+    #[test]
+    fn test_focus_area_for_row_tall_input() {
+        use crate::tui::selection::FocusArea;
+        // A three-line draft makes the input rows 25-29
+        assert_eq!(AppState::focus_area_for_row(24, 30, 5), FocusArea::Content);
+        assert_eq!(AppState::focus_area_for_row(25, 30, 5), FocusArea::Input);
+    }
 
-        // Clear it
-        state.clear_pending_permission();
-        assert!(!state.has_pending_permission());
+    // Plugin loading tests
+
+    #[test]
+    fn test_with_plugins_disabled_creates_empty_registry() {
+        let state = AppState::with_plugins(
+            PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+            false, // plugins_enabled = false
+        );
+        // With plugins disabled, registry should be empty
+        assert_eq!(state.plugins().plugin_count(), 0);
     }
 
-    #[tokio::test]
-    async fn test_appstate_handles_permission_response() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    #[test]
+    fn test_with_plugins_enabled_returns_valid_registry() {
+        let state = AppState::with_plugins(
+            PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+            true, // plugins_enabled = true
+        );
+        // Registry should be valid (may be empty if no plugins installed)
+        // Just verify we can access the registry without panicking
+        let _ = state.plugins().plugin_count();
+    }
 
-        // Set a pending permission
-        let request = PermissionRequest::new("bash", Some("echo hello"), "Execute command");
-        state.set_pending_permission(request);
+    #[test]
+    fn test_new_enables_plugins_by_default() {
+        // AppState::new should enable plugins (equivalent to with_plugins(..., true))
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        // Registry should be accessible (plugins enabled by default)
+        let _ = state.plugins().plugin_count();
+    }
 
-        // Handle the response (Allow Once)
-        state
-            .handle_permission_response(PermissionResponse::AllowOnce)
-            .await;
+    // =========================================================================
+    // 1.5.4.3 - Subagent wiring tests
+    // =========================================================================
 
-        // Permission should be cleared
-        assert!(!state.has_pending_permission());
+    #[test]
+    fn test_new_disables_subagents_by_default() {
+        // AppState::new should disable subagents by default
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(!state.subagents_enabled());
+        assert!(state.subagent_spawner().is_none());
     }
 
     #[test]
-    fn test_appstate_reset_tool_loop() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    fn test_with_options_subagents_disabled() {
+        let state = AppState::with_options(
+            PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+            true,  // plugins_enabled
+            false, // subagents_enabled
+        );
+        assert!(!state.subagents_enabled());
+        assert!(state.subagent_spawner().is_none());
+    }
 
-        // Set up some state
-        state.tool_loop_mut().start_streaming().unwrap();
-        state.handle_tool_use_start("toolu_1".to_string(), "bash".to_string(), 0);
-        let request = PermissionRequest::new("bash", None, "test");
-        state.set_pending_permission(request);
+    #[test]
+    fn test_with_options_subagents_enabled() {
+        let state = AppState::with_options(
+            PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+            true, // plugins_enabled
+            true, // subagents_enabled
+        );
+        assert!(state.subagents_enabled());
+        assert!(state.subagent_spawner().is_some());
+    }
 
-        // Reset
-        state.reset_tool_loop();
+    #[test]
+    fn test_subagent_spawner_returns_valid_spawner_when_enabled() {
+        let state = AppState::with_options(
+            PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+            false, // plugins_enabled
+            true,  // subagents_enabled
+        );
 
-        assert!(matches!(state.tool_loop_state(), ToolLoopState::Idle));
-        assert!(!state.has_pending_permission());
+        // Verify we can access the spawner
+        let spawner = state.subagent_spawner().expect("spawner should be Some");
+        // Verify spawner has a valid model configured
+        assert!(spawner.model().contains("claude"));
     }
 
     #[test]
-    fn test_appstate_tool_loop_state_helpers() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    fn test_with_plugins_disables_subagents() {
+        // with_plugins should disable subagents (for backward compatibility)
+        let state = AppState::with_plugins(
+            PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+            true, // plugins_enabled
+        );
+        assert!(!state.subagents_enabled());
+        assert!(state.subagent_spawner().is_none());
+    }
 
-        // Initially idle - needs user action
-        assert!(state.tool_loop_needs_user_action());
-        assert!(!state.tool_loop_is_active());
+    // =========================================================================
+    // 2.2.4 - Auto-context injection tests
+    // =========================================================================
 
-        // Start streaming - active
-        state.tool_loop_mut().start_streaming().unwrap();
-        assert!(!state.tool_loop_needs_user_action());
-        assert!(state.tool_loop_is_active());
+    #[test]
+    fn test_auto_context_disabled_by_default() {
+        // AppState should have auto_context disabled by default
+        // (Config enables it, but AppState needs explicit opt-in)
+        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert!(!state.auto_context_enabled());
     }
 
-    // ========================================================================
-    // Scroll State Integration Tests (Phase 10.5.4.2)
-    // ========================================================================
+    #[test]
+    fn test_set_auto_context_enabled() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Initially disabled
+        assert!(!state.auto_context_enabled());
+
+        // Enable it
+        state.set_auto_context_enabled(true);
+        assert!(state.auto_context_enabled());
+
+        // Disable it
+        state.set_auto_context_enabled(false);
+        assert!(!state.auto_context_enabled());
+    }
 
     #[test]
-    fn test_scroll_state_initial() {
-        use crate::tui::scroll::AutoScrollMode;
+    fn test_inject_context_suggestions() {
+        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+
+        // Create test suggestions
+        let suggestions = vec![
+            ContextSuggestion {
+                source: CodeReference::function("process_data"),
+                kind: ContextKind::Callers,
+                description: "Functions that call process_data".to_string(),
+                content: "main() in src/main.rs:10".to_string(),
+            },
+            ContextSuggestion {
+                source: CodeReference::file("src/handler.rs"),
+                kind: ContextKind::Imports,
+                description: "Imports in src/handler.rs".to_string(),
+                content: "use std::io".to_string(),
+            },
+        ];
 
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        // Inject the suggestions
+        state.set_pending_context(suggestions.clone());
 
-        // Should start in Follow mode at offset 0
-        assert_eq!(state.scroll_offset(), 0);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+        // Verify we have pending context
+        assert!(state.has_pending_context());
+        assert_eq!(state.pending_context().len(), 2);
     }
 
     #[test]
-    fn test_streaming_content_auto_scrolls() {
-        use crate::tui::scroll::AutoScrollMode;
+    fn test_take_pending_context_clears() {
+        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
 
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        state.set_viewport_height(20);
 
-        // Simulate content growth (streaming updates)
-        state.update_content_height(30);
-        assert_eq!(state.scroll_offset(), 0); // At bottom
+        let suggestions = vec![ContextSuggestion {
+            source: CodeReference::function("test_fn"),
+            kind: ContextKind::Callers,
+            description: "Test".to_string(),
+            content: "Content".to_string(),
+        }];
 
-        // More content arrives
-        state.update_content_height(50);
+        state.set_pending_context(suggestions);
+        assert!(state.has_pending_context());
 
-        // In Follow mode, should auto-scroll to stay at bottom
-        assert_eq!(state.scroll_offset(), 0);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+        // Take should return and clear
+        let taken = state.take_pending_context();
+        assert_eq!(taken.len(), 1);
+        assert!(!state.has_pending_context());
+        assert!(state.pending_context().is_empty());
     }
 
     #[test]
-    fn test_user_scroll_preserved_during_streaming() {
-        use crate::tui::scroll::AutoScrollMode;
+    fn test_format_context_for_message() {
+        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
 
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        state.set_viewport_height(20);
-        state.update_content_height(50);
+        let suggestions = vec![ContextSuggestion {
+            source: CodeReference::function("process_data"),
+            kind: ContextKind::Callers,
+            description: "Functions that call process_data".to_string(),
+            content: "main() in src/main.rs:10\nhandle_request() in src/api.rs:25".to_string(),
+        }];
 
-        // User scrolls up
-        state.scroll_up(15);
-        assert_eq!(state.scroll_offset(), 15);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+        let formatted = AppState::format_context_suggestions(&suggestions);
 
-        // More content arrives (streaming)
-        state.update_content_height(80);
+        // Should contain the description and content
+        assert!(formatted.contains("Functions that call process_data"));
+        assert!(formatted.contains("main() in src/main.rs:10"));
+        assert!(formatted.contains("handle_request() in src/api.rs:25"));
+    }
 
-        // User's scroll position should be preserved
-        assert_eq!(state.scroll_offset(), 15);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+    #[test]
+    fn test_format_context_empty_suggestions() {
+        let suggestions: Vec<crate::narsil::context::ContextSuggestion> = vec![];
+        let formatted = AppState::format_context_suggestions(&suggestions);
+
+        // Empty suggestions should return empty string
+        assert!(formatted.is_empty());
     }
 
     #[test]
-    fn test_scroll_down_resumes_follow_mode() {
-        use crate::tui::scroll::AutoScrollMode;
+    fn test_clear_pending_context() {
+        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
 
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        state.set_viewport_height(20);
-        state.update_content_height(50);
 
-        // User scrolls up (switches to Manual)
-        state.scroll_up(20);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
+        let suggestions = vec![ContextSuggestion {
+            source: CodeReference::function("test_fn"),
+            kind: ContextKind::Callers,
+            description: "Test".to_string(),
+            content: "Content".to_string(),
+        }];
 
-        // User scrolls all the way back down
-        state.scroll_down(20);
+        state.set_pending_context(suggestions);
+        assert!(state.has_pending_context());
 
-        // Should resume Follow mode
-        assert_eq!(state.scroll_offset(), 0);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+        state.clear_pending_context();
+        assert!(!state.has_pending_context());
     }
 
-    #[test]
-    fn test_scroll_to_bottom_method() {
-        use crate::tui::scroll::AutoScrollMode;
+    // ========================================================================
+    // Event Loop Responsiveness Tests
+    //
+    // These tests ensure the event loop remains responsive during streaming
+    // and tool execution. Key behaviors:
+    // 1. recv_api_chunk() returns None immediately when no streaming
+    // 2. recv_tool_result() returns None immediately when no channel
+    // 3. recv_background_event() returns None when both channels are None
+    // 4. has_* methods accurately reflect channel state
+    // ========================================================================
 
+    #[tokio::test]
+    async fn test_recv_api_chunk_returns_none_when_no_streaming() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        state.set_viewport_height(20);
-        state.update_content_height(50);
-
-        // User scrolls up
-        state.scroll_up(30);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Manual);
 
-        // Explicitly scroll to bottom
-        state.scroll_to_bottom(80);
+        // Initially streaming_rx is None
+        assert!(!state.has_streaming());
 
-        // Should be in Follow mode at bottom
-        assert_eq!(state.scroll_offset(), 0);
-        assert_eq!(state.scroll_state().mode(), AutoScrollMode::Follow);
+        // recv_api_chunk should return None immediately, not block
+        let result = state.recv_api_chunk().await;
+        assert!(result.is_none());
     }
 
-    #[test]
-    fn test_scroll_state_accessor() {
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    #[tokio::test]
+    async fn test_recv_api_chunk_receives_chunks_when_streaming() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Should be able to access scroll state
-        let scroll = state.scroll_state();
-        assert_eq!(scroll.offset(), 0);
-    }
+        // Set up a streaming channel
+        let (tx, rx) = mpsc::channel(100);
+        state.set_streaming_rx(rx);
+        assert!(state.has_streaming());
 
-    // ========================================================================
-    // Tool Block UI Tests (Phase 10.5.6)
-    // ========================================================================
+        // Send a chunk
+        tx.send(StreamEvent::ContentDelta("Hello".to_string()))
+            .await
+            .unwrap();
 
-    #[test]
-    fn test_tool_blocks_initially_empty() {
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(state.tool_blocks().is_empty());
-        assert!(!state.has_tool_blocks());
+        // Should receive the chunk
+        let result = state.recv_api_chunk().await;
+        assert!(matches!(result, Some(StreamEvent::ContentDelta(s)) if s == "Hello"));
     }
 
-    #[test]
-    fn test_start_tool_block() {
+    #[tokio::test]
+    async fn test_recv_tool_result_returns_none_when_no_channel() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        let index = state.start_tool_block("bash", "git status");
-
-        assert_eq!(index, 0);
-        assert!(state.has_tool_blocks());
-        assert_eq!(state.tool_blocks().len(), 1);
+        // Initially tool_result_rx is None
+        assert!(!state.has_tool_result_rx());
 
-        let block = &state.tool_blocks()[0];
-        assert_eq!(block.tool_name(), "bash");
-        assert_eq!(block.tool_input(), "git status");
-        assert!(!block.is_complete());
+        // recv_tool_result should return None immediately, not block
+        let result = state.recv_tool_result().await;
+        assert!(result.is_none());
     }
 
-    #[test]
-    fn test_complete_tool_block_success() {
+    #[tokio::test]
+    async fn test_recv_tool_result_receives_results_when_channel_set() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        let index = state.start_tool_block("bash", "echo hello");
 
-        state.complete_tool_block(index, "hello", false);
+        // Set up a tool result channel
+        let (tx, rx) = mpsc::unbounded_channel();
+        state.set_tool_result_rx(rx);
+        assert!(state.has_tool_result_rx());
 
-        let block = &state.tool_blocks()[0];
-        assert!(block.is_complete());
-        assert_eq!(block.result(), Some("hello"));
-        assert!(!block.is_error());
+        // Send a result
+        let result_block = crate::types::ToolResultBlock {
+            tool_use_id: "toolu_123".to_string(),
+            content: "Success".to_string(),
+            is_error: false,
+        };
+        tx.send(("toolu_123".to_string(), result_block)).unwrap();
+
+        // Should receive the result
+        let result = state.recv_tool_result().await;
+        assert!(result.is_some());
+        let (id, block) = result.unwrap();
+        assert_eq!(id, "toolu_123");
+        assert_eq!(block.content, "Success");
+        assert!(!block.is_error);
     }
 
-    #[test]
-    fn test_complete_tool_block_error() {
+    #[tokio::test]
+    async fn test_recv_background_event_returns_none_when_no_channels() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        let index = state.start_tool_block("bash", "bad-command");
 
-        state.complete_tool_block(index, "Command not found", true);
+        // Initially both channels are None
+        assert!(!state.has_streaming());
+        assert!(!state.has_tool_result_rx());
+        assert!(!state.has_background_work());
 
-        let block = &state.tool_blocks()[0];
-        assert!(block.is_complete());
-        assert_eq!(block.result(), Some("Command not found"));
-        assert!(block.is_error());
+        // recv_background_event should return None immediately, not block
+        let result = state.recv_background_event().await;
+        assert!(result.is_none());
     }
 
-    #[test]
-    fn test_multiple_tool_blocks() {
+    #[tokio::test]
+    async fn test_recv_background_event_prioritizes_tool_results() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        let idx1 = state.start_tool_block("bash", "ls");
-        let idx2 = state.start_tool_block("read", "/tmp/file.txt");
-
-        assert_eq!(idx1, 0);
-        assert_eq!(idx2, 1);
-        assert_eq!(state.tool_blocks().len(), 2);
+        // Set up both channels
+        let (api_tx, api_rx) = mpsc::channel(100);
+        let (tool_tx, tool_rx) = mpsc::unbounded_channel();
+        state.set_streaming_rx(api_rx);
+        state.set_tool_result_rx(tool_rx);
 
-        state.complete_tool_block(idx1, "file1.txt\nfile2.txt", false);
-        state.complete_tool_block(idx2, "file contents", false);
+        // Send to both channels
+        api_tx
+            .send(StreamEvent::ContentDelta("API chunk".to_string()))
+            .await
+            .unwrap();
+        let result_block = crate::types::ToolResultBlock {
+            tool_use_id: "toolu_456".to_string(),
+            content: "Tool result".to_string(),
+            is_error: false,
+        };
+        tool_tx
+            .send(("toolu_456".to_string(), result_block))
+            .unwrap();
 
-        assert!(state.tool_blocks()[0].is_complete());
-        assert!(state.tool_blocks()[1].is_complete());
+        // Tool results are prioritized (biased select)
+        let result = state.recv_background_event().await;
+        assert!(matches!(result, Some(BackgroundEvent::ToolResult(id, _)) if id == "toolu_456"));
     }
 
-    #[test]
-    fn test_clear_tool_blocks() {
+    #[tokio::test]
+    async fn test_recv_background_event_receives_api_chunks() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        state.start_tool_block("bash", "ls");
-        state.start_tool_block("read", "/tmp/test");
-        assert_eq!(state.tool_blocks().len(), 2);
+        // Set up only API channel (no tool channel)
+        let (api_tx, api_rx) = mpsc::channel(100);
+        state.set_streaming_rx(api_rx);
 
-        state.clear_tool_blocks();
+        // Send an API chunk
+        api_tx
+            .send(StreamEvent::ContentDelta("API data".to_string()))
+            .await
+            .unwrap();
 
-        assert!(state.tool_blocks().is_empty());
-        assert!(!state.has_tool_blocks());
+        // Should receive the API chunk
+        let result = state.recv_background_event().await;
+        assert!(
+            matches!(result, Some(BackgroundEvent::ApiChunk(StreamEvent::ContentDelta(s))) if s == "API data")
+        );
     }
 
-    #[test]
-    fn test_complete_invalid_index_is_safe() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // Completing a non-existent index should not panic
-        state.complete_tool_block(999, "result", false);
+    #[tokio::test]
+    async fn test_title_generated_and_stored_after_first_turn() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let sse_response = r#"event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Renaming a Git branch"}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_partial_json(
+                serde_json::json!({ "model": "title-model" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(sse_response, "text/event-stream"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-        assert!(state.tool_blocks().is_empty());
-    }
+        let client = AnthropicClient::new_with_base_url(
+            secrecy::SecretString::from("key"),
+            "main-model",
+            &mock_server.uri(),
+        );
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_title_model(Some("title-model".to_string()));
 
-    // ========================================================================
-    // Context Truncation Integration Tests (Cost Optimization)
-    // ========================================================================
+        // No title before the first exchange completes
+        state.add_message(Message {
+            role: Role::User,
+            content: "How do I rename a git branch?".to_string(),
+        });
+        state.maybe_request_title(&client);
+        assert!(!state.has_background_work());
 
-    #[test]
-    fn test_api_messages_truncated_returns_truncated() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.add_message(Message {
+            role: Role::Assistant,
+            content: "Use git branch -m old new.".to_string(),
+        });
+        state.maybe_request_title(&client);
+        // Only requested once per session
+        state.maybe_request_title(&client);
 
-        // Add many messages to potentially exceed budget
-        for i in 0..50 {
-            state
-                .api_messages
-                .push(ApiMessageV2::user(format!("Message {}", i)));
-        }
+        let event = state.recv_background_event().await;
+        let Some(BackgroundEvent::Title(title)) = event else {
+            panic!("expected title event, got {event:?}");
+        };
+        assert!(state.apply_generated_title(title));
+        assert!(!state.has_background_work());
 
-        let truncated = state.api_messages_truncated();
+        assert_eq!(state.session_title(), Some("Renaming a Git branch"));
+        assert_eq!(state.to_session().title(), Some("Renaming a Git branch"));
 
-        // Should return messages (truncation logic is in api::context)
-        assert!(!truncated.is_empty());
-        // First message preserved
-        assert_eq!(truncated[0].content.to_text(), "Message 0");
-        // Most recent preserved
-        assert_eq!(truncated.last().unwrap().content.to_text(), "Message 49");
+        // Naming the session keeps the generated title
+        state.apply_command_action(CommandAction::NameSession("branch-rename".to_string()));
+        let session = state.to_session();
+        assert_eq!(session.name(), Some("branch-rename"));
+        assert_eq!(session.title(), Some("Renaming a Git branch"));
     }
 
-    #[test]
-    fn test_api_messages_truncated_under_budget_unchanged() {
+    #[tokio::test]
+    async fn test_explain_sends_last_error_and_shows_reply_out_of_band() {
+        use crate::types::ConversationEntry;
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let sse_response = r#"event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"The API is overloaded. Wait a minute and retry."}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_string_contains("overloaded_error"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(sse_response, "text/event-stream"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = AnthropicClient::new_with_base_url(
+            secrecy::SecretString::from("key"),
+            "main-model",
+            &mock_server.uri(),
+        );
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        state.api_messages.push(ApiMessageV2::user("Hello"));
+        // Nothing to explain before an error
+        let output = state.apply_command_action(CommandAction::ExplainLastError);
+        assert!(output.contains("No errors"), "got: {output}");
+        state.maybe_request_explanation(&client);
+        assert!(!state.has_background_work());
+
         state
-            .api_messages
-            .push(ApiMessageV2::assistant("Hi there!"));
+            .append_chunk(StreamEvent::Error(
+                "529 Overloaded: {\"type\":\"overloaded_error\"}".to_string(),
+            ))
+            .unwrap();
+        assert!(state.last_error().is_some());
+        let messages_before = state.api_messages().len();
 
-        let truncated = state.api_messages_truncated();
+        let output = state.apply_command_action(CommandAction::ExplainLastError);
+        assert!(output.contains("API error"), "got: {output}");
+        state.maybe_request_explanation(&client);
 
-        // Under budget - should be unchanged
-        assert_eq!(truncated.len(), 2);
-        assert_eq!(truncated[0].content.to_text(), "Hello");
-        assert_eq!(truncated[1].content.to_text(), "Hi there!");
+        let event = state.recv_background_event().await;
+        let Some(BackgroundEvent::Explanation(explanation)) = event else {
+            panic!("expected explanation event, got {event:?}");
+        };
+        state.apply_explanation(explanation);
+        assert!(!state.has_background_work());
+
+        let last = state.timeline().entries().last().unwrap();
+        assert!(
+            matches!(last, ConversationEntry::AssistantMessage(s) if s.contains("Wait a minute")),
+            "got: {last:?}"
+        );
+        // The explanation is not part of the conversation sent to the API
+        assert_eq!(state.api_messages().len(), messages_before);
     }
 
     #[test]
-    fn test_api_messages_truncated_empty() {
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    fn test_record_tool_error_sets_last_error() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        let truncated = state.api_messages_truncated();
+        state.record_tool_result(
+            "toolu_missing",
+            crate::types::ToolResultBlock {
+                tool_use_id: "toolu_missing".to_string(),
+                content: "No such file".to_string(),
+                is_error: true,
+            },
+        );
 
-        assert!(truncated.is_empty());
+        assert_eq!(
+            state.last_error(),
+            Some(&LastError::tool("unknown", "No such file"))
+        );
     }
 
     #[test]
-    fn test_api_messages_truncated_with_large_content() {
+    fn test_has_background_work() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Add first message (will be preserved)
-        state.api_messages.push(ApiMessageV2::user("System prompt"));
-
-        // Add many large messages that would exceed 100k tokens
-        let large_content = "x".repeat(10_000); // ~2500 tokens each
-        for _ in 0..50 {
-            // 50 * 2500 = 125k tokens
-            state
-                .api_messages
-                .push(ApiMessageV2::assistant(&large_content));
-        }
-
-        let truncated = state.api_messages_truncated();
+        // Initially no background work
+        assert!(!state.has_background_work());
 
-        // Should be fewer than 51 messages
-        assert!(
-            truncated.len() < 51,
-            "Should be truncated, got {}",
-            truncated.len()
-        );
-        // First message always preserved
-        assert_eq!(truncated[0].content.to_text(), "System prompt");
-    }
+        // Set up streaming channel
+        let (_tx, rx) = mpsc::channel::<StreamEvent>(100);
+        state.set_streaming_rx(rx);
+        assert!(state.has_background_work());
 
-    // =========================================================================
-    // Focus Area Tests
-    // =========================================================================
+        // Clear streaming, set tool channel
+        state.streaming_rx = None;
+        let (_tx, rx) = mpsc::unbounded_channel();
+        state.set_tool_result_rx(rx);
+        assert!(state.has_background_work());
 
-    #[test]
-    fn test_focus_area_default_is_input() {
-        use crate::tui::selection::FocusArea;
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert_eq!(state.focus_area(), FocusArea::Input);
+        // Clear tool channel
+        state.clear_tool_result_rx();
+        assert!(!state.has_background_work());
     }
 
     #[test]
-    fn test_focus_area_can_be_set() {
-        use crate::tui::selection::FocusArea;
+    fn test_clear_tool_result_rx() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        state.set_focus_area(FocusArea::Content);
-        assert_eq!(state.focus_area(), FocusArea::Content);
+        // Set up channel
+        let (_tx, rx) = mpsc::unbounded_channel();
+        state.set_tool_result_rx(rx);
+        assert!(state.has_tool_result_rx());
 
-        state.set_focus_area(FocusArea::Input);
-        assert_eq!(state.focus_area(), FocusArea::Input);
+        // Clear it
+        state.clear_tool_result_rx();
+        assert!(!state.has_tool_result_rx());
     }
 
     #[test]
-    fn test_focus_change_clears_selection() {
-        use crate::tui::selection::{ContentPosition, FocusArea};
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // Create a selection
-        state.selection_mut().start(ContentPosition::new(0, 0));
-        state.selection_mut().update(ContentPosition::new(5, 10));
-        state.selection_mut().end();
-        assert!(state.selection().has_selection());
+    fn test_background_event_enum() {
+        // Test ApiChunk variant
+        let chunk = BackgroundEvent::ApiChunk(StreamEvent::ContentDelta("test".to_string()));
+        assert!(matches!(chunk, BackgroundEvent::ApiChunk(_)));
 
-        // Change focus should clear selection
-        state.set_focus_area(FocusArea::Content);
-        assert!(!state.selection().has_selection());
+        // Test ToolResult variant
+        let result = crate::types::ToolResultBlock {
+            tool_use_id: "id".to_string(),
+            content: "result".to_string(),
+            is_error: false,
+        };
+        let event = BackgroundEvent::ToolResult("id".to_string(), result);
+        assert!(matches!(event, BackgroundEvent::ToolResult(_, _)));
     }
 
-    #[test]
-    fn test_focus_same_area_preserves_selection() {
-        use crate::tui::selection::{ContentPosition, FocusArea};
+    #[tokio::test]
+    async fn test_spawn_tool_execution_sets_channel() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Set focus to content
-        state.set_focus_area(FocusArea::Content);
+        // No channel initially
+        assert!(!state.has_tool_result_rx());
 
-        // Create a selection
-        state.selection_mut().start(ContentPosition::new(0, 0));
-        state.selection_mut().update(ContentPosition::new(5, 10));
-        state.selection_mut().end();
-        assert!(state.selection().has_selection());
+        // Set up tool use and spawn
+        state.tool_loop_mut().start_streaming().unwrap();
+        state.handle_tool_use_start("toolu_789".to_string(), "bash".to_string(), 0);
+        state.handle_tool_use_input_delta(0, r#"{"command":"echo hi"}"#);
+        state.handle_tool_use_complete(0).unwrap();
+        state.handle_message_complete(StopReason::ToolUse).unwrap();
+        state.approve_all_tools().unwrap();
 
-        // Setting same focus should NOT clear selection
-        state.set_focus_area(FocusArea::Content);
-        assert!(state.selection().has_selection());
+        // Spawn should set up channel (requires tokio runtime)
+        let _handle = state.spawn_tool_execution();
+        assert!(state.has_tool_result_rx());
+        assert!(state.has_executing_tools());
     }
 
-    #[test]
-    fn test_focus_area_for_row_content() {
-        use crate::tui::selection::FocusArea;
-        // Terminal height 30: input is rows 27-29, content is 0-26
-        assert_eq!(AppState::focus_area_for_row(0, 30), FocusArea::Content);
-        assert_eq!(AppState::focus_area_for_row(10, 30), FocusArea::Content);
-        assert_eq!(AppState::focus_area_for_row(26, 30), FocusArea::Content);
+    #[tokio::test]
+    async fn test_cancel_aborts_running_tools() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        state.set_permission_mode(PermissionMode::FullAccess, None);
+        state.tool_loop_mut().start_streaming().unwrap();
+        state.handle_tool_use_start("toolu_1".to_string(), "bash".to_string(), 0);
+        state.handle_tool_use_input_delta(0, r#"{"command":"sleep 1 && touch done.txt"}"#);
+        state.handle_tool_use_complete(0).unwrap();
+        state.handle_message_complete(StopReason::ToolUse).unwrap();
+        state.approve_all_tools().unwrap();
+
+        let handle = state.spawn_tool_execution().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        state.cancel_in_flight();
+
+        // The task is aborted and the command it started never finishes
+        assert!(handle.await.unwrap_err().is_cancelled());
+        assert!(!state.is_busy());
+        assert!(!state.has_executing_tools());
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+        assert!(!dir.path().join("done.txt").exists());
+        assert!(state.recv_background_event().await.is_none());
     }
 
-    #[test]
-    fn test_focus_area_for_row_input() {
-        use crate::tui::selection::FocusArea;
-        // Terminal height 30: input is rows 27-29
-        assert_eq!(AppState::focus_area_for_row(27, 30), FocusArea::Input);
-        assert_eq!(AppState::focus_area_for_row(28, 30), FocusArea::Input);
-        assert_eq!(AppState::focus_area_for_row(29, 30), FocusArea::Input);
+    #[tokio::test]
+    async fn test_tool_needing_permission_pauses_for_prompt() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        state.tool_loop_mut().start_streaming().unwrap();
+        state.handle_tool_use_start("toolu_1".to_string(), "write_file".to_string(), 0);
+        state.handle_tool_use_input_delta(0, r#"{"path":"notes.txt","content":"hi"}"#);
+        state.handle_tool_use_complete(0).unwrap();
+        state.handle_message_complete(StopReason::ToolUse).unwrap();
+        state.approve_all_tools().unwrap();
+
+        let _handle = state.spawn_tool_execution();
+        let event = state.recv_background_event().await;
+        let Some(BackgroundEvent::PermissionNeeded(request)) = event else {
+            panic!("expected permission request, got {event:?}");
+        };
+        assert_eq!(request.tool_name, "write_file");
+        state.request_tool_permission(request);
+        assert!(state.has_pending_permission());
+        assert!(!state.has_executing_tools());
+        assert!(!state.is_busy());
+        assert!(!dir.path().join("notes.txt").exists());
+
+        // Allowing the call runs it on the next spawn
+        state
+            .handle_permission_response(PermissionResponse::AllowOnce)
+            .await;
+        let _handle = state.spawn_tool_execution();
+        let event = state.recv_background_event().await;
+        let Some(BackgroundEvent::ToolResult(tool_id, result)) = event else {
+            panic!("expected tool result, got {event:?}");
+        };
+        assert!(!result.is_error, "{}", result.content);
+        state.record_tool_result(&tool_id, result);
+        assert!(state.all_tools_complete());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("notes.txt")).unwrap(),
+            "hi"
+        );
     }
 
-    #[test]
-    fn test_focus_area_for_row_small_terminal() {
-        use crate::tui::selection::FocusArea;
-        // Minimum terminal height 7: content rows 0-3, input rows 4-6
-        assert_eq!(AppState::focus_area_for_row(0, 7), FocusArea::Content);
-        assert_eq!(AppState::focus_area_for_row(3, 7), FocusArea::Content);
-        assert_eq!(AppState::focus_area_for_row(4, 7), FocusArea::Input);
-        assert_eq!(AppState::focus_area_for_row(6, 7), FocusArea::Input);
+    #[tokio::test]
+    async fn test_rejected_permission_prompt_is_audited() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log_path = dir.path().join("tools.jsonl");
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        state
+            .enable_tool_audit(ToolAuditConfig::new(&log_path))
+            .unwrap();
+        state.tool_loop_mut().start_streaming().unwrap();
+        state.handle_tool_use_start("toolu_1".to_string(), "write_file".to_string(), 0);
+        state.handle_tool_use_input_delta(0, r#"{"path":"notes.txt","content":"hi"}"#);
+        state.handle_tool_use_complete(0).unwrap();
+        state.handle_message_complete(StopReason::ToolUse).unwrap();
+        state.approve_all_tools().unwrap();
+
+        let _handle = state.spawn_tool_execution();
+        let event = state.recv_background_event().await;
+        let Some(BackgroundEvent::PermissionNeeded(request)) = event else {
+            panic!("expected permission request, got {event:?}");
+        };
+        state.request_tool_permission(request);
+        state
+            .handle_permission_response(PermissionResponse::Deny)
+            .await;
+        state.deny_all_tools().unwrap();
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        let record: Value = serde_json::from_str(log.lines().next().unwrap()).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert_eq!(record["tool"], "write_file");
+        assert_eq!(record["input"]["path"], "notes.txt");
+        assert_eq!(record["status"], "rejected");
+        assert!(!dir.path().join("notes.txt").exists());
     }
 
-    // Plugin loading tests
+    #[tokio::test]
+    async fn test_project_hooks_load_only_when_trusted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".patina")).unwrap();
+        std::fs::write(
+            dir.path().join(".patina/hooks.toml"),
+            "[[SessionStart]]\nhooks = [{ type = \"command\", command = \"echo project hook; exit 2\" }]\n",
+        )
+        .unwrap();
 
-    #[test]
-    fn test_with_plugins_disabled_creates_empty_registry() {
-        let state = AppState::with_plugins(
-            PathBuf::from("/test"),
-            false,
-            ParallelMode::Enabled,
-            false, // plugins_enabled = false
-        );
-        // With plugins disabled, registry should be empty
-        assert_eq!(state.plugins().plugin_count(), 0);
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        state.load_hooks(None, false);
+        assert!(state.fire_session_start().await.is_ok());
+
+        let mut trusted = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        trusted.load_hooks(None, true);
+        let err = trusted.fire_session_start().await.unwrap_err();
+        assert!(err.to_string().contains("project hook"), "{err}");
     }
 
-    #[test]
-    fn test_with_plugins_enabled_returns_valid_registry() {
-        let state = AppState::with_plugins(
-            PathBuf::from("/test"),
-            false,
-            ParallelMode::Enabled,
-            true, // plugins_enabled = true
-        );
-        // Registry should be valid (may be empty if no plugins installed)
-        // Just verify we can access the registry without panicking
-        let _ = state.plugins().plugin_count();
+    #[tokio::test]
+    async fn test_mcp_reconnect_shows_notice_and_refreshes_tools() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let (tx, rx) = mpsc::unbounded_channel();
+        state.set_mcp_manager(McpManager::new().with_connection_events(tx.clone()));
+        state.set_mcp_events_receiver(rx);
+        state.mcp_tools = vec![ToolDefinition::new(
+            "mcp__docs__stale",
+            "Gone after the restart",
+            serde_json::json!({"type": "object"}),
+        )];
+
+        tx.send(McpConnectionEvent::Reconnected {
+            server: "docs".to_string(),
+            tools: 0,
+        })
+        .unwrap();
+        let Some(BackgroundEvent::McpConnection(event)) = state.recv_background_event().await
+        else {
+            panic!("expected connection event");
+        };
+        state.apply_mcp_connection_event(event);
+        let notice = state.timeline().iter().last().unwrap();
+        assert!(format!("{notice:?}").contains("MCP server 'docs' reconnected"));
+
+        let Some(BackgroundEvent::McpTools(tools)) = state.recv_background_event().await else {
+            panic!("expected refreshed tools");
+        };
+        state.apply_mcp_tools(tools);
+        assert!(state.mcp_tools.is_empty());
+        assert!(state.mcp_tools_rx.is_none());
     }
 
     #[test]
-    fn test_new_enables_plugins_by_default() {
-        // AppState::new should enable plugins (equivalent to with_plugins(..., true))
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        // Registry should be accessible (plugins enabled by default)
-        let _ = state.plugins().plugin_count();
-    }
+    fn test_record_tool_result_updates_state() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-    // =========================================================================
-    // 1.5.4.3 - Subagent wiring tests
-    // =========================================================================
+        // Mark a tool as executing
+        state.mark_tool_executing("toolu_abc");
+        assert!(state.has_executing_tools());
 
-    #[test]
-    fn test_new_disables_subagents_by_default() {
-        // AppState::new should disable subagents by default
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(!state.subagents_enabled());
-        assert!(state.subagent_spawner().is_none());
-    }
+        // Record result
+        let result = crate::types::ToolResultBlock {
+            tool_use_id: "toolu_abc".to_string(),
+            content: "Done".to_string(),
+            is_error: false,
+        };
+        state.record_tool_result("toolu_abc", result);
 
-    #[test]
-    fn test_with_options_subagents_disabled() {
-        let state = AppState::with_options(
-            PathBuf::from("/test"),
-            false,
-            ParallelMode::Enabled,
-            true,  // plugins_enabled
-            false, // subagents_enabled
-        );
-        assert!(!state.subagents_enabled());
-        assert!(state.subagent_spawner().is_none());
+        // Should no longer have executing tools
+        assert!(!state.has_executing_tools());
     }
 
     #[test]
-    fn test_with_options_subagents_enabled() {
-        let state = AppState::with_options(
-            PathBuf::from("/test"),
-            false,
-            ParallelMode::Enabled,
-            true, // plugins_enabled
-            true, // subagents_enabled
+    fn test_read_mcp_resource_is_recorded_in_session_context() {
+        use crate::types::{ToolResultBlock, ToolUseBlock};
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let read = |id: &str, uri: &str| {
+            ToolUseBlock::new(
+                id,
+                "read_mcp_resource",
+                serde_json::json!({"server": "docs", "uri": uri}),
+            )
+        };
+        state.add_pending_tool(read("toolu_ok", "file:///guide.md"));
+        state.add_pending_tool(read("toolu_err", "file:///missing.md"));
+
+        state.record_tool_result(
+            "toolu_err",
+            ToolResultBlock::error("toolu_err", "not found"),
         );
-        assert!(state.subagents_enabled());
-        assert!(state.subagent_spawner().is_some());
+        assert!(state.session_context().is_none());
+
+        state.record_tool_result("toolu_ok", ToolResultBlock::success("toolu_ok", "# Guide"));
+        let resources = state.session_context().unwrap().resources();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].server(), "docs");
+        assert_eq!(resources[0].uri(), "file:///guide.md");
     }
 
     #[test]
-    fn test_subagent_spawner_returns_valid_spawner_when_enabled() {
-        let state = AppState::with_options(
-            PathBuf::from("/test"),
-            false,
-            ParallelMode::Enabled,
-            false, // plugins_enabled
-            true,  // subagents_enabled
+    fn test_successful_edits_notify_the_ide() {
+        use crate::types::{ToolResultBlock, ToolUseBlock};
+
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let notifier = IdeNotifier::new();
+        let mut notifications = notifier.subscribe();
+        state.set_ide_notifier(notifier);
+
+        state.add_pending_tool(ToolUseBlock::new(
+            "toolu_edit",
+            "edit",
+            serde_json::json!({"path": "src/lib.rs", "old_string": "old", "new_string": "new"}),
+        ));
+        state.add_pending_tool(ToolUseBlock::new(
+            "toolu_failed",
+            "write_file",
+            serde_json::json!({"path": "a.txt", "content": "x"}),
+        ));
+        state.add_pending_tool(ToolUseBlock::new(
+            "toolu_read",
+            "read_file",
+            serde_json::json!({"path": "src/lib.rs"}),
+        ));
+
+        state.record_tool_result(
+            "toolu_failed",
+            ToolResultBlock::error("toolu_failed", "denied"),
         );
+        state.record_tool_result("toolu_read", ToolResultBlock::success("toolu_read", "text"));
+        state.record_tool_result("toolu_edit", ToolResultBlock::success("toolu_edit", "ok"));
 
-        // Verify we can access the spawner
-        let spawner = state.subagent_spawner().expect("spawner should be Some");
-        // Verify spawner has a valid model configured
-        assert!(spawner.model().contains("claude"));
+        let notification: Value = serde_json::from_str(&notifications.try_recv().unwrap()).unwrap();
+        assert_eq!(notification["method"], "file/didChange");
+        assert_eq!(notification["params"]["path"], "/test/src/lib.rs");
+        assert_eq!(notification["params"]["diff"], "- old\n+ new\n");
+        assert!(notifications.try_recv().is_err());
     }
 
     #[test]
-    fn test_with_plugins_disables_subagents() {
-        // with_plugins should disable subagents (for backward compatibility)
-        let state = AppState::with_plugins(
-            PathBuf::from("/test"),
-            false,
-            ParallelMode::Enabled,
-            true, // plugins_enabled
-        );
-        assert!(!state.subagents_enabled());
-        assert!(state.subagent_spawner().is_none());
-    }
+    fn test_all_tools_complete() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-    // =========================================================================
-    // 2.2.4 - Auto-context injection tests
-    // =========================================================================
+        // No executing tools initially
+        assert!(state.all_tools_complete());
 
-    #[test]
-    fn test_auto_context_disabled_by_default() {
-        // AppState should have auto_context disabled by default
-        // (Config enables it, but AppState needs explicit opt-in)
-        let state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-        assert!(!state.auto_context_enabled());
+        // Mark a tool as executing
+        state.mark_tool_executing("toolu_xyz");
+        assert!(!state.all_tools_complete());
+
+        // Record result
+        let result = crate::types::ToolResultBlock {
+            tool_use_id: "toolu_xyz".to_string(),
+            content: "Complete".to_string(),
+            is_error: false,
+        };
+        state.record_tool_result("toolu_xyz", result);
+        assert!(state.all_tools_complete());
     }
 
     #[test]
-    fn test_set_auto_context_enabled() {
+    fn test_ctrl_c_cancel_keeps_partial_response() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.set_ctrl_c_mode(CtrlCMode::CancelThenExit);
 
-        // Initially disabled
-        assert!(!state.auto_context_enabled());
+        let (tx, rx) = mpsc::channel(100);
+        state.set_streaming_rx(rx);
+        state.set_loading(true);
+        state.set_current_response(String::new());
+        state
+            .append_chunk(StreamEvent::ContentDelta("Partial".to_string()))
+            .unwrap();
 
-        // Enable it
-        state.set_auto_context_enabled(true);
-        assert!(state.auto_context_enabled());
+        assert_eq!(state.handle_ctrl_c(), CtrlCAction::Cancel);
 
-        // Disable it
-        state.set_auto_context_enabled(false);
-        assert!(!state.auto_context_enabled());
+        assert!(!state.is_busy());
+        assert!(!state.has_streaming());
+        assert!(tx.is_closed());
+        assert!(!state.timeline().is_streaming());
+        assert!(state
+            .api_messages()
+            .last()
+            .is_some_and(|m| m.role == Role::Assistant));
+        assert!(state.ctrl_c_exit_armed());
+        assert_eq!(state.handle_ctrl_c(), CtrlCAction::Exit);
     }
 
-    #[test]
-    fn test_inject_context_suggestions() {
-        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
-
+    #[tokio::test]
+    async fn test_cancel_aborts_stream_and_marks_turn_cancelled() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.timeline.push_streaming();
 
-        // Create test suggestions
-        let suggestions = vec![
-            ContextSuggestion {
-                source: CodeReference::function("process_data"),
-                kind: ContextKind::Callers,
-                description: "Functions that call process_data".to_string(),
-                content: "main() in src/main.rs:10".to_string(),
-            },
-            ContextSuggestion {
-                source: CodeReference::file("src/handler.rs"),
-                kind: ContextKind::Imports,
-                description: "Imports in src/handler.rs".to_string(),
-                content: "use std::io".to_string(),
-            },
-        ];
+        let (tx, rx) = mpsc::channel(100);
+        let task = tokio::spawn(async move {
+            let _tx = tx;
+            std::future::pending::<()>().await;
+        });
+        state.set_streaming_rx(rx);
+        state.set_stream_task(task.abort_handle());
+        state.set_loading(true);
+        state
+            .append_chunk(StreamEvent::ContentDelta("Partial".to_string()))
+            .unwrap();
 
-        // Inject the suggestions
-        state.set_pending_context(suggestions.clone());
+        state.cancel_in_flight();
 
-        // Verify we have pending context
-        assert!(state.has_pending_context());
-        assert_eq!(state.pending_context().len(), 2);
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert!(!state.is_busy());
+        let entries = state.timeline().entries();
+        assert_eq!(entries[entries.len() - 2].text(), Some("Partial"));
+        assert_eq!(entries[entries.len() - 1].text(), Some(CANCELLED_NOTICE));
+        assert_eq!(
+            state.api_messages().last().unwrap().content.to_text(),
+            "Partial"
+        );
     }
 
     #[test]
-    fn test_take_pending_context_clears() {
-        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
-
+    fn test_ctrl_c_exit_mode_does_not_cancel() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let (_tx, rx) = mpsc::channel(100);
+        state.set_streaming_rx(rx);
 
-        let suggestions = vec![ContextSuggestion {
-            source: CodeReference::function("test_fn"),
-            kind: ContextKind::Callers,
-            description: "Test".to_string(),
-            content: "Content".to_string(),
-        }];
+        assert_eq!(state.handle_ctrl_c(), CtrlCAction::Exit);
+        assert!(state.has_streaming());
+    }
 
-        state.set_pending_context(suggestions);
-        assert!(state.has_pending_context());
+    #[test]
+    fn test_set_context_window_raises_budget_and_keeps_usage() {
+        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert_eq!(state.token_budget().limit(), DEFAULT_MAX_INPUT_TOKENS);
+        state.record_api_usage(50_000, 1_000);
 
-        // Take should return and clear
-        let taken = state.take_pending_context();
-        assert_eq!(taken.len(), 1);
-        assert!(!state.has_pending_context());
-        assert!(state.pending_context().is_empty());
+        state.set_context_window(LARGE_CONTEXT_WINDOW);
+
+        assert_eq!(state.context_window(), LARGE_CONTEXT_WINDOW);
+        assert_eq!(state.token_budget().limit(), LARGE_CONTEXT_WINDOW);
+        assert_eq!(state.token_budget().used(), 51_000);
     }
 
     #[test]
-    fn test_format_context_for_message() {
-        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
+    fn test_summary_style_command_applies_to_next_compaction() {
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        assert_eq!(
+            state.apply_command_action(CommandAction::ShowSummaryStyle),
+            "Summary style: detailed"
+        );
 
-        let suggestions = vec![ContextSuggestion {
-            source: CodeReference::function("process_data"),
-            kind: ContextKind::Callers,
-            description: "Functions that call process_data".to_string(),
-            content: "main() in src/main.rs:10\nhandle_request() in src/api.rs:25".to_string(),
-        }];
+        let output =
+            state.apply_command_action(CommandAction::SetSummaryStyle(SummaryStyle::Narrative));
+        assert_eq!(output, "Summary style set to paragraph.");
+        assert_eq!(state.summary_style(), SummaryStyle::Narrative);
 
-        let formatted = AppState::format_context_suggestions(&suggestions);
+        state.set_context_window(1000);
+        let padding = "x".repeat(400);
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("System context"));
+        for i in 0..10 {
+            state
+                .api_messages_mut()
+                .push(ApiMessageV2::user(format!("Q{i} {padding}")));
+            state
+                .api_messages_mut()
+                .push(ApiMessageV2::assistant(format!("Implemented step {i}.")));
+        }
 
-        // Should contain the description and content
-        assert!(formatted.contains("Functions that call process_data"));
-        assert!(formatted.contains("main() in src/main.rs:10"));
-        assert!(formatted.contains("handle_request() in src/api.rs:25"));
+        assert!(state.maybe_auto_compact());
+        assert!(state.api_messages()[1]
+            .content
+            .to_text()
+            .contains("Summary of earlier conversation:"));
     }
 
     #[test]
-    fn test_format_context_empty_suggestions() {
-        let suggestions: Vec<crate::narsil::context::ContextSuggestion> = vec![];
-        let formatted = AppState::format_context_suggestions(&suggestions);
+    fn test_tokens_command_reports_context_usage() {
+        use crate::types::{ContentBlock, MessageContent};
+
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        state.set_context_window(1000);
+        assert_eq!(
+            state.apply_command_action(CommandAction::ShowTokens),
+            "Context: ~0 of 1000 tokens (0.0%)\nMessages: 0 (0 tool results)"
+        );
 
-        // Empty suggestions should return empty string
-        assert!(formatted.is_empty());
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("x".repeat(400)));
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user_with_content(MessageContent::Blocks(
+                vec![ContentBlock::tool_result("tool_1", "ok")],
+            )));
+        let used = estimate_messages_tokens(state.api_messages());
+        let output = state.apply_command_action(CommandAction::ShowTokens);
+        assert!(output.starts_with(&format!("Context: ~{used} of 1000 tokens (")));
+        assert!(output.ends_with("Messages: 2 (1 tool results)"), "{output}");
     }
 
     #[test]
-    fn test_clear_pending_context() {
-        use crate::narsil::context::{CodeReference, ContextKind, ContextSuggestion};
+    fn test_clear_conversation_keeps_session_identity() {
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        let mut context = SessionContext::new();
+        context.add_skill("rust");
+        let mut session = Session::new(PathBuf::from("/tmp"));
+        session.add_message(test_message(Role::User, "Hello"));
+        session.set_context(Some(context));
+        state.restore_from_session(&session);
+        state.set_session_id("session-1".to_string());
+        state.api_messages_mut().push(ApiMessageV2::user("Hello"));
 
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let output =
+            state.apply_command_action(CommandAction::ClearConversation { keep_context: true });
+        assert_eq!(output, "Conversation cleared; context files kept.");
+        assert!(state.api_messages().is_empty());
+        assert!(state.timeline().is_empty());
+        assert_eq!(state.session_id(), Some("session-1"));
+        let saved = state.to_session();
+        assert!(saved.messages().is_empty());
+        assert_eq!(saved.context().unwrap().active_skills(), ["rust"]);
 
-        let suggestions = vec![ContextSuggestion {
-            source: CodeReference::function("test_fn"),
-            kind: ContextKind::Callers,
-            description: "Test".to_string(),
-            content: "Content".to_string(),
-        }];
+        let output = state.apply_command_action(CommandAction::ClearConversation {
+            keep_context: false,
+        });
+        assert_eq!(output, "Conversation cleared.");
+        assert!(state.to_session().context().is_none());
+        assert_eq!(state.working_dir, PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_compact_now_summarizes_below_threshold() {
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        let padding = "x".repeat(400);
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("System context"));
+        for i in 0..10 {
+            state
+                .api_messages_mut()
+                .push(ApiMessageV2::user(format!("Q{i} {padding}")));
+            state
+                .api_messages_mut()
+                .push(ApiMessageV2::assistant(format!("Implemented step {i}.")));
+        }
+        let last = state.api_messages().last().unwrap().content.to_text();
+        assert!(!state.maybe_auto_compact(), "well under the threshold");
+
+        let output = state.apply_command_action(CommandAction::CompactNow);
+        assert!(
+            output.starts_with("Compacted 16 earlier messages into a detailed summary"),
+            "{output}"
+        );
+        assert!(output.contains("reclaiming ~"));
 
-        state.set_pending_context(suggestions);
-        assert!(state.has_pending_context());
+        let messages = state.api_messages();
+        assert_eq!(messages.len(), 6);
+        assert_eq!(messages[0].content.to_text(), "System context");
+        assert_eq!(messages.last().unwrap().content.to_text(), last);
+    }
 
-        state.clear_pending_context();
-        assert!(!state.has_pending_context());
+    #[test]
+    fn test_compact_now_with_short_history_is_a_no_op() {
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        assert!(state
+            .apply_command_action(CommandAction::CompactNow)
+            .starts_with("Not enough history to compact"));
+
+        state.api_messages_mut().push(ApiMessageV2::user("Hello"));
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::assistant("Hi there"));
+        assert!(state
+            .apply_command_action(CommandAction::CompactNow)
+            .starts_with("Not enough history to compact"));
+        assert_eq!(state.api_messages().len(), 2);
     }
 
-    // ========================================================================
-    // Event Loop Responsiveness Tests
-    //
-    // These tests ensure the event loop remains responsive during streaming
-    // and tool execution. Key behaviors:
-    // 1. recv_api_chunk() returns None immediately when no streaming
-    // 2. recv_tool_result() returns None immediately when no channel
-    // 3. recv_background_event() returns None when both channels are None
-    // 4. has_* methods accurately reflect channel state
-    // ========================================================================
+    #[test]
+    fn test_auto_compact_summarizes_history_past_threshold() {
+        use crate::types::ConversationEntry;
 
-    #[tokio::test]
-    async fn test_recv_api_chunk_returns_none_when_no_streaming() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        state.set_context_window(1000);
+        let padding = "x".repeat(400);
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("System context"));
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user(format!("Q0 {padding}")));
+        assert!(!state.maybe_auto_compact(), "under the threshold");
 
-        // Initially streaming_rx is None
-        assert!(!state.has_streaming());
+        for i in 1..10 {
+            state
+                .api_messages_mut()
+                .push(ApiMessageV2::assistant(format!("Implemented step {i}.")));
+            state
+                .api_messages_mut()
+                .push(ApiMessageV2::user(format!("Q{i} {padding}")));
+        }
+        let last = state.api_messages().last().unwrap().content.to_text();
+
+        assert!(state.maybe_auto_compact());
+
+        let messages = state.api_messages();
+        assert!(messages.len() < 20);
+        assert_eq!(messages[0].content.to_text(), "System context");
+        assert!(messages[1]
+            .content
+            .to_text()
+            .contains("Previous conversation"));
+        assert_eq!(messages.last().unwrap().content.to_text(), last);
+        assert!(estimate_messages_tokens(messages) <= 800);
+        let entries = state.timeline().entries();
+        assert!(matches!(
+            entries.last(),
+            Some(ConversationEntry::AssistantMessage(s)) if s.contains("Context compacted: summarized 15")
+        ));
+    }
 
-        // recv_api_chunk should return None immediately, not block
-        let result = state.recv_api_chunk().await;
-        assert!(result.is_none());
+    #[test]
+    fn test_set_auto_compact_threshold_rejects_out_of_range() {
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        assert!(state.set_auto_compact_threshold(0.0).is_err());
+        assert!(state.set_auto_compact_threshold(1.5).is_err());
+        assert!(state.set_auto_compact_threshold(f64::NAN).is_err());
+        assert!(state.set_auto_compact_threshold(0.5).is_ok());
     }
 
-    #[tokio::test]
-    async fn test_recv_api_chunk_receives_chunks_when_streaming() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    #[test]
+    fn test_check_context_budget_warns_near_limit_and_clears() {
+        let mut state = AppState::new(PathBuf::from("/tmp"), false, ParallelMode::Enabled);
+        state.set_context_window(1000);
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("x".repeat(400)));
+        assert_eq!(state.check_context_budget(), BudgetStatus::Ok);
+        assert_eq!(state.context_warning(), None);
 
-        // Set up a streaming channel
-        let (tx, rx) = mpsc::channel(100);
-        state.set_streaming_rx(rx);
-        assert!(state.has_streaming());
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("x".repeat(3200)));
+        assert!(matches!(
+            state.check_context_budget(),
+            BudgetStatus::Warning { limit: 1000, .. }
+        ));
+        assert!(state.context_warning().unwrap().contains("full"));
 
-        // Send a chunk
-        tx.send(StreamEvent::ContentDelta("Hello".to_string()))
-            .await
-            .unwrap();
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("x".repeat(800)));
+        assert!(matches!(
+            state.check_context_budget(),
+            BudgetStatus::OverLimit { limit: 1000, .. }
+        ));
 
-        // Should receive the chunk
-        let result = state.recv_api_chunk().await;
-        assert!(matches!(result, Some(StreamEvent::ContentDelta(s)) if s == "Hello"));
+        state.api_messages_mut().clear();
+        assert_eq!(state.check_context_budget(), BudgetStatus::Ok);
+        assert_eq!(state.context_warning(), None);
     }
 
     #[tokio::test]
-    async fn test_recv_tool_result_returns_none_when_no_channel() {
+    async fn test_set_permission_mode_applies_to_manager() {
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        assert_eq!(state.permission_mode(), PermissionMode::Ask);
+        assert_eq!(state.permission_mode_rule(), None);
 
-        // Initially tool_result_rx is None
-        assert!(!state.has_tool_result_rx());
+        state.set_permission_mode(PermissionMode::ReadOnly, Some("/opt".to_string()));
 
-        // recv_tool_result should return None immediately, not block
-        let result = state.recv_tool_result().await;
-        assert!(result.is_none());
+        assert_eq!(state.permission_mode(), PermissionMode::ReadOnly);
+        assert_eq!(state.permission_mode_rule(), Some("/opt"));
+        assert_eq!(
+            state.permission_manager.lock().await.mode(),
+            PermissionMode::ReadOnly
+        );
     }
 
-    #[tokio::test]
-    async fn test_recv_tool_result_receives_results_when_channel_set() {
+    #[test]
+    fn test_paste_inlines_small_text() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        state.insert_char('>');
 
-        // Set up a tool result channel
-        let (tx, rx) = mpsc::unbounded_channel();
-        state.set_tool_result_rx(rx);
-        assert!(state.has_tool_result_rx());
-
-        // Send a result
-        let result_block = crate::types::ToolResultBlock {
-            tool_use_id: "toolu_123".to_string(),
-            content: "Success".to_string(),
-            is_error: false,
-        };
-        tx.send(("toolu_123".to_string(), result_block)).unwrap();
+        state.pastes = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        state.paste("héllo\nworld");
 
-        // Should receive the result
-        let result = state.recv_tool_result().await;
-        assert!(result.is_some());
-        let (id, block) = result.unwrap();
-        assert_eq!(id, "toolu_123");
-        assert_eq!(block.content, "Success");
-        assert!(!block.is_error);
+        assert_eq!(state.input, ">héllo\nworld");
+        assert_eq!(state.cursor_position(), 12);
     }
 
-    #[tokio::test]
-    async fn test_recv_background_event_returns_none_when_no_channels() {
+    #[test]
+    fn test_paste_oversized_inserts_file_reference() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // Initially both channels are None
-        assert!(!state.has_streaming());
-        assert!(!state.has_tool_result_rx());
-        assert!(!state.has_background_work());
-
-        // recv_background_event should return None immediately, not block
-        let result = state.recv_background_event().await;
-        assert!(result.is_none());
+        state.set_paste_threshold(1024);
+        let blob = "x".repeat(1024 * 1024);
+
+        state.pastes = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        state.paste(&blob);
+
+        assert!(state.input.starts_with('@'));
+        assert!(state.input.len() < 1024, "Input buffer flooded");
+        let path = PathBuf::from(&state.input[1..]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), blob);
+        assert_eq!(state.pastes.expand_references(&state.input), blob);
+        let notice = state
+            .timeline()
+            .iter()
+            .last()
+            .and_then(|e| e.text())
+            .unwrap();
+        assert!(notice.contains("referenced instead of inlined"));
     }
 
-    #[tokio::test]
-    async fn test_recv_background_event_prioritizes_tool_results() {
+    #[test]
+    fn test_paste_rejects_binary() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
         let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
 
-        // Set up both channels
-        let (api_tx, api_rx) = mpsc::channel(100);
-        let (tool_tx, tool_rx) = mpsc::unbounded_channel();
-        state.set_streaming_rx(api_rx);
-        state.set_tool_result_rx(tool_rx);
+        state.pastes = PasteFiles::with_dir(temp_dir.path().join("pastes"));
+        state.paste("\u{7f}ELF\u{2}\u{1}\0\0\0");
 
-        // Send to both channels
-        api_tx
-            .send(StreamEvent::ContentDelta("API chunk".to_string()))
-            .await
-            .unwrap();
-        let result_block = crate::types::ToolResultBlock {
-            tool_use_id: "toolu_456".to_string(),
-            content: "Tool result".to_string(),
-            is_error: false,
-        };
-        tool_tx
-            .send(("toolu_456".to_string(), result_block))
+        assert!(state.input.is_empty());
+        let notice = state
+            .timeline()
+            .iter()
+            .last()
+            .and_then(|e| e.text())
             .unwrap();
-
-        // Tool results are prioritized (biased select)
-        let result = state.recv_background_event().await;
-        assert!(matches!(result, Some(BackgroundEvent::ToolResult(id, _)) if id == "toolu_456"));
+        assert!(notice.contains("binary"));
     }
 
-    #[tokio::test]
-    async fn test_recv_background_event_receives_api_chunks() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+    #[test]
+    fn test_reload_changed_plugins_reports_in_timeline() {
+        use crate::types::ConversationEntry;
 
-        // Set up only API channel (no tool channel)
-        let (api_tx, api_rx) = mpsc::channel(100);
-        state.set_streaming_rx(api_rx);
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
 
-        // Send an API chunk
-        api_tx
-            .send(StreamEvent::ContentDelta("API data".to_string()))
-            .await
-            .unwrap();
+        // A plugin added after startup is loaded
+        let plugin_dir = dir.path().join(".patina/plugins/demo");
+        std::fs::create_dir_all(plugin_dir.join(".claude-plugin")).unwrap();
+        std::fs::write(
+            plugin_dir.join(".claude-plugin/plugin.json"),
+            r#"{"name": "demo", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+        state.reload_changed_plugins(plugin_dir.clone());
+        assert!(state.plugins().has_plugin("demo"));
+        let last = state.timeline().entries().last().unwrap();
+        assert!(
+            matches!(last, ConversationEntry::AssistantMessage(s) if s == "Plugin 'demo' loaded"),
+            "got: {last:?}"
+        );
 
-        // Should receive the API chunk
-        let result = state.recv_background_event().await;
+        // A broken edit is reported and the loaded version stays
+        std::fs::write(plugin_dir.join(".claude-plugin/plugin.json"), "{ broken").unwrap();
+        state.reload_changed_plugins(plugin_dir);
+        assert!(state.plugins().has_plugin("demo"));
+        let last = state.timeline().entries().last().unwrap();
         assert!(
-            matches!(result, Some(BackgroundEvent::ApiChunk(StreamEvent::ContentDelta(s))) if s == "API data")
+            matches!(last, ConversationEntry::AssistantMessage(s) if s.contains("keeping the previous version")),
+            "got: {last:?}"
         );
     }
 
     #[test]
-    fn test_has_background_work() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // Initially no background work
-        assert!(!state.has_background_work());
+    fn test_skills_auto_activate_and_can_be_turned_off() {
+        use crate::skills::ActivationContext;
+
+        let dir = tempfile::tempdir().unwrap();
+        let skill_dir = dir.path().join(".patina/skills/rust");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: rust\ndescription: Rust help\ntriggers:\n  file_patterns:\n    - \"*.rs\"\n---\n\nUse clippy.\n",
+        )
+        .unwrap();
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        let files = [PathBuf::from("src/lib.rs")];
+        let ctx = ActivationContext {
+            prompt: "",
+            files: &files,
+        };
 
-        // Set up streaming channel
-        let (_tx, rx) = mpsc::channel::<StreamEvent>(100);
-        state.set_streaming_rx(rx);
-        assert!(state.has_background_work());
+        state.auto_activate_skills(&ctx);
+        assert_eq!(state.active_skills(), ["rust"]);
+        assert_eq!(state.timeline().len(), 1);
+
+        // Already active: not announced again
+        state.auto_activate_skills(&ctx);
+        assert_eq!(state.timeline().len(), 1);
+
+        // Turned off: stays off even when triggers match again
+        state.apply_command_action(CommandAction::DeactivateSkill("rust".to_string()));
+        state.auto_activate_skills(&ctx);
+        assert!(state.active_skills().is_empty());
+        assert!(state
+            .apply_command_action(CommandAction::ListSkills)
+            .contains("rust [off]"));
+
+        let client = AnthropicClient::new(secrecy::SecretString::from("key"), "model")
+            .with_system_prompt("Base prompt.");
+        assert_eq!(
+            state.request_client(&client).system_prompt(),
+            Some("Base prompt.")
+        );
 
-        // Clear streaming, set tool channel
-        state.streaming_rx = None;
-        let (_tx, rx) = mpsc::unbounded_channel();
-        state.set_tool_result_rx(rx);
-        assert!(state.has_background_work());
+        let output = state.apply_command_action(CommandAction::ActivateSkill("rust".to_string()));
+        assert_eq!(output, "Skill 'rust' is on.");
+        assert_eq!(state.active_skills(), ["rust"]);
+        let request = state.request_client(&client);
+        let system = request.system_prompt().unwrap();
+        assert!(system.starts_with("Base prompt.\n\n# Active skills"));
+        assert!(system.contains("## Skill: rust\n\nUse clippy."));
 
-        // Clear tool channel
-        state.clear_tool_result_rx();
-        assert!(!state.has_background_work());
+        let output = state.apply_command_action(CommandAction::ActivateSkill("nope".to_string()));
+        assert!(output.starts_with("Unknown skill"));
     }
 
     #[test]
-    fn test_clear_tool_result_rx() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // Set up channel
-        let (_tx, rx) = mpsc::unbounded_channel();
-        state.set_tool_result_rx(rx);
-        assert!(state.has_tool_result_rx());
+    fn test_editor_context_reaches_system_prompt() {
+        use crate::ide::rpc::EditorContextUpdate;
+        use crate::session::EditorSelection;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::new(dir.path().to_path_buf(), false, ParallelMode::Enabled);
+        let client = AnthropicClient::new(secrecy::SecretString::from("key"), "model")
+            .with_system_prompt("Base prompt.");
+
+        state.apply_editor_context(EditorContextUpdate::OpenFiles(vec![
+            dir.path().join("src/lib.rs"),
+            PathBuf::from("/elsewhere/notes.md"),
+        ]));
+        state.apply_editor_context(EditorContextUpdate::Selection(Some(EditorSelection {
+            path: dir.path().join("src/lib.rs"),
+            start_line: 9,
+            end_line: 11,
+            text: "fn parse() {}".to_string(),
+        })));
+
+        let request = state.request_client(&client);
+        let system = request.system_prompt().unwrap();
+        assert!(system.starts_with("Base prompt.\n\n# Editor context"));
+        assert!(system.contains("- src/lib.rs\n- /elsewhere/notes.md"));
+        assert!(system.contains("Selected in src/lib.rs (lines 10-12):\n```\nfn parse() {}\n```"));
+
+        // Clearing the selection keeps the open files
+        state.apply_editor_context(EditorContextUpdate::Selection(None));
+        let request = state.request_client(&client);
+        let system = request.system_prompt().unwrap();
+        assert!(system.contains("- src/lib.rs"));
+        assert!(!system.contains("Selected in"));
+    }
+
+    /// Runs git in `dir`, failing the test if it fails.
+    fn git(dir: &std::path::Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
 
-        // Clear it
-        state.clear_tool_result_rx();
-        assert!(!state.has_tool_result_rx());
+    /// Creates a git repository with one commit.
+    fn git_repo_with_commit() -> (tempfile::TempDir, PathBuf) {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().canonicalize().unwrap();
+        git(&repo, &["init", "-q"]);
+        git(&repo, &["config", "user.email", "test@test.com"]);
+        git(&repo, &["config", "user.name", "Test User"]);
+        std::fs::write(repo.join("README.md"), "# Test").unwrap();
+        git(&repo, &["add", "README.md"]);
+        git(&repo, &["commit", "-q", "-m", "Initial commit"]);
+        (temp, repo)
     }
 
     #[test]
-    fn test_background_event_enum() {
-        // Test ApiChunk variant
-        let chunk = BackgroundEvent::ApiChunk(StreamEvent::ContentDelta("test".to_string()));
-        assert!(matches!(chunk, BackgroundEvent::ApiChunk(_)));
+    fn test_worktree_new_moves_session_into_worktree() {
+        use crate::app::commands::{CommandResult, SlashCommandHandler};
 
-        // Test ToolResult variant
-        let result = crate::types::ToolResultBlock {
-            tool_use_id: "id".to_string(),
-            content: "result".to_string(),
-            is_error: false,
+        let (_temp, repo) = git_repo_with_commit();
+        let git = |args: &[&str]| {
+            git(&repo, args);
         };
-        let event = BackgroundEvent::ToolResult("id".to_string(), result);
-        assert!(matches!(event, BackgroundEvent::ToolResult(_, _)));
-    }
-
-    #[tokio::test]
-    async fn test_spawn_tool_execution_sets_channel() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // No channel initially
-        assert!(!state.has_tool_result_rx());
-
-        // Set up tool use and spawn
-        state.tool_loop_mut().start_streaming().unwrap();
-        state.handle_tool_use_start("toolu_789".to_string(), "bash".to_string(), 0);
-        state.handle_tool_use_input_delta(0, r#"{"command":"echo hi"}"#);
-        state.handle_tool_use_complete(0).unwrap();
-        state.handle_message_complete(StopReason::ToolUse).unwrap();
-        state.approve_all_tools().unwrap();
 
-        // Spawn should set up channel (requires tokio runtime)
-        let _handle = state.spawn_tool_execution();
-        assert!(state.has_tool_result_rx());
-        assert!(state.has_executing_tools());
-    }
-
-    #[test]
-    fn test_record_tool_result_updates_state() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
+        let mut state = AppState::new(repo.clone(), false, ParallelMode::Enabled);
+        state.set_session_id("original".to_string());
 
-        // Mark a tool as executing
-        state.mark_tool_executing("toolu_abc");
-        assert!(state.has_executing_tools());
+        // Uncommitted changes would be left behind, so they are refused
+        std::fs::write(repo.join("README.md"), "# Changed").unwrap();
+        let handler = SlashCommandHandler::new(state.working_dir.clone());
+        assert!(matches!(
+            handler.handle("/worktree new feature"),
+            CommandResult::Error(ref e) if e.contains("uncommitted changes")
+        ));
+        git(&["checkout", "README.md"]);
 
-        // Record result
-        let result = crate::types::ToolResultBlock {
-            tool_use_id: "toolu_abc".to_string(),
-            content: "Done".to_string(),
-            is_error: false,
+        let CommandResult::Action(action) = handler.handle("/worktree new feature") else {
+            panic!("expected a worktree switch");
         };
-        state.record_tool_result("toolu_abc", result);
+        let output = state.apply_command_action(action);
+        let worktree_dir = repo.join(".worktrees").join("feature");
+        assert!(
+            output.contains("Working in worktree 'feature'"),
+            "got: {output}"
+        );
+        assert_eq!(state.working_dir, worktree_dir);
+        assert_eq!(state.worktree_branch(), Some("wt/feature"));
+        assert_eq!(state.tool_executor.shell_state().cwd(), worktree_dir);
 
-        // Should no longer have executing tools
-        assert!(!state.has_executing_tools());
+        // Saved as a new session linked to the worktree
+        assert!(state.session_id().is_none());
+        let session = state.to_session();
+        assert_eq!(session.working_dir(), &worktree_dir);
+        let link = session.worktree_session().unwrap();
+        assert_eq!(link.worktree_name(), "feature");
+        assert!(!link.original_branch().is_empty());
+
+        // The branch now exists, so a second worktree of that name is refused
+        let handler = SlashCommandHandler::new(repo.clone());
+        git(&["worktree", "remove", worktree_dir.to_str().unwrap()]);
+        assert!(matches!(
+            handler.handle("/worktree new feature"),
+            CommandResult::Error(ref e) if e.contains("branch already exists")
+        ));
     }
 
     #[test]
-    fn test_all_tools_complete() {
-        let mut state = AppState::new(PathBuf::from("/test"), false, ParallelMode::Enabled);
-
-        // No executing tools initially
-        assert!(state.all_tools_complete());
+    fn test_auto_commit_worktree_after_editing_turn() {
+        use crate::app::commands::{CommandResult, SlashCommandHandler};
+
+        let (_temp, repo) = git_repo_with_commit();
+        let mut state = AppState::new(repo.clone(), false, ParallelMode::Enabled);
+
+        // Not linked to a worktree: nothing is committed
+        state.set_auto_commit_worktree(true);
+        std::fs::write(repo.join("notes.txt"), "main").unwrap();
+        state.turn_edited_files = vec![repo.join("notes.txt")];
+        state.auto_commit_worktree();
+        assert_eq!(git(&repo, &["rev-list", "--count", "HEAD"]).trim(), "1");
+        std::fs::remove_file(repo.join("notes.txt")).unwrap();
+
+        let handler = SlashCommandHandler::new(repo.clone());
+        let CommandResult::Action(action) = handler.handle("/worktree new greet") else {
+            panic!("expected a worktree switch");
+        };
+        state.apply_command_action(action);
+        let worktree_dir = state.working_dir.clone();
 
-        // Mark a tool as executing
-        state.mark_tool_executing("toolu_xyz");
-        assert!(!state.all_tools_complete());
+        // A turn without edits commits nothing
+        std::fs::write(worktree_dir.join("hello.txt"), "hello").unwrap();
+        state.auto_commit_worktree();
+        assert!(state.worktree_session().unwrap().commits().is_empty());
 
-        // Record result
-        let result = crate::types::ToolResultBlock {
-            tool_use_id: "toolu_xyz".to_string(),
-            content: "Complete".to_string(),
-            is_error: false,
-        };
-        state.record_tool_result("toolu_xyz", result);
-        assert!(state.all_tools_complete());
+        // Only the files the turn edited are committed
+        std::fs::write(worktree_dir.join("scratch.txt"), "untouched").unwrap();
+        state.turn_edited_files = vec![worktree_dir.join("hello.txt")];
+        state
+            .timeline
+            .push_assistant_message("**Added** a greeting file.\n\nIt says hello.");
+        state.auto_commit_worktree();
+
+        let commits = state.worktree_session().unwrap().commits();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].message, "Added a greeting file.");
+        assert_eq!(
+            git(&worktree_dir, &["log", "-1", "--format=%h %s"]).trim(),
+            format!("{} Added a greeting file.", commits[0].hash)
+        );
+        assert_eq!(
+            git(&worktree_dir, &["show", "--name-only", "--format=", "HEAD"]).trim(),
+            "hello.txt"
+        );
+        // The main checkout is untouched
+        assert_eq!(git(&repo, &["rev-list", "--count", "HEAD"]).trim(), "1");
+        assert!(state
+            .to_session()
+            .worktree_session()
+            .is_some_and(|link| link.commits().len() == 1));
     }
 }
@@ -243,6 +243,17 @@ impl ToolLoop {
         self.iteration
     }
 
+    /// Returns the maximum number of loop iterations.
+    #[must_use]
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Sets the maximum number of loop iterations.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
     // =========================================================================
     // State Transitions
     // =========================================================================
@@ -343,6 +354,7 @@ impl ToolLoop {
     /// - `ToolUse` -> PendingApproval (if there are tool calls)
     /// - `EndTurn` -> Idle
     /// - `MaxTokens` -> Idle (with truncation warning)
+    /// - `Refusal` -> Idle
     pub fn message_complete(&mut self, stop_reason: StopReason) -> Result<(), ToolLoopError> {
         if !matches!(self.state, ToolLoopState::Streaming) {
             return Ok(());
@@ -370,6 +382,10 @@ impl ToolLoop {
                 self.pending_calls.clear();
                 // Note: Could emit a warning about truncation here
             }
+            StopReason::Refusal => {
+                self.state = ToolLoopState::Idle;
+                self.pending_calls.clear();
+            }
         }
 
         Ok(())
@@ -406,9 +422,13 @@ impl ToolLoop {
 
     /// Denies all pending tool calls.
     ///
-    /// Transitions from PendingApproval back to Idle.
+    /// Transitions from PendingApproval (or Executing, when a call asked for
+    /// permission mid-run) back to Idle.
     pub fn deny_all(&mut self) -> Result<(), ToolLoopError> {
-        if !matches!(self.state, ToolLoopState::PendingApproval) {
+        if !matches!(
+            self.state,
+            ToolLoopState::PendingApproval | ToolLoopState::Executing
+        ) {
             return Err(ToolLoopError::InvalidStateTransition {
                 from: format!("{:?}", self.state),
                 to: "Idle".to_string(),
@@ -420,6 +440,41 @@ impl ToolLoop {
         Ok(())
     }
 
+    /// Denies all pending tool calls, answering each with the user's reason.
+    ///
+    /// Unlike [`deny_all`](Self::deny_all), the calls are kept and given an
+    /// error result of `denied: <reason>`, so the conversation can continue
+    /// and the model learns why. Transitions from PendingApproval to
+    /// Executing, ready for [`finish_execution`](Self::finish_execution).
+    ///
+    /// While Executing (a call asked for permission mid-run), only calls
+    /// without a result are denied; the others keep their output.
+    pub fn deny_all_with_reason(&mut self, reason: &str) -> Result<(), ToolLoopError> {
+        if !matches!(
+            self.state,
+            ToolLoopState::PendingApproval | ToolLoopState::Executing
+        ) {
+            return Err(ToolLoopError::InvalidStateTransition {
+                from: format!("{:?}", self.state),
+                to: "Executing".to_string(),
+            });
+        }
+
+        for (tool_id, call) in &mut self.pending_calls {
+            if call.result.is_some() {
+                continue;
+            }
+            call.approve();
+            call.set_result(ToolResultBlock::error(
+                tool_id.clone(),
+                format!("denied: {reason}"),
+            ));
+        }
+
+        self.state = ToolLoopState::Executing;
+        Ok(())
+    }
+
     /// Records a tool execution result.
     pub fn set_tool_result(
         &mut self,
@@ -578,12 +633,7 @@ impl ToolLoop {
         match &self.state {
             ToolLoopState::Error(msg) => {
                 let error_msg = msg.clone();
-                self.state = ToolLoopState::Idle;
-                self.pending_calls.clear();
-                self.accumulators.clear();
-                self.text_content.clear();
-                self.stop_reason = None;
-                // Note: iteration count is preserved
+                self.discard_to_idle();
                 Ok(error_msg)
             }
             _ => Err(ToolLoopError::InvalidStateTransition {
@@ -593,6 +643,51 @@ impl ToolLoop {
         }
     }
 
+    /// Describes why the loop is wedged, or `None` if its state is consistent.
+    ///
+    /// A wedged loop is one no normal transition can move forward, such as
+    /// waiting for approval of tools that do not exist.
+    #[must_use]
+    pub fn inconsistency(&self) -> Option<String> {
+        match &self.state {
+            ToolLoopState::PendingApproval if self.pending_calls.is_empty() => {
+                Some("waiting for approval with no pending tools".to_string())
+            }
+            ToolLoopState::Executing if self.pending_calls.is_empty() => {
+                Some("executing with no tool calls".to_string())
+            }
+            ToolLoopState::Idle if !self.pending_calls.is_empty() => Some(format!(
+                "idle with {} leftover tool call(s)",
+                self.pending_calls.len()
+            )),
+            _ => None,
+        }
+    }
+
+    /// Resets a wedged loop to Idle, discarding partial tool data.
+    ///
+    /// Like `recover_from_error()`, this preserves the iteration count.
+    ///
+    /// # Returns
+    ///
+    /// Returns the detected inconsistency, or `None` if the loop was
+    /// consistent and left untouched.
+    pub fn recover_from_inconsistency(&mut self) -> Option<String> {
+        let reason = self.inconsistency()?;
+        self.discard_to_idle();
+        Some(reason)
+    }
+
+    /// Returns to Idle, dropping pending tools and streamed content but
+    /// keeping the iteration count.
+    fn discard_to_idle(&mut self) {
+        self.state = ToolLoopState::Idle;
+        self.pending_calls.clear();
+        self.accumulators.clear();
+        self.text_content.clear();
+        self.stop_reason = None;
+    }
+
     /// Retries tool execution by transitioning from PendingApproval back to
     /// allow re-approval.
     ///
@@ -892,6 +987,9 @@ pub fn tool_use_to_call(tool_use: &ToolUseBlock) -> crate::tools::ToolCall {
 
 /// Converts a `tools::ToolResult` to a `ToolResultBlock`.
 ///
+/// Result content reported to Claude for a cancelled or denied tool.
+pub const TOOL_CANCELLED_MESSAGE: &str = "Tool execution cancelled";
+
 /// Maps the executor result types to API content blocks:
 /// - `Success(output)` → `ToolResultBlock::success(id, output)`
 /// - `Error(msg)` → `ToolResultBlock::error(id, msg)`
@@ -907,10 +1005,7 @@ pub fn result_to_block(
     match result {
         ToolResult::Success(output) => Some(ToolResultBlock::success(tool_use_id, output)),
         ToolResult::Error(error) => Some(ToolResultBlock::error(tool_use_id, error)),
-        ToolResult::Cancelled => Some(ToolResultBlock::error(
-            tool_use_id,
-            "Tool execution cancelled",
-        )),
+        ToolResult::Cancelled => Some(ToolResultBlock::error(tool_use_id, TOOL_CANCELLED_MESSAGE)),
         ToolResult::NeedsPermission(_) => None, // Should be handled at a higher level
     }
 }
@@ -1056,8 +1151,8 @@ impl ToolLoop {
                     needs_permission.push(tool_id);
                 }
                 Err(ExecutionError::ExecutionFailed(msg)) => {
-                    // Record the error as a tool result
-                    let error_block = ToolResultBlock::error(&tool_id, msg);
+                    // Record the failure as a tool result, distinct from tool errors
+                    let error_block = ToolResultBlock::failure(&tool_id, msg);
                     if let Some(call) = self.pending_calls.get_mut(&tool_id) {
                         call.set_result(error_block);
                     }
@@ -1072,7 +1167,11 @@ impl ToolLoop {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::{ToolBackend, ToolCall};
     use serde_json::json;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
 
     #[test]
     fn test_tool_loop_initial_state() {
@@ -1151,6 +1250,61 @@ mod tests {
         assert!(loop_state.pending_calls().is_empty());
     }
 
+    #[test]
+    fn test_tool_loop_deny_with_reason_continues() {
+        let mut loop_state = ToolLoop::new();
+        loop_state.start_streaming().unwrap();
+        loop_state.start_tool_use(0, "id".to_string(), "bash".to_string());
+        loop_state.append_tool_input(0, "{}");
+        loop_state.complete_tool_use(0).unwrap();
+        loop_state.message_complete(StopReason::ToolUse).unwrap();
+
+        loop_state
+            .deny_all_with_reason("don't touch the migrations directory")
+            .unwrap();
+        assert_eq!(*loop_state.state(), ToolLoopState::Executing);
+
+        let data = loop_state.finish_execution().unwrap();
+        assert_eq!(data.assistant_content.len(), 1);
+        assert_eq!(data.tool_results.len(), 1);
+        let result = data.tool_results[0].as_tool_result().unwrap();
+        assert_eq!(result.tool_use_id, "id");
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "denied: don't touch the migrations directory"
+        );
+    }
+
+    #[test]
+    fn test_tool_loop_deny_with_reason_while_executing_keeps_results() {
+        let mut loop_state = ToolLoop::new();
+        loop_state.start_streaming().unwrap();
+        for (index, id) in ["ran", "paused"].into_iter().enumerate() {
+            loop_state.start_tool_use(index, id.to_string(), "bash".to_string());
+            loop_state.append_tool_input(index, "{}");
+            loop_state.complete_tool_use(index).unwrap();
+        }
+        loop_state.message_complete(StopReason::ToolUse).unwrap();
+        loop_state.approve_all().unwrap();
+        loop_state
+            .set_tool_result("ran", ToolResultBlock::success("ran", "ok"))
+            .unwrap();
+
+        // "paused" asked for permission and the user denied it
+        loop_state.deny_all_with_reason("not now").unwrap();
+
+        let data = loop_state.finish_execution().unwrap();
+        let results: HashMap<_, _> = data
+            .tool_results
+            .iter()
+            .filter_map(|block| block.as_tool_result())
+            .map(|result| (result.tool_use_id.as_str(), result.content.as_str()))
+            .collect();
+        assert_eq!(results["ran"], "ok");
+        assert_eq!(results["paused"], "denied: not now");
+    }
+
     #[test]
     fn test_tool_loop_multiple_tools() {
         let mut loop_state = ToolLoop::new();
@@ -1541,6 +1695,142 @@ mod tests {
         assert_eq!(error_count, 1);
     }
 
+    /// Backend returning scripted results keyed by tool name.
+    struct ScriptedBackend {
+        results: HashMap<&'static str, String>,
+        calls: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl ToolBackend for ScriptedBackend {
+        fn execute(
+            &self,
+            call: ToolCall,
+        ) -> Pin<Box<dyn Future<Output = anyhow::Result<crate::tools::ToolResult>> + Send + '_>>
+        {
+            self.calls.lock().unwrap().push(call.name.clone());
+            let result = match self.results.get(call.name.as_str()) {
+                Some(output) => crate::tools::ToolResult::Success(output.clone()),
+                None => crate::tools::ToolResult::Error(format!("unscripted tool: {}", call.name)),
+            };
+            Box::pin(async move { Ok(result) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_pending_with_mock_backend() {
+        use crate::hooks::HookManager;
+        use crate::tools::HookedToolExecutor;
+
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let backend = ScriptedBackend {
+            results: HashMap::from([("bash", "scripted output".to_string())]),
+            calls: Arc::clone(&calls),
+        };
+
+        let hooks = HookManager::new("test-session".to_string());
+        let executor = HookedToolExecutor::new(std::path::PathBuf::from("/nonexistent"), hooks)
+            .with_backend(Box::new(backend));
+
+        let mut loop_state = ToolLoop::new();
+        loop_state.start_streaming().unwrap();
+        loop_state.start_tool_use(0, "toolu_1".to_string(), "bash".to_string());
+        loop_state.append_tool_input(0, r#"{"command":"rm -rf /"}"#);
+        loop_state.complete_tool_use(0).unwrap();
+        loop_state.start_tool_use(1, "toolu_2".to_string(), "read_file".to_string());
+        loop_state.append_tool_input(1, r#"{"path":"missing.txt"}"#);
+        loop_state.complete_tool_use(1).unwrap();
+        loop_state.message_complete(StopReason::ToolUse).unwrap();
+        loop_state.approve_all().unwrap();
+
+        let needs_permission = loop_state.execute_pending(&executor).await.unwrap();
+        assert!(needs_permission.is_empty());
+        assert!(loop_state.all_tools_executed());
+
+        let results = loop_state.collect_tool_results();
+        let bash = results
+            .iter()
+            .filter_map(|b| b.as_tool_result())
+            .find(|r| r.tool_use_id == "toolu_1")
+            .unwrap();
+        assert_eq!(bash.content, "scripted output");
+        assert!(!bash.is_error);
+
+        let read = results
+            .iter()
+            .filter_map(|b| b.as_tool_result())
+            .find(|r| r.tool_use_id == "toolu_2")
+            .unwrap();
+        assert!(read.is_error);
+        assert!(read.content.contains("unscripted tool: read_file"));
+
+        let mut calls = calls.lock().unwrap().clone();
+        calls.sort();
+        assert_eq!(calls, vec!["bash", "read_file"]);
+    }
+
+    /// Backend that fails to run any tool.
+    struct FailingBackend;
+
+    impl ToolBackend for FailingBackend {
+        fn execute(
+            &self,
+            _call: ToolCall,
+        ) -> Pin<Box<dyn Future<Output = anyhow::Result<crate::tools::ToolResult>> + Send + '_>>
+        {
+            Box::pin(async { Err(anyhow::anyhow!("backend unavailable")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nonzero_exit_is_tool_error_not_failure() {
+        use crate::hooks::HookManager;
+        use crate::tools::HookedToolExecutor;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let hooks = HookManager::new("test-session".to_string());
+        let executor = HookedToolExecutor::new(temp_dir.path().to_path_buf(), hooks);
+
+        let tool_use = ToolUseBlock::new("toolu_1", "bash", json!({"command": "exit 3"}));
+        let block = execute_tool(&tool_use, &executor).await.unwrap();
+
+        assert!(block.is_error);
+        assert!(!block.is_failure());
+        assert!(block.content.contains("Exit code 3"));
+    }
+
+    #[tokio::test]
+    async fn test_execution_failure_is_distinct_from_tool_error() {
+        use crate::hooks::HookManager;
+        use crate::tools::HookedToolExecutor;
+
+        let hooks = HookManager::new("test-session".to_string());
+        let executor = HookedToolExecutor::new(std::path::PathBuf::from("/nonexistent"), hooks)
+            .with_backend(Box::new(FailingBackend));
+
+        let tool_use = ToolUseBlock::new("toolu_1", "bash", json!({"command": "true"}));
+        assert!(matches!(
+            execute_tool(&tool_use, &executor).await,
+            Err(ExecutionError::ExecutionFailed(msg)) if msg.contains("backend unavailable")
+        ));
+
+        let mut loop_state = ToolLoop::new();
+        loop_state.start_streaming().unwrap();
+        loop_state.add_tool_use(tool_use);
+        loop_state.message_complete(StopReason::ToolUse).unwrap();
+        loop_state.approve_all().unwrap();
+        loop_state.execute_pending(&executor).await.unwrap();
+
+        let results = loop_state.collect_tool_results();
+        let result = results[0].as_tool_result().unwrap();
+        assert!(result.is_error);
+        assert!(result.is_failure());
+        assert!(result
+            .content
+            .starts_with(crate::types::content::TOOL_FAILURE_PREFIX));
+        assert!(result.content.contains("backend unavailable"));
+    }
+
     #[tokio::test]
     async fn test_execute_pending_wrong_state() {
         use crate::hooks::HookManager;
@@ -1908,6 +2198,50 @@ mod tests {
         assert_eq!(*loop_state.state(), ToolLoopState::Executing);
     }
 
+    #[test]
+    fn test_inconsistent_pending_approval_is_detected_and_reset() {
+        let mut loop_state = ToolLoop::new();
+        loop_state.start_streaming().unwrap();
+        loop_state.append_text("partial");
+        loop_state.force_state(ToolLoopState::PendingApproval);
+
+        assert!(loop_state.inconsistency().is_some());
+        let reason = loop_state.recover_from_inconsistency().unwrap();
+        assert!(reason.contains("no pending tools"));
+
+        assert_eq!(*loop_state.state(), ToolLoopState::Idle);
+        assert!(loop_state.text_content().is_empty());
+        assert!(loop_state.inconsistency().is_none());
+        // The reset loop accepts a new turn
+        assert!(loop_state.start_streaming().is_ok());
+    }
+
+    #[test]
+    fn test_inconsistent_executing_and_idle_are_detected() {
+        let mut loop_state = ToolLoop::new();
+        loop_state.force_state(ToolLoopState::Executing);
+        assert!(loop_state.inconsistency().is_some());
+
+        let mut loop_state = ToolLoop::new();
+        loop_state.add_tool_use(ToolUseBlock::new("t1", "bash", json!({})));
+        assert!(loop_state.recover_from_inconsistency().is_some());
+        assert!(loop_state.pending_calls().is_empty());
+    }
+
+    #[test]
+    fn test_consistent_loop_is_not_reset() {
+        let mut loop_state = ToolLoop::new();
+        loop_state.start_streaming().unwrap();
+        loop_state.start_tool_use(0, "id".to_string(), "bash".to_string());
+        loop_state.append_tool_input(0, "{}");
+        loop_state.complete_tool_use(0).unwrap();
+        loop_state.message_complete(StopReason::ToolUse).unwrap();
+
+        assert!(loop_state.recover_from_inconsistency().is_none());
+        assert_eq!(*loop_state.state(), ToolLoopState::PendingApproval);
+        assert_eq!(loop_state.pending_calls().len(), 1);
+    }
+
     #[test]
     fn test_snapshot_captures_state() {
         let mut loop_state = ToolLoop::new();
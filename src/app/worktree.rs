@@ -0,0 +1,255 @@
+//! Wrapping up a worktree when the session ends.
+//!
+//! A session moved into a worktree with `/worktree new` or `/worktree switch`
+//! is offered, once the terminal is restored, to merge the worktree branch
+//! back into the branch it came from, to remove the worktree, or to keep it.
+//!
+//! With `auto_commit_worktree`, changes are also committed after each turn;
+//! the commit message comes from [`commit_message_for_turn`].
+
+use anyhow::{bail, Context, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crate::session::WorktreeSession;
+use crate::worktree::WorktreeManager;
+
+/// Longest commit subject taken from an assistant reply, in characters.
+const COMMIT_SUBJECT_MAX_CHARS: usize = 72;
+
+/// Builds a commit message for the changes made in one assistant turn.
+///
+/// Uses the first line of the reply with Markdown markers removed, cut to
+/// [`COMMIT_SUBJECT_MAX_CHARS`].
+#[must_use]
+pub fn commit_message_for_turn(reply: &str) -> String {
+    let subject = reply
+        .lines()
+        .map(|line| {
+            line.replace("**", "")
+                .replace('`', "")
+                .trim()
+                .trim_start_matches(['#', '*', '-', '>', ' '])
+                .trim_end_matches([':', ' '])
+                .to_string()
+        })
+        .find(|line| !line.is_empty())
+        .unwrap_or_else(|| "Update files".to_string());
+
+    if subject.chars().count() <= COMMIT_SUBJECT_MAX_CHARS {
+        return subject;
+    }
+    let cut: String = subject.chars().take(COMMIT_SUBJECT_MAX_CHARS - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+/// What to do with a worktree when the session ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapUp {
+    /// Merge the worktree branch into the original branch, then remove the
+    /// worktree and the merged branch.
+    Merge,
+    /// Remove the worktree and keep its branch.
+    Remove,
+    /// Leave everything as it is.
+    Keep,
+}
+
+impl WrapUp {
+    /// Parses an answer to the exit prompt; anything unrecognised keeps.
+    #[must_use]
+    pub fn from_answer(answer: &str) -> Self {
+        match answer.trim().to_lowercase().as_str() {
+            "m" | "merge" => Self::Merge,
+            "r" | "remove" => Self::Remove,
+            _ => Self::Keep,
+        }
+    }
+}
+
+/// Asks on stdin what to do with the worktree the session ended in.
+///
+/// Does nothing when stdin is not a terminal. Failures are printed rather
+/// than returned, as the session is already over.
+pub fn offer_wrap_up(working_dir: &Path, worktree: &WorktreeSession) {
+    if !io::stdin().is_terminal() {
+        return;
+    }
+
+    let name = worktree.worktree_name();
+    let original = worktree.original_branch();
+    println!("\nThis session worked in worktree '{}'.", name);
+
+    let clean = WorktreeManager::new(working_dir)
+        .and_then(|manager| manager.status(working_dir))
+        .map(|status| status.is_clean())
+        .unwrap_or(false);
+    if !clean {
+        println!(
+            "It has uncommitted changes, so it was kept at {}.",
+            working_dir.display()
+        );
+        return;
+    }
+
+    print!(
+        "[m]erge it into {} and remove it, [r]emove it without merging, or [k]eep it? [k] ",
+        original
+    );
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return;
+    }
+
+    match wrap_up(working_dir, worktree, WrapUp::from_answer(&answer)) {
+        Ok(message) => println!("{}", message),
+        Err(e) => println!("{:#}. The worktree was kept.", e),
+    }
+}
+
+/// Merges, removes or keeps the worktree checked out at `working_dir`.
+///
+/// Returns a message describing what was done.
+///
+/// # Errors
+///
+/// Returns an error if the main worktree is not on the original branch or a
+/// git command fails. A conflicting merge is aborted.
+pub fn wrap_up(working_dir: &Path, worktree: &WorktreeSession, choice: WrapUp) -> Result<String> {
+    let name = worktree.worktree_name();
+    if choice == WrapUp::Keep {
+        return Ok(format!(
+            "Kept worktree '{}' at {}.",
+            name,
+            working_dir.display()
+        ));
+    }
+
+    let manager = WorktreeManager::new(working_dir)?;
+    let branch = manager.current_branch(working_dir)?;
+    let main = manager
+        .list()?
+        .into_iter()
+        .find(|w| w.is_main)
+        .context("Main worktree not found")?;
+
+    if choice == WrapUp::Merge {
+        let original = worktree.original_branch();
+        if main.branch != original {
+            bail!(
+                "The main worktree is on {} rather than {}; merge {} by hand",
+                main.branch,
+                original,
+                branch
+            );
+        }
+        manager
+            .merge(&branch, &main.path)
+            .with_context(|| format!("Failed to merge {} into {}", branch, original))?;
+    }
+
+    // Listed and removed through the main worktree, as this one goes away
+    let main_manager = WorktreeManager::new(&main.path)?;
+    main_manager.remove(name)?;
+
+    if choice == WrapUp::Merge {
+        main_manager.delete_branch(&branch)?;
+        Ok(format!(
+            "Merged {} into {} and removed worktree '{}'.",
+            branch,
+            worktree.original_branch(),
+            name
+        ))
+    } else {
+        Ok(format!(
+            "Removed worktree '{}'. Branch {} was kept.",
+            name, branch
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    fn repo_with_worktree(name: &str) -> (tempfile::TempDir, WorktreeManager, String, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        git(root, &["config", "user.email", "test@test.com"]);
+        git(root, &["config", "user.name", "Test User"]);
+        std::fs::write(root.join("README.md"), "# Test").unwrap();
+        git(root, &["add", "README.md"]);
+        git(root, &["commit", "-q", "-m", "Initial commit"]);
+
+        let manager = WorktreeManager::new(root.to_path_buf()).unwrap();
+        let original = manager.current_branch(root).unwrap();
+        let info = manager.create(name).unwrap();
+        std::fs::write(info.path.join("feature.txt"), "done").unwrap();
+        git(&info.path, &["add", "feature.txt"]);
+        git(&info.path, &["commit", "-q", "-m", "Add feature"]);
+        let path = info.path.to_string_lossy().to_string();
+        (dir, manager, original, path)
+    }
+
+    #[test]
+    fn test_commit_message_for_turn() {
+        assert_eq!(
+            commit_message_for_turn("\n## Fixed the parser:\n\nDetails follow."),
+            "Fixed the parser"
+        );
+        assert_eq!(
+            commit_message_for_turn("**Added retries** to the client"),
+            "Added retries to the client"
+        );
+        assert_eq!(commit_message_for_turn("  \n"), "Update files");
+
+        let long = commit_message_for_turn(&"word ".repeat(40));
+        assert!(long.ends_with("..."));
+        assert!(long.chars().count() <= COMMIT_SUBJECT_MAX_CHARS);
+    }
+
+    #[test]
+    fn test_from_answer_defaults_to_keep() {
+        assert_eq!(WrapUp::from_answer("m\n"), WrapUp::Merge);
+        assert_eq!(WrapUp::from_answer("Remove"), WrapUp::Remove);
+        assert_eq!(WrapUp::from_answer(""), WrapUp::Keep);
+        assert_eq!(WrapUp::from_answer("x"), WrapUp::Keep);
+    }
+
+    #[test]
+    fn test_merge_brings_commits_back_and_removes_worktree() {
+        let (dir, manager, original, path) = repo_with_worktree("feature");
+        let session = WorktreeSession::new("feature", original);
+
+        let message = wrap_up(Path::new(&path), &session, WrapUp::Merge).unwrap();
+
+        assert!(message.contains("Merged wt/feature"), "got: {message}");
+        assert!(dir.path().join("feature.txt").exists());
+        assert!(!Path::new(&path).exists());
+        assert!(!manager.branch_exists("wt/feature").unwrap());
+    }
+
+    #[test]
+    fn test_remove_keeps_the_branch() {
+        let (dir, manager, original, path) = repo_with_worktree("spike");
+        let session = WorktreeSession::new("spike", original);
+
+        wrap_up(Path::new(&path), &session, WrapUp::Remove).unwrap();
+
+        assert!(!dir.path().join("feature.txt").exists());
+        assert!(!Path::new(&path).exists());
+        assert!(manager.branch_exists("wt/spike").unwrap());
+    }
+}
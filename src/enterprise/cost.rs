@@ -110,6 +110,20 @@ fn default_model_pricing() -> HashMap<&'static str, ModelPricing> {
     pricing.insert("claude-3-5-sonnet-20240620", ModelPricing::new(3.0, 15.0));
     pricing.insert("claude-3-5-sonnet", ModelPricing::new(3.0, 15.0));
 
+    // Claude 3.5 Haiku: $0.80/1M input, $4/1M output
+    pricing.insert("claude-3-5-haiku-20241022", ModelPricing::new(0.8, 4.0));
+    pricing.insert("claude-3-5-haiku", ModelPricing::new(0.8, 4.0));
+
+    // Claude 3.5 Sonnet (October 2024), 3.7 Sonnet and Sonnet 4: $3/1M input, $15/1M output
+    pricing.insert("claude-3-5-sonnet-20241022", ModelPricing::new(3.0, 15.0));
+    pricing.insert("claude-3-7-sonnet-20250219", ModelPricing::new(3.0, 15.0));
+    pricing.insert("claude-sonnet-4-20250514", ModelPricing::new(3.0, 15.0));
+    pricing.insert("claude-sonnet-4", ModelPricing::new(3.0, 15.0));
+
+    // Claude Opus 4: $15/1M input, $75/1M output
+    pricing.insert("claude-opus-4-20250514", ModelPricing::new(15.0, 75.0));
+    pricing.insert("claude-opus-4", ModelPricing::new(15.0, 75.0));
+
     // Claude 3 Haiku: $0.25/1M input, $1.25/1M output
     pricing.insert("claude-3-haiku-20240307", ModelPricing::new(0.25, 1.25));
     pricing.insert("claude-3-haiku", ModelPricing::new(0.25, 1.25));
@@ -502,6 +516,18 @@ mod tests {
         assert!((record.cost - 1.5).abs() < 0.01);
     }
 
+    #[test]
+    fn test_usage_record_prices_sonnet_4() {
+        let record = UsageRecord::new(
+            "claude-sonnet-4-20250514",
+            1_000_000,
+            1_000_000,
+            Duration::from_secs(1),
+        );
+        // Sonnet 4: $3/1M input + $15/1M output = $18
+        assert!((record.cost - 18.0).abs() < 0.01);
+    }
+
     #[test]
     fn test_budget_limit_creation() {
         let limit = BudgetLimit::new(BudgetPeriod::Daily, 100.0);
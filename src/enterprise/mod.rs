@@ -3,6 +3,10 @@
 //! This module provides enterprise-grade functionality including:
 //! - Audit logging for compliance and security
 //! - Cost controls for budget management
+//! - Organization policies that users cannot relax
+//! - A tamper-evident log of tool executions
 
 pub mod audit;
 pub mod cost;
+pub mod policy;
+pub mod tool_audit;
@@ -0,0 +1,255 @@
+//! Organization-wide tool execution policy.
+//!
+//! Administrators can install a policy file that every Patina user on the
+//! machine runs under. Its restrictions are merged into the user's
+//! [`ToolExecutionPolicy`] so that they can only be added to, never relaxed:
+//!
+//! - `blocked_patterns` are added to the dangerous command patterns
+//! - `protected_paths` are added to the protected paths
+//! - `allowlist_mode = true` makes every command also match one of
+//!   `allowed_commands`, on top of the user's own allowlist, so it can only
+//!   narrow what the user allows
+//!
+//! Command restrictions cover bash calls, plugin tool commands, hook
+//! commands and stdio MCP server launches alike.
+//!
+//! # Example
+//!
+//! ```toml
+//! # /etc/patina/policy.toml
+//! blocked_patterns = ['\bterraform\s+destroy\b', '\bkubectl\s+delete\b']
+//! protected_paths = ["/srv/production"]
+//!
+//! allowlist_mode = true
+//! allowed_commands = ['^git\s', '^cargo\s']
+//! ```
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::tools::ToolExecutionPolicy;
+
+/// Where the organization policy is installed.
+#[cfg(unix)]
+pub const ORG_POLICY_PATH: &str = "/etc/patina/policy.toml";
+
+/// Where the organization policy is installed.
+#[cfg(windows)]
+pub const ORG_POLICY_PATH: &str = r"C:\ProgramData\patina\policy.toml";
+
+/// The policy file as written by an administrator.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OrgPolicyFile {
+    blocked_patterns: Vec<String>,
+    protected_paths: Vec<PathBuf>,
+    allowlist_mode: bool,
+    allowed_commands: Vec<String>,
+}
+
+/// Restrictions an organization enforces on every user.
+#[derive(Debug, Default)]
+pub struct OrgPolicy {
+    /// Commands that are always blocked.
+    pub blocked_patterns: Vec<Regex>,
+    /// Paths that tools may never write to.
+    pub protected_paths: Vec<PathBuf>,
+    /// Whether only `allowed_commands` may run.
+    pub allowlist_mode: bool,
+    /// Commands allowed when `allowlist_mode` is on.
+    pub allowed_commands: Vec<Regex>,
+}
+
+impl OrgPolicy {
+    /// Loads the organization policy from `path`.
+    ///
+    /// Returns `None` if no policy is installed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid policy.
+    /// Callers should refuse to run rather than ignore a broken policy.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read org policy {}", path.display()))?;
+        let policy = Self::parse(&content)
+            .with_context(|| format!("Invalid org policy {}", path.display()))?;
+        Ok(Some(policy))
+    }
+
+    /// Parses a policy from TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML is invalid, has unknown keys, or a
+    /// pattern is not a valid regular expression.
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: OrgPolicyFile = toml::from_str(content)?;
+        let compile = |patterns: &[String]| -> Result<Vec<Regex>> {
+            patterns
+                .iter()
+                .map(|p| Regex::new(p).with_context(|| format!("Invalid pattern '{}'", p)))
+                .collect()
+        };
+        Ok(Self {
+            blocked_patterns: compile(&file.blocked_patterns)?,
+            protected_paths: file.protected_paths,
+            allowlist_mode: file.allowlist_mode,
+            allowed_commands: compile(&file.allowed_commands)?,
+        })
+    }
+
+    /// Merges these restrictions into a user policy.
+    ///
+    /// Blocked patterns and protected paths are unioned, and an org
+    /// allowlist gates commands in addition to the user's allowlist. Each
+    /// restriction the organization adds is logged.
+    pub fn apply(&self, user: &mut ToolExecutionPolicy) {
+        for pattern in &self.blocked_patterns {
+            if !user
+                .dangerous_patterns
+                .iter()
+                .any(|p| p.as_str() == pattern.as_str())
+            {
+                info!(pattern = %pattern, "Org policy blocks a command pattern");
+                user.dangerous_patterns.push(pattern.clone());
+            }
+        }
+
+        for path in &self.protected_paths {
+            if !user.protected_paths.contains(path) {
+                info!(path = %path.display(), "Org policy protects a path");
+                user.protected_paths.push(path.clone());
+            }
+        }
+
+        if self.allowlist_mode {
+            info!("Org policy restricts commands to its allowlist");
+            user.org_allowed_commands = Some(self.allowed_commands.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(policy: &ToolExecutionPolicy, command: &str) -> bool {
+        policy
+            .dangerous_patterns
+            .iter()
+            .any(|p| p.is_match(command))
+    }
+
+    #[test]
+    fn test_user_cannot_remove_org_blocked_pattern() {
+        let org = OrgPolicy::parse(r"blocked_patterns = ['\bterraform\s+destroy\b']").unwrap();
+
+        // A user policy that dropped every pattern, including the defaults
+        let mut user = ToolExecutionPolicy {
+            dangerous_patterns: vec![],
+            ..ToolExecutionPolicy::default()
+        };
+        org.apply(&mut user);
+        assert!(blocks(&user, "terraform destroy -auto-approve"));
+
+        // Applying twice does not duplicate the pattern
+        org.apply(&mut user);
+        assert_eq!(user.dangerous_patterns.len(), 1);
+    }
+
+    #[test]
+    fn test_org_policy_adds_to_defaults() {
+        let org = OrgPolicy::parse(
+            r#"
+            blocked_patterns = ['\bkubectl\s+delete\b']
+            protected_paths = ["/srv/production"]
+            "#,
+        )
+        .unwrap();
+        let mut user = ToolExecutionPolicy::default();
+        let defaults = user.dangerous_patterns.len();
+
+        org.apply(&mut user);
+
+        assert_eq!(user.dangerous_patterns.len(), defaults + 1);
+        assert!(blocks(&user, "kubectl delete pod web"));
+        assert!(user
+            .protected_paths
+            .contains(&PathBuf::from("/srv/production")));
+        assert!(!user.allowlist_mode);
+    }
+
+    #[test]
+    fn test_org_allowlist_gates_commands() {
+        let org =
+            OrgPolicy::parse("allowlist_mode = true\nallowed_commands = ['^git\\s']").unwrap();
+
+        // A user without an allowlist gets the org's
+        let mut user = ToolExecutionPolicy::default();
+        org.apply(&mut user);
+        assert!(user.check_command("git status").is_ok());
+        assert!(user.check_command("ls -la").is_err());
+
+        // A broader user allowlist is narrowed to the org's
+        let mut user = ToolExecutionPolicy {
+            allowlist_mode: true,
+            allowed_commands: vec![Regex::new(".*").unwrap()],
+            ..ToolExecutionPolicy::default()
+        };
+        org.apply(&mut user);
+        assert!(user.check_command("git log").is_ok());
+        assert!(user.check_command("cargo build").is_err());
+
+        // Without allowlist mode the org leaves the user's allowlist alone
+        let org = OrgPolicy::parse("").unwrap();
+        let mut user = ToolExecutionPolicy {
+            allowlist_mode: true,
+            allowed_commands: vec![Regex::new("^ls$").unwrap()],
+            ..ToolExecutionPolicy::default()
+        };
+        org.apply(&mut user);
+        assert!(user.org_allowed_commands.is_none());
+        assert!(user.check_command("ls").is_ok());
+        assert!(user.check_command("git status").is_err());
+    }
+
+    #[test]
+    fn test_org_allowlist_cannot_widen_a_narrower_user_allowlist() {
+        let org =
+            OrgPolicy::parse("allowlist_mode = true\nallowed_commands = ['^git\\s', '^cargo\\s']")
+                .unwrap();
+        let mut user = ToolExecutionPolicy {
+            allowlist_mode: true,
+            allowed_commands: vec![Regex::new(r"^git\s+status$").unwrap()],
+            ..ToolExecutionPolicy::default()
+        };
+
+        org.apply(&mut user);
+
+        assert!(user.allowlist_mode);
+        assert!(user.check_command("git status").is_ok());
+        // Allowed by the org but not by the user
+        assert!(user.check_command("git log").is_err());
+        assert!(user.check_command("cargo build").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_and_invalid_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.toml");
+        assert!(OrgPolicy::load(&path).unwrap().is_none());
+
+        std::fs::write(&path, "blocked_patterns = ['(unclosed']").unwrap();
+        assert!(OrgPolicy::load(&path).is_err());
+
+        std::fs::write(&path, "allow_everything = true").unwrap();
+        assert!(OrgPolicy::load(&path).is_err());
+    }
+}
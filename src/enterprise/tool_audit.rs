@@ -0,0 +1,563 @@
+//! Tamper-evident log of tool executions.
+//!
+//! Every tool call the executor runs or refuses is appended to a JSON Lines
+//! file as a [`ToolAuditRecord`]. Each record carries the hash of the record before it
+//! and a keyed SHA-256 hash over its own fields, the same construction used
+//! for session checksums, so editing, inserting or removing a record breaks
+//! the chain from that point on. [`verify_log`] reports where.
+//!
+//! When the file would grow past `max_file_bytes` it is renamed to
+//! `<path>.1`, older files shift to `.2`, `.3` and so on up to `max_files`,
+//! and a new file is started. The chain continues across files.
+//!
+//! Several Patina processes may share one log. Each append and rotation
+//! happens while holding `<path>.lock`, and the hash of the last record is
+//! re-read under the lock, so concurrent writers extend a single chain.
+//! Waiting for the lock and syncing the file block, so async callers should
+//! write records from a blocking thread.
+//!
+//! # Limitations
+//!
+//! The hash key is a constant built into Patina and the latest hash is not
+//! anchored anywhere outside the log. The chain therefore catches edits to
+//! individual records, but not records removed from the end of the log, nor
+//! a chain rewritten in full by someone who recomputes every hash. Ship the
+//! log to append-only storage if those must be detected.
+//!
+//! # Example
+//!
+//! ```toml
+//! # settings.toml
+//! [audit]
+//! enabled = true
+//! path = "/var/log/patina/tools.jsonl"
+//! max_file_bytes = 10485760
+//! max_files = 5
+//! ```
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::tools::ToolResult;
+
+/// Key mixed into every record hash.
+const CHAIN_KEY: &[u8] = b"patina-tool-audit-v1";
+
+/// `prev_hash` of the first record ever written.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Default size at which the log is rotated (10 MiB).
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated files kept.
+pub const DEFAULT_MAX_FILES: usize = 5;
+
+/// How long a writer waits for another to release the log.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Age past which a lock is taken to be left over from a crashed writer.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// Bytes read from the end of a file when looking for its last record.
+const TAIL_CHUNK_BYTES: u64 = 8 * 1024;
+
+/// Where and how the tool audit log is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolAuditConfig {
+    /// File records are appended to.
+    pub path: PathBuf,
+    /// Size in bytes past which the file is rotated.
+    pub max_file_bytes: u64,
+    /// Number of rotated files kept; older ones are deleted.
+    pub max_files: usize,
+}
+
+impl ToolAuditConfig {
+    /// Creates a configuration with the default rotation limits.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+/// How a tool execution ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolAuditStatus {
+    /// The tool succeeded.
+    Success,
+    /// The tool reported an error.
+    Error,
+    /// The tool was cancelled.
+    Cancelled,
+    /// A permission rule, the permission mode or an allowlist refused the call.
+    Denied,
+    /// The user declined the permission prompt.
+    Rejected,
+    /// A `PreToolUse` hook blocked the call.
+    Blocked,
+}
+
+impl ToolAuditStatus {
+    /// Returns the status of an executed tool's result.
+    ///
+    /// Returns `None` for results that did not execute the tool.
+    #[must_use]
+    pub fn of(result: &ToolResult) -> Option<Self> {
+        match result {
+            ToolResult::Success(_) => Some(Self::Success),
+            ToolResult::Error(_) => Some(Self::Error),
+            ToolResult::Cancelled => Some(Self::Cancelled),
+            ToolResult::NeedsPermission(_) => None,
+        }
+    }
+}
+
+/// One line of the tool audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolAuditRecord {
+    /// When the tool finished, in RFC 3339.
+    pub timestamp: String,
+    /// Session the tool ran in.
+    pub session_id: String,
+    /// Tool name.
+    pub tool: String,
+    /// Tool input as sent by the model.
+    pub input: Value,
+    /// How the execution ended.
+    pub status: ToolAuditStatus,
+    /// Hash of the previous record.
+    pub prev_hash: String,
+    /// Hash of this record.
+    pub hash: String,
+}
+
+impl ToolAuditRecord {
+    /// Computes the hash of this record's fields other than `hash`.
+    #[must_use]
+    pub fn compute_hash(&self) -> String {
+        let body = json!({
+            "timestamp": self.timestamp,
+            "session_id": self.session_id,
+            "tool": self.tool,
+            "input": self.input,
+            "status": self.status,
+            "prev_hash": self.prev_hash,
+        });
+        let mut hasher = Sha256::new();
+        hasher.update(CHAIN_KEY);
+        hasher.update(body.to_string().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Appends hash-chained tool execution records to a file.
+#[derive(Debug)]
+pub struct ToolAuditLog {
+    config: ToolAuditConfig,
+}
+
+impl ToolAuditLog {
+    /// Opens the log, continuing the chain from its last record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log directory cannot be created or the last
+    /// record cannot be read.
+    pub fn open(config: ToolAuditConfig) -> Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create audit log directory {}", parent.display())
+            })?;
+        }
+        chain_head(&config.path)?;
+        Ok(Self { config })
+    }
+
+    /// Returns the file records are appended to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.config.path
+    }
+
+    /// Appends a record for one tool execution.
+    ///
+    /// Holds the log's lock while reading the previous hash, rotating and
+    /// appending, so other processes writing the same log cannot fork the
+    /// chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock cannot be taken, the record cannot be
+    /// written or the log cannot be rotated.
+    pub fn record(
+        &mut self,
+        session_id: &str,
+        tool: &str,
+        input: &Value,
+        status: ToolAuditStatus,
+    ) -> Result<ToolAuditRecord> {
+        let _lock = LogLock::acquire(&self.config.path)?;
+
+        let mut record = ToolAuditRecord {
+            timestamp: humantime::format_rfc3339_millis(SystemTime::now()).to_string(),
+            session_id: session_id.to_string(),
+            tool: tool.to_string(),
+            input: input.clone(),
+            status,
+            prev_hash: chain_head(&self.config.path)?,
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash();
+
+        let mut line = serde_json::to_string(&record).context("Failed to serialize record")?;
+        line.push('\n');
+        let size = fs::metadata(&self.config.path).map_or(0, |m| m.len());
+        if size > 0 && size + line.len() as u64 > self.config.max_file_bytes {
+            self.rotate()?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)
+            .with_context(|| format!("Failed to open {}", self.config.path.display()))?;
+        file.write_all(line.as_bytes())
+            .context("Failed to write audit record")?;
+        Ok(record)
+    }
+
+    /// Moves the current file to `.1`, shifting older files up.
+    ///
+    /// Must be called with the log's lock held.
+    fn rotate(&self) -> Result<()> {
+        let path = &self.config.path;
+        if self.config.max_files == 0 {
+            fs::remove_file(path).context("Failed to remove full audit log")?;
+        } else {
+            let _ = fs::remove_file(rotated_path(path, self.config.max_files));
+            for n in (1..self.config.max_files).rev() {
+                let from = rotated_path(path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(path, n + 1))
+                        .with_context(|| format!("Failed to rotate {}", from.display()))?;
+                }
+            }
+            fs::rename(path, rotated_path(path, 1))
+                .with_context(|| format!("Failed to rotate {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Exclusive lock on a log, held while `<path>.lock` exists.
+///
+/// Creating the lock file fails while another writer holds it, on every
+/// platform. A lock older than [`STALE_LOCK_AGE`] is assumed to be left
+/// over from a writer that crashed and is removed.
+struct LogLock {
+    path: PathBuf,
+}
+
+impl LogLock {
+    /// Waits up to [`LOCK_TIMEOUT`] to take the lock of the log at `log`.
+    fn acquire(log: &Path) -> Result<Self> {
+        let mut name = log.as_os_str().to_owned();
+        name.push(".lock");
+        let path = PathBuf::from(name);
+
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK_AGE);
+                    if stale {
+                        tracing::warn!("Removing stale audit log lock {}", path.display());
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        bail!("Timed out waiting for audit log lock {}", path.display());
+                    }
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for LogLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Returns the hash the next record of the log at `path` must chain from.
+///
+/// A rotation interrupted before the new file was written leaves only `.1`,
+/// so its last record is used when the current file has none.
+fn chain_head(path: &Path) -> Result<String> {
+    let last = match last_record(path)? {
+        Some(record) => Some(record),
+        None => last_record(&rotated_path(path, 1))?,
+    };
+    Ok(last.map_or_else(|| GENESIS_HASH.to_string(), |record| record.hash))
+}
+
+/// Returns the path of the `n`th rotated file.
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Reads the last record of a log file, if it has one.
+///
+/// Only the end of the file is read, growing the window until it holds the
+/// whole last line.
+fn last_record(path: &Path) -> Result<Option<ToolAuditRecord>> {
+    let read = || -> std::io::Result<Option<String>> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        let mut window = TAIL_CHUNK_BYTES.min(len);
+        loop {
+            file.seek(SeekFrom::Start(len - window))?;
+            let mut tail = Vec::new();
+            (&mut file).take(window).read_to_end(&mut tail)?;
+            let tail = String::from_utf8_lossy(&tail);
+            let tail = tail.trim_end();
+            match tail.rfind('\n') {
+                Some(newline) => return Ok(Some(tail[newline + 1..].to_string())),
+                None if window == len => {
+                    return Ok((!tail.trim().is_empty()).then(|| tail.to_string()));
+                }
+                None => window = (window * 2).min(len),
+            }
+        }
+    };
+    let line = read().with_context(|| format!("Failed to read {}", path.display()))?;
+    line.map(|line| {
+        serde_json::from_str(&line)
+            .with_context(|| format!("Last record of {} is unreadable", path.display()))
+    })
+    .transpose()
+}
+
+/// Verifies the hash chain of a log and its rotated files.
+///
+/// Files are checked oldest first. The first record may follow records in
+/// files that were rotated away; every later record must hash correctly and
+/// follow the one before it.
+///
+/// Returns the number of records checked.
+///
+/// # Errors
+///
+/// Returns an error naming the file and line where the chain breaks.
+pub fn verify_log(path: &Path) -> Result<usize> {
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|n| rotated_path(path, n))
+        .take_while(|p| p.exists())
+        .collect();
+    files.reverse();
+    files.push(path.to_path_buf());
+
+    let mut prev_hash: Option<String> = None;
+    let mut count = 0;
+    for file in files.iter().filter(|f| f.exists()) {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let at = || format!("{} line {}", file.display(), index + 1);
+            let record: ToolAuditRecord = serde_json::from_str(line)
+                .with_context(|| format!("{}: unreadable record", at()))?;
+            if record.compute_hash() != record.hash {
+                bail!("{}: record was modified", at());
+            }
+            if prev_hash
+                .as_ref()
+                .is_some_and(|prev| *prev != record.prev_hash)
+            {
+                bail!("{}: chain broken, a record was removed or inserted", at());
+            }
+            prev_hash = Some(record.hash);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_in(dir: &Path) -> ToolAuditLog {
+        ToolAuditLog::open(ToolAuditConfig::new(dir.join("audit/tools.jsonl"))).unwrap()
+    }
+
+    #[test]
+    fn test_records_are_chained_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = log_in(dir.path());
+        let first = log
+            .record(
+                "s1",
+                "bash",
+                &json!({"command": "ls"}),
+                ToolAuditStatus::Success,
+            )
+            .unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+
+        let mut reopened = log_in(dir.path());
+        let second = reopened
+            .record(
+                "s2",
+                "edit",
+                &json!({"path": "a.rs"}),
+                ToolAuditStatus::Error,
+            )
+            .unwrap();
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(verify_log(reopened.path()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_two_writers_extend_one_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ToolAuditConfig {
+            max_file_bytes: 2_000,
+            max_files: 100,
+            ..ToolAuditConfig::new(dir.path().join("tools.jsonl"))
+        };
+
+        let writers: Vec<_> = ["s1", "s2"]
+            .into_iter()
+            .map(|session| {
+                let mut log = ToolAuditLog::open(config.clone()).unwrap();
+                std::thread::spawn(move || {
+                    for n in 0..40 {
+                        log.record(
+                            session,
+                            "bash",
+                            &json!({ "command": format!("echo {n}") }),
+                            ToolAuditStatus::Success,
+                        )
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert!(rotated_path(&config.path, 1).exists());
+        assert_eq!(verify_log(&config.path).unwrap(), 80);
+    }
+
+    #[test]
+    fn test_chain_continues_after_a_large_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = log_in(dir.path());
+        let large = "x".repeat(3 * TAIL_CHUNK_BYTES as usize);
+        let first = log
+            .record(
+                "s1",
+                "write_file",
+                &json!({ "content": large }),
+                ToolAuditStatus::Success,
+            )
+            .unwrap();
+        let second = log
+            .record("s1", "bash", &json!({}), ToolAuditStatus::Success)
+            .unwrap();
+        assert_eq!(second.prev_hash, first.hash);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = log_in(dir.path());
+        for command in ["ls", "rm -rf build", "cargo test"] {
+            log.record(
+                "s1",
+                "bash",
+                &json!({ "command": command }),
+                ToolAuditStatus::Success,
+            )
+            .unwrap();
+        }
+        let path = log.path().to_path_buf();
+        let original = fs::read_to_string(&path).unwrap();
+
+        fs::write(&path, original.replace("rm -rf build", "echo build")).unwrap();
+        let err = verify_log(&path).unwrap_err().to_string();
+        assert!(
+            err.contains("line 2") && err.contains("modified"),
+            "got: {err}"
+        );
+
+        let without_second: Vec<&str> = original
+            .lines()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, line)| line)
+            .collect();
+        fs::write(&path, without_second.join("\n")).unwrap();
+        let err = verify_log(&path).unwrap_err().to_string();
+        assert!(err.contains("chain broken"), "got: {err}");
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ToolAuditConfig {
+            max_file_bytes: 400,
+            max_files: 2,
+            ..ToolAuditConfig::new(dir.path().join("tools.jsonl"))
+        };
+        let mut log = ToolAuditLog::open(config.clone()).unwrap();
+        for n in 0..12 {
+            log.record(
+                "s1",
+                "read_file",
+                &json!({ "path": format!("f{n}.rs") }),
+                ToolAuditStatus::Success,
+            )
+            .unwrap();
+        }
+
+        assert!(rotated_path(&config.path, 1).exists());
+        assert!(rotated_path(&config.path, 2).exists());
+        assert!(!rotated_path(&config.path, 3).exists());
+        assert!(fs::metadata(&config.path).unwrap().len() <= 400);
+        let checked = verify_log(&config.path).unwrap();
+        assert!(checked > 2 && checked < 12, "checked {checked}");
+    }
+}
@@ -3,10 +3,13 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
+use crate::enterprise::policy::OrgPolicy;
 use crate::shell::ShellConfig;
 use crate::tools::ToolExecutionPolicy;
 
@@ -60,6 +63,10 @@ pub struct HookContext {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_reason: Option<String>,
+
+    /// Working directory of the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -68,13 +75,31 @@ pub struct HookDefinition {
     pub hooks: Vec<HookCommand>,
 }
 
+/// Timeout for hooks that don't set `timeout_ms`.
+pub const DEFAULT_HOOK_TIMEOUT_MS: u64 = 60_000;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct HookCommand {
     #[serde(rename = "type")]
     pub hook_type: String,
     pub command: String,
+    /// How long the hook may run before it is killed
+    /// (defaults to [`DEFAULT_HOOK_TIMEOUT_MS`]).
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Whether a hook that times out or fails to start blocks the event.
+    ///
+    /// Non-blocking hooks (the default) are logged and skipped instead.
+    #[serde(default)]
+    pub blocking: bool,
+}
+
+impl HookCommand {
+    /// Returns how long this hook may run before it is killed.
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.unwrap_or(DEFAULT_HOOK_TIMEOUT_MS))
+    }
 }
 
 #[derive(Debug)]
@@ -96,8 +121,25 @@ pub enum HookDecision {
     Deny,
 }
 
+/// Outcome of the `UserPromptSubmit` hooks for a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptHookResult {
+    /// Send this prompt, which a hook may have rewritten.
+    Submit(String),
+    /// Don't send the prompt; the hook's message says why.
+    Block { reason: String },
+}
+
+/// Output a `UserPromptSubmit` hook prints to replace the prompt.
+#[derive(Debug, Deserialize)]
+struct PromptRewrite {
+    prompt: String,
+}
+
 pub struct HookExecutor {
     hooks: HashMap<HookEvent, Vec<HookDefinition>>,
+    /// Policy every hook command is checked against before it runs
+    policy: ToolExecutionPolicy,
 }
 
 /// Checks if a tool name matches a matcher pattern.
@@ -136,6 +178,7 @@ impl HookExecutor {
     pub fn new() -> Self {
         Self {
             hooks: HashMap::new(),
+            policy: ToolExecutionPolicy::default(),
         }
     }
 
@@ -144,49 +187,29 @@ impl HookExecutor {
     }
 
     pub async fn execute(&self, event: HookEvent, context: &HookContext) -> Result<HookResult> {
-        let definitions = match self.hooks.get(&event) {
-            Some(defs) => defs,
-            None => {
-                return Ok(HookResult {
-                    exit_code: 0,
-                    stdout: String::new(),
-                    stderr: String::new(),
-                    decision: HookDecision::Continue,
-                })
-            }
-        };
-
         let context_json = serde_json::to_string(context)?;
 
-        for def in definitions {
-            if let Some(ref matcher) = def.matcher {
-                if let Some(ref tool_name) = context.tool_name {
-                    if !matches_pattern(matcher, tool_name)? {
-                        continue;
-                    }
+        for hook in self.matching_hooks(event, context.tool_name.as_deref()) {
+            let Some(result) = self.run_isolated(hook, &context_json).await else {
+                continue;
+            };
+
+            match result.exit_code {
+                0 => continue,
+                2 => {
+                    return Ok(HookResult {
+                        decision: HookDecision::Block {
+                            reason: result.stdout.clone(),
+                        },
+                        ..result
+                    })
                 }
-            }
-
-            for hook in &def.hooks {
-                let result = self.run_hook_command(&hook.command, &context_json).await?;
-
-                match result.exit_code {
-                    0 => continue,
-                    2 => {
-                        return Ok(HookResult {
-                            decision: HookDecision::Block {
-                                reason: result.stdout.clone(),
-                            },
-                            ..result
-                        })
-                    }
-                    _ => {
-                        tracing::warn!(
-                            "Hook exited with code {}: {}",
-                            result.exit_code,
-                            result.stderr
-                        );
-                    }
+                _ => {
+                    tracing::warn!(
+                        "Hook exited with code {}: {}",
+                        result.exit_code,
+                        result.stderr
+                    );
                 }
             }
         }
@@ -199,9 +222,61 @@ impl HookExecutor {
         })
     }
 
-    async fn run_hook_command(&self, command: &str, stdin_data: &str) -> Result<HookResult> {
+    /// Returns the hooks registered for an event whose matcher accepts the tool.
+    fn matching_hooks<'a>(
+        &'a self,
+        event: HookEvent,
+        tool_name: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a HookCommand> + 'a {
+        self.hooks
+            .get(&event)
+            .into_iter()
+            .flatten()
+            .filter(move |def| match (&def.matcher, tool_name) {
+                (Some(matcher), Some(tool_name)) => match matches_pattern(matcher, tool_name) {
+                    Ok(matched) => matched,
+                    Err(e) => {
+                        // A bad matcher only disables its own hooks
+                        tracing::warn!(matcher = %matcher, error = %e, "Invalid hook matcher");
+                        false
+                    }
+                },
+                _ => true,
+            })
+            .flat_map(|def| &def.hooks)
+    }
+
+    /// Runs one hook so that a hang or failure to start can't stop the others.
+    ///
+    /// Returns `None` if a non-blocking hook failed. A blocking hook that
+    /// fails is reported as exiting with code 2, with the error as its output.
+    async fn run_isolated(&self, hook: &HookCommand, stdin_data: &str) -> Option<HookResult> {
+        match self.run_hook_command(hook, stdin_data).await {
+            Ok(result) => Some(result),
+            Err(e) if hook.blocking => {
+                tracing::warn!(command = %hook.command, error = %e, "Blocking hook failed");
+                Some(HookResult {
+                    exit_code: 2,
+                    stdout: format!("Hook failed: {}", e),
+                    stderr: e.to_string(),
+                    decision: HookDecision::Continue,
+                })
+            }
+            Err(e) => {
+                tracing::warn!(command = %hook.command, error = %e, "Hook failed; continuing");
+                None
+            }
+        }
+    }
+
+    /// Runs one hook command, killing it if it outlives its timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be started or times out.
+    async fn run_hook_command(&self, hook: &HookCommand, stdin_data: &str) -> Result<HookResult> {
         // Validate command is not empty or whitespace-only
-        let trimmed = command.trim();
+        let trimmed = hook.command.trim();
         if trimmed.is_empty() {
             return Ok(HookResult {
                 exit_code: 1,
@@ -211,28 +286,17 @@ impl HookExecutor {
             });
         }
 
-        // Security validation: check against dangerous command patterns
-        // Reuses the same patterns as ToolExecutionPolicy to ensure consistent security
-        let policy = ToolExecutionPolicy::default();
-        for pattern in &policy.dangerous_patterns {
-            if pattern.is_match(trimmed) {
-                tracing::warn!(
-                    command = %trimmed,
-                    pattern = %pattern.as_str(),
-                    "Hook command blocked by security policy"
-                );
-                // Return exit_code 2 to indicate a block (consistent with hook semantics)
-                // The security policy message goes in stdout (block reason)
-                return Ok(HookResult {
-                    exit_code: 2,
-                    stdout: format!(
-                        "Hook command blocked by security policy: matches dangerous pattern '{}'",
-                        pattern.as_str()
-                    ),
-                    stderr: String::new(),
-                    decision: HookDecision::Continue,
-                });
-            }
+        // Security validation: hooks run under the same command policy as bash
+        if let Err(reason) = self.policy.check_command(trimmed) {
+            tracing::warn!(command = %trimmed, "Hook command blocked by security policy");
+            // Return exit_code 2 to indicate a block (consistent with hook semantics)
+            // The security policy message goes in stdout (block reason)
+            return Ok(HookResult {
+                exit_code: 2,
+                stdout: format!("Hook command blocked: {}", reason),
+                stderr: String::new(),
+                decision: HookDecision::Continue,
+            });
         }
 
         // Log hook execution for audit trail
@@ -246,18 +310,27 @@ impl HookExecutor {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()?;
 
-        if let Some(mut stdin) = child.stdin.take() {
-            // Ignore broken pipe errors - they occur when the process exits
-            // before we finish writing, which is fine (the process got what it needed
-            // or decided to exit early)
-            let _ = stdin.write_all(stdin_data.as_bytes()).await;
-            // Explicitly drop stdin to close the pipe and signal EOF to the child
-            drop(stdin);
-        }
+        let stdin = child.stdin.take();
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                // Ignore broken pipe errors - they occur when the process exits
+                // before we finish writing, which is fine (the process got what it needed
+                // or decided to exit early)
+                let _ = stdin.write_all(stdin_data.as_bytes()).await;
+                // Explicitly drop stdin to close the pipe and signal EOF to the child
+                drop(stdin);
+            }
+            child.wait_with_output().await
+        };
 
-        let output = child.wait_with_output().await?;
+        // Dropping the child on timeout kills it
+        let timeout = hook.timeout();
+        let output = tokio::time::timeout(timeout, run)
+            .await
+            .map_err(|_| anyhow::anyhow!("Hook timed out after {}ms", timeout.as_millis()))??;
 
         Ok(HookResult {
             exit_code: output.status.code().unwrap_or(-1),
@@ -304,6 +377,7 @@ impl Default for HookExecutor {
 pub struct HookManager {
     executor: HookExecutor,
     session_id: String,
+    working_dir: Option<PathBuf>,
 }
 
 impl HookManager {
@@ -313,9 +387,27 @@ impl HookManager {
         Self {
             executor: HookExecutor::new(),
             session_id,
+            working_dir: None,
         }
     }
 
+    /// Sets the working directory passed to hooks as `cwd`.
+    #[must_use]
+    pub fn with_working_dir(mut self, working_dir: PathBuf) -> Self {
+        self.working_dir = Some(working_dir);
+        self
+    }
+
+    /// Changes the working directory passed to hooks as `cwd`.
+    pub fn set_working_dir(&mut self, working_dir: PathBuf) {
+        self.working_dir = Some(working_dir);
+    }
+
+    /// Merges an organization policy into the policy hook commands run under.
+    pub fn apply_org_policy(&mut self, org: &OrgPolicy) {
+        org.apply(&mut self.executor.policy);
+    }
+
     /// Returns the session ID.
     #[must_use]
     pub fn session_id(&self) -> &str {
@@ -416,6 +508,7 @@ impl HookManager {
                 hook_type: "command".to_string(),
                 command: command.to_string(),
                 timeout_ms: Some(30000),
+                blocking: false,
             }],
         };
         self.executor.register(event, vec![definition]);
@@ -423,7 +516,9 @@ impl HookManager {
 
     /// Fires the SessionStart event.
     ///
-    /// Called when the application session begins.
+    /// Called when the application session begins. As with `PreToolUse`, a
+    /// hook exiting with code 2 blocks: the session does not start and the
+    /// hook's stdout is the reason. Other non-zero exit codes are logged.
     pub async fn fire_session_start(&self) -> Result<HookResult> {
         let context = HookContext {
             hook_event_name: HookEvent::SessionStart.as_str().to_string(),
@@ -433,6 +528,7 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor
             .execute(HookEvent::SessionStart, &context)
@@ -441,7 +537,8 @@ impl HookManager {
 
     /// Fires the SessionEnd event.
     ///
-    /// Called when the application session ends.
+    /// Called when the application session ends, before the final save. The
+    /// session is ending regardless, so a blocking exit code is only logged.
     ///
     /// # Arguments
     ///
@@ -455,30 +552,71 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: stop_reason.map(String::from),
+            cwd: self.working_dir.clone(),
         };
         self.executor.execute(HookEvent::SessionEnd, &context).await
     }
 
     /// Fires the UserPromptSubmit event.
     ///
-    /// Called when the user submits a prompt before it's sent to the API.
+    /// Called before a prompt is sent to the API. Hooks run in order, each
+    /// seeing the prompt as rewritten by the ones before it:
+    /// - Exit code 0 continues. If stdout is a JSON object with a `prompt`
+    ///   string, that text replaces the prompt.
+    /// - Exit code 2 blocks the prompt, with stdout as the reason.
+    /// - Other exit codes are logged and the prompt is left unchanged.
     ///
     /// # Arguments
     ///
     /// * `prompt` - The user's input text
-    pub async fn fire_user_prompt_submit(&self, prompt: &str) -> Result<HookResult> {
-        let context = HookContext {
-            hook_event_name: HookEvent::UserPromptSubmit.as_str().to_string(),
-            session_id: self.session_id.clone(),
-            tool_name: None,
-            tool_input: None,
-            tool_response: None,
-            prompt: Some(prompt.to_string()),
-            stop_reason: None,
-        };
-        self.executor
-            .execute(HookEvent::UserPromptSubmit, &context)
-            .await
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hook input cannot be serialized.
+    pub async fn fire_user_prompt_submit(&self, prompt: &str) -> Result<PromptHookResult> {
+        let mut prompt = prompt.to_string();
+
+        for hook in self
+            .executor
+            .matching_hooks(HookEvent::UserPromptSubmit, None)
+        {
+            let context = HookContext {
+                hook_event_name: HookEvent::UserPromptSubmit.as_str().to_string(),
+                session_id: self.session_id.clone(),
+                tool_name: None,
+                tool_input: None,
+                tool_response: None,
+                prompt: Some(prompt.clone()),
+                stop_reason: None,
+                cwd: self.working_dir.clone(),
+            };
+            let context_json = serde_json::to_string(&context)?;
+            let Some(result) = self.executor.run_isolated(hook, &context_json).await else {
+                continue;
+            };
+
+            match result.exit_code {
+                0 => {
+                    if let Ok(rewrite) = serde_json::from_str::<PromptRewrite>(&result.stdout) {
+                        prompt = rewrite.prompt;
+                    }
+                }
+                2 => {
+                    return Ok(PromptHookResult::Block {
+                        reason: result.stdout.trim().to_string(),
+                    })
+                }
+                _ => {
+                    tracing::warn!(
+                        "Hook exited with code {}: {}",
+                        result.exit_code,
+                        result.stderr
+                    );
+                }
+            }
+        }
+
+        Ok(PromptHookResult::Submit(prompt))
     }
 
     /// Fires the PreToolUse event.
@@ -502,6 +640,7 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor.execute(HookEvent::PreToolUse, &context).await
     }
@@ -529,6 +668,7 @@ impl HookManager {
             tool_response: Some(tool_response),
             prompt: None,
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor
             .execute(HookEvent::PostToolUse, &context)
@@ -558,6 +698,7 @@ impl HookManager {
             tool_response: Some(error_response),
             prompt: None,
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor
             .execute(HookEvent::PostToolUseFailure, &context)
@@ -585,6 +726,7 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor
             .execute(HookEvent::PermissionRequest, &context)
@@ -607,6 +749,7 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: Some(stop_reason.to_string()),
+            cwd: self.working_dir.clone(),
         };
         self.executor.execute(HookEvent::Stop, &context).await
     }
@@ -632,6 +775,7 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: Some(stop_reason.to_string()),
+            cwd: self.working_dir.clone(),
         };
         self.executor
             .execute(HookEvent::SubagentStop, &context)
@@ -654,6 +798,7 @@ impl HookManager {
             tool_response: None,
             prompt: Some(message.to_string()),
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor
             .execute(HookEvent::Notification, &context)
@@ -672,6 +817,7 @@ impl HookManager {
             tool_response: None,
             prompt: None,
             stop_reason: None,
+            cwd: self.working_dir.clone(),
         };
         self.executor.execute(HookEvent::PreCompact, &context).await
     }
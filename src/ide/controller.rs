@@ -5,7 +5,9 @@
 //! to the appropriate handlers.
 
 use super::handlers::{PromptContext, QueuedPrompt, StatusContext};
+use super::notify::IdeNotifier;
 use super::protocol::{parse_request, serialize_response, IdeRequest, IdeResponse};
+use super::rpc::{self, EditorContextUpdate};
 use anyhow::Result;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -38,6 +40,12 @@ pub struct IdeController {
     prompt_tx: mpsc::UnboundedSender<QueuedPrompt>,
     /// Receiver for prompts (held by controller, given to main app)
     prompt_rx: Option<mpsc::UnboundedReceiver<QueuedPrompt>>,
+    /// Channel to send editor context to the main application
+    context_tx: mpsc::UnboundedSender<EditorContextUpdate>,
+    /// Receiver for editor context (held by controller, given to main app)
+    context_rx: Option<mpsc::UnboundedReceiver<EditorContextUpdate>>,
+    /// Notifications pushed to connected clients
+    notifier: IdeNotifier,
 }
 
 impl IdeController {
@@ -45,11 +53,15 @@ impl IdeController {
     #[must_use]
     pub fn new(port: u16) -> Self {
         let (prompt_tx, prompt_rx) = mpsc::unbounded_channel();
+        let (context_tx, context_rx) = mpsc::unbounded_channel();
         Self {
             port,
             state: Arc::new(Mutex::new(IdeSharedState::default())),
             prompt_tx,
             prompt_rx: Some(prompt_rx),
+            context_tx,
+            context_rx: Some(context_rx),
+            notifier: IdeNotifier::new(),
         }
     }
 
@@ -60,6 +72,22 @@ impl IdeController {
         self.prompt_rx.take()
     }
 
+    /// Takes the editor context receiver for the main application to consume
+    ///
+    /// Selections and open files reported over JSON-RPC arrive here. This can
+    /// only be called once. Subsequent calls return `None`.
+    pub fn take_context_receiver(
+        &mut self,
+    ) -> Option<mpsc::UnboundedReceiver<EditorContextUpdate>> {
+        self.context_rx.take()
+    }
+
+    /// Returns a handle for pushing notifications to connected clients
+    #[must_use]
+    pub fn notifier(&self) -> IdeNotifier {
+        self.notifier.clone()
+    }
+
     /// Returns a clone of the shared state for updating from the main app
     #[must_use]
     pub fn shared_state(&self) -> Arc<Mutex<IdeSharedState>> {
@@ -86,10 +114,19 @@ impl IdeController {
 
                     let state = Arc::clone(&self.state);
                     let prompt_tx = self.prompt_tx.clone();
+                    let context_tx = self.context_tx.clone();
+                    let notifier = self.notifier.clone();
 
                     tokio::spawn(async move {
-                        if let Err(e) =
-                            handle_connection(stream, state, prompt_tx, session_id.clone()).await
+                        if let Err(e) = handle_connection(
+                            stream,
+                            state,
+                            prompt_tx,
+                            context_tx,
+                            &notifier,
+                            session_id.clone(),
+                        )
+                        .await
                         {
                             warn!("IDE connection {} error: {}", session_id, e);
                         }
@@ -105,18 +142,31 @@ impl IdeController {
 }
 
 /// Handles a single IDE connection
+///
+/// Notifications from `notifier` are written between responses.
 async fn handle_connection(
     mut stream: TcpStream,
     state: Arc<Mutex<IdeSharedState>>,
     prompt_tx: mpsc::UnboundedSender<QueuedPrompt>,
+    context_tx: mpsc::UnboundedSender<EditorContextUpdate>,
+    notifier: &IdeNotifier,
     session_id: String,
 ) -> Result<()> {
     let mut buffer = vec![0u8; 8192];
+    let mut notifications = notifier.subscribe();
 
     loop {
         // Read message length (4 bytes, big-endian) followed by JSON
         // For simplicity, we use a newline-delimited protocol here
-        let n = stream.read(&mut buffer).await?;
+        let n = tokio::select! {
+            read = stream.read(&mut buffer) => read?,
+            Some(notification) = notifications.recv() => {
+                stream.write_all(notification.as_bytes()).await?;
+                stream.write_all(b"\n").await?;
+                stream.flush().await?;
+                continue;
+            }
+        };
         if n == 0 {
             break; // Connection closed
         }
@@ -128,6 +178,18 @@ async fn handle_connection(
                 continue;
             }
 
+            // JSON-RPC messages share the connection with the typed protocol
+            if let Ok(message) = serde_json::from_slice::<serde_json::Value>(line) {
+                if rpc::is_rpc_message(&message) {
+                    if let Some(response) = rpc::handle_message(message, &context_tx) {
+                        stream.write_all(&serde_json::to_vec(&response)?).await?;
+                        stream.write_all(b"\n").await?;
+                        stream.flush().await?;
+                    }
+                    continue;
+                }
+            }
+
             let response = match parse_request(line) {
                 Ok(request) => {
                     debug!("IDE request: {:?}", request);
@@ -228,10 +290,14 @@ mod tests {
 
         // Spawn server handler
         let prompt_tx = controller.prompt_tx.clone();
+        let context_tx = controller.context_tx.clone();
+        let notifier = controller.notifier();
         tokio::spawn(async move {
             if let Ok((stream, _)) = listener.accept().await {
                 let session_id = "test-session".to_string();
-                let _ = handle_connection(stream, state, prompt_tx, session_id).await;
+                let _ =
+                    handle_connection(stream, state, prompt_tx, context_tx, &notifier, session_id)
+                        .await;
             }
         });
 
@@ -271,11 +337,21 @@ mod tests {
         let actual_addr = listener.local_addr().unwrap();
 
         let prompt_tx = controller.prompt_tx.clone();
+        let context_tx = controller.context_tx.clone();
+        let notifier = controller.notifier();
         let state_clone = Arc::clone(&state);
         tokio::spawn(async move {
             if let Ok((stream, _)) = listener.accept().await {
                 let session_id = "test-session".to_string();
-                let _ = handle_connection(stream, state_clone, prompt_tx, session_id).await;
+                let _ = handle_connection(
+                    stream,
+                    state_clone,
+                    prompt_tx,
+                    context_tx,
+                    &notifier,
+                    session_id,
+                )
+                .await;
             }
         });
 
@@ -309,10 +385,14 @@ mod tests {
         let actual_addr = listener.local_addr().unwrap();
 
         let prompt_tx = controller.prompt_tx.clone();
+        let context_tx = controller.context_tx.clone();
+        let notifier = controller.notifier();
         tokio::spawn(async move {
             if let Ok((stream, _)) = listener.accept().await {
                 let session_id = "test-session".to_string();
-                let _ = handle_connection(stream, state, prompt_tx, session_id).await;
+                let _ =
+                    handle_connection(stream, state, prompt_tx, context_tx, &notifier, session_id)
+                        .await;
             }
         });
 
@@ -341,4 +421,140 @@ mod tests {
         let prompt = queued.unwrap();
         assert_eq!(prompt.text, "Hello, Claude!");
     }
+
+    #[tokio::test]
+    async fn test_ide_server_editor_context_over_jsonrpc() {
+        let mut controller = IdeController::new(0);
+        let state = controller.shared_state();
+        let mut context_rx = controller.take_context_receiver().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let actual_addr = listener.local_addr().unwrap();
+
+        let prompt_tx = controller.prompt_tx.clone();
+        let context_tx = controller.context_tx.clone();
+        let notifier = controller.notifier();
+        tokio::spawn(async move {
+            if let Ok((stream, _)) = listener.accept().await {
+                let session_id = "test-session".to_string();
+                let _ =
+                    handle_connection(stream, state, prompt_tx, context_tx, &notifier, session_id)
+                        .await;
+            }
+        });
+
+        let stream = TcpStream::connect(actual_addr).await.unwrap();
+        let (read_half, mut write_half) = stream.into_split();
+        let mut reader = BufReader::new(read_half);
+
+        let requests = [
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "context/setSelection", "params": {"path": "src/lib.rs", "range": {"start": {"line": 2, "character": 0}, "end": {"line": 4, "character": 1}}, "text": "fn parse() {}"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "context/openFiles", "params": {"paths": ["src/lib.rs", "src/main.rs"]}}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "context/unknown"}"#,
+        ];
+        let mut responses = Vec::new();
+        for request in requests {
+            write_half.write_all(request.as_bytes()).await.unwrap();
+            write_half.write_all(b"\n").await.unwrap();
+            write_half.flush().await.unwrap();
+
+            let mut line = String::new();
+            timeout(Duration::from_secs(2), reader.read_line(&mut line))
+                .await
+                .expect("Timeout waiting for response")
+                .unwrap();
+            responses.push(serde_json::from_str::<serde_json::Value>(&line).unwrap());
+        }
+
+        assert_eq!(responses[0]["jsonrpc"], "2.0");
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"]["received"], true);
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["result"]["received"], true);
+        assert_eq!(responses[2]["error"]["code"], rpc::METHOD_NOT_FOUND);
+
+        let selection = timeout(Duration::from_secs(1), context_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match selection {
+            EditorContextUpdate::Selection(Some(selection)) => {
+                assert_eq!(selection.path, std::path::PathBuf::from("src/lib.rs"));
+                assert_eq!((selection.start_line, selection.end_line), (2, 4));
+                assert_eq!(selection.text, "fn parse() {}");
+            }
+            other => panic!("Expected a selection, got {:?}", other),
+        }
+        let open_files = timeout(Duration::from_secs(1), context_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            open_files,
+            EditorContextUpdate::OpenFiles(vec!["src/lib.rs".into(), "src/main.rs".into()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ide_server_pushes_file_changes() {
+        use crate::ide::rpc::RpcNotification;
+        use std::path::Path;
+
+        let controller = IdeController::new(0);
+        let state = controller.shared_state();
+        let notifier = controller.notifier();
+
+        // Sent before any client connects: kept for the first one
+        notifier.notify(&RpcNotification::file_did_change(
+            Path::new("/project/src/lib.rs"),
+            Some("- old\n+ new\n".to_string()),
+        ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let actual_addr = listener.local_addr().unwrap();
+
+        let prompt_tx = controller.prompt_tx.clone();
+        let context_tx = controller.context_tx.clone();
+        let server_notifier = notifier.clone();
+        tokio::spawn(async move {
+            if let Ok((stream, _)) = listener.accept().await {
+                let session_id = "test-session".to_string();
+                let _ = handle_connection(
+                    stream,
+                    state,
+                    prompt_tx,
+                    context_tx,
+                    &server_notifier,
+                    session_id,
+                )
+                .await;
+            }
+        });
+
+        let stream = TcpStream::connect(actual_addr).await.unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut line = String::new();
+        timeout(Duration::from_secs(2), reader.read_line(&mut line))
+            .await
+            .expect("Timeout waiting for notification")
+            .unwrap();
+        let buffered: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(buffered["method"], "file/didChange");
+        assert_eq!(buffered["params"]["path"], "/project/src/lib.rs");
+        assert_eq!(buffered["params"]["diff"], "- old\n+ new\n");
+
+        notifier.notify(&RpcNotification::file_did_change(
+            Path::new("/project/README.md"),
+            None,
+        ));
+        let mut line = String::new();
+        timeout(Duration::from_secs(2), reader.read_line(&mut line))
+            .await
+            .expect("Timeout waiting for notification")
+            .unwrap();
+        let pushed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(pushed["params"]["path"], "/project/README.md");
+        assert!(pushed["params"]["diff"].is_null());
+    }
 }
@@ -8,10 +8,14 @@
 //! - [`protocol`] - Message types and serialization
 //! - [`handlers`] - Request handlers
 //! - [`controller`] - Server lifecycle management
+//! - [`rpc`] - JSON-RPC methods for editor context
+//! - [`notify`] - Notifications pushed to connected editors
 
 pub mod controller;
 pub mod handlers;
+pub mod notify;
 pub mod protocol;
+pub mod rpc;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,138 @@
+//! Outbound notifications to connected IDE clients
+//!
+//! The application reports events through an [`IdeNotifier`], which fans
+//! each notification out to every connected client. Notifications sent
+//! while no client is connected are kept, up to
+//! [`MAX_PENDING_NOTIFICATIONS`], and delivered to the next client that
+//! connects.
+
+use super::rpc::RpcNotification;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Most notifications kept while no client is connected; older ones are dropped
+pub const MAX_PENDING_NOTIFICATIONS: usize = 32;
+
+#[derive(Debug, Default)]
+struct NotifierState {
+    /// Outbound channels of the connected clients
+    clients: Vec<mpsc::UnboundedSender<String>>,
+    /// Serialized notifications waiting for a client
+    pending: VecDeque<String>,
+}
+
+/// Handle for sending notifications to connected IDE clients
+///
+/// Clones share the same clients and pending notifications.
+#[derive(Debug, Clone, Default)]
+pub struct IdeNotifier {
+    state: Arc<Mutex<NotifierState>>,
+}
+
+impl IdeNotifier {
+    /// Creates a notifier with no clients
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends a notification to every connected client
+    ///
+    /// If no client is connected, the notification is kept for the next one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn notify(&self, notification: &RpcNotification) {
+        let line = match serde_json::to_string(notification) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize IDE notification: {}", e);
+                return;
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .clients
+            .retain(|client| client.send(line.clone()).is_ok());
+        if state.clients.is_empty() {
+            if state.pending.len() == MAX_PENDING_NOTIFICATIONS {
+                state.pending.pop_front();
+            }
+            state.pending.push_back(line);
+        }
+    }
+
+    /// Returns true if at least one client is connected
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn has_clients(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.clients.retain(|client| !client.is_closed());
+        !state.clients.is_empty()
+    }
+
+    /// Registers a client and returns the notifications to write to it
+    ///
+    /// Pending notifications are delivered first. The client is forgotten
+    /// once the receiver is dropped.
+    pub(crate) fn subscribe(&self) -> mpsc::UnboundedReceiver<String> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut state = self.state.lock().unwrap();
+        for line in state.pending.drain(..) {
+            let _ = tx.send(line);
+        }
+        state.clients.push(tx);
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn change(n: usize) -> RpcNotification {
+        RpcNotification::file_did_change(Path::new(&format!("file{n}.rs")), None)
+    }
+
+    #[test]
+    fn test_notifications_are_buffered_until_a_client_connects() {
+        let notifier = IdeNotifier::new();
+        for n in 0..MAX_PENDING_NOTIFICATIONS + 2 {
+            notifier.notify(&change(n));
+        }
+        assert!(!notifier.has_clients());
+
+        let mut rx = notifier.subscribe();
+        assert!(notifier.has_clients());
+        let first = rx.try_recv().unwrap();
+        assert!(first.contains("file2.rs"), "oldest were dropped: {first}");
+        let mut received = 1;
+        while rx.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, MAX_PENDING_NOTIFICATIONS);
+
+        // Delivered directly once connected
+        notifier.notify(&change(99));
+        assert!(rx.try_recv().unwrap().contains("file99.rs"));
+    }
+
+    #[test]
+    fn test_disconnected_clients_are_dropped() {
+        let notifier = IdeNotifier::new();
+        drop(notifier.subscribe());
+        assert!(!notifier.has_clients());
+
+        notifier.notify(&change(1));
+        let mut rx = notifier.subscribe();
+        assert!(rx.try_recv().unwrap().contains("file1.rs"));
+    }
+}
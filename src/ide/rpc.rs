@@ -0,0 +1,393 @@
+//! JSON-RPC 2.0 methods for editor context
+//!
+//! Next to the `type`-tagged messages in [`protocol`](super::protocol), the
+//! IDE server accepts JSON-RPC 2.0 requests on the same newline-delimited
+//! connection. Any line with a `"jsonrpc"` field is handled here.
+//!
+//! # Methods
+//!
+//! `context/setSelection` reports what the user has selected. Ranges follow
+//! the Language Server Protocol: lines and characters are 0-indexed. An empty
+//! `text` clears the selection.
+//!
+//! ```ignore
+//! {"jsonrpc": "2.0", "id": 1, "method": "context/setSelection", "params": {
+//!     "path": "/home/user/project/src/lib.rs",
+//!     "range": {"start": {"line": 9, "character": 0}, "end": {"line": 19, "character": 1}},
+//!     "text": "fn parse() {}"
+//! }}
+//! ```
+//!
+//! `context/openFiles` replaces the list of files open in the editor:
+//!
+//! ```ignore
+//! {"jsonrpc": "2.0", "id": 2, "method": "context/openFiles", "params": {
+//!     "paths": ["/home/user/project/src/lib.rs", "/home/user/project/src/main.rs"]
+//! }}
+//! ```
+//!
+//! Both confirm receipt with `{"jsonrpc": "2.0", "id": 1, "result": {"received": true}}`.
+//! Notifications (requests without an `id`) are applied without a reply.
+//! Failures use the standard JSON-RPC error codes.
+//!
+//! # Notifications
+//!
+//! The server sends `file/didChange` when a tool changed a file, so the
+//! editor can show the change or reload the buffer. `diff` is the `edit`
+//! tool's diff, or `null` when the whole file was written:
+//!
+//! ```ignore
+//! {"jsonrpc": "2.0", "method": "file/didChange", "params": {
+//!     "path": "/home/user/project/src/lib.rs",
+//!     "diff": "- fn old_name()\n+ fn new_name()\n"
+//! }}
+//! ```
+
+use crate::session::EditorSelection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// JSON-RPC version spoken by the server
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// The JSON sent is not a valid request object
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters
+pub const INVALID_PARAMS: i64 = -32602;
+/// The server could not handle a valid request
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Editor context reported by an IDE, forwarded to the application
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorContextUpdate {
+    /// The user's selection changed; `None` when nothing is selected
+    Selection(Option<EditorSelection>),
+    /// The set of open files changed
+    OpenFiles(Vec<PathBuf>),
+}
+
+/// A JSON-RPC request or notification
+#[derive(Debug, Clone, Deserialize)]
+pub struct RpcRequest {
+    /// Protocol version, must be "2.0"
+    pub jsonrpc: String,
+    /// Request ID; absent for notifications
+    #[serde(default)]
+    pub id: Option<Value>,
+    /// Method name
+    pub method: String,
+    /// Method parameters
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// A JSON-RPC response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcResponse {
+    /// Protocol version, always "2.0"
+    pub jsonrpc: String,
+    /// ID of the request this answers (`null` if it could not be read)
+    pub id: Value,
+    /// Result on success
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Error on failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+/// A JSON-RPC notification sent to the editor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcNotification {
+    /// Protocol version, always "2.0"
+    pub jsonrpc: String,
+    /// Method name
+    pub method: String,
+    /// Method parameters
+    pub params: Value,
+}
+
+impl RpcNotification {
+    /// Creates a `file/didChange` notification
+    #[must_use]
+    pub fn file_did_change(path: &Path, diff: Option<String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: "file/didChange".to_string(),
+            params: json!({ "path": path, "diff": diff }),
+        }
+    }
+}
+
+/// A JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    /// Error code
+    pub code: i64,
+    /// Human-readable message
+    pub message: String,
+}
+
+impl RpcResponse {
+    /// Creates a success response
+    #[must_use]
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Creates an error response
+    #[must_use]
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// A position in a text document (0-indexed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Position {
+    /// Line number
+    pub line: u32,
+    /// Character offset in the line
+    pub character: u32,
+}
+
+/// A range in a text document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Range {
+    /// Start position
+    pub start: Position,
+    /// End position
+    pub end: Position,
+}
+
+/// Parameters of `context/setSelection`
+#[derive(Debug, Deserialize)]
+struct SetSelectionParams {
+    path: PathBuf,
+    range: Range,
+    text: String,
+}
+
+/// Parameters of `context/openFiles`
+#[derive(Debug, Deserialize)]
+struct OpenFilesParams {
+    paths: Vec<PathBuf>,
+}
+
+/// Returns true if a parsed line is a JSON-RPC message
+#[must_use]
+pub fn is_rpc_message(message: &Value) -> bool {
+    message.get("jsonrpc").is_some()
+}
+
+/// Handle a JSON-RPC message
+///
+/// # Arguments
+///
+/// * `message` - The parsed message
+/// * `context_tx` - Channel that forwards editor context to the application
+///
+/// # Returns
+///
+/// Returns the response to send, or `None` for a notification.
+pub fn handle_message(
+    message: Value,
+    context_tx: &mpsc::UnboundedSender<EditorContextUpdate>,
+) -> Option<RpcResponse> {
+    let request: RpcRequest = match serde_json::from_value(message) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(RpcResponse::error(
+                Value::Null,
+                INVALID_REQUEST,
+                format!("Invalid request: {}", e),
+            ))
+        }
+    };
+    let id = request.id.clone();
+    let response_id = id.clone().unwrap_or(Value::Null);
+
+    if request.jsonrpc != JSONRPC_VERSION {
+        return Some(RpcResponse::error(
+            response_id,
+            INVALID_REQUEST,
+            format!("Unsupported jsonrpc version '{}'", request.jsonrpc),
+        ));
+    }
+
+    let update = match parse_update(&request) {
+        Ok(update) => update,
+        Err(error) => return id.map(|id| RpcResponse::error(id, error.code, error.message)),
+    };
+
+    if context_tx.send(update).is_err() {
+        return id.map(|id| {
+            RpcResponse::error(id, INTERNAL_ERROR, "Patina is no longer accepting context")
+        });
+    }
+
+    id.map(|id| RpcResponse::success(id, json!({ "received": true })))
+}
+
+/// Turns a request into the editor context update it reports
+fn parse_update(request: &RpcRequest) -> Result<EditorContextUpdate, RpcError> {
+    let invalid_params = |e: serde_json::Error| RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params for {}: {}", request.method, e),
+    };
+
+    match request.method.as_str() {
+        "context/setSelection" => {
+            let params: SetSelectionParams =
+                serde_json::from_value(request.params.clone()).map_err(invalid_params)?;
+            let selection = (!params.text.is_empty()).then_some(EditorSelection {
+                path: params.path,
+                start_line: params.range.start.line,
+                end_line: params.range.end.line,
+                text: params.text,
+            });
+            Ok(EditorContextUpdate::Selection(selection))
+        }
+        "context/openFiles" => {
+            let params: OpenFilesParams =
+                serde_json::from_value(request.params.clone()).map_err(invalid_params)?;
+            Ok(EditorContextUpdate::OpenFiles(params.paths))
+        }
+        other => Err(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("Method '{}' not found", other),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(message: Value) -> (Option<RpcResponse>, Option<EditorContextUpdate>) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let response = handle_message(message, &tx);
+        (response, rx.try_recv().ok())
+    }
+
+    #[test]
+    fn test_set_selection_forwards_selection() {
+        let (response, update) = handle(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "context/setSelection",
+            "params": {
+                "path": "src/lib.rs",
+                "range": {"start": {"line": 9, "character": 0}, "end": {"line": 19, "character": 1}},
+                "text": "fn parse() {}"
+            }
+        }));
+
+        let response = response.unwrap();
+        assert_eq!(response.id, json!(1));
+        assert_eq!(response.result, Some(json!({"received": true})));
+        assert_eq!(
+            update,
+            Some(EditorContextUpdate::Selection(Some(EditorSelection {
+                path: PathBuf::from("src/lib.rs"),
+                start_line: 9,
+                end_line: 19,
+                text: "fn parse() {}".to_string(),
+            })))
+        );
+    }
+
+    #[test]
+    fn test_empty_selection_clears() {
+        let (_, update) = handle(json!({
+            "jsonrpc": "2.0",
+            "id": "a",
+            "method": "context/setSelection",
+            "params": {
+                "path": "src/lib.rs",
+                "range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 0}},
+                "text": ""
+            }
+        }));
+        assert_eq!(update, Some(EditorContextUpdate::Selection(None)));
+    }
+
+    #[test]
+    fn test_open_files_notification_has_no_reply() {
+        let (response, update) = handle(json!({
+            "jsonrpc": "2.0",
+            "method": "context/openFiles",
+            "params": {"paths": ["a.rs", "b.rs"]}
+        }));
+        assert!(response.is_none());
+        assert_eq!(
+            update,
+            Some(EditorContextUpdate::OpenFiles(vec![
+                PathBuf::from("a.rs"),
+                PathBuf::from("b.rs")
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_errors_use_jsonrpc_codes() {
+        let (response, update) = handle(json!({
+            "jsonrpc": "2.0", "id": 3, "method": "context/unknown"
+        }));
+        assert_eq!(response.unwrap().error.unwrap().code, METHOD_NOT_FOUND);
+        assert!(update.is_none());
+
+        let (response, _) = handle(json!({
+            "jsonrpc": "2.0", "id": 4, "method": "context/openFiles", "params": {}
+        }));
+        assert_eq!(response.unwrap().error.unwrap().code, INVALID_PARAMS);
+
+        let (response, _) = handle(json!({"jsonrpc": "1.0", "id": 5, "method": "x"}));
+        assert_eq!(response.unwrap().error.unwrap().code, INVALID_REQUEST);
+
+        let (response, _) = handle(json!({"jsonrpc": "2.0", "id": 6}));
+        let response = response.unwrap();
+        assert_eq!(response.id, Value::Null);
+        assert_eq!(response.error.unwrap().code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_file_did_change_notification() {
+        let notification =
+            RpcNotification::file_did_change(Path::new("/p/src/lib.rs"), Some("- a\n+ b\n".into()));
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["method"], "file/didChange");
+        assert_eq!(json["params"]["path"], "/p/src/lib.rs");
+        assert_eq!(json["params"]["diff"], "- a\n+ b\n");
+        assert!(json.get("id").is_none());
+
+        let rewrite = RpcNotification::file_did_change(Path::new("a.rs"), None);
+        assert_eq!(rewrite.params["diff"], Value::Null);
+    }
+
+    #[test]
+    fn test_error_response_omits_result() {
+        let response = RpcResponse::error(json!(7), METHOD_NOT_FOUND, "nope");
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["jsonrpc"], "2.0");
+        assert_eq!(json["error"]["code"], METHOD_NOT_FOUND);
+        assert!(json.get("result").is_none());
+    }
+}
@@ -1,21 +1,27 @@
 //! Patina - High-performance terminal client for Claude API
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use std::io::{IsTerminal, Read};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // Use the library crate
+use patina::api::SummaryStyle;
 use patina::app;
 use patina::auth::{flow::OAuthFlow, storage as auth_storage};
 use patina::plugins::registry::{PluginInstaller, PluginSource};
 use patina::session::{default_sessions_dir, format_session_list, SessionManager};
-use patina::types::config::{NarsilMode, ParallelMode, ResumeMode};
+use patina::types::config::{NarsilMode, OutputFormat, ParallelMode, ResumeMode, DEFAULT_MODEL};
+use patina::types::Settings;
+use patina::update::{ReleaseChannel, UpdateChecker, UpdateInstaller};
 use patina::util::get_cache_dir;
+use patina::util::redact::RedactingWriter;
+use patina::util::version::{verbose_version, VERSION};
 
 #[derive(Parser, Debug)]
 #[command(name = "patina")]
 #[command(about = "Patina - High-performance terminal client for Claude API")]
-#[command(version)]
+#[command(version, disable_version_flag = true)]
 struct Args {
     /// Initial prompt to start the conversation with.
     /// Starts interactive mode with this prompt pre-submitted.
@@ -27,14 +33,42 @@ struct Args {
     #[arg(short = 'p', long)]
     print: bool,
 
+    /// Print mode output: text streams the response, json prints one object
+    /// with the response, tool calls, token usage and stop reason at the end.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "text",
+        requires = "print"
+    )]
+    output_format: OutputFormat,
+
+    /// Autonomous mode: keep running tools until the prompt is done (non-interactive).
+    /// Only read-only tools, edits inside the project and allowed commands run; stops at
+    /// the iteration limit, the cost budget, or the first denied tool.
+    #[arg(
+        long,
+        requires = "prompt",
+        conflicts_with = "dangerously_skip_permissions"
+    )]
+    auto: bool,
+
     /// API key (or set ANTHROPIC_API_KEY env var)
     #[arg(long, env = "ANTHROPIC_API_KEY", hide_env_values = true)]
     api_key: Option<secrecy::SecretString>,
 
     /// Model to use
-    #[arg(short, long, default_value = "claude-sonnet-4-20250514")]
+    #[arg(short, long, default_value = DEFAULT_MODEL)]
     model: String,
 
+    /// Print version information and exit.
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// With --version, also print commit, build date, Rust version, and features.
+    #[arg(long, requires = "version")]
+    verbose: bool,
+
     /// Working directory
     #[arg(short = 'C', long, default_value = ".")]
     directory: std::path::PathBuf,
@@ -78,6 +112,10 @@ struct Args {
     #[arg(long)]
     list_sessions: bool,
 
+    /// Only list sessions carrying this tag (use with --list-sessions).
+    #[arg(long, value_name = "TAG", requires = "list_sessions")]
+    tag: Option<String>,
+
     /// Bypass all permission prompts (DANGEROUS: allows all tool executions without approval).
     #[arg(long)]
     dangerously_skip_permissions: bool,
@@ -117,6 +155,13 @@ struct Args {
     #[arg(long)]
     no_plugins: bool,
 
+    /// Reload plugins when their files change.
+    ///
+    /// Edited plugins are validated and swapped in place; a plugin that fails
+    /// to load keeps its previous version.
+    #[arg(long)]
+    watch_plugins: bool,
+
     /// Enable subagent orchestration for parallel task execution.
     ///
     /// When enabled, subagents can be spawned to handle complex tasks
@@ -138,6 +183,38 @@ struct Args {
     #[arg(long)]
     no_auto_context: bool,
 
+    /// Disable file backups before write_file and edit.
+    ///
+    /// Useful when everything is under version control and `.rct_backups`
+    /// would only clutter the working tree.
+    #[arg(long)]
+    no_backups: bool,
+
+    /// How history is summarized when compacted: bullet, paragraph or detailed.
+    ///
+    /// Change it during a session with `/compact <style>`.
+    #[arg(long, value_name = "STYLE", default_value = "detailed")]
+    summary_style: SummaryStyle,
+
+    /// Update to the newest release on the update channel and exit.
+    ///
+    /// The replaced binary is kept so `--rollback` can restore it.
+    #[arg(long, conflicts_with = "rollback")]
+    update: bool,
+
+    /// Release channel for --update: stable, beta or nightly.
+    #[arg(
+        long,
+        value_name = "CHANNEL",
+        default_value = "stable",
+        requires = "update"
+    )]
+    update_channel: ReleaseChannel,
+
+    /// Restore the version replaced by the last --update and exit.
+    #[arg(long)]
+    rollback: bool,
+
     /// Subcommand for plugin and other operations.
     #[command(subcommand)]
     command: Option<Command>,
@@ -182,16 +259,32 @@ enum PluginAction {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    if args.version {
+        if args.verbose {
+            println!("{}", verbose_version(&args.model));
+        } else {
+            println!("patina {VERSION}");
+        }
+        return Ok(());
+    }
 
     // Handle subcommands first
     if let Some(cmd) = args.command {
         return handle_command(cmd).await;
     }
 
+    if args.update {
+        return self_update(args.update_channel).await;
+    }
+    if args.rollback {
+        return rollback().await;
+    }
+
     // Handle --list-sessions before any other initialization
     if args.list_sessions {
-        return list_sessions().await;
+        return list_sessions(args.tag.as_deref()).await;
     }
 
     // Handle --oauth-logout before other initialization
@@ -204,14 +297,23 @@ async fn main() -> Result<()> {
         return oauth_login().await;
     }
 
+    // `echo "explain this" | patina -p` takes the prompt from stdin
+    if args.print && args.prompt.is_none() {
+        if std::io::stdin().is_terminal() {
+            anyhow::bail!("--print requires a prompt argument or piped input");
+        }
+        args.prompt = Some(read_piped_prompt(std::io::stdin().lock())?);
+    }
+
     let filter = if args.debug { "debug" } else { "info" };
 
     // Determine if we're running in interactive TUI mode
     // TUI mode uses alternate screen which conflicts with stdout logging
-    let is_tui_mode = !args.print || args.prompt.is_none();
+    let is_tui_mode = !(args.print || args.auto) || args.prompt.is_none();
 
     if is_tui_mode && args.debug {
-        // TUI mode with debug: write logs to file to avoid corrupting display
+        // TUI mode with debug: write logs to file to avoid corrupting display.
+        // Log lines can echo tool input, so secrets are redacted on the way out.
         let log_path = std::env::temp_dir().join("patina.log");
         let file = std::fs::OpenOptions::new()
             .create(true)
@@ -229,19 +331,25 @@ async fn main() -> Result<()> {
                 tracing_subscriber::fmt::layer()
                     .with_target(false)
                     .with_ansi(false)
-                    .with_writer(std::sync::Mutex::new(file)),
+                    .with_writer(std::sync::Mutex::new(RedactingWriter::new(file))),
             )
             .init();
 
         eprintln!("Debug logs written to: {}", log_path.display());
     } else {
-        // Print mode or no debug: log to stdout
+        // Print/auto mode or no debug: log to stderr so stdout carries only
+        // the response (`patina -p ... | jq` must see nothing else)
         tracing_subscriber::registry()
             .with(
                 tracing_subscriber::EnvFilter::try_from_default_env()
                     .unwrap_or_else(|_| filter.into()),
             )
-            .with(tracing_subscriber::fmt::layer().with_target(false))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_ansi(std::io::stderr().is_terminal())
+                    .with_writer(|| RedactingWriter::new(std::io::stderr())),
+            )
             .init();
     }
 
@@ -289,16 +397,10 @@ async fn main() -> Result<()> {
     // - print mode (-p) with prompt: non-interactive (send prompt, print response, exit)
     // - prompt only: interactive mode with initial prompt pre-submitted
     // - no prompt: interactive mode
-    let (initial_prompt, print_mode) = match (args.prompt, args.print) {
-        (Some(prompt), true) => (Some(prompt), true), // Non-interactive
-        (Some(prompt), false) => (Some(prompt), false), // Interactive with initial prompt
-        (None, true) => {
-            // -p without prompt reads from stdin (not yet implemented)
-            eprintln!("Error: --print requires a prompt argument or piped input");
-            std::process::exit(1);
-        }
-        (None, false) => (None, false), // Pure interactive
-    };
+    // A -p without a prompt argument has already read it from stdin.
+    let (initial_prompt, print_mode) = (args.prompt, args.print);
+
+    let settings = Settings::load(&Settings::default_path()?)?;
 
     app::run(app::Config {
         api_key,
@@ -310,23 +412,54 @@ async fn main() -> Result<()> {
         skip_permissions: args.dangerously_skip_permissions,
         initial_prompt,
         print_mode,
+        output_format: args.output_format,
+        auto_mode: args.auto,
         vision_model: None,
         oauth_client_id: args.oauth_client_id,
         initial_images: args.image,
         plugins_enabled: !args.no_plugins,
+        watch_plugins: args.watch_plugins,
         subagents_enabled: args.enable_subagents,
         ide_port: args.ide_port,
         auto_context_enabled: !args.no_auto_context,
+        backups_enabled: !args.no_backups,
+        summary_style: args.summary_style,
+        settings,
     })
     .await
 }
 
+/// Reads the `--print` prompt piped to stdin.
+///
+/// # Errors
+///
+/// Returns an error if stdin cannot be read, is not UTF-8, or holds only
+/// whitespace.
+fn read_piped_prompt(mut input: impl Read) -> Result<String> {
+    let mut prompt = String::new();
+    input
+        .read_to_string(&mut prompt)
+        .context("Failed to read the prompt from stdin")?;
+    let prompt = prompt.trim();
+    if prompt.is_empty() {
+        anyhow::bail!(
+            "--print read an empty prompt from stdin; pipe in some text or pass the prompt as an argument"
+        );
+    }
+    Ok(prompt.to_string())
+}
+
 /// Lists all available sessions and exits.
-async fn list_sessions() -> Result<()> {
+///
+/// When `tag` is set, only sessions carrying that tag are listed.
+async fn list_sessions(tag: Option<&str>) -> Result<()> {
     let sessions_dir = default_sessions_dir()?;
     let manager = SessionManager::new(sessions_dir);
 
-    let sessions = manager.list_sorted().await?;
+    let sessions = match tag {
+        Some(tag) => manager.list_by_tag(tag).await?,
+        None => manager.list_sorted().await?,
+    };
     let output = format_session_list(&sessions);
 
     println!("{output}");
@@ -334,6 +467,39 @@ async fn list_sessions() -> Result<()> {
     Ok(())
 }
 
+/// Installs the newest release on `channel` over the running binary.
+async fn self_update(channel: ReleaseChannel) -> Result<()> {
+    let checker = UpdateChecker::new(VERSION, channel)?;
+    let Some(manifest) = checker.check_for_updates().await? else {
+        println!(
+            "patina {VERSION} is up to date on the {} channel.",
+            channel.as_str()
+        );
+        return Ok(());
+    };
+
+    let platform = UpdateChecker::get_platform_key();
+    let release = manifest.platforms.get(platform).ok_or_else(|| {
+        anyhow::anyhow!("patina {} has no build for {platform}", manifest.version)
+    })?;
+    let installer = UpdateInstaller::new(std::env::current_exe()?);
+    installer.download_and_install(release).await?;
+
+    println!(
+        "Updated patina {VERSION} -> {}. Run `patina --rollback` to undo.",
+        manifest.version
+    );
+    Ok(())
+}
+
+/// Swaps the running binary with the one replaced by the last update.
+async fn rollback() -> Result<()> {
+    let installer = UpdateInstaller::new(std::env::current_exe()?);
+    let version = installer.rollback().await?;
+    println!("Rolled back to {version}.");
+    Ok(())
+}
+
 /// Runs the OAuth login flow and stores credentials.
 ///
 /// Note: OAuth is currently disabled pending client_id registration with Anthropic.
@@ -521,4 +687,108 @@ mod tests {
             Some("Explain this architecture diagram".to_string())
         );
     }
+
+    /// Test that --tag filters --list-sessions output.
+    #[test]
+    fn test_cli_list_sessions_tag_filter() {
+        let args = Args::parse_from(["patina", "--list-sessions", "--tag", "bugfix"]);
+
+        assert!(args.list_sessions);
+        assert_eq!(args.tag.as_deref(), Some("bugfix"));
+    }
+
+    /// Test that --verbose is accepted alongside --version.
+    #[test]
+    fn test_cli_version_verbose() {
+        let args = Args::parse_from(["patina", "--version", "--verbose"]);
+
+        assert!(args.version);
+        assert!(args.verbose);
+        assert_eq!(args.model, DEFAULT_MODEL);
+    }
+
+    /// Test that --verbose is rejected without --version.
+    #[test]
+    fn test_cli_verbose_requires_version() {
+        assert!(Args::try_parse_from(["patina", "--verbose"]).is_err());
+    }
+
+    /// Test that a piped prompt is read for --print.
+    #[test]
+    fn test_read_piped_prompt() {
+        let prompt = read_piped_prompt(std::io::Cursor::new("explain this\n")).unwrap();
+        assert_eq!(prompt, "explain this");
+
+        let prompt = read_piped_prompt(std::io::Cursor::new("line one\nline two\n")).unwrap();
+        assert_eq!(prompt, "line one\nline two");
+    }
+
+    /// Test that empty stdin is rejected with a hint.
+    #[test]
+    fn test_read_piped_prompt_empty() {
+        for input in ["", " \n\t\n"] {
+            let err = read_piped_prompt(std::io::Cursor::new(input)).unwrap_err();
+            assert!(err.to_string().contains("empty prompt"), "got: {err}");
+        }
+
+        let err = read_piped_prompt(std::io::Cursor::new(vec![0xff, 0xfe])).unwrap_err();
+        assert!(err.to_string().contains("stdin"), "got: {err}");
+    }
+
+    /// Test that --update-channel accepts beta and requires --update.
+    #[test]
+    fn test_cli_update_channel() {
+        let args = Args::parse_from(["patina", "--update", "--update-channel", "beta"]);
+        assert!(args.update);
+        assert_eq!(args.update_channel, ReleaseChannel::Latest);
+
+        let args = Args::parse_from(["patina", "--update"]);
+        assert_eq!(args.update_channel, ReleaseChannel::Stable);
+
+        assert!(Args::try_parse_from(["patina", "--update-channel", "beta"]).is_err());
+        assert!(
+            Args::try_parse_from(["patina", "--update", "--update-channel", "canary"]).is_err()
+        );
+        assert!(Args::try_parse_from(["patina", "--update", "--rollback"]).is_err());
+    }
+
+    /// Test that --auto is accepted with a prompt.
+    #[test]
+    fn test_cli_auto_with_prompt() {
+        let args = Args::parse_from(["patina", "--auto", "Fix the failing tests"]);
+
+        assert!(args.auto);
+        assert_eq!(args.prompt.as_deref(), Some("Fix the failing tests"));
+    }
+
+    /// Test that --auto needs a prompt and keeps permission checks.
+    #[test]
+    fn test_cli_auto_requires_prompt() {
+        assert!(Args::try_parse_from(["patina", "--auto"]).is_err());
+        assert!(Args::try_parse_from([
+            "patina",
+            "--auto",
+            "--dangerously-skip-permissions",
+            "Fix the failing tests"
+        ])
+        .is_err());
+    }
+
+    /// Test that --summary-style parses style names and defaults to detailed.
+    #[test]
+    fn test_cli_summary_style() {
+        let args = Args::parse_from(["patina", "--summary-style", "bullet"]);
+        assert_eq!(args.summary_style, SummaryStyle::BulletPoints);
+
+        let args = Args::parse_from(["patina"]);
+        assert_eq!(args.summary_style, SummaryStyle::Timeline);
+
+        assert!(Args::try_parse_from(["patina", "--summary-style", "terse"]).is_err());
+    }
+
+    /// Test that --tag is rejected without --list-sessions.
+    #[test]
+    fn test_cli_tag_requires_list_sessions() {
+        assert!(Args::try_parse_from(["patina", "--tag", "bugfix"]).is_err());
+    }
 }
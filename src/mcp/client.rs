@@ -32,12 +32,14 @@
 use crate::error::{RctError, RctResult};
 use crate::mcp::protocol::{JsonRpcRequest, JsonRpcResponse};
 use crate::mcp::transport::{StdioTransport, Transport};
+use crate::tools::ToolResult;
 use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
 /// Commands that are ALWAYS blocked, even with absolute paths (Unix).
 ///
@@ -336,6 +338,89 @@ pub fn validate_mcp_command(command: &str, args: &[String]) -> RctResult<()> {
 /// Default timeout for MCP requests.
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Default number of reconnect attempts before giving up on a server.
+pub const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// How a client reconnects after its server disconnects.
+///
+/// The first attempt is made as soon as the disconnect is noticed. Each
+/// failed attempt doubles the wait before the next, from `initial_backoff`
+/// up to `max_backoff`. After `max_attempts` failures the server is given up
+/// on until the client is restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Failed attempts allowed before giving up (0 disables reconnection)
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt
+    pub initial_backoff: Duration,
+    /// Upper bound on the wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Returns the wait after `failures` consecutive failed attempts.
+    #[must_use]
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A change in a server's connection, reported for display in the timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpConnectionEvent {
+    /// The server process exited or its pipes closed.
+    Disconnected {
+        /// Server name
+        server: String,
+    },
+    /// The server was restarted, re-initialized and its tools re-listed.
+    Reconnected {
+        /// Server name
+        server: String,
+        /// Number of tools the server now provides
+        tools: usize,
+    },
+    /// Reconnection failed too many times; the server's tools stay unavailable.
+    GaveUp {
+        /// Server name
+        server: String,
+        /// Failed reconnect attempts
+        attempts: u32,
+    },
+}
+
+impl McpConnectionEvent {
+    /// Returns the notice to show in the timeline.
+    #[must_use]
+    pub fn notice(&self) -> String {
+        match self {
+            Self::Disconnected { server } => {
+                format!("MCP server '{}' disconnected; reconnecting...", server)
+            }
+            Self::Reconnected { server, tools } => {
+                format!("MCP server '{}' reconnected ({} tools)", server, tools)
+            }
+            Self::GaveUp { server, attempts } => format!(
+                "MCP server '{}' is unavailable after {} reconnect attempts",
+                server, attempts
+            ),
+        }
+    }
+}
+
 /// MCP tool definition from tools/list response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpTool {
@@ -349,6 +434,39 @@ pub struct McpTool {
     pub input_schema: serde_json::Value,
 }
 
+/// MCP resource from resources/list response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpResource {
+    /// URI identifying the resource on its server
+    pub uri: String,
+    /// Human-readable name
+    pub name: String,
+    /// Optional description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// MIME type, if known
+    #[serde(default, rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// One item of resource content from resources/read response.
+///
+/// Text resources carry `text`; binary resources carry base64 in `blob`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpResourceContent {
+    /// URI of the resource this content belongs to
+    pub uri: String,
+    /// MIME type, if known
+    #[serde(default, rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Text content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Base64-encoded binary content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
 /// MCP server capabilities from initialize response.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServerCapabilities {
@@ -373,6 +491,85 @@ pub struct ServerInfo {
     pub version: String,
 }
 
+/// Partial output reported by a server while a tool call is running.
+///
+/// Parsed from `notifications/progress` sent for the call's progress token.
+/// Servers that stream partial results attach MCP content blocks under
+/// `content`; plain progress updates carry only counters and a message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct McpPartialResult {
+    /// Progress so far, in server-defined units
+    pub progress: Option<f64>,
+    /// Total expected progress, if known
+    pub total: Option<f64>,
+    /// Human-readable progress message
+    pub message: Option<String>,
+    /// Partial result content blocks
+    pub content: Vec<serde_json::Value>,
+}
+
+impl McpPartialResult {
+    /// Parses a progress notification's params.
+    fn from_params(params: &serde_json::Value) -> Self {
+        Self {
+            progress: params.get("progress").and_then(serde_json::Value::as_f64),
+            total: params.get("total").and_then(serde_json::Value::as_f64),
+            message: params
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map(String::from),
+            content: params
+                .get("content")
+                .and_then(|c| c.as_array())
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the text to display for this update.
+    ///
+    /// Joins the text content blocks, falling back to the progress message.
+    #[must_use]
+    pub fn text(&self) -> String {
+        let text: Vec<&str> = self
+            .content
+            .iter()
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect();
+        if text.is_empty() {
+            self.message.clone().unwrap_or_default()
+        } else {
+            text.join("\n")
+        }
+    }
+}
+
+/// Combines streamed partial results with the final tool result.
+///
+/// The partial content blocks are placed, in order, before the final
+/// result's own content, so the model sees everything the tool produced.
+#[must_use]
+pub fn aggregate_partial_results(
+    partials: &[McpPartialResult],
+    mut result: serde_json::Value,
+) -> serde_json::Value {
+    let mut content: Vec<serde_json::Value> = partials
+        .iter()
+        .flat_map(|partial| partial.content.iter().cloned())
+        .collect();
+    if content.is_empty() {
+        return result;
+    }
+
+    if let Some(final_content) = result.get("content").and_then(|c| c.as_array()) {
+        content.extend(final_content.iter().cloned());
+    }
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("content".to_string(), serde_json::Value::Array(content));
+    }
+    result
+}
+
 /// High-level MCP client for server management.
 ///
 /// Manages the connection lifecycle and provides methods for
@@ -402,6 +599,20 @@ pub struct McpClient {
     capabilities: Option<ServerCapabilities>,
     /// Server info (set after initialization)
     server_info: Option<ServerInfo>,
+    /// Notifications forwarded by the transport (set after start)
+    notifications: Option<mpsc::Receiver<JsonRpcRequest>>,
+    /// Tools from the latest tools/list
+    tools: Vec<McpTool>,
+    /// How to reconnect after a disconnect
+    reconnect_policy: ReconnectPolicy,
+    /// Failed reconnect attempts since the last disconnect
+    reconnect_failures: u32,
+    /// When the next reconnect may be tried (set while disconnected)
+    next_reconnect: Option<Instant>,
+    /// Whether reconnection has been given up on
+    gave_up: bool,
+    /// Receives connection notices, if subscribed
+    events: Option<mpsc::UnboundedSender<McpConnectionEvent>>,
 }
 
 impl McpClient {
@@ -430,9 +641,33 @@ impl McpClient {
             request_id: AtomicI64::new(1),
             capabilities: None,
             server_info: None,
+            notifications: None,
+            tools: Vec::new(),
+            reconnect_policy: ReconnectPolicy::default(),
+            reconnect_failures: 0,
+            next_reconnect: None,
+            gave_up: false,
+            events: None,
         }
     }
 
+    /// Sets how the client reconnects after its server disconnects.
+    #[must_use]
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// Sends disconnect and reconnect notices to `events`.
+    #[must_use]
+    pub fn with_connection_events(
+        mut self,
+        events: mpsc::UnboundedSender<McpConnectionEvent>,
+    ) -> Self {
+        self.events = Some(events);
+        self
+    }
+
     /// Returns the server name.
     #[must_use]
     pub fn name(&self) -> &str {
@@ -463,6 +698,18 @@ impl McpClient {
         self.server_info.as_ref()
     }
 
+    /// Returns the tools from the latest `list_tools` or reconnect.
+    #[must_use]
+    pub fn tools(&self) -> &[McpTool] {
+        &self.tools
+    }
+
+    /// Returns whether the server has disconnected and is being reconnected.
+    #[must_use]
+    pub fn is_reconnecting(&self) -> bool {
+        self.next_reconnect.is_some()
+    }
+
     /// Gets the next request ID.
     fn next_request_id(&self) -> i64 {
         self.request_id.fetch_add(1, Ordering::SeqCst)
@@ -495,6 +742,10 @@ impl McpClient {
             .context("Failed to start MCP transport")?;
 
         self.connected.store(true, Ordering::SeqCst);
+        self.notifications = self.transport.take_notifications();
+        self.next_reconnect = None;
+        self.gave_up = false;
+        self.reconnect_failures = 0;
 
         // Perform MCP initialization
         self.initialize().await?;
@@ -569,6 +820,7 @@ impl McpClient {
             self.connected.store(false, Ordering::SeqCst);
             self.initialized.store(false, Ordering::SeqCst);
         }
+        self.next_reconnect = None;
 
         Ok(())
     }
@@ -578,6 +830,132 @@ impl McpClient {
         let _ = self.transport.stop().await;
         self.connected.store(false, Ordering::SeqCst);
         self.initialized.store(false, Ordering::SeqCst);
+        self.next_reconnect = None;
+    }
+
+    /// Sends a connection notice to the subscriber, if any.
+    fn emit(&self, event: McpConnectionEvent) {
+        tracing::info!(server = %self.name, "{}", event.notice());
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    /// Records that the server went away and schedules a reconnect.
+    fn mark_disconnected(&mut self) {
+        if !self.is_connected() {
+            return;
+        }
+        self.connected.store(false, Ordering::SeqCst);
+        self.initialized.store(false, Ordering::SeqCst);
+        self.reconnect_failures = 0;
+        self.emit(McpConnectionEvent::Disconnected {
+            server: self.name.clone(),
+        });
+        if self.reconnect_policy.max_attempts == 0 {
+            self.give_up();
+        } else {
+            self.next_reconnect = Some(Instant::now());
+        }
+    }
+
+    /// Stops reconnecting; calls fail until the client is restarted.
+    fn give_up(&mut self) {
+        self.next_reconnect = None;
+        self.gave_up = true;
+        self.emit(McpConnectionEvent::GaveUp {
+            server: self.name.clone(),
+            attempts: self.reconnect_failures,
+        });
+    }
+
+    /// Marks the client disconnected if the server process has gone.
+    fn check_alive(&mut self) {
+        if self.is_connected() && !self.transport.is_alive() {
+            self.mark_disconnected();
+        }
+    }
+
+    /// Makes sure the server is usable before sending a request.
+    ///
+    /// While disconnected, tries a reconnect once its backoff has elapsed.
+    /// Otherwise fails at once, so calls never wait on a dead server.
+    async fn ensure_connected(&mut self) -> Result<()> {
+        self.check_alive();
+        if self.is_connected() {
+            return Ok(());
+        }
+
+        if self.gave_up {
+            return Err(anyhow!(
+                "MCP server '{}' is unavailable after {} reconnect attempts",
+                self.name,
+                self.reconnect_failures
+            ));
+        }
+        let Some(next) = self.next_reconnect else {
+            return Err(anyhow!("Not connected to server"));
+        };
+
+        let now = Instant::now();
+        if now < next {
+            return Err(anyhow!(
+                "MCP server '{}' is disconnected; retrying in {:.1}s",
+                self.name,
+                (next - now).as_secs_f64()
+            ));
+        }
+
+        self.reconnect().await
+    }
+
+    /// Makes one reconnect attempt, scheduling the next on failure.
+    async fn reconnect(&mut self) -> Result<()> {
+        let _ = self.transport.stop().await;
+        let attempt = async {
+            self.transport
+                .start()
+                .await
+                .context("Failed to restart MCP transport")?;
+            self.notifications = self.transport.take_notifications();
+            self.initialize().await?;
+            self.fetch_tools().await
+        };
+
+        match attempt.await {
+            Ok(tools) => {
+                self.connected.store(true, Ordering::SeqCst);
+                self.reconnect_failures = 0;
+                self.next_reconnect = None;
+                self.emit(McpConnectionEvent::Reconnected {
+                    server: self.name.clone(),
+                    tools: tools.len(),
+                });
+                Ok(())
+            }
+            Err(e) => {
+                let _ = self.transport.stop().await;
+                self.initialized.store(false, Ordering::SeqCst);
+                self.reconnect_failures += 1;
+                if self.reconnect_failures >= self.reconnect_policy.max_attempts {
+                    self.give_up();
+                } else {
+                    self.next_reconnect = Some(
+                        Instant::now() + self.reconnect_policy.backoff(self.reconnect_failures),
+                    );
+                }
+                Err(e.context(format!("MCP server '{}' is disconnected", self.name)))
+            }
+        }
+    }
+
+    /// Sends a request, noticing if the server died while it was in flight.
+    async fn request(&mut self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let response = self.transport.send_request(request, DEFAULT_TIMEOUT).await;
+        if response.is_err() {
+            self.check_alive();
+        }
+        response
     }
 
     /// Lists available tools from the MCP server.
@@ -586,10 +964,16 @@ impl McpClient {
     ///
     /// Returns an error if the request fails or response is invalid.
     pub async fn list_tools(&mut self) -> Result<Vec<McpTool>> {
-        if !self.is_connected() {
-            return Err(anyhow!("Not connected to server"));
+        self.ensure_connected().await?;
+        let tools = self.fetch_tools().await;
+        if tools.is_err() {
+            self.check_alive();
         }
+        tools
+    }
 
+    /// Sends tools/list and caches the result.
+    async fn fetch_tools(&mut self) -> Result<Vec<McpTool>> {
         let request =
             JsonRpcRequest::new(self.next_request_id(), "tools/list", serde_json::json!({}));
 
@@ -614,12 +998,81 @@ impl McpClient {
             .ok_or_else(|| anyhow!("No tools field"))?;
         let tools: Vec<McpTool> =
             serde_json::from_value(tools_value.clone()).context("Failed to parse tools")?;
+        self.tools = tools.clone();
 
         Ok(tools)
     }
 
+    /// Lists resources the MCP server exposes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or response is invalid.
+    pub async fn list_resources(&mut self) -> Result<Vec<McpResource>> {
+        let result = self
+            .call_method("resources/list", serde_json::json!({}))
+            .await?;
+        let resources = result
+            .get("resources")
+            .ok_or_else(|| anyhow!("No resources field"))?;
+        serde_json::from_value(resources.clone()).context("Failed to parse resources")
+    }
+
+    /// Reads a resource from the MCP server.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - URI of the resource, as listed by `list_resources`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, the server rejects the URI,
+    /// or the response is invalid.
+    pub async fn read_resource(&mut self, uri: &str) -> Result<Vec<McpResourceContent>> {
+        let result = self
+            .call_method("resources/read", serde_json::json!({ "uri": uri }))
+            .await?;
+        let contents = result
+            .get("contents")
+            .ok_or_else(|| anyhow!("No contents field"))?;
+        serde_json::from_value(contents.clone()).context("Failed to parse resource contents")
+    }
+
+    /// Sends a request and returns its result, failing on a JSON-RPC error.
+    async fn call_method(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.ensure_connected().await?;
+
+        let request = JsonRpcRequest::new(self.next_request_id(), method, params);
+        let response = self
+            .request(request)
+            .await
+            .with_context(|| format!("Failed to send {} request", method))?;
+
+        if response.is_error() {
+            let error = response.error().unwrap();
+            return Err(anyhow!(
+                "{} failed: {} ({})",
+                method,
+                error.message(),
+                error.code()
+            ));
+        }
+
+        response
+            .result()
+            .cloned()
+            .ok_or_else(|| anyhow!("No result"))
+    }
+
     /// Calls a tool on the MCP server.
     ///
+    /// Partial results the server streams are folded into the returned
+    /// result, as with [`call_tool_streaming`](Self::call_tool_streaming).
+    ///
     /// # Arguments
     ///
     /// * `name` - Name of the tool to call
@@ -633,25 +1086,113 @@ impl McpClient {
         name: &str,
         arguments: serde_json::Value,
     ) -> Result<serde_json::Value> {
-        if !self.is_connected() {
-            return Err(anyhow!("Not connected to server"));
-        }
+        self.call_tool_with_updates(name, arguments, None).await
+    }
 
+    /// Calls a tool, forwarding partial results as the server reports them.
+    ///
+    /// The request carries a progress token; each `notifications/progress`
+    /// for that token is sent to `updates` as it arrives. Updates are dropped
+    /// while `updates` is full, but the returned result still aggregates all
+    /// the partial content with the final response (see
+    /// [`aggregate_partial_results`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the tool to call
+    /// * `arguments` - Arguments to pass to the tool
+    /// * `updates` - Channel receiving partial results in order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the call fails.
+    pub async fn call_tool_streaming(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        updates: mpsc::Sender<McpPartialResult>,
+    ) -> Result<serde_json::Value> {
+        self.call_tool_with_updates(name, arguments, Some(&updates))
+            .await
+    }
+
+    /// Sends a `tools/call` with a progress token, reading notifications
+    /// until the response arrives so none are left queued between calls.
+    async fn call_tool_with_updates(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        updates: Option<&mpsc::Sender<McpPartialResult>>,
+    ) -> Result<serde_json::Value> {
+        self.ensure_connected().await?;
+
+        let id = self.next_request_id();
+        let token = serde_json::json!(id);
         let request = JsonRpcRequest::new(
-            self.next_request_id(),
+            id,
             "tools/call",
             serde_json::json!({
                 "name": name,
-                "arguments": arguments
+                "arguments": arguments,
+                "_meta": { "progressToken": token }
             }),
         );
 
-        let response = self
-            .transport
-            .send_request(request, DEFAULT_TIMEOUT)
-            .await
-            .context("Failed to send tools/call request")?;
+        let Self {
+            transport,
+            notifications,
+            ..
+        } = self;
+        let mut partials = Vec::new();
+        let mut forward = |notification: JsonRpcRequest| {
+            let params = notification.params();
+            if notification.method() != "notifications/progress"
+                || params.get("progressToken") != Some(&token)
+            {
+                return;
+            }
+            let partial = McpPartialResult::from_params(params);
+            if let Some(updates) = updates {
+                let _ = updates.try_send(partial.clone());
+            }
+            partials.push(partial);
+        };
+
+        let response = {
+            let response_fut = transport.send_request(request, DEFAULT_TIMEOUT);
+            tokio::pin!(response_fut);
+            loop {
+                tokio::select! {
+                    biased;
+
+                    Some(notification) = async {
+                        match notifications.as_mut() {
+                            Some(rx) => rx.recv().await,
+                            None => None,
+                        }
+                    } => forward(notification),
+                    response = &mut response_fut => break response,
+                }
+            }
+        };
 
+        // Pick up notifications that raced the response
+        if let Some(rx) = notifications.as_mut() {
+            while let Ok(notification) = rx.try_recv() {
+                forward(notification);
+            }
+        }
+
+        if response.is_err() {
+            self.check_alive();
+        }
+        let response = response.context("Failed to send tools/call request")?;
+        let result = Self::tool_call_result(response)?;
+        Ok(aggregate_partial_results(&partials, result))
+    }
+
+    /// Extracts the result of a `tools/call` response.
+    fn tool_call_result(response: JsonRpcResponse) -> Result<serde_json::Value> {
         if response.is_error() {
             let error = response.error().unwrap();
             return Err(anyhow!(
@@ -673,15 +1214,57 @@ impl McpClient {
     ///
     /// Returns an error if the request fails.
     pub async fn send_request(&mut self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
-        if !self.is_connected() {
-            return Err(anyhow!("Not connected to server"));
-        }
+        self.ensure_connected().await?;
 
-        self.transport
-            .send_request(request, DEFAULT_TIMEOUT)
+        self.request(request)
             .await
             .context("Failed to send request")
     }
+
+    /// Calls a tool and converts the outcome to a [`ToolResult`].
+    ///
+    /// Text content blocks are joined into the output; a result flagged
+    /// `isError` and any failure to reach the server (including while it
+    /// is disconnected) become [`ToolResult::Error`].
+    pub async fn execute_tool(&mut self, name: &str, arguments: serde_json::Value) -> ToolResult {
+        Self::to_tool_result(self.call_tool(name, arguments).await)
+    }
+
+    /// Calls a tool like [`execute_tool`](Self::execute_tool), forwarding
+    /// partial results to `updates` while it runs.
+    pub async fn execute_tool_streaming(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        updates: mpsc::Sender<McpPartialResult>,
+    ) -> ToolResult {
+        Self::to_tool_result(self.call_tool_streaming(name, arguments, updates).await)
+    }
+
+    /// Converts the outcome of a tool call to a [`ToolResult`].
+    fn to_tool_result(outcome: Result<serde_json::Value>) -> ToolResult {
+        match outcome {
+            Ok(result) => {
+                let text = result
+                    .get("content")
+                    .and_then(|c| c.as_array())
+                    .map(|blocks| {
+                        blocks
+                            .iter()
+                            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_else(|| result.to_string());
+                if result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+                    ToolResult::Error(text)
+                } else {
+                    ToolResult::Success(text)
+                }
+            }
+            Err(e) => ToolResult::Error(format!("{:#}", e)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -692,6 +1275,68 @@ mod tests {
     // is_absolute_path tests - Cross-platform path detection
     // =============================================================================
 
+    #[test]
+    fn test_partial_result_text() {
+        let partial = McpPartialResult::from_params(&serde_json::json!({
+            "progressToken": 1,
+            "progress": 1,
+            "content": [{"type": "text", "text": "hit 1"}, {"type": "text", "text": "hit 2"}]
+        }));
+        assert_eq!(partial.progress, Some(1.0));
+        assert_eq!(partial.text(), "hit 1\nhit 2");
+
+        let message_only = McpPartialResult::from_params(&serde_json::json!({
+            "progressToken": 1,
+            "progress": 5,
+            "total": 10,
+            "message": "Indexing"
+        }));
+        assert!(message_only.content.is_empty());
+        assert_eq!(message_only.text(), "Indexing");
+    }
+
+    #[test]
+    fn test_aggregate_partial_results() {
+        let partial = |text: &str| McpPartialResult {
+            content: vec![serde_json::json!({"type": "text", "text": text})],
+            ..McpPartialResult::default()
+        };
+        let result = serde_json::json!({"content": [{"type": "text", "text": "done"}]});
+
+        let aggregated = aggregate_partial_results(&[partial("a"), partial("b")], result.clone());
+        let texts: Vec<&str> = aggregated["content"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["a", "b", "done"]);
+
+        // Progress without content leaves the result unchanged
+        let progress_only = McpPartialResult {
+            message: Some("working".to_string()),
+            ..McpPartialResult::default()
+        };
+        assert_eq!(
+            aggregate_partial_results(&[progress_only], result.clone()),
+            result
+        );
+    }
+
+    #[test]
+    fn test_reconnect_backoff_doubles_up_to_max() {
+        let policy = ReconnectPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(3),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+        assert_eq!(policy.backoff(40), Duration::from_secs(3));
+    }
+
     #[test]
     fn test_unix_absolute_path() {
         assert!(is_absolute_path("/bin/bash"));
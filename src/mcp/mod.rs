@@ -2,6 +2,9 @@
 //!
 //! This module implements the Model Context Protocol for communication with
 //! external tools and services.
+//!
+//! Tools from every server are exposed as `mcp__<server>__<tool>`, so two
+//! servers may offer a tool with the same name without ambiguity.
 
 pub mod client;
 pub mod protocol;
@@ -10,14 +13,92 @@ pub mod transport;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Deserialize)]
+use crate::tools::ToolResult;
+use anyhow::anyhow;
+use client::{
+    McpClient, McpConnectionEvent, McpPartialResult, McpResource, McpResourceContent,
+    ReconnectPolicy, DEFAULT_MAX_RECONNECT_ATTEMPTS,
+};
+use tokio::sync::mpsc;
+
+/// Prefix shared by the names of all MCP tools.
+pub const MCP_TOOL_PREFIX: &str = "mcp__";
+
+/// Separator between the server and tool segments of an MCP tool name.
+const SEGMENT_SEPARATOR: &str = "__";
+
+/// Longest tool name the API accepts.
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Returns whether `server` can be used in qualified tool names.
+///
+/// Server names cannot contain `__` or start or end with `_`: `a_` with
+/// tool `b` and `a` with tool `_b` would both qualify to `mcp__a___b`.
+#[must_use]
+pub fn is_valid_server_name(server: &str) -> bool {
+    !server.is_empty()
+        && !server.contains(SEGMENT_SEPARATOR)
+        && !server.starts_with('_')
+        && !server.ends_with('_')
+}
+
+/// Returns whether `tool` can be the tool segment of a qualified name.
+fn is_valid_tool_segment(tool: &str) -> bool {
+    !tool.is_empty() && !tool.starts_with('_')
+}
+
+/// Returns the name a server's tool is exposed under: `mcp__<server>__<tool>`.
+///
+/// Returns `None` if the server or tool name would make the qualified name
+/// ambiguous, or if it would exceed [`MAX_TOOL_NAME_LEN`].
+#[must_use]
+pub fn qualified_tool_name(server: &str, tool: &str) -> Option<String> {
+    let name = format!("{MCP_TOOL_PREFIX}{server}{SEGMENT_SEPARATOR}{tool}");
+    (is_valid_server_name(server) && is_valid_tool_segment(tool) && name.len() <= MAX_TOOL_NAME_LEN)
+        .then_some(name)
+}
+
+/// Splits `mcp__<server>__<tool>` into its server and tool segments.
+///
+/// Server names cannot contain `__`, so the first separator after the
+/// prefix ends the server segment; the tool segment may contain `__`.
+/// Returns `None` for names that are not server-qualified MCP tools,
+/// including names [`qualified_tool_name`] would reject.
+#[must_use]
+pub fn parse_tool_name(name: &str) -> Option<(&str, &str)> {
+    let (server, tool) = name
+        .strip_prefix(MCP_TOOL_PREFIX)?
+        .split_once(SEGMENT_SEPARATOR)?;
+    (is_valid_server_name(server) && is_valid_tool_segment(tool) && name.len() <= MAX_TOOL_NAME_LEN)
+        .then_some((server, tool))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct McpServerConfig {
     pub transport: McpTransport,
     #[serde(default)]
     pub enabled: bool,
+    /// Reconnect attempts after a disconnect before giving up on the server.
+    #[serde(default = "default_max_reconnect_attempts")]
+    pub max_reconnect_attempts: u32,
+}
+
+fn default_max_reconnect_attempts() -> u32 {
+    DEFAULT_MAX_RECONNECT_ATTEMPTS
+}
+
+impl McpServerConfig {
+    /// Returns the reconnect policy for this server.
+    #[must_use]
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        ReconnectPolicy {
+            max_attempts: self.max_reconnect_attempts,
+            ..ReconnectPolicy::default()
+        }
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum McpTransport {
     Stdio {
@@ -46,13 +127,143 @@ pub struct McpTool {
     pub input_schema: serde_json::Value,
 }
 
+/// Partial output from a running MCP tool, for display in the timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpToolProgress {
+    /// Qualified `mcp__<server>__<tool>` name of the running tool
+    pub tool: String,
+    /// Text of the partial result
+    pub text: String,
+}
+
 pub struct McpManager {
+    /// Tools from all servers, under their server-qualified names
     tools: Vec<McpTool>,
+    /// Running servers by name
+    clients: HashMap<String, McpClient>,
+    /// Receives connection notices from every server, if subscribed
+    events: Option<mpsc::UnboundedSender<McpConnectionEvent>>,
 }
 
 impl McpManager {
     pub fn new() -> Self {
-        Self { tools: Vec::new() }
+        Self {
+            tools: Vec::new(),
+            clients: HashMap::new(),
+            events: None,
+        }
+    }
+
+    /// Sends the disconnect and reconnect notices of the servers started by
+    /// [`initialize`](Self::initialize) to `events`.
+    #[must_use]
+    pub fn with_connection_events(
+        mut self,
+        events: mpsc::UnboundedSender<McpConnectionEvent>,
+    ) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Registers a started server and its tools.
+    ///
+    /// Each tool is exposed as `mcp__<server>__<tool>`. A tool name another
+    /// server already provides is logged as a collision; both stay callable
+    /// by their qualified names. Tools whose qualified name would be
+    /// ambiguous or longer than [`MAX_TOOL_NAME_LEN`] are logged and skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server name is empty, contains `__`, starts
+    /// or ends with `_` (which would make qualified names ambiguous) or is
+    /// already registered, or if its tools cannot be listed. The client is
+    /// stopped in that case.
+    pub async fn add_server(&mut self, mut client: McpClient) -> anyhow::Result<()> {
+        let server = client.name().to_string();
+        let tools = if !is_valid_server_name(&server) {
+            Err(anyhow!(
+                "Invalid MCP server name '{}': names must be non-empty, cannot contain '__' \
+                 and cannot start or end with '_'",
+                server
+            ))
+        } else if self.clients.contains_key(&server) {
+            Err(anyhow!("MCP server '{}' is already registered", server))
+        } else {
+            client.list_tools().await
+        };
+        let tools = match tools {
+            Ok(tools) => tools,
+            Err(e) => {
+                client.force_stop().await;
+                return Err(e);
+            }
+        };
+        self.register_tools(&server, tools);
+        self.clients.insert(server, client);
+        Ok(())
+    }
+
+    /// Replaces a server's tools with the ones it listed most recently.
+    ///
+    /// Call after the server reconnects, since a restarted server may
+    /// provide different tools.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server is not registered.
+    pub fn refresh_tools(&mut self, server: &str) -> anyhow::Result<()> {
+        let client = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("Unknown MCP server '{}'", server))?;
+        let tools = client.tools().to_vec();
+        self.tools
+            .retain(|t| parse_tool_name(&t.name).map_or(true, |(s, _)| s != server));
+        self.register_tools(server, tools);
+        Ok(())
+    }
+
+    /// Adds a server's tools under their qualified names.
+    fn register_tools(&mut self, server: &str, tools: Vec<client::McpTool>) {
+        for tool in tools {
+            let Some(name) = qualified_tool_name(server, &tool.name) else {
+                tracing::warn!(
+                    server = %server,
+                    "Skipping MCP tool '{}': its qualified name would be ambiguous or longer than {} characters",
+                    tool.name,
+                    MAX_TOOL_NAME_LEN
+                );
+                continue;
+            };
+            if self.tools.iter().any(|t| t.name == name) {
+                tracing::warn!(
+                    server = %server,
+                    "MCP server lists tool '{}' more than once; keeping the first",
+                    tool.name
+                );
+                continue;
+            }
+            for other in &self.tools {
+                let Some((other_server, other_tool)) = parse_tool_name(&other.name) else {
+                    continue;
+                };
+                if other_tool == tool.name {
+                    tracing::warn!(
+                        "MCP tool '{}' is provided by servers '{}' and '{}'; call it as '{}' or '{}'",
+                        tool.name,
+                        other_server,
+                        server,
+                        other.name,
+                        name
+                    );
+                }
+            }
+            self.tools.push(McpTool {
+                name,
+                description: tool.description,
+                input_schema: tool.input_schema,
+            });
+        }
     }
 
     pub async fn initialize(
@@ -64,10 +275,24 @@ impl McpManager {
                 continue;
             }
 
+            let policy = config.reconnect_policy();
             match config.transport {
                 McpTransport::Stdio { command, args, env } => {
                     tracing::info!("Starting MCP server '{}': {} {:?}", name, command, args);
-                    let _ = (command, args, env);
+                    let _ = env;
+                    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                    let mut client =
+                        McpClient::new(name.as_str(), &command, args).with_reconnect_policy(policy);
+                    if let Some(events) = &self.events {
+                        client = client.with_connection_events(events.clone());
+                    }
+                    let started = match client.start().await {
+                        Ok(()) => self.add_server(client).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = started {
+                        tracing::warn!("MCP server '{}' unavailable: {:#}", name, e);
+                    }
                 }
                 McpTransport::Sse { url, headers } => {
                     tracing::info!("Connecting to MCP SSE server '{}': {}", name, url);
@@ -87,13 +312,183 @@ impl McpManager {
         &self.tools
     }
 
+    /// Returns whether any server declared the resources capability.
+    #[must_use]
+    pub fn has_resources(&self) -> bool {
+        self.clients
+            .values()
+            .any(|client| client.capabilities().is_some_and(|c| c.resources.is_some()))
+    }
+
+    /// Calls a tool by its qualified `mcp__<server>__<tool>` name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not server-qualified, the server is
+    /// not registered, or the call fails.
     pub async fn call_tool(
-        &self,
-        _tool_name: &str,
-        _input: serde_json::Value,
+        &mut self,
+        tool_name: &str,
+        input: serde_json::Value,
     ) -> anyhow::Result<serde_json::Value> {
-        Ok(serde_json::json!({}))
+        let (client, tool) = self.route(tool_name)?;
+        client.call_tool(tool, input).await
+    }
+
+    /// Calls a tool by its qualified name and converts the outcome to a
+    /// [`ToolResult`].
+    pub async fn execute_tool(&mut self, tool_name: &str, input: serde_json::Value) -> ToolResult {
+        match self.route(tool_name) {
+            Ok((client, tool)) => client.execute_tool(tool, input).await,
+            Err(e) => ToolResult::Error(e.to_string()),
+        }
     }
+
+    /// Calls a tool like [`execute_tool`](Self::execute_tool), forwarding the
+    /// partial results the server streams to `updates`.
+    pub async fn execute_tool_streaming(
+        &mut self,
+        tool_name: &str,
+        input: serde_json::Value,
+        updates: mpsc::Sender<McpPartialResult>,
+    ) -> ToolResult {
+        match self.route(tool_name) {
+            Ok((client, tool)) => client.execute_tool_streaming(tool, input, updates).await,
+            Err(e) => ToolResult::Error(e.to_string()),
+        }
+    }
+
+    /// Finds the client and bare tool name for a qualified tool name.
+    fn route<'a>(&mut self, tool_name: &'a str) -> anyhow::Result<(&mut McpClient, &'a str)> {
+        let (server, tool) = parse_tool_name(tool_name).ok_or_else(|| {
+            anyhow!(
+                "Invalid MCP tool name '{}': expected mcp__<server>__<tool>",
+                tool_name
+            )
+        })?;
+        let client = self
+            .clients
+            .get_mut(server)
+            .ok_or_else(|| anyhow!("Unknown MCP server '{}' for tool '{}'", server, tool_name))?;
+        Ok((client, tool))
+    }
+
+    /// Lists the resources of every server, as `(server, resource)` pairs
+    /// ordered by server name.
+    ///
+    /// Servers that fail to list resources (including servers without
+    /// resource support) are logged and skipped.
+    pub async fn list_resources(&mut self) -> Vec<(String, McpResource)> {
+        let mut servers: Vec<&String> = self.clients.keys().collect();
+        servers.sort();
+        let servers: Vec<String> = servers.into_iter().cloned().collect();
+
+        let mut resources = Vec::new();
+        for server in servers {
+            let Some(client) = self.clients.get_mut(&server) else {
+                continue;
+            };
+            match client.list_resources().await {
+                Ok(listed) => {
+                    resources.extend(listed.into_iter().map(|r| (server.clone(), r)));
+                }
+                Err(e) => tracing::debug!("MCP server '{}' resources unavailable: {:#}", server, e),
+            }
+        }
+        resources
+    }
+
+    /// Reads a resource from a server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server is not registered or the read fails.
+    pub async fn read_resource(
+        &mut self,
+        server: &str,
+        uri: &str,
+    ) -> anyhow::Result<Vec<McpResourceContent>> {
+        let client = self
+            .clients
+            .get_mut(server)
+            .ok_or_else(|| anyhow!("Unknown MCP server '{}'", server))?;
+        client.read_resource(uri).await
+    }
+
+    /// Runs the `read_mcp_resource` tool.
+    ///
+    /// With `server` and `uri`, returns the resource's content. Without a
+    /// `uri`, lists the available resources (of `server` if given).
+    pub async fn read_resource_tool(&mut self, input: &serde_json::Value) -> ToolResult {
+        let server = input.get("server").and_then(|v| v.as_str());
+        let uri = input.get("uri").and_then(|v| v.as_str());
+
+        let Some(uri) = uri else {
+            let listed: Vec<String> = self
+                .list_resources()
+                .await
+                .into_iter()
+                .filter(|(name, _)| server.map_or(true, |s| s == name))
+                .map(|(name, resource)| format_resource_entry(&name, &resource))
+                .collect();
+            return if listed.is_empty() {
+                ToolResult::Success("No MCP resources available".to_string())
+            } else {
+                ToolResult::Success(listed.join("\n"))
+            };
+        };
+        let Some(server) = server else {
+            return ToolResult::Error("Missing server for MCP resource".to_string());
+        };
+
+        match self.read_resource(server, uri).await {
+            Ok(contents) => ToolResult::Success(format_resource_contents(&contents)),
+            Err(e) => ToolResult::Error(format!("{:#}", e)),
+        }
+    }
+
+    /// Stops all servers.
+    pub async fn shutdown(&mut self) {
+        for client in self.clients.values_mut() {
+            let _ = client.stop().await;
+        }
+        self.clients.clear();
+        self.tools.clear();
+    }
+}
+
+/// Formats one line of the resource listing.
+fn format_resource_entry(server: &str, resource: &McpResource) -> String {
+    let mut line = format!("{} {} ({})", server, resource.uri, resource.name);
+    if let Some(mime) = &resource.mime_type {
+        line.push_str(&format!(" [{}]", mime));
+    }
+    if let Some(description) = &resource.description {
+        line.push_str(&format!(" - {}", description));
+    }
+    line
+}
+
+/// Formats resource contents for the model.
+///
+/// Text is returned as-is; binary content is summarized, since base64 is of
+/// no use in the conversation.
+#[must_use]
+pub fn format_resource_contents(contents: &[McpResourceContent]) -> String {
+    contents
+        .iter()
+        .map(|content| match (&content.text, &content.blob) {
+            (Some(text), _) => text.clone(),
+            (None, Some(blob)) => format!(
+                "[binary resource {} ({}, {} bytes base64)]",
+                content.uri,
+                content.mime_type.as_deref().unwrap_or("unknown type"),
+                blob.len()
+            ),
+            (None, None) => format!("[empty resource {}]", content.uri),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
 impl Default for McpManager {
@@ -30,6 +30,7 @@ use anyhow::{anyhow, Context, Result};
 use std::collections::HashMap;
 use std::future::Future;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -47,6 +48,15 @@ use tokio::sync::{mpsc, oneshot, Mutex};
 /// before the child is ready to read, causing timeouts.
 const SPAWN_WARMUP_MS: u64 = 50;
 
+/// Error message for requests to a server whose process has gone away.
+const SERVER_CLOSED: &str = "MCP server closed the connection";
+
+/// Server notifications buffered until the client reads them.
+///
+/// Notifications that arrive while the buffer is full are dropped, so a
+/// chatty server cannot grow memory without bound between tool calls.
+pub const NOTIFICATION_BUFFER: usize = 256;
+
 /// Transport trait for MCP communication.
 ///
 /// Implementations of this trait provide the communication layer
@@ -118,6 +128,10 @@ pub struct StdioTransport {
     child: Option<Child>,
     writer_tx: Option<mpsc::Sender<WriterMessage>>,
     pending_requests: Arc<Mutex<HashMap<String, oneshot::Sender<Result<JsonRpcResponse>>>>>,
+    /// Receiver for notifications sent by the server, until taken.
+    notifications: Option<mpsc::Receiver<JsonRpcRequest>>,
+    /// Cleared by the I/O tasks when the server's pipes close.
+    alive: Arc<AtomicBool>,
 }
 
 impl StdioTransport {
@@ -135,9 +149,30 @@ impl StdioTransport {
             child: None,
             writer_tx: None,
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            notifications: None,
+            alive: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Returns whether the server process is running and its pipes are open.
+    ///
+    /// Becomes `false` as soon as the server exits or closes stdout, so a
+    /// crash is noticed without waiting for a request to time out.
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        self.child.is_some() && self.alive.load(Ordering::SeqCst)
+    }
+
+    /// Takes the receiver for notifications sent by the server.
+    ///
+    /// Server notifications (such as `notifications/progress`) arrive
+    /// interleaved with responses; they are forwarded here in the order
+    /// received, up to [`NOTIFICATION_BUFFER`] unread. Returns `None` before
+    /// `start()` or once taken.
+    pub fn take_notifications(&mut self) -> Option<mpsc::Receiver<JsonRpcRequest>> {
+        self.notifications.take()
+    }
+
     /// Spawns the child process and starts I/O tasks.
     async fn spawn_and_start(&mut self) -> Result<()> {
         let mut child = Command::new(&self.command)
@@ -145,6 +180,7 @@ impl StdioTransport {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::null())
+            .kill_on_drop(true)
             .spawn()
             .with_context(|| format!("Failed to spawn command: {}", self.command))?;
 
@@ -160,6 +196,16 @@ impl StdioTransport {
         // Clone pending_requests for the reader task
         let pending_requests = Arc::clone(&self.pending_requests);
 
+        // Fresh liveness flag so tasks from a previous run cannot clear it
+        let alive = Arc::new(AtomicBool::new(true));
+        self.alive = Arc::clone(&alive);
+        let writer_alive = Arc::clone(&alive);
+
+        // Create channel for server notifications
+        let (notification_tx, notification_rx) =
+            mpsc::channel::<JsonRpcRequest>(NOTIFICATION_BUFFER);
+        self.notifications = Some(notification_rx);
+
         // Spawn writer task
         let mut stdin = stdin;
         tokio::spawn(async move {
@@ -182,6 +228,7 @@ impl StdioTransport {
                     WriterMessage::Stop => break,
                 }
             }
+            writer_alive.store(false, Ordering::SeqCst);
         });
 
         // Spawn reader task
@@ -198,6 +245,16 @@ impl StdioTransport {
                             continue;
                         }
 
+                        // Notifications carry a method and no ID
+                        if let Ok(notification) = serde_json::from_str::<JsonRpcRequest>(trimmed) {
+                            if notification.is_notification() {
+                                if notification_tx.try_send(notification).is_err() {
+                                    tracing::debug!("MCP notification buffer full; dropping");
+                                }
+                                continue;
+                            }
+                        }
+
                         match serde_json::from_str::<JsonRpcResponse>(trimmed) {
                             Ok(response) => {
                                 // Find the pending request by ID
@@ -220,6 +277,14 @@ impl StdioTransport {
                     }
                 }
             }
+
+            // The server is gone: fail waiting requests instead of letting
+            // them run into their timeouts
+            alive.store(false, Ordering::SeqCst);
+            let mut pending = pending_requests.lock().await;
+            for (_, tx) in pending.drain() {
+                let _ = tx.send(Err(anyhow!(SERVER_CLOSED)));
+            }
         });
 
         // Brief warmup delay to ensure child process is ready to receive messages
@@ -238,6 +303,8 @@ impl Transport for StdioTransport {
     }
 
     async fn stop(&mut self) -> Result<()> {
+        self.alive.store(false, Ordering::SeqCst);
+
         // Send stop message to writer
         if let Some(tx) = self.writer_tx.take() {
             let _ = tx.send(WriterMessage::Stop).await;
@@ -279,9 +346,12 @@ impl Transport for StdioTransport {
         // Create response channel
         let (response_tx, response_rx) = oneshot::channel();
 
-        // Register pending request
+        // Register pending request, unless the server has already gone
         {
             let mut pending = self.pending_requests.lock().await;
+            if !self.alive.load(Ordering::SeqCst) {
+                return Err(anyhow!(SERVER_CLOSED));
+            }
             pending.insert(id_str, response_tx);
         }
 
@@ -0,0 +1,194 @@
+//! Fixed set of tool calls a session may run without prompting.
+//!
+//! An [`Allowlist`] replaces prompting entirely: calls it admits are allowed
+//! and every other call is denied. Unattended runs such as `--auto` use one
+//! so nothing waits on a prompt that nobody will answer.
+//!
+//! The allowlist admits:
+//!
+//! - tools classified as read-only, such as `read_file` or `grep`
+//! - bash commands on the strict [`read_only`](super::read_only) allowlist
+//! - bash commands whose arguments start with one of the configured
+//!   command prefixes, such as `cargo test`
+//! - `write_file` and `edit` on paths inside the write root, if one is set
+//!
+//! # Example
+//!
+//! ```
+//! use patina::permissions::allowlist::Allowlist;
+//!
+//! let allowlist = Allowlist::new("/work/project")
+//!     .with_write_root("/work/project")
+//!     .with_commands(&["cargo test".to_string()]);
+//!
+//! assert!(allowlist.allows("bash", Some("cargo test --lib")));
+//! assert!(allowlist.allows("edit", Some("src/lib.rs")));
+//! assert!(!allowlist.allows("edit", Some("../other/src/lib.rs")));
+//! assert!(!allowlist.allows("bash", Some("cargo publish")));
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use super::patterns::normalize_path;
+use super::read_only::{is_read_only_command, split_argv};
+use crate::tools::parallel::{classify_tool, ToolSafetyClass};
+
+/// Tools that write to the file given as their permission input.
+const WRITE_TOOLS: &[&str] = &["write_file", "edit"];
+
+/// Tool calls allowed without prompting; anything else is denied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Allowlist {
+    /// Directory relative file paths are resolved against.
+    working_dir: PathBuf,
+    /// Directory that `write_file` and `edit` must stay within, if allowed.
+    write_root: Option<PathBuf>,
+    /// Additional bash commands, as argument prefixes.
+    commands: Vec<Vec<String>>,
+}
+
+impl Allowlist {
+    /// Creates an allowlist of read-only tools and commands.
+    #[must_use]
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Also allows `write_file` and `edit` on paths inside `root`.
+    ///
+    /// Paths are compared lexically; the tool executor still applies its own
+    /// confinement and symlink checks.
+    #[must_use]
+    pub fn with_write_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.write_root = Some(root.into());
+        self
+    }
+
+    /// Also allows bash commands that start with one of `commands`.
+    ///
+    /// Each entry is split into arguments and must match the start of the
+    /// command argument for argument, so `cargo test` admits
+    /// `cargo test --lib` but not `cargo tests` or `env cargo test`. Entries
+    /// that use shell syntax are ignored.
+    #[must_use]
+    pub fn with_commands(mut self, commands: &[String]) -> Self {
+        for command in commands {
+            match split_argv(command.trim()) {
+                Some(argv) => self.commands.push(argv),
+                None => warn!(command = %command, "Ignoring allowed command with shell syntax"),
+            }
+        }
+        self
+    }
+
+    /// Returns whether a tool call is on the allowlist.
+    #[must_use]
+    pub fn allows(&self, tool_name: &str, tool_input: Option<&str>) -> bool {
+        match (tool_name, tool_input) {
+            ("bash", Some(command)) => {
+                is_read_only_command(command) || self.allows_command(command)
+            }
+            ("bash", None) => false,
+            (tool, Some(path)) if WRITE_TOOLS.contains(&tool) => self.allows_write(path),
+            (tool, None) if WRITE_TOOLS.contains(&tool) => false,
+            (tool, _) => classify_tool(tool) == ToolSafetyClass::ReadOnly,
+        }
+    }
+
+    /// Returns whether `command` starts with one of the configured commands.
+    fn allows_command(&self, command: &str) -> bool {
+        let Some(argv) = split_argv(command.trim()) else {
+            return false;
+        };
+        self.commands.iter().any(|prefix| argv.starts_with(prefix))
+    }
+
+    /// Returns whether `path` resolves inside the write root.
+    fn allows_write(&self, path: &str) -> bool {
+        let Some(root) = &self.write_root else {
+            return false;
+        };
+        let path = normalize(&self.working_dir.join(path));
+        let root = normalize(root);
+        !path.starts_with("..") && path.starts_with(&root)
+    }
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    PathBuf::from(normalize_path(&path.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_read_only_tools_and_commands() {
+        let allowlist = Allowlist::new("/work/project");
+
+        assert!(allowlist.allows("read_file", Some("/etc/hosts")));
+        assert!(allowlist.allows("grep", Some("fn main")));
+        assert!(allowlist.allows("bash", Some("git status")));
+        assert!(!allowlist.allows("bash", Some("cargo test")));
+        assert!(!allowlist.allows("bash", None));
+        assert!(!allowlist.allows("mcp__server__tool", Some("{}")));
+        // Writes need a write root
+        assert!(!allowlist.allows("write_file", Some("notes.txt")));
+    }
+
+    #[test]
+    fn test_write_root_confines_writes() {
+        let allowlist = Allowlist::new("/work/project/crate").with_write_root("/work/project");
+
+        for path in [
+            "src/lib.rs",
+            "./src/../Cargo.toml",
+            "../README.md",
+            "/work/project/src/main.rs",
+        ] {
+            assert!(allowlist.allows("edit", Some(path)), "{path}");
+            assert!(allowlist.allows("write_file", Some(path)), "{path}");
+        }
+        for path in [
+            "../../outside.txt",
+            "/etc/passwd",
+            "/work/project-other/x",
+            "/work/project/../other/x",
+        ] {
+            assert!(!allowlist.allows("edit", Some(path)), "{path}");
+            assert!(!allowlist.allows("write_file", Some(path)), "{path}");
+        }
+        assert!(!allowlist.allows("edit", None));
+    }
+
+    #[test]
+    fn test_commands_match_argument_prefixes() {
+        let allowlist = Allowlist::new("/work/project").with_commands(&[
+            "cargo test".to_string(),
+            "npm run test".to_string(),
+            "make; rm -rf ~".to_string(),
+        ]);
+
+        for command in ["cargo test", "cargo test --lib auth", "npm run test"] {
+            assert!(allowlist.allows("bash", Some(command)), "{command}");
+        }
+        for command in [
+            "cargo tests",
+            "cargo build",
+            "env cargo test",
+            "cargo test; rm -rf ~",
+            "cargo test && rm -rf ~",
+            "cargo test $(rm -rf ~)",
+            "npm run build",
+            "make",
+        ] {
+            assert!(!allowlist.allows("bash", Some(command)), "{command}");
+        }
+    }
+}
@@ -27,20 +27,34 @@
 //! // Check if a tool execution is allowed
 //! let decision = manager.check("Bash", Some("git status"));
 //! assert!(matches!(decision, PermissionDecision::Allowed));
+//!
+//! // Rules can also be written as `tool:pattern`
+//! let rule = PermissionRule::parse("read_file:src/**", true).unwrap();
+//! assert!(rule.matches("read_file", Some("src/app/mod.rs")));
 //! ```
 
+pub mod allowlist;
 pub mod patterns;
+pub mod read_only;
 
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
-use patterns::matches_pattern;
+use crate::tools::parallel::{classify_tool, contains_shell_operators, ToolSafetyClass};
+use crate::types::config::PermissionMode;
+use allowlist::Allowlist;
+use patterns::{has_parent_component, matches_pattern, normalize_path};
+use read_only::is_read_only_command;
+
+/// Tools whose permission input is a file path.
+const PATH_TOOLS: &[&str] = &["read_file", "write_file", "list_files", "edit"];
 
 /// The decision result from checking permissions.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -56,7 +70,7 @@ pub enum PermissionDecision {
 }
 
 /// User response to a permission prompt.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PermissionResponse {
     /// Allow this tool execution once (session grant).
     AllowOnce,
@@ -64,6 +78,25 @@ pub enum PermissionResponse {
     AllowAlways,
     /// Deny this tool execution.
     Deny,
+    /// Deny this tool execution and tell the model why.
+    DenyWithReason(String),
+}
+
+impl PermissionResponse {
+    /// Returns true if the response lets the tool run.
+    #[must_use]
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::AllowOnce | Self::AllowAlways)
+    }
+
+    /// Returns the reason the user gave for denying, if any.
+    #[must_use]
+    pub fn denial_reason(&self) -> Option<&str> {
+        match self {
+            Self::DenyWithReason(reason) => Some(reason),
+            _ => None,
+        }
+    }
 }
 
 /// A permission rule that controls tool execution.
@@ -94,7 +127,47 @@ impl PermissionRule {
         }
     }
 
+    /// Parses a rule written as `tool` or `tool:pattern`.
+    ///
+    /// The pattern is matched against the tool's extracted input: the
+    /// command for `bash`, the path for file tools.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use patina::permissions::PermissionRule;
+    ///
+    /// let rule = PermissionRule::parse("bash:git diff*", true).unwrap();
+    /// assert!(rule.matches("bash", Some("git diff HEAD")));
+    /// assert!(!rule.matches("bash", Some("git push")));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool name or the pattern after `:` is empty.
+    pub fn parse(spec: &str, allow: bool) -> Result<Self> {
+        let (tool, pattern) = match spec.split_once(':') {
+            Some((tool, pattern)) => (tool.trim(), Some(pattern.trim())),
+            None => (spec.trim(), None),
+        };
+        if tool.is_empty() {
+            anyhow::bail!("permission rule '{}' has no tool name", spec);
+        }
+        if pattern.is_some_and(str::is_empty) {
+            anyhow::bail!("permission rule '{}' has an empty pattern", spec);
+        }
+        Ok(Self::new(tool, pattern, allow))
+    }
+
     /// Checks if this rule matches the given tool and input.
+    ///
+    /// A wildcard allow rule for `bash` never matches a command that chains
+    /// others with shell operators, so `git diff*` cannot approve
+    /// `git diff; git push`. A trailing `*` after a word only matches at a
+    /// word boundary, so `git diff*` does not match `git difftool` either.
+    ///
+    /// Paths for file tools are normalized first; an allow rule never matches
+    /// a path with a `..` component. Deny rules match regardless.
     #[must_use]
     pub fn matches(&self, tool_name: &str, tool_input: Option<&str>) -> bool {
         // Check tool pattern
@@ -104,6 +177,21 @@ impl PermissionRule {
 
         // If rule has input pattern, check it
         match (&self.input_pattern, tool_input) {
+            (Some(pattern), Some(input)) if tool_name == "bash" => {
+                if self.allow
+                    && (pattern.contains('*') || pattern.contains('?'))
+                    && contains_shell_operators(input)
+                {
+                    return false;
+                }
+                matches_command(pattern, input)
+            }
+            (Some(pattern), Some(input)) if PATH_TOOLS.contains(&tool_name) => {
+                if self.allow && has_parent_component(input) {
+                    return false;
+                }
+                matches_pattern(pattern, &normalize_path(input))
+            }
             (Some(pattern), Some(input)) => matches_pattern(pattern, input),
             (Some(_), None) => false, // Rule requires input but none provided
             (None, _) => true,        // No input pattern required
@@ -111,6 +199,15 @@ impl PermissionRule {
     }
 }
 
+impl fmt::Display for PermissionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.input_pattern {
+            Some(pattern) => write!(f, "{}:{}", self.tool_pattern, pattern),
+            None => write!(f, "{}", self.tool_pattern),
+        }
+    }
+}
+
 /// Configuration for permission storage.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PermissionConfig {
@@ -169,12 +266,18 @@ impl SessionGrant {
 pub struct PermissionManager {
     /// Persistent permission rules.
     rules: Vec<PermissionRule>,
+    /// Rules for this session only (never saved).
+    session_rules: Vec<PermissionRule>,
     /// Session-based grants (cleared on restart).
     session_grants: Vec<SessionGrant>,
-    /// Path to the permissions config file.
+    /// Path to the JSON file persistent rules are saved to.
     config_path: Option<PathBuf>,
     /// Whether to skip all permission checks.
     skip_permissions: bool,
+    /// Baseline permission mode.
+    mode: PermissionMode,
+    /// Tool calls allowed without prompting, denying all others, if set.
+    allowlist: Option<Allowlist>,
     /// Tool-specific deny counts for rate limiting prompts.
     deny_counts: HashMap<String, u32>,
 }
@@ -191,40 +294,83 @@ impl PermissionManager {
     pub fn new() -> Self {
         Self {
             rules: Vec::new(),
+            session_rules: Vec::new(),
             session_grants: Vec::new(),
             config_path: None,
             skip_permissions: false,
+            mode: PermissionMode::default(),
+            allowlist: None,
             deny_counts: HashMap::new(),
         }
     }
 
-    /// Creates a permission manager from a config file.
+    /// Creates a permission manager from a rules file.
     ///
     /// If the file doesn't exist, creates an empty manager that will
     /// save to that path when rules are added.
     ///
     /// # Errors
     ///
-    /// Returns an error if the config file exists but cannot be parsed.
+    /// Returns an error if the rules file exists but cannot be parsed.
     pub fn from_config_file(path: PathBuf) -> Result<Self> {
-        let rules = if path.exists() {
-            let content = fs::read_to_string(&path)?;
-            let config: PermissionConfig = toml::from_str(&content)?;
-            config.rules
+        let mut manager = Self::new();
+        manager.load_from(path)?;
+        Ok(manager)
+    }
+
+    /// Loads persistent rules from a JSON file and saves new rules there.
+    ///
+    /// Rules added before loading are kept after the loaded ones. If the file
+    /// doesn't exist, rules are read from a `.toml` file of the same name
+    /// written by earlier versions, if there is one; the next save writes
+    /// them as JSON. Session grants are never stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rules file exists but cannot be parsed.
+    pub fn load_from(&mut self, path: PathBuf) -> Result<()> {
+        let legacy_path = path.with_extension("toml");
+        let config: PermissionConfig = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        } else if legacy_path.exists() {
+            toml::from_str(&fs::read_to_string(&legacy_path)?)
+                .with_context(|| format!("Failed to parse {}", legacy_path.display()))?
         } else {
-            Vec::new()
+            PermissionConfig::default()
         };
 
-        Ok(Self {
-            rules,
-            session_grants: Vec::new(),
-            config_path: Some(path),
-            skip_permissions: false,
-            deny_counts: HashMap::new(),
-        })
+        let mut rules = config.rules;
+        rules.append(&mut self.rules);
+        self.rules = rules;
+        self.config_path = Some(path);
+        Ok(())
     }
 
-    /// Returns the default permissions config path.
+    /// Saves the persistent rules to a JSON file.
+    ///
+    /// The file is replaced atomically, and session rules are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file or its directory cannot be written.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let config = PermissionConfig {
+            rules: self.rules.clone(),
+        };
+        let content = serde_json::to_string_pretty(&config)?;
+
+        // Ensure parent directory exists
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        crate::util::atomic_write_blocking(path, content)?;
+        debug!(path = %path.display(), "Saved permission rules");
+        Ok(())
+    }
+
+    /// Returns the default permission rules path.
     ///
     /// # Errors
     ///
@@ -233,7 +379,7 @@ impl PermissionManager {
         let config_dir = directories::ProjectDirs::from("com", "patina", "patina")
             .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
 
-        Ok(config_dir.config_dir().join("permissions.toml"))
+        Ok(config_dir.config_dir().join("permissions.json"))
     }
 
     /// Sets whether to skip all permission checks.
@@ -250,13 +396,33 @@ impl PermissionManager {
         self.skip_permissions
     }
 
+    /// Sets the baseline permission mode.
+    pub fn set_mode(&mut self, mode: PermissionMode) {
+        self.mode = mode;
+    }
+
+    /// Replaces prompting with `allowlist`: calls on it are allowed and all
+    /// others denied. Read-only mode and deny rules still apply first.
+    pub fn set_allowlist(&mut self, allowlist: Option<Allowlist>) {
+        self.allowlist = allowlist;
+    }
+
+    /// Returns the baseline permission mode.
+    #[must_use]
+    pub fn mode(&self) -> PermissionMode {
+        self.mode
+    }
+
     /// Checks if a tool execution is allowed.
     ///
     /// The check order is:
     /// 1. If skip_permissions is true, return Allowed
-    /// 2. Check persistent rules (deny rules first, then allow rules)
-    /// 3. Check session grants
-    /// 4. Return NeedsPrompt if no rule matches
+    /// 2. In read-only mode, deny tools that are not known to be read-only
+    /// 3. Check persistent deny rules
+    /// 4. With an allowlist set, return Allowed or Denied by the allowlist
+    /// 5. In full-access mode, return Allowed
+    /// 6. Check persistent allow rules, then session grants
+    /// 7. Return NeedsPrompt if no rule matches
     #[must_use]
     pub fn check(&self, tool_name: &str, tool_input: Option<&str>) -> PermissionDecision {
         // Check skip_permissions flag
@@ -264,8 +430,17 @@ impl PermissionManager {
             return PermissionDecision::Allowed;
         }
 
+        if self.mode == PermissionMode::ReadOnly && !is_read_only(tool_name, tool_input) {
+            debug!(
+                tool = %tool_name,
+                input = ?tool_input,
+                "Permission denied by read-only mode"
+            );
+            return PermissionDecision::Denied;
+        }
+
         // Check deny rules first (deny takes precedence)
-        for rule in &self.rules {
+        for rule in self.rules.iter().chain(&self.session_rules) {
             if !rule.allow && rule.matches(tool_name, tool_input) {
                 debug!(
                     tool = %tool_name,
@@ -277,8 +452,27 @@ impl PermissionManager {
             }
         }
 
+        if let Some(allowlist) = &self.allowlist {
+            let allowed = allowlist.allows(tool_name, tool_input);
+            debug!(
+                tool = %tool_name,
+                input = ?tool_input,
+                allowed,
+                "Permission decided by allowlist"
+            );
+            return if allowed {
+                PermissionDecision::Allowed
+            } else {
+                PermissionDecision::Denied
+            };
+        }
+
+        if self.mode == PermissionMode::FullAccess {
+            return PermissionDecision::Allowed;
+        }
+
         // Check allow rules
-        for rule in &self.rules {
+        for rule in self.rules.iter().chain(&self.session_rules) {
             if rule.allow && rule.matches(tool_name, tool_input) {
                 debug!(
                     tool = %tool_name,
@@ -319,6 +513,13 @@ impl PermissionManager {
         self.save_if_configured();
     }
 
+    /// Adds a rule that applies until the manager is dropped.
+    ///
+    /// Unlike [`add_rule`](Self::add_rule), the rule is never saved.
+    pub fn add_session_rule(&mut self, rule: PermissionRule) {
+        self.session_rules.push(rule);
+    }
+
     /// Adds a session grant for a specific tool execution.
     ///
     /// Session grants are not persisted and are cleared on restart.
@@ -347,7 +548,8 @@ impl PermissionManager {
     /// This method:
     /// - For `AllowOnce`: Adds a session grant
     /// - For `AllowAlways`: Adds a persistent rule
-    /// - For `Deny`: Does nothing (the caller should handle denial)
+    /// - For `Deny` and `DenyWithReason`: Counts the denial (the caller
+    ///   should handle denial)
     pub fn handle_response(
         &mut self,
         tool_name: &str,
@@ -363,7 +565,7 @@ impl PermissionManager {
                 let rule = PermissionRule::new(tool_name, tool_input, true);
                 self.add_rule(rule);
             }
-            PermissionResponse::Deny => {
+            PermissionResponse::Deny | PermissionResponse::DenyWithReason(_) => {
                 // Track denial count for rate limiting
                 *self.deny_counts.entry(tool_name.to_string()).or_insert(0) += 1;
             }
@@ -392,30 +594,44 @@ impl PermissionManager {
         &self.rules
     }
 
-    /// Saves rules to the config file if a path is configured.
+    /// Saves rules to the rules file if a path is configured.
     fn save_if_configured(&self) {
         if let Some(ref path) = self.config_path {
-            if let Err(e) = self.save_to_file(path) {
-                warn!(error = %e, path = %path.display(), "Failed to save permissions config");
+            if let Err(e) = self.save_to(path) {
+                warn!(error = %e, path = %path.display(), "Failed to save permission rules");
             }
         }
     }
+}
 
-    /// Saves rules to a file.
-    fn save_to_file(&self, path: &PathBuf) -> Result<()> {
-        let config = PermissionConfig {
-            rules: self.rules.clone(),
-        };
-        let content = toml::to_string_pretty(&config)?;
-
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
+/// Matches a bash command against a rule pattern.
+///
+/// A trailing `*` directly after a word stands for further arguments, not
+/// more characters of the same word: `git diff*` matches `git diff` and
+/// `git diff HEAD` but not `git difftool`.
+fn matches_command(pattern: &str, command: &str) -> bool {
+    let stem = pattern.trim_end_matches('*');
+    let word_end = stem.len() < pattern.len()
+        && stem
+            .chars()
+            .last()
+            .is_some_and(|c| !c.is_whitespace() && c != '?');
+    if word_end {
+        matches_pattern(stem, command) || matches_pattern(&format!("{stem} *"), command)
+    } else {
+        matches_pattern(pattern, command)
+    }
+}
 
-        fs::write(path, content)?;
-        debug!(path = %path.display(), "Saved permissions config");
-        Ok(())
+/// Returns whether a tool call is known not to modify anything.
+///
+/// Bash commands must be on the strict [`read_only`] allowlist; tools that
+/// cannot be classified (MCP tools, unknown tools) are treated as mutating.
+fn is_read_only(tool_name: &str, tool_input: Option<&str>) -> bool {
+    match (tool_name, tool_input) {
+        ("bash", Some(command)) => is_read_only_command(command),
+        ("bash", None) => false,
+        _ => classify_tool(tool_name) == ToolSafetyClass::ReadOnly,
     }
 }
 
@@ -428,6 +644,9 @@ pub struct PermissionRequest {
     pub tool_input: Option<String>,
     /// A human-readable description of what the tool will do.
     pub description: String,
+    /// What the tool is about to change, if known: a diff for edits or a
+    /// size summary for writes.
+    pub preview: Option<String>,
 }
 
 impl PermissionRequest {
@@ -438,8 +657,16 @@ impl PermissionRequest {
             tool_name: tool_name.to_string(),
             tool_input: tool_input.map(String::from),
             description: description.to_string(),
+            preview: None,
         }
     }
+
+    /// Attaches a preview of the change to the request.
+    #[must_use]
+    pub fn with_preview(mut self, preview: impl Into<String>) -> Self {
+        self.preview = Some(preview.into());
+        self
+    }
 }
 
 #[cfg(test)]
@@ -476,6 +703,76 @@ mod tests {
         assert!(!rule.matches("Bash", None));
     }
 
+    #[test]
+    fn test_rule_parse_tool_and_pattern() {
+        let rule = PermissionRule::parse("bash:git diff*", true).unwrap();
+        assert_eq!(rule, PermissionRule::new("bash", Some("git diff*"), true));
+        assert_eq!(rule.to_string(), "bash:git diff*");
+
+        let rule = PermissionRule::parse("read_file", true).unwrap();
+        assert_eq!(rule.input_pattern, None);
+        assert_eq!(rule.to_string(), "read_file");
+
+        assert!(PermissionRule::parse(":git *", true).is_err());
+        assert!(PermissionRule::parse("bash:", true).is_err());
+    }
+
+    #[test]
+    fn test_glob_rule_allows_git_diff_but_not_push() {
+        let mut manager = PermissionManager::new();
+        manager.add_rule(PermissionRule::parse("bash:git diff*", true).unwrap());
+
+        assert_eq!(
+            manager.check("bash", Some("git diff HEAD")),
+            PermissionDecision::Allowed
+        );
+        assert_eq!(
+            manager.check("bash", Some("git diff")),
+            PermissionDecision::Allowed
+        );
+        assert_eq!(
+            manager.check("bash", Some("git push")),
+            PermissionDecision::NeedsPrompt
+        );
+        assert_eq!(
+            manager.check("bash", Some("git diff; git push")),
+            PermissionDecision::NeedsPrompt
+        );
+        assert_eq!(
+            manager.check("bash", Some("git diff && git push origin main")),
+            PermissionDecision::NeedsPrompt
+        );
+        assert_eq!(
+            manager.check("bash", Some("git difftool --tool=evil")),
+            PermissionDecision::NeedsPrompt
+        );
+        assert_eq!(
+            manager.check("bash", Some("git diff &rm -rf ~")),
+            PermissionDecision::NeedsPrompt
+        );
+    }
+
+    #[test]
+    fn test_glob_rule_matches_paths() {
+        let rule = PermissionRule::parse("read_file:src/**", true).unwrap();
+        assert!(rule.matches("read_file", Some("src/main.rs")));
+        assert!(rule.matches("read_file", Some("src/app/state.rs")));
+        assert!(!rule.matches("read_file", Some("tests/unit.rs")));
+        assert!(!rule.matches("write_file", Some("src/main.rs")));
+        assert!(rule.matches("read_file", Some("./src//main.rs")));
+        assert!(!rule.matches("read_file", Some("src/../.env")));
+        assert!(!rule.matches("read_file", Some("src/../../etc/passwd")));
+
+        let deny = PermissionRule::parse("read_file:.env", false).unwrap();
+        assert!(deny.matches("read_file", Some("src/../.env")));
+    }
+
+    #[test]
+    fn test_glob_deny_rule_matches_chained_commands() {
+        let rule = PermissionRule::parse("bash:*git push*", false).unwrap();
+        assert!(rule.matches("bash", Some("git diff; git push")));
+    }
+
     #[test]
     fn test_rule_deny() {
         let rule = PermissionRule::new("Bash", Some("rm -rf *"), false);
@@ -588,6 +885,19 @@ mod tests {
         assert!(manager.rules().is_empty());
     }
 
+    #[test]
+    fn test_manager_handle_deny_with_reason() {
+        let mut manager = PermissionManager::new();
+        let response = PermissionResponse::DenyWithReason("leave migrations alone".to_string());
+        assert!(!response.is_allowed());
+        assert_eq!(response.denial_reason(), Some("leave migrations alone"));
+
+        manager.handle_response("Bash", Some("rm -r migrations"), response);
+
+        assert_eq!(manager.deny_count("Bash"), 1);
+        assert!(manager.rules().is_empty());
+    }
+
     #[test]
     fn test_manager_clear_session_grants() {
         let mut manager = PermissionManager::new();
@@ -604,10 +914,98 @@ mod tests {
     // Config persistence tests
     // =========================================================================
 
+    #[test]
+    fn test_load_from_persists_glob_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("permissions.json");
+
+        let mut manager = PermissionManager::new();
+        manager.load_from(config_path.clone()).unwrap();
+        manager.add_rule(PermissionRule::parse("bash:git diff*", true).unwrap());
+
+        let mut restarted = PermissionManager::new();
+        restarted.load_from(config_path).unwrap();
+        assert_eq!(
+            restarted.check("bash", Some("git diff HEAD")),
+            PermissionDecision::Allowed
+        );
+        assert_eq!(
+            restarted.check("bash", Some("git push")),
+            PermissionDecision::NeedsPrompt
+        );
+    }
+
+    #[test]
+    fn test_allow_always_round_trips_through_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("permissions.json");
+
+        let mut manager = PermissionManager::new();
+        manager.load_from(config_path.clone()).unwrap();
+        manager.add_session_rule(PermissionRule::new("*", None, true));
+        manager.handle_response("bash", Some("cargo build"), PermissionResponse::AllowAlways);
+        manager.handle_response("bash", Some("cargo test"), PermissionResponse::AllowOnce);
+        manager.add_rule(PermissionRule::new("bash", Some("rm *"), false));
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(saved["rules"].as_array().unwrap().len(), 2);
+
+        let mut restarted = PermissionManager::new();
+        restarted.load_from(config_path).unwrap();
+        assert_eq!(restarted.rules(), manager.rules());
+        assert_eq!(
+            restarted.check("bash", Some("cargo build")),
+            PermissionDecision::Allowed
+        );
+        assert_eq!(
+            restarted.check("bash", Some("cargo test")),
+            PermissionDecision::NeedsPrompt,
+            "allow-once grants and session rules stay in memory"
+        );
+        assert_eq!(
+            restarted.check("bash", Some("rm -rf target")),
+            PermissionDecision::Denied
+        );
+    }
+
+    #[test]
+    fn test_load_from_reads_legacy_toml_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("permissions.toml"),
+            "[[rules]]\ntool_pattern = \"bash\"\ninput_pattern = \"git *\"\nallow = true\n",
+        )
+        .unwrap();
+        let config_path = temp_dir.path().join("permissions.json");
+
+        let mut manager = PermissionManager::new();
+        manager.load_from(config_path.clone()).unwrap();
+        assert_eq!(
+            manager.check("bash", Some("git status")),
+            PermissionDecision::Allowed
+        );
+
+        manager.add_rule(PermissionRule::new("read_file", None, true));
+        let mut restarted = PermissionManager::new();
+        restarted.load_from(config_path).unwrap();
+        assert_eq!(restarted.rules().len(), 2);
+    }
+
+    #[test]
+    fn test_load_from_rejects_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("permissions.json");
+        fs::write(&config_path, "{ not json").unwrap();
+
+        let err = PermissionManager::new().load_from(config_path).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse"));
+    }
+
     #[test]
     fn test_config_save_and_load() {
         let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("permissions.toml");
+        let config_path = temp_dir.path().join("permissions.json");
 
         // Create manager and add rules
         let mut manager = PermissionManager::from_config_file(config_path.clone()).unwrap();
@@ -626,7 +1024,7 @@ mod tests {
     #[test]
     fn test_config_empty_file() {
         let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("permissions.toml");
+        let config_path = temp_dir.path().join("permissions.json");
 
         // File doesn't exist yet
         let manager = PermissionManager::from_config_file(config_path).unwrap();
@@ -644,6 +1042,10 @@ mod tests {
         assert_eq!(request.tool_name, "Bash");
         assert_eq!(request.tool_input, Some("git status".to_string()));
         assert_eq!(request.description, "Run git status command");
+        assert_eq!(request.preview, None);
+
+        let request = request.with_preview("- old\n+ new");
+        assert_eq!(request.preview.as_deref(), Some("- old\n+ new"));
     }
 
     // =========================================================================
@@ -676,4 +1078,135 @@ mod tests {
         assert!(!grant.is_expired());
         assert!(grant.matches("Bash", None));
     }
+
+    // =========================================================================
+    // Permission mode tests
+    // =========================================================================
+
+    #[test]
+    fn test_default_mode_is_ask() {
+        let manager = PermissionManager::new();
+        assert_eq!(manager.mode(), PermissionMode::Ask);
+        assert_eq!(
+            manager.check("write_file", Some("src/main.rs")),
+            PermissionDecision::NeedsPrompt
+        );
+    }
+
+    #[test]
+    fn test_read_only_mode_denies_mutating_tools() {
+        let mut manager = PermissionManager::new();
+        manager.add_rule(PermissionRule::new("write_file", None, true));
+        manager.set_mode(PermissionMode::ReadOnly);
+
+        // Allow rules do not override read-only mode
+        assert_eq!(
+            manager.check("write_file", Some("src/main.rs")),
+            PermissionDecision::Denied
+        );
+        assert_eq!(
+            manager.check("bash", Some("rm -rf build")),
+            PermissionDecision::Denied
+        );
+        assert_eq!(
+            manager.check("mcp__server__tool", None),
+            PermissionDecision::Denied
+        );
+
+        // Read-only tools still go through the normal checks
+        assert_eq!(
+            manager.check("read_file", Some("src/main.rs")),
+            PermissionDecision::NeedsPrompt
+        );
+        assert_eq!(
+            manager.check("bash", Some("ls -la")),
+            PermissionDecision::NeedsPrompt
+        );
+    }
+
+    #[test]
+    fn test_read_only_mode_denies_commands_off_the_allowlist() {
+        let mut manager = PermissionManager::new();
+        manager.add_rule(PermissionRule::new("bash", None, true));
+        manager.set_mode(PermissionMode::ReadOnly);
+
+        for command in [
+            "env rm -rf ~",
+            "command rm -rf target",
+            "xargs rm",
+            "nice rm -rf target",
+            "find . -delete",
+            "find . -exec rm {} +",
+            "awk 'BEGIN{system(\"rm -rf ~\")}'",
+            "sed -n 1p Cargo.toml",
+            "git -c core.pager=less log",
+            "git branch -D main",
+            "git config user.name x",
+            "git stash drop",
+            "git remote add origin https://example.com/x.git",
+            "cargo test",
+        ] {
+            assert_eq!(
+                manager.check("bash", Some(command)),
+                PermissionDecision::Denied,
+                "{command}"
+            );
+        }
+        for command in ["git log --oneline", "find . -name '*.rs'", "git branch -a"] {
+            assert_eq!(
+                manager.check("bash", Some(command)),
+                PermissionDecision::Allowed,
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_allowlist_replaces_prompts() {
+        let mut manager = PermissionManager::new();
+        manager.add_rule(PermissionRule::new("bash", Some("git log*"), false));
+        manager.add_rule(PermissionRule::new("bash", Some("rm *"), true));
+        manager.set_mode(PermissionMode::FullAccess);
+        manager.set_allowlist(Some(Allowlist::new("/work").with_write_root("/work")));
+
+        assert_eq!(
+            manager.check("edit", Some("src/main.rs")),
+            PermissionDecision::Allowed
+        );
+        assert_eq!(
+            manager.check("bash", Some("git status")),
+            PermissionDecision::Allowed
+        );
+        // Neither allow rules nor full-access mode widen the allowlist
+        assert_eq!(
+            manager.check("bash", Some("rm -rf build")),
+            PermissionDecision::Denied
+        );
+        assert_eq!(
+            manager.check("write_file", Some("/etc/hosts")),
+            PermissionDecision::Denied
+        );
+        // Deny rules still apply
+        assert_eq!(
+            manager.check("bash", Some("git log")),
+            PermissionDecision::Denied
+        );
+    }
+
+    #[test]
+    fn test_full_access_mode_allows_without_prompt() {
+        let mut manager = PermissionManager::new();
+        manager.add_rule(PermissionRule::new("bash", Some("rm *"), false));
+        manager.set_mode(PermissionMode::FullAccess);
+
+        assert_eq!(
+            manager.check("write_file", Some("src/main.rs")),
+            PermissionDecision::Allowed
+        );
+        // Deny rules still apply
+        assert_eq!(
+            manager.check("bash", Some("rm -rf build")),
+            PermissionDecision::Denied
+        );
+    }
 }
@@ -4,7 +4,8 @@
 //!
 //! # Supported Patterns
 //!
-//! - `*` - Matches any sequence of characters (but not empty)
+//! - `*` - Matches any sequence of characters, including `/` and none
+//! - `**` - Same as `*`; reads naturally in path patterns like `src/**`
 //! - `?` - Matches any single character
 //! - Exact match - Matches the literal string
 //!
@@ -175,6 +176,59 @@ pub fn normalize_input(input: &str) -> String {
     input.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+/// Normalizes a relative or absolute path for pattern matching.
+///
+/// Empty and `.` components are dropped and `..` is resolved lexically, so
+/// `./src//main.rs` becomes `src/main.rs`. The file system is not consulted.
+///
+/// # Examples
+///
+/// ```
+/// use patina::permissions::patterns::normalize_path;
+///
+/// assert_eq!(normalize_path("./src//main.rs"), "src/main.rs");
+/// assert_eq!(normalize_path("src/../.env"), ".env");
+/// assert_eq!(normalize_path("/etc/./passwd"), "/etc/passwd");
+/// ```
+#[must_use]
+pub fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if matches!(parts.last(), None | Some(&"..")) {
+                    parts.push(part);
+                } else {
+                    parts.pop();
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    if path.starts_with('/') {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+/// Returns whether a path has a `..` component.
+///
+/// # Examples
+///
+/// ```
+/// use patina::permissions::patterns::has_parent_component;
+///
+/// assert!(has_parent_component("src/../../etc/passwd"));
+/// assert!(!has_parent_component("src/..rc"));
+/// ```
+#[must_use]
+pub fn has_parent_component(path: &str) -> bool {
+    path.split('/').any(|part| part == "..")
+}
+
 /// Common permission patterns for well-known tools.
 pub mod common {
     /// Pattern for all git commands.
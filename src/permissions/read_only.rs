@@ -0,0 +1,441 @@
+//! Strict allowlist of bash commands that only read.
+//!
+//! Read-only mode runs a bash command only if it is on this allowlist. The
+//! list is deliberately separate from the parallel-execution heuristic in
+//! [`classify_bash_command`](crate::tools::parallel::classify_bash_command):
+//! that one decides what may run concurrently, this one decides what may run
+//! at all without someone approving it.
+//!
+//! A command qualifies only if:
+//!
+//! - it is a single simple command: no pipes, redirections, separators,
+//!   substitutions, variable expansion or unquoted globs
+//! - the program is listed here, so wrappers that run another command
+//!   (`env`, `command`, `xargs`, `nice`, `timeout`, ...) never qualify
+//! - its arguments match the program's read-only forms: no `find -exec` or
+//!   `-delete`, no output files, no `git -c`, and only the listing forms of
+//!   git subcommands such as `branch`, `stash` or `config`
+//!
+//! # Examples
+//!
+//! ```
+//! use patina::permissions::read_only::is_read_only_command;
+//!
+//! assert!(is_read_only_command("git log --oneline -5"));
+//! assert!(is_read_only_command("grep -rn 'fn main' src"));
+//! assert!(!is_read_only_command("env rm -rf target"));
+//! assert!(!is_read_only_command("find . -name '*.tmp' -delete"));
+//! assert!(!is_read_only_command("git branch -D main"));
+//! ```
+
+/// Splits a command line into its arguments, as the shell would.
+///
+/// Returns `None` if the command uses shell syntax other than quoting and
+/// backslash escapes (operators, redirection, expansion, unquoted globs), or
+/// if a quote is left open.
+///
+/// # Examples
+///
+/// ```
+/// use patina::permissions::read_only::split_argv;
+///
+/// assert_eq!(
+///     split_argv(r#"grep -n "fn main" 'src/a b.rs'"#),
+///     Some(vec!["grep".into(), "-n".into(), "fn main".into(), "src/a b.rs".into()])
+/// );
+/// assert_eq!(split_argv("ls *.rs"), None);
+/// assert_eq!(split_argv("echo $HOME"), None);
+/// ```
+#[must_use]
+pub fn split_argv(command: &str) -> Option<Vec<String>> {
+    let mut argv = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => {
+                if in_word {
+                    argv.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '$' | '`' => return None,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\' | '$' | '`') => word.push(c),
+                            c => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next()? {
+                    '\n' => return None,
+                    c => word.push(c),
+                }
+            }
+            '|' | '&' | ';' | '<' | '>' | '(' | ')' | '{' | '}' | '$' | '`' | '*' | '?' | '['
+            | ']' | '!' | '\n' | '\r' => return None,
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        argv.push(word);
+    }
+
+    (!argv.is_empty()).then_some(argv)
+}
+
+/// Returns whether a bash command is on the read-only allowlist.
+#[must_use]
+pub fn is_read_only_command(command: &str) -> bool {
+    let Some(argv) = split_argv(command.trim()) else {
+        return false;
+    };
+    let args: Vec<&str> = argv[1..].iter().map(String::as_str).collect();
+
+    match argv[0].as_str() {
+        // No option of these writes files or runs other programs
+        "cat" | "head" | "tail" | "wc" | "stat" | "md5sum" | "sha1sum" | "sha256sum"
+        | "hexdump" | "strings" | "ls" | "du" | "df" | "grep" | "pwd" | "whoami" | "uname"
+        | "id" | "printenv" | "echo" | "which" | "basename" | "dirname" | "realpath"
+        | "readlink" | "cut" | "diff" | "cmp" | "comm" | "nl" | "rev" | "tac" | "jq" | "true"
+        | "false" | "test" => true,
+        // `file -C` compiles a magic file next to the input
+        "file" => !args
+            .iter()
+            .any(|a| *a == "--compile" || has_short_flag(a, 'C')),
+        "tree" => !args.iter().any(|a| has_short_flag(a, 'o')),
+        "sort" => !args.iter().any(|a| {
+            has_short_flag(a, 'o') || a.starts_with("--output") || a.starts_with("--compress")
+        }),
+        // `uniq INPUT OUTPUT` writes OUTPUT
+        "uniq" => args.iter().filter(|a| !a.starts_with('-')).count() <= 1,
+        // `--pre` and `-z` run other programs on each file
+        "rg" => !args.iter().any(|a| {
+            a.starts_with("--pre") || a.starts_with("--search-zip") || has_short_flag(a, 'z')
+        }),
+        "find" => !args.iter().any(|a| FIND_ACTIONS.contains(a)),
+        // Anything else sets the clock or the host name
+        "date" => args
+            .iter()
+            .all(|a| a.starts_with('+') || matches!(*a, "-u" | "--utc" | "-R" | "--rfc-email")),
+        "hostname" => args
+            .iter()
+            .all(|a| matches!(*a, "-f" | "-s" | "-d" | "-i" | "-I")),
+        "git" => is_read_only_git(&args),
+        "cargo" => matches!(
+            args.as_slice(),
+            ["--version" | "-V"] | ["locate-project" | "verify-project" | "read-manifest", ..]
+        ),
+        "npm" => matches!(
+            args.as_slice(),
+            ["--version" | "-v"]
+                | [
+                    "ls" | "list" | "view" | "info" | "show" | "outdated" | "search" | "explain",
+                    ..
+                ]
+        ),
+        _ => false,
+    }
+}
+
+/// `find` expressions that delete files, write files or run commands.
+const FIND_ACTIONS: &[&str] = &[
+    "-delete", "-exec", "-execdir", "-ok", "-okdir", "-fprint", "-fprint0", "-fprintf", "-fls",
+];
+
+/// Git subcommands whose every form only reads.
+const GIT_READ_SUBCOMMANDS: &[&str] = &[
+    "status",
+    "log",
+    "diff",
+    "show",
+    "blame",
+    "shortlog",
+    "describe",
+    "rev-parse",
+    "rev-list",
+    "ls-files",
+    "ls-tree",
+    "cat-file",
+    "grep",
+    "name-rev",
+    "for-each-ref",
+    "show-ref",
+    "merge-base",
+];
+
+/// Options of `git branch` and `git tag` that only list.
+const GIT_LIST_FLAGS: &[&str] = &[
+    "-l",
+    "--list",
+    "-a",
+    "--all",
+    "-r",
+    "--remotes",
+    "-v",
+    "-vv",
+    "--verbose",
+    "--show-current",
+    "--no-color",
+];
+
+/// Options of `git config` that read values.
+const GIT_CONFIG_READ_FLAGS: &[&str] = &["--get", "--get-all", "--get-regexp", "--list", "-l"];
+
+/// Options `git config` may combine with a read.
+const GIT_CONFIG_SCOPE_FLAGS: &[&str] = &[
+    "--global",
+    "--local",
+    "--system",
+    "--show-origin",
+    "--show-scope",
+    "--name-only",
+    "-z",
+    "--null",
+];
+
+/// Returns whether the arguments after `git` only read the repository.
+///
+/// Global options are rejected except `--no-pager`: `-c` and `-C` can point
+/// git at configuration that runs arbitrary commands.
+fn is_read_only_git(args: &[&str]) -> bool {
+    let args = match args {
+        ["--no-pager", rest @ ..] => rest,
+        _ => args,
+    };
+    let Some((subcommand, args)) = args.split_first() else {
+        return false;
+    };
+    // Write to a file, run an external diff driver or open a pager command
+    if args.iter().any(|a| {
+        a.starts_with("--output")
+            || a.starts_with("--ext-diff")
+            || a.starts_with("--open-files-in-pager")
+            || a.starts_with("-O")
+    }) {
+        return false;
+    }
+
+    let (flags, positionals): (Vec<&str>, Vec<&str>) =
+        args.iter().partition(|a| a.starts_with('-'));
+    match *subcommand {
+        s if GIT_READ_SUBCOMMANDS.contains(&s) => true,
+        // Positional arguments create branches and tags unless listing
+        "branch" | "tag" => {
+            let listing = flags.iter().any(|a| matches!(*a, "-l" | "--list"));
+            flags.iter().all(|a| {
+                GIT_LIST_FLAGS.contains(a) || a.starts_with("--sort=") || a.starts_with("--format=")
+            }) && (listing || positionals.is_empty())
+        }
+        "config" => {
+            flags.iter().any(|a| GIT_CONFIG_READ_FLAGS.contains(a))
+                && flags.iter().all(|a| {
+                    GIT_CONFIG_READ_FLAGS.contains(a) || GIT_CONFIG_SCOPE_FLAGS.contains(a)
+                })
+        }
+        "remote" => matches!(args, [] | ["-v" | "--verbose"] | ["get-url" | "show", ..]),
+        "stash" => matches!(args, ["list" | "show", ..]),
+        "reflog" => matches!(args, [] | ["show", ..]),
+        "worktree" => matches!(args, ["list", ..]),
+        _ => false,
+    }
+}
+
+/// Returns whether `arg` is a cluster of short options that includes `flag`.
+fn has_short_flag(arg: &str, flag: char) -> bool {
+    arg.starts_with('-') && !arg.starts_with("--") && arg[1..].contains(flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_argv_handles_quotes_and_escapes() {
+        assert_eq!(
+            split_argv(r#"grep -e "a \"b\"" 'c d' e\ f ''"#).unwrap(),
+            vec!["grep", "-e", "a \"b\"", "c d", "e f", ""]
+        );
+        assert_eq!(split_argv("  ls   -la  ").unwrap(), vec!["ls", "-la"]);
+    }
+
+    #[test]
+    fn test_split_argv_rejects_shell_syntax() {
+        for command in [
+            "",
+            "ls | wc",
+            "ls > out",
+            "cat < in",
+            "ls; rm x",
+            "ls && rm x",
+            "echo $(rm x)",
+            "echo `rm x`",
+            "echo \"$HOME\"",
+            "ls *.rs",
+            "ls src/?.rs",
+            "(rm x)",
+            "echo 'open",
+            "ls\nrm x",
+        ] {
+            assert_eq!(split_argv(command), None, "{command:?}");
+        }
+    }
+
+    #[test]
+    fn test_allows_read_only_commands() {
+        for command in [
+            "ls -la",
+            "cat src/main.rs",
+            "grep -rn 'a|b' src",
+            "rg --type rust 'fn main'",
+            "find . -name '*.rs' -type f",
+            "wc -l Cargo.toml",
+            "sort -u names.txt",
+            "date +%Y",
+            "git status",
+            "git --no-pager log --oneline -5",
+            "git diff HEAD~1",
+            "git branch",
+            "git branch -a -v",
+            "git branch --list 'feature/*'",
+            "git tag -l",
+            "git config --get user.name",
+            "git config --list --show-origin",
+            "git remote -v",
+            "git stash list",
+            "cargo --version",
+            "npm ls",
+        ] {
+            assert!(is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_command_wrappers() {
+        for command in [
+            "env rm -rf ~",
+            "env",
+            "command rm -rf target",
+            "xargs rm",
+            "nice rm -rf target",
+            "nohup rm -rf target",
+            "timeout 5 rm -rf target",
+            "sudo ls",
+            "exec rm -rf target",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_find_actions() {
+        for command in [
+            "find . -delete",
+            "find . -name '*.o' -delete",
+            "find . -exec rm {} +",
+            "find . -execdir rm '{}' ';'",
+            "find . -ok rm '{}' ';'",
+            "find . -fprint out.txt",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_scripting_and_writing_commands() {
+        for command in [
+            "awk 'BEGIN{system(\"rm -rf ~\")}'",
+            "awk '{print $1}' file",
+            "sed -n 1p file",
+            "sed -i s/a/b/ file",
+            "tee out.txt",
+            "sort -o out.txt in.txt",
+            "sort -uo out.txt in.txt",
+            "uniq in.txt out.txt",
+            "rg --pre ./run.sh pattern",
+            "tree -o out.txt",
+            "date -s 2020-01-01",
+            "hostname evil",
+            "xxd -r dump bin",
+            "rm -rf target",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_git_global_options_and_mutations() {
+        for command in [
+            "git -c core.pager='rm -rf ~' log",
+            "git -c alias.x='!rm -rf ~' x",
+            "git -C /tmp status",
+            "git --git-dir=/tmp/x log",
+            "git branch -D main",
+            "git branch -d feature",
+            "git branch -m old new",
+            "git branch new-branch",
+            "git tag v1.0",
+            "git tag -d v1.0",
+            "git config user.name x",
+            "git config core.pager 'rm -rf ~'",
+            "git config --unset user.name",
+            "git stash",
+            "git stash drop",
+            "git stash pop",
+            "git remote add origin https://example.com/x.git",
+            "git remote remove origin",
+            "git reflog expire --all",
+            "git diff --output=out.patch",
+            "git diff --ext-diff",
+            "git grep -Orm foo",
+            "git push origin main",
+            "git commit -m x",
+            "git checkout .",
+            "git reset --hard",
+            "git",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_build_and_test_commands() {
+        for command in [
+            "cargo test",
+            "cargo check",
+            "cargo clippy",
+            "cargo build",
+            "cargo metadata",
+            "npm test",
+            "npm run build",
+            "npm install",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+}
@@ -5,18 +5,24 @@
 //! - Plugin registry for managing loaded plugins
 //! - Host API traits for plugin development
 //! - Manifest parsing for `rct-plugin.toml` files
+//! - Optional hot-reload of changed plugins (`--watch-plugins`)
+//! - Custom tools declared in plugin manifests
 
 pub mod host;
 pub mod manifest;
 pub mod narsil;
 pub mod registry;
+pub mod tools;
+pub mod watcher;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use tools::{PluginTool, PluginToolSpec, PluginTools};
+
 #[derive(Debug, Deserialize)]
 pub struct PluginManifest {
     pub name: String,
@@ -27,6 +33,9 @@ pub struct PluginManifest {
     pub author: Option<String>,
     #[serde(default)]
     pub min_rct_version: Option<String>,
+    /// Tools the plugin adds for the model to call.
+    #[serde(default)]
+    pub tools: Vec<PluginToolSpec>,
 }
 
 #[derive(Debug)]
@@ -80,10 +89,54 @@ pub struct HookDef {
     pub command: String,
 }
 
+/// Path of a plugin's manifest, relative to the plugin directory.
+const MANIFEST_PATH: &str = ".claude-plugin/plugin.json";
+
+/// Returns the directories plugins are loaded from.
+///
+/// - `~/.config/patina/plugins/` (user plugins)
+/// - `<working_dir>/.patina/plugins/` (project-local plugins)
+#[must_use]
+pub fn plugin_search_paths(working_dir: &Path) -> Vec<PathBuf> {
+    let mut search_paths = Vec::new();
+    if let Some(base_dirs) = directories::BaseDirs::new() {
+        search_paths.push(base_dirs.config_dir().join("patina/plugins"));
+    }
+    search_paths.push(working_dir.join(".patina/plugins"));
+    search_paths
+}
+
+/// Outcome of reloading a plugin directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginReload {
+    /// A plugin that was not loaded before was added.
+    Loaded(String),
+    /// A loaded plugin was replaced with its new version.
+    Reloaded(String),
+    /// A loaded plugin's manifest is gone, so the plugin was unloaded.
+    Removed(String),
+    /// The directory holds no plugin and none was loaded from it.
+    Ignored,
+}
+
+impl PluginReload {
+    /// Returns the notice to show in the timeline, if any.
+    #[must_use]
+    pub fn notice(&self) -> Option<String> {
+        match self {
+            Self::Loaded(name) => Some(format!("Plugin '{}' loaded", name)),
+            Self::Reloaded(name) => Some(format!("Plugin '{}' reloaded", name)),
+            Self::Removed(name) => Some(format!("Plugin '{}' unloaded", name)),
+            Self::Ignored => None,
+        }
+    }
+}
+
 pub struct PluginRegistry {
     plugins: HashMap<String, Plugin>,
     commands: HashMap<String, (String, Command)>,
     skills: Vec<(String, Skill)>,
+    tools: PluginTools,
 }
 
 impl PluginRegistry {
@@ -92,6 +145,7 @@ impl PluginRegistry {
             plugins: HashMap::new(),
             commands: HashMap::new(),
             skills: Vec::new(),
+            tools: PluginTools::default(),
         }
     }
 
@@ -124,22 +178,14 @@ impl PluginRegistry {
                 }
             };
 
-            let manifest_path = entry.path().join(".claude-plugin/plugin.json");
+            let manifest_path = entry.path().join(MANIFEST_PATH);
 
             if manifest_path.exists() {
-                if let Ok(plugin) = self.load_plugin(entry.path()) {
-                    let name = plugin.manifest.name.clone();
-
-                    for cmd in &plugin.commands {
-                        let key = format!("{}:{}", name, cmd.name);
-                        self.commands.insert(key, (name.clone(), cmd.clone()));
-                    }
-
-                    for skill in &plugin.skills {
-                        self.skills.push((name.clone(), skill.clone()));
+                match self.load_plugin(entry.path()) {
+                    Ok(plugin) => self.insert_plugin(plugin),
+                    Err(e) => {
+                        tracing::warn!("Skipping plugin in {}: {:#}", entry.path().display(), e)
                     }
-
-                    self.plugins.insert(name, plugin);
                 }
             }
         }
@@ -147,10 +193,36 @@ impl PluginRegistry {
         Ok(())
     }
 
+    /// Registers a loaded plugin with its commands and skills.
+    fn insert_plugin(&mut self, plugin: Plugin) {
+        let name = plugin.manifest.name.clone();
+
+        for cmd in &plugin.commands {
+            let key = format!("{}:{}", name, cmd.name);
+            self.commands.insert(key, (name.clone(), cmd.clone()));
+        }
+
+        for skill in &plugin.skills {
+            self.skills.push((name.clone(), skill.clone()));
+        }
+
+        self.tools.remove_plugin(&name);
+        for spec in &plugin.manifest.tools {
+            self.tools.insert(PluginTool {
+                plugin: name.clone(),
+                plugin_dir: plugin.path.clone(),
+                spec: spec.clone(),
+            });
+        }
+
+        self.plugins.insert(name, plugin);
+    }
+
     fn load_plugin(&self, plugin_dir: &Path) -> Result<Plugin> {
-        let manifest_path = plugin_dir.join(".claude-plugin/plugin.json");
+        let manifest_path = plugin_dir.join(MANIFEST_PATH);
         let manifest: PluginManifest =
             serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)?;
+        self.validate_tools(&manifest, plugin_dir)?;
 
         let commands = self.load_commands(plugin_dir)?;
         let skills = self.load_skills(plugin_dir)?;
@@ -167,6 +239,30 @@ impl PluginRegistry {
         })
     }
 
+    /// Rejects tools that are invalid or whose names are already taken.
+    ///
+    /// A name may be reused only by the plugin that already owns it, so
+    /// reloading a plugin does not conflict with its previous version.
+    fn validate_tools(&self, manifest: &PluginManifest, plugin_dir: &Path) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        for spec in &manifest.tools {
+            spec.validate()?;
+            if !seen.insert(spec.name.as_str()) {
+                bail!("tool '{}' is declared more than once", spec.name);
+            }
+            if let Some(existing) = self.tools.get(&spec.name) {
+                if existing.plugin != manifest.name && existing.plugin_dir != plugin_dir {
+                    bail!(
+                        "tool '{}' is already provided by plugin '{}'",
+                        spec.name,
+                        existing.plugin
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Loads commands from a plugin directory.
     ///
     /// Uses graceful degradation: logs warnings for unreadable command files
@@ -274,6 +370,14 @@ impl PluginRegistry {
         None
     }
 
+    /// Returns the tools registered by loaded plugins.
+    ///
+    /// The returned handle is shared: clones see later loads and unloads.
+    #[must_use]
+    pub fn tools(&self) -> &PluginTools {
+        &self.tools
+    }
+
     pub fn all_skills(&self) -> impl Iterator<Item = &Skill> {
         self.skills.iter().map(|(_, s)| s)
     }
@@ -302,6 +406,9 @@ impl PluginRegistry {
             // Remove skills from this plugin
             self.skills.retain(|(plugin_name, _)| plugin_name != name);
 
+            // Remove tools from this plugin
+            self.tools.remove_plugin(name);
+
             true
         } else {
             false
@@ -310,27 +417,60 @@ impl PluginRegistry {
 
     /// Reloads a plugin from its directory.
     ///
+    /// The new version is loaded before the old one is removed, so a plugin
+    /// that fails to load leaves the loaded version in place.
+    ///
     /// # Errors
     ///
     /// Returns an error if the plugin cannot be loaded from the path.
     pub fn reload_plugin(&mut self, name: &str, plugin_dir: &Path) -> Result<()> {
-        self.unload_plugin(name);
-
         let plugin = self.load_plugin(plugin_dir)?;
-        let loaded_name = plugin.manifest.name.clone();
 
-        for cmd in &plugin.commands {
-            let key = format!("{}:{}", loaded_name, cmd.name);
-            self.commands
-                .insert(key, (loaded_name.clone(), cmd.clone()));
+        self.unload_plugin(name);
+        self.unload_plugin(&plugin.manifest.name);
+        self.insert_plugin(plugin);
+        Ok(())
+    }
+
+    /// Reloads whatever plugin lives in `plugin_dir` after it changed on disk.
+    ///
+    /// Adds a new plugin, replaces a loaded one, or unloads one whose
+    /// manifest was removed. The plugin is fully loaded before anything is
+    /// swapped, so a broken edit leaves the previous version in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plugin in the directory cannot be loaded.
+    pub fn reload_plugin_dir(&mut self, plugin_dir: &Path) -> Result<PluginReload> {
+        let existing = self
+            .plugins
+            .values()
+            .find(|p| p.path == plugin_dir)
+            .map(|p| p.manifest.name.clone());
+
+        if !plugin_dir.join(MANIFEST_PATH).exists() {
+            return Ok(match existing {
+                Some(name) => {
+                    self.unload_plugin(&name);
+                    PluginReload::Removed(name)
+                }
+                None => PluginReload::Ignored,
+            });
         }
 
-        for skill in &plugin.skills {
-            self.skills.push((loaded_name.clone(), skill.clone()));
+        let plugin = self.load_plugin(plugin_dir)?;
+        let name = plugin.manifest.name.clone();
+        if let Some(old) = &existing {
+            self.unload_plugin(old);
         }
+        let replaced = self.unload_plugin(&name);
+        self.insert_plugin(plugin);
 
-        self.plugins.insert(loaded_name, plugin);
-        Ok(())
+        Ok(if existing.is_some() || replaced {
+            PluginReload::Reloaded(name)
+        } else {
+            PluginReload::Loaded(name)
+        })
     }
 
     /// Returns the manifest for a plugin.
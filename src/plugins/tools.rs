@@ -0,0 +1,324 @@
+//! Tools declared by plugins.
+//!
+//! A plugin declares tools in the `tools` array of its manifest:
+//!
+//! ```json
+//! {
+//!   "name": "jira",
+//!   "version": "1.0.0",
+//!   "tools": [
+//!     {
+//!       "name": "jira_ticket",
+//!       "description": "Fetches a Jira ticket by key",
+//!       "input_schema": {
+//!         "type": "object",
+//!         "properties": { "key": { "type": "string" } },
+//!         "required": ["key"]
+//!       },
+//!       "command": "./bin/ticket.sh"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The tools are advertised to the model next to the built-in ones. When the
+//! model calls one, its `command` runs in the plugin directory with the tool
+//! input as JSON on stdin; stdout becomes the tool result and a non-zero exit
+//! status reports an error.
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::api::tools::{is_builtin_tool, ToolDefinition};
+use crate::shell::ShellConfig;
+use crate::tools::ToolResult;
+
+/// How long a plugin tool command may run before it is killed.
+pub const PLUGIN_TOOL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Tool names the API accepts.
+static TOOL_NAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-zA-Z0-9_-]{1,64}$").unwrap());
+
+/// A tool as declared in a plugin manifest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginToolSpec {
+    /// Tool name the model calls.
+    pub name: String,
+    /// What the tool does, shown to the model.
+    pub description: String,
+    /// JSON Schema for the tool input.
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    /// Shell command run for each call, from the plugin directory.
+    pub command: String,
+}
+
+fn default_input_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+impl PluginToolSpec {
+    /// Checks that the tool can be advertised to the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a valid API tool name or is taken
+    /// by a built-in tool, the schema is not an object schema, or the
+    /// command is empty.
+    pub fn validate(&self) -> Result<()> {
+        if !TOOL_NAME_REGEX.is_match(&self.name) {
+            bail!(
+                "invalid tool name '{}': use letters, digits, '_' or '-' (at most 64)",
+                self.name
+            );
+        }
+        if is_builtin_tool(&self.name) {
+            bail!("tool '{}' conflicts with a built-in tool", self.name);
+        }
+        if self.input_schema.get("type").and_then(Value::as_str) != Some("object") {
+            bail!(
+                "tool '{}' input_schema must have \"type\": \"object\"",
+                self.name
+            );
+        }
+        if self.command.trim().is_empty() {
+            bail!("tool '{}' has an empty command", self.name);
+        }
+        Ok(())
+    }
+}
+
+/// A tool registered by a loaded plugin.
+#[derive(Debug, Clone)]
+pub struct PluginTool {
+    /// Name of the plugin that declared the tool.
+    pub plugin: String,
+    /// Directory the plugin was loaded from; the command runs here.
+    pub plugin_dir: PathBuf,
+    /// The tool as declared in the manifest.
+    pub spec: PluginToolSpec,
+}
+
+impl PluginTool {
+    /// Returns the definition advertised to the model.
+    #[must_use]
+    pub fn definition(&self) -> ToolDefinition {
+        ToolDefinition::new(
+            self.spec.name.clone(),
+            self.spec.description.clone(),
+            self.spec.input_schema.clone(),
+        )
+    }
+
+    /// Runs the tool command with `input` on stdin.
+    ///
+    /// `working_dir` is exported as `PATINA_WORKING_DIR` so the command can
+    /// find the project it is working on.
+    pub async fn run(&self, working_dir: &Path, input: &Value) -> ToolResult {
+        match self.run_command(working_dir, input).await {
+            Ok(result) => result,
+            Err(e) => ToolResult::Error(format!(
+                "Plugin tool '{}' from '{}' failed: {:#}",
+                self.spec.name, self.plugin, e
+            )),
+        }
+    }
+
+    async fn run_command(&self, working_dir: &Path, input: &Value) -> Result<ToolResult> {
+        tracing::info!(
+            tool = %self.spec.name,
+            plugin = %self.plugin,
+            "Executing plugin tool"
+        );
+
+        let shell = ShellConfig::default();
+        let mut child = Command::new(&shell.command)
+            .args(&shell.args)
+            .arg(self.spec.command.trim())
+            .current_dir(&self.plugin_dir)
+            .env("PATINA_WORKING_DIR", working_dir)
+            .env("PATINA_TOOL_NAME", &self.spec.name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let stdin_data = serde_json::to_vec(input)?;
+        let stdin = child.stdin.take();
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                // The command may exit without reading its input
+                let _ = stdin.write_all(&stdin_data).await;
+                drop(stdin);
+            }
+            child.wait_with_output().await
+        };
+
+        // Dropping the child on timeout kills it
+        let output = tokio::time::timeout(PLUGIN_TOOL_TIMEOUT, run)
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out after {}s", PLUGIN_TOOL_TIMEOUT.as_secs()))??;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if output.status.success() {
+            return Ok(ToolResult::Success(stdout));
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = if stderr.trim().is_empty() {
+            stdout.trim().to_string()
+        } else {
+            stderr.trim().to_string()
+        };
+        Ok(ToolResult::Error(format!(
+            "Plugin tool '{}' exited with status {}: {}",
+            self.spec.name,
+            output.status.code().unwrap_or(-1),
+            message
+        )))
+    }
+}
+
+/// Shared view of the tools registered by loaded plugins.
+///
+/// The plugin registry updates it as plugins load and unload; the tool
+/// executor holds a clone and looks calls up in it, so reloaded plugins take
+/// effect for the next call.
+#[derive(Debug, Clone, Default)]
+pub struct PluginTools {
+    tools: Arc<RwLock<HashMap<String, PluginTool>>>,
+}
+
+impl PluginTools {
+    /// Returns the tool registered under `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<PluginTool> {
+        self.tools.read().unwrap().get(name).cloned()
+    }
+
+    /// Returns the definitions of all registered tools, sorted by name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let mut definitions: Vec<ToolDefinition> = self
+            .tools
+            .read()
+            .unwrap()
+            .values()
+            .map(PluginTool::definition)
+            .collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
+    /// Returns the number of registered tools.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tools.read().unwrap().len()
+    }
+
+    /// Returns true if no plugin registered a tool.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Registers a tool, replacing any tool with the same name.
+    pub(crate) fn insert(&self, tool: PluginTool) {
+        self.tools
+            .write()
+            .unwrap()
+            .insert(tool.spec.name.clone(), tool);
+    }
+
+    /// Removes every tool registered by `plugin`.
+    pub(crate) fn remove_plugin(&self, plugin: &str) {
+        self.tools
+            .write()
+            .unwrap()
+            .retain(|_, t| t.plugin != plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str) -> PluginToolSpec {
+        PluginToolSpec {
+            name: name.to_string(),
+            description: "Test tool".to_string(),
+            input_schema: default_input_schema(),
+            command: "cat".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_builtin_and_invalid_names() {
+        assert!(spec("jira_ticket").validate().is_ok());
+        assert!(spec("bash").validate().is_err());
+        assert!(spec("read_mcp_resource").validate().is_err());
+        assert!(spec("mcp__server__tool").validate().is_err());
+        assert!(spec("has space").validate().is_err());
+        assert!(spec("").validate().is_err());
+
+        let mut no_command = spec("empty");
+        no_command.command = "  ".to_string();
+        assert!(no_command.validate().is_err());
+
+        let mut bad_schema = spec("bad_schema");
+        bad_schema.input_schema = json!({ "type": "string" });
+        assert!(bad_schema.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_passes_input_on_stdin_and_reports_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = PluginTool {
+            plugin: "demo".to_string(),
+            plugin_dir: dir.path().to_path_buf(),
+            spec: spec("echo_input"),
+        };
+        let result = tool.run(dir.path(), &json!({ "key": "ABC-1" })).await;
+        assert!(
+            matches!(result, ToolResult::Success(ref out) if out.contains("ABC-1")),
+            "got: {result:?}"
+        );
+
+        let failing = PluginTool {
+            spec: PluginToolSpec {
+                command: "echo 'no such ticket' >&2; exit 3".to_string(),
+                ..spec("failing")
+            },
+            ..tool
+        };
+        let result = failing.run(dir.path(), &json!({})).await;
+        assert!(
+            matches!(result, ToolResult::Error(ref e) if e.contains("status 3") && e.contains("no such ticket")),
+            "got: {result:?}"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! File watching for plugin hot-reload.
+//!
+//! With `--watch-plugins`, the plugin search paths are watched recursively.
+//! Each change is mapped to the plugin directory it belongs to (the
+//! directory directly under a search path) and sent on a channel. The app
+//! reloads those plugins between events, so a slash command never sees a
+//! half-swapped registry.
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// A watched search path.
+#[derive(Debug, Clone)]
+struct WatchRoot {
+    /// Path as configured; reported plugin directories are built from it so
+    /// they match the paths plugins were loaded from.
+    path: PathBuf,
+    /// Canonical path, as the watcher reports events under it.
+    canonical: PathBuf,
+}
+
+/// Watches plugin search paths and reports changed plugin directories.
+///
+/// Watching stops when this is dropped.
+pub struct PluginWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl PluginWatcher {
+    /// Starts watching `search_paths`.
+    ///
+    /// Search paths that do not exist are skipped. Changed plugin
+    /// directories are sent to the returned receiver, possibly several times
+    /// for one save; callers should coalesce them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform watcher cannot be created or a
+    /// search path cannot be watched.
+    pub fn start(search_paths: &[PathBuf]) -> Result<(Self, mpsc::UnboundedReceiver<PathBuf>)> {
+        let roots: Vec<WatchRoot> = search_paths
+            .iter()
+            .filter_map(|path| {
+                let canonical = path.canonicalize().ok()?;
+                Some(WatchRoot {
+                    path: path.clone(),
+                    canonical,
+                })
+            })
+            .collect();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handler_roots = roots.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        tracing::debug!("Plugin watcher error: {}", e);
+                        return;
+                    }
+                };
+                if matches!(event.kind, EventKind::Access(_)) {
+                    return;
+                }
+                for path in &event.paths {
+                    if let Some(dir) = plugin_dir_for(path, &handler_roots) {
+                        let _ = tx.send(dir);
+                    }
+                }
+            })
+            .context("Failed to create plugin watcher")?;
+
+        for root in &roots {
+            watcher
+                .watch(&root.canonical, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to watch {}", root.path.display()))?;
+            tracing::info!("Watching plugins in {}", root.path.display());
+        }
+
+        Ok((Self { _watcher: watcher }, rx))
+    }
+}
+
+/// Maps a changed path to the plugin directory that contains it.
+fn plugin_dir_for(path: &Path, roots: &[WatchRoot]) -> Option<PathBuf> {
+    roots.iter().find_map(|root| {
+        let relative = path.strip_prefix(&root.canonical).ok()?;
+        let plugin = relative.components().next()?;
+        Some(root.path.join(plugin))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_dir_for_maps_to_top_level_directory() {
+        let roots = vec![WatchRoot {
+            path: PathBuf::from(".patina/plugins"),
+            canonical: PathBuf::from("/work/.patina/plugins"),
+        }];
+
+        assert_eq!(
+            plugin_dir_for(
+                Path::new("/work/.patina/plugins/demo/commands/hello.md"),
+                &roots
+            ),
+            Some(PathBuf::from(".patina/plugins/demo"))
+        );
+        assert_eq!(
+            plugin_dir_for(Path::new("/work/.patina/plugins/demo"), &roots),
+            Some(PathBuf::from(".patina/plugins/demo"))
+        );
+        // The search path itself and unrelated paths are not plugins
+        assert_eq!(
+            plugin_dir_for(Path::new("/work/.patina/plugins"), &roots),
+            None
+        );
+        assert_eq!(plugin_dir_for(Path::new("/elsewhere/demo"), &roots), None);
+    }
+}
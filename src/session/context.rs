@@ -1,14 +1,24 @@
-//! Session context tracking for file reads and active skills.
+//! Session context tracking for file reads, MCP resources and active skills.
 //!
-//! This module provides types for tracking files read during a session
-//! and skills that were active, enabling context restoration on resume.
+//! This module provides types for tracking files and MCP resources read
+//! during a session and skills that were active, enabling context
+//! restoration on resume. It also holds what a connected IDE reports the
+//! user is looking at, so the model can be told about it.
 
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
+/// Default number of context files checked at once by [`SessionContext::restore`].
+pub const DEFAULT_RESTORE_CONCURRENCY: usize = 16;
+
+/// Longest editor selection included in [`SessionContext::editor_prompt`], in characters.
+pub const MAX_EDITOR_SELECTION_CHARS: usize = 8_000;
+
 /// A file that was read during the session and may be needed for context restoration.
 ///
 /// When resuming a session, context files can be re-read to restore the conversation
@@ -129,6 +139,77 @@ impl ContextFile {
             }
         }
     }
+
+    /// Compares the file on disk with the stored hash.
+    async fn status(&self) -> Result<FileStatus> {
+        if !self.path.exists() {
+            return Ok(FileStatus::Missing);
+        }
+        Ok(if self.is_unchanged().await? {
+            FileStatus::Unchanged
+        } else {
+            FileStatus::Changed
+        })
+    }
+}
+
+/// An MCP resource that was read into the conversation during the session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContextResource {
+    /// Name of the MCP server that exposes the resource.
+    server: String,
+
+    /// URI of the resource on that server.
+    uri: String,
+}
+
+impl ContextResource {
+    /// Creates a new context resource entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `server` - Name of the MCP server.
+    /// * `uri` - URI of the resource.
+    #[must_use]
+    pub fn new(server: impl Into<String>, uri: impl Into<String>) -> Self {
+        Self {
+            server: server.into(),
+            uri: uri.into(),
+        }
+    }
+
+    /// Returns the MCP server name.
+    #[must_use]
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Returns the resource URI.
+    #[must_use]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
+/// Text the user has selected in their editor, as reported by an IDE.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EditorSelection {
+    /// File the selection is in.
+    pub path: PathBuf,
+    /// First selected line (0-indexed).
+    pub start_line: u32,
+    /// Last selected line (0-indexed).
+    pub end_line: u32,
+    /// The selected text.
+    pub text: String,
+}
+
+/// How a context file compares to when the session was saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileStatus {
+    Unchanged,
+    Changed,
+    Missing,
 }
 
 /// Result of restoring session context.
@@ -152,6 +233,10 @@ pub struct ContextRestoreResult {
     /// Skills that were active during the session.
     /// These should be re-enabled on resume.
     pub active_skills: Vec<String>,
+
+    /// MCP resources that were read during the session.
+    /// These can be re-read from their servers on resume.
+    pub resources: Vec<ContextResource>,
 }
 
 /// Tracks session context including files read and active skills.
@@ -167,6 +252,18 @@ pub struct SessionContext {
 
     /// Names of skills that were active during the session.
     active_skills: Vec<String>,
+
+    /// MCP resources that were read during the session.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resources: Vec<ContextResource>,
+
+    /// Files open in a connected IDE.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    open_files: Vec<PathBuf>,
+
+    /// Current selection in a connected IDE.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selection: Option<EditorSelection>,
 }
 
 impl SessionContext {
@@ -197,6 +294,23 @@ impl SessionContext {
         self.context_files.push(file);
     }
 
+    /// Returns the MCP resources read during the session.
+    #[must_use]
+    pub fn resources(&self) -> &[ContextResource] {
+        &self.resources
+    }
+
+    /// Adds an MCP resource if not already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource to add.
+    pub fn add_resource(&mut self, resource: ContextResource) {
+        if !self.resources.contains(&resource) {
+            self.resources.push(resource);
+        }
+    }
+
     /// Adds an active skill if not already present.
     ///
     /// # Arguments
@@ -218,11 +332,72 @@ impl SessionContext {
         self.active_skills.retain(|s| s != skill_name);
     }
 
+    /// Returns the files open in a connected IDE.
+    #[must_use]
+    pub fn open_files(&self) -> &[PathBuf] {
+        &self.open_files
+    }
+
+    /// Replaces the files open in a connected IDE.
+    pub fn set_open_files(&mut self, paths: Vec<PathBuf>) {
+        self.open_files = paths;
+    }
+
+    /// Returns the current selection in a connected IDE.
+    #[must_use]
+    pub fn selection(&self) -> Option<&EditorSelection> {
+        self.selection.as_ref()
+    }
+
+    /// Sets the current selection in a connected IDE; `None` clears it.
+    pub fn set_selection(&mut self, selection: Option<EditorSelection>) {
+        self.selection = selection;
+    }
+
+    /// Describes the IDE's open files and selection for the system prompt.
+    ///
+    /// Returns `None` if no IDE reported any. Selections longer than
+    /// [`MAX_EDITOR_SELECTION_CHARS`] are cut short.
+    #[must_use]
+    pub fn editor_prompt(&self) -> Option<String> {
+        if self.open_files.is_empty() && self.selection.is_none() {
+            return None;
+        }
+
+        let mut prompt =
+            String::from("# Editor context\n\nWhat the user has open in their editor.");
+        if !self.open_files.is_empty() {
+            prompt.push_str("\n\nOpen files:");
+            for path in &self.open_files {
+                prompt.push_str(&format!("\n- {}", path.display()));
+            }
+        }
+        if let Some(selection) = &self.selection {
+            let mut text: String = selection
+                .text
+                .chars()
+                .take(MAX_EDITOR_SELECTION_CHARS)
+                .collect();
+            if text.len() < selection.text.len() {
+                text.push_str("\n[selection truncated]");
+            }
+            prompt.push_str(&format!(
+                "\n\nSelected in {} (lines {}-{}):\n```\n{}\n```",
+                selection.path.display(),
+                selection.start_line + 1,
+                selection.end_line + 1,
+                text
+            ));
+        }
+        Some(prompt)
+    }
+
     /// Restores the session context by verifying context files.
     ///
     /// Checks each tracked file against its stored hash to determine which
     /// files are unchanged and can be safely used for context restoration.
     /// Files that have changed or are missing are reported separately.
+    /// Up to [`DEFAULT_RESTORE_CONCURRENCY`] files are hashed at once.
     ///
     /// # Errors
     ///
@@ -250,24 +425,51 @@ impl SessionContext {
     /// # }
     /// ```
     pub async fn restore(&self) -> Result<ContextRestoreResult> {
+        self.restore_with_concurrency(DEFAULT_RESTORE_CONCURRENCY)
+            .await
+    }
+
+    /// Restores the session context, checking up to `concurrency` files at once.
+    ///
+    /// Each result list keeps the order files were added in, whatever the
+    /// concurrency. A `concurrency` of 0 is treated as 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if file hashing fails unexpectedly.
+    pub async fn restore_with_concurrency(
+        &self,
+        concurrency: usize,
+    ) -> Result<ContextRestoreResult> {
+        self.restore_by(concurrency, ContextFile::status).await
+    }
+
+    /// Categorizes context files using `status` to check each one.
+    async fn restore_by<'a, F, Fut>(
+        &'a self,
+        concurrency: usize,
+        status: F,
+    ) -> Result<ContextRestoreResult>
+    where
+        F: Fn(&'a ContextFile) -> Fut,
+        Fut: Future<Output = Result<FileStatus>>,
+    {
+        // `buffered` yields in input order, keeping the lists deterministic
+        let statuses: Vec<FileStatus> = stream::iter(&self.context_files)
+            .map(status)
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await?;
+
         let mut restored_files = Vec::new();
         let mut changed_files = Vec::new();
         let mut missing_files = Vec::new();
-
-        for context_file in &self.context_files {
+        for (context_file, status) in self.context_files.iter().zip(statuses) {
             let path = context_file.path().to_path_buf();
-
-            // Check if file exists
-            if !path.exists() {
-                missing_files.push(path);
-                continue;
-            }
-
-            // Check if file is unchanged
-            if context_file.is_unchanged().await? {
-                restored_files.push(path);
-            } else {
-                changed_files.push(path);
+            match status {
+                FileStatus::Unchanged => restored_files.push(path),
+                FileStatus::Changed => changed_files.push(path),
+                FileStatus::Missing => missing_files.push(path),
             }
         }
 
@@ -276,6 +478,7 @@ impl SessionContext {
             changed_files,
             missing_files,
             active_skills: self.active_skills.clone(),
+            resources: self.resources.clone(),
         })
     }
 }
@@ -330,4 +533,88 @@ mod tests {
         assert_eq!(ctx.active_skills().len(), 1);
         assert_eq!(ctx.active_skills()[0], "skill2");
     }
+
+    /// Writes `count` files, giving each a correct hash, a stale hash or no
+    /// file on disk in turn.
+    async fn many_context_files(dir: &Path, count: usize) -> SessionContext {
+        let mut ctx = SessionContext::new();
+        for i in 0..count {
+            let path = dir.join(format!("file{i}.txt"));
+            match i % 3 {
+                0 => {
+                    std::fs::write(&path, format!("content {i}")).unwrap();
+                    let hash = ContextFile::compute_hash(&path).await.unwrap();
+                    ctx.add_file(ContextFile::with_hash(path, hash));
+                }
+                1 => {
+                    std::fs::write(&path, format!("content {i}")).unwrap();
+                    ctx.add_file(ContextFile::with_hash(path, "stale"));
+                }
+                _ => ctx.add_file(ContextFile::with_hash(path, "gone")),
+            }
+        }
+        ctx
+    }
+
+    #[tokio::test]
+    async fn test_restore_concurrent_matches_sequential() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let ctx = many_context_files(temp_dir.path(), 300).await;
+
+        let sequential = ctx.restore_with_concurrency(1).await.unwrap();
+        let concurrent = ctx.restore_with_concurrency(32).await.unwrap();
+
+        assert_eq!(sequential.restored_files.len(), 100);
+        assert_eq!(sequential.changed_files.len(), 100);
+        assert_eq!(sequential.missing_files.len(), 100);
+        assert_eq!(concurrent.restored_files, sequential.restored_files);
+        assert_eq!(concurrent.changed_files, sequential.changed_files);
+        assert_eq!(concurrent.missing_files, sequential.missing_files);
+
+        // Each list keeps the order the files were added in
+        let expected: Vec<PathBuf> = (0..300)
+            .step_by(3)
+            .map(|i| temp_dir.path().join(format!("file{i}.txt")))
+            .collect();
+        assert_eq!(concurrent.restored_files, expected);
+    }
+
+    #[tokio::test]
+    async fn test_restore_checks_files_concurrently_up_to_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let mut ctx = SessionContext::new();
+        for i in 0..40 {
+            ctx.add_file(ContextFile::new(format!("/virtual/file{i}.txt")));
+        }
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let result = ctx
+            .restore_by(8, |file| {
+                let in_flight = Arc::clone(&in_flight);
+                let peak = Arc::clone(&peak);
+                // Later files finish first, so ordering must not follow completion
+                let index: u64 = file.path().file_stem().unwrap().to_str().unwrap()["file".len()..]
+                    .parse()
+                    .unwrap();
+                let delay = 40 - index;
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(FileStatus::Unchanged)
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(peak.load(Ordering::SeqCst), 8);
+        let expected: Vec<PathBuf> = (0..40)
+            .map(|i| PathBuf::from(format!("/virtual/file{i}.txt")))
+            .collect();
+        assert_eq!(result.restored_files, expected);
+    }
 }
@@ -7,16 +7,26 @@ use std::time::SystemTime;
 
 /// Formats a single session entry for display with ID, working directory,
 /// and timestamp.
+///
+/// The session name (or generated title, if unnamed) follows the ID and tags
+/// are appended when present.
 #[must_use]
 pub fn format_session_entry(metadata: &SessionMetadata) -> String {
     let updated = format_timestamp(metadata.updated_at);
-    format!(
-        "{} | {} | {} msgs | {}",
-        metadata.id,
+    let mut entry = metadata.id.clone();
+    if let Some(name) = metadata.name.as_ref().or(metadata.title.as_ref()) {
+        entry.push_str(&format!(" | {name}"));
+    }
+    entry.push_str(&format!(
+        " | {} | {} msgs | {}",
         metadata.working_dir.display(),
         metadata.message_count,
         updated
-    )
+    ));
+    if !metadata.tags.is_empty() {
+        entry.push_str(&format!(" | tags: {}", metadata.tags.join(", ")));
+    }
+    entry
 }
 
 /// Formats a list of session metadata for display.
@@ -31,7 +41,7 @@ pub fn format_session_list(sessions: &[SessionMetadata]) -> String {
 
     // Sort by updated_at descending (most recent first)
     let mut sorted = sessions.to_vec();
-    sorted.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
 
     let mut output = String::from("Available sessions:\n\n");
 
@@ -152,12 +162,58 @@ mod tests {
             created_at: std::time::UNIX_EPOCH,
             updated_at: std::time::UNIX_EPOCH,
             message_count: 5,
+            tags: Vec::new(),
+            name: None,
+            title: None,
         };
 
         let formatted = format_session_entry(&metadata);
         assert!(formatted.contains("test-123"));
         assert!(formatted.contains("/test/project"));
         assert!(formatted.contains("5 msgs"));
+        assert!(!formatted.contains("tags:"));
+    }
+
+    #[test]
+    fn test_format_session_entry_with_tags() {
+        let metadata = SessionMetadata {
+            id: "test-123".to_string(),
+            working_dir: PathBuf::from("/test/project"),
+            created_at: std::time::UNIX_EPOCH,
+            updated_at: std::time::UNIX_EPOCH,
+            message_count: 2,
+            tags: vec!["bugfix".to_string(), "urgent".to_string()],
+            name: None,
+            title: None,
+        };
+
+        let formatted = format_session_entry(&metadata);
+        assert!(formatted.ends_with("| tags: bugfix, urgent"));
+    }
+
+    #[test]
+    fn test_format_session_entry_with_name() {
+        let metadata = SessionMetadata {
+            id: "test-123".to_string(),
+            working_dir: PathBuf::from("/test/project"),
+            created_at: std::time::UNIX_EPOCH,
+            updated_at: std::time::UNIX_EPOCH,
+            message_count: 2,
+            tags: Vec::new(),
+            name: Some("Fix login redirect".to_string()),
+            title: None,
+        };
+
+        let formatted = format_session_entry(&metadata);
+        assert!(formatted.starts_with("test-123 | Fix login redirect | /test/project"));
+
+        let metadata = SessionMetadata {
+            name: None,
+            title: Some("Renaming a Git branch".to_string()),
+            ..metadata
+        };
+        let formatted = format_session_entry(&metadata);
+        assert!(formatted.starts_with("test-123 | Renaming a Git branch | /test/project"));
     }
 
     #[test]
@@ -176,6 +232,9 @@ mod tests {
                 created_at: std::time::UNIX_EPOCH,
                 updated_at: std::time::UNIX_EPOCH,
                 message_count: 3,
+                tags: Vec::new(),
+                name: None,
+                title: None,
             },
             SessionMetadata {
                 id: "session-2".to_string(),
@@ -183,6 +242,9 @@ mod tests {
                 created_at: std::time::UNIX_EPOCH,
                 updated_at: std::time::UNIX_EPOCH,
                 message_count: 7,
+                tags: Vec::new(),
+                name: None,
+                title: None,
             },
         ];
 
@@ -10,9 +10,13 @@ use std::time::SystemTime;
 use tokio::fs;
 use uuid::Uuid;
 
-use super::persistence::{atomic_write, validate_session_id, SessionFile};
+use super::persistence::{
+    durable_write, network_filesystem_type, validate_session_id, SessionFile,
+};
 use super::worktree::WorktreeCommit;
 use super::Session;
+use crate::types::config::SessionWriteMode;
+use crate::util::atomic_write;
 
 /// Metadata about a session without the full message content.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +35,18 @@ pub struct SessionMetadata {
 
     /// Number of messages in the session.
     pub message_count: usize,
+
+    /// Tags assigned to the session.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Human-readable session name, if set.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Title generated from the first exchange, if any.
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
 /// Context information for restoring a session in a worktree.
@@ -70,6 +86,12 @@ pub struct SessionRestoreResult {
 pub struct SessionManager {
     /// Directory where sessions are stored.
     sessions_dir: PathBuf,
+
+    /// Whether secrets are redacted from sessions before they are written.
+    redact_secrets: bool,
+
+    /// Whether writes are fsynced and verified.
+    durable_writes: bool,
 }
 
 impl SessionManager {
@@ -80,7 +102,79 @@ impl SessionManager {
     /// * `sessions_dir` - Directory where sessions will be stored.
     #[must_use]
     pub fn new(sessions_dir: PathBuf) -> Self {
-        Self { sessions_dir }
+        Self {
+            sessions_dir,
+            redact_secrets: false,
+            durable_writes: false,
+        }
+    }
+
+    /// Sets how session files are written.
+    ///
+    /// [`SessionWriteMode::Auto`] enables durable writes when the sessions
+    /// directory is on a network filesystem (detected on Linux only), and
+    /// logs a warning when it is.
+    #[must_use]
+    pub fn with_write_mode(mut self, mode: SessionWriteMode) -> Self {
+        self.durable_writes = match mode {
+            SessionWriteMode::Fast => false,
+            SessionWriteMode::Durable => true,
+            SessionWriteMode::Auto => {
+                let network_fs = self.network_filesystem();
+                if let Some(fs_type) = &network_fs {
+                    tracing::warn!(
+                        fs_type = %fs_type,
+                        dir = %self.sessions_dir.display(),
+                        "Sessions directory is on a network filesystem; using durable writes"
+                    );
+                }
+                network_fs.is_some()
+            }
+        };
+        self
+    }
+
+    /// Returns true if session writes are fsynced and verified.
+    #[must_use]
+    pub fn durable_writes(&self) -> bool {
+        self.durable_writes
+    }
+
+    /// Returns the filesystem type if the sessions directory is on a network mount.
+    #[must_use]
+    pub fn network_filesystem(&self) -> Option<String> {
+        network_filesystem_type(&self.sessions_dir)
+    }
+
+    /// Writes a serialized session using the configured write path.
+    async fn write_session_file(&self, path: &std::path::Path, json: &str) -> Result<()> {
+        if self.durable_writes {
+            durable_write(path, json).await
+        } else {
+            atomic_write(path, json).await
+        }
+        .context("Failed to write session file")
+    }
+
+    /// Enables redacting secrets from sessions before writing them to disk.
+    ///
+    /// The stored copy (and its integrity checksum) contains the redacted
+    /// content; the caller's in-memory session is left untouched.
+    #[must_use]
+    pub fn with_secret_redaction(mut self, enabled: bool) -> Self {
+        self.redact_secrets = enabled;
+        self
+    }
+
+    /// Prepares a copy of `session` for writing under `session_id`.
+    fn prepare_for_write(&self, session: &Session, session_id: &str) -> Session {
+        let mut session_to_save = session.clone();
+        session_to_save.set_id(Some(session_id.to_string()));
+        session_to_save.touch();
+        if self.redact_secrets {
+            session_to_save.redact_secrets();
+        }
+        session_to_save
     }
 
     /// Saves a session to disk.
@@ -104,10 +198,8 @@ impl SessionManager {
             .map(String::from)
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-        // Create a copy with the ID set
-        let mut session_to_save = session.clone();
-        session_to_save.set_id(Some(session_id.clone()));
-        session_to_save.touch();
+        // Create a copy with the ID set (and secrets redacted, if enabled)
+        let session_to_save = self.prepare_for_write(session, &session_id);
 
         // Wrap with checksum for integrity
         let session_file = SessionFile::new(session_to_save)?;
@@ -117,33 +209,63 @@ impl SessionManager {
             serde_json::to_string_pretty(&session_file).context("Failed to serialize session")?;
 
         let path = self.session_path(&session_id);
-        atomic_write(&path, &json)
-            .await
-            .context("Failed to write session file")?;
+        self.write_session_file(&path, &json).await?;
 
         Ok(session_id)
     }
 
-    /// Loads a session from disk.
+    /// Loads a session from disk by ID or name.
+    ///
+    /// An existing session file with a matching ID takes precedence;
+    /// otherwise the sessions are scanned for one with that name, and the
+    /// most recently updated match is loaded.
     ///
     /// # Arguments
     ///
-    /// * `session_id` - The ID of the session to load.
+    /// * `session_id` - The ID or name of the session to load.
     ///
     /// # Errors
     ///
     /// Returns an error if the session ID is invalid, doesn't exist, cannot be read,
-    /// or fails integrity verification.
+    /// was saved by a newer version of patina, or fails integrity verification.
     pub async fn load(&self, session_id: &str) -> Result<Session> {
         validate_session_id(session_id)?;
+        if self.session_path(session_id).exists() {
+            return self.load_by_id(session_id).await;
+        }
+
+        let id = self
+            .find_by_name(session_id)
+            .await?
+            .with_context(|| format!("No session with ID or name '{}'", session_id))?;
+        self.load_by_id(&id).await
+    }
+
+    /// Finds the most recently updated session with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sessions directory cannot be read.
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<String>> {
+        let sessions = self.list_with_metadata().await?;
+
+        Ok(sessions
+            .into_iter()
+            .filter(|(_, metadata)| metadata.name.as_deref() == Some(name))
+            .max_by_key(|(_, metadata)| metadata.updated_at)
+            .map(|(id, _)| id))
+    }
+
+    /// Loads the session file with the given ID.
+    async fn load_by_id(&self, session_id: &str) -> Result<Session> {
         let path = self.session_path(session_id);
 
         let json = fs::read_to_string(&path)
             .await
             .context("Failed to read session file")?;
 
-        let session_file: SessionFile =
-            serde_json::from_str(&json).context("Failed to deserialize session")?;
+        // Upgrade files from older schema versions before verifying them
+        let session_file = SessionFile::parse(&json)?;
 
         // Verify integrity checksum (convert RctError to anyhow::Error)
         Ok(session_file.verify()?)
@@ -161,10 +283,8 @@ impl SessionManager {
     /// Returns an error if the session ID is invalid or cannot be written.
     pub async fn update(&self, session_id: &str, session: &Session) -> Result<()> {
         validate_session_id(session_id)?;
-        // Create a copy with the correct ID
-        let mut session_to_save = session.clone();
-        session_to_save.set_id(Some(session_id.to_string()));
-        session_to_save.touch();
+        // Create a copy with the correct ID (and secrets redacted, if enabled)
+        let session_to_save = self.prepare_for_write(session, session_id);
 
         // Wrap with checksum for integrity
         let session_file = SessionFile::new(session_to_save)?;
@@ -173,9 +293,7 @@ impl SessionManager {
             serde_json::to_string_pretty(&session_file).context("Failed to serialize session")?;
 
         let path = self.session_path(session_id);
-        atomic_write(&path, &json)
-            .await
-            .context("Failed to write session file")?;
+        self.write_session_file(&path, &json).await?;
 
         Ok(())
     }
@@ -253,9 +371,8 @@ impl SessionManager {
     ///
     /// Returns an error if the session ID is invalid or cannot be read.
     pub async fn get_metadata(&self, session_id: &str) -> Result<SessionMetadata> {
-        // Note: load() already validates session_id, but we validate here for clarity
         validate_session_id(session_id)?;
-        let session = self.load(session_id).await?;
+        let session = self.load_by_id(session_id).await?;
 
         Ok(SessionMetadata {
             id: session_id.to_string(),
@@ -263,6 +380,9 @@ impl SessionManager {
             created_at: session.created_at(),
             updated_at: session.updated_at(),
             message_count: session.messages().len(),
+            tags: session.tags().to_vec(),
+            name: session.name().map(String::from),
+            title: session.title().map(String::from),
         })
     }
 
@@ -363,6 +483,9 @@ impl SessionManager {
                                 created_at: session.created_at(),
                                 updated_at: session.updated_at(),
                                 message_count: session.messages().len(),
+                                tags: session.tags().to_vec(),
+                                name: session.name().map(String::from),
+                                title: session.title().map(String::from),
                             },
                         ));
                     }
@@ -455,8 +578,23 @@ impl SessionManager {
         let mut sorted: Vec<SessionMetadata> =
             sessions.into_iter().map(|(_, metadata)| metadata).collect();
 
-        sorted.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        sorted.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
 
         Ok(sorted)
     }
+
+    /// Lists sessions carrying the given tag, most recently updated first.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag to filter by.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sessions cannot be read.
+    pub async fn list_by_tag(&self, tag: &str) -> Result<Vec<SessionMetadata>> {
+        let mut sessions = self.list_sorted().await?;
+        sessions.retain(|metadata| metadata.tags.iter().any(|t| t == tag));
+        Ok(sessions)
+    }
 }
@@ -29,17 +29,26 @@ mod context;
 mod format;
 mod manager;
 mod persistence;
+mod shell_state;
+mod title;
 mod ui_state;
 mod worktree;
 
 // Re-export types
-pub use context::{ContextFile, ContextRestoreResult, SessionContext};
+pub use context::{
+    ContextFile, ContextResource, ContextRestoreResult, EditorSelection, SessionContext,
+    DEFAULT_RESTORE_CONCURRENCY, MAX_EDITOR_SELECTION_CHARS,
+};
 pub use format::{format_session_entry, format_session_list};
 pub use manager::{SessionManager, SessionMetadata, SessionRestoreResult, WorktreeRestoreContext};
+pub use persistence::{validate_session_name, SESSION_SCHEMA_VERSION};
+pub use shell_state::ShellSnapshot;
+pub use title::{generate_title, MAX_TITLE_CHARS, TITLE_MAX_TOKENS};
 pub use ui_state::UiState;
 pub use worktree::{WorktreeCommit, WorktreeSession};
 
 use crate::types::message::Message;
+use crate::types::params::RequestPreset;
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
@@ -104,6 +113,36 @@ pub struct Session {
     /// skills that were active, enabling context restoration on resume.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     context: Option<SessionContext>,
+
+    /// User-assigned tags for organizing and filtering sessions.
+    ///
+    /// Skipped when empty so sessions saved before tagging existed keep
+    /// a stable serialized form (and therefore a valid checksum).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+
+    /// Request parameter preset active for this session.
+    ///
+    /// Restored on resume so subsequent turns keep the same sampling settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preset: Option<RequestPreset>,
+
+    /// Human-readable session name, shown in `--list-sessions`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    /// Title generated from the first exchange.
+    ///
+    /// Kept apart from `name` so `/save <name>` never loses it; shown in
+    /// `--list-sessions` when the session has no name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+
+    /// Working directory and exported variables of the bash tool.
+    ///
+    /// Restored on resume after the directory is re-validated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shell_state: Option<ShellSnapshot>,
 }
 
 impl Session {
@@ -124,6 +163,11 @@ impl Session {
             worktree_session: None,
             ui_state: None,
             context: None,
+            tags: Vec::new(),
+            preset: None,
+            name: None,
+            title: None,
+            shell_state: None,
         }
     }
 
@@ -139,6 +183,20 @@ impl Session {
         &self.messages
     }
 
+    /// Replaces recognized secrets in message content with `[REDACTED]` and
+    /// drops secret-looking exports from the saved shell state.
+    ///
+    /// Used before writing a session to disk so stored transcripts are safe
+    /// to share; the live conversation is never redacted.
+    pub fn redact_secrets(&mut self) {
+        for message in &mut self.messages {
+            message.content = crate::util::redact::redact_secrets(&message.content);
+        }
+        if let Some(shell_state) = &mut self.shell_state {
+            shell_state.redact_secrets();
+        }
+    }
+
     /// Returns the working directory for this session.
     #[must_use]
     pub fn working_dir(&self) -> &PathBuf {
@@ -230,6 +288,116 @@ impl Session {
         self.context = context;
         self.updated_at = SystemTime::now();
     }
+
+    /// Returns the tags assigned to this session.
+    #[must_use]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Adds a tag to the session.
+    ///
+    /// Tags are trimmed and deduplicated; empty tags are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag to add.
+    pub fn add_tag(&mut self, tag: impl Into<String>) {
+        let tag = tag.into();
+        let tag = tag.trim();
+        if tag.is_empty() || self.has_tag(tag) {
+            return;
+        }
+        self.tags.push(tag.to_string());
+        self.updated_at = SystemTime::now();
+    }
+
+    /// Removes a tag from the session.
+    ///
+    /// Returns `true` if the tag was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag to remove.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|t| t != tag.trim());
+        let removed = self.tags.len() != before;
+        if removed {
+            self.updated_at = SystemTime::now();
+        }
+        removed
+    }
+
+    /// Returns `true` if the session has the given tag.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns the request parameter preset active for this session, if any.
+    #[must_use]
+    pub fn preset(&self) -> Option<&RequestPreset> {
+        self.preset.as_ref()
+    }
+
+    /// Sets the request parameter preset.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset` - The preset to record, or `None` to use default parameters.
+    pub fn set_preset(&mut self, preset: Option<RequestPreset>) {
+        self.preset = preset;
+        self.updated_at = SystemTime::now();
+    }
+
+    /// Returns the session name, if one is set.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Sets the session name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to record, or `None` to clear it.
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+        self.updated_at = SystemTime::now();
+    }
+
+    /// Returns the generated session title, if any.
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Sets the generated session title.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title to record, or `None` to clear it.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+        self.updated_at = SystemTime::now();
+    }
+
+    /// Returns the saved shell state, if any.
+    #[must_use]
+    pub fn shell_state(&self) -> Option<&ShellSnapshot> {
+        self.shell_state.as_ref()
+    }
+
+    /// Sets the shell state to restore on resume.
+    ///
+    /// # Arguments
+    ///
+    /// * `shell_state` - The snapshot to record, or `None` to clear it.
+    pub fn set_shell_state(&mut self, shell_state: Option<ShellSnapshot>) {
+        self.shell_state = shell_state;
+        self.updated_at = SystemTime::now();
+    }
 }
 
 #[cfg(test)]
@@ -237,6 +405,7 @@ mod tests {
     use super::persistence::validate_session_id;
     use super::*;
     use crate::types::message::Role;
+    use std::collections::HashMap;
     use std::path::Path;
     use tempfile::TempDir;
     use tokio::fs;
@@ -350,6 +519,88 @@ mod tests {
         assert_eq!(wt.commits()[0].hash, "abc123");
     }
 
+    #[tokio::test]
+    async fn test_session_manager_redacts_secrets_on_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager =
+            SessionManager::new(temp_dir.path().to_path_buf()).with_secret_redaction(true);
+        let key = "sk-ant-REDACTED";
+
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.add_message(test_message(Role::User, &format!("use key {key}")));
+
+        let id = manager.save(&session).await.unwrap();
+
+        // Live session is untouched
+        assert!(session.messages()[0].content.contains(key));
+
+        // Stored copy is redacted, and its checksum still verifies on load
+        let raw = std::fs::read_to_string(temp_dir.path().join(format!("{id}.json"))).unwrap();
+        assert!(!raw.contains(key));
+        let loaded = manager.load(&id).await.unwrap();
+        assert_eq!(loaded.messages()[0].content, "use key [REDACTED]");
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_redacts_exported_secrets_on_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager =
+            SessionManager::new(temp_dir.path().to_path_buf()).with_secret_redaction(true);
+        let env = HashMap::from([
+            ("API_TOKEN".to_string(), "s3cr3t-value-123".to_string()),
+            ("EDITOR".to_string(), "vim".to_string()),
+        ]);
+
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.set_shell_state(Some(ShellSnapshot::new(PathBuf::from("/test"), env)));
+
+        let id = manager.save(&session).await.unwrap();
+
+        let raw = std::fs::read_to_string(temp_dir.path().join(format!("{id}.json"))).unwrap();
+        assert!(!raw.contains("s3cr3t-value-123"));
+        let loaded = manager.load(&id).await.unwrap();
+        let env = loaded.shell_state().unwrap().env();
+        assert!(!env.contains_key("API_TOKEN"));
+        assert_eq!(env.get("EDITOR").map(String::as_str), Some("vim"));
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_durable_writes_round_trip() {
+        use crate::types::config::SessionWriteMode;
+
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::new(temp_dir.path().to_path_buf())
+            .with_write_mode(SessionWriteMode::Durable);
+        assert!(manager.durable_writes());
+
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.add_message(test_message(Role::User, "Hello"));
+        let id = manager.save(&session).await.unwrap();
+
+        session.add_message(test_message(Role::Assistant, "Hi"));
+        manager.update(&id, &session).await.unwrap();
+
+        let loaded = manager.load(&id).await.unwrap();
+        assert_eq!(loaded.messages().len(), 2);
+        assert!(!SessionManager::new(temp_dir.path().to_path_buf())
+            .with_write_mode(SessionWriteMode::Fast)
+            .durable_writes());
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_keeps_secrets_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::new(temp_dir.path().to_path_buf());
+        let key = "sk-ant-REDACTED";
+
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.add_message(test_message(Role::User, key));
+
+        let id = manager.save(&session).await.unwrap();
+        let loaded = manager.load(&id).await.unwrap();
+        assert_eq!(loaded.messages()[0].content, key);
+    }
+
     // =========================================================================
     // Session restore per worktree tests (8.5.2)
     // =========================================================================
@@ -768,6 +1019,29 @@ mod tests {
         assert_eq!(deserialized.active_skills().len(), 1);
     }
 
+    #[test]
+    fn test_session_context_resources() {
+        let mut ctx = SessionContext::new();
+        ctx.add_resource(ContextResource::new("docs", "file:///guide.md"));
+        ctx.add_resource(ContextResource::new("docs", "file:///guide.md")); // duplicate
+        ctx.add_resource(ContextResource::new("db", "file:///guide.md"));
+        assert_eq!(ctx.resources().len(), 2);
+
+        let json = serde_json::to_string(&ctx).expect("Failed to serialize");
+        let deserialized: SessionContext =
+            serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(deserialized.resources(), ctx.resources());
+
+        // Contexts saved before resources were tracked still load
+        let legacy: SessionContext =
+            serde_json::from_str(r#"{"context_files":[],"active_skills":[]}"#)
+                .expect("Failed to deserialize");
+        assert!(legacy.resources().is_empty());
+        assert!(!serde_json::to_string(&legacy)
+            .unwrap()
+            .contains("resources"));
+    }
+
     #[test]
     fn test_session_with_context() {
         let mut session = Session::new(PathBuf::from("/test"));
@@ -1077,6 +1351,9 @@ mod tests {
             created_at: UNIX_EPOCH + Duration::from_secs(1706745600), // 2024-02-01 00:00:00 UTC
             updated_at: UNIX_EPOCH + Duration::from_secs(1706745600),
             message_count: 5,
+            tags: Vec::new(),
+            name: None,
+            title: None,
         };
 
         let output = super::format_session_entry(&metadata);
@@ -1107,6 +1384,9 @@ mod tests {
             created_at: UNIX_EPOCH + Duration::from_secs(1706745600),
             updated_at: UNIX_EPOCH + Duration::from_secs(1706745600),
             message_count: 3,
+            tags: Vec::new(),
+            name: None,
+            title: None,
         }];
 
         let output = super::format_session_list(&sessions);
@@ -1128,6 +1408,9 @@ mod tests {
                 created_at: UNIX_EPOCH + Duration::from_secs(1000),
                 updated_at: UNIX_EPOCH + Duration::from_secs(1000),
                 message_count: 1,
+                tags: Vec::new(),
+                name: None,
+                title: None,
             },
             SessionMetadata {
                 id: "new-session".to_string(),
@@ -1135,6 +1418,9 @@ mod tests {
                 created_at: UNIX_EPOCH + Duration::from_secs(2000),
                 updated_at: UNIX_EPOCH + Duration::from_secs(2000),
                 message_count: 2,
+                tags: Vec::new(),
+                name: None,
+                title: None,
             },
         ];
 
@@ -1174,4 +1460,117 @@ mod tests {
         assert_eq!(sorted.len(), 2);
         assert!(sorted[0].updated_at >= sorted[1].updated_at);
     }
+
+    // =========================================================================
+    // Session tagging tests
+    // =========================================================================
+
+    #[test]
+    fn test_session_tags_empty_by_default() {
+        let session = Session::new(PathBuf::from("/test"));
+        assert!(session.tags().is_empty());
+    }
+
+    #[test]
+    fn test_session_add_tag_deduplicates() {
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.add_tag("bugfix");
+        session.add_tag("refactor");
+        session.add_tag("bugfix");
+        session.add_tag("  ");
+
+        assert_eq!(session.tags(), ["bugfix", "refactor"]);
+        assert!(session.has_tag("bugfix"));
+    }
+
+    #[test]
+    fn test_session_remove_tag() {
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.add_tag("bugfix");
+        session.add_tag("refactor");
+
+        assert!(session.remove_tag("bugfix"));
+        assert!(!session.remove_tag("bugfix"));
+        assert_eq!(session.tags(), ["refactor"]);
+    }
+
+    #[test]
+    fn test_session_without_tags_deserializes() {
+        // Sessions saved before tagging existed have no `tags` field
+        let session = Session::new(PathBuf::from("/test"));
+        let json = serde_json::to_string(&session).expect("Failed to serialize");
+        assert!(!json.contains("tags"));
+
+        let deserialized: Session = serde_json::from_str(&json).expect("Failed to deserialize");
+        assert!(deserialized.tags().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_tags_survive_integrity_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::new(temp_dir.path().to_path_buf());
+
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.add_tag("release");
+        session.add_message(test_message(Role::User, "Hello"));
+
+        let id = manager.save(&session).await.unwrap();
+        let loaded = manager.load(&id).await.unwrap();
+
+        assert_eq!(loaded.tags(), ["release"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_by_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::new(temp_dir.path().to_path_buf());
+
+        let mut session1 = Session::new(PathBuf::from("/project1"));
+        session1.add_tag("bugfix");
+        let id1 = manager.save(&session1).await.unwrap();
+
+        let mut session2 = Session::new(PathBuf::from("/project2"));
+        session2.add_tag("feature");
+        manager.save(&session2).await.unwrap();
+
+        let session3 = Session::new(PathBuf::from("/project3"));
+        manager.save(&session3).await.unwrap();
+
+        let tagged = manager.list_by_tag("bugfix").await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].id, id1);
+        assert_eq!(tagged[0].tags, vec!["bugfix".to_string()]);
+
+        assert!(manager.list_by_tag("missing").await.unwrap().is_empty());
+    }
+
+    // =========================================================================
+    // Request preset tests
+    // =========================================================================
+
+    #[tokio::test]
+    async fn test_session_preset_persists_across_save_load() {
+        use crate::types::RequestParams;
+
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::new(temp_dir.path().to_path_buf());
+
+        let mut session = Session::new(PathBuf::from("/test"));
+        session.set_preset(Some(RequestPreset::new(
+            "creative",
+            RequestParams {
+                temperature: Some(1.0),
+                top_p: Some(0.95),
+                max_tokens: None,
+            },
+        )));
+
+        let id = manager.save(&session).await.unwrap();
+        let loaded = manager.load(&id).await.unwrap();
+
+        let preset = loaded.preset().expect("preset should be restored");
+        assert_eq!(preset.name, "creative");
+        assert_eq!(preset.params.temperature, Some(1.0));
+        assert_eq!(preset.params.top_p, Some(0.95));
+    }
 }
@@ -4,6 +4,7 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sha2::{Digest, Sha256};
 use std::path::Path;
 use tokio::fs;
@@ -19,12 +20,20 @@ use crate::error::{RctError, RctResult};
 /// For stronger security, this should be derived from a user-configured secret.
 pub(super) const INTEGRITY_KEY: &[u8] = b"rct-session-integrity-v1";
 
-/// Writes data to a file atomically using write-to-temp-then-rename pattern.
+/// Writes data to a file with fsync and read-back verification.
 ///
-/// This ensures that concurrent writes don't corrupt the file - each write
-/// either fully succeeds or the file remains unchanged.
-pub(super) async fn atomic_write(path: &Path, contents: &str) -> Result<()> {
-    // Create temp file in same directory (ensures same filesystem for rename)
+/// Rename-based atomicity alone is unreliable on network filesystems (NFS,
+/// SMB), where data may still be cached client-side after the rename. This
+/// path fsyncs the temp file before the rename and the directory after it,
+/// then reads the file back and checks it matches what was written.
+///
+/// # Errors
+///
+/// Returns an error if any write, sync or rename fails, or if the file read
+/// back does not match `contents`.
+pub(super) async fn durable_write(path: &Path, contents: &str) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
     let parent = path.parent().unwrap_or(Path::new("."));
     let temp_name = format!(
         ".{}.tmp.{}",
@@ -35,16 +44,134 @@ pub(super) async fn atomic_write(path: &Path, contents: &str) -> Result<()> {
     );
     let temp_path = parent.join(temp_name);
 
-    // Write to temp file
-    fs::write(&temp_path, contents)
+    let mut file = fs::File::create(&temp_path)
+        .await
+        .context("Failed to create temp file")?;
+    file.write_all(contents.as_bytes())
         .await
         .context("Failed to write temp file")?;
+    file.sync_all().await.context("Failed to sync temp file")?;
+    drop(file);
 
-    // Atomic rename (on POSIX this is atomic, on Windows it's mostly atomic)
     fs::rename(&temp_path, path)
         .await
         .context("Failed to rename temp file")?;
 
+    sync_dir(parent).await?;
+    verify_written(path, contents).await
+}
+
+/// Fsyncs a directory so a rename within it is durable.
+///
+/// Directories cannot be opened for syncing on Windows; this is a no-op there.
+async fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)
+        .await
+        .context("Failed to open sessions directory")?
+        .sync_all()
+        .await
+        .context("Failed to sync sessions directory")?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Reads `path` back and checks that it contains exactly `expected`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or its content differs.
+pub(super) async fn verify_written(path: &Path, expected: &str) -> Result<()> {
+    let actual = fs::read(path)
+        .await
+        .context("Failed to read back session file")?;
+    if actual != expected.as_bytes() {
+        anyhow::bail!(
+            "Session file verification failed: wrote {} bytes but read back {} different bytes from {}",
+            expected.len(),
+            actual.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Filesystem types treated as network mounts.
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smb3",
+    "smbfs",
+    "afs",
+    "9p",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "fuse.sshfs",
+];
+
+/// Returns the filesystem type if `path` is on a network mount.
+///
+/// Detection reads `/proc/self/mounts` and is only available on Linux; other
+/// platforms always return `None`. The nearest existing ancestor is checked
+/// when `path` does not exist yet.
+#[must_use]
+pub(super) fn network_filesystem_type(path: &Path) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let existing = path.ancestors().find(|p| p.exists())?;
+        let canonical = existing.canonicalize().ok()?;
+        let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+        network_fs_type_in(&mounts, &canonical)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Finds the mount containing `path` in a `/proc/self/mounts` listing and
+/// returns its type if it is a network filesystem.
+fn network_fs_type_in(mounts: &str, path: &Path) -> Option<String> {
+    let (_, fs_type) = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces in mount points are escaped as \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point)
+                .then(|| (mount_point, fs_type.to_string()))
+        })
+        .max_by_key(|(mount_point, _)| mount_point.len())?;
+
+    NETWORK_FS_TYPES
+        .contains(&fs_type.as_str())
+        .then_some(fs_type)
+}
+
+/// Current on-disk schema version of session files.
+///
+/// Files written before the version was recorded are version 1. Bump this and
+/// add a step to [`MIGRATIONS`] when the stored shape changes.
+pub const SESSION_SCHEMA_VERSION: u32 = 2;
+
+/// A migration step that upgrades a session file by one schema version.
+type Migration = fn(&mut Value) -> Result<()>;
+
+/// Migration steps, where `MIGRATIONS[n - 1]` upgrades version `n` to `n + 1`.
+///
+/// Steps run on the whole file before its checksum is verified, so a step
+/// that rewrites the session content must also update the checksum.
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2];
+
+/// Version 2 records `schema_version`; the session shape is unchanged.
+fn migrate_v1_to_v2(_file: &mut Value) -> Result<()> {
     Ok(())
 }
 
@@ -54,6 +181,8 @@ pub(super) async fn atomic_write(path: &Path, contents: &str) -> Result<()> {
 /// wrapping the actual session data with a checksum for integrity verification.
 #[derive(Debug, Serialize, Deserialize)]
 pub(super) struct SessionFile {
+    /// Schema version the file was written with.
+    schema_version: u32,
     /// The session data.
     pub(super) session: Session,
     /// HMAC-SHA256 checksum of the session JSON (hex-encoded).
@@ -66,7 +195,49 @@ impl SessionFile {
         let session_json =
             serde_json::to_string(&session).context("Failed to serialize session for checksum")?;
         let checksum = compute_checksum(&session_json);
-        Ok(Self { session, checksum })
+        Ok(Self {
+            schema_version: SESSION_SCHEMA_VERSION,
+            session,
+            checksum,
+        })
+    }
+
+    /// Parses a stored session file, migrating older schema versions to the
+    /// current one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid, the file was written by a
+    /// newer version of patina, or a migration fails.
+    pub(super) fn parse(json: &str) -> Result<Self> {
+        let mut file: Value =
+            serde_json::from_str(json).context("Failed to deserialize session")?;
+
+        let version = match file.get("schema_version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|v| *v >= 1)
+                .context("Invalid session schema_version")?,
+        };
+        if version > SESSION_SCHEMA_VERSION {
+            anyhow::bail!(
+                "Session was saved by a newer version of patina (schema version {}, \
+                 this version supports up to {}); upgrade patina to open it",
+                version,
+                SESSION_SCHEMA_VERSION
+            );
+        }
+
+        for migrate in &MIGRATIONS[version as usize - 1..] {
+            migrate(&mut file)?;
+        }
+        if let Some(fields) = file.as_object_mut() {
+            fields.insert("schema_version".to_string(), SESSION_SCHEMA_VERSION.into());
+        }
+
+        serde_json::from_value(file).context("Failed to deserialize session")
     }
 
     /// Verifies the checksum and returns the session if valid.
@@ -134,6 +305,23 @@ pub(super) fn validate_session_id(session_id: &str) -> RctResult<()> {
     Ok(())
 }
 
+/// Validates a user-chosen session name.
+///
+/// Names can be passed to `--resume` in place of an ID, so they follow the
+/// same rules.
+///
+/// # Errors
+///
+/// Returns an error if the name is empty or contains characters other than
+/// ASCII letters, digits, hyphens and underscores.
+pub fn validate_session_name(name: &str) -> RctResult<()> {
+    validate_session_id(name).map_err(|_| {
+        RctError::session_validation(
+            "invalid session name: must be non-empty and contain only alphanumeric characters, hyphens, and underscores",
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +341,34 @@ mod tests {
         assert_ne!(checksum1, checksum2);
     }
 
+    #[test]
+    fn test_migrations_reach_current_version() {
+        assert_eq!(MIGRATIONS.len() as u32, SESSION_SCHEMA_VERSION - 1);
+    }
+
+    #[test]
+    fn test_parse_migrates_v1_file() {
+        let file = SessionFile::new(Session::new(std::path::PathBuf::from("/test"))).unwrap();
+        let mut v1 = serde_json::to_value(&file).unwrap();
+        v1.as_object_mut().unwrap().remove("schema_version");
+
+        let parsed = SessionFile::parse(&v1.to_string()).unwrap();
+
+        assert_eq!(parsed.schema_version, SESSION_SCHEMA_VERSION);
+        assert!(parsed.verify().is_ok());
+    }
+
+    #[test]
+    fn test_parse_rejects_newer_version() {
+        let file = SessionFile::new(Session::new(std::path::PathBuf::from("/test"))).unwrap();
+        let mut future = serde_json::to_value(&file).unwrap();
+        future["schema_version"] = (SESSION_SCHEMA_VERSION + 1).into();
+
+        let err = SessionFile::parse(&future.to_string()).unwrap_err();
+
+        assert!(err.to_string().contains("newer version"), "got: {err}");
+    }
+
     #[test]
     fn test_validate_session_id_valid() {
         assert!(validate_session_id("abc-123_def").is_ok());
@@ -176,4 +392,65 @@ mod tests {
         assert!(validate_session_id("test session").is_err());
         assert!(validate_session_id("test@session").is_err());
     }
+
+    #[test]
+    fn test_validate_session_name() {
+        assert!(validate_session_name("my-refactor").is_ok());
+        assert!(validate_session_name("").is_err());
+        assert!(validate_session_name("../escape").is_err());
+        let err = validate_session_name("has space").unwrap_err();
+        assert!(err.to_string().contains("session name"));
+    }
+
+    #[tokio::test]
+    async fn test_durable_write_produces_correct_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.json");
+
+        durable_write(&path, "first").await.unwrap();
+        durable_write(&path, "{\"second\": true}").await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"second\": true}"
+        );
+        // No temp files are left behind
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_written_detects_mismatch() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.json");
+        std::fs::write(&path, "truncated").unwrap();
+
+        assert!(verify_written(&path, "truncated").await.is_ok());
+        let err = verify_written(&path, "truncated content")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("verification failed"));
+    }
+
+    #[test]
+    fn test_network_fs_type_uses_longest_mount() {
+        let mounts = "\
+/dev/sda1 / ext4 rw 0 0
+server:/export /home/me/shared nfs4 rw 0 0
+//nas/share /mnt/my\\040nas cifs rw 0 0
+tmpfs /home/me/shared/cache tmpfs rw 0 0
+";
+        assert_eq!(
+            network_fs_type_in(mounts, Path::new("/home/me/shared/sessions")),
+            Some("nfs4".to_string())
+        );
+        assert_eq!(
+            network_fs_type_in(mounts, Path::new("/mnt/my nas/sessions")),
+            Some("cifs".to_string())
+        );
+        assert_eq!(
+            network_fs_type_in(mounts, Path::new("/home/me/shared/cache/x")),
+            None
+        );
+        assert_eq!(network_fs_type_in(mounts, Path::new("/var/lib")), None);
+    }
 }
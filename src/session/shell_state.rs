@@ -0,0 +1,102 @@
+//! Shell state for session persistence.
+//!
+//! Captures the bash tool's tracked working directory and exported variables
+//! so they survive a session resume.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Snapshot of the persistent shell state.
+///
+/// The directory is stored as it was when saved; it is re-validated against
+/// the working directory before being used again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShellSnapshot {
+    /// Current working directory of the shell.
+    cwd: PathBuf,
+
+    /// Environment variables set via `export`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    env: HashMap<String, String>,
+}
+
+impl ShellSnapshot {
+    /// Creates a snapshot from a working directory and environment map.
+    #[must_use]
+    pub fn new(cwd: PathBuf, env: HashMap<String, String>) -> Self {
+        Self { cwd, env }
+    }
+
+    /// Returns the saved working directory.
+    #[must_use]
+    pub fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    /// Returns the saved environment variables.
+    #[must_use]
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// Drops exported variables whose `NAME=value` looks like a credential.
+    ///
+    /// They are dropped rather than kept as `[REDACTED]` so a resumed shell
+    /// never exports the placeholder in place of the real value.
+    pub(crate) fn redact_secrets(&mut self) {
+        self.env.retain(|name, value| {
+            let assignment = format!("{name}={value}");
+            crate::util::redact::redact_secrets(&assignment) == assignment
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_snapshot_serialization() {
+        let env = HashMap::from([("FOO".to_string(), "bar baz".to_string())]);
+        let snapshot = ShellSnapshot::new(PathBuf::from("/project/src"), env);
+
+        let json = serde_json::to_string(&snapshot).expect("Failed to serialize");
+        let restored: ShellSnapshot = serde_json::from_str(&json).expect("Failed to deserialize");
+
+        assert_eq!(restored, snapshot);
+        assert_eq!(
+            restored.env().get("FOO").map(String::as_str),
+            Some("bar baz")
+        );
+    }
+
+    #[test]
+    fn test_redact_secrets_drops_credential_exports() {
+        let env = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), "hunter2hunter2".to_string()),
+            (
+                "KEY".to_string(),
+                "sk-ant-REDACTED".to_string(),
+            ),
+            ("RUST_LOG".to_string(), "debug".to_string()),
+        ]);
+        let mut snapshot = ShellSnapshot::new(PathBuf::from("/project"), env);
+
+        snapshot.redact_secrets();
+
+        assert_eq!(snapshot.env().len(), 1);
+        assert_eq!(
+            snapshot.env().get("RUST_LOG").map(String::as_str),
+            Some("debug")
+        );
+    }
+
+    #[test]
+    fn test_shell_snapshot_env_optional() {
+        let restored: ShellSnapshot =
+            serde_json::from_str(r#"{"cwd":"/project"}"#).expect("Failed to deserialize");
+        assert_eq!(restored.cwd(), Path::new("/project"));
+        assert!(restored.env().is_empty());
+    }
+}
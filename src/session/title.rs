@@ -0,0 +1,140 @@
+//! Conversation title generation.
+//!
+//! After the first exchange, a short title can be requested from a cheap
+//! model and stored as the session name so `--list-sessions` is readable.
+
+use anyhow::Result;
+
+use crate::api::AnthropicClient;
+use crate::types::{Message, Role};
+
+/// Maximum title length in characters.
+pub const MAX_TITLE_CHARS: usize = 60;
+
+/// `max_tokens` to request for a title; a few words need very few tokens.
+pub const TITLE_MAX_TOKENS: u32 = 32;
+
+/// Maximum characters of each message included in the title prompt.
+const MAX_EXCERPT_CHARS: usize = 2000;
+
+/// Requests a short title for a conversation from its first exchange.
+///
+/// The request is sent with `client` as configured, so callers choose the
+/// (typically cheaper) model with [`AnthropicClient::with_model`].
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the model returns an empty title.
+pub async fn generate_title(
+    client: &AnthropicClient,
+    user_message: &str,
+    assistant_message: &str,
+) -> Result<String> {
+    let prompt = format!(
+        "Write a short title (at most 6 words) for the conversation below. \
+         Reply with the title only, without quotes or punctuation at the end.\n\n\
+         User: {}\n\nAssistant: {}",
+        excerpt(user_message),
+        excerpt(assistant_message)
+    );
+
+    let response = client
+        .complete(&[Message {
+            role: Role::User,
+            content: prompt,
+        }])
+        .await?;
+
+    clean_title(&response).ok_or_else(|| anyhow::anyhow!("Model returned an empty title"))
+}
+
+/// Normalizes a model-generated title.
+///
+/// Takes the first non-empty line, strips a `Title:` prefix, surrounding
+/// quotes and trailing punctuation, and caps the length.
+fn clean_title(raw: &str) -> Option<String> {
+    let line = raw.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line)
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`' | '*' | '#'))
+        .trim_end_matches(['.', '!', '?', ':'])
+        .trim();
+
+    if line.is_empty() {
+        return None;
+    }
+
+    Some(
+        line.chars()
+            .take(MAX_TITLE_CHARS)
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    )
+}
+
+/// Truncates a message for inclusion in the title prompt.
+fn excerpt(text: &str) -> String {
+    text.chars().take(MAX_EXCERPT_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secrecy::SecretString;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            clean_title("\"Fixing the login redirect.\"").as_deref(),
+            Some("Fixing the login redirect")
+        );
+        assert_eq!(
+            clean_title("\nTitle: Rust lifetimes\nExtra").as_deref(),
+            Some("Rust lifetimes")
+        );
+        assert_eq!(clean_title("  \n \"\" "), None);
+        assert_eq!(
+            clean_title(&"x".repeat(200)).map(|t| t.chars().count()),
+            Some(MAX_TITLE_CHARS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_title_uses_first_exchange() {
+        let mock_server = MockServer::start().await;
+        let sse_response = r#"event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"\"Parsing TOML config\""}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_string_contains("How do I parse TOML"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(sse_response, "text/event-stream"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = AnthropicClient::new_with_base_url(
+            SecretString::from("test-key"),
+            "claude-3-haiku-20240307",
+            &mock_server.uri(),
+        );
+        let title = generate_title(&client, "How do I parse TOML?", "Use the toml crate.")
+            .await
+            .unwrap();
+
+        assert_eq!(title, "Parsing TOML config");
+    }
+}
@@ -4,11 +4,13 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::tui::selection::{ContentPosition, FocusArea};
+
 /// UI state for session resume.
 ///
 /// Captures the terminal UI state so it can be restored when resuming a session.
 /// This allows users to continue exactly where they left off, including their
-/// scroll position, any unsent input, and cursor position.
+/// scroll position, any unsent input, cursor position, focus and selection.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UiState {
     /// Vertical scroll offset in the message view.
@@ -19,6 +21,17 @@ pub struct UiState {
 
     /// Cursor position within the input buffer.
     cursor_position: usize,
+
+    /// Area that had keyboard focus.
+    #[serde(default)]
+    focus_area: FocusArea,
+
+    /// Completed content selection as (start, end), if any.
+    ///
+    /// Positions refer to rendered lines, so the range is clamped to the
+    /// content on restore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selection: Option<(ContentPosition, ContentPosition)>,
 }
 
 impl UiState {
@@ -31,6 +44,8 @@ impl UiState {
             scroll_offset: 0,
             input_buffer: String::new(),
             cursor_position: 0,
+            focus_area: FocusArea::default(),
+            selection: None,
         }
     }
 
@@ -47,9 +62,23 @@ impl UiState {
             scroll_offset,
             input_buffer,
             cursor_position,
+            focus_area: FocusArea::default(),
+            selection: None,
         }
     }
 
+    /// Sets the focus area and content selection to save.
+    #[must_use]
+    pub fn with_focus(
+        mut self,
+        focus_area: FocusArea,
+        selection: Option<(ContentPosition, ContentPosition)>,
+    ) -> Self {
+        self.focus_area = focus_area;
+        self.selection = selection;
+        self
+    }
+
     /// Returns the scroll offset.
     #[must_use]
     pub fn scroll_offset(&self) -> usize {
@@ -67,6 +96,18 @@ impl UiState {
     pub fn cursor_position(&self) -> usize {
         self.cursor_position
     }
+
+    /// Returns the area that had keyboard focus.
+    #[must_use]
+    pub fn focus_area(&self) -> FocusArea {
+        self.focus_area
+    }
+
+    /// Returns the saved content selection as (start, end), if any.
+    #[must_use]
+    pub fn selection(&self) -> Option<(ContentPosition, ContentPosition)> {
+        self.selection
+    }
 }
 
 impl Default for UiState {
@@ -101,5 +142,30 @@ mod tests {
         assert_eq!(state.scroll_offset(), 10);
         assert_eq!(state.input_buffer(), "hello world");
         assert_eq!(state.cursor_position(), 5);
+        assert_eq!(state.focus_area(), FocusArea::Input);
+        assert_eq!(state.selection(), None);
+    }
+
+    #[test]
+    fn test_ui_state_focus_round_trip() {
+        let selection = (ContentPosition::new(1, 2), ContentPosition::new(3, 4));
+        let state = UiState::with_state(0, String::new(), 0)
+            .with_focus(FocusArea::Content, Some(selection));
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: UiState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.focus_area(), FocusArea::Content);
+        assert_eq!(restored.selection(), Some(selection));
+    }
+
+    #[test]
+    fn test_ui_state_without_focus_fields_deserializes() {
+        let json = r#"{"scroll_offset":3,"input_buffer":"hi","cursor_position":2}"#;
+        let state: UiState = serde_json::from_str(json).unwrap();
+
+        assert_eq!(state.scroll_offset(), 3);
+        assert_eq!(state.focus_area(), FocusArea::Input);
+        assert_eq!(state.selection(), None);
     }
 }
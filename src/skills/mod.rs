@@ -1,7 +1,34 @@
 //! Skills system - auto-invoked context providers
 
+use glob::Pattern;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Returns the directories skills are loaded from.
+///
+/// - `~/.config/patina/skills/` (user skills)
+/// - `<working_dir>/.patina/skills/` (project skills)
+#[must_use]
+pub fn skill_search_paths(working_dir: &Path) -> Vec<PathBuf> {
+    let mut search_paths = Vec::new();
+    if let Some(base_dirs) = directories::BaseDirs::new() {
+        search_paths.push(base_dirs.config_dir().join("patina/skills"));
+    }
+    search_paths.push(working_dir.join(".patina/skills"));
+    search_paths
+}
+
+/// Most characters of skill instructions added to the system prompt.
+pub const MAX_SKILL_PROMPT_CHARS: usize = 16_000;
+
+/// What the conversation is working on, matched against skill triggers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActivationContext<'a> {
+    /// Text of the prompt being submitted.
+    pub prompt: &'a str,
+    /// Files read or changed by tools.
+    pub files: &'a [PathBuf],
+}
 
 #[derive(Debug, Deserialize)]
 pub struct SkillConfig {
@@ -32,6 +59,62 @@ pub struct Skill {
     pub source_path: PathBuf,
 }
 
+impl Skill {
+    /// Returns the instructions from the body of the skill's `SKILL.md`.
+    #[must_use]
+    pub fn instructions(&self) -> &str {
+        &self.instructions
+    }
+
+    /// Returns true if the skill's triggers match `ctx`.
+    #[must_use]
+    pub fn should_activate(&self, ctx: &ActivationContext<'_>) -> bool {
+        self.activation_reason(ctx).is_some()
+    }
+
+    /// Describes which trigger matched `ctx`, if any.
+    ///
+    /// Used to tell the user why a skill was turned on.
+    #[must_use]
+    pub fn activation_reason(&self, ctx: &ActivationContext<'_>) -> Option<String> {
+        let triggers = &self.config.triggers;
+        if triggers.always_active {
+            return Some("always active".to_string());
+        }
+
+        let prompt = ctx.prompt.to_lowercase();
+        if let Some(keyword) = triggers
+            .keywords
+            .iter()
+            .find(|k| !k.is_empty() && prompt.contains(&k.to_lowercase()))
+        {
+            return Some(format!("prompt mentions '{}'", keyword));
+        }
+
+        ctx.files
+            .iter()
+            .find(|file| self.matches_file(file))
+            .map(|file| format!("{} was used", file.display()))
+    }
+
+    /// Returns true if `file_path` matches one of the skill's file patterns.
+    ///
+    /// Patterns are tried against the whole path and the file name, so
+    /// `*.rs` matches `src/main.rs`.
+    #[must_use]
+    pub fn matches_file(&self, file_path: &Path) -> bool {
+        let file_name = file_path.file_name().and_then(|s| s.to_str());
+        self.config
+            .triggers
+            .file_patterns
+            .iter()
+            .filter_map(|p| Pattern::new(p).ok())
+            .any(|pattern| {
+                pattern.matches_path(file_path) || file_name.is_some_and(|n| pattern.matches(n))
+            })
+    }
+}
+
 pub struct SkillEngine {
     skills: Vec<Skill>,
 }
@@ -126,6 +209,62 @@ impl SkillEngine {
         &self.skills
     }
 
+    /// Loads skills from each directory, skipping ones that cannot be read.
+    pub fn load_all(&mut self, dirs: &[PathBuf]) {
+        for dir in dirs {
+            if let Err(e) = self.load_from_dir(dir) {
+                tracing::warn!("Failed to load skills from {}: {}", dir.display(), e);
+            }
+        }
+    }
+
+    /// Returns the skill with the given name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Skill> {
+        self.skills.iter().find(|s| s.name == name)
+    }
+
+    /// Builds the system prompt section for the named active skills.
+    ///
+    /// Skills are added in the order given. Instructions identical to an
+    /// earlier skill's are added once, and a skill whose instructions would
+    /// take the total past `max_chars` is left out. Returns `None` when no
+    /// skill contributes anything.
+    #[must_use]
+    pub fn system_prompt_for(&self, names: &[String], max_chars: usize) -> Option<String> {
+        let mut sections: Vec<String> = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
+        let mut total = 0;
+
+        for skill in names.iter().filter_map(|name| self.get(name)) {
+            let instructions = skill.instructions().trim();
+            if instructions.is_empty() || seen.contains(&instructions) {
+                continue;
+            }
+            let section = format!("## Skill: {}\n\n{}", skill.name, instructions);
+            let len = section.chars().count();
+            if total + len > max_chars {
+                tracing::warn!(
+                    skill = %skill.name,
+                    "Skill instructions left out of the system prompt: over the {} character limit",
+                    max_chars
+                );
+                continue;
+            }
+            total += len;
+            seen.push(instructions);
+            sections.push(section);
+        }
+
+        if sections.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "# Active skills\n\nFollow these instructions where they apply.\n\n{}",
+            sections.join("\n\n")
+        ))
+    }
+
     /// Matches skills based on file path patterns.
     ///
     /// Returns skills whose file_patterns match the given file path.
@@ -144,31 +283,10 @@ impl SkillEngine {
     /// ```
     #[must_use]
     pub fn match_skills_for_file(&self, file_path: &str) -> Vec<&Skill> {
-        use glob::Pattern;
-        use std::path::Path;
-
         let file_path = Path::new(file_path);
-        let file_name = file_path.file_name().and_then(|s| s.to_str());
-
         self.skills
             .iter()
-            .filter(|skill| {
-                for pattern_str in &skill.config.triggers.file_patterns {
-                    if let Ok(pattern) = Pattern::new(pattern_str) {
-                        // Try matching against full path
-                        if pattern.matches_path(file_path) {
-                            return true;
-                        }
-                        // Try matching against just the file name
-                        if let Some(name) = file_name {
-                            if pattern.matches(name) {
-                                return true;
-                            }
-                        }
-                    }
-                }
-                false
-            })
+            .filter(|skill| skill.matches_file(file_path))
             .collect()
     }
 
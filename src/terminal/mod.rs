@@ -5,6 +5,8 @@
 //!   (Sixel, Kitty graphics, iTerm2 inline images)
 //! - **Key binding configuration**: Configures terminal emulators to pass through Cmd+A/C/V
 //!   to the application instead of handling them as terminal shortcuts.
+//! - **Keyboard fallback hint**: A one-time hint naming the clipboard shortcuts to use
+//!   when keyboard enhancement is unavailable.
 //!
 //! # Graphics Protocol Detection
 //!
@@ -26,6 +28,7 @@
 
 use std::env;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tracing::{debug, info, warn};
 
@@ -324,6 +327,89 @@ pub fn terminal_keyboard_hint() -> &'static str {
     }
 }
 
+/// Clipboard shortcuts that work in the current terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardBindings {
+    /// Shortcut that selects all content.
+    pub select_all: &'static str,
+    /// Shortcut that copies the selection.
+    pub copy: &'static str,
+    /// Shortcut that pastes from the clipboard.
+    pub paste: &'static str,
+}
+
+impl ClipboardBindings {
+    /// Cmd shortcuts, available with keyboard enhancement (kitty protocol).
+    pub const ENHANCED: Self = Self {
+        select_all: "Cmd+A",
+        copy: "Cmd+C",
+        paste: "Cmd+V",
+    };
+
+    /// Option shortcuts for JetBrains terminals, where the IDE takes Cmd.
+    pub const JETBRAINS: Self = Self {
+        select_all: "Option+A",
+        copy: "Option+C",
+        paste: "Option+V",
+    };
+
+    /// Ctrl shortcuts that work in any terminal.
+    pub const STANDARD: Self = Self {
+        select_all: "Ctrl+A",
+        copy: "Ctrl+Y",
+        paste: "Ctrl+Shift+V",
+    };
+
+    /// Returns the bindings for a terminal with the given capabilities.
+    #[must_use]
+    pub fn active(enhancement_supported: bool, is_jetbrains: bool) -> Self {
+        if enhancement_supported {
+            Self::ENHANCED
+        } else if is_jetbrains {
+            Self::JETBRAINS
+        } else {
+            Self::STANDARD
+        }
+    }
+}
+
+/// Returns the hint shown when Cmd shortcuts are unavailable.
+#[must_use]
+pub fn keyboard_fallback_hint(bindings: &ClipboardBindings) -> String {
+    format!(
+        "Cmd shortcuts aren't supported in this terminal. Use {} to select all, {} to copy and {} to paste. (Esc to dismiss)",
+        bindings.select_all, bindings.copy, bindings.paste
+    )
+}
+
+/// Returns the file recording that the keyboard fallback hint was shown.
+#[must_use]
+pub fn keyboard_hint_marker() -> Option<PathBuf> {
+    directories::ProjectDirs::from("com", "patina", "patina")
+        .map(|dirs| dirs.data_dir().join("keyboard-hint-shown"))
+}
+
+/// Returns true if the keyboard fallback hint should be shown.
+///
+/// The hint is shown only when keyboard enhancement is unsupported and it has
+/// not been shown before.
+#[must_use]
+pub fn should_show_keyboard_hint(enhancement_supported: bool, marker: &Path) -> bool {
+    !enhancement_supported && !marker.exists()
+}
+
+/// Records that the keyboard fallback hint was shown, so it is not shown again.
+///
+/// # Errors
+///
+/// Returns an error if the marker file cannot be written.
+pub fn mark_keyboard_hint_shown(marker: &Path) -> std::io::Result<()> {
+    if let Some(parent) = marker.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(marker, b"")
+}
+
 /// Detects if running on macOS.
 #[must_use]
 pub fn is_macos() -> bool {
@@ -629,4 +715,44 @@ mod tests {
         // In test environment, should return "Standard" hint
         // (unless running in iTerm2, Kitty, or JetBrains)
     }
+
+    #[test]
+    fn test_clipboard_bindings_active() {
+        assert_eq!(
+            ClipboardBindings::active(true, false),
+            ClipboardBindings::ENHANCED
+        );
+        assert_eq!(
+            ClipboardBindings::active(false, true),
+            ClipboardBindings::JETBRAINS
+        );
+        assert_eq!(
+            ClipboardBindings::active(false, false),
+            ClipboardBindings::STANDARD
+        );
+    }
+
+    #[test]
+    fn test_keyboard_fallback_hint_uses_bindings() {
+        let hint = keyboard_fallback_hint(&ClipboardBindings::STANDARD);
+        assert!(hint.contains("Ctrl+A"));
+        assert!(hint.contains("Ctrl+Y"));
+        assert!(hint.contains("Ctrl+Shift+V"));
+
+        let hint = keyboard_fallback_hint(&ClipboardBindings::JETBRAINS);
+        assert!(hint.contains("Option+C"));
+    }
+
+    #[test]
+    fn test_keyboard_hint_shown_once() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let marker = temp_dir.path().join("state/keyboard-hint-shown");
+
+        // Supported terminals never need the hint
+        assert!(!should_show_keyboard_hint(true, &marker));
+
+        assert!(should_show_keyboard_hint(false, &marker));
+        mark_keyboard_hint_shown(&marker).unwrap();
+        assert!(!should_show_keyboard_hint(false, &marker));
+    }
 }
@@ -0,0 +1,67 @@
+//! Pluggable tool execution backend.
+//!
+//! `ToolBackend` abstracts the step that actually runs a tool call, so the
+//! hook/permission layer in `HookedToolExecutor` and the tool loop can be
+//! driven by a substitute (e.g. a mock returning scripted results in tests,
+//! or an embedder's sandboxed runner).
+
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+
+use super::executor::{ToolCall, ToolExecutor, ToolResult};
+use super::stateful::StatefulToolExecutor;
+
+/// Executes tool calls on behalf of `HookedToolExecutor`.
+///
+/// Implemented by [`ToolExecutor`] and [`StatefulToolExecutor`]. Install a
+/// custom backend with [`HookedToolExecutor::with_backend`](super::HookedToolExecutor::with_backend).
+///
+/// # Examples
+///
+/// ```
+/// use patina::tools::{ToolBackend, ToolCall, ToolResult};
+/// use std::future::Future;
+/// use std::pin::Pin;
+///
+/// struct EchoBackend;
+///
+/// impl ToolBackend for EchoBackend {
+///     fn execute(
+///         &self,
+///         call: ToolCall,
+///     ) -> Pin<Box<dyn Future<Output = anyhow::Result<ToolResult>> + Send + '_>> {
+///         Box::pin(async move { Ok(ToolResult::Success(call.name)) })
+///     }
+/// }
+/// ```
+pub trait ToolBackend: Send + Sync {
+    /// Executes a single tool call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if execution fails in a way that cannot be reported
+    /// as a `ToolResult::Error` (e.g. a timeout or I/O failure).
+    fn execute(
+        &self,
+        call: ToolCall,
+    ) -> Pin<Box<dyn Future<Output = Result<ToolResult>> + Send + '_>>;
+}
+
+impl ToolBackend for ToolExecutor {
+    fn execute(
+        &self,
+        call: ToolCall,
+    ) -> Pin<Box<dyn Future<Output = Result<ToolResult>> + Send + '_>> {
+        Box::pin(ToolExecutor::execute(self, call))
+    }
+}
+
+impl ToolBackend for StatefulToolExecutor {
+    fn execute(
+        &self,
+        call: ToolCall,
+    ) -> Pin<Box<dyn Future<Output = Result<ToolResult>> + Send + '_>> {
+        Box::pin(StatefulToolExecutor::execute(self, call))
+    }
+}
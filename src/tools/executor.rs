@@ -4,23 +4,50 @@
 
 use anyhow::Result;
 use glob::Pattern;
+use ignore::WalkBuilder;
 use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
 use tokio::process::Command;
+use tokio::sync::{mpsc, Mutex};
 use tracing::{debug, warn};
-use walkdir::WalkDir;
 
-use super::security::{normalize_command, ToolExecutionPolicy};
+use super::output_files::ToolOutputFiles;
+use super::security::ToolExecutionPolicy;
 use super::{vision, web_fetch, web_search};
+use crate::mcp::{McpManager, McpToolProgress, MCP_TOOL_PREFIX};
 use crate::permissions::PermissionRequest;
+use crate::plugins::tools::PluginTools;
 use crate::shell::ShellConfig;
+use crate::types::config::PathConfinement;
+use crate::util::{atomic_write, format_bytes};
 
 /// Tool executor with security policy enforcement.
 pub struct ToolExecutor {
     working_dir: PathBuf,
+    /// Boundary that file paths are confined to (working dir or repo root).
+    confinement_root: PathBuf,
+    /// How `confinement_root` is derived from the working directory.
+    path_confinement: PathConfinement,
     pub(crate) policy: ToolExecutionPolicy,
+    /// Restrictions applied to `web_fetch` requests.
+    web_fetch_config: web_fetch::WebFetchConfig,
+    /// Fetch tool, built on first use so its response cache lasts the session.
+    web_fetch_tool: OnceLock<web_fetch::WebFetchTool>,
+    /// Provider and endpoint used by `web_search`.
+    web_search_config: web_search::WebSearchConfig,
+    /// Size limits and downscaling for `analyze_image`.
+    vision_config: vision::VisionConfig,
+    /// Session files that oversized output is saved to, when enabled.
+    tool_outputs: Option<ToolOutputFiles>,
+    /// MCP servers that `mcp__<server>__<tool>` calls are routed to.
+    mcp: Option<Arc<Mutex<McpManager>>>,
+    /// Receives partial results from running MCP tools, when displayed.
+    mcp_progress: Option<mpsc::Sender<McpToolProgress>>,
+    /// Tools registered by loaded plugins.
+    plugin_tools: PluginTools,
 }
 
 #[derive(Debug)]
@@ -47,8 +74,18 @@ pub enum ToolResult {
 impl ToolExecutor {
     pub fn new(working_dir: PathBuf) -> Self {
         Self {
+            confinement_root: working_dir.clone(),
+            path_confinement: PathConfinement::default(),
             working_dir,
             policy: ToolExecutionPolicy::default(),
+            web_fetch_config: web_fetch::WebFetchConfig::default(),
+            web_fetch_tool: OnceLock::new(),
+            web_search_config: web_search::WebSearchConfig::default(),
+            vision_config: vision::VisionConfig::default(),
+            tool_outputs: None,
+            mcp: None,
+            mcp_progress: None,
+            plugin_tools: PluginTools::default(),
         }
     }
 
@@ -57,10 +94,107 @@ impl ToolExecutor {
         self
     }
 
-    /// Validates that a path is within the working directory.
+    /// Sets the boundary that file paths are confined to.
+    #[must_use]
+    pub fn with_path_confinement(mut self, confinement: PathConfinement) -> Self {
+        self.set_path_confinement(confinement);
+        self
+    }
+
+    /// Sets the restrictions applied to `web_fetch` requests.
+    pub fn set_web_fetch_config(&mut self, config: web_fetch::WebFetchConfig) {
+        self.web_fetch_config = config;
+        self.web_fetch_tool = OnceLock::new();
+    }
+
+    /// Sets the provider and endpoint used by `web_search`.
+    pub fn set_web_search_config(&mut self, config: web_search::WebSearchConfig) {
+        self.web_search_config = config;
+    }
+
+    /// Sets the size limits and downscaling used by `analyze_image`.
+    pub fn set_vision_config(&mut self, config: vision::VisionConfig) {
+        self.vision_config = config;
+    }
+
+    /// Routes `mcp__<server>__<tool>` calls to the given MCP servers.
+    #[must_use]
+    pub fn with_mcp_manager(mut self, mcp: Arc<Mutex<McpManager>>) -> Self {
+        self.set_mcp_manager(mcp);
+        self
+    }
+
+    /// Routes `mcp__<server>__<tool>` calls to the given MCP servers.
+    pub fn set_mcp_manager(&mut self, mcp: Arc<Mutex<McpManager>>) {
+        self.mcp = Some(mcp);
+    }
+
+    /// Sends partial results from running MCP tools to `progress`.
+    ///
+    /// Updates are dropped while `progress` is full; the tool result the
+    /// model receives always includes every partial result.
+    pub fn set_mcp_progress(&mut self, progress: mpsc::Sender<McpToolProgress>) {
+        self.mcp_progress = Some(progress);
+    }
+
+    /// Runs calls to plugin-declared tools through the given registry view.
+    pub fn set_plugin_tools(&mut self, tools: PluginTools) {
+        self.plugin_tools = tools;
+    }
+
+    /// Sets the size in bytes above which tool output is saved to a file.
+    ///
+    /// The model then receives the file path and a short preview instead of
+    /// the full output. `None` disables offloading. Files from an earlier
+    /// threshold are removed.
+    pub fn set_tool_output_threshold(&mut self, threshold: Option<usize>) {
+        self.tool_outputs = threshold.map(ToolOutputFiles::new);
+    }
+
+    /// Returns the directory oversized tool output is saved to, if enabled.
+    #[must_use]
+    pub fn tool_output_dir(&self) -> Option<&Path> {
+        self.tool_outputs.as_ref().map(ToolOutputFiles::dir)
+    }
+
+    /// Sets the boundary that file paths are confined to.
+    ///
+    /// With [`PathConfinement::RepoRoot`], the enclosing git repository root is
+    /// detected from the working directory. Outside a repository this falls
+    /// back to the working directory.
+    pub fn set_path_confinement(&mut self, confinement: PathConfinement) {
+        self.path_confinement = confinement;
+        self.confinement_root = match confinement {
+            PathConfinement::WorkingDir => self.working_dir.clone(),
+            PathConfinement::RepoRoot => find_repo_root(&self.working_dir).unwrap_or_else(|| {
+                warn!(
+                    working_dir = %self.working_dir.display(),
+                    "No git repository found, confining paths to working directory"
+                );
+                self.working_dir.clone()
+            }),
+        };
+    }
+
+    /// Moves tool execution to another directory, such as a git worktree.
+    ///
+    /// The confinement root is recomputed for the new directory.
+    pub fn set_working_dir(&mut self, working_dir: PathBuf) {
+        self.working_dir = working_dir;
+        self.set_path_confinement(self.path_confinement);
+    }
+
+    /// Returns the boundary that file paths are confined to.
+    #[must_use]
+    pub fn confinement_root(&self) -> &Path {
+        &self.confinement_root
+    }
+
+    /// Validates that a path is within the confinement root.
     ///
-    /// Returns the canonicalized path if valid, or an error message if the path
-    /// attempts to escape the working directory.
+    /// Relative paths are resolved against the working directory. Returns the
+    /// canonicalized path if valid, or an error message if the path attempts to
+    /// escape the confinement root (the working directory by default).
     ///
     /// # Errors
     ///
@@ -83,9 +217,9 @@ impl ToolExecutor {
 
         let full_path = self.working_dir.join(path);
 
-        // Canonicalize the working directory
-        let canonical_working_dir = self
-            .working_dir
+        // Canonicalize the confinement root
+        let canonical_root = self
+            .confinement_root
             .canonicalize()
             .map_err(|e| format!("Failed to canonicalize working directory: {e}"))?;
 
@@ -120,12 +254,12 @@ impl ToolExecutor {
             }
         };
 
-        // Verify the canonical path starts with the working directory
-        if !canonical_full_path.starts_with(&canonical_working_dir) {
+        // Verify the canonical path starts with the confinement root
+        if !canonical_full_path.starts_with(&canonical_root) {
             warn!(
                 path = %path,
                 canonical_path = %canonical_full_path.display(),
-                working_dir = %canonical_working_dir.display(),
+                working_dir = %canonical_root.display(),
                 "Security: path traversal attempt - path escapes working directory"
             );
             return Err("Path traversal outside working directory".to_string());
@@ -151,6 +285,15 @@ impl ToolExecutor {
         Ok(canonical_path)
     }
 
+    /// Validates a project file read on the user's behalf rather than by a tool.
+    ///
+    /// Applies the confinement, protected-path and symlink checks used for
+    /// writes, so such files get no more access than a tool would.
+    pub(crate) fn validate_project_file(&self, path: &str) -> std::result::Result<PathBuf, String> {
+        self.check_symlink(path)?;
+        self.validate_write_path(path)
+    }
+
     /// Checks if a path is a symlink and returns an error if so.
     ///
     /// This is a security measure to prevent TOCTOU (Time-of-Check-Time-of-Use)
@@ -195,7 +338,55 @@ impl ToolExecutor {
         }
     }
 
+    /// Rejects FIFOs, sockets and devices before reading.
+    ///
+    /// Reading a named pipe with no writer blocks forever, and devices such as
+    /// `/dev/zero` never reach EOF, so only regular files are read.
+    fn check_regular_file(path: &Path, display: &str) -> std::result::Result<(), String> {
+        // Missing files fall through to the read, which reports the error
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return Ok(());
+        };
+        let file_type = metadata.file_type();
+        if file_type.is_file() || file_type.is_dir() {
+            return Ok(());
+        }
+
+        #[cfg(unix)]
+        let kind = {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                "a named pipe"
+            } else if file_type.is_socket() {
+                "a socket"
+            } else if file_type.is_char_device() || file_type.is_block_device() {
+                "a device"
+            } else {
+                "a special file"
+            }
+        };
+        #[cfg(not(unix))]
+        let kind = "a special file";
+
+        warn!(path = %path.display(), kind, "Refusing to read non-regular file");
+        Err(format!(
+            "Cannot read {display}: it is {kind}, not a regular file"
+        ))
+    }
+
     pub async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
+        let result = self.dispatch(&call).await?;
+
+        // read_file is exempt so saved output can be paged back in
+        Ok(match (&self.tool_outputs, result) {
+            (Some(outputs), ToolResult::Success(output)) if call.name != "read_file" => {
+                ToolResult::Success(outputs.offload(&call.name, output))
+            }
+            (_, result) => result,
+        })
+    }
+
+    async fn dispatch(&self, call: &ToolCall) -> Result<ToolResult> {
         match call.name.as_str() {
             "bash" => self.execute_bash(&call.input).await,
             "read_file" => self.read_file(&call.input).await,
@@ -207,7 +398,29 @@ impl ToolExecutor {
             "web_fetch" => self.web_fetch(&call.input).await,
             "web_search" => self.web_search(&call.input).await,
             "analyze_image" => self.analyze_image(&call.input).await,
-            _ => Ok(ToolResult::Error(format!("Unknown tool: {}", call.name))),
+            "read_mcp_resource" => match &self.mcp {
+                Some(mcp) => Ok(mcp.lock().await.read_resource_tool(&call.input).await),
+                None => Ok(ToolResult::Error(
+                    "No MCP servers are configured".to_string(),
+                )),
+            },
+            name if name.starts_with(MCP_TOOL_PREFIX) => match &self.mcp {
+                Some(mcp) => Ok(self.execute_mcp_tool(mcp, name, &call.input).await),
+                None => Ok(ToolResult::Error(format!(
+                    "Unknown tool: {} (no MCP servers are configured)",
+                    name
+                ))),
+            },
+            name => match self.plugin_tools.get(name) {
+                Some(tool) => match self.policy.check_command(tool.spec.command.trim()) {
+                    Ok(()) => Ok(tool.run(&self.working_dir, &call.input).await),
+                    Err(reason) => Ok(ToolResult::Error(format!(
+                        "Plugin tool '{}' from '{}': {}",
+                        name, tool.plugin, reason
+                    ))),
+                },
+                None => Ok(ToolResult::Error(format!("Unknown tool: {}", call.name))),
+            },
         }
     }
 
@@ -217,40 +430,8 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing command"))?;
 
-        // Normalize the command to detect escape-based bypasses (e.g., r\m -> rm)
-        let normalized = normalize_command(command);
-
-        // Check both original and normalized command against dangerous patterns
-        for pattern in &self.policy.dangerous_patterns {
-            if pattern.is_match(command) || pattern.is_match(&normalized) {
-                warn!(
-                    pattern = %pattern.as_str(),
-                    command = %command,
-                    "Security violation: command blocked by dangerous pattern"
-                );
-                return Ok(ToolResult::Error(format!(
-                    "Command blocked by security policy: matches {:?}",
-                    pattern.as_str()
-                )));
-            }
-        }
-
-        // In allowlist mode, only allow commands that match an allowed pattern
-        if self.policy.allowlist_mode {
-            let is_allowed = self
-                .policy
-                .allowed_commands
-                .iter()
-                .any(|pattern| pattern.is_match(command) || pattern.is_match(&normalized));
-            if !is_allowed {
-                warn!(
-                    command = %command,
-                    "Security: command blocked by allowlist policy"
-                );
-                return Ok(ToolResult::Error(
-                    "Command blocked: not in allowlist".to_string(),
-                ));
-            }
+        if let Err(reason) = self.policy.check_command(command) {
+            return Ok(ToolResult::Error(reason));
         }
 
         // Spawn the command with kill_on_drop to ensure process cleanup on timeout
@@ -342,30 +523,74 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing path"))?;
 
-        // Check for symlinks BEFORE path validation to prevent TOCTOU attacks
-        if let Err(e) = self.check_symlink(path) {
-            return Ok(ToolResult::Error(e));
-        }
-
-        // Validate path is within working directory
-        let full_path = match self.validate_path(path) {
-            Ok(p) => p,
-            Err(e) => return Ok(ToolResult::Error(e)),
+        // Output saved by this session may be read by its absolute path
+        let saved_output = self
+            .tool_outputs
+            .as_ref()
+            .and_then(|outputs| outputs.resolve(path));
+        let full_path = match saved_output {
+            Some(p) => p,
+            None => match self.validate_read_path(path) {
+                Ok(p) => p,
+                Err(e) => return Ok(ToolResult::Error(e)),
+            },
         };
 
-        match tokio::fs::read_to_string(&full_path).await {
-            Ok(content) => Ok(ToolResult::Success(content)),
+        // Optional line window: 1-based `offset` and `limit` line count
+        let offset = input.get("offset").and_then(|v| v.as_u64());
+        let limit = input.get("limit").and_then(|v| v.as_u64());
+
+        let bytes = match tokio::fs::read(&full_path).await {
+            Ok(bytes) => bytes,
             Err(e) => {
                 debug!(
                     path = %path,
                     error = %e,
                     "File read failed"
                 );
-                Ok(ToolResult::Error(format!("Failed to read file: {}", e)))
+                return Ok(ToolResult::Error(format!("Failed to read file: {}", e)));
+            }
+        };
+
+        if looks_binary(&bytes) {
+            return Ok(ToolResult::Error(format!(
+                "{} is a binary file, cannot read as text ({})",
+                path,
+                format_bytes(bytes.len() as u64)
+            )));
+        }
+
+        let content = match String::from_utf8(bytes) {
+            Ok(content) => content,
+            Err(e) => {
+                return Ok(ToolResult::Error(format!(
+                    "Failed to read file: {} is not valid UTF-8 text ({})",
+                    path, e
+                )))
             }
+        };
+
+        if offset.is_none() && limit.is_none() {
+            return Ok(ToolResult::Success(content));
+        }
+        match numbered_line_window(&content, offset, limit) {
+            Ok(window) => Ok(ToolResult::Success(window)),
+            Err(e) => Ok(ToolResult::Error(e)),
         }
     }
 
+    /// Checks a `read_file` path against the symlink, confinement and
+    /// regular-file rules, returning the canonical path.
+    fn validate_read_path(&self, path: &str) -> std::result::Result<PathBuf, String> {
+        // Check for symlinks BEFORE path validation to prevent TOCTOU attacks
+        self.check_symlink(path)?;
+
+        // Validate path is within working directory
+        let full_path = self.validate_path(path)?;
+        Self::check_regular_file(&full_path, path)?;
+        Ok(full_path)
+    }
+
     async fn write_file(&self, input: &serde_json::Value) -> Result<ToolResult> {
         let path = input
             .get("path")
@@ -413,7 +638,9 @@ impl ToolExecutor {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        match tokio::fs::write(&full_path, content).await {
+        // Write to a temp file and rename it into place, so an interrupted
+        // write never leaves a half-written file behind
+        match atomic_write(&full_path, content).await {
             Ok(()) => Ok(ToolResult::Success(format!(
                 "Wrote {} bytes to {}",
                 content.len(),
@@ -425,7 +652,7 @@ impl ToolExecutor {
                     error = %e,
                     "File write failed"
                 );
-                Ok(ToolResult::Error(format!("Failed to write file: {}", e)))
+                Ok(ToolResult::Error(format!("Failed to write file: {:#}", e)))
             }
         }
     }
@@ -451,6 +678,23 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing new_string"))?;
 
+        let replace_all = input
+            .get("replace_all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let start_line = input.get("start_line").and_then(|v| v.as_u64());
+        let end_line = input.get("end_line").and_then(|v| v.as_u64());
+        let line_range = match (start_line, end_line) {
+            (Some(start), Some(end)) => Some((start, end)),
+            (None, None) => None,
+            _ => {
+                return Ok(ToolResult::Error(
+                    "start_line and end_line must be provided together".to_string(),
+                ))
+            }
+        };
+
         // Check for symlinks BEFORE path validation to prevent TOCTOU attacks
         if let Err(e) = self.check_symlink(path) {
             return Ok(ToolResult::Error(e));
@@ -462,24 +706,43 @@ impl ToolExecutor {
             Err(e) => return Ok(ToolResult::Error(e)),
         };
 
+        if let Err(e) = Self::check_regular_file(&full_path, path) {
+            return Ok(ToolResult::Error(e));
+        }
+
         // Read file content
         let content = match tokio::fs::read_to_string(&full_path).await {
             Ok(c) => c,
             Err(e) => return Ok(ToolResult::Error(format!("Failed to read file: {e}"))),
         };
 
+        // Restrict the search to the requested lines, if any
+        let (scope_start, scope_end) = match line_range {
+            Some((start, end)) => match Self::line_range_bytes(&content, start, end) {
+                Ok(range) => range,
+                Err(e) => return Ok(ToolResult::Error(e)),
+            },
+            None => (0, content.len()),
+        };
+        let scope = &content[scope_start..scope_end];
+
         // Count matches
-        let match_count = content.matches(old_string).count();
+        let match_count = scope.matches(old_string).count();
 
         if match_count == 0 {
-            return Ok(ToolResult::Error(
-                "No matches found for old_string: 0 matches".to_string(),
-            ));
+            let location = match line_range {
+                Some((start, end)) => format!(" in lines {start}-{end}"),
+                None => String::new(),
+            };
+            return Ok(ToolResult::Error(format!(
+                "No matches found for old_string{location}: 0 matches"
+            )));
         }
 
-        if match_count > 1 {
+        if match_count > 1 && !replace_all {
             return Ok(ToolResult::Error(format!(
-                "Multiple matches found: {match_count} matches. Edit requires a unique match to avoid ambiguity."
+                "Multiple matches found: {match_count} matches. Edit requires a unique match to avoid ambiguity \
+                 (set replace_all to replace every occurrence)."
             )));
         }
 
@@ -489,23 +752,87 @@ impl ToolExecutor {
         }
 
         // Perform the replacement
-        let new_content = content.replacen(old_string, new_string, 1);
+        let replaced = if replace_all {
+            scope.replace(old_string, new_string)
+        } else {
+            scope.replacen(old_string, new_string, 1)
+        };
+        let new_content = format!(
+            "{}{}{}",
+            &content[..scope_start],
+            replaced,
+            &content[scope_end..]
+        );
 
         // Write the modified content
-        if let Err(e) = tokio::fs::write(&full_path, &new_content).await {
-            return Ok(ToolResult::Error(format!("Failed to write file: {e}")));
+        if let Err(e) = atomic_write(&full_path, &new_content).await {
+            return Ok(ToolResult::Error(format!("Failed to write file: {e:#}")));
         }
 
-        // Generate diff output
-        let diff = Self::generate_diff(old_string, new_string);
+        if match_count == 1 {
+            let diff = Self::generate_diff(old_string, new_string);
+            return Ok(ToolResult::Success(format!(
+                "Successfully replaced in {path}:\n{diff}"
+            )));
+        }
 
+        let diff =
+            Self::generate_multi_site_diff(&content, scope_start, scope, old_string, new_string);
         Ok(ToolResult::Success(format!(
-            "Successfully replaced in {path}:\n{diff}"
+            "Successfully replaced {match_count} occurrences in {path}:\n{diff}"
         )))
     }
 
+    /// Generates a diff for every replaced occurrence, each headed by its line number.
+    ///
+    /// `scope` is the searched slice of `content`, starting at byte `scope_start`.
+    fn generate_multi_site_diff(
+        content: &str,
+        scope_start: usize,
+        scope: &str,
+        old: &str,
+        new: &str,
+    ) -> String {
+        scope
+            .match_indices(old)
+            .map(|(offset, _)| {
+                let line = content[..scope_start + offset].matches('\n').count() + 1;
+                format!("@@ line {line} @@\n{}", Self::generate_diff(old, new))
+            })
+            .collect()
+    }
+
+    /// Returns the byte range covering lines `start..=end` (1-based) of `content`.
+    fn line_range_bytes(
+        content: &str,
+        start: u64,
+        end: u64,
+    ) -> std::result::Result<(usize, usize), String> {
+        let line_count = content.lines().count() as u64;
+        if start == 0 || start > end || end > line_count {
+            return Err(format!(
+                "Line range {start}-{end} is out of bounds (file has {line_count} lines)"
+            ));
+        }
+
+        let mut range_start = 0;
+        let mut offset = 0;
+        for (index, line) in content.split_inclusive('\n').enumerate() {
+            let number = index as u64 + 1;
+            if number == start {
+                range_start = offset;
+            }
+            offset += line.len();
+            if number == end {
+                break;
+            }
+        }
+
+        Ok((range_start, offset))
+    }
+
     /// Generates a simple diff output showing the replacement.
-    fn generate_diff(old: &str, new: &str) -> String {
+    pub(crate) fn generate_diff(old: &str, new: &str) -> String {
         let old_lines: Vec<&str> = old.lines().collect();
         let new_lines: Vec<&str> = new.lines().collect();
 
@@ -526,19 +853,42 @@ impl ToolExecutor {
     }
 
     /// Creates a backup of an existing file before modification.
-    async fn create_backup(&self, path: &Path) -> std::result::Result<PathBuf, String> {
-        let backup_dir = self.working_dir.join(".rct_backups");
+    ///
+    /// Backups go to the policy's `backup_dir` (`.rct_backups` by default).
+    /// Backups are keyed by the file's path relative to the working directory,
+    /// so `a/mod.rs` and `b/mod.rs` are kept apart. When `max_backups_per_file`
+    /// is set, the oldest backups of the same file beyond that count are
+    /// removed afterwards.
+    ///
+    /// Returns `None` without touching the filesystem when backups are
+    /// disabled by the policy.
+    async fn create_backup(&self, path: &Path) -> std::result::Result<Option<PathBuf>, String> {
+        if !self.policy.backups_enabled {
+            return Ok(None);
+        }
+
+        let backup_dir = match &self.policy.backup_dir {
+            Some(dir) => self.working_dir.join(dir),
+            None => self.working_dir.join(".rct_backups"),
+        };
 
         // Create backup directory if it doesn't exist
         tokio::fs::create_dir_all(&backup_dir)
             .await
             .map_err(|e| format!("Failed to create backup directory: {e}"))?;
 
-        // Generate backup filename with timestamp
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
+        // Generate backup filename from the relative path and a timestamp
+        let relative = match path.strip_prefix(&self.working_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => self
+                .working_dir
+                .canonicalize()
+                .ok()
+                .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+                .unwrap_or_else(|| path.to_path_buf()),
+        };
+        let stem = backup_stem(&relative);
+        let filename = stem.as_str();
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
@@ -551,7 +901,19 @@ impl ToolExecutor {
             .await
             .map_err(|e| format!("Failed to copy file to backup: {e}"))?;
 
-        Ok(backup_path)
+        if self.policy.max_backups_per_file > 0 {
+            if let Err(e) =
+                prune_backups(&backup_dir, filename, self.policy.max_backups_per_file).await
+            {
+                warn!(
+                    file = %filename,
+                    error = %e,
+                    "Failed to prune old backups"
+                );
+            }
+        }
+
+        Ok(Some(backup_path))
     }
 
     async fn list_files(&self, input: &serde_json::Value) -> Result<ToolResult> {
@@ -612,6 +974,7 @@ impl ToolExecutor {
     ///
     /// * `pattern` - The glob pattern (e.g., `**/*.rs`)
     /// * `respect_gitignore` - Whether to respect .gitignore rules (optional)
+    /// * `git_tracked_only` - Match only files tracked by git (optional)
     ///
     /// # Errors
     ///
@@ -627,6 +990,11 @@ impl ToolExecutor {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+        let git_tracked_only = input
+            .get("git_tracked_only")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         // Block path traversal attempts
         if pattern.contains("..") {
             return Ok(ToolResult::Error(
@@ -634,13 +1002,6 @@ impl ToolExecutor {
             ));
         }
 
-        // Load gitignore patterns if requested
-        let gitignore_patterns = if respect_gitignore {
-            self.load_gitignore_patterns()
-        } else {
-            Vec::new()
-        };
-
         // Compile the glob pattern
         let glob_pattern = match Pattern::new(pattern) {
             Ok(p) => p,
@@ -657,18 +1018,7 @@ impl ToolExecutor {
         let mut matches = Vec::new();
 
         // Walk the directory tree
-        for entry in WalkDir::new(&self.working_dir)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-
-            // Skip directories
-            if path.is_dir() {
-                continue;
-            }
-
+        for path in self.candidate_files(respect_gitignore, false, git_tracked_only) {
             // Get relative path
             let relative = match path.strip_prefix(&self.working_dir) {
                 Ok(r) => r,
@@ -677,11 +1027,6 @@ impl ToolExecutor {
 
             let relative_str = relative.to_string_lossy();
 
-            // Check gitignore patterns
-            if respect_gitignore && self.is_gitignored(&relative_str, &gitignore_patterns) {
-                continue;
-            }
-
             // Check if path matches the glob pattern
             if glob_pattern.matches(&relative_str) {
                 matches.push(relative_str.to_string());
@@ -696,55 +1041,87 @@ impl ToolExecutor {
         Ok(ToolResult::Success(matches.join("\n")))
     }
 
-    /// Loads gitignore patterns from .gitignore file if it exists.
-    fn load_gitignore_patterns(&self) -> Vec<String> {
-        let gitignore_path = self.working_dir.join(".gitignore");
-        if !gitignore_path.exists() {
-            return Vec::new();
+    /// Returns the files a search should consider.
+    ///
+    /// With `git_tracked_only`, this is the set of files git tracks under the
+    /// working directory. Outside a git repository, or when git is not
+    /// available, it falls back to [`Self::walk_files`].
+    fn candidate_files(
+        &self,
+        respect_gitignore: bool,
+        skip_git_dir: bool,
+        git_tracked_only: bool,
+    ) -> Box<dyn Iterator<Item = PathBuf>> {
+        if git_tracked_only {
+            if let Some(files) = self.git_tracked_files() {
+                return Box::new(files.into_iter());
+            }
+            debug!("git ls-files unavailable, falling back to directory walk");
         }
+        Box::new(self.walk_files(respect_gitignore, skip_git_dir))
+    }
 
-        let content = match fs::read_to_string(&gitignore_path) {
-            Ok(c) => c,
-            Err(_) => return Vec::new(),
-        };
+    /// Lists the files tracked by git under the working directory.
+    ///
+    /// Returns `None` if git is not installed or the working directory is not
+    /// inside a git repository. Tracked files deleted from the working tree
+    /// and submodule directories are omitted.
+    fn git_tracked_files(&self) -> Option<Vec<PathBuf>> {
+        let output = std::process::Command::new("git")
+            .args(["ls-files", "-z", "--cached"])
+            .current_dir(&self.working_dir)
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
 
-        content
-            .lines()
-            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
-            .map(|line| line.trim().to_string())
-            .collect()
+        // Paths are relative to the working directory and NUL-separated, so
+        // unusual file names need no unquoting.
+        let listing = String::from_utf8_lossy(&output.stdout);
+        Some(
+            listing
+                .split('\0')
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| self.working_dir.join(entry))
+                .filter(|path| path.symlink_metadata().is_ok_and(|m| !m.is_dir()))
+                .collect(),
+        )
     }
 
-    /// Checks if a path matches any gitignore pattern.
-    fn is_gitignored(&self, path: &str, patterns: &[String]) -> bool {
-        for pattern in patterns {
-            // Handle directory patterns (ending with /)
-            if pattern.ends_with('/') {
-                let dir_name = &pattern[..pattern.len() - 1];
-                // Match if path starts with the directory or contains it as a component
-                if path.starts_with(dir_name) || path.starts_with(&format!("{dir_name}/")) {
-                    return true;
-                }
-            }
-            // Handle glob patterns like *.log
-            else if pattern.starts_with('*') {
-                if let Ok(glob) = Pattern::new(pattern) {
-                    // Check against the full path and just the filename
-                    let filename = Path::new(path)
-                        .file_name()
-                        .map(|n| n.to_string_lossy())
-                        .unwrap_or_default();
-                    if glob.matches(path) || glob.matches(&filename) {
-                        return true;
-                    }
-                }
-            }
-            // Handle exact matches and path prefixes
-            else if path == pattern || path.starts_with(&format!("{pattern}/")) {
-                return true;
-            }
+    /// Walks the working directory and returns the paths of all non-directory entries.
+    ///
+    /// With `respect_gitignore`, ignore rules are applied with full gitignore
+    /// semantics via the `ignore` crate: nested `.gitignore` files, those in
+    /// parent directories, `.git/info/exclude` and the global excludes file,
+    /// including negation and anchored patterns. The `.git` directory is then
+    /// skipped as well, as it is whenever `skip_git_dir` is set.
+    fn walk_files(
+        &self,
+        respect_gitignore: bool,
+        skip_git_dir: bool,
+    ) -> impl Iterator<Item = PathBuf> {
+        let mut builder = WalkBuilder::new(&self.working_dir);
+        builder
+            .standard_filters(false)
+            .follow_links(false)
+            .git_ignore(respect_gitignore)
+            .git_global(respect_gitignore)
+            .git_exclude(respect_gitignore)
+            .parents(respect_gitignore)
+            // Honor .gitignore even outside a git repository
+            .require_git(false);
+        if respect_gitignore || skip_git_dir {
+            builder.filter_entry(|entry| entry.file_name() != ".git");
         }
-        false
+
+        builder
+            .build()
+            .filter_map(|entry| entry.ok())
+            .map(ignore::DirEntry::into_path)
+            .filter(|path| !path.is_dir())
     }
 
     /// Searches file contents for a pattern.
@@ -754,6 +1131,12 @@ impl ToolExecutor {
     /// * `pattern` - The regex pattern to search for
     /// * `case_insensitive` - Whether to perform case-insensitive search (optional)
     /// * `file_pattern` - Glob pattern to filter files (optional)
+    /// * `before` / `after` - Context lines around each match (optional)
+    /// * `files_with_matches` - Report matching file paths only (optional)
+    /// * `respect_gitignore` - Whether to respect .gitignore rules (optional)
+    /// * `git_tracked_only` - Search only files tracked by git (optional)
+    ///
+    /// The `.git` directory is never searched.
     ///
     /// # Errors
     ///
@@ -774,6 +1157,26 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
+        // Context lines around each match, like `grep -B` / `grep -A`
+        let before = input.get("before").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let after = input.get("after").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+
+        // List matching files only, like `grep -l`
+        let files_with_matches = input
+            .get("files_with_matches")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let respect_gitignore = input
+            .get("respect_gitignore")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let git_tracked_only = input
+            .get("git_tracked_only")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         // Compile the regex pattern
         let regex = match if case_insensitive {
             regex::RegexBuilder::new(pattern)
@@ -797,20 +1200,11 @@ impl ToolExecutor {
         let file_glob = file_pattern.as_ref().and_then(|p| Pattern::new(p).ok());
 
         let mut results = Vec::new();
+        let mut output_size = 0;
+        let mut truncated = false;
 
-        // Walk the directory tree
-        for entry in WalkDir::new(&self.working_dir)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-
-            // Skip directories
-            if path.is_dir() {
-                continue;
-            }
-
+        // Walk the directory tree (searching .git is never useful)
+        'files: for path in self.candidate_files(respect_gitignore, true, git_tracked_only) {
             // Get relative path
             let relative = match path.strip_prefix(&self.working_dir) {
                 Ok(r) => r,
@@ -831,15 +1225,59 @@ impl ToolExecutor {
             }
 
             // Read file content (skip binary files)
-            let content = match fs::read_to_string(path) {
+            let content = match fs::read_to_string(&path) {
                 Ok(c) => c,
                 Err(_) => continue, // Skip files we can't read as text
             };
 
+            if files_with_matches {
+                // Stop at the first hit; the path is all that is reported
+                if content.lines().any(|line| regex.is_match(line)) {
+                    output_size += relative_str.len() + 1;
+                    if output_size > self.policy.max_output_size {
+                        truncated = true;
+                        break;
+                    }
+                    results.push(relative_str.into_owned());
+                }
+                continue;
+            }
+
             // Search for matches
-            for (line_num, line) in content.lines().enumerate() {
-                if regex.is_match(line) {
-                    results.push(format!("{}:{}: {}", relative_str, line_num + 1, line));
+            let lines: Vec<&str> = content.lines().collect();
+            let matches: Vec<usize> = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| regex.is_match(line))
+                .map(|(idx, _)| idx)
+                .collect();
+
+            for (start, end) in context_groups(&matches, before, after, lines.len()) {
+                if (before > 0 || after > 0) && !results.is_empty() {
+                    results.push("--".to_string());
+                }
+                for (idx, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+                    let separator = if matches.binary_search(&idx).is_ok() {
+                        ':'
+                    } else {
+                        '-'
+                    };
+                    let formatted = format!(
+                        "{}{}{}{} {}",
+                        relative_str,
+                        separator,
+                        idx + 1,
+                        separator,
+                        line
+                    );
+
+                    // Cap total output to keep huge result sets out of the context
+                    output_size += formatted.len() + 1;
+                    if output_size > self.policy.max_output_size {
+                        truncated = true;
+                        break 'files;
+                    }
+                    results.push(formatted);
                 }
             }
         }
@@ -848,7 +1286,49 @@ impl ToolExecutor {
             return Ok(ToolResult::Success(String::new()));
         }
 
-        Ok(ToolResult::Success(results.join("\n")))
+        let mut output = results.join("\n");
+        if truncated {
+            warn!(
+                max_size = self.policy.max_output_size,
+                "Grep output truncated"
+            );
+            output.push_str(&format!(
+                "\n\n[Output truncated: exceeded {} byte limit]",
+                self.policy.max_output_size
+            ));
+        }
+
+        Ok(ToolResult::Success(output))
+    }
+
+    /// Calls an MCP tool, forwarding its partial results for display.
+    async fn execute_mcp_tool(
+        &self,
+        mcp: &Mutex<McpManager>,
+        name: &str,
+        input: &serde_json::Value,
+    ) -> ToolResult {
+        let Some(progress) = &self.mcp_progress else {
+            return mcp.lock().await.execute_tool(name, input.clone()).await;
+        };
+
+        let (updates, mut partials) = mpsc::channel(MCP_PROGRESS_BUFFER);
+        let call = async move {
+            mcp.lock()
+                .await
+                .execute_tool_streaming(name, input.clone(), updates)
+                .await
+        };
+        let forward = async {
+            while let Some(partial) = partials.recv().await {
+                let _ = progress.try_send(McpToolProgress {
+                    tool: name.to_string(),
+                    text: partial.text(),
+                });
+            }
+        };
+        let (result, ()) = tokio::join!(call, forward);
+        result
     }
 
     /// Fetches content from a URL and converts HTML to markdown.
@@ -862,7 +1342,7 @@ impl ToolExecutor {
     /// Returns an error if:
     /// - The URL is invalid
     /// - The URL uses a disallowed scheme (file://)
-    /// - The URL points to localhost or private IP ranges
+    /// - The URL points to localhost or private IP ranges not on the allowlist
     /// - The request times out
     /// - The content exceeds the maximum length
     async fn web_fetch(&self, input: &serde_json::Value) -> Result<ToolResult> {
@@ -871,12 +1351,18 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing url"))?;
 
-        let tool = web_fetch::WebFetchTool::new(web_fetch::WebFetchConfig::default());
+        let tool = self
+            .web_fetch_tool
+            .get_or_init(|| web_fetch::WebFetchTool::new(self.web_fetch_config.clone()));
 
         match tool.fetch(url).await {
             Ok(result) => Ok(ToolResult::Success(format!(
-                "Fetched {} ({}, status {})\n\n{}",
-                url, result.content_type, result.status, result.content
+                "Fetched {} ({}, status {}{})\n\n{}",
+                url,
+                result.content_type,
+                result.status,
+                if result.cached { ", cached" } else { "" },
+                result.content
             ))),
             Err(e) => {
                 debug!(
@@ -909,7 +1395,7 @@ impl ToolExecutor {
             .map(|v| v as usize)
             .unwrap_or(10);
 
-        let tool = web_search::WebSearchTool::new(web_search::WebSearchConfig::default());
+        let tool = web_search::WebSearchTool::new(self.web_search_config.clone());
 
         match tool.search(query, max_results).await {
             Ok(results) => {
@@ -931,51 +1417,82 @@ impl ToolExecutor {
     ///
     /// # Arguments
     ///
-    /// * `path` - The relative path to the image file
+    /// * `source` - `{"path": ...}`, `{"url": ...}` or `{"clipboard": true}`
+    /// * `path` - The relative path to the image file (same as `source.path`)
     /// * `prompt` - Optional prompt to guide the analysis
     ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - The path is missing
-    /// - The file cannot be read
-    /// - The image format is not supported
+    /// Failures are returned as [`ToolResult::Error`], including:
+    /// - A missing or malformed source
+    /// - A file, URL or clipboard image that cannot be read
+    /// - A URL blocked by the `web_fetch` restrictions
+    /// - An unsupported image format
     async fn analyze_image(&self, input: &serde_json::Value) -> Result<ToolResult> {
-        let path = input
-            .get("path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing path"))?;
+        let source = match vision::ImageInput::from_tool_input(input) {
+            Ok(source) => source,
+            Err(e) => return Ok(ToolResult::Error(format!("Failed to analyze image: {e}"))),
+        };
 
         let prompt = input.get("prompt").and_then(|v| v.as_str());
+        let tool = vision::VisionTool::new(self.vision_config.clone());
 
-        // Check for symlinks BEFORE path validation to prevent TOCTOU attacks
-        if let Err(e) = self.check_symlink(path) {
-            return Ok(ToolResult::Error(e));
-        }
+        let (label, result) = match &source {
+            vision::ImageInput::Path(path) => {
+                // Check for symlinks BEFORE path validation to prevent TOCTOU attacks
+                if let Err(e) = self.check_symlink(path) {
+                    return Ok(ToolResult::Error(e));
+                }
 
-        // Validate path is within working directory
-        let full_path = match self.validate_path(path) {
-            Ok(p) => p,
-            Err(e) => return Ok(ToolResult::Error(e)),
-        };
+                // Validate path is within working directory
+                let full_path = match self.validate_path(path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(ToolResult::Error(e)),
+                };
 
-        let tool = vision::VisionTool::new(vision::VisionConfig::default());
+                (format!("Path: {path}"), tool.analyze(&full_path, prompt))
+            }
+            vision::ImageInput::Url(url) => {
+                // Same SSRF protections as web_fetch
+                let fetcher = self
+                    .web_fetch_tool
+                    .get_or_init(|| web_fetch::WebFetchTool::new(self.web_fetch_config.clone()));
+                let result = match fetcher.fetch_bytes(url, tool.max_file_size()).await {
+                    Ok(bytes) => tool.analyze_bytes(&bytes, prompt),
+                    Err(e) => Err(vision::VisionError::Fetch(format!("{e:#}"))),
+                };
+                (format!("URL: {url}"), result)
+            }
+            vision::ImageInput::Clipboard => {
+                let result = tokio::task::spawn_blocking(vision::read_clipboard_image)
+                    .await
+                    .map_err(|e| vision::VisionError::Clipboard(e.to_string()))
+                    .and_then(|bytes| tool.analyze_bytes(&bytes?, prompt));
+                ("Source: clipboard".to_string(), result)
+            }
+        };
 
-        match tool.analyze(&full_path, prompt) {
+        match result {
             Ok(result) => {
                 // Return information about the loaded image
                 // The actual image data is available via result.image for API submission
+                let dimensions = match (result.original_dimensions, result.resized_dimensions) {
+                    (Some((w, h)), Some((rw, rh))) => {
+                        format!("\n- Dimensions: {w}x{h} (resized to {rw}x{rh})")
+                    }
+                    (Some((w, h)), None) => format!("\n- Dimensions: {w}x{h}"),
+                    (None, _) => String::new(),
+                };
                 let response = format!(
-                    "Image loaded successfully:\n- Path: {}\n- Format: {}\n- Prompt: {}",
-                    path,
+                    "Image loaded successfully:\n- {}\n- Format: {}{}\n- Prompt: {}",
+                    label,
                     result.media_type.as_str(),
+                    dimensions,
                     result.prompt.as_deref().unwrap_or("(none)")
                 );
                 Ok(ToolResult::Success(response))
             }
             Err(e) => {
                 debug!(
-                    path = %path,
+                    source = ?source,
                     error = %e,
                     "Image analysis failed"
                 );
@@ -985,11 +1502,249 @@ impl ToolExecutor {
     }
 }
 
+/// Finds the enclosing git repository root by walking up from `start`.
+///
+/// A `.git` file (worktrees, submodules) counts as well as a `.git` directory.
+fn find_repo_root(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().ok()?;
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Encodes a relative path as a single file name for its backups.
+///
+/// Components are joined with `%2F` and a literal `%` becomes `%25`, so
+/// distinct paths never share a name: `src/app/mod.rs` becomes
+/// `src%2Fapp%2Fmod.rs`.
+fn backup_stem(relative: &Path) -> String {
+    let stem = relative
+        .components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy().replace('%', "%25")),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("%2F");
+    if stem.is_empty() {
+        "unknown".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Removes the oldest backups of `filename` in `backup_dir`, keeping `keep`.
+///
+/// Backups are named `<filename>.<unix timestamp>.bak`, where `filename` is
+/// from [`backup_stem`]; other files in the directory are left alone.
+async fn prune_backups(backup_dir: &Path, filename: &str, keep: usize) -> std::io::Result<()> {
+    let prefix = format!("{filename}.");
+    let mut backups = Vec::new();
+
+    let mut entries = tokio::fs::read_dir(backup_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let Some(timestamp) = name
+            .to_str()
+            .and_then(|n| n.strip_prefix(&prefix))
+            .and_then(|rest| rest.strip_suffix(".bak"))
+            .and_then(|ts| ts.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        backups.push((timestamp, entry.path()));
+    }
+
+    if backups.len() <= keep {
+        return Ok(());
+    }
+    backups.sort_unstable();
+    let excess = backups.len() - keep;
+    for (_, path) in backups.into_iter().take(excess) {
+        tokio::fs::remove_file(&path).await?;
+    }
+    Ok(())
+}
+
+/// Number of leading bytes inspected when sniffing for binary content.
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// Partial results one MCP call may queue for forwarding to the display.
+const MCP_PROGRESS_BUFFER: usize = 32;
+
+/// Returns true if `bytes` looks like binary data: a NUL byte within the
+/// first [`BINARY_SNIFF_LEN`] bytes, the same heuristic git uses.
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+/// Returns lines `offset..offset + limit` (1-based) of `content`, each
+/// prefixed with its line number and a tab.
+///
+/// `offset` defaults to the first line and `limit` to the rest of the file.
+fn numbered_line_window(
+    content: &str,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> std::result::Result<String, String> {
+    let offset = offset.unwrap_or(1);
+    if offset == 0 {
+        return Err("offset must be at least 1 (line numbers start at 1)".to_string());
+    }
+    if limit == Some(0) {
+        return Err("limit must be at least 1".to_string());
+    }
+
+    let total = content.lines().count();
+    let start = usize::try_from(offset - 1).unwrap_or(usize::MAX);
+    if start >= total && total > 0 {
+        return Err(format!(
+            "offset {offset} is beyond the end of the file ({total} lines)"
+        ));
+    }
+    let limit = limit.map_or(usize::MAX, |l| usize::try_from(l).unwrap_or(usize::MAX));
+
+    Ok(content
+        .lines()
+        .enumerate()
+        .skip(start)
+        .take(limit)
+        .map(|(idx, line)| format!("{:>6}\t{}", idx + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Groups matching line indices into inclusive line ranges with context.
+///
+/// Each match expands to `before` lines above and `after` lines below,
+/// clamped to the file; overlapping or adjacent ranges are merged.
+fn context_groups(
+    matches: &[usize],
+    before: usize,
+    after: usize,
+    line_count: usize,
+) -> Vec<(usize, usize)> {
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &idx in matches {
+        let start = idx.saturating_sub(before);
+        let end = idx.saturating_add(after).min(line_count.saturating_sub(1));
+        match groups.last_mut() {
+            Some((_, last_end)) if start <= last_end.saturating_add(1) => {
+                *last_end = end.max(*last_end);
+            }
+            _ => groups.push((start, end)),
+        }
+    }
+    groups
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_numbered_line_window() {
+        let content = "a\nb\nc\nd\n";
+
+        assert_eq!(
+            numbered_line_window(content, Some(2), Some(2)).unwrap(),
+            "     2\tb\n     3\tc"
+        );
+        assert_eq!(
+            numbered_line_window(content, Some(3), None).unwrap(),
+            "     3\tc\n     4\td"
+        );
+        assert_eq!(
+            numbered_line_window(content, None, Some(1)).unwrap(),
+            "     1\ta"
+        );
+        // A limit past the end just returns the remaining lines
+        assert_eq!(
+            numbered_line_window(content, Some(4), Some(10)).unwrap(),
+            "     4\td"
+        );
+    }
+
+    #[test]
+    fn test_numbered_line_window_rejects_bad_ranges() {
+        let content = "a\nb\n";
+
+        assert!(numbered_line_window(content, Some(0), None).is_err());
+        assert!(numbered_line_window(content, Some(1), Some(0)).is_err());
+        let err = numbered_line_window(content, Some(3), None).unwrap_err();
+        assert!(err.contains("beyond the end"), "got: {err}");
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b"plain text\n"));
+        assert!(!looks_binary(b""));
+        assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+
+        // Only the leading bytes are sniffed
+        let mut late_nul = vec![b'a'; BINARY_SNIFF_LEN];
+        late_nul.push(0);
+        assert!(!looks_binary(&late_nul));
+    }
+
+    #[test]
+    fn test_backup_stem_encodes_relative_path() {
+        assert_eq!(backup_stem(Path::new("notes.txt")), "notes.txt");
+        assert_eq!(
+            backup_stem(Path::new("src/app/mod.rs")),
+            "src%2Fapp%2Fmod.rs"
+        );
+        // A literal "%2F" in a name cannot collide with a separator
+        assert_eq!(backup_stem(Path::new("a%2Fb")), "a%252Fb");
+        assert_ne!(
+            backup_stem(Path::new("a%2Fb")),
+            backup_stem(Path::new("a/b"))
+        );
+    }
+
+    #[test]
+    fn test_context_groups_without_context() {
+        assert_eq!(context_groups(&[1, 4], 0, 0, 10), vec![(1, 1), (4, 4)]);
+    }
+
+    #[test]
+    fn test_context_groups_merges_overlapping_and_adjacent() {
+        // 1..=3 and 4..=6 are adjacent, 8..=10 is separated by line 7
+        assert_eq!(context_groups(&[2, 5, 9], 1, 1, 12), vec![(1, 6), (8, 10)]);
+        assert_eq!(context_groups(&[3, 9], 1, 0, 10), vec![(2, 3), (8, 9)]);
+    }
+
+    #[test]
+    fn test_context_groups_clamped_to_file() {
+        assert_eq!(context_groups(&[0, 4], 3, 3, 5), vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_context_groups_huge_context_does_not_overflow() {
+        assert_eq!(
+            context_groups(&[2, 7], usize::MAX, usize::MAX, 10),
+            vec![(0, 9)]
+        );
+        assert_eq!(
+            context_groups(&[usize::MAX - 2], 1, 5, usize::MAX),
+            vec![(usize::MAX - 3, usize::MAX - 1)]
+        );
+    }
+
+    /// Creates `<tmp>/repo/.git`, `<tmp>/repo/app` and `<tmp>/repo/lib/shared.txt`.
+    fn repo_fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("app")).unwrap();
+        std::fs::create_dir_all(repo.join("lib")).unwrap();
+        std::fs::write(repo.join("lib/shared.txt"), "shared").unwrap();
+        std::fs::write(temp_dir.path().join("outside.txt"), "outside").unwrap();
+        temp_dir
+    }
+
     #[test]
     fn test_tool_executor_new() {
         let executor = ToolExecutor::new(PathBuf::from("/tmp"));
@@ -1071,17 +1826,230 @@ mod tests {
     }
 
     #[test]
-    fn test_is_gitignored() {
-        let executor = ToolExecutor::new(PathBuf::from("/tmp"));
-        let patterns = vec![
-            "target/".to_string(),
-            "*.log".to_string(),
-            "node_modules".to_string(),
-        ];
-
-        assert!(executor.is_gitignored("target/debug/main", &patterns));
-        assert!(executor.is_gitignored("app.log", &patterns));
-        assert!(executor.is_gitignored("node_modules/pkg", &patterns));
-        assert!(!executor.is_gitignored("src/main.rs", &patterns));
+    fn test_validate_path_cwd_confinement_rejects_sibling() {
+        let temp_dir = repo_fixture();
+        let executor = ToolExecutor::new(temp_dir.path().join("repo/app"));
+
+        assert!(executor.validate_path("../lib/shared.txt").is_err());
+    }
+
+    #[test]
+    fn test_validate_path_repo_root_confinement_allows_sibling() {
+        let temp_dir = repo_fixture();
+        let executor = ToolExecutor::new(temp_dir.path().join("repo/app"))
+            .with_path_confinement(PathConfinement::RepoRoot);
+
+        let path = executor.validate_path("../lib/shared.txt").unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "shared");
+    }
+
+    #[test]
+    fn test_validate_path_repo_root_confinement_rejects_outside_repo() {
+        let temp_dir = repo_fixture();
+        let executor = ToolExecutor::new(temp_dir.path().join("repo/app"))
+            .with_path_confinement(PathConfinement::RepoRoot);
+
+        let result = executor.validate_path("../../outside.txt");
+        assert!(result.unwrap_err().contains("Path traversal"));
+        assert!(executor.validate_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_find_repo_root_detects_git_dir() {
+        let temp_dir = repo_fixture();
+        let root = find_repo_root(&temp_dir.path().join("repo/app")).unwrap();
+        assert_eq!(root, temp_dir.path().join("repo").canonicalize().unwrap());
+    }
+
+    /// Returns the relative paths `walk_files` yields, sorted.
+    fn walked(executor: &ToolExecutor, respect_gitignore: bool) -> Vec<String> {
+        let mut paths: Vec<String> = executor
+            .walk_files(respect_gitignore, false)
+            .map(|p| {
+                p.strip_prefix(&executor.working_dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_walk_files_gitignore_basic_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\nnode_modules\n").unwrap();
+        for file in [
+            "target/debug/main",
+            "node_modules/pkg/index.js",
+            "src/main.rs",
+        ] {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        std::fs::write(root.join("app.log"), "").unwrap();
+
+        let executor = ToolExecutor::new(root.to_path_buf());
+
+        assert_eq!(walked(&executor, true), vec![".gitignore", "src/main.rs"]);
+        assert_eq!(walked(&executor, false).len(), 5);
+    }
+
+    #[test]
+    fn test_walk_files_gitignore_negation() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join(".gitignore"), "*.log\n!keep.log\n").unwrap();
+        std::fs::write(root.join("debug.log"), "").unwrap();
+        std::fs::write(root.join("keep.log"), "").unwrap();
+
+        let executor = ToolExecutor::new(root.to_path_buf());
+
+        assert_eq!(walked(&executor, true), vec![".gitignore", "keep.log"]);
+    }
+
+    #[test]
+    fn test_walk_files_nested_and_anchored_gitignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        // `/build` only matches at the root; the nested file ignores its own `*.scratch`
+        std::fs::write(root.join(".gitignore"), "/build\n").unwrap();
+        std::fs::create_dir_all(root.join("build")).unwrap();
+        std::fs::create_dir_all(root.join("sub/build")).unwrap();
+        std::fs::write(root.join("build/out.o"), "").unwrap();
+        std::fs::write(root.join("sub/build/out.o"), "").unwrap();
+        std::fs::write(root.join("sub/.gitignore"), "*.scratch\n").unwrap();
+        std::fs::write(root.join("sub/notes.scratch"), "").unwrap();
+        std::fs::write(root.join("top.scratch"), "").unwrap();
+
+        let executor = ToolExecutor::new(root.to_path_buf());
+
+        assert_eq!(
+            walked(&executor, true),
+            vec![
+                ".gitignore",
+                "sub/.gitignore",
+                "sub/build/out.o",
+                "top.scratch"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_read_file_fifo_does_not_hang() {
+        let temp_dir = TempDir::new().unwrap();
+        let fifo = temp_dir.path().join("pipe");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::write(temp_dir.path().join("regular.txt"), "hello").unwrap();
+
+        let executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+        let read = |path: &str| ToolCall {
+            name: "read_file".to_string(),
+            input: serde_json::json!({ "path": path }),
+        };
+
+        // No writer is attached, so a blocking read would never return
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            executor.execute(read("pipe")),
+        )
+        .await
+        .expect("reading a FIFO should not hang")
+        .unwrap();
+        match result {
+            ToolResult::Error(e) => assert!(e.contains("named pipe"), "got: {e}"),
+            other => panic!("expected error, got {other:?}"),
+        }
+
+        match executor.execute(read("regular.txt")).await.unwrap() {
+            ToolResult::Success(content) => assert_eq!(content, "hello"),
+            other => panic!("expected success, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_large_output_offloaded_and_readable() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+        executor.set_tool_output_threshold(Some(1024));
+
+        let output = match executor
+            .execute(ToolCall {
+                name: "bash".to_string(),
+                input: serde_json::json!({ "command": "seq 1 5000" }),
+            })
+            .await
+            .unwrap()
+        {
+            ToolResult::Success(output) => output,
+            other => panic!("expected success, got {other:?}"),
+        };
+
+        let dir = executor.tool_output_dir().unwrap().to_path_buf();
+        let saved = dir.join("bash-1.txt");
+        assert!(output.starts_with(&format!("Output saved to {}", saved.display())));
+        assert!(output.contains("5000 lines"));
+        assert!(output.contains("\n20"));
+        assert!(!output.contains("4999"));
+
+        // The model can page through the saved file by its absolute path
+        let read = executor
+            .execute(ToolCall {
+                name: "read_file".to_string(),
+                input: serde_json::json!({
+                    "path": saved.to_str().unwrap(),
+                    "offset": 4999,
+                    "limit": 2,
+                }),
+            })
+            .await
+            .unwrap();
+        match read {
+            ToolResult::Success(content) => assert_eq!(content, "  4999\t4999\n  5000\t5000"),
+            other => panic!("expected success, got {other:?}"),
+        }
+
+        // Other absolute paths are still rejected
+        let outside = temp_dir.path().join("outside.txt");
+        std::fs::write(&outside, "x").unwrap();
+        let read = executor
+            .execute(ToolCall {
+                name: "read_file".to_string(),
+                input: serde_json::json!({ "path": outside.to_str().unwrap() }),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(read, ToolResult::Error(_)));
+
+        drop(executor);
+        assert!(!dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_small_output_not_offloaded() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+        executor.set_tool_output_threshold(Some(1024));
+
+        let result = executor
+            .execute(ToolCall {
+                name: "bash".to_string(),
+                input: serde_json::json!({ "command": "echo hello" }),
+            })
+            .await
+            .unwrap();
+        match result {
+            ToolResult::Success(output) => assert_eq!(output.trim(), "hello"),
+            other => panic!("expected success, got {other:?}"),
+        }
+        assert!(!executor.tool_output_dir().unwrap().exists());
     }
 }
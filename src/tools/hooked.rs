@@ -5,21 +5,40 @@
 //! tool execution.
 
 use anyhow::Result;
-use serde_json::json;
-use std::path::PathBuf;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::debug;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, warn};
 
+use crate::enterprise::policy::OrgPolicy;
+use crate::enterprise::tool_audit::{ToolAuditLog, ToolAuditStatus};
 use crate::hooks::{HookDecision, HookManager};
+use crate::mcp::{McpManager, McpToolProgress};
 use crate::permissions::{
     PermissionDecision, PermissionManager, PermissionRequest, PermissionResponse,
 };
 
-use super::parallel::{ParallelConfig, ParallelExecutor, SortByIndex};
+use super::backend::ToolBackend;
+use super::parallel::{McpSafetyRegistry, ParallelConfig, ParallelExecutor, SortByIndex};
 use super::security::ToolExecutionPolicy;
 use super::stateful::{ShellState, StatefulToolExecutor};
-use super::{ToolCall, ToolResult};
+use super::vision::VisionConfig;
+use super::web_fetch::WebFetchConfig;
+use super::web_search::WebSearchConfig;
+use super::{ToolCall, ToolExecutor, ToolResult};
+use crate::plugins::tools::PluginTools;
+use crate::session::ShellSnapshot;
+use crate::types::config::PathConfinement;
+use crate::util::redact::Redactor;
+
+/// Project hook file, relative to the working directory.
+pub const PROJECT_HOOKS_FILE: &str = ".patina/hooks.toml";
+
+/// Tools whose output is file content, passed to the model unredacted so
+/// the code it reads and later edits is never rewritten.
+const FILE_CONTENT_TOOLS: &[&str] = &["read_file"];
 
 /// Tool executor with hook and permission integration.
 ///
@@ -69,9 +88,15 @@ use super::{ToolCall, ToolResult};
 /// ```
 pub struct HookedToolExecutor {
     inner: StatefulToolExecutor,
+    /// Custom backend that replaces `inner` for executing tool calls.
+    backend: Option<Box<dyn ToolBackend>>,
     hooks: HookManager,
     permissions: Option<Arc<Mutex<PermissionManager>>>,
     parallel: ParallelExecutor,
+    /// Tamper-evident record of every executed or refused tool call.
+    audit: Option<Arc<std::sync::Mutex<ToolAuditLog>>>,
+    /// Scrubs secrets from tool output and audited input.
+    redactor: Option<Redactor>,
 }
 
 impl HookedToolExecutor {
@@ -85,12 +110,21 @@ impl HookedToolExecutor {
     pub fn new(working_dir: PathBuf, hook_manager: HookManager) -> Self {
         Self {
             inner: StatefulToolExecutor::new(working_dir),
+            backend: None,
             hooks: hook_manager,
             permissions: None,
             parallel: ParallelExecutor::new(ParallelConfig::default()),
+            audit: None,
+            redactor: None,
         }
     }
 
+    /// Returns the hook manager.
+    #[must_use]
+    pub fn hooks(&self) -> &HookManager {
+        &self.hooks
+    }
+
     /// Returns the current shell state.
     ///
     /// This provides access to the tracked working directory and environment
@@ -103,6 +137,52 @@ impl HookedToolExecutor {
         self.inner.shell_state()
     }
 
+    /// Loads the project `.env` into the environment of bash commands.
+    ///
+    /// See [`StatefulToolExecutor::load_dotenv`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is rejected or cannot be read.
+    pub fn load_dotenv(&self) -> Result<usize> {
+        self.inner.load_dotenv()
+    }
+
+    /// Loads lifecycle hooks from a TOML file; a missing file is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be parsed.
+    pub fn load_hooks(&mut self, path: &Path) -> Result<()> {
+        self.hooks.load_config_graceful(path)
+    }
+
+    /// Loads lifecycle hooks from the project's `.patina/hooks.toml`.
+    ///
+    /// Hooks run arbitrary commands, so only call this for a project the user
+    /// trusts. The file must pass the same confinement, protected-path and
+    /// symlink checks as a write. A missing file is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is rejected by the path checks or cannot
+    /// be parsed.
+    pub fn load_project_hooks(&mut self) -> Result<()> {
+        let path = self
+            .inner
+            .inner
+            .validate_project_file(PROJECT_HOOKS_FILE)
+            .map_err(|e| anyhow::anyhow!("{} not loaded: {}", PROJECT_HOOKS_FILE, e))?;
+        self.hooks.load_config_graceful(&path)
+    }
+
+    /// Restores shell state saved with a session.
+    ///
+    /// See [`StatefulToolExecutor::restore_shell_state`].
+    pub fn restore_shell_state(&self, snapshot: &ShellSnapshot) {
+        self.inner.restore_shell_state(snapshot);
+    }
+
     /// Creates a new hooked tool executor with a custom policy.
     #[must_use]
     pub fn with_policy(mut self, policy: ToolExecutionPolicy) -> Self {
@@ -110,6 +190,139 @@ impl HookedToolExecutor {
         self
     }
 
+    /// Sets the boundary that file paths are confined to.
+    #[must_use]
+    pub fn with_path_confinement(mut self, confinement: PathConfinement) -> Self {
+        self.set_path_confinement(confinement);
+        self
+    }
+
+    /// Sets the boundary that file paths are confined to.
+    pub fn set_path_confinement(&mut self, confinement: PathConfinement) {
+        self.inner.inner.set_path_confinement(confinement);
+    }
+
+    /// Returns the directory file paths are confined to.
+    #[must_use]
+    pub fn confinement_root(&self) -> &Path {
+        self.inner.inner.confinement_root()
+    }
+
+    /// Moves tool execution and hook `cwd` to another directory.
+    pub fn set_working_dir(&mut self, working_dir: PathBuf) {
+        self.hooks.set_working_dir(working_dir.clone());
+        self.inner.set_working_dir(working_dir);
+    }
+
+    /// Sets the restrictions applied to `web_fetch` requests.
+    pub fn set_web_fetch_config(&mut self, config: WebFetchConfig) {
+        self.inner.inner.set_web_fetch_config(config);
+    }
+
+    /// Sets the provider and endpoint used by `web_search`.
+    pub fn set_web_search_config(&mut self, config: WebSearchConfig) {
+        self.inner.inner.set_web_search_config(config);
+    }
+
+    /// Sets the size limits and downscaling used by `analyze_image`.
+    pub fn set_vision_config(&mut self, config: VisionConfig) {
+        self.inner.inner.set_vision_config(config);
+    }
+
+    /// Sets the size in bytes above which tool output is saved to a file.
+    pub fn set_tool_output_threshold(&mut self, threshold: Option<usize>) {
+        self.inner.inner.set_tool_output_threshold(threshold);
+    }
+
+    /// Runs calls to plugin-declared tools through the given registry view.
+    pub fn set_plugin_tools(&mut self, tools: PluginTools) {
+        self.inner.inner.set_plugin_tools(tools);
+    }
+
+    /// Routes `mcp__<server>__<tool>` calls to the given MCP servers and
+    /// sends their partial results to `progress`.
+    pub fn set_mcp_manager(
+        &mut self,
+        mcp: Arc<Mutex<McpManager>>,
+        progress: mpsc::Sender<McpToolProgress>,
+    ) {
+        self.inner.inner.set_mcp_manager(mcp);
+        self.inner.inner.set_mcp_progress(progress);
+    }
+
+    /// Sets whether files are backed up before being modified.
+    pub fn set_backups_enabled(&mut self, enabled: bool) {
+        self.inner.inner.policy.backups_enabled = enabled;
+    }
+
+    /// Records every executed or refused tool call in the given audit log.
+    pub fn set_audit_log(&mut self, log: ToolAuditLog) {
+        self.audit = Some(Arc::new(std::sync::Mutex::new(log)));
+    }
+
+    /// Records a tool call in the audit log, if one is set.
+    ///
+    /// The write runs on a blocking thread, since it may wait for another
+    /// process to release the log and syncs the file. Failures are logged.
+    pub async fn record_audit(&self, tool_name: &str, input: &Value, status: ToolAuditStatus) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let input = match &self.redactor {
+            Some(redactor) => redactor.redact_json(input),
+            None => input.clone(),
+        };
+        let audit = Arc::clone(audit);
+        let session_id = self.hooks.session_id().to_string();
+        let tool = tool_name.to_string();
+        let recorded = tokio::task::spawn_blocking(move || {
+            audit.lock().expect("audit log lock poisoned").record(
+                &session_id,
+                &tool,
+                &input,
+                status,
+            )
+        })
+        .await;
+        match recorded {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!(tool = %tool_name, error = %e, "Failed to write tool audit record"),
+            Err(e) => warn!(tool = %tool_name, error = %e, "Tool audit task failed"),
+        }
+    }
+
+    /// Redacts secrets from tool output before it is returned or audited.
+    pub fn set_redactor(&mut self, redactor: Redactor) {
+        self.redactor = Some(redactor);
+    }
+
+    /// Merges an organization policy into the execution policy and the
+    /// policy hook commands run under.
+    pub fn apply_org_policy(&mut self, org: &OrgPolicy) {
+        org.apply(&mut self.inner.inner.policy);
+        self.hooks.apply_org_policy(org);
+    }
+
+    /// Checks a shell command against the execution policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the command is blocked.
+    pub fn check_command(&self, command: &str) -> Result<(), String> {
+        self.inner.inner.policy.check_command(command)
+    }
+
+    /// Replaces the built-in executor with a custom backend.
+    ///
+    /// Hooks and permission checks still run around every call; only the
+    /// execution step is delegated. Shell state, policy and path confinement
+    /// apply to the built-in executor and are not consulted by the backend.
+    #[must_use]
+    pub fn with_backend(mut self, backend: Box<dyn ToolBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
     /// Configures the permission manager for this executor.
     ///
     /// When configured, tools will be checked against permission rules
@@ -146,6 +359,21 @@ impl HookedToolExecutor {
         self
     }
 
+    /// Sets the safety classes declared for MCP tools in parallel batches.
+    pub fn set_mcp_safety(&mut self, registry: McpSafetyRegistry) {
+        let config = self.parallel.config().clone().with_mcp_safety(registry);
+        self.parallel = ParallelExecutor::new(config);
+    }
+
+    /// Sets how long one tool may run before it is cancelled.
+    ///
+    /// Applies to every call, not only parallel batches. `None` means no limit.
+    pub fn set_tool_timeout(&mut self, timeout: Option<Duration>) {
+        let mut config = self.parallel.config().clone();
+        config.tool_timeout = timeout;
+        self.parallel = ParallelExecutor::new(config);
+    }
+
     /// Returns the parallel executor configuration.
     #[must_use]
     pub fn parallel_config(&self) -> &ParallelConfig {
@@ -177,6 +405,49 @@ impl HookedToolExecutor {
         }
     }
 
+    /// Builds the permission prompt request for a tool call.
+    ///
+    /// Mutating file tools carry a preview of the change so it can be
+    /// reviewed before approving.
+    fn permission_request(&self, call: &ToolCall, tool_input: Option<&str>) -> PermissionRequest {
+        let description = self.generate_description(call);
+        let request = PermissionRequest::new(&call.name, tool_input, &description);
+        match Self::generate_preview(call) {
+            Some(preview) => request.with_preview(preview),
+            None => request,
+        }
+    }
+
+    /// Describes the change an `edit` or `write_file` call would make.
+    fn generate_preview(call: &ToolCall) -> Option<String> {
+        let text = |key: &str| call.input.get(key).and_then(|v| v.as_str());
+        let preview = match call.name.as_str() {
+            "edit" => {
+                let diff = ToolExecutor::generate_diff(text("old_string")?, text("new_string")?);
+                let replace_all = call
+                    .input
+                    .get("replace_all")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                if replace_all {
+                    format!("Every occurrence:\n{diff}")
+                } else {
+                    diff
+                }
+            }
+            "write_file" => {
+                let content = text("content")?;
+                format!(
+                    "Writes {} lines ({} bytes)",
+                    content.lines().count(),
+                    content.len()
+                )
+            }
+            _ => return None,
+        };
+        Some(truncate_preview(&preview))
+    }
+
     /// Extracts a human-readable input string from the tool call.
     fn extract_tool_input(&self, call: &ToolCall) -> Option<String> {
         match call.name.as_str() {
@@ -304,11 +575,15 @@ impl HookedToolExecutor {
     /// Executes a tool call with permission checks and hook integration.
     ///
     /// This method:
-    /// 1. Checks permissions - if denied, returns `ToolResult::Cancelled`
-    ///    If no rule matches, returns `ToolResult::NeedsPermission`
-    /// 2. Fires `PreToolUse` hook - if it returns Block, returns `ToolResult::Cancelled`
-    /// 3. Executes the actual tool
-    /// 4. Fires `PostToolUse` on success or `PostToolUseFailure` on failure
+    /// 1. Checks permissions - if denied, audits the call and returns
+    ///    `ToolResult::Cancelled`. If no rule matches, returns
+    ///    `ToolResult::NeedsPermission`
+    /// 2. Fires `PreToolUse` hook - if it returns Block, audits the call and
+    ///    returns `ToolResult::Cancelled`
+    /// 3. Executes the actual tool, redacting secrets from its output if a
+    ///    redactor is set; file contents from `read_file` are left intact
+    /// 4. Records the execution in the audit log, if one is set
+    /// 5. Fires `PostToolUse` on success or `PostToolUseFailure` on failure
     ///
     /// # Errors
     ///
@@ -320,8 +595,10 @@ impl HookedToolExecutor {
         // Check permissions if configured
         if let Some(ref permissions) = self.permissions {
             let input_str = self.extract_tool_input(&call);
-            let manager = permissions.lock().await;
-            let decision = manager.check(&tool_name, input_str.as_deref());
+            let decision = permissions
+                .lock()
+                .await
+                .check(&tool_name, input_str.as_deref());
 
             match decision {
                 PermissionDecision::Denied => {
@@ -330,6 +607,8 @@ impl HookedToolExecutor {
                         input = ?input_str,
                         "Tool execution denied by permission rule"
                     );
+                    self.record_audit(&tool_name, &tool_input, ToolAuditStatus::Denied)
+                        .await;
                     return Ok(ToolResult::Cancelled);
                 }
                 PermissionDecision::NeedsPrompt => {
@@ -338,9 +617,7 @@ impl HookedToolExecutor {
                         input = ?input_str,
                         "Tool execution requires permission prompt"
                     );
-                    let description = self.generate_description(&call);
-                    let request =
-                        PermissionRequest::new(&tool_name, input_str.as_deref(), &description);
+                    let request = self.permission_request(&call, input_str.as_deref());
                     return Ok(ToolResult::NeedsPermission(request));
                 }
                 PermissionDecision::Allowed | PermissionDecision::SessionGrant => {
@@ -363,11 +640,41 @@ impl HookedToolExecutor {
 
         // Check if hook blocked execution
         if matches!(pre_result.decision, HookDecision::Block { .. }) {
+            self.record_audit(&tool_name, &tool_input, ToolAuditStatus::Blocked)
+                .await;
             return Ok(ToolResult::Cancelled);
         }
 
-        // Execute the actual tool
-        let result = self.inner.execute(call).await?;
+        // Execute the actual tool, cancelling it past the configured limit
+        let run = async {
+            match &self.backend {
+                Some(backend) => backend.execute(call).await,
+                None => self.inner.execute(call).await,
+            }
+        };
+        let result = match self.parallel.config().tool_timeout {
+            Some(limit) => match tokio::time::timeout(limit, run).await {
+                Ok(result) => result?,
+                Err(_) => {
+                    ToolResult::Error(format!("Tool '{}' timed out after {:?}", tool_name, limit))
+                }
+            },
+            None => run.await?,
+        };
+        let result = match (&self.redactor, result) {
+            (_, result) if FILE_CONTENT_TOOLS.contains(&tool_name.as_str()) => result,
+            (Some(redactor), ToolResult::Success(output)) => {
+                ToolResult::Success(redactor.redact(&output))
+            }
+            (Some(redactor), ToolResult::Error(error)) => {
+                ToolResult::Error(redactor.redact(&error))
+            }
+            (_, result) => result,
+        };
+
+        if let Some(status) = ToolAuditStatus::of(&result) {
+            self.record_audit(&tool_name, &tool_input, status).await;
+        }
 
         // Fire post-execution hooks based on result
         match &result {
@@ -457,7 +764,7 @@ impl HookedToolExecutor {
         // the parallel executor. We pass a closure that wraps single tool execution.
         let indexed_results = self
             .parallel
-            .execute_batch(
+            .execute_batch_with_timeout(
                 calls
                     .iter()
                     .map(|call| (call.name.as_str(), call.input.clone())),
@@ -475,6 +782,9 @@ impl HookedToolExecutor {
                         ToolResult::Success(format!("Executed {}", call.name))
                     }
                 },
+                |name, limit| {
+                    ToolResult::Error(format!("Tool '{}' timed out after {:?}", name, limit))
+                },
             )
             .await;
 
@@ -501,9 +811,26 @@ impl HookedToolExecutor {
     }
 }
 
+/// Maximum number of preview lines shown in a permission prompt.
+const PREVIEW_MAX_LINES: usize = 12;
+
+/// Limits a preview to `PREVIEW_MAX_LINES`, noting how many were left out.
+fn truncate_preview(preview: &str) -> String {
+    let lines: Vec<&str> = preview.lines().collect();
+    if lines.len() <= PREVIEW_MAX_LINES {
+        return preview.trim_end().to_string();
+    }
+    format!(
+        "{}\n… {} more lines",
+        lines[..PREVIEW_MAX_LINES].join("\n"),
+        lines.len() - PREVIEW_MAX_LINES
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::parallel::ToolSafetyClass;
     use serde_json::json;
 
     #[test]
@@ -524,6 +851,236 @@ mod tests {
         assert!(!executor.parallel_config().enabled);
     }
 
+    #[test]
+    fn test_hooked_executor_set_mcp_safety_keeps_config() {
+        let hooks = HookManager::new("test-session".to_string());
+        let mut executor = HookedToolExecutor::new(PathBuf::from("/tmp"), hooks)
+            .with_parallel_config(ParallelConfig::default().with_max_concurrency(3));
+
+        executor.set_mcp_safety(
+            McpSafetyRegistry::new().with_rule("mcp__docs__*", ToolSafetyClass::ReadOnly),
+        );
+
+        let config = executor.parallel_config();
+        assert_eq!(config.max_concurrency, 3);
+        assert_eq!(
+            config.mcp_safety.classify("mcp__docs__search"),
+            Some(ToolSafetyClass::ReadOnly)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_org_policy_blocks_commands_the_user_policy_allows() {
+        let hooks = HookManager::new("test".to_string());
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = HookedToolExecutor::new(dir.path().to_path_buf(), hooks).with_policy(
+            ToolExecutionPolicy {
+                dangerous_patterns: vec![],
+                ..ToolExecutionPolicy::default()
+            },
+        );
+        let org = OrgPolicy::parse(r"blocked_patterns = ['^echo\s+deploy\b']").unwrap();
+        executor.apply_org_policy(&org);
+
+        let call = ToolCall {
+            name: "bash".to_string(),
+            input: json!({"command": "echo deploy now"}),
+        };
+        let result = executor.execute(call).await.unwrap();
+        assert!(
+            matches!(result, ToolResult::Error(ref e) if e.contains("blocked")),
+            "got: {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_timeout_cancels_slow_call() {
+        let hooks = HookManager::new("test".to_string());
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = HookedToolExecutor::new(dir.path().to_path_buf(), hooks);
+        executor.set_tool_timeout(Some(Duration::from_millis(100)));
+
+        let slow = ToolCall {
+            name: "bash".to_string(),
+            input: json!({"command": "sleep 5"}),
+        };
+        let started = std::time::Instant::now();
+        let result = executor.execute(slow).await.unwrap();
+        assert!(
+            matches!(result, ToolResult::Error(ref e) if e.contains("timed out")),
+            "got: {result:?}"
+        );
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let fast = ToolCall {
+            name: "bash".to_string(),
+            input: json!({"command": "echo ok"}),
+        };
+        let result = executor.execute(fast).await.unwrap();
+        assert!(matches!(result, ToolResult::Success(ref out) if out.contains("ok")));
+    }
+
+    #[tokio::test]
+    async fn test_executed_tools_are_audited() {
+        use crate::enterprise::tool_audit::{verify_log, ToolAuditConfig};
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("tools.jsonl");
+        let hooks = HookManager::new("audit-session".to_string());
+        let mut executor = HookedToolExecutor::new(dir.path().to_path_buf(), hooks);
+        executor.set_audit_log(ToolAuditLog::open(ToolAuditConfig::new(&log_path)).unwrap());
+
+        for path in ["exists.txt", "missing.txt"] {
+            std::fs::write(dir.path().join("exists.txt"), "hello").unwrap();
+            let call = ToolCall {
+                name: "read_file".to_string(),
+                input: json!({ "path": path }),
+            };
+            executor.execute(call).await.unwrap();
+        }
+
+        assert_eq!(verify_log(&log_path).unwrap(), 2);
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&log_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["session_id"], "audit-session");
+        assert_eq!(lines[0]["tool"], "read_file");
+        assert_eq!(lines[0]["input"]["path"], "exists.txt");
+        assert_eq!(lines[0]["status"], "success");
+        assert_eq!(lines[1]["status"], "error");
+        assert_eq!(lines[1]["prev_hash"], lines[0]["hash"]);
+    }
+
+    #[tokio::test]
+    async fn test_refused_tools_are_audited() {
+        use crate::enterprise::tool_audit::{verify_log, ToolAuditConfig};
+        use crate::hooks::HookEvent;
+        use crate::permissions::PermissionRule;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("tools.jsonl");
+        let mut hooks = HookManager::new("audit-session".to_string());
+        hooks.register_tool_hook(HookEvent::PreToolUse, None, "echo no; exit 2");
+        let mut manager = PermissionManager::new();
+        manager.add_rule(PermissionRule::new("write_file", None, false));
+        manager.add_rule(PermissionRule::new("bash", None, true));
+        let mut executor = HookedToolExecutor::new(dir.path().to_path_buf(), hooks)
+            .with_permissions(Arc::new(Mutex::new(manager)));
+        executor.set_audit_log(ToolAuditLog::open(ToolAuditConfig::new(&log_path)).unwrap());
+
+        for (name, input) in [
+            ("write_file", json!({ "path": "a.txt", "content": "x" })),
+            ("bash", json!({ "command": "echo hi" })),
+        ] {
+            let call = ToolCall {
+                name: name.to_string(),
+                input,
+            };
+            let result = executor.execute(call).await.unwrap();
+            assert!(
+                matches!(result, ToolResult::Cancelled),
+                "{name}: {result:?}"
+            );
+        }
+
+        assert_eq!(verify_log(&log_path).unwrap(), 2);
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&log_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["tool"], "write_file");
+        assert_eq!(lines[0]["status"], "denied");
+        assert_eq!(lines[1]["tool"], "bash");
+        assert_eq!(lines[1]["status"], "blocked");
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_secrets_are_redacted_from_output_and_audit() {
+        use crate::enterprise::tool_audit::ToolAuditConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("tools.jsonl");
+        let hooks = HookManager::new("redact-session".to_string());
+        let mut executor = HookedToolExecutor::new(dir.path().to_path_buf(), hooks);
+        executor.set_audit_log(ToolAuditLog::open(ToolAuditConfig::new(&log_path)).unwrap());
+        executor.set_redactor(Redactor::default());
+
+        let secret = "ANTHROPIC_API_KEY=sk-ant-REDACTED";
+        let write = ToolCall {
+            name: "write_file".to_string(),
+            input: json!({ "path": ".env", "content": secret }),
+        };
+        executor.execute(write).await.unwrap();
+        let cat = ToolCall {
+            name: "bash".to_string(),
+            input: json!({ "command": "cat .env" }),
+        };
+        let output = match executor.execute(cat).await.unwrap() {
+            ToolResult::Success(output) => output,
+            other => panic!("expected success, got {other:?}"),
+        };
+
+        // The file itself is untouched; only what leaves the tool is redacted
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".env")).unwrap(),
+            secret
+        );
+        assert!(output.contains("ANTHROPIC_API_KEY=[REDACTED]"));
+        assert!(!output.contains("sk-ant-"));
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("[REDACTED]"));
+        assert!(!log.contains("sk-ant-"));
+    }
+
+    #[tokio::test]
+    async fn test_file_contents_reach_the_model_unredacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = HookManager::new("redact-session".to_string());
+        let mut executor = HookedToolExecutor::new(dir.path().to_path_buf(), hooks);
+        executor.set_redactor(Redactor::new(&[], true).unwrap());
+
+        let code = "let token = client.get_token(&cfg)?;\n\
+                    pub refresh_token: Option<String>,\n\
+                    const password = await prompt('Password: ');\n";
+        std::fs::write(dir.path().join("auth.rs"), code).unwrap();
+        let blob = "integrity: wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+        std::fs::write(dir.path().join("lock.yaml"), blob).unwrap();
+        let run = |name: &str, input: serde_json::Value| {
+            executor.execute(ToolCall {
+                name: name.to_string(),
+                input,
+            })
+        };
+
+        let read = run("read_file", json!({ "path": "auth.rs" }))
+            .await
+            .unwrap();
+        assert!(
+            matches!(read, ToolResult::Success(ref out) if out.contains(code)),
+            "got: {read:?}"
+        );
+        let read = run("read_file", json!({ "path": "lock.yaml" }))
+            .await
+            .unwrap();
+        assert!(
+            matches!(read, ToolResult::Success(ref out) if out.contains(blob)),
+            "got: {read:?}"
+        );
+
+        // Other output is still scanned for random-looking tokens
+        let cat = run("bash", json!({ "command": "cat lock.yaml" }))
+            .await
+            .unwrap();
+        assert!(
+            matches!(cat, ToolResult::Success(ref out) if out.contains("[REDACTED]")),
+            "got: {cat:?}"
+        );
+    }
+
     #[test]
     fn test_extract_tool_input_bash() {
         let hooks = HookManager::new("test".to_string());
@@ -554,6 +1111,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_permission_request_previews_edit_diff() {
+        let hooks = HookManager::new("test".to_string());
+        let executor = HookedToolExecutor::new(PathBuf::from("/tmp"), hooks);
+
+        let call = ToolCall {
+            name: "edit".to_string(),
+            input: json!({"path": "src/lib.rs", "old_string": "let x = 1;", "new_string": "let x = 2;"}),
+        };
+        let request = executor.permission_request(&call, Some("src/lib.rs"));
+        assert_eq!(request.description, "Edit file: src/lib.rs");
+        assert_eq!(
+            request.preview.as_deref(),
+            Some("- let x = 1;\n+ let x = 2;")
+        );
+    }
+
+    #[test]
+    fn test_permission_request_summarizes_write_and_truncates_long_diffs() {
+        let hooks = HookManager::new("test".to_string());
+        let executor = HookedToolExecutor::new(PathBuf::from("/tmp"), hooks);
+
+        let call = ToolCall {
+            name: "write_file".to_string(),
+            input: json!({"path": "notes.txt", "content": "one\ntwo\n"}),
+        };
+        let request = executor.permission_request(&call, Some("notes.txt"));
+        assert_eq!(request.preview.as_deref(), Some("Writes 2 lines (8 bytes)"));
+
+        let old: Vec<String> = (0..20).map(|i| format!("line {i}")).collect();
+        let call = ToolCall {
+            name: "edit".to_string(),
+            input: json!({"path": "a.txt", "old_string": old.join("\n"), "new_string": ""}),
+        };
+        let preview = executor.permission_request(&call, None).preview.unwrap();
+        assert_eq!(preview.lines().count(), PREVIEW_MAX_LINES + 1);
+        assert!(preview.ends_with("… 8 more lines"));
+
+        let call = ToolCall {
+            name: "bash".to_string(),
+            input: json!({"command": "ls"}),
+        };
+        assert_eq!(executor.permission_request(&call, Some("ls")).preview, None);
+    }
+
     #[test]
     fn test_extract_tool_input_glob() {
         let hooks = HookManager::new("test".to_string());
@@ -8,10 +8,14 @@
 //! - Grep content search with regex support
 //! - Web content fetching with HTML to markdown conversion
 //! - Hook integration via `HookedToolExecutor`
+//! - Pluggable execution backends via `ToolBackend`
 //! - Parallel tool execution for performance optimization
+//! - Offloading of oversized tool output to session files
 
+mod backend;
 mod executor;
 mod hooked;
+pub mod output_files;
 pub mod parallel;
 mod security;
 mod stateful;
@@ -19,6 +23,9 @@ pub mod vision;
 pub mod web_fetch;
 pub mod web_search;
 
+// Re-export backend trait
+pub use backend::ToolBackend;
+
 // Re-export executor types
 pub use executor::{ToolCall, ToolExecutor, ToolResult};
 
@@ -0,0 +1,182 @@
+//! Offloading of oversized tool output to session-scoped files.
+//!
+//! A full diff or a long log costs context tokens on every later turn. When
+//! enabled, tool output above a threshold is written to a file and the model
+//! receives the path, the size and a short preview instead, so it can page
+//! through the file with `read_file` only if it needs to. The files live in a
+//! per-session temp directory that is removed when the executor is dropped.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tracing::warn;
+
+use crate::util::format_bytes;
+
+/// Number of leading lines included in the preview.
+pub const PREVIEW_LINES: usize = 20;
+
+/// Session-scoped store for tool output that is too large to inline.
+#[derive(Debug)]
+pub struct ToolOutputFiles {
+    dir: PathBuf,
+    threshold: usize,
+    counter: AtomicUsize,
+}
+
+impl ToolOutputFiles {
+    /// Creates a store for output larger than `threshold` bytes in a new
+    /// session directory under the system temp directory.
+    #[must_use]
+    pub fn new(threshold: usize) -> Self {
+        let dir = std::env::temp_dir()
+            .join("patina-tool-outputs")
+            .join(uuid::Uuid::new_v4().to_string());
+        Self::with_dir(dir, threshold)
+    }
+
+    /// Creates a store that writes to `dir`, which is removed on drop.
+    #[must_use]
+    pub fn with_dir(dir: PathBuf, threshold: usize) -> Self {
+        Self {
+            dir,
+            threshold,
+            counter: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the directory output files are written to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the canonical path of an output file from this session.
+    ///
+    /// Returns `None` for anything outside the session directory, including
+    /// symlinks that resolve outside it.
+    #[must_use]
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let dir = self.dir.canonicalize().ok()?;
+        let path = Path::new(path).canonicalize().ok()?;
+        (path.starts_with(&dir) && path.is_file()).then_some(path)
+    }
+
+    /// Returns the output to give the model for a tool result.
+    ///
+    /// Output within the threshold is returned unchanged. Larger output is
+    /// saved to a file and replaced by a reference with a preview; if the file
+    /// cannot be written, the output is returned unchanged.
+    #[must_use]
+    pub fn offload(&self, tool: &str, output: String) -> String {
+        if output.len() <= self.threshold {
+            return output;
+        }
+
+        match self.save(tool, &output) {
+            Ok(path) => reference(&path, &output),
+            Err(e) => {
+                warn!(tool, error = %e, "Failed to save tool output to a file");
+                output
+            }
+        }
+    }
+
+    /// Writes output to a new file in the session directory.
+    fn save(&self, tool: &str, output: &str) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let n = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        let name: String = tool
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let path = self.dir.join(format!("{name}-{n}.txt"));
+        std::fs::write(&path, output)?;
+        Ok(path)
+    }
+}
+
+impl Drop for ToolOutputFiles {
+    fn drop(&mut self) {
+        if self.dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+                warn!(dir = %self.dir.display(), error = %e, "Failed to remove tool output files");
+            }
+        }
+    }
+}
+
+/// Builds the message that replaces offloaded output.
+fn reference(path: &Path, output: &str) -> String {
+    let lines = output.lines().count();
+    let preview: Vec<&str> = output.lines().take(PREVIEW_LINES).collect();
+    format!(
+        "Output saved to {} ({} lines, {}). Use read_file with this path, \
+         and offset and limit for large files, to see more.\n\nFirst {} lines:\n{}",
+        path.display(),
+        lines,
+        format_bytes(output.len() as u64),
+        preview.len(),
+        preview.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_small_output_is_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ToolOutputFiles::with_dir(temp_dir.path().join("out"), 100);
+
+        assert_eq!(files.offload("bash", "ok".to_string()), "ok");
+        assert!(!files.dir().exists());
+    }
+
+    #[test]
+    fn test_large_output_is_saved_with_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ToolOutputFiles::with_dir(temp_dir.path().join("out"), 100);
+        let output: String = (1..=50).map(|i| format!("line {i}\n")).collect();
+
+        let message = files.offload("mcp__git__diff", output.clone());
+
+        let path = files.dir().join("mcp__git__diff-1.txt");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), output);
+        assert!(message.starts_with(&format!("Output saved to {}", path.display())));
+        assert!(message.contains("50 lines"));
+        assert!(message.contains("line 20"));
+        assert!(!message.contains("line 21"));
+    }
+
+    #[test]
+    fn test_resolve_only_accepts_session_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ToolOutputFiles::with_dir(temp_dir.path().join("out"), 0);
+        let message = files.offload("bash", "output".to_string());
+        let saved = files.dir().join("bash-1.txt");
+        assert!(message.contains(&saved.display().to_string()));
+
+        assert!(files.resolve(saved.to_str().unwrap()).is_some());
+
+        let outside = temp_dir.path().join("secret.txt");
+        std::fs::write(&outside, "secret").unwrap();
+        assert!(files.resolve(outside.to_str().unwrap()).is_none());
+        let escape = files.dir().join("../secret.txt");
+        assert!(files.resolve(escape.to_str().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_dir_removed_on_drop() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("out");
+        let files = ToolOutputFiles::with_dir(dir.clone(), 0);
+        let _ = files.offload("bash", "output".to_string());
+        assert!(dir.exists());
+
+        drop(files);
+        assert!(!dir.exists());
+    }
+}
@@ -4,7 +4,8 @@
 //! whether they can safely be executed in parallel.
 
 use once_cell::sync::Lazy;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 /// Classification of tool safety for parallel execution.
 ///
@@ -31,7 +32,8 @@ use std::collections::HashSet;
 /// let unknown_class = ToolSafetyClass::Unknown;
 /// assert!(!unknown_class.is_parallelizable());
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ToolSafetyClass {
     /// Tool only reads data and has no side effects.
     /// Safe to execute in parallel with other ReadOnly tools.
@@ -110,6 +112,93 @@ pub fn classify_tool(tool_name: &str) -> ToolSafetyClass {
     }
 }
 
+/// User-declared safety classes for MCP tools.
+///
+/// MCP tools are classified as `Unknown` unless a rule here matches. A pattern
+/// ending in `*` matches by prefix (`mcp__docs__*`); any other pattern must
+/// match the tool name exactly. Exact names take precedence over prefixes,
+/// and longer prefixes over shorter ones.
+///
+/// # Examples
+///
+/// ```
+/// use patina::tools::parallel::{McpSafetyRegistry, ToolSafetyClass};
+///
+/// let registry = McpSafetyRegistry::new()
+///     .with_rule("mcp__docs__*", ToolSafetyClass::ReadOnly)
+///     .with_rule("mcp__docs__publish", ToolSafetyClass::Mutating);
+///
+/// assert_eq!(registry.classify("mcp__docs__search"), Some(ToolSafetyClass::ReadOnly));
+/// assert_eq!(registry.classify("mcp__docs__publish"), Some(ToolSafetyClass::Mutating));
+/// assert_eq!(registry.classify("mcp__other__search"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct McpSafetyRegistry {
+    rules: HashMap<String, ToolSafetyClass>,
+}
+
+impl McpSafetyRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule for an exact tool name or a `*`-terminated prefix.
+    #[must_use]
+    pub fn with_rule(mut self, pattern: impl Into<String>, class: ToolSafetyClass) -> Self {
+        self.insert(pattern, class);
+        self
+    }
+
+    /// Adds or replaces a rule for an exact tool name or a `*`-terminated prefix.
+    pub fn insert(&mut self, pattern: impl Into<String>, class: ToolSafetyClass) {
+        self.rules.insert(pattern.into(), class);
+    }
+
+    /// Returns true if no rules are declared.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the declared class for an MCP tool, if any rule matches.
+    ///
+    /// Names that do not start with `mcp__` are never matched, so built-in
+    /// tools cannot be reclassified.
+    #[must_use]
+    pub fn classify(&self, tool_name: &str) -> Option<ToolSafetyClass> {
+        if !tool_name.starts_with("mcp__") {
+            return None;
+        }
+
+        if let Some(class) = self.rules.get(tool_name) {
+            return Some(*class);
+        }
+
+        self.rules
+            .iter()
+            .filter_map(|(pattern, class)| {
+                let prefix = pattern.strip_suffix('*')?;
+                tool_name
+                    .starts_with(prefix)
+                    .then_some((prefix.len(), *class))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, class)| class)
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, ToolSafetyClass)> for McpSafetyRegistry {
+    fn from_iter<I: IntoIterator<Item = (S, ToolSafetyClass)>>(iter: I) -> Self {
+        let mut registry = Self::new();
+        for (pattern, class) in iter {
+            registry.insert(pattern, class);
+        }
+        registry
+    }
+}
+
 /// Static set of bash commands that are safe to run in parallel.
 ///
 /// These commands only read data and have no side effects that could
@@ -350,7 +439,8 @@ pub fn classify_bash_command(command: &str) -> ToolSafetyClass {
 }
 
 /// Checks if a command contains shell operators that could chain to mutating operations.
-fn contains_shell_operators(command: &str) -> bool {
+#[must_use]
+pub fn contains_shell_operators(command: &str) -> bool {
     // Check for pipes, redirections, command substitution, etc.
     // These could chain a read-only command to a mutating one
 
@@ -364,17 +454,18 @@ fn contains_shell_operators(command: &str) -> bool {
         return true;
     }
 
-    // Background execution (&)
-    // Be careful not to match && which we check separately
-    if command.contains(" & ") || command.ends_with(" &") || command.ends_with('&') {
-        // Check it's not && at the end
-        if !command.ends_with("&&") {
-            return true;
-        }
+    // Background execution, && and fd duplication (any &)
+    if command.contains('&') {
+        return true;
     }
 
-    // Logical operators (&& or ||)
-    if command.contains("&&") || command.contains("||") {
+    // Logical or (||)
+    if command.contains("||") {
+        return true;
+    }
+
+    // Process substitution <() (>() is caught by the redirection check)
+    if command.contains("<(") {
         return true;
     }
 
@@ -383,8 +474,8 @@ fn contains_shell_operators(command: &str) -> bool {
         return true;
     }
 
-    // Semicolon (command separator)
-    if command.contains(';') {
+    // Semicolon or newline (command separators)
+    if command.contains(';') || command.contains('\n') {
         return true;
     }
 
@@ -698,6 +789,22 @@ mod tests {
             ToolSafetyClass::Unknown
         );
         assert_eq!(classify_bash_command("ls &"), ToolSafetyClass::Unknown);
+        assert_eq!(
+            classify_bash_command("ls\nrm file"),
+            ToolSafetyClass::Unknown
+        );
+        assert_eq!(
+            classify_bash_command("ls&rm file"),
+            ToolSafetyClass::Unknown
+        );
+        assert_eq!(
+            classify_bash_command("diff <(rm file) b"),
+            ToolSafetyClass::Unknown
+        );
+        assert_eq!(
+            classify_bash_command("cat >(rm file)"),
+            ToolSafetyClass::Unknown
+        );
     }
 
     #[test]
@@ -763,4 +870,68 @@ mod tests {
         assert_eq!(classify_bash_command(""), ToolSafetyClass::Unknown);
         assert_eq!(classify_bash_command("   "), ToolSafetyClass::Unknown);
     }
+
+    // =========================================================================
+    // Tests for McpSafetyRegistry
+    // =========================================================================
+
+    #[test]
+    fn test_mcp_registry_empty_matches_nothing() {
+        let registry = McpSafetyRegistry::new();
+        assert!(registry.is_empty());
+        assert_eq!(registry.classify("mcp__docs__search"), None);
+    }
+
+    #[test]
+    fn test_mcp_registry_exact_and_prefix() {
+        let registry = McpSafetyRegistry::new()
+            .with_rule("mcp__docs__search", ToolSafetyClass::ReadOnly)
+            .with_rule("mcp__github__*", ToolSafetyClass::Mutating);
+
+        assert_eq!(
+            registry.classify("mcp__docs__search"),
+            Some(ToolSafetyClass::ReadOnly)
+        );
+        assert_eq!(registry.classify("mcp__docs__search_all"), None);
+        assert_eq!(
+            registry.classify("mcp__github__create_issue"),
+            Some(ToolSafetyClass::Mutating)
+        );
+    }
+
+    #[test]
+    fn test_mcp_registry_exact_beats_longest_prefix() {
+        let registry: McpSafetyRegistry = [
+            ("mcp__*", ToolSafetyClass::Mutating),
+            ("mcp__docs__*", ToolSafetyClass::ReadOnly),
+            ("mcp__docs__publish", ToolSafetyClass::Unknown),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            registry.classify("mcp__docs__fetch"),
+            Some(ToolSafetyClass::ReadOnly)
+        );
+        assert_eq!(
+            registry.classify("mcp__docs__publish"),
+            Some(ToolSafetyClass::Unknown)
+        );
+        assert_eq!(
+            registry.classify("mcp__other__run"),
+            Some(ToolSafetyClass::Mutating)
+        );
+    }
+
+    #[test]
+    fn test_mcp_registry_ignores_builtin_tools() {
+        let registry = McpSafetyRegistry::new().with_rule("bash", ToolSafetyClass::ReadOnly);
+        assert_eq!(registry.classify("bash"), None);
+    }
+
+    #[test]
+    fn test_tool_safety_class_deserializes_kebab_case() {
+        let class: ToolSafetyClass = serde_json::from_str("\"read-only\"").unwrap();
+        assert_eq!(class, ToolSafetyClass::ReadOnly);
+    }
 }
@@ -16,7 +16,8 @@ mod classification;
 
 // Re-export classification types
 pub use classification::{
-    classify_bash_command, classify_tool, ToolSafetyClass, SAFE_BASH_COMMANDS,
+    classify_bash_command, classify_tool, contains_shell_operators, McpSafetyRegistry,
+    ToolSafetyClass, SAFE_BASH_COMMANDS,
 };
 
 // =============================================================================
@@ -25,6 +26,7 @@ pub use classification::{
 
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Semaphore;
 
 /// Configuration for parallel execution.
@@ -32,7 +34,7 @@ use tokio::sync::Semaphore;
 /// # Examples
 ///
 /// ```
-/// use patina::tools::parallel::ParallelConfig;
+/// use patina::tools::parallel::{McpSafetyRegistry, ParallelConfig};
 ///
 /// // Default configuration
 /// let config = ParallelConfig::default();
@@ -44,6 +46,8 @@ use tokio::sync::Semaphore;
 ///     enabled: true,
 ///     max_concurrency: 16,
 ///     aggressive: false,
+///     tool_timeout: None,
+///     mcp_safety: McpSafetyRegistry::new(),
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -59,6 +63,15 @@ pub struct ParallelConfig {
     /// WARNING: This can cause race conditions with external tools.
     /// Only enable if you understand the risks.
     pub aggressive: bool,
+
+    /// Maximum time a single tool may run within a batch.
+    /// A tool that exceeds it is cancelled; the rest of the batch continues.
+    /// `None` means no per-tool limit.
+    pub tool_timeout: Option<Duration>,
+
+    /// Safety classes declared for MCP tools.
+    /// MCP tools without a matching rule are classified as Unknown.
+    pub mcp_safety: McpSafetyRegistry,
 }
 
 impl Default for ParallelConfig {
@@ -67,6 +80,8 @@ impl Default for ParallelConfig {
             enabled: true,
             max_concurrency: 8,
             aggressive: false,
+            tool_timeout: None,
+            mcp_safety: McpSafetyRegistry::new(),
         }
     }
 }
@@ -100,6 +115,8 @@ impl ParallelConfig {
             enabled: true,
             max_concurrency: 16,
             aggressive: true,
+            tool_timeout: None,
+            mcp_safety: McpSafetyRegistry::new(),
         }
     }
 
@@ -114,6 +131,20 @@ impl ParallelConfig {
         self.max_concurrency = max_concurrency;
         self
     }
+
+    /// Sets the maximum time a single tool may run within a batch.
+    #[must_use]
+    pub fn with_tool_timeout(mut self, timeout: Duration) -> Self {
+        self.tool_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the safety classes declared for MCP tools.
+    #[must_use]
+    pub fn with_mcp_safety(mut self, registry: McpSafetyRegistry) -> Self {
+        self.mcp_safety = registry;
+        self
+    }
 }
 
 /// Result of a single tool execution with its original index.
@@ -268,6 +299,43 @@ impl ParallelExecutor {
         self.execute_with_grouping(classified, execute_fn).await
     }
 
+    /// Executes a batch like [`execute_batch`](Self::execute_batch), cancelling
+    /// tools that exceed the configured `tool_timeout`.
+    ///
+    /// A cancelled tool's result is produced by `on_timeout`, which receives
+    /// the tool name and the limit it exceeded. Other tools in the batch are
+    /// unaffected, and results keep their original indices.
+    pub async fn execute_batch_with_timeout<'a, T, I, F, Fut, G>(
+        &self,
+        tools: I,
+        execute_fn: F,
+        on_timeout: G,
+    ) -> Vec<IndexedResult<T>>
+    where
+        I: Iterator<Item = (&'a str, serde_json::Value)>,
+        F: Fn(&str, serde_json::Value) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = T> + Send,
+        G: Fn(&str, Duration) -> T + Clone + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let Some(limit) = self.config.tool_timeout else {
+            return self.execute_batch(tools, execute_fn).await;
+        };
+
+        let timed_fn = move |name: &str, input: serde_json::Value| {
+            let name = name.to_string();
+            let fut = execute_fn(&name, input);
+            let on_timeout = on_timeout.clone();
+            async move {
+                match tokio::time::timeout(limit, fut).await {
+                    Ok(result) => result,
+                    Err(_) => on_timeout(&name, limit),
+                }
+            }
+        };
+        self.execute_batch(tools, timed_fn).await
+    }
+
     /// Classifies a tool for execution, considering bash command content.
     fn classify_for_execution(&self, name: &str, input: &serde_json::Value) -> ToolSafetyClass {
         // For bash commands, we need to look at the actual command
@@ -278,6 +346,10 @@ impl ParallelExecutor {
             return ToolSafetyClass::Unknown;
         }
 
+        if let Some(class) = self.config.mcp_safety.classify(name) {
+            return class;
+        }
+
         classify_tool(name)
     }
 
@@ -431,7 +503,10 @@ impl<T> SortByIndex<T> for Vec<IndexedResult<T>> {
 
 #[cfg(test)]
 mod tests {
-    use super::{IndexedResult, ParallelConfig, ParallelExecutor, SortByIndex, ToolSafetyClass};
+    use super::{
+        IndexedResult, McpSafetyRegistry, ParallelConfig, ParallelExecutor, SortByIndex,
+        ToolSafetyClass,
+    };
     use serde_json::json;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
@@ -457,6 +532,8 @@ mod tests {
             enabled: true,
             max_concurrency: 16,
             aggressive: false,
+            tool_timeout: None,
+            mcp_safety: McpSafetyRegistry::new(),
         };
         let executor = ParallelExecutor::new(config);
 
@@ -498,6 +575,38 @@ mod tests {
         assert!(!executor.is_parallelizable(ToolSafetyClass::Mutating));
     }
 
+    #[test]
+    fn test_classify_for_execution_uses_mcp_registry() {
+        let registry = McpSafetyRegistry::new()
+            .with_rule("mcp__docs__*", ToolSafetyClass::ReadOnly)
+            .with_rule("mcp__docs__publish", ToolSafetyClass::Mutating);
+        let executor = ParallelExecutor::new(ParallelConfig::default().with_mcp_safety(registry));
+
+        let search = executor.classify_for_execution("mcp__docs__search", &json!({}));
+        assert_eq!(search, ToolSafetyClass::ReadOnly);
+        assert!(executor.is_parallelizable(search));
+
+        // Undeclared MCP tools stay Unknown
+        assert_eq!(
+            executor.classify_for_execution("mcp__other__search", &json!({})),
+            ToolSafetyClass::Unknown
+        );
+    }
+
+    #[test]
+    fn test_mcp_registry_mutating_not_parallel_in_aggressive_mode() {
+        let registry = McpSafetyRegistry::new().with_rule("mcp__db__*", ToolSafetyClass::Mutating);
+        let executor =
+            ParallelExecutor::new(ParallelConfig::aggressive().with_mcp_safety(registry));
+
+        let write = executor.classify_for_execution("mcp__db__insert", &json!({}));
+        assert!(!executor.is_parallelizable(write));
+
+        // Undeclared MCP tools are still batched in aggressive mode
+        let other = executor.classify_for_execution("mcp__other__run", &json!({}));
+        assert!(executor.is_parallelizable(other));
+    }
+
     // =========================================================================
     // 1.4.3 Tests for execute_batch method
     // =========================================================================
@@ -605,6 +714,49 @@ mod tests {
         assert_eq!(sorted.len(), 4);
     }
 
+    #[tokio::test]
+    async fn test_execute_batch_with_timeout_cancels_slow_tool() {
+        use crate::tools::ToolResult;
+        use std::time::{Duration, Instant};
+
+        let config = ParallelConfig::default().with_tool_timeout(Duration::from_millis(100));
+        let executor = ParallelExecutor::new(config);
+
+        let tools = [
+            ("read_file", json!({"path": "fast1.txt"})),
+            ("web_fetch", json!({"url": "https://slow.example"})),
+            ("read_file", json!({"path": "fast2.txt"})),
+        ];
+
+        let started = Instant::now();
+        let results = executor
+            .execute_batch_with_timeout(
+                tools.iter().map(|(n, i)| (*n, i.clone())),
+                |name, input| {
+                    let name = name.to_string();
+                    async move {
+                        if name == "web_fetch" {
+                            tokio::time::sleep(Duration::from_secs(30)).await;
+                        }
+                        ToolResult::Success(input.to_string())
+                    }
+                },
+                |name, limit| {
+                    ToolResult::Error(format!("Tool '{}' timed out after {:?}", name, limit))
+                },
+            )
+            .await
+            .into_sorted_results();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(results.len(), 3);
+        assert!(matches!(&results[0], ToolResult::Success(out) if out.contains("fast1.txt")));
+        assert!(
+            matches!(&results[1], ToolResult::Error(msg) if msg.contains("'web_fetch' timed out"))
+        );
+        assert!(matches!(&results[2], ToolResult::Success(out) if out.contains("fast2.txt")));
+    }
+
     #[tokio::test]
     async fn test_execute_batch_empty() {
         let executor = ParallelExecutor::new(ParallelConfig::default());
@@ -127,6 +127,7 @@ pub(crate) static DANGEROUS_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
 ///   `allowlist_mode = true` and providing patterns in `allowed_commands`.
 ///
 /// In both modes, dangerous patterns are always checked and will block matching commands.
+#[derive(Clone)]
 pub struct ToolExecutionPolicy {
     /// Patterns that match dangerous commands (always blocked).
     pub dangerous_patterns: Vec<Regex>,
@@ -150,6 +151,24 @@ pub struct ToolExecutionPolicy {
     ///
     /// Only used when `allowlist_mode` is true.
     pub allowed_commands: Vec<Regex>,
+    /// Commands an organization policy allows, if it enforces an allowlist.
+    ///
+    /// Checked in addition to `allowed_commands`, so a command must satisfy
+    /// both the user's allowlist and the organization's.
+    pub org_allowed_commands: Option<Vec<Regex>>,
+    /// Directory that file backups are written to before modification.
+    ///
+    /// Relative paths are resolved against the working directory. Defaults to
+    /// `.rct_backups` in the working directory.
+    pub backup_dir: Option<PathBuf>,
+    /// Maximum number of backups kept per file name; older ones are pruned.
+    ///
+    /// Zero (the default) keeps every backup.
+    pub max_backups_per_file: usize,
+    /// Whether files are backed up before `write_file` and `edit` modify them.
+    ///
+    /// Defaults to true; disable with `--no-backups`.
+    pub backups_enabled: bool,
 }
 
 impl Default for ToolExecutionPolicy {
@@ -162,10 +181,74 @@ impl Default for ToolExecutionPolicy {
             command_timeout: Duration::from_secs(300),
             allowlist_mode: false,
             allowed_commands: vec![],
+            org_allowed_commands: None,
+            backup_dir: None,
+            max_backups_per_file: 0,
+            backups_enabled: true,
         }
     }
 }
 
+impl ToolExecutionPolicy {
+    /// Checks a shell command against the dangerous patterns and, in
+    /// allowlist mode, the allowed commands, then against the organization's
+    /// allowed commands if it has any.
+    ///
+    /// Both the command and its [normalized](normalize_command) form are
+    /// matched, so escaped characters cannot slip past a pattern. Applies to
+    /// every command Patina runs: bash calls, plugin tools, hooks and MCP
+    /// server launches.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the command is blocked.
+    pub fn check_command(&self, command: &str) -> Result<(), String> {
+        let normalized = normalize_command(command);
+
+        for pattern in &self.dangerous_patterns {
+            if pattern.is_match(command) || pattern.is_match(&normalized) {
+                tracing::warn!(
+                    pattern = %pattern.as_str(),
+                    command = %command,
+                    "Security violation: command blocked by dangerous pattern"
+                );
+                return Err(format!(
+                    "Command blocked by security policy: matches {:?}",
+                    pattern.as_str()
+                ));
+            }
+        }
+
+        if self.allowlist_mode
+            && !self
+                .allowed_commands
+                .iter()
+                .any(|pattern| pattern.is_match(command) || pattern.is_match(&normalized))
+        {
+            tracing::warn!(
+                command = %command,
+                "Security: command blocked by allowlist policy"
+            );
+            return Err("Command blocked: not in allowlist".to_string());
+        }
+
+        if let Some(org_allowed) = &self.org_allowed_commands {
+            if !org_allowed
+                .iter()
+                .any(|pattern| pattern.is_match(command) || pattern.is_match(&normalized))
+            {
+                tracing::warn!(
+                    command = %command,
+                    "Security: command blocked by organization allowlist"
+                );
+                return Err("Command blocked: not in organization allowlist".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Returns platform-specific protected paths.
 #[cfg(unix)]
 fn default_protected_paths() -> Vec<PathBuf> {
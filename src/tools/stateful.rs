@@ -2,8 +2,12 @@
 //!
 //! This module provides tool executors that maintain shell state (current directory,
 //! environment variables) across command executions.
+//!
+//! A project `.env` file can also be loaded into the shell environment. Its
+//! values are passed to commands only; they are never persisted with the
+//! session or placed in the conversation.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
@@ -12,8 +16,13 @@ use tokio::process::Command;
 use tracing::{debug, warn};
 
 use super::executor::{ToolCall, ToolExecutor, ToolResult};
-use super::security::{normalize_command, ToolExecutionPolicy};
+use super::security::ToolExecutionPolicy;
+use crate::session::ShellSnapshot;
 use crate::shell::ShellConfig;
+use crate::types::config::PathConfinement;
+
+/// Name of the project environment file loaded by [`StatefulToolExecutor::load_dotenv`].
+pub const DOTENV_FILE: &str = ".env";
 
 /// Shell state that persists across command executions.
 ///
@@ -24,8 +33,14 @@ use crate::shell::ShellConfig;
 pub struct ShellState {
     /// Current working directory for command execution.
     cwd: PathBuf,
+    /// Directory before the last successful `cd`, used by `cd -`.
+    prev_cwd: Option<PathBuf>,
     /// Environment variables set during the session via export.
     env: HashMap<String, String>,
+    /// Variables loaded from the project `.env`; exports override them.
+    ///
+    /// Kept apart from `env` so they are never saved with the session.
+    dotenv: HashMap<String, String>,
 }
 
 impl ShellState {
@@ -34,7 +49,9 @@ impl ShellState {
     pub fn new(initial_cwd: PathBuf) -> Self {
         Self {
             cwd: initial_cwd,
+            prev_cwd: None,
             env: HashMap::new(),
+            dotenv: HashMap::new(),
         }
     }
 
@@ -44,15 +61,37 @@ impl ShellState {
         &self.cwd
     }
 
+    /// Returns the directory before the last successful `cd`, if any.
+    #[must_use]
+    pub fn prev_cwd(&self) -> Option<&Path> {
+        self.prev_cwd.as_deref()
+    }
+
     /// Returns the environment variables set during the session.
     #[must_use]
     pub fn env(&self) -> &HashMap<String, String> {
         &self.env
     }
 
+    /// Returns the names of variables loaded from the project `.env`.
+    #[must_use]
+    pub fn dotenv_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.dotenv.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Captures the working directory and environment for session persistence.
+    ///
+    /// Variables loaded from `.env` are deliberately left out.
+    #[must_use]
+    pub fn snapshot(&self) -> ShellSnapshot {
+        ShellSnapshot::new(self.cwd.clone(), self.env.clone())
+    }
+
     /// Processes a command and updates shell state accordingly.
     ///
-    /// Parses `cd` and `export` commands to update the tracked state.
+    /// Parses `cd`, `export` and `unset` commands to update the tracked state.
     pub fn process_command(&mut self, command: &str) {
         // Handle cd commands
         if let Some(new_dir) = Self::parse_cd(command) {
@@ -60,9 +99,14 @@ impl ShellState {
         }
 
         // Handle export commands
-        if let Some((key, value)) = Self::parse_export(command) {
+        for (key, value) in Self::parse_export(command) {
             self.env.insert(key.to_string(), value.to_string());
         }
+
+        // Handle unset commands
+        for key in Self::parse_unset(command) {
+            self.env.remove(key);
+        }
     }
 
     /// Updates the current working directory.
@@ -84,8 +128,11 @@ impl ShellState {
                 .map(|h| h.join(rest))
                 .unwrap_or_else(|| self.cwd.clone())
         } else if new_dir == "-" {
-            // cd - not supported without tracking previous dir
-            return;
+            // cd - with no previous directory leaves the cwd unchanged
+            match &self.prev_cwd {
+                Some(prev) => prev.clone(),
+                None => return,
+            }
         } else {
             self.cwd.join(new_dir)
         };
@@ -97,10 +144,10 @@ impl ShellState {
                 new_cwd = %canonical.display(),
                 "Shell state: updated cwd"
             );
-            self.cwd = canonical;
+            self.prev_cwd = Some(std::mem::replace(&mut self.cwd, canonical));
         } else if target.exists() {
             // Path exists but canonicalize failed - just use it
-            self.cwd = target;
+            self.prev_cwd = Some(std::mem::replace(&mut self.cwd, target));
         }
         // If path doesn't exist, don't change cwd
     }
@@ -126,27 +173,99 @@ impl ShellState {
         None
     }
 
-    /// Parses an `export` command and extracts the key-value pair.
-    fn parse_export(command: &str) -> Option<(&str, &str)> {
-        let trimmed = command.trim();
+    /// Parses an `export` command and extracts its key-value pairs.
+    ///
+    /// Handles several assignments in one command: `export A=1 B="two words"`.
+    fn parse_export(command: &str) -> Vec<(&str, &str)> {
+        let Some(rest) = command.trim().strip_prefix("export ") else {
+            return Vec::new();
+        };
+        let assignments = rest.split(['&', '|', ';']).next().unwrap_or_default();
+
+        Self::split_words(assignments)
+            .into_iter()
+            .filter_map(|word| {
+                let (key, value) = word.split_once('=')?;
+                let value = value.trim_matches('"').trim_matches('\'');
+                (!key.is_empty()).then_some((key, value))
+            })
+            .collect()
+    }
 
-        if let Some(rest) = trimmed.strip_prefix("export ") {
-            // Handle: export VAR=value or export VAR="value"
-            let assignment = rest.split(['&', '|', ';']).next().map(|s| s.trim())?;
-
-            if let Some(eq_pos) = assignment.find('=') {
-                let key = assignment[..eq_pos].trim();
-                let value = assignment[eq_pos + 1..]
-                    .trim()
-                    .trim_matches('"')
-                    .trim_matches('\'');
-                if !key.is_empty() {
-                    return Some((key, value));
+    /// Parses an `unset` command and extracts the variable names.
+    fn parse_unset(command: &str) -> Vec<&str> {
+        let Some(rest) = command.trim().strip_prefix("unset ") else {
+            return Vec::new();
+        };
+        let names = rest.split(['&', '|', ';']).next().unwrap_or_default();
+
+        // Skip options such as `-v`
+        names
+            .split_whitespace()
+            .filter(|name| !name.starts_with('-'))
+            .collect()
+    }
+
+    /// Parses `.env` contents into variables.
+    ///
+    /// Supports `KEY=value`, an optional `export` prefix, quoted values and
+    /// `#` comments. Lines that are not valid assignments are skipped.
+    fn parse_dotenv(contents: &str) -> HashMap<String, String> {
+        contents
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let line = line.strip_prefix("export ").unwrap_or(line);
+                let (key, value) = line.split_once('=')?;
+                let key = key.trim();
+                let valid_key = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid_key {
+                    return None;
+                }
+
+                let value = value.trim();
+                let value = match value.chars().next() {
+                    Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or_default(),
+                    _ => value.split(" #").next().unwrap_or_default().trim_end(),
+                };
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    /// Splits on whitespace that is not inside single or double quotes.
+    fn split_words(input: &str) -> Vec<&str> {
+        let mut words = Vec::new();
+        let mut quote = None;
+        let mut start = None;
+
+        for (i, c) in input.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    start.get_or_insert(i);
+                }
+                None if c.is_whitespace() => {
+                    if let Some(s) = start.take() {
+                        words.push(&input[s..i]);
+                    }
+                }
+                None => {
+                    start.get_or_insert(i);
                 }
             }
         }
+        if let Some(s) = start {
+            words.push(&input[s..]);
+        }
 
-        None
+        words
     }
 }
 
@@ -183,6 +302,8 @@ impl ShellState {
 pub struct StatefulToolExecutor {
     pub(crate) inner: ToolExecutor,
     state: RwLock<ShellState>,
+    /// Canonical starting directory; results from elsewhere are labelled with their cwd.
+    initial_cwd: PathBuf,
 }
 
 impl StatefulToolExecutor {
@@ -194,7 +315,8 @@ impl StatefulToolExecutor {
             .unwrap_or_else(|_| working_dir.clone());
         Self {
             inner: ToolExecutor::new(working_dir),
-            state: RwLock::new(ShellState::new(canonical)),
+            state: RwLock::new(ShellState::new(canonical.clone())),
+            initial_cwd: canonical,
         }
     }
 
@@ -207,6 +329,89 @@ impl StatefulToolExecutor {
         self.state.read().expect("shell state lock poisoned")
     }
 
+    /// Restores shell state saved with a session.
+    ///
+    /// The environment is always restored. The saved directory is only used if
+    /// it still exists and lies inside the working directory; otherwise the
+    /// shell stays in the working directory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn restore_shell_state(&self, snapshot: &ShellSnapshot) {
+        let mut state = self.state.write().expect("shell state lock poisoned");
+        state.env = snapshot.env().clone();
+        state.prev_cwd = None;
+
+        match snapshot.cwd().canonicalize() {
+            Ok(cwd) if cwd.is_dir() && cwd.starts_with(&self.initial_cwd) => state.cwd = cwd,
+            _ => {
+                warn!(
+                    cwd = %snapshot.cwd().display(),
+                    "Saved shell directory is missing or outside the working directory; not restoring it"
+                );
+                state.cwd = self.initial_cwd.clone();
+            }
+        }
+    }
+
+    /// Moves tool execution to another directory, such as a git worktree.
+    ///
+    /// The bash tool starts over in the new directory; exported variables
+    /// are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn set_working_dir(&mut self, working_dir: PathBuf) {
+        let canonical = working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| working_dir.clone());
+        self.inner.set_working_dir(working_dir);
+
+        let mut state = self.state.write().expect("shell state lock poisoned");
+        state.cwd = canonical.clone();
+        state.prev_cwd = None;
+        drop(state);
+        self.initial_cwd = canonical;
+    }
+
+    /// Loads the project `.env` into the environment of bash commands.
+    ///
+    /// The file must pass the same confinement, protected-path and symlink
+    /// checks as a write. Its values are never logged, persisted with the
+    /// session, or returned to the model. A missing file is not an error.
+    ///
+    /// Returns the number of variables loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is rejected by the path checks or cannot
+    /// be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn load_dotenv(&self) -> Result<usize> {
+        if std::fs::symlink_metadata(self.initial_cwd.join(DOTENV_FILE)).is_err() {
+            return Ok(0);
+        }
+
+        let path = self
+            .inner
+            .validate_project_file(DOTENV_FILE)
+            .map_err(|e| anyhow::anyhow!("{} not loaded: {}", DOTENV_FILE, e))?;
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let vars = ShellState::parse_dotenv(&contents);
+        let count = vars.len();
+
+        let mut state = self.state.write().expect("shell state lock poisoned");
+        state.dotenv = vars;
+        debug!(count, "Loaded project environment file");
+        Ok(count)
+    }
+
     /// Sets a custom execution policy for the tool executor.
     ///
     /// # Arguments
@@ -218,6 +423,13 @@ impl StatefulToolExecutor {
         self
     }
 
+    /// Sets the boundary that file paths are confined to.
+    #[must_use]
+    pub fn with_path_confinement(mut self, confinement: PathConfinement) -> Self {
+        self.inner.set_path_confinement(confinement);
+        self
+    }
+
     /// Executes a tool call with persistent shell state.
     ///
     /// For bash commands:
@@ -244,14 +456,27 @@ impl StatefulToolExecutor {
         let is_pure_cd = Self::is_pure_cd(command);
 
         // Get current shell state BEFORE processing the command
-        let (effective_cwd, env_vars) = {
+        let (effective_cwd, prev_cwd, dotenv_vars, env_vars) = {
             let state = self.state.read().expect("shell state lock poisoned");
-            (state.cwd.clone(), state.env.clone())
+            (
+                state.cwd.clone(),
+                state.prev_cwd.clone(),
+                state.dotenv.clone(),
+                state.env.clone(),
+            )
         };
 
         // For pure cd commands, update state and return success immediately
         if is_pure_cd {
             let mut state = self.state.write().expect("shell state lock poisoned");
+            if ShellState::parse_cd(command) == Some("-") {
+                // Like a shell, `cd -` prints the directory it switched to
+                if state.prev_cwd.is_none() {
+                    return Ok(ToolResult::Error("cd: OLDPWD not set".to_string()));
+                }
+                state.process_command(command);
+                return Ok(ToolResult::Success(state.cwd.display().to_string()));
+            }
             state.process_command(command);
             return Ok(ToolResult::Success(format!(
                 "Changed directory to {}",
@@ -259,41 +484,8 @@ impl StatefulToolExecutor {
             )));
         }
 
-        // Normalize command for security checks
-        let normalized = normalize_command(command);
-
-        // Check dangerous patterns
-        for pattern in &self.inner.policy.dangerous_patterns {
-            if pattern.is_match(command) || pattern.is_match(&normalized) {
-                warn!(
-                    pattern = %pattern.as_str(),
-                    command = %command,
-                    "Security violation: command blocked by dangerous pattern"
-                );
-                return Ok(ToolResult::Error(format!(
-                    "Command blocked by security policy: matches {:?}",
-                    pattern.as_str()
-                )));
-            }
-        }
-
-        // Check allowlist mode
-        if self.inner.policy.allowlist_mode {
-            let is_allowed = self
-                .inner
-                .policy
-                .allowed_commands
-                .iter()
-                .any(|pattern| pattern.is_match(command) || pattern.is_match(&normalized));
-            if !is_allowed {
-                warn!(
-                    command = %command,
-                    "Security: command blocked by allowlist policy"
-                );
-                return Ok(ToolResult::Error(
-                    "Command blocked: not in allowlist".to_string(),
-                ));
-            }
+        if let Err(reason) = self.inner.policy.check_command(command) {
+            return Ok(ToolResult::Error(reason));
         }
 
         // Execute the command with the tracked cwd and env
@@ -307,7 +499,11 @@ impl StatefulToolExecutor {
             .kill_on_drop(true);
 
         // Apply tracked environment variables
-        for (key, value) in &env_vars {
+        if let Some(prev) = &prev_cwd {
+            cmd.env("OLDPWD", prev);
+        }
+        // Session exports are applied last so they override `.env` values
+        for (key, value) in dotenv_vars.iter().chain(&env_vars) {
             cmd.env(key, value);
         }
 
@@ -352,7 +548,9 @@ impl StatefulToolExecutor {
                     } else {
                         final_output
                     };
-                    Ok(ToolResult::Success(result))
+                    Ok(ToolResult::Success(
+                        self.with_cwd_header(&effective_cwd, result),
+                    ))
                 } else {
                     let result = if truncated {
                         format!(
@@ -367,7 +565,9 @@ impl StatefulToolExecutor {
                             final_output
                         )
                     };
-                    Ok(ToolResult::Error(result))
+                    Ok(ToolResult::Error(
+                        self.with_cwd_header(&effective_cwd, result),
+                    ))
                 }
             }
             Ok(Err(e)) => {
@@ -387,6 +587,18 @@ impl StatefulToolExecutor {
         }
     }
 
+    /// Prefixes command output with the directory it ran in, like a shell prompt.
+    ///
+    /// Output from the starting directory is returned unchanged, so the header
+    /// only appears once `cd` has moved the shell elsewhere.
+    fn with_cwd_header(&self, cwd: &Path, output: String) -> String {
+        if cwd == self.initial_cwd {
+            output
+        } else {
+            format!("[cwd: {}]\n{}", cwd.display(), output)
+        }
+    }
+
     /// Checks if a command is a pure `cd` (no other operations).
     fn is_pure_cd(command: &str) -> bool {
         let trimmed = command.trim();
@@ -401,6 +613,7 @@ impl StatefulToolExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
 
     #[test]
     fn test_shell_state_new() {
@@ -409,6 +622,36 @@ mod tests {
         assert!(state.env().is_empty());
     }
 
+    #[test]
+    fn test_cd_dash_returns_to_previous_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let origin = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir(origin.join("a")).unwrap();
+        let mut state = ShellState::new(origin.clone());
+
+        state.process_command("cd a");
+        assert_eq!(state.cwd(), origin.join("a"));
+        assert_eq!(state.prev_cwd(), Some(origin.as_path()));
+
+        state.process_command("cd -");
+        assert_eq!(state.cwd(), origin);
+        assert_eq!(state.prev_cwd(), Some(origin.join("a").as_path()));
+
+        state.process_command("cd -");
+        assert_eq!(state.cwd(), origin.join("a"));
+    }
+
+    #[test]
+    fn test_cd_dash_without_previous_directory_is_noop() {
+        let temp_dir = TempDir::new().unwrap();
+        let origin = temp_dir.path().canonicalize().unwrap();
+        let mut state = ShellState::new(origin.clone());
+
+        state.process_command("cd -");
+        assert_eq!(state.cwd(), origin);
+        assert!(state.prev_cwd().is_none());
+    }
+
     #[test]
     fn test_parse_cd_simple() {
         assert_eq!(ShellState::parse_cd("cd"), Some("~"));
@@ -433,11 +676,11 @@ mod tests {
     fn test_parse_export_simple() {
         assert_eq!(
             ShellState::parse_export("export FOO=bar"),
-            Some(("FOO", "bar"))
+            vec![("FOO", "bar")]
         );
         assert_eq!(
             ShellState::parse_export("export PATH=/usr/bin"),
-            Some(("PATH", "/usr/bin"))
+            vec![("PATH", "/usr/bin")]
         );
     }
 
@@ -445,18 +688,165 @@ mod tests {
     fn test_parse_export_quoted() {
         assert_eq!(
             ShellState::parse_export("export FOO=\"bar baz\""),
-            Some(("FOO", "bar baz"))
+            vec![("FOO", "bar baz")]
         );
         assert_eq!(
             ShellState::parse_export("export FOO='bar baz'"),
-            Some(("FOO", "bar baz"))
+            vec![("FOO", "bar baz")]
+        );
+    }
+
+    #[test]
+    fn test_parse_export_multiple_assignments() {
+        assert_eq!(
+            ShellState::parse_export("export A=1 B=\"two words\" C=3 && make"),
+            vec![("A", "1"), ("B", "two words"), ("C", "3")]
         );
     }
 
     #[test]
     fn test_parse_export_not_export() {
-        assert_eq!(ShellState::parse_export("echo foo"), None);
-        assert_eq!(ShellState::parse_export("FOO=bar"), None);
+        assert!(ShellState::parse_export("echo foo").is_empty());
+        assert!(ShellState::parse_export("FOO=bar").is_empty());
+    }
+
+    #[test]
+    fn test_parse_unset() {
+        assert_eq!(ShellState::parse_unset("unset FOO"), vec!["FOO"]);
+        assert_eq!(
+            ShellState::parse_unset("unset -v A B; echo done"),
+            vec!["A", "B"]
+        );
+        assert!(ShellState::parse_unset("echo unset FOO").is_empty());
+    }
+
+    #[test]
+    fn test_process_command_exports_and_unsets() {
+        let mut state = ShellState::new(PathBuf::from("/test"));
+
+        state.process_command("export A=1 B=2");
+        assert_eq!(state.env().get("A").map(String::as_str), Some("1"));
+        assert_eq!(state.env().get("B").map(String::as_str), Some("2"));
+
+        state.process_command("unset A");
+        assert!(!state.env().contains_key("A"));
+        assert_eq!(state.env().get("B").map(String::as_str), Some("2"));
+    }
+
+    #[test]
+    fn test_restore_shell_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        let executor = StatefulToolExecutor::new(root.clone());
+
+        let env = HashMap::from([("FOO".to_string(), "bar".to_string())]);
+        executor.restore_shell_state(&ShellSnapshot::new(root.join("sub"), env));
+
+        let state = executor.shell_state();
+        assert_eq!(state.cwd(), root.join("sub"));
+        assert_eq!(state.env().get("FOO").map(String::as_str), Some("bar"));
+    }
+
+    #[test]
+    fn test_restore_shell_state_rejects_cwd_outside_working_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("project")).unwrap();
+        let executor = StatefulToolExecutor::new(root.join("project"));
+
+        executor.restore_shell_state(&ShellSnapshot::new(root.clone(), HashMap::new()));
+        assert_eq!(executor.shell_state().cwd(), root.join("project"));
+
+        executor.restore_shell_state(&ShellSnapshot::new(
+            root.join("project/missing"),
+            HashMap::new(),
+        ));
+        assert_eq!(executor.shell_state().cwd(), root.join("project"));
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let vars = ShellState::parse_dotenv(
+            "# comment\n\nexport A=1\nB = \"two # words\"\nC='x=y'\nD=plain # trailing\n1BAD=no\nnot a line\n",
+        );
+
+        assert_eq!(vars.get("A").map(String::as_str), Some("1"));
+        assert_eq!(vars.get("B").map(String::as_str), Some("two # words"));
+        assert_eq!(vars.get("C").map(String::as_str), Some("x=y"));
+        assert_eq!(vars.get("D").map(String::as_str), Some("plain"));
+        assert_eq!(vars.len(), 4);
+    }
+
+    async fn run_bash(executor: &StatefulToolExecutor, command: &str) -> String {
+        let call = ToolCall {
+            name: "bash".to_string(),
+            input: serde_json::json!({ "command": command }),
+        };
+        match executor.execute(call).await.unwrap() {
+            ToolResult::Success(output) => output,
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_dotenv_exposes_vars_to_bash_only() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(DOTENV_FILE),
+            "PATINA_DOTENV_SECRET=s3cr3t-value\nPATINA_DOTENV_MODE=dev\n",
+        )
+        .unwrap();
+        let executor = StatefulToolExecutor::new(temp_dir.path().to_path_buf());
+
+        assert_eq!(executor.load_dotenv().unwrap(), 2);
+
+        let output = run_bash(
+            &executor,
+            r#"[ "$PATINA_DOTENV_SECRET" = "s3cr3t-value" ] && echo present"#,
+        )
+        .await;
+        assert!(output.contains("present"));
+        assert!(!output.contains("s3cr3t-value"));
+
+        // Exports override `.env` values
+        run_bash(&executor, "export PATINA_DOTENV_MODE=prod").await;
+        let output = run_bash(&executor, "echo mode=$PATINA_DOTENV_MODE").await;
+        assert!(output.contains("mode=prod"));
+
+        // Never saved with the session
+        let snapshot = executor.shell_state().snapshot();
+        assert!(!snapshot.env().contains_key("PATINA_DOTENV_SECRET"));
+        assert_eq!(
+            executor.shell_state().dotenv_keys(),
+            vec!["PATINA_DOTENV_MODE", "PATINA_DOTENV_SECRET"]
+        );
+    }
+
+    #[test]
+    fn test_load_dotenv_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let executor = StatefulToolExecutor::new(temp_dir.path().to_path_buf());
+
+        assert_eq!(executor.load_dotenv().unwrap(), 0);
+        assert!(executor.shell_state().dotenv_keys().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_dotenv_rejects_symlink() {
+        let temp_dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secrets"), "TOKEN=abc\n").unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("secrets"),
+            temp_dir.path().join(DOTENV_FILE),
+        )
+        .unwrap();
+        let executor = StatefulToolExecutor::new(temp_dir.path().to_path_buf());
+
+        assert!(executor.load_dotenv().is_err());
+        assert!(executor.shell_state().dotenv_keys().is_empty());
     }
 
     #[test]
@@ -1,7 +1,8 @@
 //! Vision tool for analyzing images.
 //!
 //! This module provides image analysis capabilities by:
-//! - Loading images from file paths
+//! - Loading images from file paths, URLs or the system clipboard
+//! - Optionally downscaling large images to a maximum dimension
 //! - Encoding images as base64 for the Claude Vision API
 //! - Supporting optional analysis prompts
 //!
@@ -11,6 +12,9 @@
 //! - Path traversal attacks via .. or absolute paths
 //! - Access to files outside the working directory
 //!
+//! URLs are downloaded with the `web_fetch` client, so the same localhost,
+//! private network and redirect restrictions apply.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -24,24 +28,44 @@
 //! ```
 
 use crate::types::image::{ImageContent, ImageError, MediaType};
+use image::{imageops::FilterType, DynamicImage, ImageFormat};
+use serde_json::Value;
+use std::io::Cursor;
 use std::path::Path;
 use thiserror::Error;
 
+/// Recommended maximum long edge in pixels; larger images are resized by the API anyway.
+pub const RECOMMENDED_MAX_DIMENSION: u32 = 1568;
+
 /// Configuration for the vision tool.
 #[derive(Debug, Clone)]
 pub struct VisionConfig {
     /// Maximum file size in bytes (default: 20MB, matching Claude API limit).
     pub max_file_size: usize,
+    /// Longest edge in pixels to downscale larger images to.
+    ///
+    /// `None` (the default) sends images unchanged.
+    pub max_dimension: Option<u32>,
 }
 
 impl Default for VisionConfig {
     fn default() -> Self {
         Self {
             max_file_size: 20 * 1024 * 1024, // 20MB
+            max_dimension: None,
         }
     }
 }
 
+impl VisionConfig {
+    /// Enables downscaling of images whose longest edge exceeds `max_dimension`.
+    #[must_use]
+    pub fn with_max_dimension(mut self, max_dimension: u32) -> Self {
+        self.max_dimension = Some(max_dimension);
+        self
+    }
+}
+
 /// Result of a vision analysis operation.
 #[derive(Debug, Clone)]
 pub struct VisionResult {
@@ -51,6 +75,10 @@ pub struct VisionResult {
     pub media_type: MediaType,
     /// Optional analysis prompt provided by the user.
     pub prompt: Option<String>,
+    /// Width and height of the image as loaded, if they could be read.
+    pub original_dimensions: Option<(u32, u32)>,
+    /// Width and height after downscaling; `None` if the image was sent unchanged.
+    pub resized_dimensions: Option<(u32, u32)>,
 }
 
 /// Errors that can occur during vision operations.
@@ -67,6 +95,75 @@ pub enum VisionError {
     /// Path validation failed.
     #[error("invalid path: {0}")]
     InvalidPath(String),
+
+    /// The `source` input is missing or malformed.
+    #[error("invalid image source: {0}")]
+    InvalidSource(String),
+
+    /// Downloading an image URL failed.
+    #[error("failed to fetch image: {0}")]
+    Fetch(String),
+
+    /// The clipboard holds no image or cannot be read.
+    #[error("failed to read clipboard image: {0}")]
+    Clipboard(String),
+
+    /// Decoding, resizing or re-encoding an image failed.
+    #[error("failed to downscale image: {0}")]
+    Resize(String),
+}
+
+/// Where `analyze_image` loads an image from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageInput {
+    /// A file relative to the working directory.
+    Path(String),
+    /// An `http` or `https` URL.
+    Url(String),
+    /// The image currently on the system clipboard.
+    Clipboard,
+}
+
+impl ImageInput {
+    /// Reads the image source from `analyze_image` tool input.
+    ///
+    /// Accepts `"source": {"path": ...}`, `{"url": ...}` or
+    /// `{"clipboard": true}`, and a top-level `"path"` for compatibility.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no source is given, or the source does not name
+    /// exactly one of `path`, `url` or `clipboard`.
+    pub fn from_tool_input(input: &Value) -> Result<Self, VisionError> {
+        let Some(source) = input.get("source") else {
+            return input
+                .get("path")
+                .and_then(Value::as_str)
+                .map(|path| Self::Path(path.to_string()))
+                .ok_or_else(|| VisionError::InvalidSource("missing source or path".to_string()));
+        };
+
+        let Some(source) = source.as_object() else {
+            return Err(VisionError::InvalidSource(
+                "source must be an object".to_string(),
+            ));
+        };
+        if source.len() != 1 {
+            return Err(VisionError::InvalidSource(
+                "source must have exactly one of path, url or clipboard".to_string(),
+            ));
+        }
+
+        match source.iter().next() {
+            Some((key, Value::String(path))) if key == "path" => Ok(Self::Path(path.clone())),
+            Some((key, Value::String(url))) if key == "url" => Ok(Self::Url(url.clone())),
+            Some((key, Value::Bool(true))) if key == "clipboard" => Ok(Self::Clipboard),
+            Some((key, _)) => Err(VisionError::InvalidSource(format!(
+                "unexpected value for '{key}'; use {{\"path\": \"...\"}}, {{\"url\": \"...\"}} or {{\"clipboard\": true}}"
+            ))),
+            None => Err(VisionError::InvalidSource("source is empty".to_string())),
+        }
+    }
 }
 
 /// Tool for analyzing images using the Claude Vision API.
@@ -108,17 +205,124 @@ impl VisionTool {
             }
         }
 
-        // Load image using existing infrastructure
-        let image = ImageContent::from_file(path)?;
+        let bytes = std::fs::read(path).map_err(ImageError::from)?;
+        self.analyze_bytes(&bytes, prompt)
+    }
+
+    /// Analyzes raw image data, e.g. downloaded from a URL or read from the clipboard.
+    ///
+    /// The format is detected from magic bytes. With
+    /// [`VisionConfig::max_dimension`] set, larger images are downscaled and
+    /// re-encoded in their original format; animated GIFs keep only their
+    /// first frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data exceeds the size limit, is not a PNG,
+    /// JPEG, GIF or WebP image, or cannot be downscaled.
+    pub fn analyze_bytes(
+        &self,
+        bytes: &[u8],
+        prompt: Option<&str>,
+    ) -> Result<VisionResult, VisionError> {
+        if bytes.len() > self.config.max_file_size {
+            return Err(VisionError::ImageLoad(ImageError::FileTooLarge));
+        }
+
+        let media_type = MediaType::from_magic_bytes(bytes).ok_or_else(|| {
+            VisionError::UnsupportedFormat("expected PNG, JPEG, GIF or WebP".to_string())
+        })?;
+        let format = image_format(media_type);
+
+        let original_dimensions = image::ImageReader::with_format(Cursor::new(bytes), format)
+            .into_dimensions()
+            .ok();
+
+        let resized = match (self.config.max_dimension, original_dimensions) {
+            (Some(max), Some((width, height))) if width.max(height) > max => {
+                Some(downscale(bytes, format, max)?)
+            }
+            _ => None,
+        };
+
+        let (image, resized_dimensions) = match resized {
+            Some((data, dimensions)) => (
+                ImageContent::from_bytes(&data, media_type)?,
+                Some(dimensions),
+            ),
+            None => (ImageContent::from_bytes(bytes, media_type)?, None),
+        };
 
         Ok(VisionResult {
-            media_type: image.media_type,
+            media_type,
             image,
             prompt: prompt.map(String::from),
+            original_dimensions,
+            resized_dimensions,
         })
     }
 }
 
+/// Returns the `image` crate format for a media type.
+fn image_format(media_type: MediaType) -> ImageFormat {
+    match media_type {
+        MediaType::Png => ImageFormat::Png,
+        MediaType::Jpeg => ImageFormat::Jpeg,
+        MediaType::Gif => ImageFormat::Gif,
+        MediaType::Webp => ImageFormat::WebP,
+    }
+}
+
+/// Resizes an image so its longest edge is `max_dimension`, keeping the
+/// aspect ratio, and re-encodes it in `format`.
+///
+/// Returns the encoded image and its new dimensions.
+fn downscale(
+    bytes: &[u8],
+    format: ImageFormat,
+    max_dimension: u32,
+) -> Result<(Vec<u8>, (u32, u32)), VisionError> {
+    let decoded = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| VisionError::Resize(e.to_string()))?;
+    let resized = decoded.resize(max_dimension, max_dimension, FilterType::Lanczos3);
+
+    // JPEG has no alpha channel and the WebP encoder only takes 8-bit RGB(A)
+    let resized = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(resized.to_rgb8()),
+        ImageFormat::WebP => DynamicImage::ImageRgba8(resized.to_rgba8()),
+        _ => resized,
+    };
+
+    let mut encoded = Cursor::new(Vec::new());
+    resized
+        .write_to(&mut encoded, format)
+        .map_err(|e| VisionError::Resize(e.to_string()))?;
+    Ok((encoded.into_inner(), (resized.width(), resized.height())))
+}
+
+/// Reads the image on the system clipboard, encoded as PNG.
+///
+/// # Errors
+///
+/// Returns an error if the clipboard is unavailable (e.g. no display),
+/// holds no image, or the image cannot be encoded.
+pub fn read_clipboard_image() -> Result<Vec<u8>, VisionError> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| VisionError::Clipboard(e.to_string()))?;
+    let data = clipboard
+        .get_image()
+        .map_err(|e| VisionError::Clipboard(e.to_string()))?;
+
+    let (width, height) = (data.width as u32, data.height as u32);
+    let rgba = image::RgbaImage::from_raw(width, height, data.bytes.into_owned())
+        .ok_or_else(|| VisionError::Clipboard("image data has the wrong size".to_string()))?;
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    rgba.write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| VisionError::Clipboard(e.to_string()))?;
+    Ok(png.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,7 +350,14 @@ mod tests {
         let properties = &tool.input_schema["properties"];
         assert!(properties.is_object());
 
-        // Should have 'path' property (required)
+        // Should have 'source' property (path, url or clipboard)
+        assert_eq!(properties["source"]["type"], "object");
+        let source = &properties["source"]["properties"];
+        assert_eq!(source["path"]["type"], "string");
+        assert_eq!(source["url"]["type"], "string");
+        assert_eq!(source["clipboard"]["type"], "boolean");
+
+        // Should keep the top-level 'path' property for compatibility
         assert!(properties["path"].is_object());
         assert_eq!(properties["path"]["type"], "string");
 
@@ -154,12 +365,156 @@ mod tests {
         assert!(properties["prompt"].is_object());
         assert_eq!(properties["prompt"]["type"], "string");
 
-        // 'path' should be required
-        let required = tool.input_schema["required"]
-            .as_array()
-            .expect("required should be an array");
-        assert!(required.contains(&json!("path")));
-        assert!(!required.contains(&json!("prompt")));
+        // Either 'source' or 'path' may be given, so neither is required
+        assert_eq!(tool.input_schema["required"], json!([]));
+    }
+
+    #[test]
+    fn test_image_input_from_tool_input() {
+        assert_eq!(
+            ImageInput::from_tool_input(&json!({"path": "a.png"})).unwrap(),
+            ImageInput::Path("a.png".to_string())
+        );
+        assert_eq!(
+            ImageInput::from_tool_input(&json!({"source": {"path": "b.png"}})).unwrap(),
+            ImageInput::Path("b.png".to_string())
+        );
+        assert_eq!(
+            ImageInput::from_tool_input(&json!({"source": {"url": "https://example.com/c.png"}}))
+                .unwrap(),
+            ImageInput::Url("https://example.com/c.png".to_string())
+        );
+        assert_eq!(
+            ImageInput::from_tool_input(&json!({"source": {"clipboard": true}})).unwrap(),
+            ImageInput::Clipboard
+        );
+    }
+
+    #[test]
+    fn test_image_input_rejects_invalid_sources() {
+        for input in [
+            json!({}),
+            json!({"source": "a.png"}),
+            json!({"source": {}}),
+            json!({"source": {"clipboard": false}}),
+            json!({"source": {"path": "a.png", "url": "https://example.com/a.png"}}),
+            json!({"source": {"file": "a.png"}}),
+        ] {
+            assert!(
+                matches!(
+                    ImageInput::from_tool_input(&input),
+                    Err(VisionError::InvalidSource(_))
+                ),
+                "should reject {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_bytes_detects_media_type() {
+        let tool = VisionTool::new(VisionConfig::default());
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+
+        let result = tool.analyze_bytes(&jpeg, Some("What is this?")).unwrap();
+        assert_eq!(result.media_type, MediaType::Jpeg);
+        assert_eq!(result.image.media_type, MediaType::Jpeg);
+        assert_eq!(result.prompt.as_deref(), Some("What is this?"));
+    }
+
+    #[test]
+    fn test_analyze_bytes_rejects_unsupported_format() {
+        let tool = VisionTool::new(VisionConfig::default());
+        let bmp = b"BM\x36\x00\x00\x00\x00\x00";
+
+        let err = tool.analyze_bytes(bmp, None).unwrap_err();
+        assert!(matches!(err, VisionError::UnsupportedFormat(_)));
+        assert!(err.to_string().contains("PNG, JPEG, GIF or WebP"));
+    }
+
+    #[test]
+    fn test_analyze_bytes_enforces_size_limit() {
+        let tool = VisionTool::new(VisionConfig {
+            max_file_size: 4,
+            ..Default::default()
+        });
+        let png = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+        assert!(matches!(
+            tool.analyze_bytes(&png, None),
+            Err(VisionError::ImageLoad(ImageError::FileTooLarge))
+        ));
+    }
+
+    /// Encodes a solid-colour test image of the given size.
+    fn encoded_image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            width,
+            height,
+            image::Rgb([200, 40, 40]),
+        ));
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    /// Decodes the base64 image data in a result.
+    fn result_bytes(result: &VisionResult) -> Vec<u8> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        match &result.image.source {
+            crate::types::image::ImageSource::Base64 { data, .. } => STANDARD.decode(data).unwrap(),
+            other => panic!("expected base64 image, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_downscales_large_png_keeping_aspect_ratio() {
+        let tool =
+            VisionTool::new(VisionConfig::default().with_max_dimension(RECOMMENDED_MAX_DIMENSION));
+        let png = encoded_image(3136, 1000, ImageFormat::Png);
+
+        let result = tool.analyze_bytes(&png, None).unwrap();
+
+        assert_eq!(result.original_dimensions, Some((3136, 1000)));
+        assert_eq!(result.resized_dimensions, Some((1568, 500)));
+        assert_eq!(result.media_type, MediaType::Png);
+
+        let data = result_bytes(&result);
+        assert_eq!(MediaType::from_magic_bytes(&data), Some(MediaType::Png));
+        let decoded = image::load_from_memory(&data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (1568, 500));
+    }
+
+    #[test]
+    fn test_downscaled_jpeg_stays_jpeg() {
+        let tool = VisionTool::new(VisionConfig::default().with_max_dimension(100));
+        let jpeg = encoded_image(200, 400, ImageFormat::Jpeg);
+
+        let result = tool.analyze_bytes(&jpeg, None).unwrap();
+
+        assert_eq!(result.resized_dimensions, Some((50, 100)));
+        assert_eq!(result.image.media_type, MediaType::Jpeg);
+        assert_eq!(
+            MediaType::from_magic_bytes(&result_bytes(&result)),
+            Some(MediaType::Jpeg)
+        );
+    }
+
+    #[test]
+    fn test_small_or_unconfigured_images_are_unchanged() {
+        let png = encoded_image(300, 200, ImageFormat::Png);
+
+        // Within the limit
+        let tool = VisionTool::new(VisionConfig::default().with_max_dimension(1568));
+        let result = tool.analyze_bytes(&png, None).unwrap();
+        assert_eq!(result.original_dimensions, Some((300, 200)));
+        assert_eq!(result.resized_dimensions, None);
+        assert_eq!(result_bytes(&result), png);
+
+        // Downscaling is opt-in
+        let tool = VisionTool::new(VisionConfig::default());
+        let result = tool.analyze_bytes(&png, None).unwrap();
+        assert_eq!(result.resized_dimensions, None);
+        assert_eq!(result_bytes(&result), png);
     }
 
     /// Test that VisionTool can be created with default config.
@@ -5,13 +5,15 @@
 //! - URL validation (reject file://, localhost, private IPs)
 //! - Content length limits
 //! - Timeout handling
-//! - Redirect limiting
+//! - Redirect limiting, with every redirect target re-validated
+//! - An allowlist for internal hosts that would otherwise be blocked
+//! - An in-memory LRU cache of successful responses, with a TTL
 //!
 //! # Security
 //!
 //! The tool validates URLs to prevent:
 //! - Local file access via file:// URLs
-//! - SSRF attacks via localhost/private IP URLs
+//! - SSRF attacks via localhost/private IP URLs, including via redirects
 //! - Memory exhaustion via content length limits
 //!
 //! # Examples
@@ -30,8 +32,10 @@
 use anyhow::{bail, Result};
 use reqwest::redirect::Policy;
 use reqwest::Client;
+use std::collections::HashMap;
 use std::net::IpAddr;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
 /// Configuration for the web fetch tool.
@@ -41,12 +45,23 @@ pub struct WebFetchConfig {
     pub timeout: Duration,
     /// Maximum content length to fetch (in bytes).
     pub max_content_length: usize,
-    /// Maximum number of redirects to follow.
-    pub max_redirects: u8,
+    /// Maximum number of redirects to follow; more than this is an error.
+    pub max_redirects: usize,
     /// Allow localhost URLs (for testing only).
     ///
     /// This should NEVER be enabled in production as it enables SSRF attacks.
     pub allow_localhost: bool,
+    /// Hosts that may be fetched even if they are localhost or private IPs.
+    ///
+    /// Entries match a host exactly (case-insensitive); a leading `*.` matches
+    /// any subdomain. Intended for internal documentation hosts.
+    pub allowed_hosts: Vec<String>,
+    /// How long a fetched page is served from the cache.
+    pub cache_ttl: Duration,
+    /// Maximum number of cached pages; the least recently used is evicted.
+    ///
+    /// Zero disables caching.
+    pub cache_max_entries: usize,
 }
 
 impl Default for WebFetchConfig {
@@ -56,6 +71,9 @@ impl Default for WebFetchConfig {
             max_content_length: 1_000_000, // 1MB
             max_redirects: 5,
             allow_localhost: false,
+            allowed_hosts: Vec::new(),
+            cache_ttl: Duration::from_secs(15 * 60),
+            cache_max_entries: 32,
         }
     }
 }
@@ -74,6 +92,76 @@ impl WebFetchConfig {
             ..Default::default()
         }
     }
+
+    /// Sets the hosts allowed even if they are localhost or private IPs.
+    #[must_use]
+    pub fn with_allowed_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.allowed_hosts = hosts;
+        self
+    }
+
+    /// Sets how long fetched pages are cached and how many are kept.
+    #[must_use]
+    pub fn with_cache(mut self, ttl: Duration, max_entries: usize) -> Self {
+        self.cache_ttl = ttl;
+        self.cache_max_entries = max_entries;
+        self
+    }
+
+    /// Sets the maximum number of redirects to follow.
+    #[must_use]
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Returns true if the host is on the allowlist.
+    #[must_use]
+    pub fn is_host_allowed(&self, host: &str) -> bool {
+        let host = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase();
+        self.allowed_hosts.iter().any(|entry| {
+            let entry = entry
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_ascii_lowercase();
+            match entry.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+                None => host == entry,
+            }
+        })
+    }
+
+    /// Checks a URL against the scheme and host restrictions.
+    fn check_url(&self, url: &reqwest::Url) -> Result<()> {
+        // Check scheme - only allow http and https
+        match url.scheme() {
+            "http" | "https" => {}
+            "file" => bail!("file:// URLs are not allowed for security reasons"),
+            scheme => bail!("URL scheme '{}' is not allowed", scheme),
+        }
+
+        // Check for localhost and private IPs unless the host is allowlisted
+        if let Some(host) = url.host_str() {
+            if self.allow_localhost || self.is_host_allowed(host) {
+                return Ok(());
+            }
+
+            if WebFetchTool::is_localhost(host) {
+                bail!("Localhost URLs are not allowed for security reasons");
+            }
+
+            if WebFetchTool::is_private_ip(host) {
+                bail!("Private IP addresses are not allowed for security reasons");
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Result of a web fetch operation.
@@ -85,12 +173,74 @@ pub struct WebFetchResult {
     pub content_type: String,
     /// HTTP status code.
     pub status: u16,
+    /// Whether the result was served from the cache.
+    pub cached: bool,
+}
+
+/// A cached response and its bookkeeping.
+#[derive(Debug)]
+struct CacheEntry {
+    result: WebFetchResult,
+    stored_at: Instant,
+    last_used: u64,
+}
+
+/// In-memory LRU cache of fetched pages, keyed by URL.
+#[derive(Debug, Default)]
+struct ResponseCache {
+    entries: HashMap<String, CacheEntry>,
+    /// Monotonic counter recording use order.
+    clock: u64,
+}
+
+impl ResponseCache {
+    /// Returns a fresh cached result, dropping it if it has expired.
+    fn get(&mut self, url: &str, ttl: Duration, now: Instant) -> Option<WebFetchResult> {
+        let expired = now.duration_since(self.entries.get(url)?.stored_at) >= ttl;
+        if expired {
+            self.entries.remove(url);
+            return None;
+        }
+
+        self.clock += 1;
+        let entry = self.entries.get_mut(url)?;
+        entry.last_used = self.clock;
+        Some(entry.result.clone())
+    }
+
+    /// Stores a result, evicting the least recently used entry when full.
+    fn insert(&mut self, url: &str, result: WebFetchResult, max_entries: usize, now: Instant) {
+        if max_entries == 0 {
+            return;
+        }
+        if !self.entries.contains_key(url) && self.entries.len() >= max_entries {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(
+            url.to_string(),
+            CacheEntry {
+                result,
+                stored_at: now,
+                last_used: self.clock,
+            },
+        );
+    }
 }
 
 /// Tool for fetching web content.
 pub struct WebFetchTool {
     config: WebFetchConfig,
     client: Client,
+    cache: Mutex<ResponseCache>,
 }
 
 impl WebFetchTool {
@@ -101,14 +251,31 @@ impl WebFetchTool {
     /// Panics if the HTTP client cannot be built (should not happen with default settings).
     #[must_use]
     pub fn new(config: WebFetchConfig) -> Self {
+        // Redirect targets go through the same checks as the original URL
+        let redirect_config = config.clone();
+        let redirect_policy = Policy::custom(move |attempt| {
+            if attempt.previous().len() > redirect_config.max_redirects {
+                let limit = redirect_config.max_redirects;
+                attempt.error(format!("Too many redirects (limit {})", limit))
+            } else if let Err(e) = redirect_config.check_url(attempt.url()) {
+                attempt.error(e.to_string())
+            } else {
+                attempt.follow()
+            }
+        });
+
         let client = Client::builder()
             .timeout(config.timeout)
-            .redirect(Policy::limited(config.max_redirects as usize))
+            .redirect(redirect_policy)
             .user_agent("Patina/0.3.0")
             .build()
             .expect("Failed to build HTTP client");
 
-        Self { config, client }
+        Self {
+            config,
+            client,
+            cache: Mutex::new(ResponseCache::default()),
+        }
     }
 
     /// Fetches content from the given URL.
@@ -118,18 +285,23 @@ impl WebFetchTool {
     /// Returns an error if:
     /// - The URL is invalid
     /// - The URL uses a disallowed scheme (file://)
-    /// - The URL points to localhost or private IP ranges
+    /// - The URL points to localhost or private IP ranges not on the allowlist
     /// - The request times out
     /// - The content exceeds the maximum length
-    /// - Too many redirects are encountered
+    /// - Too many redirects are encountered, or a redirect target is not allowed
     pub async fn fetch(&self, url: &str) -> Result<WebFetchResult> {
         // Parse and validate the URL
         let parsed_url = self.validate_url(url)?;
 
+        if let Some(result) = self.cached(parsed_url.as_str()) {
+            debug!(url = %parsed_url, "Serving web content from cache");
+            return Ok(result);
+        }
+
         debug!(url = %parsed_url, "Fetching web content");
 
         // Make the request
-        let response = self.client.get(parsed_url.as_str()).send().await?;
+        let response = self.send(&parsed_url).await?;
 
         let status = response.status().as_u16();
         let content_type = response
@@ -178,39 +350,96 @@ impl WebFetchTool {
             .trim()
             .to_string();
 
-        Ok(WebFetchResult {
+        let result = WebFetchResult {
             content,
             content_type: base_content_type,
             status,
-        })
+            cached: false,
+        };
+
+        // Only successful responses are worth serving again
+        if (200..300).contains(&status) {
+            self.cache
+                .lock()
+                .expect("web fetch cache lock poisoned")
+                .insert(
+                    parsed_url.as_str(),
+                    result.clone(),
+                    self.config.cache_max_entries,
+                    Instant::now(),
+                );
+        }
+
+        Ok(result)
     }
 
-    /// Validates a URL for security requirements.
+    /// Downloads the raw body of a URL, such as an image.
     ///
-    /// Returns the validated URL or an error if the URL is not allowed.
-    fn validate_url(&self, url: &str) -> Result<reqwest::Url> {
-        // Parse the URL
-        let parsed = reqwest::Url::parse(url)?;
+    /// The URL and any redirects go through the same checks as [`fetch`](Self::fetch).
+    /// Responses are not cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is not allowed, the request fails or
+    /// returns a non-success status, or the body exceeds `max_bytes`.
+    pub async fn fetch_bytes(&self, url: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        let parsed_url = self.validate_url(url)?;
+        debug!(url = %parsed_url, "Downloading web content");
 
-        // Check scheme - only allow http and https
-        match parsed.scheme() {
-            "http" | "https" => {}
-            "file" => bail!("file:// URLs are not allowed for security reasons"),
-            scheme => bail!("URL scheme '{}' is not allowed", scheme),
+        let response = self.send(&parsed_url).await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Request failed with status {}", status);
         }
 
-        // Check for localhost and private IPs
-        if let Some(host) = parsed.host_str() {
-            if Self::is_localhost(host) && !self.config.allow_localhost {
-                bail!("Localhost URLs are not allowed for security reasons");
+        if let Some(content_length) = response.content_length() {
+            if content_length as usize > max_bytes {
+                bail!(
+                    "Content too large: {} bytes exceeds {} byte limit",
+                    content_length,
+                    max_bytes
+                );
             }
+        }
 
-            // Check for private IP addresses (always blocked, no bypass)
-            if Self::is_private_ip(host) && !self.config.allow_localhost {
-                bail!("Private IP addresses are not allowed for security reasons");
+        let bytes = response.bytes().await?;
+        if bytes.len() > max_bytes {
+            bail!(
+                "Content too large: {} bytes exceeds {} byte limit",
+                bytes.len(),
+                max_bytes
+            );
+        }
+        Ok(bytes.to_vec())
+    }
+
+    /// Sends a GET request, reporting rejected redirects clearly.
+    async fn send(&self, url: &reqwest::Url) -> Result<reqwest::Response> {
+        match self.client.get(url.as_str()).send().await {
+            Ok(response) => Ok(response),
+            Err(e) if e.is_redirect() => {
+                let reason = std::error::Error::source(&e)
+                    .map_or_else(|| e.to_string(), ToString::to_string);
+                bail!("Redirect rejected: {}", reason);
             }
+            Err(e) => Err(e.into()),
         }
+    }
+
+    /// Returns the cached result for a URL, if one is still fresh.
+    fn cached(&self, url: &str) -> Option<WebFetchResult> {
+        let mut cache = self.cache.lock().expect("web fetch cache lock poisoned");
+        let mut result = cache.get(url, self.config.cache_ttl, Instant::now())?;
+        result.cached = true;
+        Some(result)
+    }
 
+    /// Validates a URL for security requirements.
+    ///
+    /// Returns the validated URL or an error if the URL is not allowed.
+    fn validate_url(&self, url: &str) -> Result<reqwest::Url> {
+        let parsed = reqwest::Url::parse(url)?;
+        self.config.check_url(&parsed)?;
         Ok(parsed)
     }
 
@@ -333,4 +562,93 @@ mod tests {
         // html2text should preserve link text
         assert!(markdown.contains("Example"));
     }
+
+    #[test]
+    fn test_is_host_allowed() {
+        let config = WebFetchConfig::default().with_allowed_hosts(vec![
+            "wiki.corp.example".to_string(),
+            "*.docs.internal".to_string(),
+            "10.0.0.5".to_string(),
+        ]);
+
+        assert!(config.is_host_allowed("wiki.corp.example"));
+        assert!(config.is_host_allowed("WIKI.corp.example"));
+        assert!(config.is_host_allowed("api.docs.internal"));
+        assert!(!config.is_host_allowed("docs.internal"));
+        assert!(!config.is_host_allowed("evildocs.internal"));
+        assert!(config.is_host_allowed("10.0.0.5"));
+        assert!(!config.is_host_allowed("10.0.0.6"));
+    }
+
+    #[test]
+    fn test_validate_url_permits_allowlisted_private_hosts() {
+        let tool = WebFetchTool::new(
+            WebFetchConfig::default()
+                .with_allowed_hosts(vec!["10.0.0.5".to_string(), "localhost".to_string()]),
+        );
+
+        assert!(tool.validate_url("http://10.0.0.5/wiki").is_ok());
+        assert!(tool.validate_url("http://localhost:8080/").is_ok());
+        // Other private addresses stay blocked
+        assert!(tool.validate_url("http://10.0.0.6/").is_err());
+        assert!(tool.validate_url("http://127.0.0.1/").is_err());
+        assert!(tool.validate_url("file:///etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_validate_url_default_blocks_private_hosts() {
+        let tool = WebFetchTool::new(WebFetchConfig::default());
+        assert!(tool.validate_url("http://192.168.1.1/").is_err());
+        assert!(tool.validate_url("http://localhost/").is_err());
+        assert!(tool.validate_url("https://example.com/").is_ok());
+    }
+
+    fn page(content: &str) -> WebFetchResult {
+        WebFetchResult {
+            content: content.to_string(),
+            content_type: "text/plain".to_string(),
+            status: 200,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_response_cache_expires_after_ttl() {
+        let mut cache = ResponseCache::default();
+        let start = Instant::now();
+        let ttl = Duration::from_secs(60);
+        cache.insert("https://a.example/", page("a"), 4, start);
+
+        let hit = cache.get("https://a.example/", ttl, start + Duration::from_secs(59));
+        assert_eq!(hit.map(|r| r.content), Some("a".to_string()));
+        assert!(cache
+            .get("https://a.example/", ttl, start + Duration::from_secs(60))
+            .is_none());
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_response_cache_evicts_least_recently_used() {
+        let mut cache = ResponseCache::default();
+        let now = Instant::now();
+        let ttl = Duration::from_secs(60);
+        cache.insert("a", page("a"), 2, now);
+        cache.insert("b", page("b"), 2, now);
+
+        // Touch "a" so "b" becomes the least recently used
+        assert!(cache.get("a", ttl, now).is_some());
+        cache.insert("c", page("c"), 2, now);
+
+        assert!(cache.get("a", ttl, now).is_some());
+        assert!(cache.get("b", ttl, now).is_none());
+        assert!(cache.get("c", ttl, now).is_some());
+    }
+
+    #[test]
+    fn test_response_cache_disabled_with_zero_entries() {
+        let mut cache = ResponseCache::default();
+        let now = Instant::now();
+        cache.insert("a", page("a"), 0, now);
+        assert!(cache.get("a", Duration::from_secs(60), now).is_none());
+    }
 }
@@ -1,7 +1,9 @@
 //! Web search tool for querying search engines and returning results.
 //!
 //! This module provides web search capabilities with:
-//! - DuckDuckGo HTML API integration
+//! - A pluggable [`SearchBackend`] trait
+//! - DuckDuckGo HTML API integration (the default backend)
+//! - SearXNG JSON API integration for self-hosted instances
 //! - Result parsing and formatting
 //! - Timeout handling
 //! - Rate limiting awareness
@@ -31,12 +33,28 @@
 use anyhow::{bail, Result};
 use reqwest::Client;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
 use std::time::Duration;
 use tracing::debug;
 
+/// Search provider used by [`WebSearchTool::new`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProvider {
+    /// DuckDuckGo's HTML endpoint.
+    #[default]
+    DuckDuckGo,
+    /// A SearXNG instance at `base_url`.
+    Searxng,
+}
+
 /// Configuration for the web search tool.
 #[derive(Debug, Clone)]
 pub struct WebSearchConfig {
+    /// Search provider to query.
+    pub provider: SearchProvider,
     /// Request timeout duration.
     pub timeout: Duration,
     /// Maximum number of results to return.
@@ -52,6 +70,7 @@ pub struct WebSearchConfig {
 impl Default for WebSearchConfig {
     fn default() -> Self {
         Self {
+            provider: SearchProvider::DuckDuckGo,
             timeout: Duration::from_secs(30),
             max_results: 10,
             base_url: "https://html.duckduckgo.com/html".to_string(),
@@ -87,27 +106,274 @@ pub struct SearchResult {
     pub snippet: String,
 }
 
+/// A search provider queried by [`WebSearchTool`].
+///
+/// Implemented by [`DuckDuckGoBackend`] (the default) and [`SearxngBackend`].
+/// Install another provider with [`WebSearchTool::with_backend`].
+///
+/// # Examples
+///
+/// ```
+/// use patina::tools::web_search::{SearchBackend, SearchResult};
+/// use std::future::Future;
+/// use std::pin::Pin;
+///
+/// struct FixedBackend;
+///
+/// impl SearchBackend for FixedBackend {
+///     fn search<'a>(
+///         &'a self,
+///         query: &'a str,
+///         _max_results: usize,
+///     ) -> Pin<Box<dyn Future<Output = anyhow::Result<Vec<SearchResult>>> + Send + 'a>> {
+///         Box::pin(async move {
+///             Ok(vec![SearchResult {
+///                 title: query.to_string(),
+///                 url: "https://example.com".to_string(),
+///                 snippet: String::new(),
+///             }])
+///         })
+///     }
+/// }
+/// ```
+pub trait SearchBackend: Send + Sync {
+    /// Returns up to `max_results` results for a non-empty, trimmed query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider cannot be reached or its response
+    /// cannot be parsed.
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        max_results: usize,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<SearchResult>>> + Send + 'a>>;
+}
+
+/// Builds the HTTP client shared by the built-in backends.
+fn build_client(timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .user_agent("Patina/0.3.0")
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// Searches DuckDuckGo's HTML endpoint.
+pub struct DuckDuckGoBackend {
+    base_url: String,
+    client: Client,
+}
+
+impl DuckDuckGoBackend {
+    /// Creates a DuckDuckGo backend using `config.base_url`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built (should not happen with default settings).
+    #[must_use]
+    pub fn new(config: &WebSearchConfig) -> Self {
+        Self {
+            base_url: config.base_url.clone(),
+            client: build_client(config.timeout),
+        }
+    }
+
+    async fn query(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>> {
+        // Build the request URL
+        let url = format!("{}?q={}", self.base_url, urlencoding::encode(query));
+
+        // Make the request
+        let response = self.client.get(&url).send().await?;
+
+        // Check for HTTP errors
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Search request failed with status {}", status);
+        }
+
+        // Parse the response
+        let html_content = response.text().await?;
+        Ok(parse_duckduckgo_results(&html_content, max_results))
+    }
+}
+
+impl SearchBackend for DuckDuckGoBackend {
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        max_results: usize,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<SearchResult>>> + Send + 'a>> {
+        Box::pin(self.query(query, max_results))
+    }
+}
+
+/// Searches a SearXNG instance through its JSON API.
+///
+/// The instance must have the `json` output format enabled.
+pub struct SearxngBackend {
+    base_url: String,
+    client: Client,
+}
+
+/// The subset of a SearXNG JSON response that is used.
+#[derive(Debug, Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearxngResult {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+impl SearxngBackend {
+    /// Creates a SearXNG backend for the instance at `config.base_url`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built (should not happen with default settings).
+    #[must_use]
+    pub fn new(config: &WebSearchConfig) -> Self {
+        Self {
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            client: build_client(config.timeout),
+        }
+    }
+
+    async fn query(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>> {
+        let url = format!(
+            "{}/search?q={}&format=json",
+            self.base_url,
+            urlencoding::encode(query)
+        );
+
+        let response = self.client.get(&url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Search request failed with status {}", status);
+        }
+
+        let body: SearxngResponse = response.json().await?;
+        Ok(body
+            .results
+            .into_iter()
+            .filter(|r| !r.title.is_empty() && !r.url.is_empty())
+            .take(max_results)
+            .map(|r| SearchResult {
+                title: r.title,
+                url: r.url,
+                snippet: r.content,
+            })
+            .collect())
+    }
+}
+
+impl SearchBackend for SearxngBackend {
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        max_results: usize,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<SearchResult>>> + Send + 'a>> {
+        Box::pin(self.query(query, max_results))
+    }
+}
+
+/// Parses DuckDuckGo HTML search results.
+///
+/// DuckDuckGo's HTML API returns results in a structured format that we parse
+/// using CSS selectors.
+fn parse_duckduckgo_results(html: &str, max_results: usize) -> Vec<SearchResult> {
+    let document = Html::parse_document(html);
+
+    // DuckDuckGo HTML selectors
+    let result_selector = Selector::parse(".result, .results_links, div.web-result")
+        .unwrap_or_else(|_| {
+            // Fallback to a simple div selector if the specific one fails
+            Selector::parse("div").expect("basic selector should work")
+        });
+
+    let link_selector = Selector::parse("a.result__a, a.result-link, h2 a")
+        .unwrap_or_else(|_| Selector::parse("a").expect("basic a selector should work"));
+
+    let snippet_selector = Selector::parse(".result__snippet, .result-snippet, .snippet")
+        .unwrap_or_else(|_| Selector::parse("p").expect("basic p selector should work"));
+
+    let mut results = Vec::new();
+
+    for element in document.select(&result_selector) {
+        if results.len() >= max_results {
+            break;
+        }
+
+        // Extract link and title
+        if let Some(link_elem) = element.select(&link_selector).next() {
+            let title = link_elem.text().collect::<String>().trim().to_string();
+            let url = link_elem
+                .value()
+                .attr("href")
+                .unwrap_or_default()
+                .to_string();
+
+            // Skip empty results
+            if title.is_empty() || url.is_empty() {
+                continue;
+            }
+
+            // Extract snippet
+            let snippet = element
+                .select(&snippet_selector)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            results.push(SearchResult {
+                title,
+                url,
+                snippet,
+            });
+        }
+    }
+
+    results
+}
+
 /// Tool for searching the web.
 pub struct WebSearchTool {
     config: WebSearchConfig,
-    client: Client,
+    backend: Box<dyn SearchBackend>,
 }
 
 impl WebSearchTool {
-    /// Creates a new web search tool with the given configuration.
+    /// Creates a new web search tool using the provider named in the configuration.
     ///
     /// # Panics
     ///
     /// Panics if the HTTP client cannot be built (should not happen with default settings).
     #[must_use]
     pub fn new(config: WebSearchConfig) -> Self {
-        let client = Client::builder()
-            .timeout(config.timeout)
-            .user_agent("Patina/0.3.0")
-            .build()
-            .expect("Failed to build HTTP client");
+        let backend: Box<dyn SearchBackend> = match config.provider {
+            SearchProvider::DuckDuckGo => Box::new(DuckDuckGoBackend::new(&config)),
+            SearchProvider::Searxng => Box::new(SearxngBackend::new(&config)),
+        };
+        Self { config, backend }
+    }
 
-        Self { config, client }
+    /// Creates a web search tool that queries a custom backend.
+    ///
+    /// The configuration still caps the number of results.
+    #[must_use]
+    pub fn with_backend(config: WebSearchConfig, backend: impl SearchBackend + 'static) -> Self {
+        Self {
+            config,
+            backend: Box::new(backend),
+        }
     }
 
     /// Searches the web for the given query.
@@ -135,82 +401,7 @@ impl WebSearchTool {
 
         debug!(query = %query, max_results = effective_max, "Performing web search");
 
-        // Build the request URL
-        let url = format!("{}?q={}", self.config.base_url, urlencoding::encode(query));
-
-        // Make the request
-        let response = self.client.get(&url).send().await?;
-
-        // Check for HTTP errors
-        let status = response.status();
-        if !status.is_success() {
-            bail!("Search request failed with status {}", status);
-        }
-
-        // Parse the response
-        let html_content = response.text().await?;
-        let results = Self::parse_duckduckgo_results(&html_content, effective_max);
-
-        Ok(results)
-    }
-
-    /// Parses DuckDuckGo HTML search results.
-    ///
-    /// DuckDuckGo's HTML API returns results in a structured format that we parse
-    /// using CSS selectors.
-    fn parse_duckduckgo_results(html: &str, max_results: usize) -> Vec<SearchResult> {
-        let document = Html::parse_document(html);
-
-        // DuckDuckGo HTML selectors
-        let result_selector = Selector::parse(".result, .results_links, div.web-result")
-            .unwrap_or_else(|_| {
-                // Fallback to a simple div selector if the specific one fails
-                Selector::parse("div").expect("basic selector should work")
-            });
-
-        let link_selector = Selector::parse("a.result__a, a.result-link, h2 a")
-            .unwrap_or_else(|_| Selector::parse("a").expect("basic a selector should work"));
-
-        let snippet_selector = Selector::parse(".result__snippet, .result-snippet, .snippet")
-            .unwrap_or_else(|_| Selector::parse("p").expect("basic p selector should work"));
-
-        let mut results = Vec::new();
-
-        for element in document.select(&result_selector) {
-            if results.len() >= max_results {
-                break;
-            }
-
-            // Extract link and title
-            if let Some(link_elem) = element.select(&link_selector).next() {
-                let title = link_elem.text().collect::<String>().trim().to_string();
-                let url = link_elem
-                    .value()
-                    .attr("href")
-                    .unwrap_or_default()
-                    .to_string();
-
-                // Skip empty results
-                if title.is_empty() || url.is_empty() {
-                    continue;
-                }
-
-                // Extract snippet
-                let snippet = element
-                    .select(&snippet_selector)
-                    .next()
-                    .map(|e| e.text().collect::<String>().trim().to_string())
-                    .unwrap_or_default();
-
-                results.push(SearchResult {
-                    title,
-                    url,
-                    snippet,
-                });
-            }
-        }
-
-        results
+        self.backend.search(query, effective_max).await
     }
 
     /// Formats search results as markdown.
@@ -305,7 +496,7 @@ mod tests {
             </div>
         "#;
 
-        let results = WebSearchTool::parse_duckduckgo_results(html, 10);
+        let results = parse_duckduckgo_results(html, 10);
         assert!(!results.is_empty());
         assert_eq!(results[0].title, "Example Title");
         assert_eq!(results[0].url, "https://example.com");
@@ -328,7 +519,7 @@ mod tests {
             </div>
         "#;
 
-        let results = WebSearchTool::parse_duckduckgo_results(html, 2);
+        let results = parse_duckduckgo_results(html, 2);
         assert_eq!(results.len(), 2);
     }
 }
@@ -13,12 +13,17 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::app::state::AppState;
 use crate::permissions::PermissionRequest;
 use crate::tui::theme::PatinaTheme;
 use crate::tui::widgets::compaction_progress::{CompactionProgressState, CompactionProgressWidget};
-use crate::tui::widgets::permission_prompt::{PermissionPromptState, PermissionPromptWidget};
+use crate::tui::widgets::permission_prompt::{
+    reason_hints, reason_line, PermissionPromptState, PermissionPromptWidget,
+};
+use crate::types::config::{CtrlCMode, PermissionMode, ThinkingDisplay};
+use crate::types::content::is_tool_failure;
 use crate::types::{ConversationEntry, Timeline};
 
 /// Calculates the total number of displayed lines after wrapping.
@@ -135,6 +140,26 @@ pub fn render_timeline_to_lines(timeline: &Timeline, _width: usize) -> Vec<Line<
 /// A vector of styled `Line` objects ready for display.
 #[must_use]
 pub fn render_timeline_with_throbber(timeline: &Timeline, throbber: char) -> Vec<Line<'static>> {
+    render_timeline_with_thinking(timeline, throbber, ThinkingDisplay::default())
+}
+
+/// Renders a timeline to a vector of lines, showing thinking as configured.
+///
+/// # Arguments
+///
+/// * `timeline` - The timeline containing conversation entries
+/// * `throbber` - Character to display for streaming animation
+/// * `thinking` - How thinking entries are displayed
+///
+/// # Returns
+///
+/// A vector of styled `Line` objects ready for display.
+#[must_use]
+pub fn render_timeline_with_thinking(
+    timeline: &Timeline,
+    throbber: char,
+    thinking: ThinkingDisplay,
+) -> Vec<Line<'static>> {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
     for entry in timeline.iter() {
@@ -151,6 +176,9 @@ pub fn render_timeline_with_throbber(timeline: &Timeline, throbber: char) -> Vec
             ConversationEntry::Streaming { text, .. } => {
                 render_streaming_entry_with_throbber(&mut lines, text, throbber);
             }
+            ConversationEntry::Thinking(text) => {
+                render_thinking(&mut lines, text, thinking);
+            }
             ConversationEntry::ToolExecution {
                 name,
                 input,
@@ -160,6 +188,9 @@ pub fn render_timeline_with_throbber(timeline: &Timeline, throbber: char) -> Vec
             } => {
                 render_tool_execution(&mut lines, name, input, output.as_deref(), *is_error);
             }
+            ConversationEntry::ToolProgress { text, .. } => {
+                render_tool_progress(&mut lines, text);
+            }
             ConversationEntry::ImageDisplay {
                 width,
                 height,
@@ -214,6 +245,38 @@ fn render_assistant_message(lines: &mut Vec<Line<'static>>, text: &str) {
     lines.push(Line::from(""));
 }
 
+/// Renders model reasoning to lines according to the display mode.
+///
+/// Hidden thinking produces no lines and collapsed thinking produces a
+/// single summary line, so neither contributes its content to copy.
+fn render_thinking(lines: &mut Vec<Line<'static>>, text: &str, mode: ThinkingDisplay) {
+    match mode {
+        ThinkingDisplay::Hidden => {}
+        ThinkingDisplay::Collapsed => {
+            let count = text.lines().count();
+            let noun = if count == 1 { "line" } else { "lines" };
+            lines.push(Line::from(Span::styled(
+                format!("▸ Thinking ({count} {noun}) - /thinking shown to expand"),
+                PatinaTheme::thinking(),
+            )));
+            lines.push(Line::from(""));
+        }
+        ThinkingDisplay::Shown => {
+            lines.push(Line::from(Span::styled(
+                "▾ Thinking".to_string(),
+                PatinaTheme::thinking(),
+            )));
+            for line in text.lines() {
+                lines.push(Line::from(Span::styled(
+                    line.to_string(),
+                    PatinaTheme::thinking(),
+                )));
+            }
+            lines.push(Line::from(""));
+        }
+    }
+}
+
 /// Renders a streaming entry to lines with a specified throbber character.
 fn render_streaming_entry_with_throbber(
     lines: &mut Vec<Line<'static>>,
@@ -300,8 +363,12 @@ fn render_tool_execution(
     output: Option<&str>,
     is_error: bool,
 ) {
-    // Tool block header
-    let (icon, header_style) = if is_error {
+    // Tool block header; a tool that could not run is flagged apart from one
+    // that ran and reported an error
+    let failed_to_run = is_error && output.is_some_and(is_tool_failure);
+    let (icon, header_style) = if failed_to_run {
+        ("⚠", PatinaTheme::error().add_modifier(Modifier::BOLD))
+    } else if is_error {
         ("✗", PatinaTheme::error().add_modifier(Modifier::BOLD))
     } else if output.is_some() {
         ("✓", PatinaTheme::tool_header())
@@ -309,10 +376,14 @@ fn render_tool_execution(
         ("⚙", PatinaTheme::tool_header())
     };
 
-    lines.push(Line::from(vec![
+    let mut header = vec![
         Span::styled(format!("  {} ", icon), header_style),
         Span::styled(name.to_string(), header_style),
-    ]));
+    ];
+    if failed_to_run {
+        header.push(Span::styled(" (failed to run)", header_style));
+    }
+    lines.push(Line::from(header));
 
     // Tool input line
     lines.push(Line::from(vec![
@@ -377,19 +448,58 @@ fn render_tool_execution(
     lines.push(Line::from("")); // Spacer between tool blocks
 }
 
+/// Renders partial output from a running tool, keeping the latest lines.
+fn render_tool_progress(lines: &mut Vec<Line<'static>>, text: &str) {
+    let total_lines = text.lines().count();
+    let skipped = total_lines.saturating_sub(5);
+    if skipped > 0 {
+        lines.push(Line::from(vec![
+            Span::raw("    ".to_string()),
+            Span::styled(
+                format!("... ({} earlier lines)", skipped),
+                Style::default().fg(PatinaTheme::MUTED),
+            ),
+        ]));
+    }
+
+    for line in text.lines().skip(skipped) {
+        lines.push(Line::from(vec![
+            Span::raw("    ".to_string()),
+            Span::styled(line.to_string(), PatinaTheme::streaming()),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+}
+
 pub fn render(frame: &mut Frame, state: &mut AppState) {
+    let context_warning = state.context_warning();
+    let warning_height = u16::from(context_warning.is_some());
+    let hint_height = u16::from(state.keyboard_hint().is_some());
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(3),    // Messages
-            Constraint::Length(1), // Status bar
-            Constraint::Length(3), // Input
+            Constraint::Min(3),                       // Messages
+            Constraint::Length(warning_height),       // Context window warning
+            Constraint::Length(hint_height),          // Keyboard hint
+            Constraint::Length(1),                    // Status bar
+            Constraint::Length(state.input_height()), // Input
         ])
         .split(frame.area());
 
     render_messages(frame, chunks[0], state);
-    render_status_bar(frame, chunks[1], state);
-    render_input(frame, chunks[2], state);
+    if let Some(warning) = context_warning {
+        let banner = Paragraph::new(format!("⚠ {warning}"))
+            .style(Style::default().fg(Color::Black).bg(Color::LightRed));
+        frame.render_widget(banner, chunks[1]);
+    }
+    if let Some(hint) = state.keyboard_hint() {
+        let banner = Paragraph::new(hint.to_string())
+            .style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        frame.render_widget(banner, chunks[2]);
+    }
+    render_status_bar(frame, chunks[3], state);
+    render_input(frame, chunks[4], state);
 
     // Render compaction progress overlay if compaction is active
     if let Some(compaction_state) = state.compaction_state() {
@@ -398,7 +508,7 @@ pub fn render(frame: &mut Frame, state: &mut AppState) {
 
     // Render permission modal overlay if there's a pending permission request
     if let Some(request) = state.pending_permission() {
-        render_permission_modal(frame, request);
+        render_permission_modal(frame, request, state.permission_reason());
     }
 }
 
@@ -436,12 +546,18 @@ pub fn render_compaction_overlay(frame: &mut Frame, compaction_state: &Compactio
 ///
 /// * `frame` - The ratatui frame to render into
 /// * `request` - The permission request to display
-pub fn render_permission_modal(frame: &mut Frame, request: &PermissionRequest) {
+/// * `reason` - The denial reason being typed, if in reason-input mode
+pub fn render_permission_modal(
+    frame: &mut Frame,
+    request: &PermissionRequest,
+    reason: Option<&str>,
+) {
     let area = frame.area();
-    let modal_area = PermissionPromptWidget::modal_area(area);
+    let modal_area = PermissionPromptWidget::modal_area_for(area, request);
 
     // Create the prompt state from the request
-    let prompt_state = PermissionPromptState::new(request.clone());
+    let mut prompt_state = PermissionPromptState::new(request.clone());
+    prompt_state.set_reason(reason.map(str::to_string));
 
     // Check if this is a dangerous command and add warning styling
     let is_dangerous = is_dangerous_command(request);
@@ -583,6 +699,13 @@ fn render_permission_modal_dangerous(frame: &mut Frame, area: Rect, state: &Perm
         .wrap(Wrap { trim: true });
     frame.render_widget(desc_para, chunks[5]);
 
+    // Reason input replaces the options while it is being typed
+    if let Some(reason) = state.reason() {
+        frame.render_widget(Paragraph::new(reason_line(reason)), chunks[7]);
+        frame.render_widget(Paragraph::new(reason_hints()), chunks[8]);
+        return;
+    }
+
     // Options line (using simpler rendering)
     let options_line = Line::from(vec![
         Span::styled("[y]", Style::default().fg(Color::Cyan)),
@@ -590,7 +713,9 @@ fn render_permission_modal_dangerous(frame: &mut Frame, area: Rect, state: &Perm
         Span::styled("[a]", Style::default().fg(Color::Cyan)),
         Span::raw(" Allow Always  "),
         Span::styled("[n]", Style::default().fg(Color::Red)),
-        Span::raw(" Deny"),
+        Span::raw(" Deny  "),
+        Span::styled("[r]", Style::default().fg(Color::Red)),
+        Span::raw(" Deny with reason"),
     ]);
     frame.render_widget(Paragraph::new(options_line), chunks[7]);
 
@@ -608,7 +733,7 @@ fn render_messages(frame: &mut Frame, area: Rect, state: &mut AppState) {
     // Render using unified timeline
     let throbber = state.throbber_char();
     let timeline_entry_count = state.timeline().len();
-    let lines = render_timeline_with_throbber(state.timeline(), throbber);
+    let lines = render_timeline_with_thinking(state.timeline(), throbber, state.thinking_display());
 
     tracing::debug!(
         timeline_entries = timeline_entry_count,
@@ -748,6 +873,23 @@ fn render_status_bar(frame: &mut Frame, area: Rect, state: &AppState) {
         ));
     }
 
+    // Permission mode, with the directory rule that selected it
+    let permission_mode = state.permission_mode();
+    let permission_rule = state.permission_mode_rule();
+    if permission_mode != PermissionMode::Ask || permission_rule.is_some() {
+        let label = match permission_rule {
+            Some(rule) => format!("[{}: {}]", permission_mode.as_str(), rule),
+            None => format!("[{}]", permission_mode.as_str()),
+        };
+        let color = match permission_mode {
+            PermissionMode::Ask => PatinaTheme::MUTED,
+            PermissionMode::ReadOnly => PatinaTheme::WARNING,
+            PermissionMode::FullAccess => PatinaTheme::ERROR,
+        };
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(label, Style::default().fg(color)));
+    }
+
     // Token budget display (color-coded based on usage)
     let budget = state.token_budget();
     if budget.used() > 0 {
@@ -791,18 +933,42 @@ fn render_status_bar(frame: &mut Frame, area: Rect, state: &AppState) {
 }
 
 fn render_input(frame: &mut Frame, area: Rect, state: &AppState) {
+    let title = if state.ctrl_c_exit_armed() {
+        " Input (press Ctrl+C again to quit) "
+    } else if state.ctrl_c_mode() == CtrlCMode::CancelThenExit && state.is_busy() {
+        " Input (Enter to send, Ctrl+C to cancel) "
+    } else {
+        " Input (Enter to send, Ctrl+C to quit) "
+    };
+    // Scroll a draft taller than the box so the cursor's line stays visible
+    let (line, col) = state.cursor_line_col();
+    let visible = usize::from(area.height.saturating_sub(2)).max(1);
+    let scroll = line.saturating_sub(visible - 1);
+
     let input = Paragraph::new(state.input.as_str())
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Input (Enter to send, Ctrl+C to quit) ")
+                .title(title)
                 .border_style(PatinaTheme::border_focused()),
         )
-        .style(Style::default().fg(PatinaTheme::USER_TEXT));
+        .style(Style::default().fg(PatinaTheme::USER_TEXT))
+        .scroll((scroll as u16, 0));
 
     frame.render_widget(input, area);
 
-    frame.set_cursor_position((area.x + state.input.len() as u16 + 1, area.y + 1));
+    let before_cursor: String = state
+        .input
+        .split('\n')
+        .nth(line)
+        .unwrap_or_default()
+        .chars()
+        .take(col)
+        .collect();
+    frame.set_cursor_position((
+        area.x + before_cursor.width() as u16 + 1,
+        area.y + (line - scroll) as u16 + 1,
+    ));
 }
 
 #[cfg(test)]
@@ -878,6 +1044,94 @@ mod tests {
     // Permission modal rendering tests
     // =========================================================================
 
+    #[test]
+    fn test_render_shows_keyboard_hint_until_dismissed() {
+        use crate::types::config::ParallelMode;
+
+        let mut terminal = test_terminal(100, 24);
+        let mut state = AppState::new(
+            std::path::PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+        );
+        state.show_keyboard_hint("Use Ctrl+Y to copy".to_string());
+
+        let screen = |terminal: &Terminal<TestBackend>| -> String {
+            terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol().chars().next().unwrap_or(' '))
+                .collect()
+        };
+
+        terminal.draw(|frame| render(frame, &mut state)).unwrap();
+        assert!(screen(&terminal).contains("Use Ctrl+Y to copy"));
+
+        state.dismiss_keyboard_hint();
+        terminal.draw(|frame| render(frame, &mut state)).unwrap();
+        assert!(!screen(&terminal).contains("Use Ctrl+Y to copy"));
+    }
+
+    #[test]
+    fn test_render_multiline_input_grows_and_tracks_cursor() {
+        use crate::types::config::ParallelMode;
+
+        let mut terminal = test_terminal(60, 24);
+        let mut state = AppState::new(
+            std::path::PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+        );
+        state.insert_str("first\n你好 there\nlast");
+        state.cursor_up();
+
+        terminal.draw(|frame| render(frame, &mut state)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String {
+            (0..60)
+                .map(|x| buffer[(x, y)].symbol().to_string())
+                .collect::<String>()
+        };
+        // The box is five rows tall: border, three lines, border
+        assert!(row(19).starts_with('┌'), "{}", row(19));
+        assert!(row(20).contains("first"));
+        assert!(row(21).contains("there"));
+        assert!(row(22).contains("last"));
+        // Cursor after "你好 t": two wide characters, a space and a letter
+        terminal.backend_mut().assert_cursor_position((7, 21));
+    }
+
+    #[test]
+    fn test_render_shows_context_warning_banner() {
+        use crate::types::config::ParallelMode;
+        use crate::types::ApiMessageV2;
+
+        let mut terminal = test_terminal(100, 24);
+        let mut state = AppState::new(
+            std::path::PathBuf::from("/test"),
+            false,
+            ParallelMode::Enabled,
+        );
+        state.set_context_window(1000);
+        state
+            .api_messages_mut()
+            .push(ApiMessageV2::user("x".repeat(3600)));
+        state.check_context_budget();
+
+        terminal.draw(|frame| render(frame, &mut state)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol().chars().next().unwrap_or(' '))
+            .collect();
+        assert!(screen.contains("Context 90% full"));
+    }
+
     #[test]
     fn test_permission_modal_renders() {
         let mut terminal = test_terminal(80, 24);
@@ -886,7 +1140,7 @@ mod tests {
 
         terminal
             .draw(|frame| {
-                render_permission_modal(frame, &request);
+                render_permission_modal(frame, &request, None);
             })
             .expect("Failed to draw");
 
@@ -918,7 +1172,7 @@ mod tests {
 
         terminal
             .draw(|frame| {
-                render_permission_modal(frame, &request);
+                render_permission_modal(frame, &request, None);
             })
             .expect("Failed to draw");
 
@@ -950,7 +1204,7 @@ mod tests {
 
         terminal
             .draw(|frame| {
-                render_permission_modal(frame, &request);
+                render_permission_modal(frame, &request, None);
             })
             .expect("Failed to draw");
 
@@ -978,7 +1232,7 @@ mod tests {
 
         terminal
             .draw(|frame| {
-                render_permission_modal(frame, &request);
+                render_permission_modal(frame, &request, None);
             })
             .expect("Failed to draw");
 
@@ -1003,7 +1257,7 @@ mod tests {
 
         terminal
             .draw(|frame| {
-                render_permission_modal(frame, &request);
+                render_permission_modal(frame, &request, None);
             })
             .expect("Failed to draw");
 
@@ -1367,4 +1621,52 @@ mod tests {
         assert!(content.contains("1920×1080"), "Should have dimensions");
         assert!(content.contains("4K display"), "Should have alt text");
     }
+
+    fn thinking_timeline() -> Timeline {
+        let mut timeline = Timeline::new();
+        timeline.push_user_message("What is 2+2?");
+        timeline.push_streaming();
+        timeline.append_thinking("Adding two and two.\nThat makes four.");
+        timeline.append_to_streaming("4");
+        timeline.finalize_streaming_as_message();
+        timeline
+    }
+
+    fn rendered_text(timeline: &Timeline, mode: ThinkingDisplay) -> String {
+        render_timeline_with_thinking(timeline, '⠋', mode)
+            .iter()
+            .map(|l| format!("{}\n", l))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_thinking_hidden() {
+        let content = rendered_text(&thinking_timeline(), ThinkingDisplay::Hidden);
+
+        assert!(!content.contains("Thinking"));
+        assert!(!content.contains("Adding two and two."));
+        assert!(content.contains("4"));
+    }
+
+    #[test]
+    fn test_render_thinking_collapsed() {
+        let content = rendered_text(&thinking_timeline(), ThinkingDisplay::Collapsed);
+
+        assert!(content.contains("▸ Thinking (2 lines)"));
+        assert!(!content.contains("Adding two and two."));
+        assert!(!content.contains("That makes four."));
+    }
+
+    #[test]
+    fn test_render_thinking_shown_dimmed() {
+        let lines =
+            render_timeline_with_thinking(&thinking_timeline(), '⠋', ThinkingDisplay::Shown);
+        let thinking_line = lines
+            .iter()
+            .find(|l| l.to_string() == "Adding two and two.")
+            .expect("thinking should be rendered");
+
+        assert_eq!(thinking_line.spans[0].style, PatinaTheme::thinking());
+        assert!(lines.iter().any(|l| l.to_string() == "That makes four."));
+    }
 }
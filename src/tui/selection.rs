@@ -5,6 +5,7 @@
 //! to clipboard.
 
 use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
 
 /// Represents which area of the UI has focus.
 ///
@@ -14,7 +15,8 @@ use ratatui::text::Line;
 ///
 /// When Content is focused, Ctrl+A selects all content text.
 /// When Input is focused, keyboard events go to the input field.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FocusArea {
     /// The text input area (default)
     #[default]
@@ -28,7 +30,7 @@ pub enum FocusArea {
 /// Represents a cursor position in the visible content area, where `line`
 /// is the visual line number (after text wrapping) and `col` is the
 /// character offset within that line.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct ContentPosition {
     /// Visual line number (0-indexed, after wrapping)
     pub line: usize,
@@ -130,6 +132,31 @@ impl SelectionState {
         self.selecting = false;
     }
 
+    /// Restores a completed selection, e.g. from a saved session.
+    pub fn restore(&mut self, start: ContentPosition, end: ContentPosition) {
+        self.anchor = Some(start);
+        self.cursor = Some(end);
+        self.selecting = false;
+    }
+
+    /// Clamps the selection to content with `total_lines` lines.
+    ///
+    /// A selection that starts past the last line is cleared; one that ends
+    /// past it is cut at the end of the last line.
+    pub fn clamp_to_lines(&mut self, total_lines: usize) {
+        let (Some(anchor), Some(cursor)) = (self.anchor, self.cursor) else {
+            return;
+        };
+        if anchor.line.min(cursor.line) >= total_lines {
+            self.clear();
+            return;
+        }
+
+        let last = ContentPosition::new(total_lines - 1, usize::MAX);
+        self.anchor = Some(anchor.min(last));
+        self.cursor = Some(cursor.min(last));
+    }
+
     /// Returns whether there is an active selection.
     #[must_use]
     pub fn has_selection(&self) -> bool {
@@ -266,6 +293,35 @@ mod tests {
     // select_all tests
     // =========================================================================
 
+    #[test]
+    fn test_clamp_to_lines_cuts_selection_at_last_line() {
+        let mut sel = SelectionState::new();
+        sel.restore(ContentPosition::new(2, 4), ContentPosition::new(40, 3));
+
+        sel.clamp_to_lines(10);
+
+        assert_eq!(
+            sel.range(),
+            Some((
+                ContentPosition::new(2, 4),
+                ContentPosition::new(9, usize::MAX)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_lines_clears_selection_past_end() {
+        let mut sel = SelectionState::new();
+        sel.restore(ContentPosition::new(12, 0), ContentPosition::new(15, 8));
+
+        sel.clamp_to_lines(10);
+        assert!(!sel.has_selection());
+
+        sel.restore(ContentPosition::new(0, 0), ContentPosition::new(1, 1));
+        sel.clamp_to_lines(0);
+        assert!(!sel.has_selection());
+    }
+
     #[test]
     fn test_select_all_empty() {
         let mut sel = SelectionState::new();
@@ -225,6 +225,14 @@ impl PatinaTheme {
             .add_modifier(Modifier::BOLD)
     }
 
+    /// Style for model reasoning ("thinking").
+    #[must_use]
+    pub fn thinking() -> Style {
+        Style::default()
+            .fg(Self::MUTED)
+            .add_modifier(Modifier::DIM | Modifier::ITALIC)
+    }
+
     /// Style for code blocks.
     #[must_use]
     pub fn code_block() -> Style {
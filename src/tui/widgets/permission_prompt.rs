@@ -7,6 +7,7 @@
 //! - `y` or `Enter` - Allow once (session grant)
 //! - `a` - Allow always (persistent rule)
 //! - `n` or `Esc` - Deny
+//! - `r` - Deny with a reason (type it, then `Enter`; `Esc` goes back)
 //!
 //! # Example
 //!
@@ -88,6 +89,9 @@ pub struct PermissionPromptState {
 
     /// The response if answered.
     response: Option<PermissionResponse>,
+
+    /// Reason being typed for a denial, if in reason-input mode.
+    reason: Option<String>,
 }
 
 impl PermissionPromptState {
@@ -99,6 +103,7 @@ impl PermissionPromptState {
             selected: SelectedOption::default(),
             answered: false,
             response: None,
+            reason: None,
         }
     }
 
@@ -160,7 +165,7 @@ impl PermissionPromptState {
     /// Returns the response if the prompt has been answered.
     #[must_use]
     pub fn response(&self) -> Option<PermissionResponse> {
-        self.response
+        self.response.clone()
     }
 
     /// Takes the response, consuming it (can only be called once).
@@ -168,11 +173,39 @@ impl PermissionPromptState {
         self.response.take()
     }
 
+    /// Enters reason-input mode with an empty reason.
+    pub fn start_reason(&mut self) {
+        self.reason = Some(String::new());
+    }
+
+    /// Returns true while the user is typing a denial reason.
+    #[must_use]
+    pub fn is_entering_reason(&self) -> bool {
+        self.reason.is_some()
+    }
+
+    /// Returns the denial reason typed so far, if in reason-input mode.
+    #[must_use]
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// Sets the reason-input buffer (`None` leaves reason-input mode).
+    pub fn set_reason(&mut self, reason: Option<String>) {
+        self.reason = reason;
+    }
+
+    /// Takes the reason-input buffer, leaving reason-input mode.
+    pub fn take_reason(&mut self) -> Option<String> {
+        self.reason.take()
+    }
+
     /// Resets the prompt state (for reuse).
     pub fn reset(&mut self) {
         self.answered = false;
         self.response = None;
         self.selected = SelectedOption::default();
+        self.reason = None;
     }
 }
 
@@ -208,6 +241,43 @@ impl<'a> PermissionPromptWidget<'a> {
         Rect::new(x, y, width, height)
     }
 
+    /// Calculates the modal area for a request, growing it to fit a preview.
+    #[must_use]
+    pub fn modal_area_for(area: Rect, request: &PermissionRequest) -> Rect {
+        let Some(preview) = &request.preview else {
+            return Self::modal_area(area);
+        };
+
+        // Room for every preview line plus its heading, within the terminal
+        let preview_height = preview.lines().count() as u16 + 1;
+        let width = area.width.clamp(40, 80);
+        let height = (14 + preview_height).min(area.height);
+
+        let x = (area.width.saturating_sub(width)) / 2;
+        let y = (area.height.saturating_sub(height)) / 2;
+
+        Rect::new(x, y, width, height)
+    }
+
+    /// Renders the preview with removed lines in red and added lines in green.
+    fn preview_lines(preview: &str) -> Vec<Line<'a>> {
+        let mut lines = vec![Line::from(Span::styled(
+            "Changes:",
+            Style::default().fg(Color::White),
+        ))];
+        lines.extend(preview.lines().map(|line| {
+            let color = if line.starts_with("- ") {
+                Color::Red
+            } else if line.starts_with("+ ") {
+                Color::Green
+            } else {
+                Color::Gray
+            };
+            Line::from(Span::styled(line.to_string(), Style::default().fg(color)))
+        }));
+        lines
+    }
+
     /// Renders an option button.
     fn render_option(&self, label: &str, hotkey: char, is_selected: bool) -> Line<'a> {
         let style = if is_selected {
@@ -257,17 +327,25 @@ impl Widget for PermissionPromptWidget<'_> {
         block.render(area, buf);
 
         // Layout the content vertically
+        let preview = self
+            .state
+            .request
+            .preview
+            .as_deref()
+            .map(Self::preview_lines);
+        let preview_height = preview.as_ref().map_or(0, |lines| lines.len() as u16);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(1), // Tool name
-                Constraint::Length(1), // Separator
-                Constraint::Length(2), // Input (may wrap)
-                Constraint::Length(1), // Separator
-                Constraint::Min(2),    // Description
-                Constraint::Length(1), // Separator
-                Constraint::Length(1), // Options
-                Constraint::Length(1), // Keybinding hints
+                Constraint::Length(1),              // Tool name
+                Constraint::Length(1),              // Separator
+                Constraint::Length(2),              // Input (may wrap)
+                Constraint::Length(1),              // Separator
+                Constraint::Min(2),                 // Description
+                Constraint::Length(preview_height), // Preview of the change
+                Constraint::Length(1),              // Separator
+                Constraint::Length(1),              // Options
+                Constraint::Length(1),              // Keybinding hints
             ])
             .split(inner);
 
@@ -307,8 +385,24 @@ impl Widget for PermissionPromptWidget<'_> {
             .wrap(Wrap { trim: true });
         desc_para.render(chunks[4], buf);
 
+        if let Some(lines) = preview {
+            Paragraph::new(lines).render(chunks[5], buf);
+        }
+
+        // Reason input replaces the options while it is being typed
+        if let Some(reason) = self.state.reason() {
+            buf.set_line(
+                chunks[7].x,
+                chunks[7].y,
+                &reason_line(reason),
+                chunks[7].width,
+            );
+            buf.set_line(chunks[8].x, chunks[8].y, &reason_hints(), chunks[8].width);
+            return;
+        }
+
         // Options (horizontal layout)
-        let options_area = chunks[6];
+        let options_area = chunks[7];
         let option_width = options_area.width / 3;
 
         // Allow Once
@@ -348,17 +442,44 @@ impl Widget for PermissionPromptWidget<'_> {
             Span::styled("Enter", Style::default().fg(Color::Cyan)),
             Span::raw(":confirm "),
             Span::styled("Esc", Style::default().fg(Color::Cyan)),
-            Span::raw(":deny"),
+            Span::raw(":deny "),
+            Span::styled("r", Style::default().fg(Color::Cyan)),
+            Span::raw(":deny with reason"),
         ]);
-        buf.set_line(chunks[7].x, chunks[7].y, &hints, chunks[7].width);
+        buf.set_line(chunks[8].x, chunks[8].y, &hints, chunks[8].width);
     }
 }
 
+/// Renders the denial reason being typed, with a cursor.
+#[must_use]
+pub fn reason_line(reason: &str) -> Line<'static> {
+    Line::from(vec![
+        Span::styled("Reason: ", Style::default().fg(Color::White)),
+        Span::styled(reason.to_string(), Style::default().fg(Color::Yellow)),
+        Span::styled("_", Style::default().fg(Color::Cyan)),
+    ])
+}
+
+/// Renders the keybinding hints shown while typing a denial reason.
+#[must_use]
+pub fn reason_hints() -> Line<'static> {
+    Line::from(vec![
+        Span::styled("Enter", Style::default().fg(Color::Cyan)),
+        Span::raw(":deny with reason "),
+        Span::styled("Esc", Style::default().fg(Color::Cyan)),
+        Span::raw(":back"),
+    ])
+}
+
 /// Handles keyboard input for the permission prompt.
 ///
 /// Returns `Some(response)` if a decision was made, `None` if the input
 /// was handled but no decision was made.
 ///
+/// In reason-input mode, characters are appended to the reason instead of
+/// being treated as hotkeys. `Enter` denies with the reason (or plainly,
+/// if it is blank) and `Esc` returns to the options.
+///
 /// # Arguments
 ///
 /// * `state` - The prompt state to update
@@ -371,7 +492,33 @@ pub fn handle_key_input(
     state: &mut PermissionPromptState,
     key: char,
 ) -> Option<PermissionResponse> {
+    if let Some(reason) = state.reason.as_mut() {
+        match key {
+            '\r' | '\n' => {
+                let reason = state.take_reason().unwrap_or_default();
+                let reason = reason.trim();
+                if reason.is_empty() {
+                    state.confirm_with(PermissionResponse::Deny);
+                } else {
+                    state.confirm_with(PermissionResponse::DenyWithReason(reason.to_string()));
+                }
+                return state.response();
+            }
+            '\x1b' => state.set_reason(None),
+            '\x08' => {
+                reason.pop();
+            }
+            c if !c.is_control() => reason.push(c),
+            _ => {}
+        }
+        return None;
+    }
+
     match key {
+        'r' | 'R' => {
+            state.start_reason();
+            None
+        }
         'y' | 'Y' => {
             state.confirm_with(PermissionResponse::AllowOnce);
             state.response()
@@ -620,6 +767,60 @@ mod tests {
         assert_eq!(state.selected(), SelectedOption::AllowOnce);
     }
 
+    #[test]
+    fn test_key_input_deny_with_reason() {
+        let request = PermissionRequest::new("Bash", Some("rm -r migrations"), "Test");
+        let mut state = PermissionPromptState::new(request);
+
+        assert!(handle_key_input(&mut state, 'r').is_none());
+        assert!(state.is_entering_reason());
+
+        // Hotkeys are typed into the reason, backspace edits it
+        for c in "no yx\x08".chars() {
+            assert!(handle_key_input(&mut state, c).is_none());
+        }
+        assert_eq!(state.reason(), Some("no y"));
+
+        let response = handle_key_input(&mut state, '\r');
+        assert_eq!(
+            response,
+            Some(PermissionResponse::DenyWithReason("no y".to_string()))
+        );
+        assert!(!state.is_entering_reason());
+    }
+
+    #[test]
+    fn test_key_input_reason_escape_and_blank() {
+        let request = PermissionRequest::new("Bash", None, "Test");
+        let mut state = PermissionPromptState::new(request);
+
+        // Esc leaves reason-input mode without deciding
+        handle_key_input(&mut state, 'r');
+        assert!(handle_key_input(&mut state, '\x1b').is_none());
+        assert!(!state.is_entering_reason());
+        assert!(!state.is_answered());
+
+        // A blank reason is a plain deny
+        handle_key_input(&mut state, 'r');
+        handle_key_input(&mut state, ' ');
+        let response = handle_key_input(&mut state, '\r');
+        assert_eq!(response, Some(PermissionResponse::Deny));
+    }
+
+    #[test]
+    fn test_render_shows_reason_input() {
+        let request = PermissionRequest::new("Bash", Some("ls"), "List files");
+        let mut state = PermissionPromptState::new(request);
+        state.set_reason(Some("use the Glob tool".to_string()));
+        let area = PermissionPromptWidget::modal_area(Rect::new(0, 0, 100, 50));
+        let mut buf = Buffer::empty(area);
+        PermissionPromptWidget::new(&state).render(area, &mut buf);
+
+        let text: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Reason: use the Glob tool"));
+        assert!(!text.contains("Allow Once"));
+    }
+
     // =========================================================================
     // Modal area calculation tests
     // =========================================================================
@@ -636,6 +837,40 @@ mod tests {
         assert!(modal.y + modal.height <= area.height);
     }
 
+    #[test]
+    fn test_modal_area_grows_for_preview() {
+        let area = Rect::new(0, 0, 100, 50);
+        let plain = PermissionRequest::new("edit", Some("a.rs"), "Edit file: a.rs");
+        let with_preview = plain.clone().with_preview("- old\n+ new");
+
+        assert_eq!(
+            PermissionPromptWidget::modal_area_for(area, &plain),
+            PermissionPromptWidget::modal_area(area)
+        );
+        let modal = PermissionPromptWidget::modal_area_for(area, &with_preview);
+        assert_eq!(modal.height, 17);
+        assert!(modal.width > PermissionPromptWidget::modal_area(area).width);
+
+        let small = Rect::new(0, 0, 40, 10);
+        assert!(PermissionPromptWidget::modal_area_for(small, &with_preview).height <= 10);
+    }
+
+    #[test]
+    fn test_render_shows_preview() {
+        let request = PermissionRequest::new("edit", Some("a.rs"), "Edit file: a.rs")
+            .with_preview("- let x = 1;\n+ let x = 2;");
+        let state = PermissionPromptState::new(request);
+        let area =
+            PermissionPromptWidget::modal_area_for(Rect::new(0, 0, 100, 50), state.request());
+        let mut buf = Buffer::empty(area);
+        PermissionPromptWidget::new(&state).render(area, &mut buf);
+
+        let text: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("- let x = 1;"));
+        assert!(text.contains("+ let x = 2;"));
+        assert!(text.contains("Allow Once"));
+    }
+
     #[test]
     fn test_modal_area_small_terminal() {
         let area = Rect::new(0, 0, 40, 10);
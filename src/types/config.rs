@@ -4,8 +4,12 @@
 //! and configure the application.
 
 use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use super::settings::Settings;
+use crate::api::SummaryStyle;
+
 /// Controls session resume behavior.
 ///
 /// When starting Patina, users can optionally resume a previous session
@@ -61,6 +65,150 @@ pub enum ParallelMode {
     Aggressive,
 }
 
+/// Controls the boundary that file tools are confined to.
+///
+/// Tool paths are always resolved relative to the working directory; this
+/// determines how far above it they may reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathConfinement {
+    /// Confine file access to the launch working directory.
+    #[default]
+    WorkingDir,
+
+    /// Confine file access to the enclosing git repository root.
+    ///
+    /// Useful when launching from a subdirectory. Falls back to the working
+    /// directory when not inside a repository.
+    RepoRoot,
+}
+
+/// Controls how session files are written to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionWriteMode {
+    /// Use durable writes when the sessions directory is on a network mount.
+    #[default]
+    Auto,
+
+    /// Write to a temp file and rename it into place.
+    Fast,
+
+    /// Additionally fsync the file and directory and verify the written content.
+    Durable,
+}
+
+/// Controls what Ctrl+C does in the interactive TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CtrlCMode {
+    /// Ctrl+C exits immediately.
+    #[default]
+    Exit,
+
+    /// The first Ctrl+C cancels the in-flight stream or tool and returns to
+    /// input; a second press shortly after exits.
+    CancelThenExit,
+}
+
+/// Baseline permission mode applied to tool execution.
+///
+/// Persistent permission rules and session grants apply on top of the mode,
+/// except that read-only mode denies mutating tools regardless of rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PermissionMode {
+    /// Prompt for tools not covered by a rule (default).
+    #[default]
+    Ask,
+
+    /// Deny any tool that could modify files or system state.
+    ReadOnly,
+
+    /// Allow every tool without prompting; deny rules still apply.
+    FullAccess,
+}
+
+impl PermissionMode {
+    /// Returns the mode name as written in settings.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ask => "ask",
+            Self::ReadOnly => "read-only",
+            Self::FullAccess => "full-access",
+        }
+    }
+}
+
+/// Controls how model reasoning ("thinking") content is displayed.
+///
+/// Thinking is never part of the assistant's answer text; outside of
+/// [`ThinkingDisplay::Shown`] it is also excluded from copied output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThinkingDisplay {
+    /// Thinking is not displayed at all.
+    Hidden,
+
+    /// Thinking is summarized on a single collapsed line (default).
+    #[default]
+    Collapsed,
+
+    /// Thinking is shown inline, dimmed.
+    Shown,
+}
+
+impl ThinkingDisplay {
+    /// Returns the mode name as written in settings.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hidden => "hidden",
+            Self::Collapsed => "collapsed",
+            Self::Shown => "shown",
+        }
+    }
+}
+
+impl std::str::FromStr for ThinkingDisplay {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hidden" => Ok(Self::Hidden),
+            "collapsed" => Ok(Self::Collapsed),
+            "shown" => Ok(Self::Shown),
+            other => anyhow::bail!(
+                "unknown thinking display mode '{other}' (expected hidden, collapsed or shown)"
+            ),
+        }
+    }
+}
+
+/// Output of print mode (`-p`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// The response is streamed to stdout as plain text (default).
+    #[default]
+    Text,
+
+    /// The whole run is printed as one JSON object when it ends.
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => anyhow::bail!("unknown output format '{other}' (expected text or json)"),
+        }
+    }
+}
+
 /// Controls how narsil-mcp integration is enabled.
 ///
 /// Narsil provides code intelligence and security scanning capabilities.
@@ -82,6 +230,9 @@ pub enum NarsilMode {
     Disabled,
 }
 
+/// Model used when none is specified on the command line.
+pub const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
+
 /// Application configuration.
 ///
 /// Contains all settings needed to initialize and run the Patina application.
@@ -94,7 +245,9 @@ pub enum NarsilMode {
 /// # Examples
 ///
 /// ```no_run
-/// use patina::types::config::{Config, NarsilMode, ParallelMode, ResumeMode};
+/// use patina::types::config::{Config, NarsilMode, OutputFormat, ParallelMode, ResumeMode};
+/// use patina::api::SummaryStyle;
+/// use patina::types::Settings;
 /// use secrecy::SecretString;
 /// use std::path::PathBuf;
 ///
@@ -108,13 +261,19 @@ pub enum NarsilMode {
 ///     skip_permissions: false,
 ///     initial_prompt: None,
 ///     print_mode: false,
+///     output_format: OutputFormat::Text,
+///     auto_mode: false,
 ///     vision_model: None,
 ///     oauth_client_id: None,
 ///     initial_images: Vec::new(),
 ///     plugins_enabled: true,
+///     watch_plugins: false,
 ///     subagents_enabled: false,
 ///     ide_port: None,
 ///     auto_context_enabled: true,
+///     backups_enabled: true,
+///     summary_style: SummaryStyle::default(),
+///     settings: Settings::default(),
 /// };
 /// ```
 pub struct Config {
@@ -169,6 +328,19 @@ pub struct Config {
     /// - Exits when complete
     pub print_mode: bool,
 
+    /// How print mode writes its output.
+    ///
+    /// Set with `--output-format`.
+    pub output_format: OutputFormat,
+
+    /// Whether to run in autonomous mode (non-interactive).
+    ///
+    /// When true (and `initial_prompt` is set), tools are approved
+    /// automatically and the conversation continues until Claude is done or
+    /// a guardrail trips: the iteration limit, the cost budget, or a denied
+    /// dangerous command. See [`crate::app::auto`].
+    pub auto_mode: bool,
+
     /// Optional model to use for vision (image) requests.
     ///
     /// When set, messages containing images will automatically use this model
@@ -198,6 +370,12 @@ pub struct Config {
     /// Disable with `--no-plugins` CLI flag.
     pub plugins_enabled: bool,
 
+    /// Whether to reload plugins when their files change.
+    ///
+    /// Enable with `--watch-plugins` CLI flag. Has no effect when plugins
+    /// are disabled.
+    pub watch_plugins: bool,
+
     /// Whether subagent orchestration is enabled.
     ///
     /// When true, the `SubagentSpawner` is initialized and subagents can be
@@ -224,6 +402,25 @@ pub struct Config {
     ///
     /// Disable with `--no-auto-context` CLI flag.
     pub auto_context_enabled: bool,
+
+    /// Whether files are backed up to `.rct_backups` before tools modify them.
+    ///
+    /// Default: `true`
+    ///
+    /// Disable with `--no-backups` CLI flag.
+    pub backups_enabled: bool,
+
+    /// How history is summarized when it is compacted.
+    ///
+    /// Default: [`SummaryStyle::Timeline`] (`detailed`)
+    ///
+    /// Set with `--summary-style`; change at runtime with `/compact <style>`.
+    pub summary_style: SummaryStyle,
+
+    /// User settings loaded from `settings.toml`.
+    ///
+    /// Holds optional configuration such as named request parameter presets.
+    pub settings: Settings,
 }
 
 impl Config {
@@ -263,13 +460,19 @@ impl Config {
             skip_permissions: false,
             initial_prompt: None,
             print_mode: false,
+            output_format: OutputFormat::Text,
+            auto_mode: false,
             vision_model: None,
             oauth_client_id: None,
             initial_images: Vec::new(),
             plugins_enabled: true,
+            watch_plugins: false,
             subagents_enabled: false,
             ide_port: None,
             auto_context_enabled: true,
+            backups_enabled: true,
+            summary_style: SummaryStyle::default(),
+            settings: Settings::default(),
         }
     }
 
@@ -437,12 +640,35 @@ impl Config {
         self
     }
 
+    /// Sets how print mode writes its output.
+    #[must_use]
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = format;
+        self
+    }
+
     /// Returns whether print mode is enabled.
     #[must_use]
     pub fn print_mode(&self) -> bool {
         self.print_mode
     }
 
+    /// Enables autonomous mode (non-interactive).
+    ///
+    /// In auto mode with an initial prompt, tools run without prompting and
+    /// the conversation continues until done or a guardrail trips.
+    #[must_use]
+    pub fn with_auto_mode(mut self, enabled: bool) -> Self {
+        self.auto_mode = enabled;
+        self
+    }
+
+    /// Returns whether autonomous mode is enabled.
+    #[must_use]
+    pub fn auto_mode(&self) -> bool {
+        self.auto_mode
+    }
+
     /// Sets the vision model for image requests.
     ///
     /// When set, messages containing images will automatically use this model
@@ -542,6 +768,40 @@ impl Config {
     pub fn auto_context_enabled(&self) -> bool {
         self.auto_context_enabled
     }
+
+    /// Enables or disables file backups before tools modify files.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - If false, `write_file` and `edit` skip creating backups
+    #[must_use]
+    pub fn with_backups_enabled(mut self, enabled: bool) -> Self {
+        self.backups_enabled = enabled;
+        self
+    }
+
+    /// Returns whether file backups are enabled.
+    #[must_use]
+    pub fn backups_enabled(&self) -> bool {
+        self.backups_enabled
+    }
+
+    /// Sets the user settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings loaded from `settings.toml`
+    #[must_use]
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Returns the user settings.
+    #[must_use]
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
 }
 
 #[cfg(test)]
@@ -573,13 +833,19 @@ mod tests {
             skip_permissions: false,
             initial_prompt: None,
             print_mode: false,
+            output_format: OutputFormat::Text,
+            auto_mode: false,
             vision_model: None,
             oauth_client_id: None,
             initial_images: Vec::new(),
             plugins_enabled: true,
+            watch_plugins: false,
             subagents_enabled: false,
             ide_port: None,
             auto_context_enabled: true,
+            backups_enabled: true,
+            summary_style: SummaryStyle::default(),
+            settings: Settings::default(),
         };
 
         assert_eq!(config.model(), "claude-opus-4-20250514");
@@ -598,13 +864,19 @@ mod tests {
             skip_permissions: false,
             initial_prompt: None,
             print_mode: false,
+            output_format: OutputFormat::Text,
+            auto_mode: false,
             vision_model: None,
             oauth_client_id: None,
             initial_images: Vec::new(),
             plugins_enabled: true,
+            watch_plugins: false,
             subagents_enabled: false,
             ide_port: None,
             auto_context_enabled: true,
+            backups_enabled: true,
+            summary_style: SummaryStyle::default(),
+            settings: Settings::default(),
         };
 
         assert_eq!(config.working_dir(), &path);
@@ -797,6 +1069,15 @@ mod tests {
         assert!(config.print_mode());
     }
 
+    #[test]
+    fn test_config_with_auto_mode() {
+        let config = Config::new(SecretString::new("key".into()), "model", PathBuf::from("."));
+        assert!(!config.auto_mode());
+
+        let config = config.with_auto_mode(true);
+        assert!(config.auto_mode());
+    }
+
     // =========================================================================
     // Vision model tests
     // =========================================================================
@@ -956,4 +1237,41 @@ mod tests {
 
         assert!(config.auto_context_enabled());
     }
+
+    #[test]
+    fn test_config_backups_enabled_by_default() {
+        let config = Config::new(SecretString::new("key".into()), "model", PathBuf::from("."));
+        assert!(config.backups_enabled());
+
+        let config = config.with_backups_enabled(false);
+        assert!(!config.backups_enabled());
+    }
+
+    // =========================================================================
+    // Settings tests
+    // =========================================================================
+
+    #[test]
+    fn test_config_default_settings() {
+        let config = Config::new(SecretString::new("key".into()), "model", PathBuf::from("."));
+
+        assert!(config.settings().presets.is_empty());
+    }
+
+    #[test]
+    fn test_config_with_settings() {
+        let mut settings = Settings::default();
+        settings.presets.insert(
+            "precise".to_string(),
+            crate::types::RequestParams {
+                temperature: Some(0.2),
+                ..Default::default()
+            },
+        );
+
+        let config = Config::new(SecretString::new("key".into()), "model", PathBuf::from("."))
+            .with_settings(settings);
+
+        assert!(config.settings().preset("precise").is_some());
+    }
 }
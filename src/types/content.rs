@@ -230,6 +230,9 @@ impl ToolResultBlock {
     }
 
     /// Creates a new error tool result.
+    ///
+    /// Use this when the tool ran and reported an error (e.g. a command
+    /// exited nonzero).
     #[must_use]
     pub fn error(tool_use_id: impl Into<String>, error: impl Into<String>) -> Self {
         Self {
@@ -238,6 +241,34 @@ impl ToolResultBlock {
             is_error: true,
         }
     }
+
+    /// Creates a result for a tool that failed to run at all.
+    ///
+    /// The content is prefixed with [`TOOL_FAILURE_PREFIX`] so the model can
+    /// tell a broken tool apart from an error in its own input.
+    #[must_use]
+    pub fn failure(tool_use_id: impl Into<String>, error: impl std::fmt::Display) -> Self {
+        Self {
+            tool_use_id: tool_use_id.into(),
+            content: format!("{TOOL_FAILURE_PREFIX}{error}"),
+            is_error: true,
+        }
+    }
+
+    /// Returns true if the tool failed to run, as opposed to reporting an error.
+    #[must_use]
+    pub fn is_failure(&self) -> bool {
+        self.is_error && is_tool_failure(&self.content)
+    }
+}
+
+/// Prefix of the content of a [`ToolResultBlock::failure`].
+pub const TOOL_FAILURE_PREFIX: &str = "[tool failure: the tool could not be executed] ";
+
+/// Returns true if tool result content marks an execution failure.
+#[must_use]
+pub fn is_tool_failure(content: &str) -> bool {
+    content.starts_with(TOOL_FAILURE_PREFIX)
 }
 
 /// The reason why Claude stopped generating.
@@ -262,6 +293,10 @@ pub enum StopReason {
 
     /// Stop sequence was encountered.
     StopSequence,
+
+    /// Claude declined to respond.
+    /// The response may contain no content blocks at all.
+    Refusal,
 }
 
 impl StopReason {
@@ -283,7 +318,16 @@ impl StopReason {
     /// Returns true if this is a terminal state (no more automatic actions).
     #[must_use]
     pub fn is_terminal(&self) -> bool {
-        matches!(self, Self::EndTurn | Self::MaxTokens | Self::StopSequence)
+        matches!(
+            self,
+            Self::EndTurn | Self::MaxTokens | Self::StopSequence | Self::Refusal
+        )
+    }
+
+    /// Returns true if Claude declined to respond.
+    #[must_use]
+    pub fn is_refusal(&self) -> bool {
+        matches!(self, Self::Refusal)
     }
 }
 
@@ -436,6 +480,19 @@ mod tests {
         assert!(reason.is_terminal());
     }
 
+    #[test]
+    fn test_stop_reason_refusal() {
+        let reason = StopReason::Refusal;
+        assert!(reason.is_refusal());
+        assert!(!reason.needs_tool_execution());
+        assert!(!reason.should_continue());
+        assert!(reason.is_terminal());
+        assert!(!StopReason::EndTurn.is_refusal());
+
+        let json = serde_json::to_string(&reason).expect("serialization should succeed");
+        assert_eq!(json, "\"refusal\"");
+    }
+
     #[test]
     fn test_stop_reason_serialization() {
         let reason = StopReason::ToolUse;
@@ -527,6 +584,15 @@ mod tests {
         assert!(block.is_error);
     }
 
+    #[test]
+    fn test_tool_result_block_failure() {
+        let block = ToolResultBlock::failure("id", "spawn failed");
+        assert_eq!(block.content, format!("{TOOL_FAILURE_PREFIX}spawn failed"));
+        assert!(block.is_error);
+        assert!(block.is_failure());
+        assert!(!ToolResultBlock::error("id", "exit 1").is_failure());
+    }
+
     #[test]
     fn test_content_block_accessors_return_none_for_wrong_type() {
         let text_block = ContentBlock::text("text");
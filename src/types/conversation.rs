@@ -55,6 +55,11 @@ pub enum ConversationEntry {
         complete: bool,
     },
 
+    /// Model reasoning that preceded an assistant response.
+    ///
+    /// Kept separate from the response so it is never part of the answer.
+    Thinking(String),
+
     /// A tool execution block with optional result.
     ToolExecution {
         /// Name of the tool (e.g., "bash", "read_file").
@@ -70,6 +75,17 @@ pub enum ConversationEntry {
         follows_message_idx: Option<usize>,
     },
 
+    /// Partial output streamed by a tool that is still running.
+    ///
+    /// Sits directly after its tool execution entry and is removed once the
+    /// tool's final result arrives.
+    ToolProgress {
+        /// Name of the running tool.
+        name: String,
+        /// Partial output received so far.
+        text: String,
+    },
+
     /// An image for display in the conversation.
     ///
     /// Contains the decoded pixel data ready for TUI rendering.
@@ -104,6 +120,12 @@ impl ConversationEntry {
         matches!(self, Self::Streaming { .. })
     }
 
+    /// Returns `true` if this is a thinking entry.
+    #[must_use]
+    pub fn is_thinking(&self) -> bool {
+        matches!(self, Self::Thinking(_))
+    }
+
     /// Returns `true` if this is a tool execution entry.
     #[must_use]
     pub fn is_tool_execution(&self) -> bool {
@@ -119,13 +141,17 @@ impl ConversationEntry {
     /// Returns the text content if this entry has displayable text.
     ///
     /// Returns `Some(&str)` for user messages, assistant messages, and streaming entries.
-    /// Returns `None` for tool execution and image display entries (use structured accessors instead).
+    /// Returns `None` for thinking, tool and image display entries (use structured
+    /// accessors instead).
     #[must_use]
     pub fn text(&self) -> Option<&str> {
         match self {
             Self::UserMessage(text) | Self::AssistantMessage(text) => Some(text),
             Self::Streaming { text, .. } => Some(text),
-            Self::ToolExecution { .. } | Self::ImageDisplay { .. } => None,
+            Self::Thinking(_)
+            | Self::ToolExecution { .. }
+            | Self::ToolProgress { .. }
+            | Self::ImageDisplay { .. } => None,
         }
     }
 
@@ -156,6 +182,7 @@ impl fmt::Display for ConversationEntry {
                 let status = if *complete { "complete" } else { "streaming" };
                 write!(f, "Assistant ({status}): {text}")
             }
+            Self::Thinking(text) => write!(f, "Thinking: {text}"),
             Self::ToolExecution {
                 name,
                 input,
@@ -167,6 +194,7 @@ impl fmt::Display for ConversationEntry {
                 let out = output.as_deref().unwrap_or("(pending)");
                 write!(f, "Tool[{name}] ({status}): {input} -> {out}")
             }
+            Self::ToolProgress { name, text } => write!(f, "Tool[{name}] (partial): {text}"),
             Self::ImageDisplay {
                 width,
                 height,
@@ -311,6 +339,27 @@ impl Timeline {
         }
     }
 
+    /// Appends reasoning to the thinking entry of the current response.
+    ///
+    /// While streaming, the thinking entry sits directly before the streaming
+    /// entry and is created on the first call. Otherwise the text is appended
+    /// to a trailing thinking entry, or a new one is pushed.
+    pub fn append_thinking(&mut self, text: &str) {
+        let insert_at = self.streaming_idx.unwrap_or(self.entries.len());
+        if let Some(ConversationEntry::Thinking(existing)) =
+            insert_at.checked_sub(1).map(|idx| &mut self.entries[idx])
+        {
+            existing.push_str(text);
+            return;
+        }
+
+        self.entries
+            .insert(insert_at, ConversationEntry::Thinking(text.to_string()));
+        if let Some(idx) = self.streaming_idx.as_mut() {
+            *idx += 1;
+        }
+    }
+
     /// Returns mutable access to the streaming text, if streaming.
     #[must_use]
     pub fn streaming_text_mut(&mut self) -> Option<&mut String> {
@@ -325,14 +374,25 @@ impl Timeline {
 
     /// Finalizes the streaming entry as a complete assistant message.
     ///
-    /// Converts the streaming entry in-place to an `AssistantMessage`.
-    pub fn finalize_streaming_as_message(&mut self) {
-        if let Some(idx) = self.streaming_idx.take() {
-            if let ConversationEntry::Streaming { text, .. } = &self.entries[idx] {
-                let content = text.clone();
-                self.entries[idx] = ConversationEntry::AssistantMessage(content);
-            }
+    /// Converts the streaming entry in-place to an `AssistantMessage`. If the
+    /// response carried no text (e.g. a refusal with an empty content array),
+    /// the streaming entry is removed instead so no empty message is displayed.
+    ///
+    /// # Returns
+    ///
+    /// The finalized text, or `None` if not streaming or the response was empty.
+    pub fn finalize_streaming_as_message(&mut self) -> Option<String> {
+        let idx = self.streaming_idx.take()?;
+        let ConversationEntry::Streaming { text, .. } = &self.entries[idx] else {
+            return None;
+        };
+        if text.trim().is_empty() {
+            self.entries.remove(idx);
+            return None;
         }
+        let content = text.clone();
+        self.entries[idx] = ConversationEntry::AssistantMessage(content.clone());
+        Some(content)
     }
 
     /// Finalizes the streaming entry for tool use, returning the accumulated text.
@@ -464,6 +524,59 @@ impl Timeline {
                 }
             }
         }
+        self.clear_tool_progress(tool_name);
+    }
+
+    /// Appends partial output to the most recent running tool with the given name.
+    ///
+    /// The output is shown in a progress entry directly after the tool, created
+    /// on the first call. Does nothing if no such tool is running.
+    pub fn append_tool_progress(&mut self, tool_name: &str, text: &str) {
+        let Some(tool_idx) = self.entries.iter().rposition(|e| {
+            matches!(e, ConversationEntry::ToolExecution { name, output: None, .. } if name == tool_name)
+        }) else {
+            return;
+        };
+
+        let progress_idx = tool_idx + 1;
+        if let Some(ConversationEntry::ToolProgress {
+            name,
+            text: existing,
+        }) = self.entries.get_mut(progress_idx)
+        {
+            if name == tool_name {
+                existing.push('\n');
+                existing.push_str(text);
+                return;
+            }
+        }
+
+        self.entries.insert(
+            progress_idx,
+            ConversationEntry::ToolProgress {
+                name: tool_name.to_string(),
+                text: text.to_string(),
+            },
+        );
+        if let Some(idx) = self.streaming_idx.as_mut() {
+            if *idx >= progress_idx {
+                *idx += 1;
+            }
+        }
+    }
+
+    /// Removes the partial output shown for a tool once its result is in.
+    pub fn clear_tool_progress(&mut self, tool_name: &str) {
+        while let Some(idx) = self.entries.iter().position(
+            |e| matches!(e, ConversationEntry::ToolProgress { name, .. } if name == tool_name),
+        ) {
+            self.entries.remove(idx);
+            if let Some(streaming) = self.streaming_idx.as_mut() {
+                if *streaming > idx {
+                    *streaming -= 1;
+                }
+            }
+        }
     }
 }
 
@@ -495,6 +608,83 @@ mod tests {
         assert_eq!(format!("{tool}"), "Tool[bash] (success): ls -> files");
     }
 
+    #[test]
+    fn test_tool_progress_accumulates_until_result() {
+        let mut timeline = Timeline::new();
+        timeline.push_tool_execution("search", "query", None, false);
+        timeline.push_user_message("later");
+
+        timeline.append_tool_progress("search", "hit 1");
+        timeline.append_tool_progress("search", "hit 2");
+        assert_eq!(timeline.len(), 3);
+        assert_eq!(
+            timeline.entries()[1],
+            ConversationEntry::ToolProgress {
+                name: "search".to_string(),
+                text: "hit 1\nhit 2".to_string(),
+            }
+        );
+
+        timeline.update_tool_result("search", Some("hit 1\nhit 2\ndone".to_string()), false);
+        assert_eq!(timeline.len(), 2);
+        assert!(timeline.entries()[0].is_tool_execution());
+    }
+
+    #[test]
+    fn test_tool_progress_ignored_without_running_tool() {
+        let mut timeline = Timeline::new();
+        timeline.push_tool_execution("search", "query", Some("done".to_string()), false);
+
+        timeline.append_tool_progress("search", "late");
+        assert_eq!(timeline.len(), 1);
+    }
+
+    #[test]
+    fn test_append_thinking_precedes_streaming_entry() {
+        let mut timeline = Timeline::new();
+        timeline.push_user_message("Hello");
+        timeline.push_streaming();
+
+        timeline.append_thinking("Let me ");
+        timeline.append_to_streaming("Hi");
+        timeline.append_thinking("think.");
+
+        assert_eq!(timeline.len(), 3);
+        assert!(
+            matches!(&timeline.entries()[1], ConversationEntry::Thinking(t) if t == "Let me think.")
+        );
+        assert_eq!(
+            timeline.finalize_streaming_as_message().as_deref(),
+            Some("Hi")
+        );
+        assert!(timeline.entries()[2].is_assistant());
+    }
+
+    #[test]
+    fn test_finalize_empty_streaming_discards_entry() {
+        let mut timeline = Timeline::new();
+        timeline.push_user_message("Hello");
+        timeline.push_streaming();
+
+        assert_eq!(timeline.finalize_streaming_as_message(), None);
+        assert!(!timeline.is_streaming());
+        assert_eq!(timeline.len(), 1);
+        assert!(timeline.entries()[0].is_user());
+    }
+
+    #[test]
+    fn test_finalize_streaming_returns_text() {
+        let mut timeline = Timeline::new();
+        timeline.push_streaming();
+        timeline.append_to_streaming("Hi there!");
+
+        assert_eq!(
+            timeline.finalize_streaming_as_message(),
+            Some("Hi there!".to_string())
+        );
+        assert!(timeline.entries()[0].is_assistant());
+    }
+
     #[test]
     fn test_timeline_error_display() {
         let err = TimelineError::AlreadyStreaming;
@@ -221,6 +221,14 @@ impl MediaType {
         }
     }
 
+    /// Detects the media type of raw image data from its magic bytes.
+    ///
+    /// Returns `None` for data that is not PNG, JPEG, GIF or WebP.
+    #[must_use]
+    pub fn from_magic_bytes(bytes: &[u8]) -> Option<Self> {
+        detect_media_type(bytes)
+    }
+
     /// Creates a `MediaType` from a file extension.
     ///
     /// # Arguments
@@ -6,6 +6,8 @@
 //! - [`config`] - Application configuration types
 //! - [`content`] - Content block types for API messages (text, tool_use, tool_result)
 //! - [`message`] - Message and Role types for conversation handling
+//! - [`params`] - Sampling parameters and named presets for API requests
+//! - [`settings`] - User settings loaded from `settings.toml`
 //! - [`stream`] - Stream event types for API response handling
 //!
 //! # Re-exports
@@ -21,6 +23,8 @@ pub mod content;
 pub mod conversation;
 pub mod image;
 pub mod message;
+pub mod params;
+pub mod settings;
 pub mod stream;
 
 // Re-export common types for convenience
@@ -28,6 +32,8 @@ pub use config::Config;
 pub use content::{ContentBlock, StopReason, ToolResultBlock, ToolUseBlock};
 pub use image::{ImageContent, ImageError, ImageSource, MediaType};
 pub use message::{ApiMessageV2, Message, MessageContent, Role};
+pub use params::{RequestParams, RequestPreset};
+pub use settings::Settings;
 pub use stream::{StreamEvent, ToolUseAccumulator};
 
 // Unified timeline types
@@ -0,0 +1,100 @@
+//! Model request parameters.
+//!
+//! [`RequestParams`] holds the sampling parameters sent with each API request.
+//! Named presets of these parameters can be defined in user settings and
+//! applied per session with the `/preset` command.
+
+use serde::{Deserialize, Serialize};
+
+/// Sampling parameters for API requests.
+///
+/// Unset fields are omitted from the request so the API defaults apply.
+///
+/// # Examples
+///
+/// ```
+/// use patina::types::RequestParams;
+///
+/// let precise = RequestParams {
+///     temperature: Some(0.2),
+///     ..Default::default()
+/// };
+/// assert!(precise.top_p.is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestParams {
+    /// Sampling temperature (0.0 - 1.0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling threshold (0.0 - 1.0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Maximum number of tokens to generate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl RequestParams {
+    /// Returns `true` if no parameters are set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.temperature.is_none() && self.top_p.is_none() && self.max_tokens.is_none()
+    }
+}
+
+/// A named set of request parameters applied to a session.
+///
+/// The parameters are stored alongside the name so a resumed session keeps
+/// the values it was using even if the preset definition later changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestPreset {
+    /// The preset name, as defined in settings.
+    pub name: String,
+
+    /// The parameters the preset applies.
+    pub params: RequestParams,
+}
+
+impl RequestPreset {
+    /// Creates a new named preset.
+    #[must_use]
+    pub fn new(name: impl Into<String>, params: RequestParams) -> Self {
+        Self {
+            name: name.into(),
+            params,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_params_default_is_empty() {
+        assert!(RequestParams::default().is_empty());
+    }
+
+    #[test]
+    fn test_request_params_skips_unset_fields() {
+        let params = RequestParams {
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json, serde_json::json!({ "temperature": 0.5 }));
+    }
+
+    #[test]
+    fn test_request_params_from_toml() {
+        let params: RequestParams = toml::from_str("temperature = 1.0\nmax_tokens = 2048").unwrap();
+
+        assert_eq!(params.temperature, Some(1.0));
+        assert_eq!(params.max_tokens, Some(2048));
+        assert!(params.top_p.is_none());
+    }
+}
@@ -0,0 +1,1121 @@
+//! User settings loaded from `settings.toml`.
+//!
+//! Settings live in the platform config directory next to `permissions.json`:
+//! - Linux: `~/.config/patina/settings.toml`
+//! - macOS: `~/Library/Application Support/com.patina.patina/settings.toml`
+//!
+//! Every section is optional; a missing file yields the defaults.
+//!
+//! # Example
+//!
+//! ```toml
+//! # Confine file tools to the git repository root instead of the cwd
+//! path_confinement = "repo_root"
+//!
+//! # Redact API keys, tokens and passwords from saved session files
+//! redact_sessions = true
+//!
+//! # Fsync and verify session writes (default: only on network mounts)
+//! session_write_mode = "durable"
+//!
+//! # Cap concurrent API requests across the conversation and its subagents
+//! max_concurrent_requests = 4
+//!
+//! # Title new sessions with a short summary generated by this model
+//! title_model = "claude-3-haiku-20240307"
+//!
+//! # Use the 1M-token context window beta (Sonnet 4 models)
+//! large_context = true
+//!
+//! # First Ctrl+C cancels the current response, a second one exits
+//! ctrl_c = "cancel-then-exit"
+//!
+//! # Permission mode when no directory rule matches (`ask`, `read-only`
+//! # or `full-access`)
+//! permission_mode = "ask"
+//!
+//! # Estimated spend in USD after which the session pauses for confirmation
+//! session_budget = 5.0
+//!
+//! # Model responses allowed per prompt in --auto mode
+//! auto_max_iterations = 40
+//!
+//! # Commands --auto mode may run besides read-only ones; they run project code
+//! auto_allowed_commands = ["cargo test"]
+//!
+//! # Summarize older history once the conversation fills 70% of the window
+//! auto_compact_threshold = 0.7
+//!
+//! # Save pastes larger than this many bytes to a file instead of inlining
+//! paste_file_threshold = 65536
+//!
+//! # Save tool output larger than this many bytes to a file the model can read
+//! tool_output_file_threshold = 32768
+//!
+//! # How model reasoning is displayed (`hidden`, `collapsed` or `shown`)
+//! thinking_display = "collapsed"
+//!
+//! # Tools never advertised to any model
+//! disabled_tools = ["web_search"]
+//!
+//! # Batch streamed text for up to 50ms or 512 characters before redrawing
+//! [stream_flush]
+//! interval_ms = 50
+//! max_chars = 512
+//!
+//! # Permission mode by launch directory; the most specific match wins
+//! [directory_permission_modes]
+//! "/opt" = "read-only"
+//! "~" = "read-only"
+//! "~/projects" = "full-access"
+//!
+//! # Tools advertised per model, matched by the longest model-name prefix
+//! [tool_profiles."claude-3-haiku"]
+//! disabled_tools = ["web_fetch"]
+//!
+//! [tool_profiles."claude-opus-4"]
+//! tools = ["bash", "read_file", "edit", "grep"]
+//!
+//! # Give bash commands the variables in the project's .env
+//! load_dotenv = true
+//!
+//! # Run the hooks in these projects' .patina/hooks.toml
+//! trusted_projects = ["/home/me/src/patina"]
+//!
+//! # In a worktree, commit after each turn that edited files
+//! auto_commit_worktree = true
+//!
+//! # Let web_fetch reach internal hosts and cap redirects
+//! [web_fetch]
+//! allowed_hosts = ["wiki.corp.example", "*.docs.internal"]
+//! max_redirects = 3
+//!
+//! # Search through a self-hosted SearXNG instance instead of DuckDuckGo
+//! [web_search]
+//! provider = "searxng"
+//! base_url = "https://search.internal.example"
+//!
+//! # Downscale images for analyze_image to at most 1568px on the long edge
+//! [vision]
+//! max_dimension = 1568
+//!
+//! # Keep a hash-chained log of every tool run, rotated at 10 MiB
+//! [audit]
+//! enabled = true
+//! path = "/var/log/patina/tools.jsonl"
+//! max_file_bytes = 10485760
+//! max_files = 5
+//!
+//! # Secrets are redacted from tool output before the model, the session and
+//! # the audit log see it; add patterns for in-house credential formats
+//! [redaction]
+//! enabled = true
+//! high_entropy = true
+//! patterns = ['\bcorp_[a-z0-9]{24}\b']
+//!
+//! # Tool timeout in seconds; a call running longer is cancelled
+//! tool_timeout_secs = 120
+//!
+//! # MCP servers whose tools are offered as mcp__<server>__<tool>
+//! [mcp_servers.docs]
+//! enabled = true
+//! transport = { type = "stdio", command = "/usr/local/bin/docs-mcp", args = ["--stdio"] }
+//!
+//! # Safety classes for MCP tools, so read-only ones run in parallel.
+//! # A trailing `*` matches by prefix; exact names win.
+//! [mcp_tool_safety]
+//! "mcp__docs__*" = "read-only"
+//! "mcp__docs__publish" = "mutating"
+//!
+//! [presets.precise]
+//! temperature = 0.2
+//!
+//! [presets.creative]
+//! temperature = 1.0
+//! top_p = 0.95
+//! max_tokens = 4096
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::enterprise::tool_audit::{ToolAuditConfig, DEFAULT_MAX_FILES, DEFAULT_MAX_FILE_BYTES};
+use crate::mcp::McpServerConfig;
+use crate::tools::parallel::ToolSafetyClass;
+use crate::tools::vision::VisionConfig;
+use crate::tools::web_fetch::WebFetchConfig;
+use crate::tools::web_search::{SearchProvider, WebSearchConfig};
+use crate::util::redact::Redactor;
+
+use super::config::{
+    CtrlCMode, PathConfinement, PermissionMode, SessionWriteMode, ThinkingDisplay,
+};
+use super::params::RequestParams;
+
+/// User settings for Patina.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Named request parameter presets, applied with `/preset <name>`.
+    pub presets: HashMap<String, RequestParams>,
+
+    /// Boundary that file tools are confined to (`working_dir` or `repo_root`).
+    pub path_confinement: PathConfinement,
+
+    /// Redact secrets from sessions before they are written to disk.
+    pub redact_sessions: bool,
+
+    /// How session files are written (`auto`, `fast` or `durable`).
+    pub session_write_mode: SessionWriteMode,
+
+    /// Maximum API requests in flight at once, shared with subagents.
+    ///
+    /// Unset means no limit.
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Model that generates a session title after the first exchange.
+    ///
+    /// Unset disables title generation.
+    pub title_model: Option<String>,
+
+    /// What Ctrl+C does in the TUI (`exit` or `cancel-then-exit`).
+    pub ctrl_c: CtrlCMode,
+
+    /// Enable the 1M-token context window beta.
+    ///
+    /// Raises the token budget and truncation limit to match.
+    pub large_context: bool,
+
+    /// Permission mode used when no directory rule matches the launch directory.
+    pub permission_mode: PermissionMode,
+
+    /// Permission modes keyed by directory, chosen by launch directory.
+    ///
+    /// A leading `~` stands for the home directory. The longest matching
+    /// directory wins.
+    pub directory_permission_modes: HashMap<String, PermissionMode>,
+
+    /// How model reasoning is displayed (`hidden`, `collapsed` or `shown`).
+    pub thinking_display: ThinkingDisplay,
+
+    /// Estimated spend in USD per session before the loop pauses.
+    ///
+    /// A warning is shown at 80%. Unset means no budget.
+    pub session_budget: Option<f64>,
+
+    /// Model responses allowed for one prompt in `--auto` mode.
+    ///
+    /// Unset means 25.
+    pub auto_max_iterations: Option<usize>,
+
+    /// Bash commands `--auto` mode may run besides read-only ones, matched
+    /// as argument prefixes (`"cargo test"` admits `cargo test --lib`).
+    ///
+    /// Empty by default: test and build commands run arbitrary project code.
+    pub auto_allowed_commands: Vec<String>,
+
+    /// Fraction of the context window at which older history is summarized
+    /// before the next request.
+    ///
+    /// Unset means 0.8.
+    pub auto_compact_threshold: Option<f64>,
+
+    /// Paste size in bytes above which pasted text is saved to a session file
+    /// and referenced as `@path` instead of being inlined.
+    ///
+    /// Unset means 100 KiB.
+    pub paste_file_threshold: Option<usize>,
+
+    /// Tool output size in bytes above which the output is saved to a
+    /// session file and the model gets the path and a preview instead.
+    ///
+    /// Unset means output is always inlined.
+    pub tool_output_file_threshold: Option<usize>,
+
+    /// Seconds a single tool call may run before it is cancelled and the
+    /// model gets a timeout error.
+    ///
+    /// Unset means no limit beyond each tool's own.
+    pub tool_timeout_secs: Option<u64>,
+
+    /// How streamed text is batched before the TUI redraws.
+    pub stream_flush: StreamFlush,
+
+    /// Tools that are never advertised to the model.
+    pub disabled_tools: Vec<String>,
+
+    /// Tool profiles keyed by model name prefix; the longest match applies.
+    ///
+    /// Profiles narrow the tools left after `disabled_tools`.
+    pub tool_profiles: HashMap<String, ToolProfile>,
+
+    /// MCP servers whose tools are offered to the model, keyed by server name.
+    ///
+    /// Tools are exposed as `mcp__<server>__<tool>`. Only servers with
+    /// `enabled = true` are started.
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+
+    /// Safety classes for MCP tools by exact name or `*`-terminated prefix.
+    ///
+    /// Undeclared MCP tools are `unknown` and run sequentially.
+    pub mcp_tool_safety: HashMap<String, ToolSafetyClass>,
+
+    /// Restrictions for the `web_fetch` tool.
+    pub web_fetch: WebFetchSettings,
+
+    /// Provider queried by the `web_search` tool.
+    pub web_search: WebSearchSettings,
+
+    /// Image handling for the `analyze_image` tool.
+    pub vision: VisionSettings,
+
+    /// Load the project `.env` into the environment of bash commands.
+    ///
+    /// Values are never added to the conversation or the saved session.
+    pub load_dotenv: bool,
+
+    /// Projects whose `.patina/hooks.toml` is loaded.
+    ///
+    /// Hooks run commands, so a repository's own hooks are ignored until its
+    /// directory is listed here. Unset loads only `hooks.toml` from the
+    /// config directory.
+    pub trusted_projects: Vec<PathBuf>,
+
+    /// Commit a worktree's changes after each turn in which edit tools
+    /// changed files.
+    ///
+    /// Only applies once the session is moved into a worktree with
+    /// `/worktree new` or `/worktree switch`.
+    pub auto_commit_worktree: bool,
+
+    /// Tamper-evident log of tool executions.
+    pub audit: AuditSettings,
+
+    /// Secret redaction applied to tool output.
+    pub redaction: RedactionSettings,
+}
+
+/// User-configurable redaction of secrets from tool output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSettings {
+    /// Whether tool output other than file contents is redacted at all.
+    pub enabled: bool,
+
+    /// Also redact long random-looking tokens that match no known format.
+    ///
+    /// Off by default, since hashes and encoded data trip it. File contents
+    /// read by `read_file` are never redacted.
+    pub high_entropy: bool,
+
+    /// Extra regular expressions whose matches are secrets.
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            high_entropy: false,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl RedactionSettings {
+    /// Returns the configured redactor, or `None` when redaction is off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is not a valid regular expression.
+    pub fn to_redactor(&self) -> Result<Option<Redactor>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        Redactor::new(&self.patterns, self.high_entropy).map(Some)
+    }
+}
+
+/// User-configurable tool audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditSettings {
+    /// Whether every tool execution is recorded.
+    pub enabled: bool,
+
+    /// Log file; defaults to `audit/tools.jsonl` in the data directory.
+    pub path: Option<PathBuf>,
+
+    /// Size in bytes past which the log is rotated.
+    pub max_file_bytes: u64,
+
+    /// Number of rotated files kept.
+    pub max_files: usize,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+impl AuditSettings {
+    /// Returns the audit log configuration, or `None` when auditing is off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no path is set and the data directory cannot be
+    /// determined.
+    pub fn to_config(&self) -> Result<Option<ToolAuditConfig>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => directories::ProjectDirs::from("com", "patina", "patina")
+                .context("Could not determine data directory for the audit log")?
+                .data_dir()
+                .join("audit")
+                .join("tools.jsonl"),
+        };
+        Ok(Some(ToolAuditConfig {
+            path,
+            max_file_bytes: self.max_file_bytes,
+            max_files: self.max_files,
+        }))
+    }
+}
+
+/// User-configurable `web_fetch` restrictions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebFetchSettings {
+    /// Hosts fetched even if they are localhost or private IPs.
+    ///
+    /// A leading `*.` matches any subdomain.
+    pub allowed_hosts: Vec<String>,
+
+    /// Maximum number of redirects to follow; more is an error.
+    pub max_redirects: usize,
+}
+
+impl Default for WebFetchSettings {
+    fn default() -> Self {
+        let config = WebFetchConfig::default();
+        Self {
+            allowed_hosts: config.allowed_hosts,
+            max_redirects: config.max_redirects,
+        }
+    }
+}
+
+impl WebFetchSettings {
+    /// Returns the tool configuration for these settings.
+    #[must_use]
+    pub fn to_config(&self) -> WebFetchConfig {
+        WebFetchConfig::default()
+            .with_allowed_hosts(self.allowed_hosts.clone())
+            .with_max_redirects(self.max_redirects)
+    }
+}
+
+/// User-configurable `web_search` provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSearchSettings {
+    /// Search provider: `duckduckgo` (default) or `searxng`.
+    pub provider: SearchProvider,
+
+    /// Endpoint of the provider; required for `searxng`.
+    pub base_url: Option<String>,
+}
+
+impl WebSearchSettings {
+    /// Returns the tool configuration for these settings.
+    #[must_use]
+    pub fn to_config(&self) -> WebSearchConfig {
+        let mut config = WebSearchConfig {
+            provider: self.provider,
+            ..Default::default()
+        };
+        if let Some(base_url) = &self.base_url {
+            config.base_url.clone_from(base_url);
+        }
+        config
+    }
+}
+
+/// User-configurable `analyze_image` settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisionSettings {
+    /// Longest edge in pixels to downscale larger images to; unset sends
+    /// images unchanged.
+    pub max_dimension: Option<u32>,
+}
+
+impl VisionSettings {
+    /// Returns the tool configuration for these settings.
+    #[must_use]
+    pub fn to_config(&self) -> VisionConfig {
+        VisionConfig {
+            max_dimension: self.max_dimension,
+            ..Default::default()
+        }
+    }
+}
+
+/// Batching of streamed text before the TUI redraws.
+///
+/// Text is held until `interval_ms` elapses or `max_chars` accumulate,
+/// whichever comes first. An `interval_ms` of 0 redraws on every delta.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamFlush {
+    /// Longest time text is held before it is rendered, in milliseconds.
+    pub interval_ms: u64,
+
+    /// Number of buffered characters that forces a render.
+    pub max_chars: usize,
+}
+
+impl Default for StreamFlush {
+    fn default() -> Self {
+        Self {
+            interval_ms: 30,
+            max_chars: 256,
+        }
+    }
+}
+
+/// The tools advertised to a model, layered on the global `disabled_tools`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolProfile {
+    /// Tools to advertise; unset means every default tool.
+    pub tools: Option<Vec<String>>,
+
+    /// Tools to withhold from this model.
+    pub disabled_tools: Vec<String>,
+}
+
+impl ToolProfile {
+    /// Returns true if this profile advertises the named tool.
+    #[must_use]
+    pub fn allows(&self, name: &str) -> bool {
+        self.tools
+            .as_ref()
+            .map_or(true, |tools| tools.iter().any(|t| t == name))
+            && !self.disabled_tools.iter().any(|t| t == name)
+    }
+}
+
+impl Settings {
+    /// Returns the default settings file path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config directory cannot be determined.
+    pub fn default_path() -> Result<PathBuf> {
+        let config_dir = directories::ProjectDirs::from("com", "patina", "patina")
+            .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+
+        Ok(config_dir.config_dir().join("settings.toml"))
+    }
+
+    /// Loads settings from a TOML file.
+    ///
+    /// A missing file is not an error and yields the default settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            tracing::debug!("Settings file not found at {:?}, using defaults", path);
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read settings file {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse settings file {}", path.display()))
+    }
+
+    /// Returns the preset with the given name, if defined.
+    #[must_use]
+    pub fn preset(&self, name: &str) -> Option<&RequestParams> {
+        self.presets.get(name)
+    }
+
+    /// Resolves the initial permission mode for a session launched in `dir`.
+    ///
+    /// Returns the mode and the `directory_permission_modes` key that
+    /// selected it, or the global `permission_mode` and `None` when no
+    /// directory rule matches.
+    #[must_use]
+    pub fn permission_mode_for(&self, dir: &Path) -> (PermissionMode, Option<&str>) {
+        let home = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        self.permission_mode_with_home(dir, home.as_deref())
+    }
+
+    fn permission_mode_with_home(
+        &self,
+        dir: &Path,
+        home: Option<&Path>,
+    ) -> (PermissionMode, Option<&str>) {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+        self.directory_permission_modes
+            .iter()
+            .filter_map(|(rule, mode)| {
+                let rule_dir = expand_home(rule, home)?;
+                let rule_dir = rule_dir.canonicalize().unwrap_or(rule_dir);
+                dir.starts_with(&rule_dir)
+                    .then(|| (rule_dir.components().count(), rule.as_str(), *mode))
+            })
+            // Break depth ties by rule text so the choice is deterministic
+            .max_by_key(|(depth, rule, _)| (*depth, *rule))
+            .map_or((self.permission_mode, None), |(_, rule, mode)| {
+                (mode, Some(rule))
+            })
+    }
+}
+
+/// Expands a leading `~` in a settings path to the home directory.
+///
+/// Returns `None` for `~` paths when the home directory is unknown.
+fn expand_home(path: &str, home: Option<&Path>) -> Option<PathBuf> {
+    if path == "~" {
+        return home.map(Path::to_path_buf);
+    }
+    match path.strip_prefix("~/") {
+        Some(rest) => home.map(|home| home.join(rest)),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_file_returns_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings = Settings::load(&temp_dir.path().join("settings.toml")).unwrap();
+
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_load_presets() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[presets.precise]\ntemperature = 0.2\n\n[presets.creative]\ntemperature = 1.0\ntop_p = 0.95\n",
+        )
+        .unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+
+        assert_eq!(settings.presets.len(), 2);
+        assert_eq!(settings.preset("precise").unwrap().temperature, Some(0.2));
+        assert_eq!(settings.preset("creative").unwrap().top_p, Some(0.95));
+        assert!(settings.preset("missing").is_none());
+    }
+
+    #[test]
+    fn test_load_path_confinement() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(
+            Settings::load(&path).unwrap().path_confinement,
+            PathConfinement::WorkingDir
+        );
+
+        std::fs::write(&path, "path_confinement = \"repo_root\"\n").unwrap();
+        assert_eq!(
+            Settings::load(&path).unwrap().path_confinement,
+            PathConfinement::RepoRoot
+        );
+    }
+
+    #[test]
+    fn test_load_redact_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "redact_sessions = true\n").unwrap();
+
+        assert!(Settings::load(&path).unwrap().redact_sessions);
+        assert!(!Settings::default().redact_sessions);
+    }
+
+    #[test]
+    fn test_load_session_write_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "session_write_mode = \"durable\"\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().session_write_mode,
+            SessionWriteMode::Durable
+        );
+        assert_eq!(
+            Settings::default().session_write_mode,
+            SessionWriteMode::Auto
+        );
+    }
+
+    #[test]
+    fn test_load_max_concurrent_requests() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "max_concurrent_requests = 3\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().max_concurrent_requests,
+            Some(3)
+        );
+        assert_eq!(Settings::default().max_concurrent_requests, None);
+    }
+
+    #[test]
+    fn test_load_ctrl_c_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "ctrl_c = \"cancel-then-exit\"\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().ctrl_c,
+            CtrlCMode::CancelThenExit
+        );
+        assert_eq!(Settings::default().ctrl_c, CtrlCMode::Exit);
+    }
+
+    #[test]
+    fn test_load_large_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "large_context = true\n").unwrap();
+
+        assert!(Settings::load(&path).unwrap().large_context);
+        assert!(!Settings::default().large_context);
+    }
+
+    #[test]
+    fn test_load_permission_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "permission_mode = \"read-only\"\n\n[directory_permission_modes]\n\"/opt\" = \"full-access\"\n",
+        )
+        .unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.permission_mode, PermissionMode::ReadOnly);
+        assert_eq!(
+            settings.directory_permission_modes.get("/opt"),
+            Some(&PermissionMode::FullAccess)
+        );
+        assert_eq!(Settings::default().permission_mode, PermissionMode::Ask);
+    }
+
+    #[test]
+    fn test_load_session_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "session_budget = 2.5\n").unwrap();
+
+        assert_eq!(Settings::load(&path).unwrap().session_budget, Some(2.5));
+        assert_eq!(Settings::default().session_budget, None);
+    }
+
+    #[test]
+    fn test_load_auto_max_iterations() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "auto_max_iterations = 40
+",
+        )
+        .unwrap();
+
+        assert_eq!(Settings::load(&path).unwrap().auto_max_iterations, Some(40));
+        assert_eq!(Settings::default().auto_max_iterations, None);
+    }
+
+    #[test]
+    fn test_load_auto_allowed_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "auto_allowed_commands = [\"cargo test\"]\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().auto_allowed_commands,
+            vec!["cargo test".to_string()]
+        );
+        assert!(Settings::default().auto_allowed_commands.is_empty());
+    }
+
+    #[test]
+    fn test_load_tool_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "disabled_tools = [\"web_search\"]\n\n[tool_profiles.\"claude-3-haiku\"]\ndisabled_tools = [\"web_fetch\"]\n\n[tool_profiles.coder]\ntools = [\"bash\", \"edit\"]\n",
+        )
+        .unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.disabled_tools, vec!["web_search".to_string()]);
+        let haiku = &settings.tool_profiles["claude-3-haiku"];
+        assert!(!haiku.allows("web_fetch"));
+        assert!(haiku.allows("bash"));
+        let coder = &settings.tool_profiles["coder"];
+        assert!(coder.allows("edit"));
+        assert!(!coder.allows("grep"));
+    }
+
+    #[test]
+    fn test_load_mcp_tool_safety() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[mcp_tool_safety]\n\"mcp__docs__*\" = \"read-only\"\n\"mcp__docs__publish\" = \"mutating\"\n",
+        )
+        .unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(
+            settings.mcp_tool_safety["mcp__docs__*"],
+            ToolSafetyClass::ReadOnly
+        );
+        assert_eq!(
+            settings.mcp_tool_safety["mcp__docs__publish"],
+            ToolSafetyClass::Mutating
+        );
+        assert!(Settings::default().mcp_tool_safety.is_empty());
+    }
+
+    #[test]
+    fn test_load_mcp_servers() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[mcp_servers.docs]\nenabled = true\ntransport = { type = \"stdio\", command = \"/usr/bin/docs-mcp\", args = [\"--stdio\"] }\n",
+        )
+        .unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        let docs = &settings.mcp_servers["docs"];
+        assert!(docs.enabled);
+        assert!(matches!(
+            &docs.transport,
+            crate::mcp::McpTransport::Stdio { command, args, .. }
+                if command == "/usr/bin/docs-mcp" && args == &["--stdio"]
+        ));
+        assert!(Settings::default().mcp_servers.is_empty());
+    }
+
+    #[test]
+    fn test_load_auto_compact_threshold() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "auto_compact_threshold = 0.7\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().auto_compact_threshold,
+            Some(0.7)
+        );
+        assert_eq!(Settings::default().auto_compact_threshold, None);
+    }
+
+    #[test]
+    fn test_load_paste_file_threshold() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "paste_file_threshold = 65536\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().paste_file_threshold,
+            Some(65536)
+        );
+        assert_eq!(Settings::default().paste_file_threshold, None);
+    }
+
+    #[test]
+    fn test_load_tool_output_file_threshold() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "tool_output_file_threshold = 32768\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().tool_output_file_threshold,
+            Some(32768)
+        );
+        assert_eq!(Settings::default().tool_output_file_threshold, None);
+    }
+
+    #[test]
+    fn test_load_stream_flush() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "[stream_flush]\ninterval_ms = 50\n").unwrap();
+
+        let stream_flush = Settings::load(&path).unwrap().stream_flush;
+        assert_eq!(stream_flush.interval_ms, 50);
+        assert_eq!(stream_flush.max_chars, 256);
+        assert_eq!(Settings::default().stream_flush, StreamFlush::default());
+    }
+
+    #[test]
+    fn test_load_web_fetch() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[web_fetch]\nallowed_hosts = [\"wiki.corp.example\"]\nmax_redirects = 2\n",
+        )
+        .unwrap();
+
+        let config = Settings::load(&path).unwrap().web_fetch.to_config();
+        assert_eq!(config.allowed_hosts, vec!["wiki.corp.example".to_string()]);
+        assert_eq!(config.max_redirects, 2);
+        assert!(!config.allow_localhost);
+        assert_eq!(Settings::default().web_fetch.max_redirects, 5);
+    }
+
+    #[test]
+    fn test_load_web_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[web_search]\nprovider = \"searxng\"\nbase_url = \"https://search.internal.example\"\n",
+        )
+        .unwrap();
+
+        let config = Settings::load(&path).unwrap().web_search.to_config();
+        assert_eq!(config.provider, SearchProvider::Searxng);
+        assert_eq!(config.base_url, "https://search.internal.example");
+
+        let default = Settings::default().web_search.to_config();
+        assert_eq!(default.provider, SearchProvider::DuckDuckGo);
+        assert_eq!(default.base_url, WebSearchConfig::default().base_url);
+    }
+
+    #[test]
+    fn test_load_vision() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "[vision]\nmax_dimension = 1568\n").unwrap();
+
+        let config = Settings::load(&path).unwrap().vision.to_config();
+        assert_eq!(config.max_dimension, Some(1568));
+        assert_eq!(Settings::default().vision.to_config().max_dimension, None);
+    }
+
+    #[test]
+    fn test_load_trusted_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "trusted_projects = [\"/src/app\"]\n").unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.trusted_projects, [PathBuf::from("/src/app")]);
+        assert!(Settings::default().trusted_projects.is_empty());
+    }
+
+    #[test]
+    fn test_load_dotenv_flag() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "load_dotenv = true\n").unwrap();
+
+        assert!(Settings::load(&path).unwrap().load_dotenv);
+        assert!(!Settings::default().load_dotenv);
+    }
+
+    #[test]
+    fn test_load_auto_commit_worktree_flag() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "auto_commit_worktree = true\n").unwrap();
+
+        assert!(Settings::load(&path).unwrap().auto_commit_worktree);
+        assert!(!Settings::default().auto_commit_worktree);
+    }
+
+    #[test]
+    fn test_load_audit_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[audit]\nenabled = true\npath = \"/var/log/patina/tools.jsonl\"\nmax_files = 2\n",
+        )
+        .unwrap();
+
+        let config = Settings::load(&path)
+            .unwrap()
+            .audit
+            .to_config()
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.path, PathBuf::from("/var/log/patina/tools.jsonl"));
+        assert_eq!(config.max_file_bytes, DEFAULT_MAX_FILE_BYTES);
+        assert_eq!(config.max_files, 2);
+        assert!(Settings::default().audit.to_config().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_redaction_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(
+            &path,
+            "[redaction]\nhigh_entropy = false\npatterns = ['\\bcorp_[a-z0-9]{12}\\b']\n",
+        )
+        .unwrap();
+
+        let redactor = Settings::load(&path)
+            .unwrap()
+            .redaction
+            .to_redactor()
+            .unwrap()
+            .unwrap();
+        assert_eq!(redactor.redact("corp_abcdef123456"), "[REDACTED]");
+        assert!(Settings::default()
+            .redaction
+            .to_redactor()
+            .unwrap()
+            .is_some());
+
+        std::fs::write(&path, "[redaction]\nenabled = false\n").unwrap();
+        let settings = Settings::load(&path).unwrap();
+        assert!(settings.redaction.to_redactor().unwrap().is_none());
+
+        std::fs::write(&path, "[redaction]\npatterns = ['(unclosed']\n").unwrap();
+        let settings = Settings::load(&path).unwrap();
+        assert!(settings.redaction.to_redactor().is_err());
+    }
+
+    #[test]
+    fn test_load_thinking_display() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "thinking_display = \"shown\"\n").unwrap();
+
+        assert_eq!(
+            Settings::load(&path).unwrap().thinking_display,
+            ThinkingDisplay::Shown
+        );
+        assert_eq!(
+            Settings::default().thinking_display,
+            ThinkingDisplay::Collapsed
+        );
+    }
+
+    #[test]
+    fn test_permission_mode_for_mapped_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let sensitive = temp_dir.path().join("opt");
+        let project = sensitive.join("project");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+
+        let mut settings = Settings::default();
+        settings.directory_permission_modes.insert(
+            sensitive.to_string_lossy().into_owned(),
+            PermissionMode::ReadOnly,
+        );
+        settings.directory_permission_modes.insert(
+            project.to_string_lossy().into_owned(),
+            PermissionMode::FullAccess,
+        );
+
+        let (mode, rule) = settings.permission_mode_for(&sensitive);
+        assert_eq!(mode, PermissionMode::ReadOnly);
+        assert_eq!(rule, Some(sensitive.to_str().unwrap()));
+
+        // The most specific directory wins, including for subdirectories
+        let (mode, rule) = settings.permission_mode_for(&project.join("src"));
+        assert_eq!(mode, PermissionMode::FullAccess);
+        assert_eq!(rule, Some(project.to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_permission_mode_for_unmapped_directory_uses_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let mapped = temp_dir.path().join("mapped");
+        let unmapped = temp_dir.path().join("mapped-sibling");
+        std::fs::create_dir_all(&mapped).unwrap();
+        std::fs::create_dir_all(&unmapped).unwrap();
+
+        let mut settings = Settings {
+            permission_mode: PermissionMode::Ask,
+            ..Settings::default()
+        };
+        settings.directory_permission_modes.insert(
+            mapped.to_string_lossy().into_owned(),
+            PermissionMode::ReadOnly,
+        );
+
+        assert_eq!(
+            settings.permission_mode_for(&unmapped),
+            (PermissionMode::Ask, None)
+        );
+    }
+
+    #[test]
+    fn test_permission_mode_for_expands_home() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        std::fs::create_dir_all(home.join("projects/app")).unwrap();
+
+        let mut settings = Settings::default();
+        settings
+            .directory_permission_modes
+            .insert("~".to_string(), PermissionMode::ReadOnly);
+        settings
+            .directory_permission_modes
+            .insert("~/projects".to_string(), PermissionMode::FullAccess);
+
+        assert_eq!(
+            settings.permission_mode_with_home(home, Some(home)),
+            (PermissionMode::ReadOnly, Some("~"))
+        );
+        assert_eq!(
+            settings.permission_mode_with_home(&home.join("projects/app"), Some(home)),
+            (PermissionMode::FullAccess, Some("~/projects"))
+        );
+        assert_eq!(
+            settings.permission_mode_with_home(home, None),
+            (PermissionMode::Ask, None)
+        );
+    }
+
+    #[test]
+    fn test_load_malformed_file_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.toml");
+        std::fs::write(&path, "presets = [not valid").unwrap();
+
+        assert!(Settings::load(&path).is_err());
+    }
+}
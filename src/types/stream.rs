@@ -56,11 +56,33 @@ use super::content::StopReason;
 ///     }
 /// }
 /// ```
+///
+/// # Serialization
+///
+/// Events serialize as adjacently tagged JSON, with a snake_case `type` and
+/// the variant's payload (if any) under `data`, so consumers outside this
+/// process can rely on a stable shape:
+///
+/// ```rust
+/// use patina::types::stream::StreamEvent;
+///
+/// let json = serde_json::to_string(&StreamEvent::ContentDelta("Hi".into())).unwrap();
+/// assert_eq!(json, r#"{"type":"content_delta","data":"Hi"}"#);
+///
+/// let json = serde_json::to_string(&StreamEvent::MessageStop).unwrap();
+/// assert_eq!(json, r#"{"type":"message_stop"}"#);
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum StreamEvent {
     /// A delta containing new content text.
     ContentDelta(String),
 
+    /// A delta of model reasoning from a `thinking` content block.
+    ///
+    /// Thinking is displayed separately and is never part of the answer text.
+    ThinkingDelta(String),
+
     /// A tool_use content block is starting.
     ///
     /// The tool ID is used to correlate tool results with tool calls.
@@ -109,6 +131,17 @@ pub enum StreamEvent {
         stop_reason: StopReason,
     },
 
+    /// Token usage reported by the API for this response.
+    ///
+    /// Emitted from `message_delta` before `MessageComplete`. `input_tokens`
+    /// comes from `message_start` unless the delta reports it directly.
+    Usage {
+        /// Tokens in the request (prompt, history, tools).
+        input_tokens: u32,
+        /// Tokens generated in the response so far.
+        output_tokens: u32,
+    },
+
     /// The message stream has completed (legacy, deprecated).
     ///
     /// Use `MessageComplete` instead for new code to access the stop_reason.
@@ -169,6 +202,18 @@ impl StreamEvent {
         }
     }
 
+    /// Extracts `(input_tokens, output_tokens)` if this is a usage event.
+    #[must_use]
+    pub fn usage(&self) -> Option<(u32, u32)> {
+        match self {
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+            } => Some((*input_tokens, *output_tokens)),
+            _ => None,
+        }
+    }
+
     /// Extracts the stop reason if this is a MessageComplete event.
     #[must_use]
     pub fn stop_reason(&self) -> Option<StopReason> {
@@ -309,6 +354,19 @@ mod tests {
         assert_eq!(stop.error(), None);
     }
 
+    #[test]
+    fn test_stream_event_usage() {
+        let usage = StreamEvent::Usage {
+            input_tokens: 120,
+            output_tokens: 45,
+        };
+        assert_eq!(usage.usage(), Some((120, 45)));
+        assert!(!usage.is_stop());
+
+        let stop = StreamEvent::MessageStop;
+        assert_eq!(stop.usage(), None);
+    }
+
     #[test]
     fn test_stream_event_stop_reason() {
         let complete = StreamEvent::MessageComplete {
@@ -416,4 +474,91 @@ mod tests {
         let acc = ToolUseAccumulator::default();
         assert!(!acc.is_active());
     }
+
+    #[test]
+    fn test_stream_event_serde_round_trip() {
+        let events = [
+            (
+                StreamEvent::ContentDelta("hello".to_string()),
+                serde_json::json!({ "type": "content_delta", "data": "hello" }),
+            ),
+            (
+                StreamEvent::ToolUseStart {
+                    id: "toolu_1".to_string(),
+                    name: "bash".to_string(),
+                    index: 1,
+                },
+                serde_json::json!({
+                    "type": "tool_use_start",
+                    "data": { "id": "toolu_1", "name": "bash", "index": 1 }
+                }),
+            ),
+            (
+                StreamEvent::ToolUseInputDelta {
+                    index: 1,
+                    partial_json: "{\"command\":".to_string(),
+                },
+                serde_json::json!({
+                    "type": "tool_use_input_delta",
+                    "data": { "index": 1, "partial_json": "{\"command\":" }
+                }),
+            ),
+            (
+                StreamEvent::ToolUseComplete { index: 1 },
+                serde_json::json!({ "type": "tool_use_complete", "data": { "index": 1 } }),
+            ),
+            (
+                StreamEvent::ContentBlockComplete { index: 0 },
+                serde_json::json!({ "type": "content_block_complete", "data": { "index": 0 } }),
+            ),
+            (
+                StreamEvent::MessageComplete {
+                    stop_reason: StopReason::ToolUse,
+                },
+                serde_json::json!({
+                    "type": "message_complete",
+                    "data": { "stop_reason": "tool_use" }
+                }),
+            ),
+            (
+                StreamEvent::Usage {
+                    input_tokens: 10,
+                    output_tokens: 20,
+                },
+                serde_json::json!({
+                    "type": "usage",
+                    "data": { "input_tokens": 10, "output_tokens": 20 }
+                }),
+            ),
+            (
+                StreamEvent::MessageStop,
+                serde_json::json!({ "type": "message_stop" }),
+            ),
+            (
+                StreamEvent::Error("boom".to_string()),
+                serde_json::json!({ "type": "error", "data": "boom" }),
+            ),
+        ];
+
+        for (event, expected) in events {
+            let json = serde_json::to_value(&event).unwrap();
+            assert_eq!(json, expected);
+            let decoded: StreamEvent = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded, event);
+        }
+    }
+
+    #[test]
+    fn test_stop_reason_serde_round_trip() {
+        for reason in [
+            StopReason::EndTurn,
+            StopReason::ToolUse,
+            StopReason::MaxTokens,
+            StopReason::StopSequence,
+            StopReason::Refusal,
+        ] {
+            let json = serde_json::to_string(&reason).unwrap();
+            assert_eq!(serde_json::from_str::<StopReason>(&json).unwrap(), reason);
+        }
+    }
 }
@@ -3,8 +3,11 @@
 //! This module provides functionality for automatic updates of the Patina binary:
 //! - Version checking against a release manifest
 //! - SHA256 checksum verification for downloaded binaries
+//! - Ed25519 signature verification against a pinned release key
+//! - Resumable downloads using HTTP range requests
 //! - Multi-platform support (Linux, macOS, Windows)
 //! - Multiple release channels (stable, latest, nightly)
+//! - Rollback to the previously installed binary
 //!
 //! # Example
 //!
@@ -28,11 +31,39 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Release signatures
+//!
+//! Every artifact has a detached signature next to it (`<url>.sig` unless
+//! the manifest names another location) holding the base64-encoded Ed25519
+//! signature of the artifact bytes. The public key is pinned into the binary
+//! at build time from `PATINA_RELEASE_PUBLIC_KEY` (64 hex characters); a
+//! build without it refuses to self-update rather than install unverified
+//! code.
+//!
+//! # Rollback
+//!
+//! Before an update replaces the binary, the running version is copied next
+//! to it as a hidden `.previous` file; only the most recent one is kept:
+//!
+//! ```text
+//! /usr/local/bin/
+//! ├── patina                  # installed version
+//! ├── .patina.previous        # version replaced by the last update
+//! └── .patina.<sha256>.part   # in-progress download (resumable)
+//! ```
+//!
+//! [`UpdateInstaller::rollback`] checks that `.patina.previous` still runs
+//! (`--version`) and then swaps the two files, so rolling back twice
+//! returns to the update.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 /// Release channel for update checking.
 ///
@@ -50,6 +81,23 @@ pub enum ReleaseChannel {
     Nightly,
 }
 
+impl std::str::FromStr for ReleaseChannel {
+    type Err = anyhow::Error;
+
+    /// Parses a channel name; `beta` is the user-facing name of
+    /// [`ReleaseChannel::Latest`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stable" => Ok(Self::Stable),
+            "beta" | "latest" => Ok(Self::Latest),
+            "nightly" => Ok(Self::Nightly),
+            other => {
+                anyhow::bail!("unknown update channel '{other}' (expected stable, beta or nightly)")
+            }
+        }
+    }
+}
+
 impl ReleaseChannel {
     /// Returns the string representation of the release channel.
     ///
@@ -88,10 +136,27 @@ pub struct PlatformRelease {
     pub sha256: String,
     /// Size of the binary in bytes.
     pub size: u64,
+    /// URL of the detached signature; defaults to `<url>.sig`.
+    #[serde(default)]
+    pub signature_url: Option<String>,
+}
+
+impl PlatformRelease {
+    /// Returns where the detached signature for this release is published.
+    #[must_use]
+    pub fn signature_url(&self) -> String {
+        self.signature_url
+            .clone()
+            .unwrap_or_else(|| format!("{}.sig", self.url))
+    }
 }
 
 const DEFAULT_RELEASE_BASE_URL: &str = "https://releases.rct.dev";
 
+/// Hex-encoded Ed25519 key that release artifacts are signed with, pinned
+/// at build time.
+pub const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("PATINA_RELEASE_PUBLIC_KEY");
+
 /// Checks for available updates from the release server.
 ///
 /// The checker compares the current version against the latest available
@@ -223,33 +288,60 @@ impl UpdateChecker {
     }
 }
 
-/// Downloads and installs updates with checksum verification.
+/// Downloads and installs updates with checksum and signature verification.
 ///
-/// The installer downloads the binary to a temporary file, verifies the
-/// SHA256 checksum, then atomically replaces the current binary.
+/// The installer downloads the binary to a partial file next to the install
+/// path, verifies the SHA256 checksum and the release signature, then
+/// atomically replaces the current binary. An interrupted download is
+/// resumed with an HTTP range request on the next attempt instead of
+/// starting over.
 pub struct UpdateInstaller {
     install_path: PathBuf,
+    /// Ed25519 key the release must be signed with.
+    public_key: Option<Vec<u8>>,
 }
 
 impl UpdateInstaller {
-    /// Creates a new update installer.
+    /// Creates a new update installer that trusts [`RELEASE_PUBLIC_KEY`].
     ///
     /// # Arguments
     ///
     /// * `install_path` - Path where the updated binary should be installed
     #[must_use]
     pub fn new(install_path: PathBuf) -> Self {
-        Self { install_path }
+        Self {
+            install_path,
+            public_key: RELEASE_PUBLIC_KEY
+                .and_then(|key| hex::decode(key).ok())
+                .filter(|key| key.len() == 32),
+        }
+    }
+
+    /// Trusts `public_key` (a raw 32-byte Ed25519 key) instead of the
+    /// pinned release key.
+    ///
+    /// This is primarily useful for testing and for self-hosted release
+    /// servers.
+    #[must_use]
+    pub fn with_public_key(mut self, public_key: &[u8]) -> Self {
+        self.public_key = Some(public_key.to_vec());
+        self
     }
 
     /// Downloads and installs the update.
     ///
     /// This method:
-    /// 1. Downloads the binary from the release URL
-    /// 2. Verifies the SHA256 checksum
-    /// 3. Writes to a temporary file
-    /// 4. Sets executable permissions (Unix only)
-    /// 5. Atomically replaces the current binary
+    /// 1. Resumes a previous partial download of the same release, if any
+    /// 2. Downloads the (remaining) binary to a partial file
+    /// 3. Verifies the SHA256 checksum, discarding the file on mismatch
+    /// 4. Downloads the detached signature and verifies it against the
+    ///    trusted key, discarding the file on mismatch
+    /// 5. Sets executable permissions (Unix only)
+    /// 6. Copies the current binary to the `.previous` file for rollback
+    /// 7. Atomically renames the verified file over the current binary
+    ///
+    /// If the download is interrupted, the partial file is kept so the next
+    /// call only fetches the missing bytes.
     ///
     /// # Arguments
     ///
@@ -258,44 +350,276 @@ impl UpdateInstaller {
     /// # Errors
     ///
     /// Returns an error if:
+    /// * No release key is pinned in this build
     /// * The download fails
     /// * The checksum doesn't match
+    /// * The signature is missing or doesn't match
     /// * File operations fail
     pub async fn download_and_install(&self, release: &PlatformRelease) -> Result<()> {
-        let client = reqwest::Client::new();
-        let response = client.get(&release.url).send().await?;
-        let bytes = response.bytes().await?;
-
-        let mut hasher = Sha256::new();
-        hasher.update(&bytes);
-        let hash = hex::encode(hasher.finalize());
-
-        if hash != release.sha256 {
+        let Some(public_key) = &self.public_key else {
             anyhow::bail!(
-                "Checksum mismatch: expected {}, got {}",
-                release.sha256,
-                hash
+                "Refusing to update: this build has no release signing key \
+                 (PATINA_RELEASE_PUBLIC_KEY was not set when it was built)"
             );
+        };
+
+        let part_path = self.partial_path(release);
+        let partial_len = tokio::fs::metadata(&part_path)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let offset = resume_offset(partial_len, release.size);
+
+        // A complete partial file is left over from an attempt that failed
+        // after downloading; verify it before fetching anything
+        let already_complete = partial_len > 0 && partial_len == release.size;
+        if !already_complete {
+            self.download(release, &part_path, offset).await?;
+        }
+
+        if let Err(e) = verify_checksum(&part_path, &release.sha256).await {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
         }
 
-        let temp_path = self.install_path.with_extension("new");
-        tokio::fs::write(&temp_path, &bytes).await?;
+        let verified = match fetch_signature(release).await {
+            Ok(signature) => verify_signature(&part_path, &signature, public_key).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = verified {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
+        }
 
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            tokio::fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(0o755)).await?;
+            tokio::fs::set_permissions(&part_path, std::fs::Permissions::from_mode(0o755)).await?;
         }
 
-        tokio::fs::rename(&temp_path, &self.install_path).await?;
+        if tokio::fs::try_exists(&self.install_path).await? {
+            tokio::fs::copy(&self.install_path, self.previous_path())
+                .await
+                .context("Failed to keep the current binary for rollback")?;
+        }
+
+        tokio::fs::rename(&part_path, &self.install_path).await?;
 
         Ok(())
     }
+
+    /// Restores the binary replaced by the last update.
+    ///
+    /// The saved binary must run `--version` successfully before it is
+    /// swapped in; the version being rolled back from becomes the new
+    /// `.previous` file.
+    ///
+    /// # Returns
+    ///
+    /// The restored binary's `--version` output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no saved binary, it does not run, or
+    /// the files cannot be swapped.
+    pub async fn rollback(&self) -> Result<String> {
+        let previous = self.previous_path();
+        if !tokio::fs::try_exists(&previous).await? {
+            anyhow::bail!(
+                "No previous version to roll back to ({} does not exist)",
+                previous.display()
+            );
+        }
+
+        let output = tokio::process::Command::new(&previous)
+            .arg("--version")
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {}", previous.display()))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Refusing to roll back: {} --version exited with {}",
+                previous.display(),
+                output.status
+            );
+        }
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        let swap = self
+            .install_path
+            .with_file_name(format!(".{}.rollback", self.binary_name()));
+        tokio::fs::rename(&self.install_path, &swap).await?;
+        if let Err(e) = tokio::fs::rename(&previous, &self.install_path).await {
+            let _ = tokio::fs::rename(&swap, &self.install_path).await;
+            return Err(e).context("Failed to restore the previous binary");
+        }
+        tokio::fs::rename(&swap, &previous).await?;
+
+        Ok(version)
+    }
+
+    /// Returns where the binary replaced by the last update is kept.
+    #[must_use]
+    pub fn previous_path(&self) -> PathBuf {
+        self.install_path
+            .with_file_name(format!(".{}.previous", self.binary_name()))
+    }
+
+    /// Returns the installed binary's file name.
+    fn binary_name(&self) -> &str {
+        self.install_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("patina")
+    }
+
+    /// Returns the partial download path for a release.
+    ///
+    /// The name includes the start of the expected checksum so a partial
+    /// download is never resumed against a different release.
+    fn partial_path(&self, release: &PlatformRelease) -> PathBuf {
+        let name = self.binary_name();
+        let tag: String = release
+            .sha256
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .take(12)
+            .collect();
+        self.install_path
+            .with_file_name(format!(".{name}.{tag}.part"))
+    }
+
+    /// Downloads `release` into `part_path`, starting at byte `offset`.
+    async fn download(
+        &self,
+        release: &PlatformRelease,
+        part_path: &Path,
+        offset: u64,
+    ) -> Result<()> {
+        let client = reqwest::Client::new();
+        let mut request = client.get(&release.url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let mut response = request.send().await?;
+        let status = response.status();
+
+        let mut options = tokio::fs::OpenOptions::new();
+        options.create(true).write(true);
+        if status == reqwest::StatusCode::PARTIAL_CONTENT && offset > 0 {
+            options.append(true);
+        } else if status.is_success() {
+            // Full body: the server ignored the range, or there was nothing to resume
+            options.truncate(true);
+        } else {
+            if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+                // The partial file no longer matches the artifact; start over next time
+                let _ = tokio::fs::remove_file(part_path).await;
+            }
+            anyhow::bail!("Download failed: HTTP {}", status);
+        }
+
+        let mut file = options
+            .open(part_path)
+            .await
+            .context("Failed to open partial download")?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the byte offset to resume a download from.
+///
+/// A partial file shorter than the expected size is resumed where it ended.
+/// Anything else (nothing downloaded, an unknown size, or a file that is
+/// already complete or too large) restarts from zero.
+fn resume_offset(partial_len: u64, expected_size: u64) -> u64 {
+    if partial_len < expected_size {
+        partial_len
+    } else {
+        0
+    }
+}
+
+/// Verifies the SHA256 checksum of the file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or its checksum does not
+/// match `expected` (a hex digest, compared case-insensitively).
+async fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .context("Failed to open downloaded file")?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    let hash = hex::encode(hasher.finalize());
+
+    if !hash.eq_ignore_ascii_case(expected) {
+        anyhow::bail!("Checksum mismatch: expected {}, got {}", expected, hash);
+    }
+    Ok(())
+}
+
+/// Downloads and decodes the detached signature of `release`.
+async fn fetch_signature(release: &PlatformRelease) -> Result<Vec<u8>> {
+    let url = release.signature_url();
+    let response = reqwest::get(&url)
+        .await
+        .with_context(|| format!("Failed to download release signature from {url}"))?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Release signature unavailable: HTTP {} from {}",
+            response.status(),
+            url
+        );
+    }
+    let body = response.text().await?;
+    base64::engine::general_purpose::STANDARD
+        .decode(body.trim())
+        .context("Release signature is not valid base64")
+}
+
+/// Verifies that `signature` is the release key's Ed25519 signature of the
+/// file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the signature does not
+/// verify.
+async fn verify_signature(path: &Path, signature: &[u8], public_key: &[u8]) -> Result<()> {
+    let content = tokio::fs::read(path)
+        .await
+        .context("Failed to read downloaded file")?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(&content, signature)
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Signature mismatch: the download is not signed by the Patina release key"
+            )
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn release_key() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
 
     #[test]
     fn test_release_channel_as_str() {
@@ -304,6 +628,23 @@ mod tests {
         assert_eq!(ReleaseChannel::Nightly.as_str(), "nightly");
     }
 
+    #[test]
+    fn test_release_channel_from_str() {
+        assert_eq!(
+            "stable".parse::<ReleaseChannel>().unwrap(),
+            ReleaseChannel::Stable
+        );
+        assert_eq!(
+            "beta".parse::<ReleaseChannel>().unwrap(),
+            ReleaseChannel::Latest
+        );
+        assert_eq!(
+            "nightly".parse::<ReleaseChannel>().unwrap(),
+            ReleaseChannel::Nightly
+        );
+        assert!("canary".parse::<ReleaseChannel>().is_err());
+    }
+
     #[test]
     fn test_update_checker_new() {
         let checker = UpdateChecker::new("1.0.0", ReleaseChannel::Stable);
@@ -339,4 +680,214 @@ mod tests {
         ];
         assert!(valid_platforms.contains(&platform));
     }
+
+    #[test]
+    fn test_resume_offset() {
+        // Nothing downloaded yet
+        assert_eq!(resume_offset(0, 1000), 0);
+        // Partial download resumes where it stopped
+        assert_eq!(resume_offset(400, 1000), 400);
+        // Complete or oversized partial files are not resumed
+        assert_eq!(resume_offset(1000, 1000), 0);
+        assert_eq!(resume_offset(1200, 1000), 0);
+        // Unknown size cannot be resumed
+        assert_eq!(resume_offset(400, 0), 0);
+    }
+
+    #[tokio::test]
+    async fn test_verify_checksum_rejects_tampered_artifact() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("artifact");
+        let content = b"patina release binary";
+        let expected = hex::encode(Sha256::digest(content));
+
+        tokio::fs::write(&path, content).await.unwrap();
+        assert!(verify_checksum(&path, &expected).await.is_ok());
+        assert!(verify_checksum(&path, &expected.to_uppercase())
+            .await
+            .is_ok());
+
+        tokio::fs::write(&path, b"patina release binarY")
+            .await
+            .unwrap();
+        let err = verify_checksum(&path, &expected).await.unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature_rejects_tampered_artifact() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("artifact");
+        let key = release_key();
+        let content = b"patina release binary";
+        let signature = key.sign(content);
+
+        tokio::fs::write(&path, content).await.unwrap();
+        verify_signature(&path, signature.as_ref(), key.public_key().as_ref())
+            .await
+            .unwrap();
+
+        // Signed by another key
+        let other = release_key();
+        let err = verify_signature(&path, signature.as_ref(), other.public_key().as_ref())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Signature mismatch"), "got: {err}");
+
+        tokio::fs::write(&path, b"patina release binarY")
+            .await
+            .unwrap();
+        let err = verify_signature(&path, signature.as_ref(), key.public_key().as_ref())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Signature mismatch"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn test_install_refuses_without_release_key() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let installer = UpdateInstaller {
+            install_path: temp_dir.path().join("patina"),
+            public_key: None,
+        };
+        let release = PlatformRelease {
+            url: "http://127.0.0.1:9/patina".to_string(),
+            sha256: "abc123".to_string(),
+            size: 1,
+            signature_url: None,
+        };
+        let err = installer.download_and_install(&release).await.unwrap_err();
+        assert!(
+            err.to_string().contains("no release signing key"),
+            "got: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_partial_file() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let install_path = temp_dir.path().join("patina");
+        let binary = b"#!/bin/sh\necho 'Patina v2.0.0'\n";
+
+        Mock::given(method("GET"))
+            .and(path("/stable/patina"))
+            .and(header("range", "bytes=10-"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(binary[10..].to_vec()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let key = release_key();
+        Mock::given(method("GET"))
+            .and(path("/stable/patina.sig"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                base64::engine::general_purpose::STANDARD.encode(key.sign(binary)),
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let release = PlatformRelease {
+            url: format!("{}/stable/patina", mock_server.uri()),
+            sha256: hex::encode(Sha256::digest(binary)),
+            size: binary.len() as u64,
+            signature_url: None,
+        };
+        let installer =
+            UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
+
+        // Simulate a download that was interrupted after 10 bytes
+        let part_path = installer.partial_path(&release);
+        tokio::fs::write(&part_path, &binary[..10]).await.unwrap();
+
+        installer.download_and_install(&release).await.unwrap();
+
+        assert_eq!(tokio::fs::read(&install_path).await.unwrap(), binary);
+        assert!(
+            !part_path.exists(),
+            "partial file should be renamed into place"
+        );
+    }
+
+    #[cfg(unix)]
+    fn write_script(path: &Path, body: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::write(path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_update_keeps_previous_binary_for_rollback() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let install_path = temp_dir.path().join("patina");
+        write_script(&install_path, "echo 'patina 1.0.0'");
+        let old = std::fs::read(&install_path).unwrap();
+        let new = b"#!/bin/sh\necho 'patina 2.0.0'\n";
+
+        let key = release_key();
+        Mock::given(method("GET"))
+            .and(path("/stable/patina"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(new.to_vec()))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/stable/patina.sig"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    base64::engine::general_purpose::STANDARD.encode(key.sign(new)),
+                ),
+            )
+            .mount(&mock_server)
+            .await;
+        let release = PlatformRelease {
+            url: format!("{}/stable/patina", mock_server.uri()),
+            sha256: hex::encode(Sha256::digest(new)),
+            size: new.len() as u64,
+            signature_url: None,
+        };
+        let installer =
+            UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
+
+        installer.download_and_install(&release).await.unwrap();
+        assert_eq!(std::fs::read(&install_path).unwrap(), new);
+        assert_eq!(std::fs::read(installer.previous_path()).unwrap(), old);
+
+        let version = installer.rollback().await.unwrap();
+        assert_eq!(version, "patina 1.0.0");
+        assert_eq!(std::fs::read(&install_path).unwrap(), old);
+
+        // The rolled-back-from version is kept, so rolling back again undoes it
+        assert_eq!(std::fs::read(installer.previous_path()).unwrap(), new);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rollback_refuses_binary_that_does_not_run() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let install_path = temp_dir.path().join("patina");
+        let installer = UpdateInstaller::new(install_path.clone());
+        write_script(&install_path, "echo 'patina 2.0.0'");
+
+        let err = installer.rollback().await.unwrap_err();
+        assert!(
+            err.to_string().contains("No previous version"),
+            "got: {err}"
+        );
+
+        write_script(&installer.previous_path(), "exit 1");
+        let err = installer.rollback().await.unwrap_err();
+        assert!(
+            err.to_string().contains("Refusing to roll back"),
+            "got: {err}"
+        );
+        let installed = std::fs::read_to_string(&install_path).unwrap();
+        assert!(installed.contains("patina 2.0.0"));
+    }
 }
@@ -1,7 +1,12 @@
 //! Utility functions and helpers
 
+pub mod redact;
+pub mod version;
+
+use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
 pub fn get_config_dir() -> Option<PathBuf> {
     ProjectDirs::from("dev", "rct", "rct").map(|dirs| dirs.config_dir().to_path_buf())
@@ -52,6 +57,72 @@ pub fn sanitize_filename(name: &str) -> String {
         .collect()
 }
 
+/// Writes data to a file atomically using write-to-temp-then-rename pattern.
+///
+/// This ensures that concurrent writes don't corrupt the file - each write
+/// either fully succeeds or the file remains unchanged. An existing file's
+/// permissions are carried over to the replacement.
+///
+/// # Errors
+///
+/// Returns an error if the temp file cannot be written or renamed into place.
+pub async fn atomic_write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp_path = atomic_temp_path(path);
+
+    // Write to temp file
+    tokio::fs::write(&temp_path, contents)
+        .await
+        .context("Failed to write temp file")?;
+
+    if let Ok(metadata) = tokio::fs::metadata(path).await {
+        let _ = tokio::fs::set_permissions(&temp_path, metadata.permissions()).await;
+    }
+
+    // Atomic rename (on POSIX this is atomic, on Windows it's mostly atomic)
+    if let Err(e) = tokio::fs::rename(&temp_path, path).await {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(e).context("Failed to rename temp file");
+    }
+
+    Ok(())
+}
+
+/// Blocking version of [`atomic_write`] for callers outside an async context.
+///
+/// # Errors
+///
+/// Returns an error if the temp file cannot be written or renamed into place.
+pub fn atomic_write_blocking(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp_path = atomic_temp_path(path);
+
+    std::fs::write(&temp_path, contents).context("Failed to write temp file")?;
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = std::fs::set_permissions(&temp_path, metadata.permissions());
+    }
+
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e).context("Failed to rename temp file");
+    }
+
+    Ok(())
+}
+
+/// Returns a unique temp path next to `path`.
+///
+/// The temp file lives in the same directory so the rename stays on one
+/// filesystem.
+fn atomic_temp_path(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let temp_name = format!(
+        ".{}.tmp.{}",
+        path.file_name().and_then(|n| n.to_str()).unwrap_or("file"),
+        Uuid::new_v4()
+    );
+    parent.join(temp_name)
+}
+
 pub mod ansi {
     pub const RESET: &str = "\x1b[0m";
     pub const BOLD: &str = "\x1b[1m";
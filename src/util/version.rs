@@ -0,0 +1,82 @@
+//! Version and build information.
+//!
+//! Build metadata (git commit, build date, Rust version, enabled features) is
+//! captured by `build.rs` at compile time and reported by
+//! `patina --version --verbose` for use in bug reports.
+
+/// Crate version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git commit hash the binary was built from, or `unknown`.
+pub const GIT_COMMIT: &str = env!("PATINA_GIT_COMMIT");
+
+/// Build date (`YYYY-MM-DD`, UTC).
+pub const BUILD_DATE: &str = env!("PATINA_BUILD_DATE");
+
+/// Output of `rustc --version` for the compiler that built the binary.
+pub const RUSTC_VERSION: &str = env!("PATINA_RUSTC_VERSION");
+
+/// Comma-separated list of enabled cargo features (empty if none).
+pub const FEATURES: &str = env!("PATINA_FEATURES");
+
+/// Returns the verbose version report, one `key: value` pair per line.
+///
+/// # Examples
+///
+/// ```
+/// use patina::util::version::verbose_version;
+///
+/// let report = verbose_version("claude-sonnet-4-20250514");
+/// assert!(report.starts_with("patina "));
+/// assert!(report.contains("default model: claude-sonnet-4-20250514"));
+/// ```
+#[must_use]
+pub fn verbose_version(default_model: &str) -> String {
+    let features = if FEATURES.is_empty() {
+        "none"
+    } else {
+        FEATURES
+    };
+
+    format!(
+        "patina {VERSION}\n\
+         commit: {GIT_COMMIT}\n\
+         build date: {BUILD_DATE}\n\
+         rustc: {RUSTC_VERSION}\n\
+         features: {features}\n\
+         default model: {default_model}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::config::DEFAULT_MODEL;
+
+    #[test]
+    fn test_verbose_version_parses_cleanly() {
+        let report = verbose_version(DEFAULT_MODEL);
+        let mut lines = report.lines();
+
+        assert_eq!(lines.next(), Some(format!("patina {VERSION}").as_str()));
+
+        let fields: Vec<(&str, &str)> = lines
+            .map(|line| line.split_once(": ").expect("line should be `key: value`"))
+            .collect();
+        let keys: Vec<&str> = fields.iter().map(|(k, _)| *k).collect();
+        assert_eq!(
+            keys,
+            ["commit", "build date", "rustc", "features", "default model"]
+        );
+        assert!(fields.iter().all(|(_, v)| !v.is_empty()));
+        assert_eq!(fields[4].1, DEFAULT_MODEL);
+    }
+
+    #[test]
+    fn test_build_date_format() {
+        let parts: Vec<&str> = BUILD_DATE.split('-').collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].len(), 4);
+        assert!(parts.iter().all(|p| p.parse::<u32>().is_ok()));
+    }
+}
@@ -30,6 +30,10 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Default directory of file backups, never committed by
+/// [`WorktreeManager::commit_paths`].
+const BACKUP_DIR: &str = ".rct_backups";
+
 /// Configuration for worktree management.
 ///
 /// Controls where worktrees are created and how they are named.
@@ -240,6 +244,7 @@ impl WorktreeManager {
     /// # Errors
     ///
     /// - `WorktreeError::WorktreeExists` if a worktree with this name already exists.
+    /// - `WorktreeError::BranchExists` if the branch for this name already exists.
     /// - `WorktreeError::GitCommand` if the git command fails.
     pub fn create(&self, name: &str) -> Result<WorktreeInfo, WorktreeError> {
         // Check if worktree already exists
@@ -253,6 +258,11 @@ impl WorktreeManager {
         let worktree_path = worktree_dir.join(name);
         let branch_name = format!("{}{}", self.config.branch_prefix, name);
 
+        // `git worktree add -b` refuses existing branches with a terse message
+        if self.branch_exists(&branch_name)? {
+            return Err(WorktreeError::BranchExists(branch_name));
+        }
+
         // Create the worktree directory if it doesn't exist
         std::fs::create_dir_all(&worktree_dir)?;
 
@@ -329,6 +339,136 @@ impl WorktreeManager {
         Ok(())
     }
 
+    /// Returns `true` if a local branch with this name exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorktreeError::GitCommand` if git cannot be run.
+    pub fn branch_exists(&self, branch: &str) -> Result<bool, WorktreeError> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{}", branch))
+            .current_dir(&self.repo_root)
+            .output()
+            .map_err(|e| WorktreeError::GitCommand {
+                command: "git rev-parse --verify".to_string(),
+                message: e.to_string(),
+            })?;
+
+        Ok(output.status.success())
+    }
+
+    /// Returns the branch checked out at the given path.
+    ///
+    /// A detached HEAD is reported as `HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorktreeError::GitCommand` if the git command fails.
+    pub fn current_branch(&self, path: &Path) -> Result<String, WorktreeError> {
+        run_git(path, &["rev-parse", "--abbrev-ref", "HEAD"])
+    }
+
+    /// Checks that the working tree at `path` has no uncommitted changes.
+    ///
+    /// Only tracked files count: a new worktree starts from the last commit,
+    /// so staged or modified files would silently be left behind. Untracked
+    /// files, including the worktree directory itself, are ignored.
+    ///
+    /// # Errors
+    ///
+    /// - `WorktreeError::DirtyWorkingTree` if tracked files have changes.
+    /// - `WorktreeError::GitCommand` if git commands fail.
+    pub fn ensure_clean(&self, path: &Path) -> Result<(), WorktreeError> {
+        let status = self.status(path)?;
+        if status.modified > 0 || status.staged > 0 {
+            return Err(WorktreeError::DirtyWorkingTree(path.to_path_buf()));
+        }
+        Ok(())
+    }
+
+    /// Merges `branch` into the branch checked out at `into`.
+    ///
+    /// A merge that stops on conflicts is aborted, leaving `into` as it was.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorktreeError::GitCommand` if the merge fails.
+    pub fn merge(&self, branch: &str, into: &Path) -> Result<(), WorktreeError> {
+        if let Err(e) = run_git(into, &["merge", "--no-edit", branch]) {
+            let _ = run_git(into, &["merge", "--abort"]);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Stages and commits the given files in the worktree at `path`.
+    ///
+    /// Only `files` are committed, so backups, secrets and build output
+    /// lying around the worktree stay out of the commit. Files that no
+    /// longer exist, lie outside `path` or inside `.rct_backups`, or are
+    /// ignored by git are skipped. Returns
+    /// the short hash of the new commit, or `None` if there was nothing to
+    /// commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorktreeError::GitCommand` if staging or committing fails.
+    pub fn commit_paths(
+        &self,
+        path: &Path,
+        files: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<String>, WorktreeError> {
+        let mut pathspecs: Vec<String> = files
+            .iter()
+            .filter(|file| file.exists())
+            .filter_map(|file| file.strip_prefix(path).ok())
+            .filter(|file| !file.starts_with(BACKUP_DIR))
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect();
+        pathspecs.sort();
+        pathspecs.dedup();
+
+        // `git check-ignore` exits non-zero when none of the paths are ignored
+        let mut args = vec!["check-ignore", "--"];
+        args.extend(pathspecs.iter().map(String::as_str));
+        if let Ok(ignored) = run_git(path, &args) {
+            let ignored: Vec<&str> = ignored.lines().collect();
+            pathspecs.retain(|spec| !ignored.contains(&spec.as_str()));
+        }
+        if pathspecs.is_empty() {
+            return Ok(None);
+        }
+        let specs: Vec<&str> = pathspecs.iter().map(String::as_str).collect();
+
+        let mut args = vec!["add", "--all", "--"];
+        args.extend(&specs);
+        run_git(path, &args)?;
+
+        // `git diff --quiet` exits non-zero when there are staged changes
+        let mut args = vec!["diff", "--cached", "--quiet", "--"];
+        args.extend(&specs);
+        if run_git(path, &args).is_ok() {
+            return Ok(None);
+        }
+
+        let mut args = vec!["commit", "--quiet", "-m", message, "--"];
+        args.extend(&specs);
+        run_git(path, &args)?;
+        run_git(path, &["rev-parse", "--short", "HEAD"]).map(Some)
+    }
+
+    /// Deletes a fully merged local branch.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WorktreeError::GitCommand` if the branch is not merged or
+    /// cannot be deleted.
+    pub fn delete_branch(&self, branch: &str) -> Result<(), WorktreeError> {
+        run_git(&self.repo_root, &["branch", "-d", branch]).map(|_| ())
+    }
+
     /// Gets the status of a worktree at the given path.
     ///
     /// Returns information about modified, staged, and untracked files,
@@ -446,6 +586,9 @@ impl WorktreeManager {
     }
 
     /// Parses the output of `git worktree list --porcelain`.
+    ///
+    /// Git always lists the main worktree first, also when run from a
+    /// linked worktree.
     fn parse_worktree_list(&self, output: &str) -> Result<Vec<WorktreeInfo>, WorktreeError> {
         let mut worktrees = Vec::new();
         let mut current_path: Option<PathBuf> = None;
@@ -458,7 +601,7 @@ impl WorktreeManager {
                 // If we have a previous worktree, save it
                 if let Some(path) = current_path.take() {
                     let branch = current_branch.take().unwrap_or_default();
-                    let is_main = path == self.repo_root;
+                    let is_main = worktrees.is_empty();
                     let name = self.derive_worktree_name(&path, &branch, is_main);
 
                     worktrees.push(WorktreeInfo {
@@ -492,7 +635,7 @@ impl WorktreeManager {
         // Don't forget the last worktree
         if let Some(path) = current_path.take() {
             let branch = current_branch.take().unwrap_or_default();
-            let is_main = path == self.repo_root;
+            let is_main = worktrees.is_empty();
             let name = self.derive_worktree_name(&path, &branch, is_main);
 
             worktrees.push(WorktreeInfo {
@@ -542,6 +685,32 @@ impl WorktreeManager {
     }
 }
 
+/// Runs a git command in `dir` and returns its trimmed stdout.
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, WorktreeError> {
+    let command = format!("git {}", args.join(" "));
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| WorktreeError::GitCommand {
+            command: command.clone(),
+            message: e.to_string(),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let message = if stderr.trim().is_empty() {
+            stdout.trim().to_string()
+        } else {
+            stderr.trim().to_string()
+        };
+        return Err(WorktreeError::GitCommand { command, message });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 /// Errors that can occur during worktree operations.
 #[derive(Debug)]
 pub enum WorktreeError {
@@ -554,6 +723,12 @@ pub enum WorktreeError {
     /// The specified worktree was not found.
     WorktreeNotFound(String),
 
+    /// The branch a new worktree would create already exists.
+    BranchExists(String),
+
+    /// The working tree has uncommitted changes to tracked files.
+    DirtyWorkingTree(PathBuf),
+
     /// A git command failed.
     GitCommand {
         /// The command that failed.
@@ -578,6 +753,16 @@ impl fmt::Display for WorktreeError {
             Self::WorktreeNotFound(name) => {
                 write!(f, "worktree not found: {}", name)
             }
+            Self::BranchExists(branch) => {
+                write!(f, "branch already exists: {}", branch)
+            }
+            Self::DirtyWorkingTree(path) => {
+                write!(
+                    f,
+                    "uncommitted changes in {}; commit or stash them first",
+                    path.display()
+                )
+            }
             Self::GitCommand { command, message } => {
                 write!(f, "git command '{}' failed: {}", command, message)
             }
@@ -8,11 +8,12 @@
 mod common;
 
 use common::TestContext;
-use patina::api::AnthropicClient;
-use patina::types::{Message, Role, StreamEvent};
+use patina::api::tools::default_tools;
+use patina::api::{AnthropicClient, RetryPolicy, ToolChoice};
+use patina::types::{ApiMessageV2, Message, Role, StreamEvent};
 use secrecy::SecretString;
 use tokio::sync::mpsc;
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{body_partial_json, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 /// Placeholder test to verify test infrastructure works.
@@ -251,6 +252,68 @@ data: {"type":"message_stop"}
     );
 }
 
+/// Test that the client waits for the `retry-after` duration on a 429.
+///
+/// Verifies that the API client:
+/// - Sleeps for the server-provided delay instead of the exponential backoff
+/// - Succeeds on the retried request
+#[tokio::test]
+async fn test_api_rate_limit_honors_retry_after() {
+    use std::time::{Duration, Instant};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .set_body_string(r#"{"error":{"message":"rate_limit_exceeded"}}"#)
+                .append_header("retry-after", "2"),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    let sse_response = r#"event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(sse_response, "text/event-stream"))
+        .mount(&mock_server)
+        .await;
+
+    let key = SecretString::from("test-key-value");
+    let client = AnthropicClient::new_with_base_url(key, "claude-3-opus", &mock_server.uri());
+
+    let messages = vec![Message {
+        role: Role::User,
+        content: "Hello".to_string(),
+    }];
+
+    let (tx, mut rx) = mpsc::channel::<StreamEvent>(32);
+
+    let start = Instant::now();
+    client.stream_message(&messages, tx).await.unwrap();
+    let elapsed = start.elapsed();
+
+    // The default backoff would retry after ~100ms
+    assert!(
+        elapsed >= Duration::from_millis(1900),
+        "Expected to wait for retry-after, retried after {:?}",
+        elapsed
+    );
+
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push(event);
+    }
+    assert!(events.iter().any(|e| e.is_stop()));
+    assert!(!events.iter().any(|e| e.is_error()));
+}
+
 /// Test that the client uses exponential backoff for retries.
 ///
 /// Verifies that the API client:
@@ -310,6 +373,86 @@ async fn test_retry_exponential_backoff() {
     );
 }
 
+/// Test that a custom retry policy controls the number of attempts.
+///
+/// Verifies that the API client:
+/// - Honors the configured `max_retries`
+/// - Clamps exponential backoff to `max_backoff`
+#[tokio::test]
+async fn test_retry_policy_honors_max_retries() {
+    use std::time::{Duration, Instant};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+        .expect(5) // Initial + 4 retries
+        .mount(&mock_server)
+        .await;
+
+    let key = SecretString::from("test-key-value");
+    let policy = RetryPolicy::new(4, Duration::from_millis(50), Duration::from_millis(50));
+    let client = AnthropicClient::new_with_base_url(key, "claude-3-opus", &mock_server.uri())
+        .with_retry_policy(policy);
+
+    let messages = vec![Message {
+        role: Role::User,
+        content: "Hello".to_string(),
+    }];
+
+    let (tx, mut rx) = mpsc::channel::<StreamEvent>(32);
+
+    let start = Instant::now();
+    client.stream_message(&messages, tx).await.unwrap();
+    let elapsed = start.elapsed();
+
+    // Unclamped backoff would be 50 + 100 + 200 + 400 = 750ms; clamped is 200ms
+    assert!(
+        elapsed < Duration::from_millis(600),
+        "Expected clamped backoff, took {:?}",
+        elapsed
+    );
+
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push(event);
+    }
+    assert!(events.iter().any(|e| e.is_error()));
+}
+
+/// Test that a retry policy with zero retries makes a single attempt.
+#[tokio::test]
+async fn test_retry_policy_zero_retries() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("rate limited"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let key = SecretString::from("test-key-value");
+    let policy = RetryPolicy {
+        max_retries: 0,
+        ..RetryPolicy::default()
+    };
+    let client = AnthropicClient::new_with_base_url(key, "claude-3-opus", &mock_server.uri())
+        .with_retry_policy(policy);
+
+    let messages = vec![Message {
+        role: Role::User,
+        content: "Hello".to_string(),
+    }];
+
+    let (tx, mut rx) = mpsc::channel::<StreamEvent>(32);
+    client.stream_message(&messages, tx).await.unwrap();
+
+    let event = rx.try_recv().expect("should receive an error event");
+    assert!(event.is_error());
+}
+
 /// Test that the client retries on server errors (5xx).
 ///
 /// Verifies that the API client:
@@ -531,3 +674,69 @@ data: {"type":"message_stop"}
         "Invalid JSON should be silently skipped, not cause an error event"
     );
 }
+
+/// Test that a forced tool choice is sent in the request body.
+///
+/// Verifies that `ToolChoice::Tool` serializes as `{"type":"tool","name":...}`
+/// and that a choice naming an unknown tool is rejected before any request.
+#[tokio::test]
+async fn test_forced_tool_choice_sent_in_request() {
+    let mock_server = MockServer::start().await;
+
+    let sse_response = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_123","type":"message","role":"assistant","content":[]}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(serde_json::json!({
+            "tool_choice": { "type": "tool", "name": "grep" }
+        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(sse_response, "text/event-stream")
+                .append_header("content-type", "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let key = SecretString::from("test-key-value");
+    let client = AnthropicClient::new_with_base_url(key, "claude-3-opus", &mock_server.uri());
+    let tools = default_tools();
+    let messages = vec![ApiMessageV2::user("Find the TODOs")];
+
+    let (tx, mut rx) = mpsc::channel::<StreamEvent>(32);
+    client
+        .stream_message_v2_with_tools(&messages, Some(&tools), Some(&ToolChoice::tool("grep")), tx)
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push(event);
+    }
+    assert!(
+        !events.iter().any(|e| e.is_error()),
+        "forced tool choice should be accepted, got {events:?}"
+    );
+
+    // An unknown tool is rejected locally; the mock's expect(1) fails if it is sent
+    let (tx, _rx) = mpsc::channel::<StreamEvent>(32);
+    let result = client
+        .stream_message_v2_with_tools(
+            &messages,
+            Some(&tools),
+            Some(&ToolChoice::tool("run_tests")),
+            tx,
+        )
+        .await;
+    assert!(result.is_err(), "unknown forced tool should be rejected");
+}
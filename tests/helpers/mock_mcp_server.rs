@@ -32,6 +32,9 @@
 //!
 //! # Exit immediately (for crash testing)
 //! mock_mcp_server --exit-immediately
+//!
+//! # Stream N partial results before each tools/call response
+//! mock_mcp_server --partial-results 2
 //! ```
 //!
 //! ## Supported MCP Methods
@@ -42,6 +45,8 @@
 //! | `initialized` | (notification - no response) |
 //! | `tools/list` | `{tools: [{name, description, inputSchema}]}` |
 //! | `tools/call` | `{content: [{type, text}]}` |
+//! | `resources/list` | `{resources: [{uri, name, mimeType}]}` |
+//! | `resources/read` | `{contents: [{uri, mimeType, text}]}`, or error -32002 for unknown URIs |
 //! | `ping` | `{}` |
 //! | Unknown | JSON-RPC error -32601 (Method not found) |
 //!
@@ -56,6 +61,7 @@
 //! | No response | `--no-response` | Read input but never respond |
 //! | Exit immediately | `--exit-immediately` | Exit with code 0 before reading |
 //! | Custom exit code | `--exit-code N` | Exit with specified code |
+//! | Partial results | `--partial-results N` | Send N progress notifications with content before a `tools/call` response |
 //!
 //! ## Architecture
 //!
@@ -116,6 +122,8 @@ struct Config {
     exit_immediately: bool,
     /// Exit code to use when exiting.
     exit_code: i32,
+    /// Partial results to stream before each tools/call response.
+    partial_results: usize,
 }
 
 impl Config {
@@ -157,6 +165,12 @@ impl Config {
                         config.exit_code = args[i].parse().unwrap_or(1);
                     }
                 }
+                "--partial-results" => {
+                    i += 1;
+                    if i < args.len() {
+                        config.partial_results = args[i].parse().unwrap_or(0);
+                    }
+                }
                 _ => {}
             }
             i += 1;
@@ -223,7 +237,8 @@ fn handle_initialize(id: &serde_json::Value) -> String {
         serde_json::json!({
             "protocolVersion": "2024-11-05",
             "capabilities": {
-                "tools": {}
+                "tools": {},
+                "resources": {}
             },
             "serverInfo": {
                 "name": "mock-mcp-server",
@@ -280,6 +295,80 @@ fn handle_tools_call(id: &serde_json::Value, params: &serde_json::Value) -> Stri
     )
 }
 
+/// URI of the one resource the mock server exposes.
+const README_URI: &str = "file:///mock/README.md";
+
+/// Handle the "resources/list" method.
+fn handle_resources_list(id: &serde_json::Value) -> String {
+    success_response(
+        id,
+        serde_json::json!({
+            "resources": [
+                {
+                    "uri": README_URI,
+                    "name": "README",
+                    "description": "Mock project readme",
+                    "mimeType": "text/markdown"
+                }
+            ]
+        }),
+    )
+}
+
+/// Handle the "resources/read" method.
+fn handle_resources_read(id: &serde_json::Value, params: &serde_json::Value) -> String {
+    let uri = params
+        .get("uri")
+        .and_then(|u| u.as_str())
+        .unwrap_or_default();
+    if uri != README_URI {
+        return error_response(id, -32002, "Resource not found");
+    }
+
+    success_response(
+        id,
+        serde_json::json!({
+            "contents": [
+                {
+                    "uri": README_URI,
+                    "mimeType": "text/markdown",
+                    "text": "# Mock Project"
+                }
+            ]
+        }),
+    )
+}
+
+/// Generate progress notifications carrying partial results for a tools/call.
+///
+/// Only sent when the request includes a progress token.
+fn partial_result_notifications(params: &serde_json::Value, count: usize) -> Vec<String> {
+    let Some(token) = params.get("_meta").and_then(|m| m.get("progressToken")) else {
+        return Vec::new();
+    };
+
+    (1..=count)
+        .map(|i| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": {
+                    "progressToken": token,
+                    "progress": i,
+                    "total": count + 1,
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!("partial {}", i)
+                        }
+                    ]
+                }
+            })
+            .to_string()
+        })
+        .collect()
+}
+
 /// Handle the "ping" method.
 fn handle_ping(id: &serde_json::Value) -> String {
     success_response(id, serde_json::json!({}))
@@ -303,6 +392,8 @@ fn route_request(request: &JsonRpcRequest) -> Option<String> {
         "initialize" => handle_initialize(id),
         "tools/list" => handle_tools_list(id),
         "tools/call" => handle_tools_call(id, &request.params),
+        "resources/list" => handle_resources_list(id),
+        "resources/read" => handle_resources_read(id, &request.params),
         "ping" => handle_ping(id),
         _ => handle_unknown(id),
     };
@@ -360,6 +451,15 @@ fn main() {
 
         // Parse and route the request
         if let Some(request) = JsonRpcRequest::parse(&line) {
+            if request.method == "tools/call" && config.partial_results > 0 {
+                for notification in
+                    partial_result_notifications(&request.params, config.partial_results)
+                {
+                    writeln!(stdout, "{}", notification).ok();
+                    stdout.flush().ok();
+                }
+            }
+
             if let Some(response) = route_request(&request) {
                 writeln!(stdout, "{}", response).ok();
                 stdout.flush().ok();
@@ -413,6 +513,20 @@ mod tests {
         assert!(response.contains("echo"));
     }
 
+    #[test]
+    fn test_route_resources_read() {
+        let read = |uri: &str| {
+            route_request(&JsonRpcRequest {
+                id: Some(serde_json::json!(1)),
+                method: "resources/read".to_string(),
+                params: serde_json::json!({"uri": uri}),
+            })
+            .unwrap()
+        };
+        assert!(read(README_URI).contains("# Mock Project"));
+        assert!(read("file:///missing").contains("-32002"));
+    }
+
     #[test]
     fn test_route_unknown_method() {
         let request = JsonRpcRequest {
@@ -445,5 +559,18 @@ mod tests {
         assert!(!config.no_response);
         assert!(!config.exit_immediately);
         assert_eq!(config.exit_code, 0);
+        assert_eq!(config.partial_results, 0);
+    }
+
+    #[test]
+    fn test_partial_results_require_progress_token() {
+        let with_token = serde_json::json!({"name": "echo", "_meta": {"progressToken": 7}});
+        let notifications = partial_result_notifications(&with_token, 2);
+        assert_eq!(notifications.len(), 2);
+        assert!(notifications[0].contains("notifications/progress"));
+        assert!(notifications[1].contains("partial 2"));
+
+        let without_token = serde_json::json!({"name": "echo"});
+        assert!(partial_result_notifications(&without_token, 2).is_empty());
     }
 }
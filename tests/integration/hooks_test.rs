@@ -54,6 +54,7 @@ fn tool_context(event: HookEvent, tool_name: &str) -> HookContext {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     }
 }
 
@@ -65,6 +66,7 @@ fn simple_hook(command: &str) -> HookDefinition {
             hook_type: "command".to_string(),
             command: command.to_string(),
             timeout_ms: Some(5000),
+            blocking: false,
         }],
     }
 }
@@ -77,6 +79,7 @@ fn hook_with_matcher(matcher: &str, command: &str) -> HookDefinition {
             hook_type: "command".to_string(),
             command: command.to_string(),
             timeout_ms: Some(5000),
+            blocking: false,
         }],
     }
 }
@@ -273,6 +276,7 @@ fn post_tool_context(tool_name: &str, response: serde_json::Value) -> HookContex
         tool_response: Some(response),
         prompt: None,
         stop_reason: None,
+        cwd: None,
     }
 }
 
@@ -328,6 +332,7 @@ async fn test_post_tool_use_failure_event() {
         tool_response: Some(json!({"error": "command failed", "exit_code": 1})),
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor
@@ -483,8 +488,6 @@ async fn test_hook_matcher_glob_pattern() {
 // =============================================================================
 
 /// Test that hooks with configured timeouts complete successfully.
-/// Note: Full timeout enforcement requires implementation in HookExecutor.
-/// This test verifies basic timeout configuration and successful completion.
 #[tokio::test]
 async fn test_hook_timeout() {
     let mut executor = HookExecutor::new();
@@ -498,6 +501,7 @@ async fn test_hook_timeout() {
                 hook_type: "command".to_string(),
                 command: "sleep 0.1 && exit 0".to_string(),
                 timeout_ms: Some(5000), // 5 second timeout
+                blocking: false,
             }],
         }],
     );
@@ -514,6 +518,106 @@ async fn test_hook_timeout() {
     );
 }
 
+/// Creates a hook that sleeps past a short timeout.
+fn sleeping_hook(blocking: bool) -> HookCommand {
+    HookCommand {
+        hook_type: "command".to_string(),
+        command: "sleep 10".to_string(),
+        timeout_ms: Some(200),
+        blocking,
+    }
+}
+
+/// Test that a hook sleeping past its timeout is killed and the next hook still fires.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hook_timeout_is_non_blocking_failure() {
+    let mut executor = HookExecutor::new();
+
+    // The second hook blocks, so a Block decision proves it ran
+    executor.register(
+        HookEvent::PreToolUse,
+        vec![HookDefinition {
+            matcher: None,
+            hooks: vec![
+                sleeping_hook(false),
+                HookCommand {
+                    hook_type: "command".to_string(),
+                    command: echo_and_exit("second hook ran", 2),
+                    timeout_ms: Some(5000),
+                    blocking: false,
+                },
+            ],
+        }],
+    );
+
+    let start = std::time::Instant::now();
+    let context = tool_context(HookEvent::PreToolUse, "Bash");
+    let result = executor
+        .execute(HookEvent::PreToolUse, &context)
+        .await
+        .expect("A timed-out hook should not fail the event");
+
+    assert!(
+        start.elapsed() < std::time::Duration::from_secs(5),
+        "Timed-out hook should be killed, took {:?}",
+        start.elapsed()
+    );
+    match result.decision {
+        HookDecision::Block { reason } => assert!(reason.contains("second hook ran")),
+        other => panic!("Expected the second hook to block, got {:?}", other),
+    }
+}
+
+/// Test that a hook marked blocking blocks the event when it times out.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_blocking_hook_timeout_blocks() {
+    let mut executor = HookExecutor::new();
+    executor.register(
+        HookEvent::PreToolUse,
+        vec![HookDefinition {
+            matcher: None,
+            hooks: vec![sleeping_hook(true)],
+        }],
+    );
+
+    let context = tool_context(HookEvent::PreToolUse, "Bash");
+    let result = executor
+        .execute(HookEvent::PreToolUse, &context)
+        .await
+        .unwrap();
+
+    match result.decision {
+        HookDecision::Block { reason } => assert!(reason.contains("timed out after 200ms")),
+        other => panic!("Expected a block, got {:?}", other),
+    }
+}
+
+/// Test that a hook exiting non-zero doesn't stop later hooks from firing.
+#[tokio::test]
+async fn test_failing_hook_does_not_stop_others() {
+    let mut executor = HookExecutor::new();
+    executor.register(
+        HookEvent::PreToolUse,
+        vec![
+            simple_hook(&exit_with_code(1)),
+            simple_hook(&echo_and_exit("later hook ran", 2)),
+        ],
+    );
+
+    let context = tool_context(HookEvent::PreToolUse, "Bash");
+    let result = executor
+        .execute(HookEvent::PreToolUse, &context)
+        .await
+        .unwrap();
+
+    match result.decision {
+        HookDecision::Block { reason } => assert!(reason.contains("later hook ran")),
+        other => panic!("Expected the later hook to block, got {:?}", other),
+    }
+}
+
 /// Test that hooks don't hang on slow commands (regression test).
 #[tokio::test]
 async fn test_hook_no_hang_on_slow_command() {
@@ -554,6 +658,7 @@ async fn test_hook_completes_before_timeout() {
                 hook_type: "command".to_string(),
                 command: "echo 'fast' && exit 0".to_string(),
                 timeout_ms: Some(10000),
+                blocking: false,
             }],
         }],
     );
@@ -639,7 +744,7 @@ async fn test_multiple_hook_definitions_same_event() {
 // 4.2.4 App-level hook integration tests
 // =============================================================================
 
-use patina::hooks::HookManager;
+use patina::hooks::{HookManager, PromptHookResult};
 use tempfile::TempDir;
 
 /// Test that HookManager can be created with configuration.
@@ -676,6 +781,23 @@ timeout_ms = 5000
     );
 }
 
+/// Test that hook commands run under the org policy.
+#[tokio::test]
+async fn test_hook_manager_org_policy_blocks_hook_command() {
+    use patina::enterprise::policy::OrgPolicy;
+
+    let mut manager = HookManager::new("test-org-policy".to_string());
+    manager.register_tool_hook(HookEvent::PreToolUse, None, "echo deploy");
+    let result = manager.fire_pre_tool_use("bash", json!({})).await.unwrap();
+    assert_eq!(result.exit_code, 0);
+
+    let org = OrgPolicy::parse(r"blocked_patterns = ['^echo\s+deploy\b']").unwrap();
+    manager.apply_org_policy(&org);
+    let result = manager.fire_pre_tool_use("bash", json!({})).await.unwrap();
+    assert_eq!(result.exit_code, 2);
+    assert!(result.stdout.contains("blocked"), "{}", result.stdout);
+}
+
 // =============================================================================
 // Graceful Degradation Tests (4.2.1)
 // =============================================================================
@@ -775,6 +897,27 @@ async fn test_session_start_hook_blocks() {
     assert!(matches!(result.decision, HookDecision::Block { .. }));
 }
 
+/// Test that SessionStart hooks receive the session ID and working directory.
+///
+/// Note: This test uses bash-specific constructs ($(cat), grep -q).
+#[cfg(unix)]
+#[tokio::test]
+async fn test_session_start_receives_session_id_and_cwd() {
+    let mut manager = HookManager::new("test-session-cwd".to_string())
+        .with_working_dir(std::path::PathBuf::from("/tmp/project"));
+
+    // Exit 2 only if both fields are in the hook input
+    manager.register_hook(
+        HookEvent::SessionStart,
+        simple_hook(
+            r#"input=$(cat); echo "$input" | grep -q '"session_id":"test-session-cwd"' && echo "$input" | grep -q '"cwd":"/tmp/project"' && exit 2 || exit 1"#,
+        ),
+    );
+
+    let result = manager.fire_session_start().await.unwrap();
+    assert_eq!(result.exit_code, 2);
+}
+
 /// Test that SessionEnd hook fires on session shutdown.
 #[tokio::test]
 async fn test_session_end_hook_fires() {
@@ -832,7 +975,10 @@ async fn test_user_prompt_submit_hook_fires() {
         "Hook should fire successfully, but got error: {:?}",
         result.as_ref().err()
     );
-    assert!(matches!(result.unwrap().decision, HookDecision::Continue));
+    assert_eq!(
+        result.unwrap(),
+        PromptHookResult::Submit("Hello, Claude!".to_string())
+    );
 }
 
 /// Test that UserPromptSubmit hook can block message submission.
@@ -856,14 +1002,22 @@ async fn test_user_prompt_submit_hook_blocks() {
         .fire_user_prompt_submit("Normal prompt")
         .await
         .unwrap();
-    assert!(matches!(allowed.decision, HookDecision::Continue));
+    assert_eq!(
+        allowed,
+        PromptHookResult::Submit("Normal prompt".to_string())
+    );
 
     // This prompt should be blocked
     let blocked = manager
         .fire_user_prompt_submit("This contains blocked_keyword")
         .await
         .unwrap();
-    assert!(matches!(blocked.decision, HookDecision::Block { .. }));
+    assert_eq!(
+        blocked,
+        PromptHookResult::Block {
+            reason: "Message blocked".to_string()
+        }
+    );
 }
 
 /// Test that UserPromptSubmit hook receives the prompt in context.
@@ -885,9 +1039,53 @@ async fn test_user_prompt_submit_receives_prompt() {
         .fire_user_prompt_submit("Test message content")
         .await;
 
-    assert!(result.is_ok());
     // Exit 2 means prompt was found in context
-    assert_eq!(result.unwrap().exit_code, 2);
+    assert!(matches!(result, Ok(PromptHookResult::Block { .. })));
+}
+
+/// Test that a UserPromptSubmit hook can rewrite the prompt, and that later
+/// hooks see the rewritten text.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_user_prompt_submit_rewrites_prompt() {
+    let mut manager = HookManager::new("test-prompt-rewrite".to_string());
+
+    manager.register_hook(
+        HookEvent::UserPromptSubmit,
+        simple_hook(r#"echo '{"prompt": "rewritten prompt"}'"#),
+    );
+    manager.register_hook(
+        HookEvent::UserPromptSubmit,
+        simple_hook(
+            r#"input=$(cat); echo "$input" | grep -q '"prompt":"rewritten prompt"' && exit 0 || exit 2"#,
+        ),
+    );
+
+    let result = manager
+        .fire_user_prompt_submit("original prompt")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        PromptHookResult::Submit("rewritten prompt".to_string())
+    );
+}
+
+/// Test that plain text on stdout leaves the prompt unchanged.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_user_prompt_submit_ignores_plain_output() {
+    let mut manager = HookManager::new("test-prompt-plain".to_string());
+
+    manager.register_hook(
+        HookEvent::UserPromptSubmit,
+        simple_hook("echo 'just logging'"),
+    );
+
+    let result = manager.fire_user_prompt_submit("keep me").await.unwrap();
+
+    assert_eq!(result, PromptHookResult::Submit("keep me".to_string()));
 }
 
 /// Test Stop hook fires when stop is requested.
@@ -991,6 +1189,7 @@ async fn test_hook_blocks_rm_rf() {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor.execute(HookEvent::SessionStart, &context).await;
@@ -1084,6 +1283,7 @@ async fn test_hook_blocks_curl_pipe_bash() {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor.execute(HookEvent::SessionStart, &context).await;
@@ -1136,6 +1336,7 @@ async fn test_hook_blocks_del_recursive() {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor.execute(HookEvent::SessionStart, &context).await;
@@ -1174,6 +1375,7 @@ async fn test_hook_blocks_powershell_encoded() {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor.execute(HookEvent::SessionStart, &context).await;
@@ -1212,6 +1414,7 @@ async fn test_hook_blocks_invoke_expression() {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor.execute(HookEvent::SessionStart, &context).await;
@@ -1253,6 +1456,7 @@ async fn test_hook_allows_safe_commands() {
         tool_response: None,
         prompt: None,
         stop_reason: None,
+        cwd: None,
     };
 
     let result = executor.execute(HookEvent::SessionStart, &context).await;
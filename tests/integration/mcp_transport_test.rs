@@ -459,7 +459,9 @@ async fn test_mcp_tool_call_error() {
 // Server Lifecycle Tests (Task 3.3.1)
 // ============================================================================
 
-use patina::mcp::client::McpClient;
+use patina::mcp::client::{McpClient, McpConnectionEvent, McpPartialResult, ReconnectPolicy};
+use patina::mcp::McpManager;
+use patina::tools::{ToolCall, ToolExecutor, ToolResult};
 
 /// Tests that MCP server can be started and stopped cleanly.
 #[tokio::test]
@@ -509,6 +511,262 @@ async fn test_mcp_server_crash_recovery() {
     client2.stop().await.expect("Should stop");
 }
 
+/// Tests that a crashed server is reconnected and its tools re-listed.
+///
+/// The call in flight when the server dies fails at once with a clear
+/// error; the next call restarts the server and succeeds.
+#[tokio::test]
+#[serial]
+async fn test_mcp_reconnects_after_server_crash() {
+    // initialize, initialized, tools/list and one call succeed; the next
+    // message kills the server
+    let (tx, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let mut client = McpClient::new("flaky", mock_mcp_server_path(), vec!["--crash-after", "4"])
+        .with_connection_events(tx);
+    client.start().await.expect("Server should start");
+    let tools = client.list_tools().await.expect("Should list tools");
+
+    let first = client.execute_tool("echo", json!({"text": "one"})).await;
+    assert!(matches!(first, ToolResult::Success(ref text) if text == "one"));
+
+    let started = std::time::Instant::now();
+    let crashed = client.execute_tool("echo", json!({"text": "two"})).await;
+    assert!(
+        started.elapsed() < Duration::from_secs(5),
+        "Call should fail without waiting for the request timeout"
+    );
+    match crashed {
+        ToolResult::Error(message) => assert!(message.contains("closed"), "{}", message),
+        other => panic!("Expected an error, got {:?}", other),
+    }
+    assert!(!client.is_connected());
+    assert!(client.is_reconnecting());
+    assert_eq!(
+        events.try_recv().unwrap(),
+        McpConnectionEvent::Disconnected {
+            server: "flaky".to_string()
+        }
+    );
+
+    let retried = client.execute_tool("echo", json!({"text": "three"})).await;
+    assert!(matches!(retried, ToolResult::Success(ref text) if text == "three"));
+    assert!(client.is_connected());
+    assert_eq!(client.tools().len(), tools.len());
+    assert_eq!(
+        events.try_recv().unwrap(),
+        McpConnectionEvent::Reconnected {
+            server: "flaky".to_string(),
+            tools: tools.len()
+        }
+    );
+
+    client.stop().await.expect("Should stop");
+}
+
+/// Tests that a client with no reconnect attempts gives up on a crashed
+/// server and keeps failing fast.
+#[tokio::test]
+#[serial]
+async fn test_mcp_gives_up_without_reconnect_attempts() {
+    let (tx, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let mut client = McpClient::new(
+        "fragile",
+        mock_mcp_server_path(),
+        vec!["--crash-after", "2"],
+    )
+    .with_reconnect_policy(ReconnectPolicy {
+        max_attempts: 0,
+        ..ReconnectPolicy::default()
+    })
+    .with_connection_events(tx);
+    client.start().await.expect("Server should start");
+
+    assert!(matches!(
+        client.execute_tool("echo", json!({})).await,
+        ToolResult::Error(_)
+    ));
+    match client.execute_tool("echo", json!({})).await {
+        ToolResult::Error(message) => assert!(message.contains("unavailable"), "{}", message),
+        other => panic!("Expected an error, got {:?}", other),
+    }
+    assert!(!client.is_reconnecting());
+
+    assert!(matches!(
+        events.try_recv().unwrap(),
+        McpConnectionEvent::Disconnected { .. }
+    ));
+    let gave_up = events.try_recv().unwrap();
+    assert_eq!(
+        gave_up.notice(),
+        "MCP server 'fragile' is unavailable after 0 reconnect attempts"
+    );
+
+    client.stop().await.expect("Should stop");
+}
+
+/// Tests that two servers exposing the same tool name are both callable by
+/// their server-qualified names.
+#[tokio::test]
+#[serial]
+async fn test_mcp_shared_tool_name_across_servers() {
+    let mut manager = McpManager::new();
+    for server in ["alpha", "beta"] {
+        let mut client = McpClient::new(server, mock_mcp_server_path(), vec![]);
+        client.start().await.expect("Server should start");
+        manager
+            .add_server(client)
+            .await
+            .expect("Server should register");
+    }
+
+    let names: Vec<&str> = manager
+        .get_tools()
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect();
+    assert!(names.contains(&"mcp__alpha__echo"), "{:?}", names);
+    assert!(names.contains(&"mcp__beta__echo"), "{:?}", names);
+    assert!(!names.contains(&"echo"));
+
+    let mcp = std::sync::Arc::new(tokio::sync::Mutex::new(manager));
+    let executor = ToolExecutor::new(std::env::temp_dir()).with_mcp_manager(mcp.clone());
+    for name in ["mcp__alpha__echo", "mcp__beta__echo"] {
+        let result = executor
+            .execute(ToolCall {
+                name: name.to_string(),
+                input: json!({"text": name}),
+            })
+            .await
+            .expect("Dispatch should succeed");
+        assert!(
+            matches!(result, ToolResult::Success(ref text) if text == name),
+            "{} returned {:?}",
+            name,
+            result
+        );
+    }
+
+    // A server that does not exist is reported, not silently ignored
+    let missing = executor
+        .execute(ToolCall {
+            name: "mcp__gamma__echo".to_string(),
+            input: json!({}),
+        })
+        .await
+        .expect("Dispatch should succeed");
+    assert!(matches!(missing, ToolResult::Error(ref e) if e.contains("gamma")));
+
+    mcp.lock().await.shutdown().await;
+}
+
+/// Tests listing and reading server resources, directly and through the
+/// `read_mcp_resource` tool.
+#[tokio::test]
+#[serial]
+async fn test_mcp_resources_list_and_read() {
+    let (cmd, args) = mock_mcp_server_command();
+    let mut client = McpClient::new("docs", cmd, args);
+    client.start().await.expect("Server should start");
+
+    let resources = client
+        .list_resources()
+        .await
+        .expect("Should list resources");
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].uri, "file:///mock/README.md");
+    assert_eq!(resources[0].mime_type.as_deref(), Some("text/markdown"));
+
+    let contents = client
+        .read_resource("file:///mock/README.md")
+        .await
+        .expect("Should read resource");
+    assert_eq!(contents[0].text.as_deref(), Some("# Mock Project"));
+    assert!(client.read_resource("file:///missing").await.is_err());
+
+    let mut manager = McpManager::new();
+    manager
+        .add_server(client)
+        .await
+        .expect("Server should register");
+    let mcp = std::sync::Arc::new(tokio::sync::Mutex::new(manager));
+    let executor = ToolExecutor::new(std::env::temp_dir()).with_mcp_manager(mcp.clone());
+    let run = |input: serde_json::Value| {
+        executor.execute(ToolCall {
+            name: "read_mcp_resource".to_string(),
+            input,
+        })
+    };
+
+    let listing = run(json!({})).await.expect("Dispatch should succeed");
+    assert!(
+        matches!(listing, ToolResult::Success(ref text) if text.contains("docs file:///mock/README.md")),
+        "{:?}",
+        listing
+    );
+
+    let read = run(json!({"server": "docs", "uri": "file:///mock/README.md"}))
+        .await
+        .expect("Dispatch should succeed");
+    assert!(matches!(read, ToolResult::Success(ref text) if text == "# Mock Project"));
+
+    let missing = run(json!({"server": "docs", "uri": "file:///missing"}))
+        .await
+        .expect("Dispatch should succeed");
+    assert!(matches!(missing, ToolResult::Error(ref e) if e.contains("Resource not found")));
+
+    mcp.lock().await.shutdown().await;
+}
+
+/// Tests that `read_mcp_resource` is advertised only when a server exposes
+/// resources.
+#[tokio::test]
+#[serial]
+async fn test_read_mcp_resource_advertised_with_resources() {
+    use patina::app::state::AppState;
+    use patina::types::config::ParallelMode;
+
+    let advertised = |state: &AppState| {
+        state
+            .tools()
+            .iter()
+            .any(|tool| tool.name == "read_mcp_resource")
+    };
+
+    let mut state = AppState::new(std::env::temp_dir(), false, ParallelMode::Enabled);
+    state.set_mcp_manager(McpManager::new());
+    assert!(!advertised(&state));
+
+    let (cmd, args) = mock_mcp_server_command();
+    let mut client = McpClient::new("docs", cmd, args);
+    client.start().await.expect("Server should start");
+    let mut manager = McpManager::new();
+    manager
+        .add_server(client)
+        .await
+        .expect("Server should register");
+    assert!(manager.has_resources());
+
+    let mut state = AppState::new(std::env::temp_dir(), false, ParallelMode::Enabled);
+    state.set_mcp_manager(manager);
+    assert!(advertised(&state));
+    assert!(state
+        .tools()
+        .iter()
+        .any(|tool| tool.name.starts_with("mcp__docs__")));
+}
+
+/// Tests that server names containing the separator are rejected.
+#[tokio::test]
+#[serial]
+async fn test_mcp_rejects_ambiguous_server_name() {
+    let mut client = McpClient::new("my__server", mock_mcp_server_path(), vec![]);
+    client.start().await.expect("Server should start");
+
+    let mut manager = McpManager::new();
+    let err = manager.add_server(client).await.unwrap_err().to_string();
+    assert!(err.contains("'__'"), "{}", err);
+}
+
 // ============================================================================
 // Error Path Tests (Task 3.3.1)
 // ============================================================================
@@ -643,3 +901,141 @@ async fn test_mcp_server_restart() {
 
     client2.stop().await.expect("Should stop");
 }
+
+// ============================================================================
+// Streaming Tool Result Tests
+// ============================================================================
+
+/// Renders a timeline to plain text for assertions.
+fn render_text(timeline: &patina::types::Timeline) -> String {
+    patina::tui::render_timeline_with_throbber(timeline, '⠋')
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tests that partial results are displayed as they arrive and aggregated
+/// into the final tool result.
+#[tokio::test]
+#[serial]
+async fn test_mcp_tool_call_streams_partial_results() {
+    let mut client = McpClient::new(
+        "test-server",
+        mock_mcp_server_path(),
+        vec!["--partial-results", "2"],
+    );
+    client.start().await.expect("Server should start");
+
+    let mut timeline = patina::types::Timeline::new();
+    timeline.push_tool_execution("echo", "final", None, false);
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<McpPartialResult>(8);
+    let display = async {
+        let mut snapshots = Vec::new();
+        while let Some(partial) = rx.recv().await {
+            timeline.append_tool_progress("echo", &partial.text());
+            snapshots.push(render_text(&timeline));
+        }
+        snapshots
+    };
+    let (result, snapshots) = tokio::join!(
+        client.call_tool_streaming("echo", json!({"text": "final"}), tx),
+        display
+    );
+    let result = result.expect("Tool call should succeed");
+
+    // Each partial result is shown while the tool is still running
+    assert_eq!(snapshots.len(), 2);
+    assert!(snapshots[0].contains("partial 1"));
+    assert!(!snapshots[0].contains("partial 2"));
+    assert!(snapshots[1].contains("partial 1") && snapshots[1].contains("partial 2"));
+    assert!(snapshots[1].contains("Running..."));
+
+    // The final result aggregates the partial results with the final content
+    let texts: Vec<&str> = result["content"]
+        .as_array()
+        .expect("Should have content array")
+        .iter()
+        .filter_map(|block| block["text"].as_str())
+        .collect();
+    assert_eq!(texts, vec!["partial 1", "partial 2", "final"]);
+
+    timeline.update_tool_result("echo", Some(texts.join("\n")), false);
+    assert_eq!(timeline.len(), 1);
+    let rendered = render_text(&timeline);
+    assert!(!rendered.contains("Running..."));
+    assert!(rendered.contains("partial 2"));
+
+    client.stop().await.expect("Should stop");
+}
+
+/// Tests that a plain tool call ignores the streaming machinery.
+#[tokio::test]
+#[serial]
+async fn test_mcp_tool_call_without_partial_results() {
+    let (cmd, args) = mock_mcp_server_command();
+    let mut client = McpClient::new("test-server", cmd, args);
+    client.start().await.expect("Server should start");
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let result = client
+        .call_tool_streaming("echo", json!({"text": "only"}), tx)
+        .await
+        .expect("Tool call should succeed");
+
+    assert!(rx.recv().await.is_none(), "No partial results expected");
+    assert_eq!(result["content"][0]["text"], "only");
+
+    client.stop().await.expect("Should stop");
+}
+
+/// Tests that MCP calls made through the tool executor report partial
+/// results as progress and still return the aggregated result.
+#[tokio::test]
+#[serial]
+async fn test_mcp_tool_executor_forwards_progress() {
+    let mut client = McpClient::new(
+        "search",
+        mock_mcp_server_path(),
+        vec!["--partial-results", "2"],
+    );
+    client.start().await.expect("Server should start");
+    let mut manager = McpManager::new();
+    manager
+        .add_server(client)
+        .await
+        .expect("Server should register");
+
+    let mcp = std::sync::Arc::new(tokio::sync::Mutex::new(manager));
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let mut executor = ToolExecutor::new(std::env::temp_dir()).with_mcp_manager(mcp.clone());
+    executor.set_mcp_progress(tx);
+
+    let result = executor
+        .execute(ToolCall {
+            name: "mcp__search__echo".to_string(),
+            input: json!({"text": "final"}),
+        })
+        .await
+        .expect("Dispatch should succeed");
+    assert!(
+        matches!(result, ToolResult::Success(ref text) if text == "partial 1\npartial 2\nfinal"),
+        "{:?}",
+        result
+    );
+
+    let mut progress = Vec::new();
+    while let Ok(update) = rx.try_recv() {
+        assert_eq!(update.tool, "mcp__search__echo");
+        progress.push(update.text);
+    }
+    assert_eq!(progress, vec!["partial 1", "partial 2"]);
+
+    mcp.lock().await.shutdown().await;
+}
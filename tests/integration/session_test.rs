@@ -5,7 +5,7 @@
 //! - Resuming sessions from saved state
 //! - Session metadata handling
 
-use patina::session::{Session, SessionManager};
+use patina::session::{Session, SessionManager, SESSION_SCHEMA_VERSION};
 use patina::types::message::{Message, Role};
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -180,6 +180,33 @@ async fn test_session_resume_nonexistent() {
     assert!(result.is_err(), "Loading nonexistent session should fail");
 }
 
+/// Test that a session can be loaded by its name as well as its ID.
+#[tokio::test]
+async fn test_session_resume_by_name() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let manager = SessionManager::new(temp_dir.path().to_path_buf());
+
+    let mut named = Session::new(PathBuf::from("/test/project"));
+    named.add_message(test_message(Role::User, "Rename the parser module"));
+    named.set_name(Some("my-refactor".to_string()));
+    let named_id = manager.save(&named).await.expect("Failed to save session");
+    manager
+        .save(&Session::new(PathBuf::from("/test/other")))
+        .await
+        .expect("Failed to save session");
+
+    let resumed = manager
+        .load("my-refactor")
+        .await
+        .expect("Failed to resume session by name");
+    assert_eq!(resumed.id(), Some(named_id.as_str()));
+    assert_eq!(resumed.messages().len(), 1);
+
+    let err = manager.load("unknown-name").await.unwrap_err();
+    assert!(err.to_string().contains("No session with ID or name"));
+    assert!(manager.load("../my-refactor").await.is_err());
+}
+
 /// Test that session list returns all saved sessions.
 #[tokio::test]
 async fn test_session_list() {
@@ -335,6 +362,66 @@ async fn test_session_detects_tampering() {
     );
 }
 
+/// Test that a session saved before schema versions existed is migrated on load.
+#[tokio::test]
+async fn test_session_migrates_v1_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let manager = SessionManager::new(temp_dir.path().to_path_buf());
+
+    let mut session = Session::new(PathBuf::from("/test"));
+    session.add_message(test_message(Role::User, "Saved by an older patina"));
+    let session_id = manager
+        .save(&session)
+        .await
+        .expect("Failed to save session");
+
+    // Rewrite the file in the v1 shape, which has no schema_version
+    let session_file = temp_dir.path().join(format!("{}.json", session_id));
+    let mut file: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&session_file).unwrap()).unwrap();
+    assert_eq!(file["schema_version"], SESSION_SCHEMA_VERSION);
+    file.as_object_mut().unwrap().remove("schema_version");
+    std::fs::write(&session_file, file.to_string()).unwrap();
+
+    let loaded = manager
+        .load(&session_id)
+        .await
+        .expect("v1 session should load");
+    assert_eq!(loaded.messages()[0].content, "Saved by an older patina");
+
+    // Saving again writes the current version
+    manager.update(&session_id, &loaded).await.unwrap();
+    let file: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&session_file).unwrap()).unwrap();
+    assert_eq!(file["schema_version"], SESSION_SCHEMA_VERSION);
+}
+
+/// Test that a session from a newer schema version is rejected clearly.
+#[tokio::test]
+async fn test_session_rejects_newer_schema_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let manager = SessionManager::new(temp_dir.path().to_path_buf());
+
+    let session = Session::new(PathBuf::from("/test"));
+    let session_id = manager
+        .save(&session)
+        .await
+        .expect("Failed to save session");
+
+    let session_file = temp_dir.path().join(format!("{}.json", session_id));
+    let mut file: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&session_file).unwrap()).unwrap();
+    file["schema_version"] = serde_json::json!(SESSION_SCHEMA_VERSION + 1);
+    std::fs::write(&session_file, file.to_string()).unwrap();
+
+    let err = manager.load(&session_id).await.unwrap_err();
+    assert!(
+        err.to_string().contains("newer version"),
+        "Error should say the session is from a newer version: {}",
+        err
+    );
+}
+
 /// Test that session validates schema (rejects invalid JSON structure).
 #[tokio::test]
 async fn test_session_validates_schema() {
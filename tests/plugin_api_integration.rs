@@ -404,6 +404,151 @@ fn test_tool_routing_command_count() {
     assert_eq!(registry.command_count(), 3);
 }
 
+// ============================================================================
+// Plugin Tool Tests
+// ============================================================================
+
+/// Worked example: a `jira` plugin exposing a `jira_ticket` tool.
+///
+/// The tool's script reads the call input as JSON on stdin and prints the
+/// ticket, which becomes the tool result.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_plugin_tool_worked_example() {
+    use patina::tools::{ToolCall, ToolExecutor, ToolResult};
+
+    let ctx = TestContext::new();
+    create_jira_plugin(&ctx, "jira");
+
+    let mut registry = PluginRegistry::new();
+    registry.load_all(&[ctx.path().to_path_buf()]).unwrap();
+
+    let definitions = registry.tools().definitions();
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(definitions[0].name, "jira_ticket");
+    assert_eq!(definitions[0].input_schema["required"][0], "key");
+
+    let mut executor = ToolExecutor::new(ctx.path());
+    executor.set_plugin_tools(registry.tools().clone());
+    let result = executor
+        .execute(ToolCall {
+            name: "jira_ticket".to_string(),
+            input: serde_json::json!({ "key": "PAT-42" }),
+        })
+        .await
+        .unwrap();
+    assert!(
+        matches!(result, ToolResult::Success(ref out) if out.trim() == "PAT-42: Fix the login page"),
+        "got: {result:?}"
+    );
+
+    // Unloading the plugin withdraws the tool from the executor
+    registry.unload_plugin("jira");
+    let result = executor
+        .execute(ToolCall {
+            name: "jira_ticket".to_string(),
+            input: serde_json::json!({ "key": "PAT-42" }),
+        })
+        .await
+        .unwrap();
+    assert!(
+        matches!(result, ToolResult::Error(ref e) if e.contains("Unknown tool")),
+        "got: {result:?}"
+    );
+}
+
+/// Org policy restrictions apply to plugin tool commands as well as bash.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_plugin_tool_blocked_by_org_policy() {
+    use patina::enterprise::policy::OrgPolicy;
+    use patina::hooks::HookManager;
+    use patina::tools::{HookedToolExecutor, ToolCall, ToolResult};
+
+    let ctx = TestContext::new();
+    create_jira_plugin(&ctx, "jira");
+    let mut registry = PluginRegistry::new();
+    registry.load_all(&[ctx.path().to_path_buf()]).unwrap();
+
+    let mut executor = HookedToolExecutor::new(
+        ctx.path().to_path_buf(),
+        HookManager::new("test".to_string()),
+    );
+    executor.set_plugin_tools(registry.tools().clone());
+    executor.apply_org_policy(&OrgPolicy::parse(r"blocked_patterns = ['ticket\.sh']").unwrap());
+
+    let result = executor
+        .execute(ToolCall {
+            name: "jira_ticket".to_string(),
+            input: serde_json::json!({ "key": "PAT-42" }),
+        })
+        .await
+        .unwrap();
+    assert!(
+        matches!(result, ToolResult::Error(ref e) if e.contains("blocked by security policy")),
+        "got: {result:?}"
+    );
+
+    // Allowlist mode blocks plugin commands it does not list
+    let mut executor = HookedToolExecutor::new(
+        ctx.path().to_path_buf(),
+        HookManager::new("test".to_string()),
+    );
+    executor.set_plugin_tools(registry.tools().clone());
+    executor.apply_org_policy(
+        &OrgPolicy::parse("allowlist_mode = true\nallowed_commands = ['^git\\s']").unwrap(),
+    );
+    let result = executor
+        .execute(ToolCall {
+            name: "jira_ticket".to_string(),
+            input: serde_json::json!({ "key": "PAT-42" }),
+        })
+        .await
+        .unwrap();
+    assert!(
+        matches!(result, ToolResult::Error(ref e) if e.contains("not in organization allowlist")),
+        "got: {result:?}"
+    );
+}
+
+#[test]
+fn test_plugin_tool_builtin_name_rejected() {
+    let ctx = TestContext::new();
+    let plugin_dir = create_test_plugin(&ctx, "shadow", "1.0.0");
+    write_manifest(
+        &plugin_dir,
+        serde_json::json!({
+            "name": "shadow",
+            "version": "1.0.0",
+            "tools": [{
+                "name": "bash",
+                "description": "Not the real bash",
+                "command": "cat"
+            }]
+        }),
+    );
+
+    let mut registry = PluginRegistry::new();
+    registry.load_all(&[ctx.path().to_path_buf()]).unwrap();
+
+    assert!(!registry.has_plugin("shadow"));
+    assert!(registry.tools().is_empty());
+}
+
+#[test]
+fn test_plugin_tool_collision_between_plugins_rejected() {
+    let ctx = TestContext::new();
+    create_jira_plugin(&ctx, "jira");
+    create_jira_plugin(&ctx, "jira-copy");
+
+    let mut registry = PluginRegistry::new();
+    registry.load_all(&[ctx.path().to_path_buf()]).unwrap();
+
+    // Whichever loads first keeps the name; the other is skipped
+    assert_eq!(registry.plugin_count(), 1);
+    assert_eq!(registry.tools().len(), 1);
+}
+
 // ============================================================================
 // Helper functions
 // ============================================================================
@@ -427,3 +572,46 @@ fn create_test_plugin(ctx: &TestContext, name: &str, version: &str) -> std::path
 
     plugin_dir
 }
+
+fn write_manifest(plugin_dir: &std::path::Path, manifest: serde_json::Value) {
+    fs::write(
+        plugin_dir.join(".claude-plugin").join("plugin.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .unwrap();
+}
+
+/// Creates the example `jira` plugin under `name`.
+fn create_jira_plugin(ctx: &TestContext, name: &str) -> std::path::PathBuf {
+    let plugin_dir = create_test_plugin(ctx, name, "1.0.0");
+    write_manifest(
+        &plugin_dir,
+        serde_json::json!({
+            "name": name,
+            "version": "1.0.0",
+            "tools": [{
+                "name": "jira_ticket",
+                "description": "Fetches a Jira ticket by key",
+                "input_schema": {
+                    "type": "object",
+                    "properties": {
+                        "key": { "type": "string", "description": "Ticket key, e.g. PAT-42" }
+                    },
+                    "required": ["key"]
+                },
+                "command": "sh bin/ticket.sh"
+            }]
+        }),
+    );
+
+    let bin_dir = plugin_dir.join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    fs::write(
+        bin_dir.join("ticket.sh"),
+        "key=$(sed -n 's/.*\"key\":\"\\([^\"]*\\)\".*/\\1/p')\n\
+         echo \"$key: Fix the login page\"\n",
+    )
+    .unwrap();
+
+    plugin_dir
+}
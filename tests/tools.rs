@@ -807,6 +807,78 @@ async fn test_file_read_nonexistent() {
     }
 }
 
+/// Test that read_file rejects binary files with a clear message.
+#[tokio::test]
+async fn test_file_read_binary_rejected() {
+    let ctx = TestContext::new();
+    let mut bytes = b"\x7fELF".to_vec();
+    bytes.extend_from_slice(&[0u8; 60]);
+    std::fs::write(ctx.path().join("app.bin"), &bytes).expect("failed to create test file");
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "read_file".to_string(),
+        input: json!({ "path": "app.bin" }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    match result {
+        ToolResult::Error(e) => {
+            assert!(
+                e.contains("binary file, cannot read as text"),
+                "error should explain binary content, got: {e}"
+            );
+            assert!(e.contains("64 B"), "error should report the size, got: {e}");
+        }
+        other => panic!("expected error, got {other:?}"),
+    }
+}
+
+/// Test that read_file returns a numbered line window when offset/limit are given.
+#[tokio::test]
+async fn test_file_read_offset_limit() {
+    let ctx = TestContext::new();
+    ctx.create_file("lines.txt", "line1\nline2\nline3\nline4\nline5\n");
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "read_file".to_string(),
+        input: json!({ "path": "lines.txt", "offset": 2, "limit": 2 }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    match result {
+        ToolResult::Success(content) => {
+            assert_eq!(content, "     2\tline2\n     3\tline3");
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    // Path checks still apply before any range handling.
+    let call = ToolCall {
+        name: "read_file".to_string(),
+        input: json!({ "path": "../outside.txt", "offset": 1, "limit": 1 }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    assert!(
+        matches!(result, ToolResult::Error(_)),
+        "path traversal should be blocked, got {result:?}"
+    );
+}
+
 // =============================================================================
 // File Write Tests (2.2.2)
 // =============================================================================
@@ -1179,6 +1251,206 @@ async fn test_file_write_creates_backup() {
     }
 }
 
+/// Test that backups go to the configured directory and old ones are pruned.
+#[tokio::test]
+async fn test_file_write_backup_dir_and_retention() {
+    let ctx = TestContext::new();
+    ctx.create_file("notes.txt", "current");
+    // Older backups of this file, plus an unrelated file that must survive
+    ctx.create_file("backups/notes.txt.100.bak", "oldest");
+    ctx.create_file("backups/notes.txt.200.bak", "older");
+    ctx.create_file("backups/other.txt.100.bak", "other file");
+
+    let policy = ToolExecutionPolicy {
+        backup_dir: Some("backups".into()),
+        max_backups_per_file: 2,
+        ..Default::default()
+    };
+    let executor = ToolExecutor::new(ctx.path()).with_policy(policy);
+
+    let call = ToolCall {
+        name: "write_file".to_string(),
+        input: json!({ "path": "notes.txt", "content": "updated" }),
+    };
+
+    match executor.execute(call).await.unwrap() {
+        ToolResult::Success(_) => {}
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    assert!(
+        !ctx.path().join(".rct_backups").exists(),
+        "default backup directory should not be used"
+    );
+
+    let mut backups: Vec<String> = std::fs::read_dir(ctx.path().join("backups"))
+        .expect("should read backup dir")
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    backups.sort_unstable();
+
+    assert_eq!(backups.len(), 3, "got: {backups:?}");
+    assert!(!backups.contains(&"notes.txt.100.bak".to_string()));
+    assert!(backups.contains(&"notes.txt.200.bak".to_string()));
+    assert!(backups.contains(&"other.txt.100.bak".to_string()));
+
+    let newest = backups
+        .iter()
+        .find(|name| name.starts_with("notes.txt.") && name.as_str() != "notes.txt.200.bak")
+        .expect("new backup should exist");
+    assert_eq!(
+        std::fs::read_to_string(ctx.path().join("backups").join(newest)).unwrap(),
+        "current"
+    );
+}
+
+/// Test that files with the same name in different directories keep separate
+/// backups and do not prune each other's.
+#[tokio::test]
+async fn test_backup_retention_is_per_path() {
+    let ctx = TestContext::new();
+    ctx.create_file("a/mod.rs", "a current");
+    ctx.create_file("b/mod.rs", "b current");
+    ctx.create_file("backups/a%2Fmod.rs.100.bak", "a oldest");
+    ctx.create_file("backups/b%2Fmod.rs.100.bak", "b oldest");
+
+    let policy = ToolExecutionPolicy {
+        backup_dir: Some("backups".into()),
+        max_backups_per_file: 1,
+        ..Default::default()
+    };
+    let executor = ToolExecutor::new(ctx.path()).with_policy(policy);
+
+    let call = ToolCall {
+        name: "write_file".to_string(),
+        input: json!({ "path": "a/mod.rs", "content": "a updated" }),
+    };
+    match executor.execute(call).await.unwrap() {
+        ToolResult::Success(_) => {}
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    let backups: Vec<String> = std::fs::read_dir(ctx.path().join("backups"))
+        .expect("should read backup dir")
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+
+    assert_eq!(backups.len(), 2, "got: {backups:?}");
+    assert!(backups.contains(&"b%2Fmod.rs.100.bak".to_string()));
+    let a_backup = backups
+        .iter()
+        .find(|name| name.starts_with("a%2Fmod.rs."))
+        .expect("new backup of a/mod.rs should exist");
+    assert_eq!(
+        std::fs::read_to_string(ctx.path().join("backups").join(a_backup)).unwrap(),
+        "a current"
+    );
+}
+
+/// Test that write_file and edit skip backups when they are disabled.
+#[tokio::test]
+async fn test_backups_disabled() {
+    let ctx = TestContext::new();
+    ctx.create_file("tracked.txt", "original content");
+
+    let policy = ToolExecutionPolicy {
+        backups_enabled: false,
+        ..Default::default()
+    };
+    let executor = ToolExecutor::new(ctx.path()).with_policy(policy);
+
+    let write = ToolCall {
+        name: "write_file".to_string(),
+        input: json!({ "path": "tracked.txt", "content": "new content" }),
+    };
+    match executor.execute(write).await.unwrap() {
+        ToolResult::Success(_) => {}
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    let edit = ToolCall {
+        name: "edit".to_string(),
+        input: json!({ "path": "tracked.txt", "old_string": "new", "new_string": "edited" }),
+    };
+    match executor.execute(edit).await.unwrap() {
+        ToolResult::Success(_) => {}
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(ctx.path().join("tracked.txt")).unwrap(),
+        "edited content"
+    );
+    assert!(
+        !ctx.path().join(".rct_backups").exists(),
+        "no backup directory should be created"
+    );
+}
+
+/// Test that write_file replaces files atomically: a concurrent reader sees
+/// either the old or the new content, never a partial write.
+#[tokio::test]
+async fn test_file_write_is_atomic() {
+    let ctx = TestContext::new();
+    let old_content = "a".repeat(4 * 1024 * 1024);
+    let new_content = "b".repeat(8 * 1024 * 1024);
+    ctx.create_file("large.txt", &old_content);
+
+    let policy = ToolExecutionPolicy {
+        max_file_size: 16 * 1024 * 1024,
+        ..Default::default()
+    };
+    let executor = ToolExecutor::new(ctx.path()).with_policy(policy);
+
+    let target = ctx.path().join("large.txt");
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let reader = {
+        let target = target.clone();
+        let done = std::sync::Arc::clone(&done);
+        let (old_len, new_len) = (old_content.len(), new_content.len());
+        std::thread::spawn(move || {
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                let seen = std::fs::read(&target).expect("target should always exist");
+                let is_old = seen.len() == old_len && seen.iter().all(|&b| b == b'a');
+                let is_new = seen.len() == new_len && seen.iter().all(|&b| b == b'b');
+                assert!(
+                    is_old || is_new,
+                    "reader saw a partial write ({} bytes)",
+                    seen.len()
+                );
+            }
+        })
+    };
+
+    let call = ToolCall {
+        name: "write_file".to_string(),
+        input: json!({ "path": "large.txt", "content": new_content }),
+    };
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    reader.join().expect("reader should only see whole files");
+
+    match result {
+        ToolResult::Success(_) => {
+            assert_eq!(std::fs::read_to_string(&target).unwrap(), new_content);
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    // No temp files are left behind
+    let leftovers: Vec<_> = std::fs::read_dir(ctx.path())
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().contains(".tmp."))
+        .collect();
+    assert!(leftovers.is_empty(), "temp files left: {leftovers:?}");
+}
+
 // =============================================================================
 // Edit Tool Tests (2.2.3)
 // =============================================================================
@@ -1308,6 +1580,150 @@ async fn test_edit_unique_match_required() {
     }
 }
 
+/// Test that replace_all replaces every occurrence and reports each site.
+#[tokio::test]
+async fn test_edit_replace_all() {
+    let ctx = TestContext::new();
+    ctx.create_file("repeated.txt", "foo bar\nbaz\nfoo baz foo\n");
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "edit".to_string(),
+        input: json!({
+            "path": "repeated.txt",
+            "old_string": "foo",
+            "new_string": "qux",
+            "replace_all": true
+        }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    match result {
+        ToolResult::Success(output) => {
+            let content = std::fs::read_to_string(ctx.path().join("repeated.txt"))
+                .expect("file should exist");
+            assert_eq!(content, "qux bar\nbaz\nqux baz qux\n");
+            assert!(
+                output.contains("3 occurrences"),
+                "output should report count, got: {output}"
+            );
+            assert!(output.contains("@@ line 1 @@"), "got: {output}");
+            assert_eq!(output.matches("@@ line 3 @@").count(), 2, "got: {output}");
+        }
+        ToolResult::Error(e) => panic!("expected success, got error: {e}"),
+        ToolResult::Cancelled => panic!("expected success, got cancelled"),
+        ToolResult::NeedsPermission(_) => panic!("unexpected needs permission"),
+    }
+
+    // The original content was backed up first
+    let backups: Vec<_> = std::fs::read_dir(ctx.path().join(".rct_backups"))
+        .expect("backup dir should exist")
+        .collect();
+    assert_eq!(backups.len(), 1);
+}
+
+/// Test that a line range disambiguates a match repeated elsewhere in the file.
+#[tokio::test]
+async fn test_edit_line_range_match_inside_range() {
+    let ctx = TestContext::new();
+    ctx.create_file(
+        "boiler.rs",
+        "fn a() {\n    todo!()\n}\nfn b() {\n    todo!()\n}\n",
+    );
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "edit".to_string(),
+        input: json!({
+            "path": "boiler.rs",
+            "old_string": "todo!()",
+            "new_string": "42",
+            "start_line": 4,
+            "end_line": 6
+        }),
+    };
+
+    match executor
+        .execute(call)
+        .await
+        .expect("execution should not error")
+    {
+        ToolResult::Success(_) => {
+            let content =
+                std::fs::read_to_string(ctx.path().join("boiler.rs")).expect("file should exist");
+            assert_eq!(content, "fn a() {\n    todo!()\n}\nfn b() {\n    42\n}\n");
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+}
+
+/// Test that matches outside the line range are not counted or replaced.
+#[tokio::test]
+async fn test_edit_line_range_match_outside_range() {
+    let ctx = TestContext::new();
+    ctx.create_file("ranged.txt", "alpha\nbeta\ngamma\nalpha\n");
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "edit".to_string(),
+        input: json!({
+            "path": "ranged.txt",
+            "old_string": "alpha",
+            "new_string": "omega",
+            "start_line": 2,
+            "end_line": 3
+        }),
+    };
+
+    match executor
+        .execute(call)
+        .await
+        .expect("execution should not error")
+    {
+        ToolResult::Error(e) => {
+            assert!(e.contains("0 matches"), "got: {e}");
+            assert!(e.contains("lines 2-3"), "got: {e}");
+        }
+        other => panic!("expected error, got {other:?}"),
+    }
+    let content = std::fs::read_to_string(ctx.path().join("ranged.txt")).unwrap();
+    assert_eq!(content, "alpha\nbeta\ngamma\nalpha\n");
+}
+
+/// Test that an out-of-bounds line range is rejected.
+#[tokio::test]
+async fn test_edit_line_range_out_of_bounds() {
+    let ctx = TestContext::new();
+    ctx.create_file("short.txt", "one\ntwo\n");
+    let executor = ToolExecutor::new(ctx.path());
+
+    for (start, end) in [(0, 1), (2, 1), (1, 3)] {
+        let call = ToolCall {
+            name: "edit".to_string(),
+            input: json!({
+                "path": "short.txt",
+                "old_string": "one",
+                "new_string": "uno",
+                "start_line": start,
+                "end_line": end
+            }),
+        };
+
+        match executor
+            .execute(call)
+            .await
+            .expect("execution should not error")
+        {
+            ToolResult::Error(e) => assert!(e.contains("out of bounds"), "got: {e}"),
+            other => panic!("expected error for {start}-{end}, got {other:?}"),
+        }
+    }
+}
+
 /// Test that edit tool handles nonexistent files.
 #[tokio::test]
 async fn test_edit_nonexistent_file() {
@@ -1766,6 +2182,226 @@ async fn test_grep_file_filter() {
     }
 }
 
+/// Test that grep includes before/after context with separators between groups.
+#[tokio::test]
+async fn test_grep_context_lines() {
+    let ctx = TestContext::new();
+    ctx.create_file(
+        "notes.txt",
+        "one\ntwo\nMATCH a\nfour\nfive\nsix\nseven\nMATCH b\nnine",
+    );
+
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "grep".to_string(),
+        input: json!({ "pattern": "MATCH", "before": 1, "after": 1 }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    match result {
+        ToolResult::Success(output) => {
+            let expected = "notes.txt-2- two\n\
+                            notes.txt:3: MATCH a\n\
+                            notes.txt-4- four\n\
+                            --\n\
+                            notes.txt-7- seven\n\
+                            notes.txt:8: MATCH b\n\
+                            notes.txt-9- nine";
+            assert_eq!(output, expected);
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+}
+
+/// Test that grep lists each matching file once in files_with_matches mode.
+#[tokio::test]
+async fn test_grep_files_with_matches() {
+    let ctx = TestContext::new();
+    ctx.create_file("many.rs", "todo one\ntodo two\ntodo three");
+    ctx.create_file("once.rs", "nothing\ntodo here");
+    ctx.create_file("other.txt", "todo in text");
+    ctx.create_file("none.rs", "clean");
+
+    let executor = ToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "grep".to_string(),
+        input: json!({
+            "pattern": "todo",
+            "file_pattern": "*.rs",
+            "files_with_matches": true
+        }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    match result {
+        ToolResult::Success(output) => {
+            let mut files: Vec<&str> = output.lines().collect();
+            files.sort_unstable();
+            assert_eq!(files, vec!["many.rs", "once.rs"]);
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+}
+
+/// Test that grep honors .gitignore when asked and always skips .git.
+#[tokio::test]
+async fn test_grep_respects_gitignore() {
+    let ctx = TestContext::new();
+    ctx.create_file(".gitignore", "target/\n*.log\n");
+    ctx.create_file("src/main.rs", "needle in source");
+    ctx.create_file("target/debug/out.txt", "needle in build output");
+    ctx.create_file("debug.log", "needle in log");
+    ctx.create_file(".git/HEAD", "needle in git internals");
+
+    let executor = ToolExecutor::new(ctx.path());
+    let grep = |respect_gitignore: bool| ToolCall {
+        name: "grep".to_string(),
+        input: json!({
+            "pattern": "needle",
+            "files_with_matches": true,
+            "respect_gitignore": respect_gitignore
+        }),
+    };
+
+    let ToolResult::Success(all) = executor.execute(grep(false)).await.unwrap() else {
+        panic!("expected success");
+    };
+    let mut all: Vec<&str> = all.lines().collect();
+    all.sort_unstable();
+    assert_eq!(
+        all,
+        vec!["debug.log", "src/main.rs", "target/debug/out.txt"]
+    );
+
+    let ToolResult::Success(filtered) = executor.execute(grep(true)).await.unwrap() else {
+        panic!("expected success");
+    };
+    assert_eq!(filtered, "src/main.rs");
+}
+
+/// Runs git in `dir`, returning whether it succeeded.
+fn run_git(dir: &std::path::Path, args: &[&str]) -> bool {
+    std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Test that git_tracked_only limits grep and glob to files known to git.
+#[tokio::test]
+async fn test_grep_and_glob_git_tracked_only() {
+    let ctx = TestContext::new();
+    if !run_git(&ctx.path(), &["init", "--quiet"]) {
+        eprintln!("git not available, skipping");
+        return;
+    }
+    ctx.create_file("src/tracked.rs", "needle tracked");
+    ctx.create_file("src/untracked.rs", "needle untracked");
+    ctx.create_file("vendor/dep.rs", "needle vendored");
+    assert!(run_git(&ctx.path(), &["add", "src/tracked.rs"]));
+
+    let executor = ToolExecutor::new(ctx.path());
+
+    let grep = ToolCall {
+        name: "grep".to_string(),
+        input: json!({
+            "pattern": "needle",
+            "files_with_matches": true,
+            "git_tracked_only": true
+        }),
+    };
+    match executor.execute(grep).await.unwrap() {
+        ToolResult::Success(output) => assert_eq!(output, "src/tracked.rs"),
+        other => panic!("expected success, got {other:?}"),
+    }
+
+    let glob = ToolCall {
+        name: "glob".to_string(),
+        input: json!({ "pattern": "**/*.rs", "git_tracked_only": true }),
+    };
+    match executor.execute(glob).await.unwrap() {
+        ToolResult::Success(output) => assert_eq!(output, "src/tracked.rs"),
+        other => panic!("expected success, got {other:?}"),
+    }
+}
+
+/// Test that git_tracked_only falls back to a normal walk outside a git repository.
+#[tokio::test]
+async fn test_grep_git_tracked_only_falls_back_outside_repo() {
+    let ctx = TestContext::new();
+    ctx.create_file("a.txt", "needle");
+    ctx.create_file("b.txt", "needle");
+
+    let executor = ToolExecutor::new(ctx.path());
+    let call = ToolCall {
+        name: "grep".to_string(),
+        input: json!({
+            "pattern": "needle",
+            "files_with_matches": true,
+            "git_tracked_only": true
+        }),
+    };
+
+    match executor.execute(call).await.unwrap() {
+        ToolResult::Success(output) => {
+            let mut files: Vec<&str> = output.lines().collect();
+            files.sort_unstable();
+            assert_eq!(files, vec!["a.txt", "b.txt"]);
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+}
+
+/// Test that grep output is capped at max_output_size.
+#[tokio::test]
+async fn test_grep_output_truncated_when_exceeds_limit() {
+    let ctx = TestContext::new();
+    let content: String = (0..2000).map(|i| format!("needle {i}\n")).collect();
+    ctx.create_file("big.txt", &content);
+
+    let policy = ToolExecutionPolicy {
+        max_output_size: 1024,
+        ..Default::default()
+    };
+    let executor = ToolExecutor::new(ctx.path()).with_policy(policy);
+
+    let call = ToolCall {
+        name: "grep".to_string(),
+        input: json!({ "pattern": "needle", "after": 2 }),
+    };
+
+    let result = executor
+        .execute(call)
+        .await
+        .expect("execution should not error");
+
+    match result {
+        ToolResult::Success(output) => {
+            assert!(
+                output.contains("[Output truncated"),
+                "should note truncation, got: {output}"
+            );
+            assert!(
+                output.len() < 2048,
+                "output should be capped, got {} bytes",
+                output.len()
+            );
+        }
+        other => panic!("expected success, got {other:?}"),
+    }
+}
+
 // =============================================================================
 // Tool Hooks Integration Tests (4.2.4)
 // =============================================================================
@@ -3894,6 +4530,107 @@ async fn test_shell_state_cd_compound_command() {
     }
 }
 
+/// Test that `cd -` returns to the previous directory and prints it.
+#[tokio::test]
+async fn test_shell_state_cd_dash_prints_previous_directory() {
+    use patina::tools::StatefulToolExecutor;
+
+    let ctx = TestContext::new();
+    ctx.create_file("subdir/marker.txt", "subdir marker");
+    let origin = ctx.path().canonicalize().unwrap();
+
+    let executor = StatefulToolExecutor::new(ctx.path());
+
+    let call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "cd -" }),
+    };
+    let result = executor.execute(call).await.expect("cd - should run");
+    assert!(
+        matches!(result, ToolResult::Error(_)),
+        "cd - without a previous directory should fail, got: {result:?}"
+    );
+
+    let call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "cd subdir" }),
+    };
+    executor.execute(call).await.expect("cd should succeed");
+
+    let call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "cd -" }),
+    };
+    match executor.execute(call).await.expect("cd - should succeed") {
+        ToolResult::Success(output) => assert_eq!(output, origin.display().to_string()),
+        other => panic!("expected success, got {other:?}"),
+    }
+    assert_eq!(executor.shell_state().cwd(), origin);
+}
+
+/// Test that results report the cwd a command ran in after a cd.
+#[tokio::test]
+async fn test_shell_state_result_reports_cwd() {
+    use patina::tools::StatefulToolExecutor;
+
+    let ctx = TestContext::new();
+    ctx.create_file("nested/file.txt", "content");
+
+    let executor = StatefulToolExecutor::new(ctx.path());
+
+    // Commands in the starting directory have no cwd header
+    let call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "echo before" }),
+    };
+    match executor.execute(call).await.expect("echo should succeed") {
+        ToolResult::Success(output) => assert_eq!(output.trim(), "before"),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let cd_call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "cd nested" }),
+    };
+    executor.execute(cd_call).await.expect("cd should succeed");
+
+    let expected = format!(
+        "[cwd: {}]",
+        ctx.path().join("nested").canonicalize().unwrap().display()
+    );
+
+    let call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "echo after" }),
+    };
+    match executor.execute(call).await.expect("echo should succeed") {
+        ToolResult::Success(output) => {
+            assert!(
+                output.starts_with(&expected),
+                "expected {expected} header, got: {output}"
+            );
+            assert!(output.contains("after"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Failing commands report the cwd too
+    let call = ToolCall {
+        name: "bash".to_string(),
+        input: json!({ "command": "exit 3" }),
+    };
+    match executor.execute(call).await.expect("exit should run") {
+        ToolResult::Error(output) => {
+            assert!(
+                output.starts_with(&expected),
+                "expected {expected} header, got: {output}"
+            );
+            assert!(output.contains("Exit code 3"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
 /// Test that HookedToolExecutor exposes shell state via shell_state().
 #[tokio::test]
 async fn test_hooked_executor_exposes_shell_state() {
@@ -1,6 +1,9 @@
 //! Tests for MCP Manager
 
-use patina::mcp::{McpManager, McpServerConfig, McpTool, McpTransport};
+use patina::mcp::{
+    parse_tool_name, qualified_tool_name, McpManager, McpServerConfig, McpTool, McpTransport,
+    MAX_TOOL_NAME_LEN,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -36,6 +39,7 @@ async fn test_mcp_manager_initialize_disabled_server() {
                 env: HashMap::new(),
             },
             enabled: false,
+            max_reconnect_attempts: 5,
         },
     );
     let result = manager.initialize(configs).await;
@@ -55,6 +59,7 @@ async fn test_mcp_manager_initialize_stdio() {
                 env: HashMap::new(),
             },
             enabled: true,
+            max_reconnect_attempts: 5,
         },
     );
     let result = manager.initialize(configs).await;
@@ -73,6 +78,7 @@ async fn test_mcp_manager_initialize_sse() {
                 headers: HashMap::new(),
             },
             enabled: true,
+            max_reconnect_attempts: 5,
         },
     );
     let result = manager.initialize(configs).await;
@@ -91,6 +97,7 @@ async fn test_mcp_manager_initialize_http() {
                 headers: HashMap::new(),
             },
             enabled: true,
+            max_reconnect_attempts: 5,
         },
     );
     let result = manager.initialize(configs).await;
@@ -98,13 +105,65 @@ async fn test_mcp_manager_initialize_http() {
 }
 
 #[tokio::test]
-async fn test_mcp_manager_call_tool() {
-    let manager = McpManager::new();
+async fn test_mcp_manager_call_tool_requires_qualified_name() {
+    let mut manager = McpManager::new();
     let result = manager
         .call_tool("test-tool", serde_json::json!({"input": "value"}))
         .await;
-    assert!(result.is_ok());
-    assert_eq!(result.unwrap(), serde_json::json!({}));
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("mcp__<server>__<tool>"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_mcp_manager_call_tool_unknown_server() {
+    let mut manager = McpManager::new();
+    let result = manager
+        .call_tool("mcp__missing__search", serde_json::json!({}))
+        .await;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("Unknown MCP server 'missing'"), "{}", err);
+}
+
+#[test]
+fn test_mcp_tool_name_round_trip() {
+    let name = qualified_tool_name("docs", "search").unwrap();
+    assert_eq!(name, "mcp__docs__search");
+    assert_eq!(parse_tool_name(&name), Some(("docs", "search")));
+
+    // Tool names may contain the separator; server names may not
+    assert_eq!(
+        parse_tool_name("mcp__db__run__query"),
+        Some(("db", "run__query"))
+    );
+
+    assert_eq!(parse_tool_name("search"), None);
+    assert_eq!(parse_tool_name("mcp__docs"), None);
+    assert_eq!(parse_tool_name("mcp____search"), None);
+    assert_eq!(parse_tool_name("mcp__docs__"), None);
+}
+
+#[test]
+fn test_mcp_tool_name_rejects_ambiguous_underscores() {
+    // Both would qualify to mcp__a___b
+    assert_eq!(qualified_tool_name("a_", "b"), None);
+    assert_eq!(qualified_tool_name("a", "_b"), None);
+    assert_eq!(qualified_tool_name("_a", "b"), None);
+    assert_eq!(parse_tool_name("mcp__a___b"), None);
+    assert_eq!(parse_tool_name("mcp___a__b"), None);
+    assert!(qualified_tool_name("a_b", "c_").is_some());
+}
+
+#[test]
+fn test_mcp_tool_name_length_limit() {
+    let server = "docs";
+    let fits = "t".repeat(MAX_TOOL_NAME_LEN - "mcp__docs__".len());
+    let name = qualified_tool_name(server, &fits).unwrap();
+    assert_eq!(name.len(), MAX_TOOL_NAME_LEN);
+    assert_eq!(parse_tool_name(&name), Some((server, fits.as_str())));
+
+    let too_long = format!("{fits}t");
+    assert_eq!(qualified_tool_name(server, &too_long), None);
+    assert_eq!(parse_tool_name(&format!("mcp__docs__{too_long}")), None);
 }
 
 #[test]
@@ -676,6 +676,95 @@ fn test_plugin_continues_on_unreadable_command_file() {
     );
 }
 
+// =============================================================================
+// Test Group: Plugin Hot-Reload
+// =============================================================================
+
+use patina::plugins::PluginReload;
+
+/// Tests that a new plugin directory is loaded and then reloaded in place.
+#[test]
+fn test_reload_plugin_dir_loads_then_reloads() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let plugin_dir = create_plugin(&temp_dir, "hot", r#"{"name": "hot", "version": "1.0.0"}"#);
+    add_plugin_command(&plugin_dir, "greet", "Hello v1");
+
+    let mut registry = PluginRegistry::new();
+    let reload = registry
+        .reload_plugin_dir(&plugin_dir)
+        .expect("Should load new plugin");
+    assert_eq!(reload, PluginReload::Loaded("hot".to_string()));
+    assert_eq!(reload.notice().as_deref(), Some("Plugin 'hot' loaded"));
+
+    add_plugin_command(&plugin_dir, "greet", "Hello v2");
+    let reload = registry
+        .reload_plugin_dir(&plugin_dir)
+        .expect("Should reload plugin");
+    assert_eq!(reload, PluginReload::Reloaded("hot".to_string()));
+    assert_eq!(registry.plugin_count(), 1);
+    assert!(registry
+        .get_command("hot:greet")
+        .expect("Command should exist")
+        .content
+        .contains("Hello v2"));
+}
+
+/// Tests that a broken edit keeps the previously loaded version.
+#[test]
+fn test_reload_plugin_dir_keeps_previous_on_error() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let plugin_dir = create_plugin(
+        &temp_dir,
+        "fragile",
+        r#"{"name": "fragile", "version": "1.0.0"}"#,
+    );
+    add_plugin_command(&plugin_dir, "run", "Still here");
+
+    let mut registry = PluginRegistry::new();
+    registry
+        .load_all(&[temp_dir.path().to_path_buf()])
+        .expect("Should load plugins");
+
+    fs::write(
+        plugin_dir.join(".claude-plugin").join("plugin.json"),
+        "{ invalid json }",
+    )
+    .expect("Should write manifest");
+
+    assert!(registry.reload_plugin_dir(&plugin_dir).is_err());
+    assert!(registry.has_plugin("fragile"));
+    assert!(registry.get_command("fragile:run").is_some());
+}
+
+/// Tests that deleting a plugin's manifest unloads it.
+#[test]
+fn test_reload_plugin_dir_unloads_removed_plugin() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let plugin_dir = create_plugin(&temp_dir, "gone", r#"{"name": "gone", "version": "1.0.0"}"#);
+    add_plugin_command(&plugin_dir, "bye", "Bye");
+
+    let mut registry = PluginRegistry::new();
+    registry
+        .load_all(&[temp_dir.path().to_path_buf()])
+        .expect("Should load plugins");
+
+    fs::remove_dir_all(&plugin_dir).expect("Should remove plugin");
+
+    let reload = registry
+        .reload_plugin_dir(&plugin_dir)
+        .expect("Removal should succeed");
+    assert_eq!(reload, PluginReload::Removed("gone".to_string()));
+    assert!(!registry.has_plugin("gone"));
+    assert!(registry.get_command("gone:bye").is_none());
+
+    // A directory that never held a plugin is ignored
+    let reload = registry
+        .reload_plugin_dir(&temp_dir.path().join("unrelated"))
+        .expect("Should ignore");
+    assert_eq!(reload, PluginReload::Ignored);
+    assert_eq!(reload.notice(), None);
+}
+
 // =============================================================================
 // Test Group: TOML Plugin Discovery (9.2.1)
 // =============================================================================
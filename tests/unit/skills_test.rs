@@ -3,7 +3,7 @@
 //! These tests verify skill markdown parsing and frontmatter extraction.
 //! Following TDD RED phase - validating skill parsing behavior.
 
-use patina::skills::SkillEngine;
+use patina::skills::{ActivationContext, SkillEngine};
 use std::fs;
 use tempfile::TempDir;
 
@@ -1154,3 +1154,105 @@ fn test_skill_context_injection_empty_engine() {
         "Empty engine should produce no file context"
     );
 }
+
+// =============================================================================
+// Test Group: Auto-Activation
+// =============================================================================
+
+/// Tests `Skill::should_activate` against prompts and files in context.
+#[test]
+fn test_skill_should_activate_from_prompt_and_files() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let skill = r#"---
+name: db
+description: Database migrations
+triggers:
+  keywords:
+    - migration
+  file_patterns:
+    - "*.sql"
+---
+
+Write reversible migrations.
+"#;
+    create_skill_dir(&temp_dir, "db", skill);
+
+    let mut engine = SkillEngine::new();
+    engine.load_all(&[temp_dir.path().to_path_buf()]);
+    let skill = engine.get("db").expect("Skill should load");
+
+    let files = [std::path::PathBuf::from("db/001_init.sql")];
+    let by_file = ActivationContext {
+        prompt: "",
+        files: &files,
+    };
+    assert!(skill.should_activate(&by_file));
+    assert_eq!(
+        skill.activation_reason(&by_file).as_deref(),
+        Some("db/001_init.sql was used")
+    );
+
+    let by_prompt = ActivationContext {
+        prompt: "Add a Migration for users",
+        files: &[],
+    };
+    assert_eq!(
+        skill.activation_reason(&by_prompt).as_deref(),
+        Some("prompt mentions 'migration'")
+    );
+
+    let unrelated_files = [std::path::PathBuf::from("src/main.rs")];
+    let unrelated = ActivationContext {
+        prompt: "Fix the database connection",
+        files: &unrelated_files,
+    };
+    assert!(!skill.should_activate(&unrelated));
+}
+
+// =============================================================================
+// Test Group: System Prompt Injection
+// =============================================================================
+
+/// Tests that active skill instructions are deduplicated and capped.
+#[test]
+fn test_skill_system_prompt_dedupes_and_caps() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    create_skill_dir(
+        &temp_dir,
+        "style",
+        "---\nname: style\ndescription: Style\n---\n\nPrefer small functions.\n",
+    );
+    create_skill_dir(
+        &temp_dir,
+        "style-copy",
+        "---\nname: style-copy\ndescription: Style again\n---\n\nPrefer small functions.\n",
+    );
+    create_skill_dir(
+        &temp_dir,
+        "huge",
+        &format!(
+            "---\nname: huge\ndescription: Huge\n---\n\n{}\n",
+            "x".repeat(500)
+        ),
+    );
+
+    let mut engine = SkillEngine::new();
+    engine.load_all(&[temp_dir.path().to_path_buf()]);
+    assert_eq!(
+        engine.get("style").map(|s| s.instructions()),
+        Some("Prefer small functions.")
+    );
+
+    let names: Vec<String> = ["style", "style-copy", "huge", "missing"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let prompt = engine
+        .system_prompt_for(&names, 200)
+        .expect("Should build a prompt");
+    assert_eq!(prompt.matches("Prefer small functions.").count(), 1);
+    assert!(prompt.contains("## Skill: style\n"));
+    assert!(!prompt.contains("## Skill: huge"), "over the cap");
+
+    assert!(engine.system_prompt_for(&[], 200).is_none());
+}
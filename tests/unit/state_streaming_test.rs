@@ -6,6 +6,7 @@
 //! - Scroll auto-follow works during streaming
 
 use patina::app::state::AppState;
+use patina::app::REFUSAL_NOTICE;
 use patina::types::config::ParallelMode;
 use patina::types::{ConversationEntry, Role, StopReason, StreamEvent};
 use std::path::PathBuf;
@@ -448,3 +449,86 @@ fn test_tool_use_no_duplicate_with_both_events() {
         "MessageStop should not add duplicate for tool_use either"
     );
 }
+
+// ============================================================================
+// Content-Free Response Tests
+// ============================================================================
+
+/// Tests that a response with no content does not add an empty assistant message.
+#[test]
+fn test_empty_response_adds_no_assistant_message() {
+    let mut state = AppState::new(PathBuf::from("/tmp"), true, ParallelMode::Enabled);
+
+    state.set_streaming(true);
+    let timeline_len_before = state.timeline().len();
+
+    state
+        .append_chunk(StreamEvent::MessageComplete {
+            stop_reason: StopReason::EndTurn,
+        })
+        .unwrap();
+    state.append_chunk(StreamEvent::MessageStop).unwrap();
+
+    assert_eq!(state.api_messages_len(), 0, "No empty API message expected");
+    assert_eq!(
+        state.timeline().len(),
+        timeline_len_before - 1,
+        "Empty streaming entry should be discarded"
+    );
+    assert!(!state.timeline().is_streaming());
+}
+
+/// Tests that a content-free refusal is reported without an empty assistant message.
+#[test]
+fn test_content_free_refusal_is_reported() {
+    let mut state = AppState::new(PathBuf::from("/tmp"), true, ParallelMode::Enabled);
+
+    state.set_streaming(true);
+    state
+        .append_chunk(StreamEvent::MessageComplete {
+            stop_reason: StopReason::Refusal,
+        })
+        .unwrap();
+    state.append_chunk(StreamEvent::MessageStop).unwrap();
+
+    assert_eq!(state.api_messages_len(), 0, "No empty API message expected");
+    let assistant_entries: Vec<_> = state
+        .timeline()
+        .iter()
+        .filter(|e| e.is_assistant())
+        .collect();
+    assert_eq!(
+        assistant_entries.len(),
+        1,
+        "Only the refusal notice expected"
+    );
+    assert!(matches!(
+        assistant_entries[0],
+        ConversationEntry::AssistantMessage(text) if text == REFUSAL_NOTICE
+    ));
+    assert!(!state.is_loading());
+}
+
+/// Tests that API-reported usage replaces the token budget count.
+#[test]
+fn test_usage_event_updates_token_budget() {
+    let mut state = AppState::new(PathBuf::from("/tmp"), true, ParallelMode::Enabled);
+
+    state.set_streaming(true);
+    state
+        .append_chunk(StreamEvent::Usage {
+            input_tokens: 1_200,
+            output_tokens: 300,
+        })
+        .unwrap();
+    assert_eq!(state.token_budget().used(), 1_500);
+
+    // A later turn reports the whole context again, so it replaces the count
+    state
+        .append_chunk(StreamEvent::Usage {
+            input_tokens: 2_000,
+            output_tokens: 100,
+        })
+        .unwrap();
+    assert_eq!(state.token_budget().used(), 2_100);
+}
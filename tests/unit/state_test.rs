@@ -399,6 +399,114 @@ fn test_cursor_end() {
     assert_eq!(state.cursor_position(), 3);
 }
 
+/// Tests that a newline starts a new input line.
+#[test]
+fn test_insert_newline_moves_to_next_line() {
+    let mut state = new_state();
+    state.insert_str("ab");
+    state.insert_newline();
+    state.insert_char('c');
+
+    assert_eq!(state.input, "ab\nc");
+    assert_eq!(state.cursor_line_col(), (1, 1));
+    assert_eq!(state.input_line_count(), 2);
+}
+
+/// Tests cursor up and down keep the column across lines.
+#[test]
+fn test_cursor_up_down_keeps_column() {
+    let mut state = new_state();
+    state.insert_str("first\nsecond\nthird");
+    // Cursor after "thi"
+    state.cursor_left();
+    state.cursor_left();
+    assert_eq!(state.cursor_line_col(), (2, 3));
+
+    state.cursor_up();
+    assert_eq!(state.cursor_line_col(), (1, 3));
+    assert_eq!(state.cursor_position(), 9);
+
+    state.cursor_up();
+    assert_eq!(state.cursor_line_col(), (0, 3));
+    assert_eq!(state.cursor_position(), 3);
+
+    state.cursor_down();
+    state.cursor_down();
+    assert_eq!(state.cursor_line_col(), (2, 3));
+}
+
+/// Tests cursor up and down clamp the column to shorter lines.
+#[test]
+fn test_cursor_up_down_clamps_to_short_line() {
+    let mut state = new_state();
+    state.insert_str("a long line\nab\nanother long line");
+    assert_eq!(state.cursor_line_col(), (2, 17));
+
+    state.cursor_up();
+    assert_eq!(state.cursor_line_col(), (1, 2));
+
+    // The column is not remembered past the short line
+    state.cursor_up();
+    assert_eq!(state.cursor_line_col(), (0, 2));
+}
+
+/// Tests cursor up on the first line and down on the last do nothing.
+#[test]
+fn test_cursor_up_down_at_edges() {
+    let mut state = new_state();
+    state.insert_str("one\ntwo");
+
+    state.cursor_down();
+    assert_eq!(state.cursor_position(), 7);
+
+    state.cursor_home();
+    state.cursor_up();
+    assert_eq!(state.cursor_position(), 0);
+}
+
+/// Tests left and right cross line boundaries.
+#[test]
+fn test_cursor_left_right_across_lines() {
+    let mut state = new_state();
+    state.insert_str("ab\ncd");
+    state.cursor_home();
+    state.cursor_down();
+    assert_eq!(state.cursor_line_col(), (1, 0));
+
+    state.cursor_left();
+    assert_eq!(state.cursor_line_col(), (0, 2));
+
+    state.cursor_right();
+    assert_eq!(state.cursor_line_col(), (1, 0));
+}
+
+/// Tests the line and column count characters, not bytes.
+#[test]
+fn test_cursor_line_col_unicode() {
+    let mut state = new_state();
+    state.insert_str("你好\n🦀🦀🦀");
+    state.cursor_up();
+
+    assert_eq!(state.cursor_line_col(), (0, 2));
+    assert_eq!(state.cursor_position(), 2);
+}
+
+/// Tests the input box grows with the draft up to its limit.
+#[test]
+fn test_input_height_grows_with_lines() {
+    let mut state = new_state();
+    assert_eq!(state.input_height(), 3);
+
+    state.insert_str("one\ntwo\nthree");
+    assert_eq!(state.input_height(), 5);
+
+    state.insert_str(&"\nmore".repeat(20));
+    assert_eq!(
+        state.input_height(),
+        patina::app::MAX_INPUT_LINES as u16 + 2
+    );
+}
+
 // ============================================================================
 // Stream Chunk Tests
 // ============================================================================
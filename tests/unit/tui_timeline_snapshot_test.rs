@@ -195,8 +195,8 @@ fn test_timeline_produces_render_content() {
                 assert_eq!(output.as_deref(), Some("README.md\nCargo.toml"));
                 has_tool = true;
             }
-            ConversationEntry::Streaming { .. } => {}
-            ConversationEntry::ImageDisplay { .. } => {}
+            ConversationEntry::Streaming { .. } | ConversationEntry::Thinking(_) => {}
+            ConversationEntry::ToolProgress { .. } | ConversationEntry::ImageDisplay { .. } => {}
         }
     }
 
@@ -361,6 +361,164 @@ async fn test_fetch_blocks_excessive_redirects() {
     assert!(result.is_err(), "Should fail on redirect loop");
 }
 
+#[tokio::test]
+async fn test_fetch_redirect_limit_error_names_limit() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/loop"))
+        .respond_with(ResponseTemplate::new(302).insert_header("location", "/loop"))
+        .mount(&mock_server)
+        .await;
+
+    let tool = WebFetchTool::new(WebFetchConfig::for_testing().with_max_redirects(2));
+    let err = tool
+        .fetch(&format!("{}/loop", mock_server.uri()))
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.to_string().contains("Too many redirects (limit 2)"),
+        "Unexpected error: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_allows_allowlisted_private_host() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/wiki"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Internal wiki"))
+        .mount(&mock_server)
+        .await;
+
+    // No allow_localhost: only the allowlist lets the mock server through
+    let tool = WebFetchTool::new(
+        WebFetchConfig::default().with_allowed_hosts(vec!["127.0.0.1".to_string()]),
+    );
+    let result = tool
+        .fetch(&format!("{}/wiki", mock_server.uri()))
+        .await
+        .unwrap();
+
+    assert!(result.content.contains("Internal wiki"));
+}
+
+#[tokio::test]
+async fn test_fetch_rejects_redirect_to_private_ip() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/hop"))
+        .respond_with(
+            ResponseTemplate::new(302).insert_header("location", "http://10.255.255.1/admin"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let tool = WebFetchTool::new(
+        WebFetchConfig::default().with_allowed_hosts(vec!["127.0.0.1".to_string()]),
+    );
+    let err = tool
+        .fetch(&format!("{}/hop", mock_server.uri()))
+        .await
+        .unwrap_err();
+
+    let message = err.to_string();
+    assert!(
+        message.contains("Redirect rejected"),
+        "Unexpected error: {}",
+        message
+    );
+    assert!(
+        message.contains("Private IP"),
+        "Unexpected error: {}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_serves_repeat_requests_from_cache() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/docs"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Cached docs"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let tool = WebFetchTool::new(WebFetchConfig::for_testing());
+    let url = format!("{}/docs", mock_server.uri());
+
+    let first = tool.fetch(&url).await.unwrap();
+    let second = tool.fetch(&url).await.unwrap();
+
+    assert!(!first.cached);
+    assert!(second.cached);
+    assert_eq!(second.content, first.content);
+    mock_server.verify().await;
+}
+
+#[tokio::test]
+async fn test_fetch_refetches_after_ttl() {
+    use std::time::Duration;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/docs"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Fresh docs"))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let tool = WebFetchTool::new(WebFetchConfig::for_testing().with_cache(Duration::ZERO, 8));
+    let url = format!("{}/docs", mock_server.uri());
+
+    assert!(!tool.fetch(&url).await.unwrap().cached);
+    assert!(!tool.fetch(&url).await.unwrap().cached);
+    mock_server.verify().await;
+}
+
+#[tokio::test]
+async fn test_fetch_does_not_cache_errors() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not found"))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let tool = WebFetchTool::new(WebFetchConfig::for_testing());
+    let url = format!("{}/missing", mock_server.uri());
+
+    tool.fetch(&url).await.unwrap();
+    assert!(!tool.fetch(&url).await.unwrap().cached);
+    mock_server.verify().await;
+}
+
 // ============================================================================
 // WebFetchResult Tests
 // ============================================================================
@@ -371,6 +529,7 @@ fn test_web_fetch_result_structure() {
         content: "Hello World".to_string(),
         content_type: "text/html".to_string(),
         status: 200,
+        cached: false,
     };
 
     assert_eq!(result.content, "Hello World");
@@ -402,6 +561,10 @@ fn test_web_fetch_config_default() {
         !config.allow_localhost,
         "Localhost should be blocked by default"
     );
+    assert!(
+        config.allowed_hosts.is_empty(),
+        "No hosts should be allowlisted by default"
+    );
 }
 
 #[test]
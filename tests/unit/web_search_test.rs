@@ -3,7 +3,12 @@
 //! These tests verify web search functionality, result parsing,
 //! and error handling for the web search tool.
 
-use patina::tools::web_search::{SearchResult, WebSearchConfig, WebSearchTool};
+use patina::tools::web_search::{
+    SearchBackend, SearchProvider, SearchResult, WebSearchConfig, WebSearchTool,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 
 // ============================================================================
 // Search Result Structure Tests
@@ -266,6 +271,124 @@ async fn test_search_formats_results_as_markdown() {
     );
 }
 
+// ============================================================================
+// Search Backend Tests
+// ============================================================================
+
+/// Backend that records its queries and returns numbered results.
+struct RecordingBackend {
+    calls: Arc<Mutex<Vec<(String, usize)>>>,
+}
+
+impl SearchBackend for RecordingBackend {
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        max_results: usize,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Vec<SearchResult>>> + Send + 'a>> {
+        Box::pin(async move {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((query.to_string(), max_results));
+            Ok((0..max_results)
+                .map(|i| SearchResult {
+                    title: format!("Result {i}"),
+                    url: format!("https://example.com/{i}"),
+                    snippet: String::new(),
+                })
+                .collect())
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_search_uses_custom_backend() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let backend = RecordingBackend {
+        calls: Arc::clone(&calls),
+    };
+    let config = WebSearchConfig {
+        max_results: 3,
+        ..Default::default()
+    };
+    let tool = WebSearchTool::with_backend(config, backend);
+
+    let results = tool.search("  rust  ", 50).await.unwrap();
+    assert_eq!(results.len(), 3, "Tool config should cap max results");
+
+    // Validation happens before the backend is queried
+    assert!(tool.search("   ", 5).await.is_err());
+    assert_eq!(*calls.lock().unwrap(), vec![("rust".to_string(), 3)]);
+}
+
+#[tokio::test]
+async fn test_searxng_backend_parses_json() {
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    let body = serde_json::json!({
+        "query": "test query",
+        "results": [
+            {"title": "SearXNG Result 1", "url": "https://example.com/1", "content": "First snippet."},
+            {"title": "", "url": "https://example.com/untitled"},
+            {"title": "SearXNG Result 2", "url": "https://example.com/2", "content": "Second snippet."},
+            {"title": "SearXNG Result 3", "url": "https://example.com/3"}
+        ]
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .and(query_param("q", "test query"))
+        .and(query_param("format", "json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(&mock_server)
+        .await;
+
+    let config = WebSearchConfig {
+        provider: SearchProvider::Searxng,
+        base_url: format!("{}/", mock_server.uri()),
+        allow_localhost: true,
+        ..Default::default()
+    };
+    let tool = WebSearchTool::new(config);
+    let results = tool.search("test query", 2).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].title, "SearXNG Result 1");
+    assert_eq!(results[0].snippet, "First snippet.");
+    assert_eq!(results[1].url, "https://example.com/2");
+
+    let markdown = WebSearchTool::format_as_markdown(&results);
+    assert!(markdown.contains("SearXNG Result 2"));
+}
+
+#[tokio::test]
+async fn test_searxng_backend_api_error_returns_error() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&mock_server)
+        .await;
+
+    let config = WebSearchConfig {
+        provider: SearchProvider::Searxng,
+        base_url: mock_server.uri(),
+        allow_localhost: true,
+        ..Default::default()
+    };
+    let result = WebSearchTool::new(config).search("test", 5).await;
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("403"));
+}
+
 // ============================================================================
 // Markdown Formatting Tests
 // ============================================================================
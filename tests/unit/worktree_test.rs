@@ -345,6 +345,92 @@ fn test_remove_worktree_not_found() {
     );
 }
 
+#[test]
+fn test_create_worktree_branch_already_exists() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path().to_path_buf();
+    create_git_repo_with_commit(&repo_path);
+
+    // A branch left behind by an earlier worktree
+    std::process::Command::new("git")
+        .args(["branch", "wt/leftover"])
+        .current_dir(&repo_path)
+        .output()
+        .expect("Failed to create branch");
+
+    let manager = WorktreeManager::new(repo_path).unwrap();
+    assert!(manager.branch_exists("wt/leftover").unwrap());
+
+    let result = manager.create("leftover");
+    assert!(
+        matches!(result, Err(WorktreeError::BranchExists(ref b)) if b == "wt/leftover"),
+        "Should return BranchExists error: {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_ensure_clean_rejects_uncommitted_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path().to_path_buf();
+    create_git_repo_with_commit(&repo_path);
+
+    let manager = WorktreeManager::new(repo_path.clone()).unwrap();
+
+    // Untracked files do not follow a new worktree either way
+    std::fs::write(repo_path.join("notes.txt"), "scratch").unwrap();
+    assert!(manager.ensure_clean(&repo_path).is_ok());
+
+    std::fs::write(repo_path.join("README.md"), "# Changed").unwrap();
+    assert!(matches!(
+        manager.ensure_clean(&repo_path),
+        Err(WorktreeError::DirtyWorkingTree(_))
+    ));
+}
+
+#[test]
+fn test_commit_paths_commits_only_given_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path().to_path_buf();
+    create_git_repo_with_commit(&repo_path);
+
+    let manager = WorktreeManager::new(repo_path).unwrap();
+    let info = manager.create("auto").unwrap();
+    let new_file = info.path.join("new.txt");
+
+    // Nothing changed yet
+    assert_eq!(
+        manager
+            .commit_paths(&info.path, std::slice::from_ref(&new_file), "Nothing")
+            .unwrap(),
+        None
+    );
+
+    std::fs::write(&new_file, "content").unwrap();
+    std::fs::write(info.path.join("other.txt"), "not edited").unwrap();
+    std::fs::write(info.path.join(".gitignore"), ".env\n").unwrap();
+    std::fs::write(info.path.join(".env"), "SECRET=1").unwrap();
+    std::fs::create_dir(info.path.join(".rct_backups")).unwrap();
+    std::fs::write(info.path.join(".rct_backups/new.txt.bak"), "old").unwrap();
+    let files = [
+        new_file,
+        info.path.join(".env"),
+        info.path.join(".rct_backups/new.txt.bak"),
+    ];
+    let hash = manager
+        .commit_paths(&info.path, &files, "Add new file")
+        .unwrap()
+        .expect("a commit");
+    assert!(!hash.is_empty());
+
+    let committed = std::process::Command::new("git")
+        .args(["show", "--name-only", "--format=", "HEAD"])
+        .current_dir(&info.path)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&committed.stdout).trim(), "new.txt");
+}
+
 // ============================================================================
 // Worktree Status Tests
 // ============================================================================